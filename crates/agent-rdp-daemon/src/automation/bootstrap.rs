@@ -40,7 +40,27 @@ impl AutomationBootstrap {
     /// bootstrap. The actual IPC will be over DVC once the agent starts.
     pub async fn initialize(&self, state: &mut AutomationState) -> anyhow::Result<()> {
         info!("Initializing automation for session");
+        self.write_scripts(state).await?;
 
+        // Initialize DVC state and IPC
+        let dvc_state = new_shared_dvc_state();
+        let dvc_ipc = DvcIpc::new(dvc_state.clone());
+        state.dvc_state = Some(dvc_state);
+        state.dvc_ipc = Some(dvc_ipc);
+
+        state.enabled = true;
+        info!(
+            "Automation initialized with ID {} at {:?}",
+            state.automation_id, state.automation_dir
+        );
+
+        Ok(())
+    }
+
+    /// Write the agent script and its library files into the automation directory.
+    ///
+    /// Also run before restarting the agent, in case the files were removed.
+    pub async fn write_scripts(&self, state: &AutomationState) -> anyhow::Result<()> {
         // Create automation directory structure
         let automation_dir = &state.automation_dir;
         tokio::fs::create_dir_all(automation_dir).await?;
@@ -60,19 +80,6 @@ impl AutomationBootstrap {
         tokio::fs::write(lib_dir.join("actions.ps1"), LIB_ACTIONS).await?;
         tokio::fs::write(lib_dir.join("dvc.ps1"), LIB_DVC).await?;
         debug!("Wrote automation library files to {:?}", lib_dir);
-
-        // Initialize DVC state and IPC
-        let dvc_state = new_shared_dvc_state();
-        let dvc_ipc = DvcIpc::new(dvc_state.clone());
-        state.dvc_state = Some(dvc_state);
-        state.dvc_ipc = Some(dvc_ipc);
-
-        state.enabled = true;
-        info!(
-            "Automation initialized with ID {} at {:?}",
-            state.automation_id, automation_dir
-        );
-
         Ok(())
     }
 
@@ -81,6 +88,7 @@ impl AutomationBootstrap {
         DriveMapping {
             path: state.automation_dir.to_string_lossy().to_string(),
             name: state.drive_name.clone(),
+            read_only: false,
         }
     }
 
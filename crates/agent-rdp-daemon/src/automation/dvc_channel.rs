@@ -271,6 +271,11 @@ impl DvcProcessor for AutomationDvc {
         debug!("AutomationDvc channel {} closed", channel_id);
 
         let mut state = self.state.lock();
+        // A hung agent's channel can close after a restarted agent opened its own
+        if state.channel_id != Some(channel_id) {
+            debug!("Ignoring close of stale automation channel {}", channel_id);
+            return;
+        }
         state.channel_id = None;
         state.handshake = None;
 
@@ -366,4 +371,18 @@ mod tests {
             _ => panic!("Expected response"),
         }
     }
+
+    #[test]
+    fn test_close_of_stale_channel_keeps_current() {
+        let state = new_shared_dvc_state();
+        let mut dvc = AutomationDvc::new(state.clone());
+        dvc.start(1).unwrap();
+        // A restarted agent opens a new channel before the old one closes
+        dvc.start(2).unwrap();
+
+        dvc.close(1);
+        assert_eq!(state.lock().channel_id, Some(2));
+        dvc.close(2);
+        assert_eq!(state.lock().channel_id, None);
+    }
 }
@@ -14,7 +14,7 @@ use uuid::Uuid;
 
 use super::dvc_channel::{DvcError, DvcProtocolMessage, DvcSendCommand, SharedDvcState};
 
-/// Number of consecutive failures before suggesting reconnection.
+/// Number of consecutive failures after which the agent is considered hung.
 const CONSECUTIVE_FAILURE_THRESHOLD: u32 = 3;
 
 /// DVC-based IPC client for communicating with the PowerShell agent.
@@ -62,6 +62,19 @@ impl DvcIpc {
             + 1
     }
 
+    /// Whether enough requests in a row have failed that the agent looks hung.
+    pub fn is_unresponsive(&self) -> bool {
+        self.consecutive_failures() >= CONSECUTIVE_FAILURE_THRESHOLD
+    }
+
+    /// Forget the current agent before launching a new one: its handshake is
+    /// dropped, so `is_ready` waits for the new agent's, and the failure count
+    /// starts over.
+    pub fn forget_agent(&self) {
+        self.state.lock().handshake = None;
+        self.reset_failures();
+    }
+
     /// Check if the DVC channel is ready (handshake received).
     pub fn is_ready(&self) -> bool {
         let state = self.state.lock();
@@ -159,8 +172,7 @@ impl DvcIpc {
                         failures
                     );
                     anyhow::bail!(
-                        "DVC channel appears to be dead ({} consecutive failures). \
-                        Please reconnect with --enable-win-automation.",
+                        "DVC channel appears to be dead ({} consecutive failures)",
                         failures
                     );
                 }
@@ -179,8 +191,7 @@ impl DvcIpc {
                         failures
                     );
                     anyhow::bail!(
-                        "DVC channel appears to be dead ({} consecutive failures). \
-                        Please reconnect with --enable-win-automation.",
+                        "DVC channel appears to be dead ({} consecutive failures)",
                         failures
                     );
                 }
@@ -233,6 +244,7 @@ impl DvcIpc {
 mod tests {
     use super::*;
     use crate::automation::dvc_channel::new_shared_dvc_state;
+    use agent_rdp_protocol::SnapshotFormat;
 
     #[test]
     fn test_serialize_snapshot_request() {
@@ -245,6 +257,7 @@ mod tests {
             max_depth: 10,
             selector: None,
             focused: false,
+            format: SnapshotFormat::Json,
         };
 
         let (command, params) = ipc.serialize_request(&request).unwrap();
@@ -261,11 +274,13 @@ mod tests {
         let request = AutomateRequest::Click {
             selector: "@5".to_string(),
             double_click: false,
+            button: Some("middle".to_string()),
         };
 
         let (command, params) = ipc.serialize_request(&request).unwrap();
         assert_eq!(command, "click");
         assert_eq!(params["selector"], "@5");
+        assert_eq!(params["button"], "middle");
     }
 
     #[test]
@@ -274,4 +289,28 @@ mod tests {
         let ipc = DvcIpc::new(state);
         assert!(!ipc.is_ready());
     }
+
+    #[test]
+    fn test_forget_agent() {
+        let state = new_shared_dvc_state();
+        {
+            let mut state = state.lock();
+            state.channel_id = Some(1);
+            state.handshake = Some(crate::automation::DvcHandshake {
+                version: "1.0.0".to_string(),
+                agent_pid: 1234,
+                capabilities: Vec::new(),
+            });
+        }
+        let ipc = DvcIpc::new(state);
+        for _ in 0..CONSECUTIVE_FAILURE_THRESHOLD {
+            ipc.increment_failures();
+        }
+        assert!(ipc.is_ready());
+        assert!(ipc.is_unresponsive());
+
+        ipc.forget_agent();
+        assert!(!ipc.is_ready());
+        assert!(!ipc.is_unresponsive());
+    }
 }
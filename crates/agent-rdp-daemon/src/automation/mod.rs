@@ -7,6 +7,7 @@
 mod bootstrap;
 pub mod dvc_channel;
 mod dvc_ipc;
+pub mod watchdog;
 
 pub use bootstrap::AutomationBootstrap;
 pub use dvc_channel::{
@@ -39,6 +40,8 @@ pub struct AutomationState {
     pub agent_ready: bool,
     /// Agent process ID (if known).
     pub agent_pid: Option<u32>,
+    /// Times the watchdog relaunched the agent.
+    pub restarts: u32,
 }
 
 impl AutomationState {
@@ -56,6 +59,7 @@ impl AutomationState {
             dvc_state: None,
             agent_ready: false,
             agent_pid: None,
+            restarts: 0,
         }
     }
 
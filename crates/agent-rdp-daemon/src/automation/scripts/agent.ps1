@@ -20,6 +20,8 @@ Add-Type -AssemblyName System.Windows.Forms
 
 # Global state
 $script:RefMap = @{}  # ref number -> AutomationElement mapping
+$script:RefIds = @{}  # ref number -> stable handle of the same element
+$script:StableMap = @{}  # stable handle -> element and what identifies it, across snapshots
 $script:SnapshotId = $null
 $script:Version = "1.1.0"  # Version bump for DVC support
 # Local log path on Windows machine (RDPDR not used for logging anymore)
@@ -67,9 +69,9 @@ function Start-Agent {
 
     # Send handshake
     $capabilities = @(
-        "snapshot", "click", "select", "toggle", "expand", "collapse",
-        "context_menu", "focus", "get", "fill", "clear",
-        "scroll", "window", "run", "wait_for", "status"
+        "snapshot", "click", "invoke", "select", "toggle", "expand", "collapse",
+        "context_menu", "focus", "get", "fill", "type", "clear",
+        "scroll", "window", "run", "exec_script", "wait_for", "status"
     )
 
     try {
@@ -118,6 +120,7 @@ function Start-Agent {
                 $responseData = switch ($request.command) {
                     "snapshot"     { Invoke-Snapshot -Params $request.params }
                     "click"        { Invoke-Click -Params $request.params }
+                    "invoke"       { Invoke-Invoke -Params $request.params }
                     "select"       { Invoke-Select -Params $request.params }
                     "toggle"       { Invoke-Toggle -Params $request.params }
                     "expand"       { Invoke-Expand -Params $request.params }
@@ -125,11 +128,14 @@ function Start-Agent {
                     "context_menu" { Invoke-ContextMenu -Params $request.params }
                     "focus"        { Invoke-Focus -Params $request.params }
                     "get"          { Invoke-Get -Params $request.params }
+                    "highlight"    { Invoke-Highlight -Params $request.params }
                     "fill"         { Invoke-Fill -Params $request.params }
+                    "type"         { Invoke-Type -Params $request.params }
                     "clear"        { Invoke-Clear -Params $request.params }
                     "scroll"       { Invoke-Scroll -Params $request.params }
                     "window"       { Invoke-Window -Params $request.params }
                     "run"          { Invoke-Run -Params $request.params }
+                    "exec_script"  { Invoke-ExecScript -Params $request.params }
                     "wait_for"     { Invoke-WaitFor -Params $request.params }
                     "status"       { Get-AgentStatus }
                     default        { throw "Unknown command: $($request.command)" }
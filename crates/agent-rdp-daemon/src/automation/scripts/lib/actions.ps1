@@ -21,6 +21,15 @@ function Invoke-Click {
     # Use mouse click via SendInput for reliable, non-blocking interaction.
 
     $doubleClick = if ($null -ne $Params.double_click) { $Params.double_click } else { $false }
+    $button = if ($Params.button) { $Params.button } else { "left" }
+
+    # MOUSEEVENTF_*DOWN / *UP flags for the button
+    switch ($button) {
+        "left"   { $downFlag = 0x0002; $upFlag = 0x0004 }
+        "right"  { $downFlag = 0x0008; $upFlag = 0x0010 }
+        "middle" { $downFlag = 0x0020; $upFlag = 0x0040 }
+        default  { throw "Unknown button '$button': must be left, right or middle" }
+    }
 
     # Get the element's bounding rectangle
     $rect = $element.Current.BoundingRectangle
@@ -55,18 +64,19 @@ public class InvokeMouse {
     Start-Sleep -Milliseconds 30
 
     # Perform click(s)
-    [InvokeMouse]::mouse_event([InvokeMouse]::MOUSEEVENTF_LEFTDOWN, 0, 0, 0, [UIntPtr]::Zero)
+    [InvokeMouse]::mouse_event($downFlag, 0, 0, 0, [UIntPtr]::Zero)
     Start-Sleep -Milliseconds 30
-    [InvokeMouse]::mouse_event([InvokeMouse]::MOUSEEVENTF_LEFTUP, 0, 0, 0, [UIntPtr]::Zero)
+    [InvokeMouse]::mouse_event($upFlag, 0, 0, 0, [UIntPtr]::Zero)
 
     if ($doubleClick) {
         Start-Sleep -Milliseconds 50
-        [InvokeMouse]::mouse_event([InvokeMouse]::MOUSEEVENTF_LEFTDOWN, 0, 0, 0, [UIntPtr]::Zero)
+        [InvokeMouse]::mouse_event($downFlag, 0, 0, 0, [UIntPtr]::Zero)
         Start-Sleep -Milliseconds 30
-        [InvokeMouse]::mouse_event([InvokeMouse]::MOUSEEVENTF_LEFTUP, 0, 0, 0, [UIntPtr]::Zero)
+        [InvokeMouse]::mouse_event($upFlag, 0, 0, 0, [UIntPtr]::Zero)
     }
 
-    $method = if ($doubleClick) { "double_click" } else { "click" }
+    $method = if ($button -eq "left") { "click" } else { "$($button)_click" }
+    if ($doubleClick) { $method = "double_$method" }
 
     return @{
         clicked = $true
@@ -76,6 +86,33 @@ public class InvokeMouse {
     }
 }
 
+function Invoke-Invoke {
+    param($Params)
+
+    $element = Find-Element -Selector $Params.selector
+    if (-not $element) { throw "Element not found: $($Params.selector)" }
+
+    if (-not $element.Current.IsEnabled) {
+        throw "Element is disabled"
+    }
+
+    try {
+        $invokePattern = $element.GetCurrentPattern([System.Windows.Automation.InvokePattern]::Pattern)
+    } catch {
+        throw "Element does not support InvokePattern: $($_.Exception.Message)"
+    }
+    if (-not $invokePattern) { throw "Element does not support InvokePattern" }
+
+    # Calls the control directly, so it works for elements with no clickable point
+    try {
+        $invokePattern.Invoke()
+    } catch {
+        throw "InvokePattern failed: $($_.Exception.Message)"
+    }
+
+    return @{ invoked = $true; method = "InvokePattern" }
+}
+
 function Invoke-Expand {
     param($Params)
 
@@ -261,6 +298,92 @@ function Invoke-Get {
     return $result
 }
 
+# Highlight boxes still up, each with the runspace drawing it
+$script:Highlights = New-Object System.Collections.ArrayList
+
+# Width in pixels of a highlight box's border, drawn just outside the element
+$script:HighlightBorder = 3
+
+function Invoke-Highlight {
+    param($Params)
+
+    $element = Find-Element -Selector $Params.selector
+    if (-not $element) { throw "Element not found: $($Params.selector)" }
+
+    $rect = $element.Current.BoundingRectangle
+    if ($rect.IsEmpty -or [double]::IsInfinity($rect.X) -or $rect.Width -le 0 -or $rect.Height -le 0) {
+        throw "Element has no valid bounding rectangle (may be off-screen or invisible)"
+    }
+    $durationMs = if ($Params.duration_ms) { [int]$Params.duration_ms } else { 3000 }
+    $bounds = @{
+        x = [int]$rect.X
+        y = [int]$rect.Y
+        width = [int]$rect.Width
+        height = [int]$rect.Height
+    }
+
+    # Clean up after boxes that have closed
+    foreach ($done in @($script:Highlights | Where-Object { $_.Handle.IsCompleted })) {
+        try { $done.PowerShell.EndInvoke($done.Handle) } catch {}
+        $done.PowerShell.Dispose()
+        $done.Runspace.Dispose()
+        [void]$script:Highlights.Remove($done)
+    }
+
+    # The box needs a message loop, so it runs on an STA thread of its own
+    # while the agent goes on answering requests
+    $runspace = [runspacefactory]::CreateRunspace()
+    $runspace.ApartmentState = "STA"
+    $runspace.ThreadOptions = "ReuseThread"
+    $runspace.Open()
+    $shown = New-Object System.Threading.ManualResetEvent $false
+
+    $ps = [powershell]::Create()
+    $ps.Runspace = $runspace
+    [void]$ps.AddScript({
+        param($Bounds, $DurationMs, $Border, $Shown)
+
+        # A red frame around a transparent (TransparencyKey) middle
+        $form = New-Object HighlightForm
+        $form.FormBorderStyle = "None"
+        $form.ShowInTaskbar = $false
+        $form.TopMost = $true
+        $form.StartPosition = "Manual"
+        $form.BackColor = [System.Drawing.Color]::Red
+        $form.TransparencyKey = [System.Drawing.Color]::Magenta
+        $form.Bounds = New-Object System.Drawing.Rectangle `
+            ($Bounds.x - $Border), ($Bounds.y - $Border), `
+            ($Bounds.width + 2 * $Border), ($Bounds.height + 2 * $Border)
+
+        $middle = New-Object System.Windows.Forms.Panel
+        $middle.BackColor = [System.Drawing.Color]::Magenta
+        $middle.Bounds = New-Object System.Drawing.Rectangle $Border, $Border, $Bounds.width, $Bounds.height
+        $form.Controls.Add($middle)
+
+        $timer = New-Object System.Windows.Forms.Timer
+        $timer.Interval = $DurationMs
+        $timer.add_Tick({ $timer.Stop(); $form.Close() })
+        $form.add_Shown({ $timer.Start(); [void]$Shown.Set() })
+
+        [System.Windows.Forms.Application]::Run($form)
+        $timer.Dispose()
+        $form.Dispose()
+    }).AddArgument($bounds).AddArgument($durationMs).AddArgument($script:HighlightBorder).AddArgument($shown)
+
+    $handle = $ps.BeginInvoke()
+    [void]$script:Highlights.Add(@{ PowerShell = $ps; Runspace = $runspace; Handle = $handle })
+
+    # Answer once the box is up, so a screenshot taken next shows it
+    if (-not $shown.WaitOne(2000)) {
+        Write-Log "Highlight box for $($Params.selector) did not appear within 2s" "WARN"
+    }
+
+    return @{
+        bounds = $bounds
+        duration_ms = $durationMs
+    }
+}
+
 function Invoke-Fill {
     param($Params)
 
@@ -290,6 +413,71 @@ function Invoke-Fill {
     return @{ filled = $true; text = $Params.text; method = "sendkeys" }
 }
 
+function Test-ElementFocused {
+    param($Element)
+
+    # Focus may land on a child of the element (e.g. the edit box inside a combo box)
+    $walker = [System.Windows.Automation.TreeWalker]::ControlViewWalker
+    $current = [System.Windows.Automation.AutomationElement]::FocusedElement
+    while ($current) {
+        if ([System.Windows.Automation.Automation]::Compare($current, $Element)) { return $true }
+        $current = $walker.GetParent($current)
+    }
+    return $false
+}
+
+function Invoke-Type {
+    param($Params)
+
+    $element = Find-Element -Selector $Params.selector
+    if (-not $element) { throw "Element not found: $($Params.selector)" }
+
+    $element.SetFocus()
+    Start-Sleep -Milliseconds 50
+
+    $valuePattern = $null
+    try {
+        $valuePattern = $element.GetCurrentPattern([System.Windows.Automation.ValuePattern]::Pattern)
+    } catch {}
+
+    $method = $null
+    if ($valuePattern -and -not $valuePattern.Current.IsReadOnly) {
+        try {
+            $existing = if ($Params.clear) { "" } else { $valuePattern.Current.Value }
+            $valuePattern.SetValue($existing + $Params.text)
+            $method = "value_pattern"
+        } catch {}
+    }
+
+    if (-not $method) {
+        # Keystrokes go wherever focus is, so make sure it's on the element
+        if (-not (Test-ElementFocused $element)) {
+            throw "Could not focus element: $($Params.selector)"
+        }
+
+        # Clear, or move to the end so the text is appended
+        if ($Params.clear) {
+            [System.Windows.Forms.SendKeys]::SendWait("^a{DEL}")
+        } else {
+            [System.Windows.Forms.SendKeys]::SendWait("^{END}")
+        }
+        Start-Sleep -Milliseconds 50
+
+        # Escape special characters for SendKeys
+        $escaped = $Params.text -replace '([+^%~(){}\[\]])', '{$1}'
+        [System.Windows.Forms.SendKeys]::SendWait($escaped)
+        $method = "sendkeys"
+        Start-Sleep -Milliseconds 50
+    }
+
+    $value = $null
+    try {
+        $value = $element.GetCurrentPattern([System.Windows.Automation.ValuePattern]::Pattern).Current.Value
+    } catch {}
+
+    return @{ typed = $true; method = $method; value = $value }
+}
+
 function Invoke-Clear {
     param($Params)
 
@@ -476,15 +664,7 @@ function Invoke-Window {
         }
     } else {
         # Get foreground window
-        Add-Type -TypeDefinition @"
-using System;
-using System.Runtime.InteropServices;
-public class Win32 {
-    [DllImport("user32.dll")]
-    public static extern IntPtr GetForegroundWindow();
-}
-"@
-        $hwnd = [Win32]::GetForegroundWindow()
+        $hwnd = [WindowFocus]::GetForegroundWindow()
         $window = [System.Windows.Automation.AutomationElement]::FromHandle($hwnd)
     }
 
@@ -494,9 +674,17 @@ public class Win32 {
 
     switch ($action) {
         "focus" {
-            $window.SetFocus()
+            $hwnd = Get-TopLevelHandle -Element $window
+            if (-not [WindowFocus]::Focus($hwnd)) {
+                throw "Window did not come to the foreground: $($window.Current.Name)"
+            }
             return @{ action = "focus"; success = $true }
         }
+        "raise" {
+            $hwnd = Get-TopLevelHandle -Element $window
+            [WindowFocus]::Raise($hwnd)
+            return @{ action = "raise"; success = $true }
+        }
         "maximize" {
             $windowPattern = $window.GetCurrentPattern([System.Windows.Automation.WindowPattern]::Pattern)
             if ($windowPattern) {
@@ -530,6 +718,22 @@ public class Win32 {
     throw "Window action failed: $action"
 }
 
+# Handle of the top-level window containing an element, walking up to the
+# nearest ancestor with a native window for elements that have none (WPF).
+function Get-TopLevelHandle {
+    param($Element)
+
+    $walker = [System.Windows.Automation.TreeWalker]::ControlViewWalker
+    $current = $Element
+    while ($current -and $current.Current.NativeWindowHandle -eq 0) {
+        $current = $walker.GetParent($current)
+    }
+    if (-not $current) {
+        throw "Element has no window: $($Element.Current.Name)"
+    }
+    return [WindowFocus]::Root([IntPtr]$current.Current.NativeWindowHandle)
+}
+
 function Invoke-Run {
     param($Params)
 
@@ -578,15 +782,80 @@ function Invoke-Run {
     }
 }
 
+# Longest stdout or stderr returned from a script, in characters
+$script:MaxScriptOutputChars = 1MB
+
+function Invoke-ExecScript {
+    param($Params)
+
+    $timeoutMs = if ($Params.timeout_ms) { [int]$Params.timeout_ms } else { 10000 }
+
+    # Run from a file so multi-line scripts need no quoting; UTF-8 output so
+    # non-ASCII text survives the redirect
+    $scriptPath = Join-Path $env:TEMP "agent-rdp-$([guid]::NewGuid()).ps1"
+    $source = "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8`r`n" + $Params.script
+    [System.IO.File]::WriteAllText($scriptPath, $source, (New-Object System.Text.UTF8Encoding $true))
+
+    try {
+        $startInfo = New-Object System.Diagnostics.ProcessStartInfo
+        $startInfo.FileName = "powershell.exe"
+        $startInfo.Arguments = "-NoProfile -NonInteractive -ExecutionPolicy Bypass -File `"$scriptPath`""
+        $startInfo.WorkingDirectory = $env:USERPROFILE
+        $startInfo.UseShellExecute = $false
+        $startInfo.RedirectStandardOutput = $true
+        $startInfo.RedirectStandardError = $true
+        $startInfo.StandardOutputEncoding = [System.Text.Encoding]::UTF8
+        $startInfo.StandardErrorEncoding = [System.Text.Encoding]::UTF8
+        $startInfo.CreateNoWindow = $true
+
+        $process = [System.Diagnostics.Process]::Start($startInfo)
+
+        # Use async reading to avoid deadlock when buffer fills
+        $stdoutTask = $process.StandardOutput.ReadToEndAsync()
+        $stderrTask = $process.StandardError.ReadToEndAsync()
+
+        $exited = $process.WaitForExit($timeoutMs)
+
+        if (-not $exited) {
+            try { $process.Kill() } catch {}
+            throw "Script timed out after $timeoutMs ms and was killed"
+        }
+
+        [void]$stdoutTask.Wait(5000)
+        [void]$stderrTask.Wait(5000)
+
+        $stdout = $stdoutTask.Result
+        $stderr = $stderrTask.Result
+        $truncated = $false
+        if ($stdout.Length -gt $script:MaxScriptOutputChars) {
+            $stdout = $stdout.Substring(0, $script:MaxScriptOutputChars)
+            $truncated = $true
+        }
+        if ($stderr.Length -gt $script:MaxScriptOutputChars) {
+            $stderr = $stderr.Substring(0, $script:MaxScriptOutputChars)
+            $truncated = $true
+        }
+
+        return @{
+            exit_code = $process.ExitCode
+            stdout = $stdout
+            stderr = $stderr
+            truncated = $truncated
+        }
+    } finally {
+        Remove-Item -Path $scriptPath -Force -ErrorAction SilentlyContinue
+    }
+}
+
 function Invoke-WaitFor {
     param($Params)
 
     $selector = $Params.selector
-    $timeout = if ($Params.timeout_ms) { [int]$Params.timeout_ms } else { 30000 }
+    $timeout = if ($Params.timeout_ms) { [int]$Params.timeout_ms } else { 10000 }
     $state = if ($Params.state) { $Params.state } else { "visible" }
 
     $startTime = Get-Date
-    $pollInterval = 100  # ms
+    $pollInterval = if ($Params.poll_interval_ms) { [int]$Params.poll_interval_ms } else { 250 }
 
     while ($true) {
         $elapsed = ((Get-Date) - $startTime).TotalMilliseconds
@@ -615,6 +884,11 @@ function Invoke-WaitFor {
                     return @{ found = $true; state = $state; elapsed_ms = [int]$elapsed }
                 }
             }
+            "exists" {
+                if ($element) {
+                    return @{ found = $true; state = $state; elapsed_ms = [int]$elapsed }
+                }
+            }
         }
 
         Start-Sleep -Milliseconds $pollInterval
@@ -626,10 +900,12 @@ function Get-AgentStatus {
         agent_running = $true
         agent_pid = $PID
         version = $script:Version
+        secure_desktop = [InputState]::IsSecureDesktop()
+        scroll_lock = [InputState]::ScrollLockOn()
         capabilities = @(
-            "snapshot", "invoke", "select", "toggle", "expand", "collapse",
-            "context_menu", "focus", "get", "fill", "clear",
-            "scroll", "window", "run", "wait_for", "status"
+            "snapshot", "click", "invoke", "select", "toggle", "expand", "collapse",
+            "context_menu", "focus", "get", "fill", "type", "clear",
+            "scroll", "window", "run", "exec_script", "wait_for", "status"
         )
     }
 }
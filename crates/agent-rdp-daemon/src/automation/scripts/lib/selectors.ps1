@@ -99,6 +99,71 @@ function Find-WindowByPattern {
     return $null
 }
 
+function Test-ElementAlive {
+    param([System.Windows.Automation.AutomationElement]$Element)
+
+    # Accessing any property throws if the element is gone
+    try {
+        $null = $Element.Current.ProcessId
+        return $true
+    } catch {
+        return $false
+    }
+}
+
+# Find an element again from what identified it when its handle was recorded.
+# Among several matches, the one at the same place in the tree wins.
+function Find-ElementByIdentity {
+    param(
+        [string]$StableId,
+        $Identity
+    )
+
+    $controlType = [System.Windows.Automation.ControlType]::($Identity.role)
+    if (-not $controlType) { return $null }
+    $typeCondition = New-Object System.Windows.Automation.PropertyCondition(
+        [System.Windows.Automation.AutomationElement]::ControlTypeProperty, $controlType)
+
+    if (-not [string]::IsNullOrEmpty($Identity.automation_id)) {
+        $idCondition = New-Object System.Windows.Automation.PropertyCondition(
+            [System.Windows.Automation.AutomationElement]::AutomationIdProperty, $Identity.automation_id)
+    } elseif (-not [string]::IsNullOrEmpty($Identity.name)) {
+        $idCondition = New-Object System.Windows.Automation.PropertyCondition(
+            [System.Windows.Automation.AutomationElement]::NameProperty, $Identity.name)
+    } else {
+        return $null
+    }
+    $condition = New-Object System.Windows.Automation.AndCondition($typeCondition, $idCondition)
+
+    $root = [System.Windows.Automation.AutomationElement]::RootElement
+    $candidates = @($root.FindAll([System.Windows.Automation.TreeScope]::Descendants, $condition))
+    if ($candidates.Count -eq 0) {
+        $single = Find-ElementAcrossAllWindows -Condition $condition
+        if ($single) { $candidates = @($single) }
+    }
+
+    foreach ($candidate in $candidates) {
+        if ((Get-StableId (Get-ElementPath $candidate)) -eq $StableId) { return $candidate }
+    }
+    $sameName = @($candidates | Where-Object { $_.Current.Name -eq $Identity.name })
+    if ($sameName.Count -eq 1) { return $sameName[0] }
+    if ($candidates.Count -eq 1) { return $candidates[0] }
+    return $null
+}
+
+# Resolve a stable handle, re-locating the element if the one recorded is gone.
+function Resolve-StableHandle {
+    param([string]$StableId)
+
+    $identity = $script:StableMap[$StableId]
+    if (-not $identity) { return $null }
+    if (Test-ElementAlive $identity.element) { return $identity.element }
+
+    $element = Find-ElementByIdentity -StableId $StableId -Identity $identity
+    if ($element) { $identity.element = $element }
+    return $element
+}
+
 function Find-Element {
     param([string]$Selector)
 
@@ -121,17 +186,33 @@ function Find-Element {
 
         $element = $script:RefMap[$ref]
 
-        # Validate element is still accessible (UI may have changed)
-        try {
-            # Accessing any property will throw if element is stale
-            $null = $element.Current.ProcessId
-        } catch {
-            throw "Stale ref: $Selector - element no longer exists (UI has changed). Take a new snapshot."
+        # Validate element is still accessible (UI may have changed), and if
+        # not, look for it again by what identified it
+        if (-not (Test-ElementAlive $element)) {
+            $element = Resolve-StableHandle -StableId $script:RefIds[$ref]
+            if (-not $element) {
+                throw "Stale ref: $Selector - element no longer exists (UI has changed). Take a new snapshot."
+            }
+            $script:RefMap[$ref] = $element
         }
 
         return $element
     }
 
+    # @sXXXXXXXXXX - stable handle from any earlier snapshot
+    if ($Selector -match '^@(s[0-9a-f]{10})$') {
+        $stableId = $Matches[1]
+        if (-not $script:StableMap.ContainsKey($stableId)) {
+            throw "Unknown handle: $Selector was not in any snapshot taken by this agent."
+        }
+
+        $element = Resolve-StableHandle -StableId $stableId
+        if (-not $element) {
+            throw "Stale handle: $Selector - element could not be found again (UI has changed). Take a new snapshot."
+        }
+        return $element
+    }
+
     # #automationId - search across all windows
     if ($Selector -match '^#(.+)$') {
         $condition = New-Object System.Windows.Automation.PropertyCondition(
@@ -77,9 +77,74 @@ function Test-IsEmptyStructural {
     return $isStructural -and -not $hasName -and -not $hasValue -and -not $hasChildren
 }
 
+# Stable handles identify an element by what it is and where it sits, rather
+# than by its position in one snapshot, so they survive unrelated tree changes.
+$script:HashAlgorithm = [System.Security.Cryptography.SHA1]::Create()
+
+# Handles kept across snapshots before the map is reset
+$script:MaxStableHandles = 20000
+
+function Get-ElementRole {
+    param([System.Windows.Automation.AutomationElement]$Element)
+
+    return $Element.Current.ControlType.ProgrammaticName -replace "ControlType\.", ""
+}
+
+function Get-ElementSegment {
+    param([System.Windows.Automation.AutomationElement]$Element)
+
+    return "$(Get-ElementRole $Element)|$($Element.Current.AutomationId)|$($Element.Current.Name)"
+}
+
+# Path of an element from the desktop, as segments of each ancestor. Siblings
+# that look identical are told apart by their order.
+function Get-ElementPath {
+    param([System.Windows.Automation.AutomationElement]$Element)
+
+    $walker = [System.Windows.Automation.TreeWalker]::RawViewWalker
+    $root = [System.Windows.Automation.AutomationElement]::RootElement
+    $segments = @()
+    $current = $Element
+    while ($current -and -not [System.Windows.Automation.Automation]::Compare($current, $root)) {
+        $segment = Get-ElementSegment $current
+        $index = 0
+        $sibling = $walker.GetPreviousSibling($current)
+        while ($sibling) {
+            if ((Get-ElementSegment $sibling) -eq $segment) { $index++ }
+            $sibling = $walker.GetPreviousSibling($sibling)
+        }
+        if ($index -gt 0) { $segment += "#$index" }
+        $segments = @($segment) + $segments
+        $current = $walker.GetParent($current)
+    }
+    return "/" + ($segments -join "/")
+}
+
+function Get-StableId {
+    param([string]$Path)
+
+    $bytes = $script:HashAlgorithm.ComputeHash([System.Text.Encoding]::UTF8.GetBytes($Path))
+    return "s" + (($bytes[0..4] | ForEach-Object { $_.ToString("x2") }) -join "")
+}
+
+function Add-StableHandle {
+    param(
+        [System.Windows.Automation.AutomationElement]$Element,
+        [string]$StableId
+    )
+
+    $script:StableMap[$StableId] = @{
+        element = $Element
+        role = Get-ElementRole $Element
+        automation_id = $Element.Current.AutomationId
+        name = $Element.Current.Name
+    }
+}
+
 function Get-AccessibilityTree {
     param(
         [System.Windows.Automation.AutomationElement]$Element,
+        [string]$Path,
         [int]$MaxDepth = 10,
         [int]$CurrentDepth = 0,
         [ref]$RefCounter,
@@ -99,13 +164,19 @@ function Get-AccessibilityTree {
     $ref = $RefCounter.Value
     $script:RefMap[$ref] = $Element
 
+    if (-not $Path) { $Path = Get-ElementPath $Element }
+    $stableId = Get-StableId $Path
+    $script:RefIds[$ref] = $stableId
+    Add-StableHandle -Element $Element -StableId $stableId
+
     $rect = $Element.Current.BoundingRectangle
     $node = [ordered]@{}
 
     # Always include ref (with "e" prefix handled in output)
     $node["ref"] = $ref
+    $node["stable_id"] = $stableId
 
-    $node["role"] = $Element.Current.ControlType.ProgrammaticName -replace "ControlType\.", ""
+    $node["role"] = Get-ElementRole $Element
 
     if (-not [string]::IsNullOrEmpty($Element.Current.Name)) {
         $node["name"] = $Element.Current.Name
@@ -157,8 +228,15 @@ function Get-AccessibilityTree {
             $child = $walker.GetNextSibling($child)
         }
 
+        $seen = @{}
         foreach ($child in $childElements) {
-            $childNode = Get-AccessibilityTree -Element $child -MaxDepth $MaxDepth `
+            $segment = Get-ElementSegment $child
+            $index = [int]$seen[$segment]
+            $seen[$segment] = $index + 1
+            if ($index -gt 0) { $segment += "#$index" }
+            $childPath = if ($Path -eq "/") { "/$segment" } else { "$Path/$segment" }
+
+            $childNode = Get-AccessibilityTree -Element $child -Path $childPath -MaxDepth $MaxDepth `
                 -CurrentDepth ($CurrentDepth + 1) -RefCounter $RefCounter `
                 -InteractiveOnly $InteractiveOnly -Compact $Compact
             if ($childNode) {
@@ -189,6 +267,10 @@ function Invoke-Snapshot {
     param($Params)
 
     $script:RefMap = @{}
+    $script:RefIds = @{}
+    if ($script:StableMap.Count -gt $script:MaxStableHandles) {
+        $script:StableMap = @{}
+    }
     $script:WasTruncated = $false
     $refCounter = [ref]0
     $script:SnapshotId = [guid]::NewGuid().ToString().Substring(0, 8)
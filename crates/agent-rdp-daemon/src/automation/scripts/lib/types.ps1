@@ -1,4 +1,4 @@
-# types.ps1 - P/Invoke helper types for mouse input and window enumeration
+# types.ps1 - P/Invoke helper types for mouse input, window enumeration, focus and input state
 
 Add-Type -TypeDefinition @"
 using System;
@@ -61,4 +61,194 @@ public class WindowEnum {
         return true;
     }
 }
+
+public class WindowFocus {
+    [DllImport("user32.dll")]
+    public static extern IntPtr GetForegroundWindow();
+
+    [DllImport("user32.dll")]
+    public static extern bool SetForegroundWindow(IntPtr hWnd);
+
+    [DllImport("user32.dll")]
+    public static extern bool BringWindowToTop(IntPtr hWnd);
+
+    [DllImport("user32.dll")]
+    public static extern bool ShowWindow(IntPtr hWnd, int nCmdShow);
+
+    [DllImport("user32.dll")]
+    public static extern bool IsIconic(IntPtr hWnd);
+
+    [DllImport("user32.dll")]
+    public static extern IntPtr GetAncestor(IntPtr hWnd, uint gaFlags);
+
+    [DllImport("user32.dll")]
+    public static extern uint GetWindowThreadProcessId(IntPtr hWnd, IntPtr lpdwProcessId);
+
+    [DllImport("user32.dll")]
+    public static extern bool AttachThreadInput(uint idAttach, uint idAttachTo, bool fAttach);
+
+    [DllImport("user32.dll")]
+    public static extern bool AllowSetForegroundWindow(int dwProcessId);
+
+    [DllImport("user32.dll")]
+    public static extern bool SetWindowPos(IntPtr hWnd, IntPtr hWndInsertAfter,
+        int x, int y, int cx, int cy, uint uFlags);
+
+    [DllImport("user32.dll")]
+    public static extern void keybd_event(byte bVk, byte bScan, uint dwFlags, IntPtr dwExtraInfo);
+
+    [DllImport("kernel32.dll")]
+    public static extern uint GetCurrentThreadId();
+
+    public const uint GA_ROOT = 2;
+    public const int SW_RESTORE = 9;
+    public const int SW_SHOWNOACTIVATE = 4;
+    public const int ASFW_ANY = -1;
+    public const byte VK_MENU = 0x12;
+    public const uint KEYEVENTF_KEYUP = 0x0002;
+    public const uint SWP_NOSIZE = 0x0001;
+    public const uint SWP_NOMOVE = 0x0002;
+    public const uint SWP_NOACTIVATE = 0x0010;
+    public static readonly IntPtr HWND_TOPMOST = new IntPtr(-1);
+    public static readonly IntPtr HWND_NOTOPMOST = new IntPtr(-2);
+
+    // Top-level window containing hWnd
+    public static IntPtr Root(IntPtr hWnd) {
+        IntPtr root = GetAncestor(hWnd, GA_ROOT);
+        return root == IntPtr.Zero ? hWnd : root;
+    }
+
+    public static bool IsForeground(IntPtr hWnd) {
+        IntPtr foreground = GetForegroundWindow();
+        return foreground != IntPtr.Zero && Root(foreground) == Root(hWnd);
+    }
+
+    // Restore and activate a window. Windows only lets the foreground process
+    // call SetForegroundWindow, so the agent attaches to the foreground
+    // thread's input queue and taps Alt (which lifts the foreground lock)
+    // before asking.
+    public static bool Focus(IntPtr hWnd) {
+        if (IsIconic(hWnd)) {
+            ShowWindow(hWnd, SW_RESTORE);
+        }
+        if (SetForegroundWindow(hWnd) && IsForeground(hWnd)) {
+            return true;
+        }
+
+        AllowSetForegroundWindow(ASFW_ANY);
+        uint current = GetCurrentThreadId();
+        uint foreground = GetWindowThreadProcessId(GetForegroundWindow(), IntPtr.Zero);
+        uint target = GetWindowThreadProcessId(hWnd, IntPtr.Zero);
+        bool attachedForeground = foreground != 0 && foreground != current
+            && AttachThreadInput(current, foreground, true);
+        bool attachedTarget = target != 0 && target != current && target != foreground
+            && AttachThreadInput(current, target, true);
+        try {
+            keybd_event(VK_MENU, 0, 0, IntPtr.Zero);
+            keybd_event(VK_MENU, 0, KEYEVENTF_KEYUP, IntPtr.Zero);
+            BringWindowToTop(hWnd);
+            SetForegroundWindow(hWnd);
+        } finally {
+            if (attachedTarget) {
+                AttachThreadInput(current, target, false);
+            }
+            if (attachedForeground) {
+                AttachThreadInput(current, foreground, false);
+            }
+        }
+        return IsForeground(hWnd);
+    }
+
+    // Bring a window to the top of the z-order without activating it, so
+    // keyboard focus stays where it is.
+    public static void Raise(IntPtr hWnd) {
+        if (IsIconic(hWnd)) {
+            ShowWindow(hWnd, SW_SHOWNOACTIVATE);
+        }
+        uint flags = SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE;
+        SetWindowPos(hWnd, HWND_TOPMOST, 0, 0, 0, 0, flags);
+        SetWindowPos(hWnd, HWND_NOTOPMOST, 0, 0, 0, 0, flags);
+    }
+}
+
+public class InputState {
+    [DllImport("user32.dll", SetLastError = true)]
+    public static extern IntPtr OpenInputDesktop(uint dwFlags, bool fInherit, uint dwDesiredAccess);
+
+    [DllImport("user32.dll")]
+    public static extern bool CloseDesktop(IntPtr hDesktop);
+
+    [DllImport("user32.dll", CharSet = CharSet.Unicode)]
+    public static extern bool GetUserObjectInformation(IntPtr hObj, int nIndex,
+        System.Text.StringBuilder pvInfo, int nLength, out int lpnLengthNeeded);
+
+    [DllImport("user32.dll")]
+    public static extern short GetKeyState(int nVirtKey);
+
+    public const uint DESKTOP_READOBJECTS = 0x0001;
+    public const int UOI_NAME = 2;
+    public const int VK_SCROLL = 0x91;
+
+    // Whether input goes to a desktop other than the user's (the secure
+    // desktop of a UAC prompt, the lock or logon screen). Those can't be
+    // opened from the user's session at all.
+    public static bool IsSecureDesktop() {
+        IntPtr desktop = OpenInputDesktop(0, false, DESKTOP_READOBJECTS);
+        if (desktop == IntPtr.Zero) {
+            return true;
+        }
+        try {
+            var name = new System.Text.StringBuilder(256);
+            int needed;
+            if (!GetUserObjectInformation(desktop, UOI_NAME, name, name.Capacity * 2, out needed)) {
+                return false;
+            }
+            return !string.Equals(name.ToString(), "Default", StringComparison.OrdinalIgnoreCase);
+        } finally {
+            CloseDesktop(desktop);
+        }
+    }
+
+    // Scroll Lock as the foreground window sees it. Key state is kept per
+    // input queue, so the agent shares the foreground thread's queue to read it.
+    public static bool ScrollLockOn() {
+        uint current = WindowFocus.GetCurrentThreadId();
+        uint foreground = WindowFocus.GetWindowThreadProcessId(
+            WindowFocus.GetForegroundWindow(), IntPtr.Zero);
+        bool attached = foreground != 0 && foreground != current
+            && WindowFocus.AttachThreadInput(current, foreground, true);
+        try {
+            return (GetKeyState(VK_SCROLL) & 1) != 0;
+        } finally {
+            if (attached) {
+                WindowFocus.AttachThreadInput(current, foreground, false);
+            }
+        }
+    }
+}
+"@
+
+# Overlay box for highlight: stays on top, never takes focus and lets mouse
+# input through to the windows beneath it
+Add-Type -ReferencedAssemblies System.Windows.Forms, System.Drawing -TypeDefinition @"
+using System.Windows.Forms;
+
+public class HighlightForm : Form {
+    const int WS_EX_TOPMOST = 0x00000008;
+    const int WS_EX_TRANSPARENT = 0x00000020;
+    const int WS_EX_TOOLWINDOW = 0x00000080;
+    const int WS_EX_NOACTIVATE = 0x08000000;
+
+    protected override bool ShowWithoutActivation {
+        get { return true; }
+    }
+
+    protected override CreateParams CreateParams {
+        get {
+            CreateParams cp = base.CreateParams;
+            cp.ExStyle |= WS_EX_TOPMOST | WS_EX_TRANSPARENT | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE;
+            return cp;
+        }
+    }
+}
 "@
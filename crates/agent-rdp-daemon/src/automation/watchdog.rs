@@ -0,0 +1,73 @@
+//! Watchdog that relaunches the automation agent.
+//!
+//! The PowerShell agent can exit (closed from Task Manager, a crash in a UI
+//! Automation call) or hang, and nothing on the remote side restarts it. When
+//! a request finds the agent's channel gone, or enough requests in a row have
+//! failed, the watchdog rewrites the scripts, launches a new agent from the
+//! automation drive the same way connect does, and waits for its handshake.
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use super::{AutomationBootstrap, AutomationState, SharedAutomationState};
+use crate::rdp_session::RdpSession;
+
+/// Handshake checks after a relaunch (about 15 seconds with backoff).
+const RESTART_HANDSHAKE_ATTEMPTS: u32 = 8;
+
+/// Whether automation is enabled but its agent is gone or unresponsive.
+pub async fn agent_failed(automation_state: &SharedAutomationState) -> bool {
+    is_failed(&*automation_state.lock().await)
+}
+
+fn is_failed(state: &AutomationState) -> bool {
+    state.enabled
+        && state
+            .dvc_ipc
+            .as_ref()
+            .is_some_and(|ipc| !ipc.is_ready() || ipc.is_unresponsive())
+}
+
+/// Launch a new agent and wait for its handshake.
+///
+/// Does nothing if the agent is healthy by the time the automation state is
+/// locked, as when a concurrent request already restarted it.
+pub async fn restart_agent(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    automation_state: &SharedAutomationState,
+) -> anyhow::Result<()> {
+    let bootstrap = AutomationBootstrap::new(crate::get_session_dir(""));
+
+    // Locked in the same order as connect: session, then automation state
+    {
+        let session = rdp_session.lock().await;
+        let rdp = session
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Not connected to an RDP server"))?;
+        let mut state = automation_state.lock().await;
+        if !is_failed(&state) {
+            return Ok(());
+        }
+
+        state.restarts += 1;
+        warn!(
+            "Automation agent (PID {:?}) stopped responding; restarting it (restart {})",
+            state.agent_pid, state.restarts
+        );
+        state.agent_ready = false;
+        if let Some(ipc) = &state.dvc_ipc {
+            ipc.forget_agent();
+        }
+        bootstrap.write_scripts(&state).await?;
+        bootstrap.launch_agent(rdp, &state).await?;
+    }
+
+    let mut state = automation_state.lock().await;
+    bootstrap
+        .wait_for_agent(&mut state, RESTART_HANDSHAKE_ATTEMPTS)
+        .await?;
+    info!("Automation agent restarted (PID {:?})", state.agent_pid);
+    Ok(())
+}
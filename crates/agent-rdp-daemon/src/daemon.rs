@@ -3,14 +3,18 @@
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use agent_rdp_protocol::{Request, Response, ResponseData, SessionInfo, ConnectionState, ErrorCode};
-use tokio::sync::{broadcast, Mutex};
-use tracing::{error, info, warn};
+use agent_rdp_protocol::{
+    DisconnectInfo, Request, RequestEnvelope, Response, ResponseData, SessionInfo, ConnectionState,
+    ErrorCode, IpcFraming, PROTOCOL_VERSION,
+};
+use tokio::sync::{broadcast, watch, Mutex, Semaphore};
+use tracing::{debug, error, info, warn};
 
 use crate::automation::{new_shared_state, SharedAutomationState};
 use crate::handlers;
-use crate::ipc_server::IpcServer;
-use crate::rdp_session::RdpSession;
+use crate::ipc_server::{read_frame, write_frame, IpcServer};
+use crate::rdp_session::credentials::{CredentialProvider, SharedCredentialProvider};
+use crate::rdp_session::{DisconnectNotify, RdpSession};
 use crate::ws_server::WsServerHandle;
 
 /// Shared WebSocket server state that can be started/stopped dynamically.
@@ -19,6 +23,28 @@ pub type SharedWsHandle = Arc<Mutex<Option<WsServerHandle>>>;
 /// Clipboard change notification receiver (from RDP clipboard backend to daemon).
 pub type ClipboardChangedRx = Arc<Mutex<Option<tokio::sync::mpsc::UnboundedReceiver<()>>>>;
 
+/// Why the last RDP connection ended, kept after the session is dropped.
+pub type SharedLastDisconnect = Arc<Mutex<Option<DisconnectInfo>>>;
+
+/// When the last client request was processed.
+pub type SharedLastRequest = Arc<Mutex<Instant>>;
+
+/// Idle timeout from the last connect, in seconds (`None` disables it).
+pub type SharedIdleTimeout = Arc<Mutex<Option<u64>>>;
+
+/// WebSocket streaming frame rate; the event loop rebuilds its frame timer on changes.
+pub type SharedStreamFps = Arc<watch::Sender<u32>>;
+
+/// How long the daemon stays up after the server ends the session, so clients
+/// can still ask why.
+const DISCONNECT_LINGER: Duration = Duration::from_secs(30);
+
+/// How often the daemon checks whether it has been idle too long.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most requests with an ID one client can have in flight at once.
+const MAX_CONCURRENT_REQUESTS: usize = 16;
+
 /// The main daemon that manages an RDP session.
 pub struct Daemon {
     /// Session name.
@@ -40,24 +66,42 @@ pub struct Daemon {
     shutdown_tx: broadcast::Sender<()>,
 
     /// Channel to receive connection drop notifications from RDP session.
-    disconnect_rx: tokio::sync::mpsc::Receiver<()>,
+    disconnect_rx: tokio::sync::mpsc::Receiver<DisconnectInfo>,
 
     /// Sender for connection drop notifications (passed to RDP sessions).
-    disconnect_tx: tokio::sync::mpsc::Sender<()>,
+    disconnect_tx: DisconnectNotify,
+
+    /// Why the last RDP connection ended (reported by session info).
+    last_disconnect: SharedLastDisconnect,
 
     /// WebSocket server handle for streaming (shared so connect handler can start it).
     ws_handle: SharedWsHandle,
 
-    /// WebSocket streaming frame rate (used for frame broadcasting).
-    stream_fps: u32,
+    /// WebSocket streaming frame rate (used for frame broadcasting, changed by stream-config).
+    stream_fps: SharedStreamFps,
 
     /// Clipboard change notification receiver (set up when RDP connects with WS streaming).
     clipboard_changed_rx: ClipboardChangedRx,
+
+    /// When the last client request was processed (for the idle timeout).
+    last_request: SharedLastRequest,
+
+    /// Shut down after this many seconds without requests or WebSocket clients.
+    idle_timeout_secs: SharedIdleTimeout,
+
+    /// Credentials for connect requests, replacing their username and password.
+    credential_provider: Option<SharedCredentialProvider>,
 }
 
 impl Daemon {
     /// Create a new daemon for the given session.
-    pub async fn new(session_name: String) -> anyhow::Result<Self> {
+    ///
+    /// With a credential provider, connect requests log on with the credentials
+    /// it supplies rather than the username and password they carry.
+    pub async fn new(
+        session_name: String,
+        credential_provider: Option<Box<dyn CredentialProvider>>,
+    ) -> anyhow::Result<Self> {
         let socket_path = crate::get_socket_path(&session_name);
 
         // Clean up stale socket if it exists
@@ -70,7 +114,7 @@ impl Daemon {
         let (disconnect_tx, disconnect_rx) = tokio::sync::mpsc::channel(1);
 
         // Default frame rate (can be overridden by ConnectRequest)
-        let stream_fps = crate::ws_server::get_stream_fps();
+        let stream_fps = Arc::new(watch::Sender::new(crate::ws_server::get_stream_fps()));
 
         let rdp_session = Arc::new(Mutex::new(None));
 
@@ -95,9 +139,13 @@ impl Daemon {
             shutdown_tx,
             disconnect_rx,
             disconnect_tx,
+            last_disconnect: Arc::new(Mutex::new(None)),
             ws_handle,
             stream_fps,
             clipboard_changed_rx,
+            last_request: Arc::new(Mutex::new(Instant::now())),
+            idle_timeout_secs: Arc::new(Mutex::new(None)),
+            credential_provider: credential_provider.map(Arc::from),
         })
     }
 
@@ -106,9 +154,14 @@ impl Daemon {
         let mut shutdown_rx = self.shutdown_tx.subscribe();
 
         // Frame broadcast interval for WebSocket streaming
-        let frame_interval = Duration::from_millis(1000 / self.stream_fps.max(1) as u64);
-        let mut frame_timer = tokio::time::interval(frame_interval);
-        frame_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut stream_fps_rx = self.stream_fps.subscribe();
+        let mut frame_timer = new_frame_timer(*stream_fps_rx.borrow_and_update());
+
+        // Set when the server ended the session; the daemon exits unless a client reconnects
+        let mut linger_deadline: Option<tokio::time::Instant> = None;
+
+        let mut idle_timer = tokio::time::interval(IDLE_CHECK_INTERVAL);
+        idle_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
         loop {
             tokio::select! {
@@ -123,10 +176,15 @@ impl Daemon {
                             let start_time = self.start_time;
                             let shutdown_tx = self.shutdown_tx.clone();
                             let disconnect_tx = self.disconnect_tx.clone();
+                            let last_disconnect = Arc::clone(&self.last_disconnect);
                             let clipboard_changed_rx = Arc::clone(&self.clipboard_changed_rx);
+                            let last_request = Arc::clone(&self.last_request);
+                            let idle_timeout_secs = Arc::clone(&self.idle_timeout_secs);
+                            let credential_provider = self.credential_provider.clone();
+                            let stream_fps = Arc::clone(&self.stream_fps);
 
                             tokio::spawn(async move {
-                                if let Err(e) = handle_client(stream, session, automation_state, ws_handle, session_name, start_time, shutdown_tx, disconnect_tx, clipboard_changed_rx).await {
+                                if let Err(e) = handle_client(stream, session, automation_state, ws_handle, session_name, start_time, shutdown_tx, disconnect_tx, last_disconnect, clipboard_changed_rx, last_request, idle_timeout_secs, credential_provider, stream_fps).await {
                                     error!("Client handler error: {}", e);
                                 }
                             });
@@ -138,9 +196,51 @@ impl Daemon {
                 }
 
                 // Handle connection drop from RDP session
-                _ = self.disconnect_rx.recv() => {
-                    info!("RDP connection dropped, shutting down daemon");
-                    break;
+                Some(disconnect) = self.disconnect_rx.recv() => {
+                    info!(
+                        "RDP connection ended ({:?}: {}), shutting down daemon in {:?}",
+                        disconnect.reason, disconnect.message, DISCONNECT_LINGER
+                    );
+                    self.rdp_session.lock().await.take();
+                    if let Some(ref handle) = *self.ws_handle.lock().await {
+                        handle.broadcast_disconnected(&disconnect);
+                    }
+                    *self.last_disconnect.lock().await = Some(disconnect);
+                    linger_deadline = Some(tokio::time::Instant::now() + DISCONNECT_LINGER);
+                }
+
+                // Exit once the linger period is over, unless a client reconnected
+                _ = async {
+                    match linger_deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline).await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    linger_deadline = None;
+                    if self.rdp_session.lock().await.is_none() {
+                        info!("No new connection after the session ended, shutting down daemon");
+                        break;
+                    }
+                }
+
+                // Exit when no request or WebSocket client was seen for the idle timeout
+                _ = idle_timer.tick() => {
+                    let Some(timeout_secs) = *self.idle_timeout_secs.lock().await else {
+                        continue;
+                    };
+                    let has_viewers = self
+                        .ws_handle
+                        .lock()
+                        .await
+                        .as_ref()
+                        .is_some_and(|handle| handle.has_clients());
+                    let mut last_request = self.last_request.lock().await;
+                    if has_viewers {
+                        *last_request = Instant::now();
+                    } else if last_request.elapsed() >= Duration::from_secs(timeout_secs) {
+                        info!("No requests for {}s, shutting down idle daemon", timeout_secs);
+                        break;
+                    }
                 }
 
                 // Handle shutdown signal from client
@@ -174,6 +274,11 @@ impl Daemon {
                     }
                 }
 
+                // Restart the frame timer at the rate set by stream-config
+                Ok(()) = stream_fps_rx.changed() => {
+                    frame_timer = new_frame_timer(*stream_fps_rx.borrow_and_update());
+                }
+
                 // Handle clipboard changed notifications from RDP backend
                 result = async {
                     let mut rx_guard = self.clipboard_changed_rx.lock().await;
@@ -222,7 +327,19 @@ impl Daemon {
     }
 }
 
+/// Timer that paces frame broadcasts at the given rate.
+fn new_frame_timer(fps: u32) -> tokio::time::Interval {
+    let mut timer = tokio::time::interval(Duration::from_millis(1000 / fps.max(1) as u64));
+    timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    timer
+}
+
 /// Handle a single client connection.
+///
+/// Requests tagged with an ID run concurrently (up to
+/// `MAX_CONCURRENT_REQUESTS` at a time) and are answered as they complete;
+/// untagged requests are answered in order before the next line is read.
+/// Messages are lines until a `hello` first request switches the framing.
 async fn handle_client(
     stream: crate::ipc_server::IpcStream,
     rdp_session: Arc<Mutex<Option<RdpSession>>>,
@@ -231,61 +348,216 @@ async fn handle_client(
     session_name: String,
     start_time: Instant,
     shutdown_tx: broadcast::Sender<()>,
-    disconnect_tx: tokio::sync::mpsc::Sender<()>,
+    disconnect_tx: DisconnectNotify,
+    last_disconnect: SharedLastDisconnect,
     clipboard_changed_rx: ClipboardChangedRx,
+    last_request: SharedLastRequest,
+    idle_timeout_secs: SharedIdleTimeout,
+    credential_provider: Option<SharedCredentialProvider>,
+    stream_fps: SharedStreamFps,
 ) -> anyhow::Result<()> {
-    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::io::{AsyncBufReadExt, BufReader};
 
-    let (reader, mut writer) = tokio::io::split(stream);
+    let (reader, writer) = tokio::io::split(stream);
     let mut reader = BufReader::new(reader);
+    let writer: IpcWriter = Arc::new(Mutex::new(writer));
+    let in_flight = Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS));
+    let context = RequestContext {
+        rdp_session,
+        automation_state,
+        ws_handle,
+        session_name,
+        start_time,
+        disconnect_tx,
+        last_disconnect,
+        clipboard_changed_rx,
+        last_request,
+        idle_timeout_secs,
+        credential_provider,
+        stream_fps,
+    };
+    let mut framing = IpcFraming::Line;
+    let mut first_message = true;
     let mut line = String::new();
 
     loop {
-        line.clear();
-        let n = reader.read_line(&mut line).await?;
-
-        if n == 0 {
-            // Client disconnected
-            break;
-        }
+        let message = match framing {
+            IpcFraming::Line => {
+                line.clear();
+                if reader.read_line(&mut line).await? == 0 {
+                    // Client disconnected
+                    break;
+                }
+                line.trim().to_string()
+            }
+            // Requests carry nothing in the binary region
+            IpcFraming::LengthPrefixed => match read_frame(&mut reader).await? {
+                Some((json, _)) => String::from_utf8_lossy(&json).into_owned(),
+                None => break,
+            },
+        };
+        let is_first = std::mem::replace(&mut first_message, false);
 
-        let request: Request = match serde_json::from_str(line.trim()) {
-            Ok(req) => req,
+        let RequestEnvelope { id, request } = match serde_json::from_str(&message) {
+            Ok(envelope) => envelope,
             Err(e) => {
-                let resp = Response::error(ErrorCode::InvalidRequest, format!("Invalid request: {}", e));
-                let json = serde_json::to_string(&resp)? + "\n";
-                writer.write_all(json.as_bytes()).await?;
-                writer.flush().await?;
+                let resp = Response {
+                    id: salvage_request_id(&message),
+                    ..Response::error(ErrorCode::InvalidRequest, format!("Invalid request: {}", e))
+                };
+                write_response(&writer, &resp, framing).await?;
                 continue;
             }
         };
 
+        // Answered in the old framing; the new one applies from the next message
+        if let Request::Hello { framing: requested } = request {
+            if is_first {
+                let response = Response {
+                    id,
+                    ..Response::success(ResponseData::Hello { framing: requested })
+                };
+                write_response(&writer, &response, framing).await?;
+                debug!("Client switched to {:?} framing", requested);
+                framing = requested;
+                continue;
+            }
+        }
+
         let is_shutdown = matches!(request, Request::Shutdown);
 
-        let response = process_request(
+        match id {
+            // Answered whenever they finish; waits here once too many are in flight
+            Some(id) if !is_shutdown => {
+                let permit = Arc::clone(&in_flight).acquire_owned().await?;
+                let context = context.clone();
+                let writer = Arc::clone(&writer);
+                tokio::spawn(async move {
+                    let response = Response {
+                        id: Some(id),
+                        ..context.respond(request).await
+                    };
+                    drop(permit);
+                    if let Err(e) = write_response(&writer, &response, framing).await {
+                        debug!("Failed to send response {}: {}", id, e);
+                    }
+                });
+            }
+            _ => {
+                let response = Response {
+                    id,
+                    ..context.respond(request).await
+                };
+                write_response(&writer, &response, framing).await?;
+
+                // Trigger daemon shutdown if this was a shutdown request
+                if is_shutdown {
+                    info!("Shutdown request received, signaling daemon to exit");
+                    let _ = shutdown_tx.send(());
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Write half of a client connection, shared by its in-flight requests.
+type IpcWriter = Arc<Mutex<tokio::io::WriteHalf<crate::ipc_server::IpcStream>>>;
+
+/// Send one response, with its binary payload if it has one: after the line,
+/// or in the binary region of a length-prefixed frame.
+///
+/// Both are written under the same lock so concurrent responses can't land
+/// between them.
+async fn write_response(
+    writer: &IpcWriter,
+    response: &Response,
+    framing: IpcFraming,
+) -> anyhow::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let payload = response.payload().unwrap_or_default();
+    match framing {
+        IpcFraming::Line => {
+            let json = serde_json::to_string(response)? + "\n";
+            let mut writer = writer.lock().await;
+            writer.write_all(json.as_bytes()).await?;
+            writer.write_all(payload).await?;
+            writer.flush().await?;
+        }
+        IpcFraming::LengthPrefixed => {
+            let json = serde_json::to_vec(response)?;
+            write_frame(&mut *writer.lock().await, &json, payload).await?;
+        }
+    }
+    Ok(())
+}
+
+/// The ID of a request line that didn't parse, so the error still reaches
+/// the caller waiting on it.
+fn salvage_request_id(line: &str) -> Option<u64> {
+    serde_json::from_str::<serde_json::Value>(line)
+        .ok()?
+        .get("id")?
+        .as_u64()
+}
+
+/// Daemon state requests from one client are processed against.
+#[derive(Clone)]
+struct RequestContext {
+    rdp_session: Arc<Mutex<Option<RdpSession>>>,
+    automation_state: SharedAutomationState,
+    ws_handle: SharedWsHandle,
+    session_name: String,
+    start_time: Instant,
+    disconnect_tx: DisconnectNotify,
+    last_disconnect: SharedLastDisconnect,
+    clipboard_changed_rx: ClipboardChangedRx,
+    last_request: SharedLastRequest,
+    idle_timeout_secs: SharedIdleTimeout,
+    credential_provider: Option<SharedCredentialProvider>,
+    stream_fps: SharedStreamFps,
+}
+
+impl RequestContext {
+    /// Process a request, keeping the idle clock current.
+    async fn respond(&self, request: Request) -> Response {
+        // Marked before and after, so a long request isn't taken for idleness
+        *self.last_request.lock().await = Instant::now();
+
+        let mut response = process_request(
             request,
-            &rdp_session,
-            &automation_state,
-            &ws_handle,
-            &session_name,
-            start_time,
-            &disconnect_tx,
-            &clipboard_changed_rx,
+            &self.rdp_session,
+            &self.automation_state,
+            &self.ws_handle,
+            &self.session_name,
+            self.start_time,
+            &self.disconnect_tx,
+            &self.last_disconnect,
+            &self.clipboard_changed_rx,
+            &self.idle_timeout_secs,
+            self.credential_provider.as_ref(),
+            &self.stream_fps,
         ).await;
 
-        let json = serde_json::to_string(&response)? + "\n";
-        writer.write_all(json.as_bytes()).await?;
-        writer.flush().await?;
+        *self.last_request.lock().await = Instant::now();
 
-        // Trigger daemon shutdown if this was a shutdown request
-        if is_shutdown {
-            info!("Shutdown request received, signaling daemon to exit");
-            let _ = shutdown_tx.send(());
-            break;
+        // After the server ends the session, tell clients why rather than just "not connected"
+        if let Some(ref mut error) = response.error {
+            if error.code == ErrorCode::NotConnected {
+                if let Some(ref disconnect) = *self.last_disconnect.lock().await {
+                    error.message = format!(
+                        "{} (session ended: {:?}: {})",
+                        error.message, disconnect.reason, disconnect.message
+                    );
+                }
+            }
         }
-    }
 
-    Ok(())
+        response
+    }
 }
 
 /// Process a single request and return a response.
@@ -296,23 +568,55 @@ async fn process_request(
     ws_handle: &SharedWsHandle,
     session_name: &str,
     start_time: Instant,
-    disconnect_tx: &tokio::sync::mpsc::Sender<()>,
+    disconnect_tx: &DisconnectNotify,
+    last_disconnect: &SharedLastDisconnect,
     clipboard_changed_rx: &ClipboardChangedRx,
+    idle_timeout_secs: &SharedIdleTimeout,
+    credential_provider: Option<&SharedCredentialProvider>,
+    stream_fps: &SharedStreamFps,
 ) -> Response {
     match request {
-        Request::Ping => Response::success(ResponseData::Pong),
+        Request::Ping => Response::success(ResponseData::Pong {
+            protocol_version: PROTOCOL_VERSION,
+            version: Some(agent_rdp_protocol::VERSION.to_string()),
+        }),
+
+        Request::Hello { .. } => Response::error(
+            ErrorCode::InvalidRequest,
+            "hello must be the first request on a connection",
+        ),
 
         Request::SessionInfo => {
+            // Read the stream details before locking the session (connect locks
+            // them in the opposite order)
+            let (stream_port, stream_token, stream_viewer) = match *ws_handle.lock().await {
+                Some(ref ws) => {
+                    (Some(ws.port()), Some(ws.token().to_string()), ws.serves_viewer())
+                }
+                None => (None, None, false),
+            };
+
             let session = rdp_session.lock().await;
-            let (state, host, width, height) = if let Some(ref rdp) = *session {
+            let (state, host, width, height, keyboard_layout, cert_fingerprint, monitors, last_disconnect, metrics) = if let Some(ref rdp) = *session {
+                let state = if rdp.is_reconnecting() {
+                    ConnectionState::Reconnecting
+                } else {
+                    ConnectionState::Connected
+                };
                 (
-                    ConnectionState::Connected,
+                    state,
                     Some(rdp.host().to_string()),
                     Some(rdp.width()),
                     Some(rdp.height()),
+                    Some(rdp.keyboard_layout()),
+                    Some(rdp.cert_fingerprint()),
+                    rdp.monitors(),
+                    rdp.last_disconnect(),
+                    Some(rdp.metrics()),
                 )
             } else {
-                (ConnectionState::Disconnected, None, None, None)
+                let last_disconnect = last_disconnect.lock().await.clone();
+                (ConnectionState::Disconnected, None, None, None, None, None, Vec::new(), last_disconnect, None)
             };
 
             Response::success(ResponseData::SessionInfo(SessionInfo {
@@ -321,6 +625,18 @@ async fn process_request(
                 host,
                 width,
                 height,
+                keyboard_layout,
+                cert_fingerprint,
+                monitors,
+                last_disconnect,
+                connect_ms: metrics.as_ref().and_then(|m| m.connect_ms()),
+                rtt_ms: metrics.as_ref().and_then(|m| m.rtt_ms()),
+                last_rtt_ms: metrics.as_ref().and_then(|m| m.last_rtt_ms()),
+                frames_received: metrics.as_ref().map(|m| m.frames_received()),
+                bytes_received: metrics.as_ref().map(|m| m.bytes_received()),
+                stream_port,
+                stream_token,
+                stream_viewer,
                 pid: std::process::id(),
                 uptime_secs: start_time.elapsed().as_secs(),
             }))
@@ -332,17 +648,45 @@ async fn process_request(
         }
 
         Request::Connect(params) => {
-            handlers::connect::handle(rdp_session, automation_state, ws_handle, params, disconnect_tx.clone(), clipboard_changed_rx).await
+            let idle_timeout = params.idle_timeout_secs.filter(|&secs| secs > 0);
+            let ctx = handlers::connect::ConnectContext {
+                rdp_session,
+                automation_state,
+                ws_handle,
+                session_name,
+                disconnect_notify: disconnect_tx.clone(),
+                clipboard_changed_rx,
+                credential_provider,
+            };
+            let response = handlers::connect::handle(ctx, *params).await;
+            if response.success {
+                *last_disconnect.lock().await = None;
+                *idle_timeout_secs.lock().await = idle_timeout;
+            }
+            response
         }
 
-        Request::Disconnect => {
-            handlers::connect::handle_disconnect(rdp_session, automation_state, ws_handle).await
+        Request::Disconnect { logoff } => {
+            handlers::connect::handle_disconnect(rdp_session, automation_state, ws_handle, logoff)
+                .await
         }
 
         Request::Screenshot(params) => {
             handlers::screenshot::handle(rdp_session, params).await
         }
 
+        Request::Frame(params) => {
+            handlers::frame::handle(rdp_session, params).await
+        }
+
+        Request::Resize { width, height } => {
+            handlers::resize::handle(rdp_session, width, height).await
+        }
+
+        Request::StreamConfig { fps, quality, delta } => {
+            handlers::stream::handle_config(ws_handle, stream_fps, fps, quality, delta).await
+        }
+
         Request::Mouse(action) => {
             handlers::mouse::handle(rdp_session, action).await
         }
@@ -355,6 +699,10 @@ async fn process_request(
             handlers::scroll::handle(rdp_session, params).await
         }
 
+        Request::InputBatch(params) => {
+            handlers::input::handle(rdp_session, params).await
+        }
+
         Request::Clipboard(action) => {
             handlers::clipboard::handle(rdp_session, action).await
         }
@@ -363,12 +711,28 @@ async fn process_request(
             handlers::drive::handle(rdp_session, action).await
         }
 
-        Request::Automate(action) => {
-            handlers::automate::handle(rdp_session, automation_state, action).await
+        Request::Automate {
+            request,
+            agent_timeout_ms,
+        } => {
+            handlers::automate::handle(rdp_session, automation_state, request, agent_timeout_ms)
+                .await
         }
 
         Request::Locate(params) => {
             handlers::locate::handle(rdp_session, params).await
         }
+
+        Request::LocateClick(params) => {
+            handlers::locate::handle_click(rdp_session, params).await
+        }
+
+        Request::WaitForChange { timeout_ms, region } => {
+            handlers::wait_for_change::handle(rdp_session, timeout_ms, region).await
+        }
+
+        Request::CursorInfo { include_image } => {
+            handlers::cursor::handle(rdp_session, include_image).await
+        }
     }
 }
@@ -1,22 +1,157 @@
 //! Automation handler for Windows UI Automation.
 
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use agent_rdp_protocol::{
     AccessibilityElement, AccessibilitySnapshot, AutomateRequest, AutomationStatus, ClickResult,
-    ElementBounds, ElementValue, ErrorCode, Response, ResponseData, RunResult, WindowInfo,
+    DragResult, ElementBounds, ElementScreenshot, ElementValue, ErrorCode, HighlightResult,
+    ImageFormat, MouseRequest, Response, ResponseData, RunResult, TypeResult, WaitForResult,
+    WaitState, WindowInfo, MAX_HIGHLIGHT_DURATION_MS,
 };
+use base64::Engine;
 use tokio::sync::Mutex;
-use tracing::error;
+use tracing::{debug, error};
 
-use crate::automation::SharedAutomationState;
+use crate::automation::{watchdog, DvcIpc, SharedAutomationState};
+use crate::handlers::{mouse, screenshot};
 use crate::rdp_session::RdpSession;
 
+/// Shortest time between element checks, so waits don't flood the agent.
+const MIN_WAIT_POLL_INTERVAL_MS: u64 = 50;
+
+/// Time to wait for each answer from the agent unless the request overrides it.
+const DEFAULT_AGENT_TIMEOUT_MS: u64 = 10_000;
+
+/// Time a request gets on top of its agent waits, for session locks and mouse input.
+const HANDLER_TIMEOUT_MARGIN_MS: u64 = 5_000;
+
 /// Handle an automation request.
+///
+/// The whole request is bounded, so an agent that stops answering fails it
+/// with an automation error instead of leaving the daemon waiting. If the
+/// agent turns out to be gone or hung, it is restarted and the request is
+/// tried once more.
 pub async fn handle(
     rdp_session: &Arc<Mutex<Option<RdpSession>>>,
     automation_state: &SharedAutomationState,
     request: AutomateRequest,
+    agent_timeout_ms: Option<u64>,
+) -> Response {
+    if agent_timeout_ms == Some(0) {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            "Agent timeout must be greater than 0",
+        );
+    }
+    if let AutomateRequest::Click { button: Some(button), .. } = &request {
+        if !matches!(button.as_str(), "left" | "right" | "middle") {
+            return Response::error(
+                ErrorCode::InvalidRequest,
+                format!("Unknown button '{}': must be left, right or middle", button),
+            );
+        }
+    }
+    if let AutomateRequest::Highlight { duration_ms, .. } = &request {
+        if !(1..=MAX_HIGHLIGHT_DURATION_MS).contains(duration_ms) {
+            return Response::error(
+                ErrorCode::InvalidRequest,
+                format!(
+                    "Highlight duration {} ms is out of range (must be 1-{})",
+                    duration_ms, MAX_HIGHLIGHT_DURATION_MS
+                ),
+            );
+        }
+    }
+    let agent_timeout = Duration::from_millis(
+        agent_timeout_ms.unwrap_or_else(|| default_agent_timeout_ms(&request)),
+    );
+
+    let response =
+        handle_bounded(rdp_session, automation_state, request.clone(), agent_timeout).await;
+    if response.success || !watchdog::agent_failed(automation_state).await {
+        return response;
+    }
+
+    if let Err(e) = watchdog::restart_agent(rdp_session, automation_state).await {
+        error!("Failed to restart automation agent: {}", e);
+        return Response::error(
+            ErrorCode::AutomationError,
+            format!(
+                "Automation agent stopped responding and could not be restarted ({}); \
+                reconnect with --enable-win-automation",
+                e
+            ),
+        );
+    }
+    handle_bounded(rdp_session, automation_state, request, agent_timeout).await
+}
+
+/// Run a request within its overall time limit.
+async fn handle_bounded(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    automation_state: &SharedAutomationState,
+    request: AutomateRequest,
+    agent_timeout: Duration,
+) -> Response {
+    let limit = handler_timeout(&request, agent_timeout);
+
+    match tokio::time::timeout(
+        limit,
+        handle_request(rdp_session, automation_state, request, agent_timeout),
+    )
+    .await
+    {
+        Ok(response) => response,
+        Err(_) => {
+            error!("Automation request timed out after {} ms", limit.as_millis());
+            Response::error(
+                ErrorCode::AutomationError,
+                format!(
+                    "Automation request timed out after {} ms; the agent may have stopped responding",
+                    limit.as_millis()
+                ),
+            )
+        }
+    }
+}
+
+/// Agent timeout for a request that doesn't set one; a waited-for process
+/// gets its own timeout plus headroom.
+fn default_agent_timeout_ms(request: &AutomateRequest) -> u64 {
+    match request {
+        AutomateRequest::Run {
+            wait: true,
+            timeout_ms,
+            ..
+        } => DEFAULT_AGENT_TIMEOUT_MS.max(timeout_ms + HANDLER_TIMEOUT_MARGIN_MS),
+        AutomateRequest::ExecScript { timeout_ms, .. } => {
+            DEFAULT_AGENT_TIMEOUT_MS.max(timeout_ms + HANDLER_TIMEOUT_MARGIN_MS)
+        }
+        _ => DEFAULT_AGENT_TIMEOUT_MS,
+    }
+}
+
+/// Longest a request may take overall, given how many agent answers it waits for.
+fn handler_timeout(request: &AutomateRequest, agent_timeout: Duration) -> Duration {
+    let margin = Duration::from_millis(HANDLER_TIMEOUT_MARGIN_MS);
+    match request {
+        // Both elements are resolved before the drag
+        AutomateRequest::DragTo { .. } => agent_timeout * 2 + margin,
+        // The last check can start just before the wait runs out
+        AutomateRequest::WaitFor { timeout_ms, .. } => {
+            Duration::from_millis(*timeout_ms) + agent_timeout + margin
+        }
+        _ => agent_timeout + margin,
+    }
+}
+
+/// Check the session and agent, then run the request.
+async fn handle_request(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    automation_state: &SharedAutomationState,
+    request: AutomateRequest,
+    agent_timeout: Duration,
 ) -> Response {
     // Check if connected
     {
@@ -54,12 +189,41 @@ pub async fn handle(
     }
 
     // Clone the IPC to release the lock before async operation
-    let ipc = dvc_ipc.clone();
+    let mut ipc = dvc_ipc.clone();
+    let restarts = state.restarts;
     drop(state);
+    ipc.set_timeout(agent_timeout);
+
+    // Drags run as RDP mouse input; the agent only resolves the element bounds
+    if let AutomateRequest::DragTo { selector, target } = &request {
+        return drag_to(rdp_session, &ipc, selector, target).await;
+    }
+
+    // Element images are cropped from the desktop; the agent only resolves the bounds
+    if let AutomateRequest::Screenshot { selector, format } = &request {
+        return screenshot_element(rdp_session, &ipc, selector, *format).await;
+    }
+
+    // Waits poll the agent from here rather than blocking it for the whole wait
+    if let AutomateRequest::WaitFor {
+        selector,
+        timeout_ms,
+        state,
+        poll_interval_ms,
+    } = &request
+    {
+        return wait_for(rdp_session, &ipc, selector, *state, *timeout_ms, *poll_interval_ms).await;
+    }
 
     // Send request to PowerShell agent via DVC
     match ipc.send_request(&request).await {
-        Ok(data) => convert_response(request, data),
+        Ok(data) => {
+            let mut response = convert_response(request, data);
+            if let Some(ResponseData::AutomationStatus(status)) = response.data.as_mut() {
+                status.restarts = restarts;
+            }
+            response
+        }
         Err(e) => {
             error!("Automation request failed: {}", e);
             Response::error(ErrorCode::AutomationError, e.to_string())
@@ -67,12 +231,316 @@ pub async fn handle(
     }
 }
 
+/// Drag one element onto another with a mouse drag between their centers.
+async fn drag_to(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    ipc: &DvcIpc,
+    selector: &str,
+    target: &str,
+) -> Response {
+    let from = match element_center(ipc, selector).await {
+        Ok(center) => center,
+        Err(response) => return response,
+    };
+    let to = match element_center(ipc, target).await {
+        Ok(center) => center,
+        Err(response) => return response,
+    };
+
+    // UI Automation uses screen coordinates with the primary monitor at the origin
+    let (origin_x, origin_y) = {
+        let session = rdp_session.lock().await;
+        session
+            .as_ref()
+            .and_then(|rdp| rdp.monitors().into_iter().find(|m| m.primary))
+            .map(|m| (m.x, m.y))
+            .unwrap_or((0, 0))
+    };
+    let (from_x, from_y) = (from.0 + origin_x, from.1 + origin_y);
+    let (to_x, to_y) = (to.0 + origin_x, to.1 + origin_y);
+    debug!(
+        "Dragging {} ({}, {}) to {} ({}, {})",
+        selector, from_x, from_y, target, to_x, to_y
+    );
+
+    let coordinates = (
+        u16::try_from(from_x),
+        u16::try_from(from_y),
+        u16::try_from(to_x),
+        u16::try_from(to_y),
+    );
+    let (Ok(mouse_from_x), Ok(mouse_from_y), Ok(mouse_to_x), Ok(mouse_to_y)) = coordinates else {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            format!(
+                "Drag from ({}, {}) to ({}, {}) is outside the desktop",
+                from_x, from_y, to_x, to_y
+            ),
+        );
+    };
+
+    let response = mouse::handle_device(
+        rdp_session,
+        MouseRequest::Drag {
+            from_x: mouse_from_x,
+            from_y: mouse_from_y,
+            to_x: mouse_to_x,
+            to_y: mouse_to_y,
+            steps: None,
+        },
+    )
+    .await;
+    if !response.success {
+        return response;
+    }
+
+    Response::success(ResponseData::DragResult(DragResult {
+        dragged: true,
+        from_x,
+        from_y,
+        to_x,
+        to_y,
+    }))
+}
+
+/// Capture an element by cropping the desktop image to its bounds.
+///
+/// Bounds reaching past the desktop edges are clamped, so an element partly
+/// off screen yields its visible part.
+async fn screenshot_element(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    ipc: &DvcIpc,
+    selector: &str,
+    format: ImageFormat,
+) -> Response {
+    let bounds = match element_bounds(ipc, selector).await {
+        Ok(bounds) => bounds,
+        Err(response) => return response,
+    };
+
+    let session = rdp_session.lock().await;
+    let Some(rdp) = session.as_ref() else {
+        return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+    };
+    // UI Automation uses screen coordinates with the primary monitor at the origin
+    let (origin_x, origin_y) = rdp
+        .monitors()
+        .into_iter()
+        .find(|m| m.primary)
+        .map(|m| (m.x, m.y))
+        .unwrap_or((0, 0));
+    let (width, height, data) = rdp.get_image_data();
+    drop(session);
+
+    let left = bounds.x.saturating_add(origin_x);
+    let top = bounds.y.saturating_add(origin_y);
+    let right = left.saturating_add(bounds.width).clamp(0, i32::from(width));
+    let bottom = top.saturating_add(bounds.height).clamp(0, i32::from(height));
+    let (left, top) = (left.clamp(0, i32::from(width)), top.clamp(0, i32::from(height)));
+    if right <= left || bottom <= top {
+        return Response::error(
+            ErrorCode::ElementNotFound,
+            format!("Element '{}' is outside the desktop", selector),
+        );
+    }
+    let bounds = ElementBounds {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    };
+    debug!("Capturing {} at {:?}", selector, bounds);
+
+    let Some(desktop) = image::RgbaImage::from_raw(u32::from(width), u32::from(height), data) else {
+        return Response::error(
+            ErrorCode::InternalError,
+            "Failed to create image from decoded data",
+        );
+    };
+    let element = image::imageops::crop_imm(
+        &desktop,
+        bounds.x as u32,
+        bounds.y as u32,
+        bounds.width as u32,
+        bounds.height as u32,
+    )
+    .to_image();
+
+    let data = match screenshot::encode_image(element, format) {
+        Ok(data) => data,
+        Err(msg) => return Response::error(ErrorCode::InternalError, msg),
+    };
+    Response::success(ResponseData::ElementScreenshot(ElementScreenshot {
+        bounds,
+        format: screenshot::format_name(format).to_string(),
+        size_bytes: data.len() as u64,
+        base64: base64::engine::general_purpose::STANDARD.encode(data),
+    }))
+}
+
+/// Element state seen by one wait check.
+#[derive(Debug, Default, Clone, Copy)]
+struct ElementCheck {
+    exists: bool,
+    visible: bool,
+    enabled: bool,
+}
+
+impl ElementCheck {
+    fn satisfies(&self, state: WaitState) -> bool {
+        match state {
+            WaitState::Visible => self.visible,
+            WaitState::Enabled => self.enabled,
+            WaitState::Gone => !self.exists,
+            WaitState::Exists => self.exists,
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match (self.exists, self.visible, self.enabled) {
+            (false, _, _) => "not found",
+            (true, true, true) => "visible and enabled",
+            (true, true, false) => "visible but disabled",
+            (true, false, true) => "offscreen",
+            (true, false, false) => "offscreen and disabled",
+        }
+    }
+}
+
+/// Poll an element until it reaches a state, the timeout passes or the session
+/// disconnects.
+///
+/// Each check is a short request to the agent, so other automation requests
+/// aren't queued behind the wait, and the session is only locked to see whether
+/// it's still connected.
+async fn wait_for(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    ipc: &DvcIpc,
+    selector: &str,
+    state: WaitState,
+    timeout_ms: u64,
+    poll_interval_ms: u64,
+) -> Response {
+    let start = Instant::now();
+    let timeout = Duration::from_millis(timeout_ms);
+    let poll_interval = Duration::from_millis(poll_interval_ms.max(MIN_WAIT_POLL_INTERVAL_MS));
+
+    loop {
+        let check = match check_element(ipc, selector).await {
+            Ok(check) => check,
+            Err(response) => return response,
+        };
+        let elapsed = start.elapsed();
+
+        if check.satisfies(state) {
+            return Response::success(ResponseData::WaitForResult(WaitForResult {
+                state,
+                elapsed_ms: elapsed.as_millis() as u64,
+                exists: check.exists,
+                visible: check.visible,
+                enabled: check.enabled,
+            }));
+        }
+        if elapsed >= timeout {
+            return Response::error(
+                ErrorCode::Timeout,
+                format!(
+                    "Timed out after {} ms waiting for '{}' to be {} (last seen: {})",
+                    elapsed.as_millis(),
+                    selector,
+                    wait_state_name(state),
+                    check.describe()
+                ),
+            );
+        }
+
+        tokio::time::sleep(poll_interval.min(timeout - elapsed)).await;
+
+        // Disconnecting cancels the wait
+        if rdp_session.lock().await.is_none() {
+            return Response::error(
+                ErrorCode::NotConnected,
+                format!("Disconnected while waiting for '{}'", selector),
+            );
+        }
+    }
+}
+
+/// Describe a wait state for messages.
+fn wait_state_name(state: WaitState) -> &'static str {
+    match state {
+        WaitState::Visible => "visible",
+        WaitState::Enabled => "enabled",
+        WaitState::Gone => "gone",
+        WaitState::Exists => "present",
+    }
+}
+
+/// Check whether an element exists, is on screen and is enabled.
+async fn check_element(ipc: &DvcIpc, selector: &str) -> Result<ElementCheck, Response> {
+    let request = AutomateRequest::Get {
+        selector: selector.to_string(),
+        property: Some("states".to_string()),
+    };
+    match ipc.send_request(&request).await {
+        Ok(data) => {
+            let element = parse_element_response(data)
+                .map_err(|e| Response::error(ErrorCode::AutomationError, e.to_string()))?;
+            let has_state = |name: &str| element.states.iter().any(|state| state == name);
+            Ok(ElementCheck {
+                exists: true,
+                visible: !has_state("offscreen"),
+                enabled: has_state("enabled"),
+            })
+        }
+        Err(e) if e.to_string().contains("Element not found") => Ok(ElementCheck::default()),
+        Err(e) => Err(Response::error(ErrorCode::AutomationError, e.to_string())),
+    }
+}
+
+/// Resolve an element's center in screen coordinates via the agent.
+async fn element_center(ipc: &DvcIpc, selector: &str) -> Result<(i32, i32), Response> {
+    let bounds = element_bounds(ipc, selector).await?;
+    Ok((bounds.x + bounds.width / 2, bounds.y + bounds.height / 2))
+}
+
+/// Resolve an element's on-screen bounds in screen coordinates via the agent.
+async fn element_bounds(ipc: &DvcIpc, selector: &str) -> Result<ElementBounds, Response> {
+    let request = AutomateRequest::Get {
+        selector: selector.to_string(),
+        property: Some("bounds".to_string()),
+    };
+    let data = ipc.send_request(&request).await.map_err(|e| {
+        let message = e.to_string();
+        let code = if message.contains("Element not found") {
+            ErrorCode::ElementNotFound
+        } else {
+            ErrorCode::AutomationError
+        };
+        Response::error(code, message)
+    })?;
+
+    let element = parse_element_response(data)
+        .map_err(|e| Response::error(ErrorCode::AutomationError, e.to_string()))?;
+    match element.bounds {
+        Some(bounds) if bounds.width > 0 && bounds.height > 0 => Ok(bounds),
+        _ => Err(Response::error(
+            ErrorCode::ElementNotFound,
+            format!("Element '{}' is not visible on screen", selector),
+        )),
+    }
+}
+
 /// Convert the JSON response from PowerShell agent to protocol response.
 fn convert_response(request: AutomateRequest, data: serde_json::Value) -> Response {
     match request {
-        AutomateRequest::Snapshot { .. } => {
+        AutomateRequest::Snapshot { format, .. } => {
+            // Rendered here so YAML and text answers carry no JSON tree
             match parse_snapshot_response(data) {
-                Ok(snapshot) => Response::success(ResponseData::Snapshot(snapshot)),
+                Ok(snapshot) => match snapshot.format(format) {
+                    Some(formatted) => Response::success(ResponseData::FormattedSnapshot(formatted)),
+                    None => Response::success(ResponseData::Snapshot(snapshot)),
+                },
                 Err(e) => {
                     error!("Failed to parse snapshot response: {}", e);
                     Response::error(ErrorCode::AutomationError, e.to_string())
@@ -121,6 +589,14 @@ fn convert_response(request: AutomateRequest, data: serde_json::Value) -> Respon
             }
         }
 
+        AutomateRequest::ExecScript { .. } => match parse_run_response(data) {
+            Ok(result) => Response::success(ResponseData::RunResult(result)),
+            Err(e) => {
+                error!("Failed to parse exec-script response: {}", e);
+                Response::error(ErrorCode::AutomationError, e.to_string())
+            }
+        },
+
         AutomateRequest::Status => {
             match parse_status_response(data) {
                 Ok(status) => Response::success(ResponseData::AutomationStatus(status)),
@@ -141,6 +617,26 @@ fn convert_response(request: AutomateRequest, data: serde_json::Value) -> Respon
             }
         }
 
+        AutomateRequest::Highlight { .. } => {
+            match parse_highlight_response(data) {
+                Ok(result) => Response::success(ResponseData::HighlightResult(result)),
+                Err(e) => {
+                    error!("Failed to parse highlight response: {}", e);
+                    Response::error(ErrorCode::AutomationError, e.to_string())
+                }
+            }
+        }
+
+        AutomateRequest::Type { .. } => {
+            match parse_type_response(data) {
+                Ok(result) => Response::success(ResponseData::TypeResult(result)),
+                Err(e) => {
+                    error!("Failed to parse type response: {}", e);
+                    Response::error(ErrorCode::AutomationError, e.to_string())
+                }
+            }
+        }
+
         // All other actions return simple Ok
         _ => Response::ok(),
     }
@@ -171,6 +667,7 @@ fn parse_snapshot_response(data: serde_json::Value) -> anyhow::Result<Accessibil
 /// Parse a single element from the accessibility tree.
 fn parse_element(data: &serde_json::Value) -> anyhow::Result<AccessibilityElement> {
     let r#ref = data["ref"].as_u64().map(|v| v as u32);
+    let stable_id = data["stable_id"].as_str().map(|s| s.to_string());
     let role = data["role"].as_str().unwrap_or("unknown").to_string();
     let name = data["name"].as_str().map(|s| s.to_string());
     let automation_id = data["automation_id"].as_str().map(|s| s.to_string());
@@ -217,6 +714,7 @@ fn parse_element(data: &serde_json::Value) -> anyhow::Result<AccessibilityElemen
 
     Ok(AccessibilityElement {
         r#ref,
+        stable_id,
         role,
         name,
         automation_id,
@@ -309,12 +807,14 @@ fn parse_run_response(data: serde_json::Value) -> anyhow::Result<RunResult> {
     let stdout = data["stdout"].as_str().map(|s| s.to_string());
     let stderr = data["stderr"].as_str().map(|s| s.to_string());
     let pid = data["pid"].as_u64().map(|v| v as u32);
+    let truncated = data["truncated"].as_bool().unwrap_or(false);
 
     Ok(RunResult {
         exit_code,
         stdout,
         stderr,
         pid,
+        truncated,
     })
 }
 
@@ -323,6 +823,8 @@ fn parse_status_response(data: serde_json::Value) -> anyhow::Result<AutomationSt
     let agent_running = data["agent_running"].as_bool().unwrap_or(false);
     let agent_pid = data["agent_pid"].as_u64().map(|v| v as u32);
     let version = data["version"].as_str().map(|s| s.to_string());
+    let secure_desktop = data["secure_desktop"].as_bool().unwrap_or(false);
+    let scroll_lock = data["scroll_lock"].as_bool().unwrap_or(false);
 
     let capabilities = data["capabilities"]
         .as_array()
@@ -338,6 +840,10 @@ fn parse_status_response(data: serde_json::Value) -> anyhow::Result<AutomationSt
         agent_pid,
         capabilities,
         version,
+        // Filled in by the daemon, which does the restarts
+        restarts: 0,
+        secure_desktop,
+        scroll_lock,
     })
 }
 
@@ -364,3 +870,33 @@ fn parse_click_response(data: serde_json::Value) -> anyhow::Result<ClickResult>
         y,
     })
 }
+
+/// Parse highlight response from PowerShell agent.
+fn parse_highlight_response(data: serde_json::Value) -> anyhow::Result<HighlightResult> {
+    let bounds = &data["bounds"];
+    if !bounds.is_object() {
+        anyhow::bail!("Highlight response has no bounds");
+    }
+    Ok(HighlightResult {
+        bounds: ElementBounds {
+            x: bounds["x"].as_i64().unwrap_or(0) as i32,
+            y: bounds["y"].as_i64().unwrap_or(0) as i32,
+            width: bounds["width"].as_i64().unwrap_or(0) as i32,
+            height: bounds["height"].as_i64().unwrap_or(0) as i32,
+        },
+        duration_ms: data["duration_ms"].as_u64().unwrap_or(0),
+    })
+}
+
+/// Parse type response from PowerShell agent.
+fn parse_type_response(data: serde_json::Value) -> anyhow::Result<TypeResult> {
+    let typed = data["typed"].as_bool().unwrap_or(false);
+    let method = data["method"].as_str().unwrap_or("unknown").to_string();
+    let value = data["value"].as_str().map(|s| s.to_string());
+
+    Ok(TypeResult {
+        typed,
+        method,
+        value,
+    })
+}
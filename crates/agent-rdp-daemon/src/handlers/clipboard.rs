@@ -2,11 +2,16 @@
 //!
 //! Uses CLIPRDR to sync clipboard with remote Windows machine.
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use agent_rdp_protocol::{ClipboardRequest, ErrorCode, Response, ResponseData};
+use agent_rdp_protocol::{
+    ClipboardHistoryEntry, ClipboardRequest, ErrorCode, LineEndings, Response, ResponseData,
+};
+use base64::Engine;
 use tokio::sync::Mutex;
 
+use crate::rdp_session::clipboard::{self, files};
 use crate::rdp_session::RdpSession;
 
 /// Handle a clipboard request using the RDP session's CLIPRDR integration.
@@ -21,19 +26,137 @@ pub async fn handle(
     };
 
     match action {
-        ClipboardRequest::Get => {
+        ClipboardRequest::Get { line_endings } => {
+            let line_endings = line_endings.unwrap_or(LineEndings::Lf);
             match rdp.clipboard_get().await {
-                Ok(Some(text)) => Response::success(ResponseData::Clipboard { text }),
+                Ok(Some(text)) => Response::success(ResponseData::Clipboard {
+                    text: line_endings.apply(&text),
+                }),
                 Ok(None) => Response::success(ResponseData::Clipboard { text: String::new() }),
                 Err(e) => Response::error(ErrorCode::ClipboardError, format!("Failed to get clipboard: {}", e)),
             }
         }
 
-        ClipboardRequest::Set { text } => {
+        ClipboardRequest::Set { text, line_endings } => {
+            let text = line_endings.unwrap_or(LineEndings::Crlf).apply(&text);
             match rdp.clipboard_set(text).await {
                 Ok(()) => Response::ok(),
                 Err(e) => Response::error(ErrorCode::ClipboardError, format!("Failed to set clipboard: {}", e)),
             }
         }
+
+        ClipboardRequest::GetImage => {
+            match rdp.clipboard_get_image().await {
+                Ok(Some(dib)) => match clipboard::dib_to_png(&dib) {
+                    Ok((width, height, png)) => Response::success(ResponseData::ClipboardImage {
+                        width,
+                        height,
+                        base64: base64::engine::general_purpose::STANDARD.encode(png),
+                    }),
+                    Err(e) => Response::error(ErrorCode::ClipboardError, format!("Failed to decode clipboard image: {}", e)),
+                },
+                Ok(None) => Response::error(ErrorCode::ClipboardError, "Clipboard does not contain an image"),
+                Err(e) => Response::error(ErrorCode::ClipboardError, format!("Failed to get clipboard image: {}", e)),
+            }
+        }
+
+        ClipboardRequest::SetImage { base64 } => {
+            let png = match base64::engine::general_purpose::STANDARD.decode(&base64) {
+                Ok(png) => png,
+                Err(e) => return Response::error(ErrorCode::InvalidRequest, format!("Invalid base64 image data: {}", e)),
+            };
+            let dib = match clipboard::png_to_dib(&png) {
+                Ok(dib) => dib,
+                Err(e) => return Response::error(ErrorCode::InvalidRequest, e),
+            };
+            match rdp.clipboard_set_image(dib).await {
+                Ok(()) => Response::ok(),
+                Err(e) => Response::error(ErrorCode::ClipboardError, format!("Failed to set clipboard image: {}", e)),
+            }
+        }
+
+        ClipboardRequest::GetHtml => {
+            match rdp.clipboard_get_html().await {
+                Ok(Some(html)) => Response::success(ResponseData::ClipboardHtml { html }),
+                Ok(None) => Response::error(ErrorCode::ClipboardError, "Clipboard does not contain HTML"),
+                Err(e) => Response::error(ErrorCode::ClipboardError, format!("Failed to get clipboard HTML: {}", e)),
+            }
+        }
+
+        ClipboardRequest::SetHtml { html, text } => {
+            let text = text.unwrap_or_else(|| clipboard::html::html_to_text(&html));
+            match rdp.clipboard_set_html(html, text).await {
+                Ok(()) => Response::ok(),
+                Err(e) => Response::error(ErrorCode::ClipboardError, format!("Failed to set clipboard HTML: {}", e)),
+            }
+        }
+
+        ClipboardRequest::GetFiles { dest_dir, max_bytes } => {
+            let dest_dir = PathBuf::from(dest_dir);
+            if let Err(e) = std::fs::create_dir_all(&dest_dir) {
+                return Response::error(
+                    ErrorCode::InvalidRequest,
+                    format!("Cannot create directory {:?}: {}", dest_dir, e),
+                );
+            }
+            let max_bytes = max_bytes.unwrap_or(files::DEFAULT_MAX_TRANSFER_BYTES);
+            match rdp.clipboard_get_files(dest_dir, max_bytes).await {
+                Ok(Some(received)) => Response::success(ResponseData::ClipboardFiles {
+                    files: received
+                        .files
+                        .iter()
+                        .map(|path| path.to_string_lossy().to_string())
+                        .collect(),
+                    total_bytes: received.total_bytes,
+                }),
+                Ok(None) => Response::error(ErrorCode::ClipboardError, "Clipboard does not contain files"),
+                Err(e) => Response::error(ErrorCode::ClipboardError, format!("Failed to get clipboard files: {}", e)),
+            }
+        }
+
+        ClipboardRequest::SetFiles { paths, max_bytes } => {
+            if paths.is_empty() {
+                return Response::error(ErrorCode::InvalidRequest, "No files to copy");
+            }
+            let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+            let max_bytes = max_bytes.unwrap_or(files::DEFAULT_MAX_TRANSFER_BYTES);
+            let local_files = match files::LocalFiles::collect(&paths, max_bytes) {
+                Ok(local_files) => local_files,
+                Err(e) => return Response::error(ErrorCode::InvalidRequest, e),
+            };
+            let names = local_files.file_names();
+            let total_bytes = local_files.total_bytes();
+            match rdp.clipboard_set_files(local_files).await {
+                Ok(()) => Response::success(ResponseData::ClipboardFiles { files: names, total_bytes }),
+                Err(e) => Response::error(ErrorCode::ClipboardError, format!("Failed to set clipboard files: {}", e)),
+            }
+        }
+
+        ClipboardRequest::History { count } => {
+            let entries = rdp
+                .clipboard_history(count.map(|count| count as usize))
+                .into_iter()
+                .map(|entry| ClipboardHistoryEntry {
+                    text: entry.text,
+                    copied_at_ms: entry.copied_at_ms,
+                })
+                .collect();
+            Response::success(ResponseData::ClipboardHistory { entries })
+        }
+
+        ClipboardRequest::Clear => match rdp.clipboard_clear().await {
+            Ok(()) => Response::ok(),
+            Err(e) => Response::error(ErrorCode::ClipboardError, format!("Failed to clear clipboard: {}", e)),
+        },
+
+        ClipboardRequest::Status => {
+            let content = rdp.clipboard_status();
+            Response::success(ResponseData::ClipboardStatus {
+                text: content.text,
+                image: content.image,
+                html: content.html,
+                files: content.files,
+            })
+        }
     }
 }
@@ -1,30 +1,72 @@
 //! Connection handler.
 
 use std::sync::Arc;
+use std::time::Duration;
 
-use agent_rdp_protocol::{ConnectRequest, ErrorCode, Response, ResponseData};
+use agent_rdp_protocol::{
+    AutomateRequest, AutomationStatus, ConnectRequest, DriveMapping, ErrorCode, InputCheck,
+    InputCheckStatus, Response, ResponseData, FIRST_FRAME_TIMEOUT_MS,
+};
+use ironrdp::pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
 use tokio::sync::Mutex;
 use tracing::{info, warn};
 
-use crate::automation::{AutomationBootstrap, SharedAutomationState};
+use crate::automation::{AutomationBootstrap, SharedAutomationState, SharedDvcState};
 use crate::daemon::{ClipboardChangedRx, SharedWsHandle};
-use crate::rdp_session::{DisconnectNotify, RdpConfig, RdpSession};
-use crate::ws_server::{WsServer, WsServerConfig};
+use crate::rdp_session::audio::{AudioCapture, SharedAudioCapture};
+use crate::rdp_session::clipboard::history::{DEFAULT_HISTORY_DEPTH, MAX_HISTORY_DEPTH};
+use crate::rdp_session::credentials::{SharedCredentialProvider, StaticCredentialProvider};
+use crate::rdp_session::gateway::GatewayConfig;
+use crate::rdp_session::identity::ClientIdentity;
+use crate::rdp_session::kerberos;
+use crate::rdp_session::monitors;
+use crate::rdp_session::proxy::ProxyConfig;
+use crate::rdp_session::recording::{self, DEFAULT_RECORD_FPS, MAX_RECORD_FPS};
+use crate::rdp_session::{
+    parse_fingerprint, parse_target, performance_flags, tls_protocol_versions, CertCheck,
+    DisconnectNotify, Password, RdpConfig, RdpSession,
+};
+use crate::ws_server::{
+    get_stream_bind, get_stream_delta, get_stream_token, WsServer, WsServerConfig,
+};
+
+/// Scancode of the key the input self-check toggles.
+const SCROLL_LOCK_SCANCODE: u8 = 0x46;
+
+/// Time for a keypress to reach the remote session before the check reads it back.
+const INPUT_CHECK_DELAY_MS: u64 = 500;
+
+/// Daemon state a connect request sets up.
+pub struct ConnectContext<'a> {
+    pub rdp_session: &'a Arc<Mutex<Option<RdpSession>>>,
+    pub automation_state: &'a SharedAutomationState,
+    pub ws_handle: &'a SharedWsHandle,
+    pub session_name: &'a str,
+    pub disconnect_notify: DisconnectNotify,
+    pub clipboard_changed_rx: &'a ClipboardChangedRx,
+    /// Injected provider that takes the place of the request's username and password.
+    pub credential_provider: Option<&'a SharedCredentialProvider>,
+}
 
 /// Handle a connect request.
-pub async fn handle(
-    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
-    automation_state: &SharedAutomationState,
-    ws_handle: &SharedWsHandle,
-    params: ConnectRequest,
-    disconnect_notify: DisconnectNotify,
-    clipboard_changed_rx: &ClipboardChangedRx,
-) -> Response {
+pub async fn handle(ctx: ConnectContext<'_>, params: ConnectRequest) -> Response {
+    let ConnectContext {
+        rdp_session,
+        automation_state,
+        ws_handle,
+        session_name,
+        disconnect_notify,
+        clipboard_changed_rx,
+        credential_provider,
+    } = ctx;
     let enable_automation = params.enable_win_automation;
     let stream_port = params.stream_port;
     let stream_fps = params.stream_fps;
     let stream_quality = params.stream_quality;
+    let stream_max_frame_size = params.stream_max_frame_size;
     let serve_viewer = params.serve_viewer;
+    let test_input = params.test_input;
+    let wait_first_frame = params.wait_first_frame;
 
     // Auto-disconnect if already connected (handles stale/dropped connections)
     {
@@ -87,31 +129,90 @@ pub async fn handle(
         None
     };
 
-    // Build configuration
-    let config = RdpConfig {
-        host: params.host.clone(),
-        port: params.port,
-        username: params.username,
-        password: params.password,
-        domain: params.domain,
-        width: params.width,
-        height: params.height,
-        drives,
-        automation_dvc_state,
+    if params.capture_audio.is_some() && !params.enable_audio {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            "Audio capture needs the audio channel, which is disabled",
+        );
+    }
+
+    // Open the audio capture file up front so a bad path fails the connect
+    let audio_capture = match params.capture_audio.as_deref() {
+        Some(path) => {
+            let path = crate::get_session_dir(session_name).join(path);
+            match AudioCapture::create(&path) {
+                Ok(capture) => Some(Arc::new(parking_lot::Mutex::new(capture))),
+                Err(e) => {
+                    return Response::error(
+                        ErrorCode::InvalidRequest,
+                        format!("Failed to create audio capture file {:?}: {}", path, e),
+                    );
+                }
+            }
+        }
+        None => None,
     };
 
-    // Attempt connection
-    let rdp = match RdpSession::connect(config, Some(disconnect_notify)).await {
-        Ok(rdp) => rdp,
-        Err(e) => {
-            let code = match &e {
-                crate::rdp_session::RdpError::AuthenticationFailed => ErrorCode::AuthenticationFailed,
-                _ => ErrorCode::ConnectionFailed,
-            };
-            return Response::error(code, e.to_string());
+    // Likewise the recording file
+    let record_fps = params.record_fps.unwrap_or(DEFAULT_RECORD_FPS);
+    if record_fps == 0 || record_fps > MAX_RECORD_FPS {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            format!("Recording frame rate must be between 1 and {}", MAX_RECORD_FPS),
+        );
+    }
+    let recording_file = match params.record.as_deref() {
+        Some(path) => {
+            let path = crate::get_session_dir(session_name).join(path);
+            match recording::create_file(&path) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    return Response::error(
+                        ErrorCode::InvalidRequest,
+                        format!("Failed to create recording file {:?}: {}", path, e),
+                    );
+                }
+            }
+        }
+        None => None,
+    };
+
+    let connect_retries = params.connect_retries;
+    let retry_delays: Vec<u64> =
+        (1..=connect_retries).map(|retry| params.retry_delay_ms(retry)).collect();
+    let credentials = credential_provider.cloned();
+    let config = match build_config(session_name, params, credentials, drives, automation_dvc_state, audio_capture) {
+        Ok(config) => config,
+        Err(msg) => return Response::error(ErrorCode::InvalidRequest, msg),
+    };
+
+    // Attempt connection, retrying while the server isn't accepting connections yet
+    let attempts = connect_retries + 1;
+    let mut attempt = 1;
+    let mut rdp = loop {
+        info!("Connecting to {} (attempt {}/{})", config.host, attempt, attempts);
+        match RdpSession::connect(config.clone(), Some(disconnect_notify.clone())).await {
+            Ok(rdp) => break rdp,
+            Err(e) if e.is_transient() && attempt < attempts => {
+                let delay = Duration::from_millis(retry_delays[attempt as usize - 1]);
+                warn!("Connection attempt {} failed: {}; retrying in {:?}", attempt, e, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) if attempt > 1 => {
+                return Response::error(
+                    e.connect_error_code(),
+                    format!("{} (after {} attempts)", e, attempt),
+                );
+            }
+            Err(e) => return Response::error(e.connect_error_code(), e.to_string()),
         }
     };
 
+    if let Some(file) = recording_file {
+        rdp.start_recording(file, record_fps);
+    }
+
     let host = rdp.host();
     let width = rdp.width();
     let height = rdp.height();
@@ -130,9 +231,13 @@ pub async fn handle(
         if ws.is_none() {
             let config = WsServerConfig {
                 port: stream_port,
+                bind_addr: get_stream_bind(),
+                token: get_stream_token(),
                 fps: stream_fps,
                 jpeg_quality: stream_quality,
                 serve_viewer,
+                delta: get_stream_delta(),
+                max_frame_size: stream_max_frame_size,
             };
             let ws_server = WsServer::new(config);
             match ws_server.start(Arc::clone(rdp_session)).await {
@@ -186,19 +291,162 @@ pub async fn handle(
         }
     }
 
+    let input_check = if test_input {
+        let check = check_input(rdp_session, automation_state).await;
+        info!("Input check: {:?} ({})", check.status, check.message);
+        Some(check)
+    } else {
+        None
+    };
+
+    // Frames keep arriving while the agent starts, so wait for the first one last
+    let first_frame_received = if wait_first_frame {
+        let received = wait_for_first_frame(rdp_session).await;
+        if !received {
+            warn!("No graphics update within {} ms of connecting", FIRST_FRAME_TIMEOUT_MS);
+        }
+        Some(received)
+    } else {
+        None
+    };
+
     Response::success(ResponseData::Connected {
         host,
         width,
         height,
+        input_check,
+        first_frame_received,
     })
 }
 
+/// Wait for the server's first graphics update, so the desktop image is no
+/// longer blank.
+async fn wait_for_first_frame(rdp_session: &Arc<Mutex<Option<RdpSession>>>) -> bool {
+    // Subscribe under the lock, then wait without holding it
+    let mut first_frame = {
+        let session = rdp_session.lock().await;
+        match session.as_ref() {
+            Some(rdp) => rdp.subscribe_first_frame(),
+            None => return false,
+        }
+    };
+    let timeout = Duration::from_millis(FIRST_FRAME_TIMEOUT_MS);
+    let drawn = matches!(
+        tokio::time::timeout(timeout, first_frame.wait_for(|received| *received)).await,
+        Ok(Ok(_))
+    );
+    drawn
+}
+
+/// Check that keyboard input reaches the remote desktop.
+///
+/// Presses Scroll Lock, asks the automation agent whether it changed, then
+/// presses it again to put it back. Without the agent the result can't be
+/// read back, so the check is reported as unverified.
+async fn check_input(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    automation_state: &SharedAutomationState,
+) -> InputCheck {
+    let unverified = |reason: String| InputCheck {
+        status: InputCheckStatus::Unverified,
+        message: format!("Input was not verified: {}", reason),
+    };
+    let secure_desktop = || InputCheck {
+        status: InputCheckStatus::SecureDesktop,
+        message: "Input goes to a secure desktop (a UAC prompt, the lock or logon screen), \
+            which ignores synthesized input"
+            .to_string(),
+    };
+
+    if !automation_state.lock().await.enabled {
+        return unverified("connect with --enable-win-automation to check it".to_string());
+    }
+    let before = match agent_status(rdp_session, automation_state).await {
+        Ok(status) => status,
+        Err(e) => return unverified(e),
+    };
+    if before.secure_desktop {
+        return secure_desktop();
+    }
+
+    if let Err(e) = press_scroll_lock(rdp_session).await {
+        return unverified(e);
+    }
+    tokio::time::sleep(Duration::from_millis(INPUT_CHECK_DELAY_MS)).await;
+    let after = agent_status(rdp_session, automation_state).await;
+    if let Err(e) = press_scroll_lock(rdp_session).await {
+        warn!("Failed to restore Scroll Lock after the input check: {}", e);
+    }
+
+    match after {
+        Err(e) => unverified(e),
+        Ok(after) if after.secure_desktop => secure_desktop(),
+        Ok(after) if after.scroll_lock != before.scroll_lock => InputCheck {
+            status: InputCheckStatus::Working,
+            message: "Keyboard input reaches the remote desktop".to_string(),
+        },
+        Ok(_) => InputCheck {
+            status: InputCheckStatus::NotReaching,
+            message: "Scroll Lock didn't change when pressed; keyboard input is not reaching \
+                the remote desktop"
+                .to_string(),
+        },
+    }
+}
+
+/// Ask the automation agent for its status.
+async fn agent_status(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    automation_state: &SharedAutomationState,
+) -> Result<AutomationStatus, String> {
+    let response =
+        super::automate::handle(rdp_session, automation_state, AutomateRequest::Status, None).await;
+    match (response.data, response.error) {
+        (Some(ResponseData::AutomationStatus(status)), _) => Ok(status),
+        (_, Some(error)) => Err(error.message),
+        _ => Err("unexpected response from the automation agent".to_string()),
+    }
+}
+
+/// Press and release Scroll Lock on the remote desktop.
+async fn press_scroll_lock(rdp_session: &Arc<Mutex<Option<RdpSession>>>) -> Result<(), String> {
+    let session = rdp_session.lock().await;
+    let rdp = session.as_ref().ok_or("Not connected to an RDP server")?;
+    rdp.send_input(vec![
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), SCROLL_LOCK_SCANCODE),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, SCROLL_LOCK_SCANCODE),
+    ])
+    .await
+    .map_err(|e| e.to_string())
+}
+
 /// Handle a disconnect request.
+///
+/// A plain disconnect leaves the Windows session logged on, so programs keep
+/// running and a later connect picks it up again. With `logoff`, the user is
+/// logged off first: RDP has no client logoff PDU, so this runs `logoff.exe`
+/// through the automation agent and fails (staying connected) without one.
 pub async fn handle_disconnect(
     rdp_session: &Arc<Mutex<Option<RdpSession>>>,
     automation_state: &SharedAutomationState,
     ws_handle: &SharedWsHandle,
+    logoff: bool,
 ) -> Response {
+    if logoff {
+        let request = AutomateRequest::Run {
+            command: "logoff.exe".to_string(),
+            args: Vec::new(),
+            wait: false,
+            hidden: true,
+            timeout_ms: 0,
+        };
+        let response = super::automate::handle(rdp_session, automation_state, request, None).await;
+        if let Some(error) = response.error {
+            return Response::error(error.code, format!("Could not log off: {}", error.message));
+        }
+        info!("Logging off the remote user");
+    }
+
     // Stop WebSocket server if running
     {
         let mut ws = ws_handle.lock().await;
@@ -234,3 +482,142 @@ pub async fn handle_disconnect(
         }
     }
 }
+
+/// Check that a server is reachable and accepts the credentials, then disconnect.
+///
+/// Runs without a daemon: nothing is kept once the handshake completes.
+pub async fn dry_run(session_name: &str, params: ConnectRequest) -> Response {
+    let config = match build_config(session_name, params, None, Vec::new(), None, None) {
+        Ok(config) => config,
+        Err(msg) => return Response::error(ErrorCode::InvalidRequest, msg),
+    };
+    Response::success(ResponseData::DryRun(RdpSession::dry_run(&config).await))
+}
+
+/// Build the session configuration from a connect request.
+///
+/// Without a credential provider, the request's username and password are used.
+fn build_config(
+    session_name: &str,
+    params: ConnectRequest,
+    credentials: Option<SharedCredentialProvider>,
+    drives: Vec<DriveMapping>,
+    automation_dvc_state: Option<SharedDvcState>,
+    audio_capture: Option<SharedAudioCapture>,
+) -> Result<RdpConfig, String> {
+    // The desktop covers every monitor of a multi-monitor layout
+    let monitors = monitors::normalize(&params.monitors)?;
+    let (width, height) = monitors::desktop_size(&monitors).unwrap_or((params.width, params.height));
+
+    // Accept IPv6 literals and a port given as part of the host
+    let (host, port) = parse_target(&params.host, params.port)?;
+
+    if params.connect_timeout_ms == Some(0) {
+        return Err("Connect timeout must be greater than 0".to_string());
+    }
+    let connect_timeout = Duration::from_millis(params.connect_timeout_ms());
+
+    let proxy = match params.proxy.as_deref().map(ProxyConfig::parse) {
+        Some(Ok(proxy)) => Some(proxy),
+        Some(Err(msg)) => return Err(msg),
+        None => None,
+    };
+
+    // Kept as the normalized URL so the port and transport show up in `connect.json`
+    let kdc_url = match params.kdc.as_deref().map(kerberos::parse_kdc_url) {
+        Some(Ok(url)) => Some(url.to_string()),
+        Some(Err(msg)) => return Err(msg),
+        None => None,
+    };
+
+    let identity = ClientIdentity::new(
+        session_name,
+        params.client_name,
+        params.client_build,
+        params.dig_product_id,
+        params.hardware_id.as_deref(),
+    )?;
+
+    let clipboard_history = match params.clipboard_history {
+        Some(depth) if depth as usize > MAX_HISTORY_DEPTH => {
+            return Err(format!(
+                "Clipboard history must be at most {} entries, got {}",
+                MAX_HISTORY_DEPTH, depth
+            ));
+        }
+        Some(depth) => depth as usize,
+        None => DEFAULT_HISTORY_DEPTH,
+    };
+
+    if let Some((width, height)) = params.coordinate_space.filter(|&(w, h)| w == 0 || h == 0) {
+        return Err(format!("Logical size {}x{} must be at least 1x1", width, height));
+    }
+
+    // A minimum TLS version this build can't speak fails here rather than mid-handshake
+    if let Err(e) = tls_protocol_versions(params.min_tls) {
+        return Err(e.to_string());
+    }
+
+    let expected_cert_sha256 = match params.expected_cert_sha256.as_deref().map(parse_fingerprint) {
+        Some(Ok(fingerprint)) => Some(fingerprint),
+        Some(Err(msg)) => return Err(msg),
+        None => None,
+    };
+
+    // Gateway credentials default to the RDP credentials
+    let gateway = match params.gateway {
+        Some(gateway) => {
+            let cert_check = match gateway.expected_cert_sha256.as_deref().map(parse_fingerprint) {
+                Some(Ok(fingerprint)) => CertCheck::Pinned(fingerprint),
+                Some(Err(msg)) => return Err(msg),
+                None if gateway.insecure => CertCheck::Any,
+                None => CertCheck::WebPki,
+            };
+            Some(GatewayConfig {
+                host: gateway.host,
+                port: gateway.port,
+                username: gateway.username.unwrap_or_else(|| params.username.clone()),
+                password: Password::new(gateway.password.unwrap_or_else(|| params.password.clone())),
+                cert_check,
+            })
+        }
+        None => None,
+    };
+
+    Ok(RdpConfig {
+        host,
+        port,
+        credentials: credentials.unwrap_or_else(|| {
+            Arc::new(StaticCredentialProvider::new(params.username, params.password))
+        }),
+        domain: params.domain,
+        width,
+        height,
+        drives,
+        monitors,
+        keyboard_layout: params.keyboard_layout,
+        expected_cert_sha256,
+        auto_reconnect: params.auto_reconnect,
+        max_reconnect_attempts: params.max_reconnect_attempts,
+        keepalive_interval: params
+            .keepalive_interval_secs
+            .filter(|&secs| secs > 0)
+            .map(|secs| std::time::Duration::from_secs(secs.into())),
+        connect_timeout,
+        security: params.security,
+        min_tls: params.min_tls,
+        codec: params.codec,
+        performance_flags: performance_flags(params.perf_profile, params.perf_flags.as_deref()),
+        coordinate_space: params.coordinate_space,
+        gateway,
+        proxy,
+        kdc_url,
+        enable_clipboard: params.enable_clipboard,
+        enable_audio: params.enable_audio,
+        redirect_smartcard: params.redirect_smartcard,
+        identity,
+        clipboard_history,
+        automation_dvc_state,
+        audio_capture,
+    })
+}
@@ -0,0 +1,67 @@
+//! Cursor info handler.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use agent_rdp_protocol::{CursorInfo, ErrorCode, Response, ResponseData};
+use base64::Engine;
+use image::ImageFormat;
+use tokio::sync::Mutex;
+
+use crate::rdp_session::RdpSession;
+
+/// Handle a cursor info request.
+pub async fn handle(rdp_session: &Arc<Mutex<Option<RdpSession>>>, include_image: bool) -> Response {
+    let session = rdp_session.lock().await;
+
+    let rdp = match session.as_ref() {
+        Some(rdp) => rdp,
+        None => {
+            return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+        }
+    };
+
+    let pointer = rdp.pointer();
+    drop(session);
+
+    let mut info = CursorInfo {
+        x: pointer.x,
+        y: pointer.y,
+        visible: pointer.visible,
+        width: None,
+        height: None,
+        hotspot_x: None,
+        hotspot_y: None,
+        base64: None,
+    };
+
+    if let Some(shape) = pointer.shape {
+        info.width = Some(shape.width);
+        info.height = Some(shape.height);
+        info.hotspot_x = Some(shape.hotspot_x);
+        info.hotspot_y = Some(shape.hotspot_y);
+
+        if include_image {
+            let image = match image::RgbaImage::from_raw(
+                shape.width as u32,
+                shape.height as u32,
+                shape.bitmap_data.clone(),
+            ) {
+                Some(image) => image,
+                None => {
+                    return Response::error(ErrorCode::InternalError, "Pointer image has an unexpected size");
+                }
+            };
+            let mut buffer = Cursor::new(Vec::new());
+            if let Err(e) = image.write_to(&mut buffer, ImageFormat::Png) {
+                return Response::error(
+                    ErrorCode::InternalError,
+                    format!("Failed to encode pointer image: {}", e),
+                );
+            }
+            info.base64 = Some(base64::engine::general_purpose::STANDARD.encode(buffer.into_inner()));
+        }
+    }
+
+    Response::success(ResponseData::CursorInfo(info))
+}
@@ -1,39 +1,334 @@
 //! Drive mapping handler.
 //!
-//! Drives are configured at connect time using the --drive flag.
+//! Drives are configured at connect time using the --drive flag, and mount and
+//! unmount change them while connected. Push and pull copy files on the local
+//! side of a mapped drive, which the remote sees through RDPDR.
 
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
-use agent_rdp_protocol::{DriveRequest, ErrorCode, MappedDrive, Response, ResponseData};
+use agent_rdp_protocol::{
+    DriveMapping, DriveRequest, ErrorCode, MappedDrive, Response, ResponseData,
+};
 use tokio::sync::Mutex;
+use tracing::debug;
 
-use crate::rdp_session::RdpSession;
+use crate::rdp_session::{RdpError, RdpSession};
+use crate::rdpdr::get_disk_space;
+
+/// Prefix of remote paths written as `\\tsclient\Drive\...`, after the leading slashes.
+const TSCLIENT_PREFIX: &str = "tsclient/";
 
 /// Handle a drive request.
 pub async fn handle(
     rdp_session: &Arc<Mutex<Option<RdpSession>>>,
     action: DriveRequest,
 ) -> Response {
-    let session = rdp_session.lock().await;
-
-    let rdp = match session.as_ref() {
-        Some(rdp) => rdp,
-        None => {
-            return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+    let drives = {
+        let session = rdp_session.lock().await;
+        match session.as_ref() {
+            Some(rdp) => rdp.get_drives(),
+            None => {
+                return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+            }
         }
     };
 
     match action {
         DriveRequest::List => {
-            let drives = rdp
-                .get_drives()
-                .into_iter()
-                .map(|d| MappedDrive {
-                    name: d.name,
-                    path: d.path,
-                })
-                .collect();
-            Response::success(ResponseData::DriveList { drives })
+            let session = rdp_session.lock().await;
+            let Some(rdp) = session.as_ref() else {
+                return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+            };
+            drive_list(rdp)
+        }
+
+        DriveRequest::Mount { path, name, read_only } => {
+            if let Err(msg) = validate_drive_name(&name) {
+                return Response::error(ErrorCode::InvalidRequest, msg);
+            }
+            if drives.iter().any(|drive| drive.name.eq_ignore_ascii_case(&name)) {
+                return Response::error(
+                    ErrorCode::InvalidRequest,
+                    format!("A drive named '{}' is already mapped", name),
+                );
+            }
+            if !Path::new(&path).is_dir() {
+                return Response::error(
+                    ErrorCode::InvalidRequest,
+                    format!("Drive path '{}' is not a directory", path),
+                );
+            }
+            debug!("Mounting drive {} -> {}", name, path);
+            let session = rdp_session.lock().await;
+            let Some(rdp) = session.as_ref() else {
+                return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+            };
+            let result = rdp.mount_drive(DriveMapping { path, name, read_only }).await;
+            changed_drives(rdp, result)
+        }
+
+        DriveRequest::Unmount { name } => {
+            if !drives.iter().any(|drive| drive.name.eq_ignore_ascii_case(&name)) {
+                return Response::error(
+                    ErrorCode::InvalidRequest,
+                    format!("No mapped drive named '{}'", name),
+                );
+            }
+            debug!("Unmounting drive {}", name);
+            let session = rdp_session.lock().await;
+            let Some(rdp) = session.as_ref() else {
+                return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+            };
+            let result = rdp.unmount_drive(name).await;
+            changed_drives(rdp, result)
+        }
+
+        DriveRequest::Push { local, remote } => {
+            let (base, relative) = match resolve_remote(&drives, &remote) {
+                Ok(resolved) => resolved,
+                Err(msg) => return Response::error(ErrorCode::InvalidRequest, msg),
+            };
+            let source = PathBuf::from(local);
+            copy_response(move || {
+                let destination = base.join(relative);
+                ensure_within(&base, &destination)?;
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let destination = destination_for(&source, destination)?;
+                ensure_within(&base, &destination)?;
+                copy(&source, &destination)
+            })
+            .await
+        }
+
+        DriveRequest::Pull { remote, local } => {
+            let (base, relative) = match resolve_remote(&drives, &remote) {
+                Ok(resolved) => resolved,
+                Err(msg) => return Response::error(ErrorCode::InvalidRequest, msg),
+            };
+            let destination = PathBuf::from(local);
+            copy_response(move || {
+                let source = base.join(relative);
+                ensure_within(&base, &source)?;
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let destination = destination_for(&source, destination)?;
+                copy(&source, &destination)
+            })
+            .await
+        }
+    }
+}
+
+/// List the drives mapped on a session, with the space left on each.
+fn drive_list(rdp: &RdpSession) -> Response {
+    let drives = rdp
+        .get_drives()
+        .into_iter()
+        .map(|d| {
+            // Same figures the remote gets from a volume size query
+            let space = get_disk_space(Path::new(&d.path));
+            if let Err(e) = &space {
+                debug!("Failed to get disk space for {}: {}", d.path, e);
+            }
+            let (total_bytes, free_bytes) = space.ok().unzip();
+            MappedDrive {
+                device_id: rdp.drive_device_id(&d.name).unwrap_or(0),
+                unc_path: format!("\\\\TSCLIENT\\{}", d.name),
+                name: d.name,
+                path: d.path,
+                read_only: d.read_only,
+                total_bytes,
+                free_bytes,
+            }
+        })
+        .collect();
+    Response::success(ResponseData::DriveList { drives })
+}
+
+/// Report the result of a mount or unmount, listing the drives mapped afterwards.
+fn changed_drives(rdp: &RdpSession, result: Result<(), RdpError>) -> Response {
+    match result {
+        Ok(()) => drive_list(rdp),
+        Err(e @ RdpError::DriveRedirectionDisabled) => {
+            Response::error(ErrorCode::NotSupported, e.to_string())
+        }
+        Err(RdpError::InvalidInput(msg)) => Response::error(ErrorCode::InvalidRequest, msg),
+        Err(e) => Response::error(ErrorCode::DriveError, e.to_string()),
+    }
+}
+
+/// Check that a drive name can be shown as `\\tsclient\<name>`.
+fn validate_drive_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Drive name cannot be empty".to_string());
+    }
+    if name.contains(['/', '\\', ':']) {
+        return Err(format!("Drive name '{}' cannot contain '/', '\\' or ':'", name));
+    }
+    Ok(())
+}
+
+/// Run a copy off the async runtime and report what it copied.
+async fn copy_response<F>(copy: F) -> Response
+where
+    F: FnOnce() -> io::Result<CopySummary> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(copy).await {
+        Ok(Ok(summary)) => Response::success(ResponseData::DriveCopy {
+            destination: summary.destination.to_string_lossy().to_string(),
+            files: summary.files,
+            total_bytes: summary.total_bytes,
+        }),
+        Ok(Err(e)) => Response::error(ErrorCode::DriveError, format!("Copy failed: {}", e)),
+        Err(e) => Response::error(ErrorCode::InternalError, format!("Copy task failed: {}", e)),
+    }
+}
+
+/// Split a remote path into its drive's canonical base directory and the
+/// path inside it.
+///
+/// Accepts `Drive/dir/file`, with either slash, and `\\tsclient\Drive\dir\file`.
+/// Parent references and drive letters are rejected, so the result can't
+/// name anything outside the drive.
+fn resolve_remote(drives: &[DriveMapping], remote: &str) -> Result<(PathBuf, PathBuf), String> {
+    let trimmed = remote.trim_start_matches(['/', '\\']);
+    let trimmed = match trimmed.get(..TSCLIENT_PREFIX.len()) {
+        Some(prefix) if prefix.replace('\\', "/").eq_ignore_ascii_case(TSCLIENT_PREFIX) => {
+            &trimmed[TSCLIENT_PREFIX.len()..]
+        }
+        _ => trimmed,
+    };
+
+    let mut parts = trimmed.split(['/', '\\']).filter(|part| !part.is_empty() && *part != ".");
+    let name = parts
+        .next()
+        .ok_or_else(|| format!("Remote path '{}' must start with a drive name", remote))?;
+    let drive = drives
+        .iter()
+        .find(|drive| drive.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("No mapped drive named '{}'", name))?;
+
+    let mut relative = PathBuf::new();
+    for part in parts {
+        let component = Path::new(part).components().next();
+        if part.contains(':') || !matches!(component, Some(Component::Normal(_))) {
+            return Err(format!("Remote path '{}' must stay inside drive '{}'", remote, drive.name));
+        }
+        relative.push(part);
+    }
+
+    let base = fs::canonicalize(&drive.path)
+        .map_err(|e| format!("Drive '{}' path {:?} is unavailable: {}", drive.name, drive.path, e))?;
+    Ok((base, relative))
+}
+
+/// Refuse paths that resolve (through symlinks) outside the drive.
+///
+/// Parts of the path that don't exist yet are checked through their nearest
+/// existing ancestor.
+fn ensure_within(base: &Path, path: &Path) -> io::Result<()> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    let resolved = loop {
+        match fs::canonicalize(existing) {
+            Ok(resolved) => break resolved,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                match (existing.parent(), existing.file_name()) {
+                    (Some(parent), Some(name)) => {
+                        missing.push(name);
+                        existing = parent;
+                    }
+                    _ => return Err(e),
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    let resolved = missing.iter().rev().fold(resolved, |path, name| path.join(name));
+    if resolved.starts_with(base) {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{:?} is outside the mapped drive", path),
+        ))
+    }
+}
+
+/// Where `source` ends up: inside `destination` if that's an existing
+/// directory, otherwise at `destination` itself.
+fn destination_for(source: &Path, destination: PathBuf) -> io::Result<PathBuf> {
+    if !destination.is_dir() {
+        return Ok(destination);
+    }
+    let canonical = fs::canonicalize(source)?;
+    let name = canonical.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("{:?} has no file name", source))
+    })?;
+    Ok(destination.join(name))
+}
+
+/// What a copy wrote.
+struct CopySummary {
+    destination: PathBuf,
+    files: u32,
+    total_bytes: u64,
+}
+
+/// Copy a file, or a directory tree, to `destination`.
+fn copy(source: &Path, destination: &Path) -> io::Result<CopySummary> {
+    let metadata = fs::metadata(source)?;
+    let mut summary = CopySummary {
+        destination: destination.to_path_buf(),
+        files: 0,
+        total_bytes: 0,
+    };
+
+    if metadata.is_dir() {
+        // Copying a directory into itself would never finish
+        let canonical_source = fs::canonicalize(source)?;
+        fs::create_dir_all(destination)?;
+        if fs::canonicalize(destination)?.starts_with(&canonical_source) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot copy {:?} into itself", source),
+            ));
+        }
+        copy_dir(source, destination, &mut summary)?;
+    } else {
+        summary.total_bytes = fs::copy(source, destination)?;
+        summary.files = 1;
+    }
+
+    debug!(
+        "Copied {} file(s), {} bytes from {:?} to {:?}",
+        summary.files, summary.total_bytes, source, destination
+    );
+    Ok(summary)
+}
+
+/// Copy the contents of one directory into another, skipping symlinks so
+/// nothing outside the tree is followed.
+fn copy_dir(source: &Path, destination: &Path, summary: &mut CopySummary) -> io::Result<()> {
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = destination.join(entry.file_name());
+        if file_type.is_dir() {
+            fs::create_dir_all(&target)?;
+            copy_dir(&entry.path(), &target, summary)?;
+        } else if file_type.is_file() {
+            summary.total_bytes += fs::copy(entry.path(), &target)?;
+            summary.files += 1;
+        } else {
+            debug!("Skipping {:?}, not a regular file or directory", entry.path());
         }
     }
+    Ok(())
 }
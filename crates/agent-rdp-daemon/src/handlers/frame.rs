@@ -0,0 +1,82 @@
+//! Framebuffer handler.
+//!
+//! Hands out the desktop image as the session holds it, for local consumers
+//! that process frames themselves. Unlike screenshots nothing is cropped or
+//! drawn on, and raw frames skip image encoding entirely.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use agent_rdp_protocol::{ErrorCode, FrameEncoding, FrameRequest, Response, ResponseData};
+use base64::Engine;
+use image::ImageFormat;
+use tokio::sync::Mutex;
+
+use crate::rdp_session::RdpSession;
+
+/// Bytes per RGBA pixel.
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// Handle a frame request.
+pub async fn handle(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    params: FrameRequest,
+) -> Response {
+    let (width, height, data) = {
+        let session = rdp_session.lock().await;
+        match session.as_ref() {
+            Some(rdp) => rdp.get_image_data(),
+            None => {
+                return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+            }
+        }
+    };
+    let width = width as u32;
+    let height = height as u32;
+
+    let (stride, data) = match params.encoding {
+        FrameEncoding::Raw => (width * BYTES_PER_PIXEL, data),
+        FrameEncoding::Png => {
+            let encoded = tokio::task::spawn_blocking(move || encode_png(width, height, data)).await;
+            match encoded {
+                Ok(Ok(png)) => (0, png),
+                Ok(Err(message)) => return Response::error(ErrorCode::InternalError, message),
+                Err(e) => {
+                    return Response::error(
+                        ErrorCode::InternalError,
+                        format!("Frame encoding task failed: {}", e),
+                    );
+                }
+            }
+        }
+    };
+
+    let size_bytes = data.len() as u64;
+    let (data, payload) = if params.binary {
+        (None, data)
+    } else {
+        (Some(base64::engine::general_purpose::STANDARD.encode(data)), Vec::new())
+    };
+
+    Response::success(ResponseData::Frame {
+        width,
+        height,
+        stride,
+        encoding: params.encoding,
+        size_bytes,
+        data,
+        binary: params.binary,
+        payload,
+    })
+}
+
+/// Encode RGBA pixels as a PNG.
+fn encode_png(width: u32, height: u32, data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let image = image::RgbaImage::from_raw(width, height, data)
+        .ok_or_else(|| "Failed to create image from decoded data".to_string())?;
+    let mut buffer = Cursor::new(Vec::new());
+    image
+        .write_to(&mut buffer, ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(buffer.into_inner())
+}
@@ -0,0 +1,149 @@
+//! Input batch handler.
+//!
+//! Runs a sequence of mouse, keyboard, scroll and wait events under a single
+//! session lock, so long scripts don't pay a lock and IPC round trip per event.
+
+use std::sync::Arc;
+
+use agent_rdp_protocol::{ErrorCode, InputBatchRequest, InputEvent, MouseRequest, Response};
+use ironrdp::pdu::input::fast_path::FastPathInputEvent;
+use tokio::sync::Mutex;
+use tokio::time::{sleep, Duration};
+use tracing::debug;
+
+use super::{keyboard, mouse, scroll};
+use crate::rdp_session::RdpSession;
+
+/// Default pause after each event and between typed characters.
+const DEFAULT_EVENT_DELAY_MS: u64 = 20;
+
+/// Maximum number of events in one batch.
+const MAX_BATCH_EVENTS: usize = 10_000;
+
+/// Longest single wait, so a batch can't hold the session indefinitely.
+const MAX_WAIT_MS: u64 = 60_000;
+
+/// Input events sent together, followed by a pause.
+#[derive(Debug)]
+pub(crate) struct InputStep {
+    pub events: Vec<FastPathInputEvent>,
+    pub delay_ms: u64,
+}
+
+impl InputStep {
+    pub fn new(events: Vec<FastPathInputEvent>, delay_ms: u64) -> Self {
+        Self { events, delay_ms }
+    }
+}
+
+/// Send steps in order, taking the session lock for each one so streaming can
+/// proceed during the pauses.
+pub(crate) async fn send_steps(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    steps: Vec<InputStep>,
+) -> Response {
+    for step in steps {
+        {
+            let session = rdp_session.lock().await;
+            let Some(ref rdp) = *session else {
+                return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+            };
+            if let Err(e) = rdp.send_input(step.events).await {
+                return Response::error(ErrorCode::InternalError, e.to_string());
+            }
+        } // Lock released - streaming can proceed
+
+        if step.delay_ms > 0 {
+            sleep(Duration::from_millis(step.delay_ms)).await;
+        }
+    }
+
+    Response::ok()
+}
+
+/// Handle an input batch request.
+pub async fn handle(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    params: InputBatchRequest,
+) -> Response {
+    if params.events.len() > MAX_BATCH_EVENTS {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            format!("Too many events in batch (limit {})", MAX_BATCH_EVENTS),
+        );
+    }
+    let delay_ms = params.delay_ms.unwrap_or(DEFAULT_EVENT_DELAY_MS);
+
+    let session = rdp_session.lock().await;
+    let Some(ref rdp) = *session else {
+        return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+    };
+
+    // Build every step up front, so an invalid event fails the batch before anything is sent
+    let event_count = params.events.len();
+    let keyboard_layout = rdp.keyboard_layout();
+    // Follow the pointer through the batch, so human-like moves start where the last event left it
+    let mut pointer = rdp.pointer();
+    let mut steps = Vec::new();
+    for (index, event) in params.events.into_iter().enumerate() {
+        match event {
+            InputEvent::Mouse(MouseRequest::ReleaseAll) => {
+                return Response::error(
+                    ErrorCode::InvalidRequest,
+                    format!(
+                        "Event {}: release_all must be sent on its own, not in an input batch",
+                        index + 1
+                    ),
+                );
+            }
+            InputEvent::Mouse(action) => {
+                let action = mouse::to_device(action, |x, y| rdp.to_device(x, y));
+                for step in mouse::input_steps(action, (pointer.x, pointer.y)) {
+                    pointer.track_input(&step.events);
+                    steps.push(step);
+                }
+            }
+            InputEvent::Keyboard(action) => {
+                match keyboard::input_steps(action, delay_ms, keyboard_layout) {
+                    Ok(event_steps) => steps.extend(event_steps),
+                    Err(e) => {
+                        return Response::error(
+                            ErrorCode::InvalidRequest,
+                            format!("Event {}: {}", index + 1, e),
+                        );
+                    }
+                }
+            }
+            InputEvent::Scroll(params) => steps.push(InputStep::new(
+                scroll::scroll_events(&scroll::to_device(params, rdp), rdp.width(), rdp.height()),
+                0,
+            )),
+            InputEvent::Wait { ms } => {
+                if ms > MAX_WAIT_MS {
+                    return Response::error(
+                        ErrorCode::InvalidRequest,
+                        format!("Event {}: wait exceeds {} ms", index + 1, MAX_WAIT_MS),
+                    );
+                }
+                steps.push(InputStep::new(Vec::new(), ms));
+                continue;
+            }
+        }
+        steps.push(InputStep::new(Vec::new(), delay_ms));
+    }
+
+    debug!("Running input batch of {} events ({} steps)", event_count, steps.len());
+
+    for step in steps {
+        if !step.events.is_empty() {
+            if let Err(e) = rdp.send_input(step.events).await {
+                return Response::error(ErrorCode::InternalError, e.to_string());
+            }
+        }
+        if step.delay_ms > 0 {
+            sleep(Duration::from_millis(step.delay_ms)).await;
+        }
+    }
+
+    Response::ok()
+}
@@ -2,165 +2,359 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
-use agent_rdp_protocol::{ErrorCode, KeyboardRequest, Response};
+use agent_rdp_protocol::{ErrorCode, KeyboardRequest, LineEndings, Response, TypeMode};
 use ironrdp::pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
 use tokio::sync::Mutex;
-use tokio::time::{sleep, Duration};
-use tracing::debug;
+use tracing::{debug, warn};
 
-use crate::rdp_session::RdpSession;
+use super::input::{self, InputStep};
+use super::keymap::{self, Keymap, Modifier};
+use crate::rdp_session::{unicode_char_events, RdpSession};
+
+/// Default delay between typed characters, for reliability.
+const CHAR_DELAY_MS: u64 = 100;
+
+/// Delay between the key events of a combination.
+const KEY_EVENT_DELAY_MS: u64 = 10;
+
+/// Extra time a combination is held before its keys are released.
+const KEY_HOLD_MS: u64 = 50;
+
+/// Time the server gets to take over the clipboard before Ctrl+V is pressed.
+/// CLIPRDR has no acknowledgement we can wait on, so this covers the window
+/// in which a paste would still read the old clipboard.
+const PASTE_SETTLE_MS: u64 = 200;
+
+/// Time the focused window gets to read the clipboard after Ctrl+V.
+const PASTE_READ_TIMEOUT_MS: u64 = 2_000;
+
+/// Time the pasted text gets to reach the window before the clipboard is restored.
+const PASTE_RESTORE_DELAY_MS: u64 = 500;
 
 /// Handle a keyboard request.
+///
+/// The session lock is released between key events so streaming can proceed.
 pub async fn handle(
     rdp_session: &Arc<Mutex<Option<RdpSession>>>,
     action: KeyboardRequest,
 ) -> Response {
-    // For typing text, send one character at a time with delays for reliability
-    if let KeyboardRequest::Type { ref text } = action {
-        debug!("Typing {} characters: {:?}", text.len(), text);
-
-        const CHAR_DELAY_MS: u64 = 100;
-
-        for ch in text.chars() {
-            let code = ch as u16;
-            let events = vec![
-                FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::empty(), code),
-                FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::RELEASE, code),
-            ];
-
-            {
-                let session = rdp_session.lock().await;
-                let rdp = match session.as_ref() {
-                    Some(rdp) => rdp,
-                    None => {
-                        return Response::error(
-                            ErrorCode::NotConnected,
-                            "Not connected to an RDP server",
-                        );
-                    }
-                };
-                if let Err(e) = rdp.send_input(events).await {
-                    return Response::error(ErrorCode::InternalError, e.to_string());
-                }
-            }
-            sleep(Duration::from_millis(CHAR_DELAY_MS)).await;
+    match &action {
+        KeyboardRequest::Type { text, mode, .. } => {
+            debug!("Typing {} characters ({:?}): {:?}", text.len(), mode, text)
+        }
+        KeyboardRequest::Press { keys } => debug!("Pressing key combination: {}", keys),
+        KeyboardRequest::Hold { keys } => debug!("Holding keys: {}", keys),
+        KeyboardRequest::Release { keys } => debug!("Releasing keys: {}", keys),
+        KeyboardRequest::ReleaseAll => return release_all(rdp_session).await,
+        KeyboardRequest::Paste { text, restore } => {
+            debug!("Pasting {} characters (restore={})", text.len(), restore);
+            return paste(rdp_session, text, *restore).await;
         }
-        return Response::ok();
+        _ => {}
     }
 
-    // For key combinations, release lock between each key event
-    if let KeyboardRequest::Press { ref keys } = action {
-        debug!("Pressing key combination: {}", keys);
-        let key_infos = match parse_key_combination(keys) {
-            Ok(infos) => infos,
-            Err(e) => {
-                return Response::error(ErrorCode::InvalidRequest, e);
-            }
-        };
+    let keyboard_layout = match *rdp_session.lock().await {
+        Some(ref rdp) => rdp.keyboard_layout(),
+        None => return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server"),
+    };
 
-        // Press all keys down
-        for info in &key_infos {
-            debug!(
-                "Key down: scancode=0x{:02X}, extended={}",
-                info.scancode, info.extended
-            );
-            let event = create_key_event_ext(info.scancode, info.extended, false);
-            {
-                let session = rdp_session.lock().await;
-                let rdp = match session.as_ref() {
-                    Some(rdp) => rdp,
-                    None => {
-                        return Response::error(
-                            ErrorCode::NotConnected,
-                            "Not connected to an RDP server",
-                        );
-                    }
-                };
-                if let Err(e) = rdp.send_input(vec![event]).await {
-                    return Response::error(ErrorCode::InternalError, e.to_string());
+    let steps = match input_steps(action, CHAR_DELAY_MS, keyboard_layout) {
+        Ok(steps) => steps,
+        Err(e) => return Response::error(ErrorCode::InvalidRequest, e),
+    };
+    input::send_steps(rdp_session, steps).await
+}
+
+/// Release every key the session still has held down.
+async fn release_all(rdp_session: &Arc<Mutex<Option<RdpSession>>>) -> Response {
+    let session = rdp_session.lock().await;
+    let Some(ref rdp) = *session else {
+        return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+    };
+    match rdp.release_all_keys().await {
+        Ok(count) => {
+            debug!("Released {} held keys", count);
+            Response::ok()
+        }
+        Err(e) => Response::error(ErrorCode::InternalError, e.to_string()),
+    }
+}
+
+/// Paste text through the clipboard: announce it, press Ctrl+V, and wait for
+/// the focused window to read it.
+///
+/// The read is seen as the server asking for our clipboard data. With
+/// `restore`, the previous clipboard text is put back afterward (other
+/// content, such as images, is not restored).
+async fn paste(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    text: &str,
+    restore: bool,
+) -> Response {
+    let previous = {
+        let session = rdp_session.lock().await;
+        let Some(ref rdp) = *session else {
+            return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+        };
+        let previous = if restore {
+            match rdp.clipboard_text().await {
+                Ok(previous) => Some(previous),
+                Err(e) => {
+                    return Response::error(
+                        ErrorCode::ClipboardError,
+                        format!("Failed to read clipboard: {}", e),
+                    )
                 }
             }
-            sleep(Duration::from_millis(10)).await;
+        } else {
+            None
+        };
+        if let Err(e) = rdp.clipboard_set(LineEndings::Crlf.apply(text)).await {
+            return Response::error(
+                ErrorCode::ClipboardError,
+                format!("Failed to set clipboard: {}", e),
+            );
         }
+        previous
+    };
 
-        // Small delay before releasing
-        sleep(Duration::from_millis(50)).await;
+    tokio::time::sleep(Duration::from_millis(PASTE_SETTLE_MS)).await;
+    let read_rx = match *rdp_session.lock().await {
+        Some(ref rdp) => rdp.clipboard_watch_read(),
+        None => return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server"),
+    };
 
-        // Release all keys in reverse order
-        for info in key_infos.iter().rev() {
-            debug!(
-                "Key up: scancode=0x{:02X}, extended={}",
-                info.scancode, info.extended
+    // Neither the typing delay nor the keyboard layout applies to a combination
+    let ctrl_v = KeyboardRequest::Press {
+        keys: "ctrl+v".to_string(),
+    };
+    let steps = match input_steps(ctrl_v, 0, 0) {
+        Ok(steps) => steps,
+        Err(e) => return Response::error(ErrorCode::InternalError, e),
+    };
+    let response = input::send_steps(rdp_session, steps).await;
+    if !response.success {
+        return response;
+    }
+    let read = tokio::time::timeout(Duration::from_millis(PASTE_READ_TIMEOUT_MS), read_rx)
+        .await
+        .is_ok_and(|result| result.is_ok());
+
+    if let Some(previous) = previous {
+        tokio::time::sleep(Duration::from_millis(PASTE_RESTORE_DELAY_MS)).await;
+        let session = rdp_session.lock().await;
+        let Some(ref rdp) = *session else {
+            return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+        };
+        let restored = match previous {
+            Some(text) => rdp.clipboard_set(text).await,
+            None => rdp.clipboard_clear().await,
+        };
+        if let Err(e) = restored {
+            warn!("Failed to restore clipboard after paste: {}", e);
+            return Response::error(
+                ErrorCode::ClipboardError,
+                format!("Pasted, but failed to restore the clipboard: {}", e),
             );
-            let event = create_key_event_ext(info.scancode, info.extended, true);
-            {
-                let session = rdp_session.lock().await;
-                let rdp = match session.as_ref() {
-                    Some(rdp) => rdp,
-                    None => {
-                        return Response::error(
-                            ErrorCode::NotConnected,
-                            "Not connected to an RDP server",
-                        );
-                    }
-                };
-                if let Err(e) = rdp.send_input(vec![event]).await {
-                    return Response::error(ErrorCode::InternalError, e.to_string());
-                }
-            }
-            sleep(Duration::from_millis(10)).await;
         }
+    }
 
-        return Response::ok();
+    if !read {
+        return Response::error(
+            ErrorCode::ClipboardError,
+            format!(
+                "Pressed Ctrl+V, but nothing read the clipboard within {} ms; \
+                the focused window may not accept Ctrl+V",
+                PASTE_READ_TIMEOUT_MS
+            ),
+        );
     }
+    Response::ok()
+}
 
-    // For single key operations (KeyDown/KeyUp), use a scoped lock
-    let session = rdp_session.lock().await;
-    let rdp = match session.as_ref() {
-        Some(rdp) => rdp,
-        None => {
-            return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+/// Build the input steps for a keyboard action.
+///
+/// Typed characters are each sent as a press and release followed by the
+/// request's delay, or `char_delay_ms` if it has none. With no delay the whole
+/// text goes in one step. In scancode mode characters are translated for
+/// `keyboard_layout`.
+/// Combinations press every key in order, then release them in reverse order;
+/// `Hold` and `Release` do one half each.
+pub(crate) fn input_steps(
+    action: KeyboardRequest,
+    char_delay_ms: u64,
+    keyboard_layout: u32,
+) -> Result<Vec<InputStep>, String> {
+    match action {
+        KeyboardRequest::Type {
+            text,
+            mode,
+            delay_ms,
+        } => {
+            let char_delay_ms = delay_ms.unwrap_or(char_delay_ms);
+            let steps = match mode {
+                TypeMode::Scancode => scancode_type_steps(&text, char_delay_ms, keyboard_layout)?,
+                TypeMode::Unicode => text
+                    .chars()
+                    .map(|ch| InputStep::new(unicode_char_events(ch), char_delay_ms))
+                    .collect(),
+            };
+            Ok(if char_delay_ms == 0 { merge_steps(steps) } else { steps })
         }
-    };
 
-    let events = match action {
-        KeyboardRequest::Type { .. } | KeyboardRequest::Press { .. } => {
-            // Handled above
-            unreachable!()
+        KeyboardRequest::Press { keys } => {
+            let key_infos = parse_key_combination(&keys)?;
+
+            let mut steps: Vec<InputStep> = key_infos
+                .iter()
+                .map(|info| {
+                    InputStep::new(
+                        vec![create_key_event_ext(info.scancode, info.extended, false)],
+                        KEY_EVENT_DELAY_MS,
+                    )
+                })
+                .collect();
+            if let Some(last) = steps.last_mut() {
+                last.delay_ms += KEY_HOLD_MS;
+            }
+            steps.extend(key_infos.iter().rev().map(|info| {
+                InputStep::new(
+                    vec![create_key_event_ext(info.scancode, info.extended, true)],
+                    KEY_EVENT_DELAY_MS,
+                )
+            }));
+            Ok(steps)
         }
 
         KeyboardRequest::KeyDown { key } => match key_to_scancode(&key) {
-            Some((scancode, extended)) => {
-                vec![create_key_event_ext(scancode, extended, false)]
-            }
-            None => {
-                return Response::error(
-                    ErrorCode::InvalidRequest,
-                    format!("Unknown key: {}", key),
-                );
-            }
+            Some((scancode, extended)) => Ok(vec![InputStep::new(
+                vec![create_key_event_ext(scancode, extended, false)],
+                0,
+            )]),
+            None => Err(format!("Unknown key: {}", key)),
         },
 
         KeyboardRequest::KeyUp { key } => match key_to_scancode(&key) {
-            Some((scancode, extended)) => {
-                vec![create_key_event_ext(scancode, extended, true)]
-            }
-            None => {
-                return Response::error(
-                    ErrorCode::InvalidRequest,
-                    format!("Unknown key: {}", key),
-                );
-            }
+            Some((scancode, extended)) => Ok(vec![InputStep::new(
+                vec![create_key_event_ext(scancode, extended, true)],
+                0,
+            )]),
+            None => Err(format!("Unknown key: {}", key)),
         },
-    };
 
-    match rdp.send_input(events).await {
-        Ok(()) => Response::ok(),
-        Err(e) => Response::error(ErrorCode::InternalError, e.to_string()),
+        KeyboardRequest::Raw {
+            scancode,
+            extended,
+            release,
+        } => Ok(vec![InputStep::new(
+            vec![create_key_event_ext(scancode, extended, release)],
+            0,
+        )]),
+
+        KeyboardRequest::Hold { keys } => Ok(parse_key_combination(&keys)?
+            .iter()
+            .map(|info| {
+                InputStep::new(
+                    vec![create_key_event_ext(info.scancode, info.extended, false)],
+                    KEY_EVENT_DELAY_MS,
+                )
+            })
+            .collect()),
+
+        KeyboardRequest::Release { keys } => Ok(parse_key_combination(&keys)?
+            .iter()
+            .rev()
+            .map(|info| {
+                InputStep::new(
+                    vec![create_key_event_ext(info.scancode, info.extended, true)],
+                    KEY_EVENT_DELAY_MS,
+                )
+            })
+            .collect()),
+
+        // Depends on what is held when it runs, so it isn't built ahead of time
+        KeyboardRequest::ReleaseAll => {
+            Err("release_all must be sent on its own, not in an input batch".to_string())
+        }
+
+        // Waits on the clipboard between its steps
+        KeyboardRequest::Paste { .. } => {
+            Err("paste must be sent on its own, not in an input batch".to_string())
+        }
     }
 }
 
+/// Combine steps with no pause between them into one, keeping event order.
+fn merge_steps(steps: Vec<InputStep>) -> Vec<InputStep> {
+    if steps.is_empty() {
+        return steps;
+    }
+    let events = steps.into_iter().flat_map(|step| step.events).collect();
+    vec![InputStep::new(events, 0)]
+}
+
+/// Build the steps to type `text` as scancodes for a keyboard layout.
+///
+/// Fails listing every character the layout can't type, so callers can fall
+/// back to unicode mode.
+fn scancode_type_steps(
+    text: &str,
+    char_delay_ms: u64,
+    keyboard_layout: u32,
+) -> Result<Vec<InputStep>, String> {
+    let keymap = Keymap::for_layout(keyboard_layout).ok_or_else(|| {
+        format!(
+            "Scancode typing is not supported for keyboard layout 0x{:04X}; use unicode mode",
+            keyboard_layout
+        )
+    })?;
+
+    let text = text.replace("\r\n", "\n");
+    let mut steps = Vec::new();
+    let mut unsupported: Vec<char> = Vec::new();
+
+    for ch in text.chars() {
+        let Some(stroke) = keymap.key_stroke(ch) else {
+            if !unsupported.contains(&ch) {
+                unsupported.push(ch);
+            }
+            continue;
+        };
+
+        let modifiers: &[(u8, bool)] = match stroke.modifier {
+            Modifier::None => &[],
+            Modifier::Shift => &[(keymap::SHIFT_SCANCODE, false)],
+            Modifier::AltGr => &[(keymap::CTRL_SCANCODE, false), (keymap::ALT_SCANCODE, true)],
+        };
+
+        let mut events: Vec<FastPathInputEvent> = modifiers
+            .iter()
+            .map(|&(scancode, extended)| create_key_event_ext(scancode, extended, false))
+            .collect();
+        events.push(create_key_event_ext(stroke.scancode, false, false));
+        events.push(create_key_event_ext(stroke.scancode, false, true));
+        events.extend(
+            modifiers
+                .iter()
+                .rev()
+                .map(|&(scancode, extended)| create_key_event_ext(scancode, extended, true)),
+        );
+        steps.push(InputStep::new(events, char_delay_ms));
+    }
+
+    if !unsupported.is_empty() {
+        let list: Vec<String> = unsupported.iter().map(|ch| format!("{:?}", ch)).collect();
+        return Err(format!(
+            "Characters not representable in keyboard layout 0x{:04X}: {}; use unicode mode",
+            keyboard_layout,
+            list.join(", ")
+        ));
+    }
+
+    Ok(steps)
+}
 
 /// Parse a key combination like "ctrl+c" into key info for sending.
 fn parse_key_combination(keys: &str) -> Result<Vec<KeyInfo>, String> {
@@ -359,4 +553,133 @@ mod tests {
         assert_eq!(key_infos[0].scancode, 0x1D); // ctrl
         assert_eq!(key_infos[1].scancode, 0x2E); // c
     }
+
+    #[test]
+    fn test_hold_and_release_steps() {
+        let hold = KeyboardRequest::Hold {
+            keys: "ctrl+shift".to_string(),
+        };
+        let hold = input_steps(hold, 0, 0x0409).unwrap();
+        assert_eq!(hold.len(), 2);
+        assert!(matches!(
+            hold[0].events[0],
+            FastPathInputEvent::KeyboardEvent(flags, 0x1D) if !flags.contains(KeyboardFlags::RELEASE)
+        ));
+
+        // Released in reverse order
+        let release = KeyboardRequest::Release {
+            keys: "ctrl+shift".to_string(),
+        };
+        let release = input_steps(release, 0, 0x0409).unwrap();
+        assert!(matches!(
+            release[0].events[0],
+            FastPathInputEvent::KeyboardEvent(flags, 0x2A) if flags.contains(KeyboardFlags::RELEASE)
+        ));
+
+        let raw = KeyboardRequest::Raw {
+            scancode: 0x5D,
+            extended: true,
+            release: false,
+        };
+        let raw = input_steps(raw, 0, 0x0409).unwrap();
+        assert!(matches!(
+            raw[0].events[0],
+            FastPathInputEvent::KeyboardEvent(flags, 0x5D) if flags == KeyboardFlags::EXTENDED
+        ));
+
+        assert!(input_steps(KeyboardRequest::ReleaseAll, 0, 0x0409).is_err());
+        let paste = KeyboardRequest::Paste {
+            text: "hello".to_string(),
+            restore: false,
+        };
+        assert!(input_steps(paste, 0, 0x0409).is_err());
+    }
+
+    #[test]
+    fn test_scancode_type_steps() {
+        // "aB" on US: a, then shift+b
+        let steps = scancode_type_steps("aB", 0, 0x0409).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].events.len(), 2);
+        assert_eq!(steps[1].events.len(), 4);
+        assert!(matches!(
+            steps[1].events[0],
+            FastPathInputEvent::KeyboardEvent(_, keymap::SHIFT_SCANCODE)
+        ));
+    }
+
+    #[test]
+    fn test_scancode_type_unsupported_chars() {
+        let err = scancode_type_steps("héllo ✓", 0, 0x0409).unwrap_err();
+        assert!(err.contains("'é'"));
+        assert!(err.contains("'✓'"));
+
+        let err = scancode_type_steps("hello", 0, 0x0419).unwrap_err();
+        assert!(err.contains("0x0419"));
+    }
+
+    #[test]
+    fn test_unicode_type_surrogate_pair() {
+        let action = KeyboardRequest::Type {
+            text: "a😀".to_string(),
+            mode: TypeMode::Unicode,
+            delay_ms: None,
+        };
+        let steps = input_steps(action, CHAR_DELAY_MS, 0x0409).unwrap();
+        // One step per character, the emoji pressing both UTF-16 halves
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].events.len(), 2);
+        let presses: Vec<u16> = steps[1]
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                FastPathInputEvent::UnicodeKeyboardEvent(flags, unit)
+                    if !flags.contains(KeyboardFlags::RELEASE) =>
+                {
+                    Some(*unit)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(presses, vec![0xD83D, 0xDE00]);
+    }
+
+    #[test]
+    fn test_type_without_delay_is_one_send() {
+        let action = KeyboardRequest::Type {
+            text: "abc".to_string(),
+            mode: TypeMode::Unicode,
+            delay_ms: Some(0),
+        };
+        let steps = input_steps(action, CHAR_DELAY_MS, 0x0409).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].delay_ms, 0);
+
+        // Each character is still pressed then released, in order
+        let events: Vec<(u16, bool)> = steps[0]
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                FastPathInputEvent::UnicodeKeyboardEvent(flags, unit) => {
+                    Some((*unit, flags.contains(KeyboardFlags::RELEASE)))
+                }
+                _ => None,
+            })
+            .collect();
+        let expected: Vec<(u16, bool)> = "abc"
+            .encode_utf16()
+            .flat_map(|unit| [(unit, false), (unit, true)])
+            .collect();
+        assert_eq!(events, expected);
+
+        // A request's own delay overrides the default
+        let action = KeyboardRequest::Type {
+            text: "ab".to_string(),
+            mode: TypeMode::Scancode,
+            delay_ms: Some(5),
+        };
+        let steps = input_steps(action, 0, 0x0409).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert!(steps.iter().all(|step| step.delay_ms == 5));
+    }
 }
@@ -0,0 +1,249 @@
+//! Character to scancode maps for common keyboard layouts.
+//!
+//! Used to type text as scancodes for applications that ignore unicode
+//! injection. Dead keys are left unmapped, since pressing one would combine
+//! with the following character.
+
+/// Scancodes of the character keys, one slice per physical row.
+const ROW_SCANCODES: [&[u8]; 4] = [
+    // Number row, starting with the key left of 1
+    &[
+        0x29, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
+    ],
+    // Top letter row
+    &[
+        0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B,
+    ],
+    // Home row, ending with the key left of (ISO) or above (ANSI) Enter
+    &[
+        0x1E, 0x1F, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x2B,
+    ],
+    // Bottom row, starting with the ISO key left of Z
+    &[
+        0x56, 0x2C, 0x2D, 0x2E, 0x2F, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35,
+    ],
+];
+
+/// Left shift scancode.
+pub const SHIFT_SCANCODE: u8 = 0x2A;
+
+/// Left ctrl scancode, sent with right alt for AltGr.
+pub const CTRL_SCANCODE: u8 = 0x1D;
+
+/// Alt scancode; with the extended flag this is right alt (AltGr).
+pub const ALT_SCANCODE: u8 = 0x38;
+
+/// Modifiers held while a key is pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    None,
+    Shift,
+    AltGr,
+}
+
+/// Characters produced by each key of `ROW_SCANCODES`, per modifier.
+///
+/// A space marks a key with no (non-dead) character for that modifier.
+struct Layout {
+    lcids: &'static [u32],
+    normal: [&'static str; 4],
+    shift: [&'static str; 4],
+    altgr: [&'static str; 4],
+}
+
+const NO_ALTGR: [&str; 4] = [
+    "             ",
+    "            ",
+    "            ",
+    "           ",
+];
+
+const LAYOUTS: &[Layout] = &[
+    // US (also the default layout for en-CA and en-AU)
+    Layout {
+        lcids: &[0x0409, 0x1009, 0x0C09],
+        normal: [
+            "`1234567890-=",
+            "qwertyuiop[]",
+            "asdfghjkl;'\\",
+            " zxcvbnm,./",
+        ],
+        shift: [
+            "~!@#$%^&*()_+",
+            "QWERTYUIOP{}",
+            "ASDFGHJKL:\"|",
+            " ZXCVBNM<>?",
+        ],
+        altgr: NO_ALTGR,
+    },
+    // United Kingdom
+    Layout {
+        lcids: &[0x0809],
+        normal: [
+            "`1234567890-=",
+            "qwertyuiop[]",
+            "asdfghjkl;'#",
+            "\\zxcvbnm,./",
+        ],
+        shift: [
+            "¬!\"£$%^&*()_+",
+            "QWERTYUIOP{}",
+            "ASDFGHJKL:@~",
+            "|ZXCVBNM<>?",
+        ],
+        altgr: [
+            "¦   €        ",
+            "  é   úíó   ",
+            "á           ",
+            "           ",
+        ],
+    },
+    // German (also Austria)
+    Layout {
+        lcids: &[0x0407, 0x0C07],
+        normal: [
+            " 1234567890ß ",
+            "qwertzuiopü+",
+            "asdfghjklöä#",
+            "<yxcvbnm,.-",
+        ],
+        shift: [
+            "°!\"§$%&/()=? ",
+            "QWERTZUIOPÜ*",
+            "ASDFGHJKLÖÄ'",
+            ">YXCVBNM;:_",
+        ],
+        altgr: [
+            "  ²³   {[]}\\ ",
+            "@ €        ~",
+            "            ",
+            "|      µ   ",
+        ],
+    },
+    // French
+    Layout {
+        lcids: &[0x040C],
+        normal: [
+            "²&é\"'(-è_çà)=",
+            "azertyuiop $",
+            "qsdfghjklmù*",
+            "<wxcvbn,;:!",
+        ],
+        shift: [
+            " 1234567890°+",
+            "AZERTYUIOP £",
+            "QSDFGHJKLM%µ",
+            ">WXCVBN?./§",
+        ],
+        altgr: [
+            "   #{[| \\^@]}",
+            "  €        ¤",
+            "            ",
+            "           ",
+        ],
+    },
+];
+
+/// Keys typed for a character: a scancode and the modifier held around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyStroke {
+    pub scancode: u8,
+    pub modifier: Modifier,
+}
+
+/// Character map for one keyboard layout.
+pub struct Keymap {
+    layout: &'static Layout,
+}
+
+impl Keymap {
+    /// Look up the keymap for a layout LCID, if it is supported.
+    pub fn for_layout(lcid: u32) -> Option<Self> {
+        LAYOUTS
+            .iter()
+            .find(|layout| layout.lcids.contains(&lcid))
+            .map(|layout| Self { layout })
+    }
+
+    /// Find the key stroke that types `ch`, preferring unmodified keys.
+    pub fn key_stroke(&self, ch: char) -> Option<KeyStroke> {
+        let scancode = match ch {
+            ' ' => Some(0x39),
+            '\t' => Some(0x0F),
+            '\n' => Some(0x1C),
+            _ => None,
+        };
+        if let Some(scancode) = scancode {
+            return Some(KeyStroke {
+                scancode,
+                modifier: Modifier::None,
+            });
+        }
+
+        let layers = [
+            (Modifier::None, &self.layout.normal),
+            (Modifier::Shift, &self.layout.shift),
+            (Modifier::AltGr, &self.layout.altgr),
+        ];
+        layers.into_iter().find_map(|(modifier, rows)| {
+            rows.iter()
+                .zip(ROW_SCANCODES)
+                .find_map(|(row, scancodes)| {
+                    row.chars()
+                        .zip(scancodes.iter())
+                        .find(|(key_ch, _)| *key_ch == ch)
+                        .map(|(_, scancode)| *scancode)
+                })
+                .map(|scancode| KeyStroke { scancode, modifier })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_rows_match_scancodes() {
+        for layout in LAYOUTS {
+            for rows in [&layout.normal, &layout.shift, &layout.altgr] {
+                for (row, scancodes) in rows.iter().zip(ROW_SCANCODES) {
+                    assert_eq!(
+                        row.chars().count(),
+                        scancodes.len(),
+                        "layout 0x{:04X} row {:?}",
+                        layout.lcids[0],
+                        row
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_us_key_strokes() {
+        let keymap = Keymap::for_layout(0x0409).unwrap();
+        let stroke = |scancode, modifier| Some(KeyStroke { scancode, modifier });
+        assert_eq!(keymap.key_stroke('a'), stroke(0x1E, Modifier::None));
+        assert_eq!(keymap.key_stroke('A'), stroke(0x1E, Modifier::Shift));
+        assert_eq!(keymap.key_stroke('!'), stroke(0x02, Modifier::Shift));
+        assert_eq!(keymap.key_stroke('\\'), stroke(0x2B, Modifier::None));
+        assert_eq!(keymap.key_stroke(' '), stroke(0x39, Modifier::None));
+        assert_eq!(keymap.key_stroke('é'), None);
+    }
+
+    #[test]
+    fn test_german_key_strokes() {
+        let keymap = Keymap::for_layout(0x0407).unwrap();
+        let stroke = |scancode, modifier| Some(KeyStroke { scancode, modifier });
+        assert_eq!(keymap.key_stroke('z'), stroke(0x15, Modifier::None));
+        assert_eq!(keymap.key_stroke('y'), stroke(0x2C, Modifier::None));
+        assert_eq!(keymap.key_stroke('@'), stroke(0x10, Modifier::AltGr));
+        assert_eq!(keymap.key_stroke('^'), None); // Dead key
+    }
+
+    #[test]
+    fn test_unsupported_layout() {
+        assert!(Keymap::for_layout(0x0419).is_none());
+    }
+}
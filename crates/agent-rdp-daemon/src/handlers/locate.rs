@@ -1,53 +1,147 @@
 //! OCR-based text location handler.
 
 use std::io::Cursor;
-use std::sync::{Arc, OnceLock};
+use std::path::Path;
+use std::sync::Arc;
 
-use agent_rdp_protocol::{ErrorCode, LocateRequest, LocateResult, Response, ResponseData};
+use agent_rdp_protocol::{
+    ElementBounds, ErrorCode, LocateClickRequest, LocateRequest, LocateResult, MouseButton,
+    MouseRequest, OcrMatch, Response, ResponseData,
+};
 use image::ImageFormat;
 use tokio::sync::Mutex;
 use tracing::debug;
 
-use crate::ocr::{find_models_dir, OcrService};
+use super::mouse;
+use crate::ocr;
 use crate::rdp_session::RdpSession;
 
-/// Lazily initialized OCR service.
-static OCR_SERVICE: OnceLock<Option<OcrService>> = OnceLock::new();
-
-/// Get or initialize the OCR service.
-fn get_ocr_service() -> Option<&'static OcrService> {
-    OCR_SERVICE.get_or_init(|| {
-        match find_models_dir() {
-            Ok(models_dir) => match OcrService::new(&models_dir) {
-                Ok(service) => Some(service),
-                Err(e) => {
-                    tracing::error!("Failed to initialize OCR service: {}", e);
-                    None
-                }
-            },
-            Err(e) => {
-                tracing::error!("Failed to find OCR models: {}", e);
-                None
-            }
-        }
-    }).as_ref()
-}
+/// Confidence below which locate-click ignores a match, unless told otherwise.
+const DEFAULT_CLICK_MIN_CONFIDENCE: f32 = 0.2;
 
 /// Handle a locate request.
 pub async fn handle(
     rdp_session: &Arc<Mutex<Option<RdpSession>>>,
     params: LocateRequest,
 ) -> Response {
-    debug!("Locate request: text='{}', pattern={}, ignore_case={}, all={}",
-           params.text, params.pattern, params.ignore_case, params.all);
+    debug!("Locate request: text='{}', pattern={}, ignore_case={}, all={}, region={:?}",
+           params.text, params.pattern, params.ignore_case, params.all, params.region);
+
+    match find_lines(rdp_session, &params).await {
+        Ok((matches, total_lines)) => {
+            debug!("Found {} lines out of {} total", matches.len(), total_lines);
+            Response::success(ResponseData::LocateResult(LocateResult {
+                matches,
+                total_words: total_lines, // Now represents total lines
+            }))
+        }
+        Err(response) => response,
+    }
+}
 
+/// Handle a locate-click request: find the text, then click its center.
+pub async fn handle_click(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    params: LocateClickRequest,
+) -> Response {
+    debug!("Locate click request: text='{}', index={:?}, button={:?}",
+           params.text, params.index, params.button);
+
+    let min_confidence = params.min_confidence.unwrap_or(DEFAULT_CLICK_MIN_CONFIDENCE);
+    if !(0.0..=1.0).contains(&min_confidence) {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            format!("min_confidence must be between 0 and 1, got {}", min_confidence),
+        );
+    }
+
+    let search = LocateRequest {
+        text: params.text.clone(),
+        pattern: params.pattern,
+        ignore_case: params.ignore_case,
+        all: false,
+        region: params.region,
+        models_dir: params.models_dir,
+        lang: params.lang,
+    };
+    let matches = match find_lines(rdp_session, &search).await {
+        Ok((matches, _)) => matches,
+        Err(response) => return response,
+    };
+
+    let (matched, match_count) = match pick_match(matches, min_confidence, params.index) {
+        Ok(picked) => picked,
+        Err(message) => return Response::error(ErrorCode::TextNotFound, message),
+    };
+    let (Ok(x), Ok(y)) = (u16::try_from(matched.center_x), u16::try_from(matched.center_y)) else {
+        return Response::error(
+            ErrorCode::InternalError,
+            format!("Match center ({}, {}) is off the desktop", matched.center_x, matched.center_y),
+        );
+    };
+
+    let click = match params.button.unwrap_or(MouseButton::Left) {
+        MouseButton::Left => MouseRequest::Click { x, y, human: false },
+        MouseButton::Right => MouseRequest::RightClick { x, y },
+        MouseButton::Middle => MouseRequest::MiddleClick { x, y },
+    };
+    let response = mouse::handle_device(rdp_session, click).await;
+    if !response.success {
+        return response;
+    }
+
+    debug!("Clicked '{}' at ({}, {})", matched.text, x, y);
+    Response::success(ResponseData::LocateClicked { matched, x, y, match_count })
+}
+
+/// Pick the match to click and count the candidates.
+///
+/// Matches below `min_confidence` are dropped. Without an index the most
+/// confident one wins; with one, matches are taken in reading order.
+fn pick_match(
+    matches: Vec<OcrMatch>,
+    min_confidence: f32,
+    index: Option<u32>,
+) -> Result<(OcrMatch, u32), String> {
+    let mut candidates: Vec<OcrMatch> =
+        matches.into_iter().filter(|m| m.confidence >= min_confidence).collect();
+    let count = candidates.len() as u32;
+    if candidates.is_empty() {
+        return Err(format!(
+            "No matching text found with confidence of at least {:.0}%",
+            min_confidence * 100.0
+        ));
+    }
+
+    let picked = match index {
+        Some(index) => {
+            candidates.sort_by_key(|m| (m.y, m.x));
+            if index >= count {
+                return Err(format!("Match {} requested, but only {} found", index, count));
+            }
+            candidates.swap_remove(index as usize)
+        }
+        None => candidates
+            .into_iter()
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+            .expect("candidates is not empty"),
+    };
+    Ok((picked, count))
+}
+
+/// Capture the desktop (or a region of it) and OCR it, returning the matching
+/// lines in full-desktop coordinates along with the total line count.
+async fn find_lines(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    params: &LocateRequest,
+) -> Result<(Vec<OcrMatch>, u32), Response> {
     // Get the current screenshot first (this acquires the async lock)
     let image_data = {
         let session = rdp_session.lock().await;
         let rdp = match session.as_ref() {
             Some(rdp) => rdp,
             None => {
-                return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+                return Err(Response::error(ErrorCode::NotConnected, "Not connected to an RDP server"));
             }
         };
 
@@ -59,33 +153,47 @@ pub async fn handle(
         let rgba_image = match image::RgbaImage::from_raw(width, height, data) {
             Some(img) => img,
             None => {
-                return Response::error(
+                return Err(Response::error(
                     ErrorCode::InternalError,
                     "Failed to create image from desktop data",
-                );
+                ));
             }
         };
 
+        // Only OCR the requested region
+        let rgba_image = match params.region.as_ref() {
+            Some(region) => match crop_region(&rgba_image, region) {
+                Ok(img) => img,
+                Err(msg) => return Err(Response::error(ErrorCode::InvalidRequest, msg)),
+            },
+            None => rgba_image,
+        };
+
         // Encode to PNG for OCR
         let mut buffer = Cursor::new(Vec::new());
         if let Err(e) = rgba_image.write_to(&mut buffer, ImageFormat::Png) {
-            return Response::error(
+            return Err(Response::error(
                 ErrorCode::InternalError,
                 format!("Failed to encode image: {}", e),
-            );
+            ));
         }
 
         buffer.into_inner()
     }; // session lock is dropped here
 
-    // Get the OCR service (no async operations, just a static reference)
-    let ocr = match get_ocr_service() {
-        Some(ocr) => ocr,
-        None => {
-            return Response::error(
+    // Models are loaded by the first request and shared after that
+    let models_dir = params.models_dir.as_deref().map(Path::new);
+    let ocr = match ocr::shared_service(models_dir, params.lang.as_deref()) {
+        Ok(ocr) => ocr,
+        Err(e) if e.is::<ocr::LanguageNotAvailable>() => {
+            return Err(Response::error(ErrorCode::LanguageNotAvailable, e.to_string()));
+        }
+        Err(e) => {
+            tracing::error!("OCR service not available: {:#}", e);
+            return Err(Response::error(
                 ErrorCode::InternalError,
-                "OCR service not available. Make sure OCR models are installed.",
-            );
+                format!("OCR service not available: {:#}", e),
+            ));
         }
     };
 
@@ -99,16 +207,48 @@ pub async fn handle(
     };
 
     match result {
-        Ok((matches, total_lines)) => {
-            debug!("Found {} lines out of {} total", matches.len(), total_lines);
-            Response::success(ResponseData::LocateResult(LocateResult {
-                matches,
-                total_words: total_lines, // Now represents total lines
-            }))
+        Ok((mut matches, total_lines)) => {
+            // Report positions in full-desktop coordinates
+            if let Some(region) = params.region.as_ref() {
+                for m in &mut matches {
+                    m.x += region.x;
+                    m.y += region.y;
+                    m.center_x += region.x;
+                    m.center_y += region.y;
+                }
+            }
+            Ok((matches, total_lines))
         }
-        Err(e) => Response::error(
+        Err(e) => Err(Response::error(
             ErrorCode::InternalError,
             format!("OCR failed: {}", e),
-        ),
+        )),
     }
 }
+
+/// Crop the desktop image to a search region.
+fn crop_region(image: &image::RgbaImage, region: &ElementBounds) -> Result<image::RgbaImage, String> {
+    let (desktop_width, desktop_height) = image.dimensions();
+
+    let fits = region.x >= 0
+        && region.y >= 0
+        && region.width > 0
+        && region.height > 0
+        && region.x as u64 + region.width as u64 <= desktop_width as u64
+        && region.y as u64 + region.height as u64 <= desktop_height as u64;
+    if !fits {
+        return Err(format!(
+            "Region {}x{} at ({}, {}) is outside the desktop ({}x{})",
+            region.width, region.height, region.x, region.y, desktop_width, desktop_height
+        ));
+    }
+
+    Ok(image::imageops::crop_imm(
+        image,
+        region.x as u32,
+        region.y as u32,
+        region.width as u32,
+        region.height as u32,
+    )
+    .to_image())
+}
@@ -3,9 +3,16 @@
 pub mod automate;
 pub mod clipboard;
 pub mod connect;
+pub mod cursor;
 pub mod drive;
+pub mod frame;
+pub mod input;
 pub mod keyboard;
+pub mod keymap;
 pub mod locate;
 pub mod mouse;
+pub mod resize;
 pub mod screenshot;
 pub mod scroll;
+pub mod stream;
+pub mod wait_for_change;
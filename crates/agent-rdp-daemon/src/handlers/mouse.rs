@@ -6,184 +6,237 @@ use agent_rdp_protocol::{ErrorCode, MouseButton, MouseRequest, Response};
 use ironrdp::pdu::input::fast_path::FastPathInputEvent;
 use ironrdp::pdu::input::mouse::{MousePdu, PointerFlags};
 use tokio::sync::Mutex;
-use tokio::time::{sleep, Duration};
 use tracing::debug;
 
+use super::input::{self, InputStep};
 use crate::rdp_session::RdpSession;
 
-/// Handle a mouse request.
+/// Default number of intermediate moves for a drag.
+const DEFAULT_DRAG_STEPS: u32 = 10;
+
+/// Delay between drag events, so apps tracking pointer velocity see a real drag.
+const DRAG_STEP_DELAY_MS: u64 = 10;
+
+/// Delay between the press and release of a left click.
+const CLICK_HOLD_MS: u64 = 20;
+
+/// Rough distance in pixels covered by each move of a human-like path.
+const HUMAN_STEP_PX: f64 = 15.0;
+
+/// Fewest and most moves in a human-like path.
+const HUMAN_MIN_STEPS: u32 = 8;
+const HUMAN_MAX_STEPS: u32 = 60;
+
+/// Delay between the moves of a human-like path.
+const HUMAN_STEP_DELAY_MS: u64 = 8;
+
+/// How far a human-like path bows away from the straight line, relative to its length.
+const HUMAN_CURVE: f64 = 0.12;
+
+/// Handle a mouse request, with coordinates in the session's logical coordinate space.
+///
+/// Click, Drag and human-like moves release the session lock between events so
+/// streaming can proceed.
 pub async fn handle(
     rdp_session: &Arc<Mutex<Option<RdpSession>>>,
     action: MouseRequest,
 ) -> Response {
-    // For Click and Drag, we release the lock during sleep() to allow streaming
-    match action {
-        MouseRequest::Click { x, y } => {
-            debug!("Mouse click at ({}, {})", x, y);
-            // Send down event
-            let down_event =
-                vec![create_mouse_event(x, y, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN)];
-            {
-                let session = rdp_session.lock().await;
-                let rdp = match session.as_ref() {
-                    Some(rdp) => rdp,
-                    None => {
-                        return Response::error(
-                            ErrorCode::NotConnected,
-                            "Not connected to an RDP server",
-                        );
-                    }
-                };
-                if let Err(e) = rdp.send_input(down_event).await {
-                    return Response::error(ErrorCode::InternalError, e.to_string());
-                }
-            } // Lock released - streaming can proceed
-
-            // Small delay between down and up
-            sleep(Duration::from_millis(20)).await;
-
-            // Send up event
-            let up_event = vec![create_mouse_event(x, y, PointerFlags::LEFT_BUTTON)];
-            {
-                let session = rdp_session.lock().await;
-                let rdp = match session.as_ref() {
-                    Some(rdp) => rdp,
-                    None => {
-                        return Response::error(
-                            ErrorCode::NotConnected,
-                            "Not connected to an RDP server",
-                        );
-                    }
-                };
-                if let Err(e) = rdp.send_input(up_event).await {
-                    return Response::error(ErrorCode::InternalError, e.to_string());
-                }
-            }
-            return Response::ok();
-        }
+    run(rdp_session, action, true).await
+}
 
-        MouseRequest::Drag {
+/// Handle a mouse request whose coordinates are already desktop pixels, such as
+/// OCR matches and element bounds.
+pub async fn handle_device(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    action: MouseRequest,
+) -> Response {
+    run(rdp_session, action, false).await
+}
+
+async fn run(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    action: MouseRequest,
+    logical: bool,
+) -> Response {
+    match &action {
+        MouseRequest::Move { x, y, human } => {
+            debug!("Mouse move to ({}, {}), human: {}", x, y, human)
+        }
+        MouseRequest::Click { x, y, human } => {
+            debug!("Mouse click at ({}, {}), human: {}", x, y, human)
+        }
+        MouseRequest::Drag { from_x, from_y, to_x, to_y, steps } => debug!(
+            "Mouse drag from ({}, {}) to ({}, {}) in {} steps",
             from_x,
             from_y,
             to_x,
             to_y,
-        } => {
-            // Press at start position
-            let start_events = vec![
-                create_mouse_event(from_x, from_y, PointerFlags::MOVE),
-                create_mouse_event(from_x, from_y, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN),
-            ];
-            {
-                let session = rdp_session.lock().await;
-                let rdp = match session.as_ref() {
-                    Some(rdp) => rdp,
-                    None => {
-                        return Response::error(
-                            ErrorCode::NotConnected,
-                            "Not connected to an RDP server",
-                        );
-                    }
-                };
-                if let Err(e) = rdp.send_input(start_events).await {
-                    return Response::error(ErrorCode::InternalError, e.to_string());
-                }
-            } // Lock released - streaming can proceed
-
-            // Small delay for drag
-            sleep(Duration::from_millis(50)).await;
-
-            // Move to end and release
-            let end_events = vec![
-                create_mouse_event(to_x, to_y, PointerFlags::MOVE),
-                create_mouse_event(to_x, to_y, PointerFlags::LEFT_BUTTON),
-            ];
-            {
-                let session = rdp_session.lock().await;
-                let rdp = match session.as_ref() {
-                    Some(rdp) => rdp,
-                    None => {
-                        return Response::error(
-                            ErrorCode::NotConnected,
-                            "Not connected to an RDP server",
-                        );
-                    }
-                };
-                if let Err(e) = rdp.send_input(end_events).await {
-                    return Response::error(ErrorCode::InternalError, e.to_string());
-                }
-            }
-            return Response::ok();
-        }
-
-        // Other operations are single send_input calls with no sleeps
+            steps.unwrap_or(DEFAULT_DRAG_STEPS)
+        ),
+        MouseRequest::Down { x, y, button } => {
+            debug!("Mouse {:?} down at ({}, {})", button, x, y)
+        }
+        MouseRequest::Up { x, y, button } => debug!("Mouse {:?} up at ({}, {})", button, x, y),
+        MouseRequest::ReleaseAll => return release_all(rdp_session).await,
         _ => {}
     }
 
-    // Handle remaining operations with a single lock scope
-    let session = rdp_session.lock().await;
-    let rdp = match session.as_ref() {
-        Some(rdp) => rdp,
-        None => {
-            return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+    // Human-like paths start from the last known pointer position
+    let (action, pointer) = match *rdp_session.lock().await {
+        Some(ref rdp) if logical => {
+            (to_device(action, |x, y| rdp.to_device(x, y)), rdp.pointer())
         }
+        Some(ref rdp) => (action, rdp.pointer()),
+        None => (action, Default::default()),
     };
+    input::send_steps(rdp_session, input_steps(action, (pointer.x, pointer.y))).await
+}
 
-    let result = match action {
-        MouseRequest::Move { x, y } => {
-            debug!("Mouse move to ({}, {})", x, y);
-            let events = vec![create_mouse_event(x, y, PointerFlags::MOVE)];
-            rdp.send_input(events).await
+/// Map every point of a mouse action with `map`, from the logical coordinate
+/// space to desktop pixels.
+pub(crate) fn to_device(action: MouseRequest, map: impl Fn(u16, u16) -> (u16, u16)) -> MouseRequest {
+    match action {
+        MouseRequest::Move { x, y, human } => {
+            let (x, y) = map(x, y);
+            MouseRequest::Move { x, y, human }
+        }
+        MouseRequest::Click { x, y, human } => {
+            let (x, y) = map(x, y);
+            MouseRequest::Click { x, y, human }
         }
-
         MouseRequest::RightClick { x, y } => {
-            let events = vec![
-                create_mouse_event(x, y, PointerFlags::RIGHT_BUTTON | PointerFlags::DOWN),
-                create_mouse_event(x, y, PointerFlags::RIGHT_BUTTON),
-            ];
-            rdp.send_input(events).await
+            let (x, y) = map(x, y);
+            MouseRequest::RightClick { x, y }
         }
-
         MouseRequest::DoubleClick { x, y } => {
-            let events = vec![
-                create_mouse_event(x, y, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN),
-                create_mouse_event(x, y, PointerFlags::LEFT_BUTTON),
-                create_mouse_event(x, y, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN),
-                create_mouse_event(x, y, PointerFlags::LEFT_BUTTON),
-            ];
-            rdp.send_input(events).await
+            let (x, y) = map(x, y);
+            MouseRequest::DoubleClick { x, y }
         }
-
         MouseRequest::MiddleClick { x, y } => {
-            let events = vec![
-                create_mouse_event(
-                    x,
-                    y,
-                    PointerFlags::MIDDLE_BUTTON_OR_WHEEL | PointerFlags::DOWN,
-                ),
-                create_mouse_event(x, y, PointerFlags::MIDDLE_BUTTON_OR_WHEEL),
-            ];
-            rdp.send_input(events).await
+            let (x, y) = map(x, y);
+            MouseRequest::MiddleClick { x, y }
+        }
+        MouseRequest::Drag { from_x, from_y, to_x, to_y, steps } => {
+            let (from_x, from_y) = map(from_x, from_y);
+            let (to_x, to_y) = map(to_x, to_y);
+            MouseRequest::Drag { from_x, from_y, to_x, to_y, steps }
+        }
+        MouseRequest::Down { x, y, button } => {
+            let (x, y) = map(x, y);
+            MouseRequest::Down { x, y, button }
+        }
+        MouseRequest::Up { x, y, button } => {
+            let (x, y) = map(x, y);
+            MouseRequest::Up { x, y, button }
+        }
+        // At the pointer, which is already in desktop pixels
+        MouseRequest::ButtonDown { .. } | MouseRequest::ButtonUp { .. } | MouseRequest::ReleaseAll => {
+            action
         }
+    }
+}
 
-        MouseRequest::ButtonDown { button } => {
-            let flags = button_to_flags(button) | PointerFlags::DOWN;
-            let events = vec![create_mouse_event(0, 0, flags | PointerFlags::MOVE)];
-            rdp.send_input(events).await
+/// Release every mouse button the session still has held down.
+async fn release_all(rdp_session: &Arc<Mutex<Option<RdpSession>>>) -> Response {
+    let session = rdp_session.lock().await;
+    let Some(ref rdp) = *session else {
+        return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+    };
+    match rdp.release_all_buttons().await {
+        Ok(count) => {
+            debug!("Released {} held mouse buttons", count);
+            Response::ok()
         }
+        Err(e) => Response::error(ErrorCode::InternalError, e.to_string()),
+    }
+}
+
+/// Build the input steps for a mouse action, with the pointer starting at `pointer`.
+///
+/// `ReleaseAll` depends on the session's held buttons, so it has no steps here.
+pub(crate) fn input_steps(action: MouseRequest, pointer: (u16, u16)) -> Vec<InputStep> {
+    let single = |events: Vec<FastPathInputEvent>| vec![InputStep::new(events, 0)];
 
-        MouseRequest::ButtonUp { button } => {
-            let flags = button_to_flags(button);
-            let events = vec![create_mouse_event(0, 0, flags | PointerFlags::MOVE)];
-            rdp.send_input(events).await
+    match action {
+        MouseRequest::Move { x, y, human: false } => {
+            single(vec![create_mouse_event(x, y, PointerFlags::MOVE)])
         }
 
-        // Click and Drag are handled above
-        MouseRequest::Click { .. } | MouseRequest::Drag { .. } => unreachable!(),
-    };
+        MouseRequest::Move { x, y, human: true } => human_move_steps(pointer, (x, y)),
 
-    match result {
-        Ok(()) => Response::ok(),
-        Err(e) => Response::error(ErrorCode::InternalError, e.to_string()),
+        MouseRequest::Click { x, y, human } => {
+            let mut steps = if human {
+                human_move_steps(pointer, (x, y))
+            } else {
+                Vec::new()
+            };
+            steps.push(InputStep::new(
+                vec![create_mouse_event(x, y, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN)],
+                CLICK_HOLD_MS,
+            ));
+            steps.push(InputStep::new(
+                vec![create_mouse_event(x, y, PointerFlags::LEFT_BUTTON)],
+                0,
+            ));
+            steps
+        }
+
+        MouseRequest::RightClick { x, y } => single(vec![
+            create_mouse_event(x, y, PointerFlags::RIGHT_BUTTON | PointerFlags::DOWN),
+            create_mouse_event(x, y, PointerFlags::RIGHT_BUTTON),
+        ]),
+
+        MouseRequest::DoubleClick { x, y } => single(vec![
+            create_mouse_event(x, y, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN),
+            create_mouse_event(x, y, PointerFlags::LEFT_BUTTON),
+            create_mouse_event(x, y, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN),
+            create_mouse_event(x, y, PointerFlags::LEFT_BUTTON),
+        ]),
+
+        MouseRequest::MiddleClick { x, y } => single(vec![
+            create_mouse_event(
+                x,
+                y,
+                PointerFlags::MIDDLE_BUTTON_OR_WHEEL | PointerFlags::DOWN,
+            ),
+            create_mouse_event(x, y, PointerFlags::MIDDLE_BUTTON_OR_WHEEL),
+        ]),
+
+        // One event at a time with a short delay in between
+        MouseRequest::Drag {
+            from_x,
+            from_y,
+            to_x,
+            to_y,
+            steps,
+        } => drag_events(from_x, from_y, to_x, to_y, steps.unwrap_or(DEFAULT_DRAG_STEPS))
+            .into_iter()
+            .map(|event| InputStep::new(vec![event], DRAG_STEP_DELAY_MS))
+            .collect(),
+
+        MouseRequest::ButtonDown { button } => single(vec![create_mouse_event(
+            pointer.0,
+            pointer.1,
+            button_to_flags(button) | PointerFlags::DOWN,
+        )]),
+
+        MouseRequest::ButtonUp { button } => single(vec![create_mouse_event(
+            pointer.0,
+            pointer.1,
+            button_to_flags(button),
+        )]),
+
+        MouseRequest::Down { x, y, button } => single(vec![create_mouse_event(
+            x,
+            y,
+            button_to_flags(button) | PointerFlags::DOWN,
+        )]),
+
+        MouseRequest::Up { x, y, button } => {
+            single(vec![create_mouse_event(x, y, button_to_flags(button))])
+        }
+
+        MouseRequest::ReleaseAll => Vec::new(),
     }
 }
 
@@ -197,6 +250,86 @@ fn create_mouse_event(x: u16, y: u16, flags: PointerFlags) -> FastPathInputEvent
     })
 }
 
+/// Build the events for a left-button drag.
+///
+/// Moves to and presses at the start, then steps toward the end with plain moves
+/// (the button stays held, so DOWN is not re-sent), then releases at the end.
+fn drag_events(from_x: u16, from_y: u16, to_x: u16, to_y: u16, steps: u32) -> Vec<FastPathInputEvent> {
+    let mut events = vec![
+        create_mouse_event(from_x, from_y, PointerFlags::MOVE),
+        create_mouse_event(from_x, from_y, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN),
+    ];
+    events.extend(
+        linear_path((from_x, from_y), (to_x, to_y), steps)
+            .into_iter()
+            .map(|(x, y)| create_mouse_event(x, y, PointerFlags::MOVE)),
+    );
+    events.push(create_mouse_event(to_x, to_y, PointerFlags::LEFT_BUTTON));
+    events
+}
+
+/// Points evenly spaced along the straight line from `from` to `to`, ending at `to`.
+fn linear_path(from: (u16, u16), to: (u16, u16), steps: u32) -> Vec<(u16, u16)> {
+    let steps = steps.max(1) as i64;
+    (1..=steps)
+        .map(|step| {
+            let x = from.0 as i64 + (to.0 as i64 - from.0 as i64) * step / steps;
+            let y = from.1 as i64 + (to.1 as i64 - from.1 as i64) * step / steps;
+            (x as u16, y as u16)
+        })
+        .collect()
+}
+
+/// Points along a human-like path from `from` to `to`, ending at `to`.
+///
+/// The path is a cubic Bézier curve bowing to one side of the straight line,
+/// sampled with ease-in-out timing so the pointer speeds up and then slows
+/// down as it arrives. Longer paths get more points.
+fn human_path(from: (u16, u16), to: (u16, u16)) -> Vec<(u16, u16)> {
+    let (x0, y0) = (from.0 as f64, from.1 as f64);
+    let (dx, dy) = (to.0 as f64 - x0, to.1 as f64 - y0);
+    let distance = dx.hypot(dy);
+    if distance < 1.0 {
+        return vec![to];
+    }
+
+    let steps = ((distance / HUMAN_STEP_PX) as u32).clamp(HUMAN_MIN_STEPS, HUMAN_MAX_STEPS);
+    // Control points a third and two thirds of the way, pushed off the line
+    let (nx, ny) = (-dy * HUMAN_CURVE, dx * HUMAN_CURVE);
+    let c1 = (x0 + dx / 3.0 + nx, y0 + dy / 3.0 + ny);
+    let c2 = (x0 + dx * 2.0 / 3.0 + nx / 2.0, y0 + dy * 2.0 / 3.0 + ny / 2.0);
+    let p3 = (to.0 as f64, to.1 as f64);
+
+    let mut points: Vec<(u16, u16)> = Vec::new();
+    for step in 1..steps {
+        let linear = step as f64 / steps as f64;
+        let t = linear * linear * (3.0 - 2.0 * linear);
+        let u = 1.0 - t;
+        let bezier = |p0: f64, p1: f64, p2: f64, p3: f64| {
+            u * u * u * p0 + 3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t * p3
+        };
+        let x = bezier(x0, c1.0, c2.0, p3.0).round().clamp(0.0, u16::MAX as f64) as u16;
+        let y = bezier(y0, c1.1, c2.1, p3.1).round().clamp(0.0, u16::MAX as f64) as u16;
+        if points.last() != Some(&(x, y)) {
+            points.push((x, y));
+        }
+    }
+    if points.last() != Some(&to) {
+        points.push(to);
+    }
+    points
+}
+
+/// Steps moving the pointer along a human-like path, one move at a time.
+fn human_move_steps(from: (u16, u16), to: (u16, u16)) -> Vec<InputStep> {
+    human_path(from, to)
+        .into_iter()
+        .map(|(x, y)| {
+            InputStep::new(vec![create_mouse_event(x, y, PointerFlags::MOVE)], HUMAN_STEP_DELAY_MS)
+        })
+        .collect()
+}
+
 /// Convert MouseButton to PointerFlags.
 fn button_to_flags(button: MouseButton) -> PointerFlags {
     match button {
@@ -205,3 +338,127 @@ fn button_to_flags(button: MouseButton) -> PointerFlags {
         MouseButton::Middle => PointerFlags::MIDDLE_BUTTON_OR_WHEEL,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mouse_event(event: &FastPathInputEvent) -> (u16, u16, PointerFlags) {
+        match event {
+            FastPathInputEvent::MouseEvent(pdu) => (pdu.x_position, pdu.y_position, pdu.flags),
+            _ => panic!("Expected mouse event"),
+        }
+    }
+
+    #[test]
+    fn test_drag_events() {
+        let events: Vec<_> = drag_events(10, 20, 40, 50, 3).iter().map(mouse_event).collect();
+
+        assert_eq!(
+            events,
+            vec![
+                (10, 20, PointerFlags::MOVE),
+                (10, 20, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN),
+                (20, 30, PointerFlags::MOVE),
+                (30, 40, PointerFlags::MOVE),
+                (40, 50, PointerFlags::MOVE),
+                (40, 50, PointerFlags::LEFT_BUTTON),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drag_events_backwards_without_steps() {
+        // Zero steps still moves to the end before releasing
+        let events: Vec<_> = drag_events(100, 100, 50, 80, 0).iter().map(mouse_event).collect();
+
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[2], (50, 80, PointerFlags::MOVE));
+        assert!(events[2..].iter().all(|(_, _, flags)| !flags.contains(PointerFlags::DOWN)));
+    }
+
+    #[test]
+    fn test_human_path() {
+        let path = human_path((100, 100), (700, 400));
+        assert_eq!(path.last(), Some(&(700, 400)));
+        assert!(path.len() >= HUMAN_MIN_STEPS as usize - 1);
+        assert!(path.len() <= HUMAN_MAX_STEPS as usize);
+
+        // Curves off the straight line, but heads steadily toward the target
+        let off_line = path.iter().any(|&(x, y)| {
+            let expected_y = 100.0 + (x as f64 - 100.0) * 300.0 / 600.0;
+            (y as f64 - expected_y).abs() > 5.0
+        });
+        assert!(off_line);
+        assert!(path.windows(2).all(|pair| pair[1].0 >= pair[0].0));
+
+        // Eased: the first and last moves are shorter than the middle ones
+        let gap = |i: usize| {
+            let (a, b) = (path[i], path[i + 1]);
+            (b.0 as f64 - a.0 as f64).hypot(b.1 as f64 - a.1 as f64)
+        };
+        let middle = gap(path.len() / 2);
+        assert!(gap(0) < middle);
+        assert!(gap(path.len() - 2) < middle);
+
+        assert_eq!(human_path((5, 5), (5, 5)), vec![(5, 5)]);
+    }
+
+    #[test]
+    fn test_human_click_steps() {
+        let steps = input_steps(MouseRequest::Click { x: 300, y: 40, human: true }, (0, 0));
+        let events: Vec<_> =
+            steps.iter().flat_map(|step| step.events.iter().map(mouse_event)).collect();
+
+        let (press, release) = (events[events.len() - 2], events[events.len() - 1]);
+        assert_eq!(press, (300, 40, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN));
+        assert_eq!(release, (300, 40, PointerFlags::LEFT_BUTTON));
+        assert!(events.len() > 3);
+        let moves = &events[..events.len() - 2];
+        assert!(moves.iter().all(|(_, _, flags)| *flags == PointerFlags::MOVE));
+        assert_eq!(events[events.len() - 3], (300, 40, PointerFlags::MOVE));
+
+        // Without human, a click doesn't move first
+        let steps = input_steps(MouseRequest::Click { x: 300, y: 40, human: false }, (0, 0));
+        assert_eq!(steps.len(), 2);
+    }
+
+    #[test]
+    fn test_to_device() {
+        let double = |x: u16, y: u16| (x * 2, y * 2);
+        match to_device(MouseRequest::Drag { from_x: 1, from_y: 2, to_x: 3, to_y: 4, steps: None }, double) {
+            MouseRequest::Drag { from_x: 2, from_y: 4, to_x: 6, to_y: 8, steps: None } => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        match to_device(MouseRequest::Click { x: 5, y: 6, human: true }, double) {
+            MouseRequest::Click { x: 10, y: 12, human: true } => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_button_down_up_steps() {
+        let events = |action| -> Vec<_> {
+            input_steps(action, (50, 60))
+                .iter()
+                .flat_map(|step| step.events.iter().map(mouse_event))
+                .collect()
+        };
+
+        assert_eq!(
+            events(MouseRequest::Down { x: 5, y: 6, button: MouseButton::Right }),
+            vec![(5, 6, PointerFlags::RIGHT_BUTTON | PointerFlags::DOWN)]
+        );
+        assert_eq!(
+            events(MouseRequest::Up { x: 7, y: 8, button: MouseButton::Right }),
+            vec![(7, 8, PointerFlags::RIGHT_BUTTON)]
+        );
+
+        // Without a position, the button is pressed where the pointer is
+        assert_eq!(
+            events(MouseRequest::ButtonDown { button: MouseButton::Left }),
+            vec![(50, 60, PointerFlags::LEFT_BUTTON | PointerFlags::DOWN)]
+        );
+        assert!(events(MouseRequest::ReleaseAll).is_empty());
+    }
+}
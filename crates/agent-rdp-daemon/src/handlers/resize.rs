@@ -0,0 +1,47 @@
+//! Desktop resize handler.
+
+use std::sync::Arc;
+
+use agent_rdp_protocol::{ErrorCode, Response, ResponseData};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::rdp_session::{RdpError, RdpSession};
+
+/// Smallest desktop dimension accepted by the display control channel (MS-RDPEDISP).
+const MIN_DESKTOP_SIZE: u16 = 200;
+
+/// Largest desktop dimension accepted by the display control channel (MS-RDPEDISP).
+const MAX_DESKTOP_SIZE: u16 = 8192;
+
+/// Handle a resize request.
+pub async fn handle(rdp_session: &Arc<Mutex<Option<RdpSession>>>, width: u16, height: u16) -> Response {
+    let in_range = |size: u16| (MIN_DESKTOP_SIZE..=MAX_DESKTOP_SIZE).contains(&size);
+    if !in_range(width) || !in_range(height) {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            format!(
+                "Desktop size {}x{} is out of range (each dimension must be {}-{})",
+                width, height, MIN_DESKTOP_SIZE, MAX_DESKTOP_SIZE
+            ),
+        );
+    }
+
+    // The monitor layout PDU requires an even width
+    let width = width & !1;
+    debug!("Resize request: {}x{}", width, height);
+
+    let session = rdp_session.lock().await;
+    let rdp = match session.as_ref() {
+        Some(rdp) => rdp,
+        None => {
+            return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+        }
+    };
+
+    match rdp.resize(width, height).await {
+        Ok((width, height)) => Response::success(ResponseData::Resized { width, height }),
+        Err(e @ RdpError::ResizeNotSupported) => Response::error(ErrorCode::NotSupported, e.to_string()),
+        Err(e) => Response::error(ErrorCode::InternalError, e.to_string()),
+    }
+}
@@ -1,20 +1,53 @@
 //! Screenshot handler.
 
+use std::hash::Hasher;
 use std::io::Cursor;
 use std::sync::Arc;
+use std::time::Duration;
 
-use agent_rdp_protocol::{ErrorCode, ImageFormat, Response, ResponseData, ScreenshotRequest};
+use agent_rdp_protocol::{
+    ErrorCode, ImageFormat, Response, ResponseData, ScreenshotRequest, DEFAULT_STABLE_TIMEOUT_MS,
+};
 use base64::Engine;
-use image::ImageFormat as ImgFormat;
+use image::codecs::avif::AvifEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat as ImgFormat};
 use tokio::sync::Mutex;
 
+use super::wait_for_change::{self, MAX_WAIT_MS};
 use crate::rdp_session::RdpSession;
 
+/// AVIF encoder speed (1-10, higher is faster).
+const AVIF_SPEED: u8 = 8;
+
+/// AVIF encoder quality (1-100).
+const AVIF_QUALITY: u8 = 80;
+
 /// Handle a screenshot request.
 pub async fn handle(
     rdp_session: &Arc<Mutex<Option<RdpSession>>>,
     params: ScreenshotRequest,
 ) -> Response {
+    // Let animations finish first; the session stays unlocked meanwhile
+    let (stable, stable_wait_ms) = match params.stable_ms {
+        Some(stable_ms) => {
+            let timeout_ms = params.stable_timeout_ms.unwrap_or(DEFAULT_STABLE_TIMEOUT_MS);
+            if timeout_ms > MAX_WAIT_MS {
+                return Response::error(
+                    ErrorCode::InvalidRequest,
+                    format!("Stable timeout exceeds {} ms", MAX_WAIT_MS),
+                );
+            }
+            let quiet = Duration::from_millis(stable_ms);
+            let timeout = Duration::from_millis(timeout_ms);
+            match wait_for_change::wait_stable(rdp_session, quiet, timeout).await {
+                Ok((stable, waited_ms)) => (Some(stable), Some(waited_ms)),
+                Err(response) => return response,
+            }
+        }
+        None => (None, None),
+    };
+
     let session = rdp_session.lock().await;
 
     let rdp = match session.as_ref() {
@@ -26,7 +59,11 @@ pub async fn handle(
 
     // Get the current desktop image from the RDP session
     // The background frame processor keeps this up-to-date
-    let (width, height, data) = rdp.get_image_data();
+    let (width, height, mut data) = rdp.get_image_data();
+    let first_frame_received = rdp.first_frame_received();
+    if params.draw_cursor {
+        rdp.pointer().draw(&mut data, width, height);
+    }
     let width = width as u32;
     let height = height as u32;
 
@@ -41,31 +78,155 @@ pub async fn handle(
         }
     };
 
-    // Encode to requested format
-    let format = match params.format {
-        ImageFormat::Png => ImgFormat::Png,
-        ImageFormat::Jpeg => ImgFormat::Jpeg,
+    // Crop to the requested monitor, then to the region within it
+    let rgba_image = match params.monitor {
+        Some(index) => match rdp.monitors().get(index as usize) {
+            Some(monitor) => image::imageops::crop_imm(
+                &rgba_image,
+                monitor.x as u32,
+                monitor.y as u32,
+                monitor.width,
+                monitor.height,
+            )
+            .to_image(),
+            None => {
+                return Response::error(
+                    ErrorCode::InvalidRequest,
+                    format!("No monitor {} (the session has {})", index, rdp.monitors().len()),
+                );
+            }
+        },
+        None => rgba_image,
     };
 
-    let format_str = match params.format {
-        ImageFormat::Png => "png",
-        ImageFormat::Jpeg => "jpeg",
+    // Crop to the requested region (an empty region keeps the full frame)
+    let whole_desktop = params.monitor.is_none()
+        && params.x.is_none()
+        && params.y.is_none()
+        && params.width.is_none()
+        && params.height.is_none();
+    let rgba_image = match crop_region(rgba_image, &params) {
+        Ok(img) => img,
+        Err(message) => return Response::error(ErrorCode::InvalidRequest, message),
     };
 
-    let mut buffer = Cursor::new(Vec::new());
-    if let Err(e) = rgba_image.write_to(&mut buffer, format) {
-        return Response::error(
-            ErrorCode::InternalError,
-            format!("Failed to encode image: {}", e),
-        );
+    // Whole-desktop captures come in the logical coordinate space, so points
+    // read off them can be clicked as they are
+    let rgba_image = match rdp.coordinate_space() {
+        Some((width, height)) if whole_desktop => image::imageops::resize(
+            &rgba_image,
+            u32::from(width),
+            u32::from(height),
+            FilterType::Triangle,
+        ),
+        _ => rgba_image,
+    };
+    let (width, height) = rgba_image.dimensions();
+
+    // Polling clients skip the encode (and the transfer) when nothing changed
+    let etag = etag(&rgba_image, params.format);
+    if params.if_changed_since.as_deref() == Some(etag.as_str()) {
+        return Response::success(ResponseData::NotModified { etag, width, height });
     }
 
-    let base64_data = base64::engine::general_purpose::STANDARD.encode(buffer.into_inner());
+    let data = match encode_image(rgba_image, params.format) {
+        Ok(data) => data,
+        Err(msg) => return Response::error(ErrorCode::InternalError, msg),
+    };
+    let size_bytes = data.len() as u64;
+    let base64_data = base64::engine::general_purpose::STANDARD.encode(data);
 
     Response::success(ResponseData::Screenshot {
         width,
         height,
-        format: format_str.to_string(),
+        format: format_name(params.format).to_string(),
+        size_bytes,
         base64: base64_data,
+        stable_wait_ms,
+        stable,
+        etag,
+        first_frame_received,
     })
 }
+
+/// Hash of a capture's pixels, size and format, as a hex string.
+///
+/// Only meant to be compared with earlier values from the same daemon.
+fn etag(image: &image::RgbaImage, format: ImageFormat) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    hasher.write(format_name(format).as_bytes());
+    hasher.write_u32(image.width());
+    hasher.write_u32(image.height());
+    hasher.write(image.as_raw());
+    format!("{:016x}", hasher.finish())
+}
+
+/// Name of an image format as reported in responses.
+pub(crate) fn format_name(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Png => "png",
+        ImageFormat::Jpeg => "jpeg",
+        ImageFormat::Webp => "webp",
+        ImageFormat::Avif => "avif",
+    }
+}
+
+/// Encode a captured image in the requested format.
+pub(crate) fn encode_image(
+    rgba_image: image::RgbaImage,
+    format: ImageFormat,
+) -> Result<Vec<u8>, String> {
+    let mut buffer = Cursor::new(Vec::new());
+    let encoded = match format {
+        ImageFormat::Png => rgba_image.write_to(&mut buffer, ImgFormat::Png),
+        // JPEG has no alpha channel
+        ImageFormat::Jpeg => DynamicImage::ImageRgba8(rgba_image)
+            .to_rgb8()
+            .write_to(&mut buffer, ImgFormat::Jpeg),
+        ImageFormat::Webp => rgba_image.write_to(&mut buffer, ImgFormat::WebP),
+        // The default AVIF speed is too slow for interactive use
+        ImageFormat::Avif => rgba_image.write_with_encoder(AvifEncoder::new_with_speed_quality(
+            &mut buffer,
+            AVIF_SPEED,
+            AVIF_QUALITY,
+        )),
+    };
+    match encoded {
+        Ok(()) => Ok(buffer.into_inner()),
+        Err(e) => Err(format!("Failed to encode image: {}", e)),
+    }
+}
+
+/// Crop the desktop image to the requested region, which must lie within the desktop.
+///
+/// Missing coordinates default to the top-left corner and missing sizes extend to the
+/// desktop edge. No region, or a zero width or height, returns the full frame.
+fn crop_region(
+    image: image::RgbaImage,
+    params: &ScreenshotRequest,
+) -> Result<image::RgbaImage, String> {
+    let no_region =
+        params.x.is_none() && params.y.is_none() && params.width.is_none() && params.height.is_none();
+    if no_region || params.width == Some(0) || params.height == Some(0) {
+        return Ok(image);
+    }
+
+    let (desktop_width, desktop_height) = image.dimensions();
+    let x = params.x.unwrap_or(0);
+    let y = params.y.unwrap_or(0);
+    let width = params.width.unwrap_or(desktop_width.saturating_sub(x));
+    let height = params.height.unwrap_or(desktop_height.saturating_sub(y));
+
+    let fits = width > 0
+        && height > 0
+        && x as u64 + width as u64 <= desktop_width as u64
+        && y as u64 + height as u64 <= desktop_height as u64;
+    if !fits {
+        return Err(format!(
+            "Region {}x{} at ({}, {}) is outside the desktop ({}x{})",
+            width, height, x, y, desktop_width, desktop_height
+        ));
+    }
+
+    Ok(image::imageops::crop_imm(&image, x, y, width, height).to_image())
+}
@@ -23,22 +23,35 @@ pub async fn handle(
         }
     };
 
+    let events = scroll_events(&to_device(params, rdp), rdp.width(), rdp.height());
+
+    match rdp.send_input(events).await {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::error(ErrorCode::InternalError, e.to_string()),
+    }
+}
+
+/// Map a scroll position from the logical coordinate space to desktop pixels.
+pub(crate) fn to_device(mut params: ScrollRequest, rdp: &RdpSession) -> ScrollRequest {
+    let (x, y) = rdp.to_device(params.x.unwrap_or(0), params.y.unwrap_or(0));
+    params.x = params.x.map(|_| x);
+    params.y = params.y.map(|_| y);
+    params
+}
+
+/// Build the events for a scroll request on a desktop of the given size.
+pub(crate) fn scroll_events(params: &ScrollRequest, width: u16, height: u16) -> Vec<FastPathInputEvent> {
     // Use specified position or default to center of screen
-    let x = params.x.unwrap_or(rdp.width() / 2);
-    let y = params.y.unwrap_or(rdp.height() / 2);
+    let x = params.x.unwrap_or(width / 2);
+    let y = params.y.unwrap_or(height / 2);
 
-    let events = match params.direction {
+    match params.direction {
         ScrollDirection::Up | ScrollDirection::Down => {
             create_vertical_scroll_events(x, y, params.direction, params.amount)
         }
         ScrollDirection::Left | ScrollDirection::Right => {
             create_horizontal_scroll_events(x, y, params.direction, params.amount)
         }
-    };
-
-    match rdp.send_input(events).await {
-        Ok(()) => Response::ok(),
-        Err(e) => Response::error(ErrorCode::InternalError, e.to_string()),
     }
 }
 
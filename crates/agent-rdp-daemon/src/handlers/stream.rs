@@ -0,0 +1,55 @@
+//! WebSocket stream settings handler.
+
+use agent_rdp_protocol::{ErrorCode, Response, ResponseData};
+use tracing::debug;
+
+use crate::daemon::{SharedStreamFps, SharedWsHandle};
+use crate::ws_server::MAX_STREAM_FPS;
+
+/// Handle a stream-config request, applying the given settings to the running stream.
+pub async fn handle_config(
+    ws_handle: &SharedWsHandle,
+    stream_fps: &SharedStreamFps,
+    fps: Option<u32>,
+    quality: Option<u8>,
+    delta: Option<bool>,
+) -> Response {
+    if let Some(fps) = fps.filter(|fps| !(1..=MAX_STREAM_FPS).contains(fps)) {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            format!("Frame rate {} is out of range (must be 1-{})", fps, MAX_STREAM_FPS),
+        );
+    }
+    if let Some(quality) = quality.filter(|quality| !(1..=100).contains(quality)) {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            format!("JPEG quality {} is out of range (must be 1-100)", quality),
+        );
+    }
+
+    let ws_handle = ws_handle.lock().await;
+    let Some(ref handle) = *ws_handle else {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            "Streaming is not enabled for this session (connect with --stream-port)",
+        );
+    };
+
+    debug!("Stream config: fps={:?} quality={:?} delta={:?}", fps, quality, delta);
+    if let Some(fps) = fps {
+        // The daemon rebuilds its frame timer when this changes
+        stream_fps.send_replace(fps);
+    }
+    if let Some(quality) = quality {
+        handle.set_jpeg_quality(quality);
+    }
+    if let Some(delta) = delta {
+        handle.set_delta(delta);
+    }
+
+    Response::success(ResponseData::StreamConfig {
+        fps: *stream_fps.borrow(),
+        quality: handle.jpeg_quality(),
+        delta: handle.delta(),
+    })
+}
@@ -0,0 +1,154 @@
+//! Screen change wait handler.
+//!
+//! Blocks until the pixels of the desktop (or a region of it) differ from when
+//! the request arrived, so agents don't have to poll screenshots. Also waits
+//! for the desktop to settle before a screenshot.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use agent_rdp_protocol::{ElementBounds, ErrorCode, Response, ResponseData};
+use tokio::sync::Mutex;
+use tokio::time::{timeout_at, Duration};
+use tracing::debug;
+
+use crate::rdp_session::RdpSession;
+
+/// Longest wait accepted, so a request can't hold a client indefinitely.
+pub const MAX_WAIT_MS: u64 = 60_000;
+
+/// Handle a wait-for-change request.
+///
+/// The session lock is only held while hashing, so input and screenshots can
+/// proceed during the wait.
+pub async fn handle(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    timeout_ms: u64,
+    region: Option<ElementBounds>,
+) -> Response {
+    if timeout_ms > MAX_WAIT_MS {
+        return Response::error(
+            ErrorCode::InvalidRequest,
+            format!("Timeout exceeds {} ms", MAX_WAIT_MS),
+        );
+    }
+
+    let start = Instant::now();
+    let (mut frames, region, initial_hash) = {
+        let session = rdp_session.lock().await;
+        let Some(ref rdp) = *session else {
+            return Response::error(ErrorCode::NotConnected, "Not connected to an RDP server");
+        };
+
+        let region = match region.as_ref() {
+            Some(bounds) => match region_rect(bounds, rdp.width(), rdp.height()) {
+                Ok(rect) => Some(rect),
+                Err(msg) => return Response::error(ErrorCode::InvalidRequest, msg),
+            },
+            None => None,
+        };
+
+        // Subscribe before hashing, so an update in between is not missed
+        let frames = rdp.subscribe_frames();
+        (frames, region, rdp.image_hash(region))
+    };
+
+    debug!(
+        "Waiting up to {} ms for a change in {:?}",
+        timeout_ms, region
+    );
+    let deadline = tokio::time::Instant::from_std(start) + Duration::from_millis(timeout_ms);
+
+    let changed = loop {
+        match timeout_at(deadline, frames.changed()).await {
+            Err(_) => break false,
+            Ok(Err(_)) => {
+                return Response::error(ErrorCode::NotConnected, "Session closed while waiting");
+            }
+            Ok(Ok(())) => {}
+        }
+
+        let hash = {
+            let session = rdp_session.lock().await;
+            let Some(ref rdp) = *session else {
+                return Response::error(ErrorCode::NotConnected, "Session closed while waiting");
+            };
+            rdp.image_hash(region)
+        };
+        if hash != initial_hash {
+            break true;
+        }
+    };
+
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+    debug!("Screen changed: {} after {} ms", changed, elapsed_ms);
+    Response::success(ResponseData::ScreenChange {
+        changed,
+        elapsed_ms,
+    })
+}
+
+/// Wait until no frame has arrived for `quiet`, or until `timeout` has passed.
+///
+/// Returns whether the desktop settled and how long the wait took in milliseconds.
+pub async fn wait_stable(
+    rdp_session: &Arc<Mutex<Option<RdpSession>>>,
+    quiet: Duration,
+    timeout: Duration,
+) -> Result<(bool, u64), Response> {
+    let start = Instant::now();
+    let mut frames = {
+        let session = rdp_session.lock().await;
+        let Some(ref rdp) = *session else {
+            return Err(Response::error(ErrorCode::NotConnected, "Not connected to an RDP server"));
+        };
+        rdp.subscribe_frames()
+    };
+
+    let deadline = tokio::time::Instant::from_std(start) + timeout;
+    // Each frame restarts the quiet period
+    let stable = loop {
+        let quiet_until = tokio::time::Instant::now() + quiet;
+        match timeout_at(quiet_until.min(deadline), frames.changed()).await {
+            Err(_) => break quiet_until <= deadline,
+            Ok(Err(_)) => {
+                return Err(Response::error(
+                    ErrorCode::NotConnected,
+                    "Session closed while waiting",
+                ));
+            }
+            Ok(Ok(())) => {}
+        }
+    };
+
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+    debug!("Desktop stable: {} after {} ms", stable, elapsed_ms);
+    Ok((stable, elapsed_ms))
+}
+
+/// Convert a region to (x, y, width, height), checking it lies within the desktop.
+fn region_rect(
+    region: &ElementBounds,
+    desktop_width: u16,
+    desktop_height: u16,
+) -> Result<(u16, u16, u16, u16), String> {
+    let fits = region.x >= 0
+        && region.y >= 0
+        && region.width > 0
+        && region.height > 0
+        && region.x as i64 + region.width as i64 <= desktop_width as i64
+        && region.y as i64 + region.height as i64 <= desktop_height as i64;
+    if !fits {
+        return Err(format!(
+            "Region {}x{} at ({}, {}) is outside the desktop ({}x{})",
+            region.width, region.height, region.x, region.y, desktop_width, desktop_height
+        ));
+    }
+
+    Ok((
+        region.x as u16,
+        region.y as u16,
+        region.width as u16,
+        region.height as u16,
+    ))
+}
@@ -3,8 +3,9 @@
 use std::path::Path;
 use std::io;
 
+use agent_rdp_protocol::{FrameHeader, FRAME_HEADER_LEN};
 use thiserror::Error;
-use tokio::io::{ReadHalf, WriteHalf};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
 use tracing::info;
 
 #[derive(Error, Debug)]
@@ -118,3 +119,39 @@ impl IpcStreamExt for IpcStream {
         tokio::io::split(self)
     }
 }
+
+/// Read one length-prefixed frame as its JSON and binary regions.
+///
+/// Returns `None` when the peer closed the connection between frames.
+pub async fn read_frame<R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> io::Result<Option<(Vec<u8>, Vec<u8>)>> {
+    let mut header = [0u8; FRAME_HEADER_LEN];
+    match reader.read_exact(&mut header).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let header = FrameHeader::decode(header)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut json = vec![0u8; header.json_len];
+    reader.read_exact(&mut json).await?;
+    let mut binary = vec![0u8; header.binary_len];
+    reader.read_exact(&mut binary).await?;
+    Ok(Some((json, binary)))
+}
+
+/// Write one length-prefixed frame.
+pub async fn write_frame<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    json: &[u8],
+    binary: &[u8],
+) -> io::Result<()> {
+    let header = FrameHeader::new(json.len(), binary.len())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    writer.write_all(&header.encode()).await?;
+    writer.write_all(json).await?;
+    writer.write_all(binary).await?;
+    writer.flush().await
+}
@@ -7,14 +7,17 @@ pub mod automation;
 pub mod daemon;
 pub mod handlers;
 pub mod ipc_server;
+pub mod logging;
 pub mod ocr;
 pub mod rdp_session;
 pub mod rdpdr;
+pub mod ws_delta;
 pub mod ws_input;
 pub mod ws_server;
 
 pub use daemon::{Daemon, SharedWsHandle};
 pub use ipc_server::IpcServer;
+pub use rdp_session::credentials::{CredentialProvider, Credentials, StaticCredentialProvider};
 pub use rdp_session::RdpSession;
 
 /// Get the base directory for all agent-rdp sessions.
@@ -55,6 +58,22 @@ pub fn get_pid_path(session: &str) -> std::path::PathBuf {
     get_session_dir(session).join("pid")
 }
 
+/// Get the path of the connection settings saved for `session restart`.
+pub fn get_connect_config_path(session: &str) -> std::path::PathBuf {
+    get_session_dir(session).join(CONNECT_CONFIG_FILE)
+}
+
+/// File name of the saved connection settings, which outlive the daemon.
+const CONNECT_CONFIG_FILE: &str = "connect.json";
+
+/// Get the path of the session's daemon log.
+pub fn get_log_path(session: &str) -> std::path::PathBuf {
+    get_session_dir(session).join(LOG_FILE)
+}
+
+/// File name of the daemon log, kept after the daemon exits for post-mortems.
+const LOG_FILE: &str = "daemon.log";
+
 /// Get the TCP port for a session (Windows fallback).
 /// Uses a deterministic hash of the session name to derive a port in the range 49152-65535.
 pub fn get_session_port(session: &str) -> u16 {
@@ -66,21 +85,57 @@ pub fn get_session_port(session: &str) -> u16 {
     49152 + (hash % 16384) as u16
 }
 
-/// Clean up a session directory.
+/// Clean up a session directory after its daemon exits.
+///
+/// The saved connection settings are kept so the session can be restarted, and
+/// the logs so a crash can be looked into; the directory itself is only removed
+/// once nothing else is left in it.
 pub fn cleanup_session(session: &str) {
+    let dir = get_session_dir(session);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if name == CONNECT_CONFIG_FILE || name.to_string_lossy().starts_with(LOG_FILE) {
+            continue;
+        }
+        let path = entry.path();
+        let _ = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+    }
+    let _ = std::fs::remove_dir(&dir);
+}
+
+/// Remove a session directory entirely, including its saved connection settings.
+pub fn remove_session(session: &str) {
     let dir = get_session_dir(session);
     let _ = std::fs::remove_dir_all(&dir);
 }
 
 /// Run the daemon server for the given session.
 /// This is the main entry point called by `agent-rdp session daemon`.
-pub async fn run_server(session: &str) -> anyhow::Result<()> {
+///
+/// Programs embedding the daemon can pass a credential provider to supply the
+/// username and password at connect time; the CLI passes `None` and sends them
+/// in the connect request.
+pub async fn run_server(
+    session: &str,
+    credential_provider: Option<Box<dyn CredentialProvider>>,
+) -> anyhow::Result<()> {
     use std::io::Write;
 
     // Create session directory
     let session_dir = get_session_dir(session);
     std::fs::create_dir_all(&session_dir)?;
 
+    // Nothing would see an error here: the daemon's output is discarded, and an
+    // embedding program may have set up logging already
+    let _ = logging::init(session);
+
     // Write PID file
     let pid_path = get_pid_path(session);
     let mut pid_file = std::fs::File::create(&pid_path)?;
@@ -88,7 +143,7 @@ pub async fn run_server(session: &str) -> anyhow::Result<()> {
     drop(pid_file);
 
     // Create and run daemon
-    let mut daemon = Daemon::new(session.to_string()).await?;
+    let mut daemon = Daemon::new(session.to_string(), credential_provider).await?;
     let result = daemon.run().await;
 
     // Cleanup on exit
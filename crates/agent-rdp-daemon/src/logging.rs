@@ -0,0 +1,114 @@
+//! Daemon log file.
+//!
+//! The daemon runs detached with its output discarded, so it logs to
+//! `daemon.log` in the session directory instead, where `session logs` reads
+//! it. When the file reaches `MAX_LOG_BYTES` it is moved to `daemon.log.1`
+//! (replacing the previous one) and a new file is started.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tracing_subscriber::EnvFilter;
+
+/// Size at which the log file is rotated.
+pub const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Log level filter used when `AGENT_RDP_LOG` is not set.
+const DEFAULT_LOG_FILTER: &str = "info";
+
+/// Path of the rotated log file kept alongside `log_path`.
+pub fn rotated_path(log_path: &Path) -> PathBuf {
+    let mut name = log_path.as_os_str().to_owned();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+/// Log file that starts over in a new file once it grows past a size limit.
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_bytes: u64,
+}
+
+impl RotatingFile {
+    /// Open (or create) the log file for appending.
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self { path, file, len, max_bytes })
+    }
+
+    /// Move the current file aside and start a new one.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        fs::rename(&self.path, rotated_path(&self.path))?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Rotate between lines, so an entry never spans both files
+        if self.len > 0 && self.len + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Send this process's logs to the session's log file.
+///
+/// The level comes from `AGENT_RDP_LOG` (a `RUST_LOG`-style filter, default
+/// `info`). Fails if the file can't be opened or a global subscriber is
+/// already set, as when a program embedding the daemon installed its own.
+pub fn init(session: &str) -> anyhow::Result<()> {
+    let file = RotatingFile::open(crate::get_log_path(session), MAX_LOG_BYTES)?;
+    let filter = std::env::var("AGENT_RDP_LOG")
+        .ok()
+        .and_then(|filter| EnvFilter::try_new(filter).ok())
+        .unwrap_or_else(|| EnvFilter::new(DEFAULT_LOG_FILTER));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_file_keeps_one_old_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("daemon.log");
+        let mut file = RotatingFile::open(&path, 16).unwrap();
+
+        file.write_all(b"first line\n").unwrap();
+        file.write_all(b"second line\n").unwrap();
+        file.write_all(b"third line\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "third line\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path)).unwrap(), "second line\n");
+
+        // Reopening appends and counts what is already there
+        let mut file = RotatingFile::open(&path, 16).unwrap();
+        file.write_all(b"fourth line\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth line\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path)).unwrap(), "third line\n");
+    }
+}
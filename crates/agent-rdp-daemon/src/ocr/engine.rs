@@ -3,9 +3,38 @@
 use agent_rdp_protocol::OcrMatch;
 use anyhow::{Context, Result};
 use ocrs::{ImageSource, OcrEngine, OcrEngineParams, TextItem};
+use parking_lot::Mutex;
 use rten::Model;
 use std::path::{Path, PathBuf};
-use tracing::{debug, trace};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, info, trace, warn};
+
+/// Environment variable naming a directory that holds the OCR models.
+const MODELS_DIR_ENV: &str = "AGENT_RDP_OCR_MODELS";
+
+/// Text detection model file.
+const DETECTION_MODEL: &str = "text-detection.rten";
+
+/// Text recognition model file for the default language.
+const RECOGNITION_MODEL: &str = "text-recognition.rten";
+
+/// Language recognized when none is requested.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Loaded services keyed by language.
+type LoadedServices = Vec<(String, Arc<OcrService>)>;
+
+/// Loaded services by language, and the directory their models came from.
+static SHARED_SERVICES: Mutex<Option<(PathBuf, LoadedServices)>> = Mutex::new(None);
+
+/// A recognition language whose model isn't in the models directory.
+#[derive(Debug, Error)]
+#[error("OCR language '{lang}' is not installed (installed: {})", installed.join(", "))]
+pub struct LanguageNotAvailable {
+    pub lang: String,
+    pub installed: Vec<String>,
+}
 
 /// OCR service for text detection and recognition.
 pub struct OcrService {
@@ -14,9 +43,27 @@ pub struct OcrService {
 
 impl OcrService {
     /// Create a new OCR service by loading models from the given directory.
-    pub fn new(models_dir: &Path) -> Result<Self> {
-        let detection_path = models_dir.join("text-detection.rten");
-        let recognition_path = models_dir.join("text-recognition.rten");
+    ///
+    /// The detection model is shared by all languages. The default language
+    /// uses `text-recognition.rten` with the built-in alphabet; any other
+    /// language `xx` needs `text-recognition-xx.rten` and the alphabet it was
+    /// trained with in `alphabet-xx.txt`.
+    pub fn new(models_dir: &Path, lang: &str) -> Result<Self> {
+        let installed = installed_languages(models_dir);
+        if !installed.iter().any(|installed| installed == lang) {
+            return Err(LanguageNotAvailable { lang: lang.to_string(), installed }.into());
+        }
+
+        let detection_path = models_dir.join(DETECTION_MODEL);
+        let (recognition_path, alphabet_path) = recognition_files(models_dir, lang);
+        let alphabet = match alphabet_path {
+            Some(path) => {
+                let alphabet = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read alphabet from {:?}", path))?;
+                Some(alphabet.trim_end_matches(['\r', '\n']).to_string())
+            }
+            None => None,
+        };
 
         debug!("Loading OCR detection model from {:?}", detection_path);
         let detection_model = Model::load_file(&detection_path)
@@ -29,11 +76,12 @@ impl OcrService {
         let engine = OcrEngine::new(OcrEngineParams {
             detection_model: Some(detection_model),
             recognition_model: Some(recognition_model),
+            alphabet,
             ..Default::default()
         })
         .context("Failed to create OCR engine")?;
 
-        debug!("OCR engine initialized successfully");
+        debug!("OCR engine initialized successfully for '{}'", lang);
         Ok(Self { engine })
     }
 
@@ -105,6 +153,7 @@ impl OcrService {
             .into_rgb8();
 
         let (width, height) = (img.width(), img.height());
+        let (width_px, height_px) = (width as usize, height as usize);
         trace!("Image loaded: {}x{}", width, height);
 
         // Create ImageSource from RGB data
@@ -125,6 +174,12 @@ impl OcrService {
 
         trace!("Detected {} word regions", word_rects.len());
 
+        // Per-pixel text probabilities, used to score each line's confidence
+        let text_probs = self
+            .engine
+            .detect_text_pixels(&ocr_input)
+            .context("Failed to detect text pixels")?;
+
         // Group words into lines
         let line_rects = self.engine.find_text_lines(&ocr_input, &word_rects);
 
@@ -168,6 +223,9 @@ impl OcrService {
                 let y = min_y;
                 let width = max_x - min_x;
                 let height = max_y - min_y;
+                let confidence = mean_probability((width_px, height_px), (x, y, width, height), |px, py| {
+                    text_probs[[py, px]]
+                });
 
                 lines.push(OcrMatch {
                     text,
@@ -177,6 +235,7 @@ impl OcrService {
                     height,
                     center_x: x + width / 2,
                     center_y: y + height / 2,
+                    confidence,
                 });
             }
         }
@@ -188,6 +247,32 @@ impl OcrService {
     }
 }
 
+/// Average of `prob(x, y)` over a rectangle, clipped to the image.
+///
+/// Returns 0.0 for rectangles that don't overlap the image.
+fn mean_probability(
+    (image_width, image_height): (usize, usize),
+    (x, y, width, height): (i32, i32, i32, i32),
+    prob: impl Fn(usize, usize) -> f32,
+) -> f32 {
+    let left = x.max(0) as usize;
+    let top = y.max(0) as usize;
+    let right = ((x + width).max(0) as usize).min(image_width);
+    let bottom = ((y + height).max(0) as usize).min(image_height);
+    if left >= right || top >= bottom {
+        return 0.0;
+    }
+
+    let mut sum = 0.0f64;
+    for py in top..bottom {
+        for px in left..right {
+            sum += prob(px, py) as f64;
+        }
+    }
+    let count = ((right - left) * (bottom - top)) as f64;
+    (sum / count).clamp(0.0, 1.0) as f32
+}
+
 /// Simple glob-style pattern matching supporting * and ? wildcards.
 fn glob_match(pattern: &str, text: &str) -> bool {
     let mut pattern_chars = pattern.chars().peekable();
@@ -228,35 +313,128 @@ fn glob_match(pattern: &str, text: &str) -> bool {
     true
 }
 
-/// Find the models directory relative to the executable.
+/// Get the OCR service for a language, loading its models only when they
+/// haven't been loaded yet or come from a different directory than last time.
 ///
-/// Models are always at `bin/../models` relative to the executable:
-/// - Dev: packages/{platform}/bin/agent-rdp -> packages/{platform}/models/
-/// - npm: node_modules/@agent-rdp/{platform}/bin/agent-rdp -> node_modules/@agent-rdp/{platform}/models/
-pub fn find_models_dir() -> Result<PathBuf> {
-    let exe_path = std::env::current_exe().context("Failed to get executable path")?;
+/// Loading the models is far slower than recognizing a screen, so each
+/// language's service is kept for the life of the daemon.
+pub fn shared_service(models_dir: Option<&Path>, lang: Option<&str>) -> Result<Arc<OcrService>> {
+    let models_dir = find_models_dir(models_dir)?;
+    let lang = lang.map(str::to_lowercase).unwrap_or_else(|| DEFAULT_LANGUAGE.to_string());
+
+    let mut shared = SHARED_SERVICES.lock();
+    if shared.as_ref().is_some_and(|(loaded_from, _)| *loaded_from != models_dir) {
+        *shared = None;
+    }
+    let (_, services) = shared.get_or_insert_with(|| (models_dir.clone(), Vec::new()));
+    if let Some((_, service)) = services.iter().find(|(loaded, _)| *loaded == lang) {
+        return Ok(Arc::clone(service));
+    }
+
+    info!("Loading OCR models for '{}' from {:?}", lang, models_dir);
+    let service = Arc::new(OcrService::new(&models_dir, &lang)?);
+    services.push((lang, Arc::clone(&service)));
+    Ok(service)
+}
 
-    // Models are always sibling to bin directory: bin/../models
-    let models_dir = exe_path
-        .parent() // bin/
-        .and_then(|p| p.parent()) // package root
-        .map(|p| p.join("models"))
-        .context("Failed to compute models directory path")?;
+/// Languages that have a recognition model in the models directory, sorted.
+pub fn installed_languages(models_dir: &Path) -> Vec<String> {
+    let mut languages = Vec::new();
+    if models_dir.join(RECOGNITION_MODEL).is_file() {
+        languages.push(DEFAULT_LANGUAGE.to_string());
+    }
+    if let Ok(entries) = std::fs::read_dir(models_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let Some(lang) = name
+                .to_str()
+                .and_then(|name| name.strip_prefix("text-recognition-"))
+                .and_then(|name| name.strip_suffix(".rten"))
+            else {
+                continue;
+            };
+            let has_alphabet = recognition_files(models_dir, lang).1.is_some_and(|p| p.is_file());
+            if is_language_name(lang) && has_alphabet {
+                languages.push(lang.to_string());
+            }
+        }
+    }
+    languages.sort();
+    languages.dedup();
+    languages
+}
+
+/// Recognition model and alphabet files for a language; the default
+/// language uses the built-in alphabet.
+fn recognition_files(models_dir: &Path, lang: &str) -> (PathBuf, Option<PathBuf>) {
+    if lang == DEFAULT_LANGUAGE {
+        (models_dir.join(RECOGNITION_MODEL), None)
+    } else {
+        (
+            models_dir.join(format!("text-recognition-{}.rten", lang)),
+            Some(models_dir.join(format!("alphabet-{}.txt", lang))),
+        )
+    }
+}
+
+/// Whether a language name is safe to build file names from.
+fn is_language_name(lang: &str) -> bool {
+    !lang.is_empty()
+        && lang.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
 
-    let detection = models_dir.join("text-detection.rten");
-    let recognition = models_dir.join("text-recognition.rten");
+/// Find the directory holding the OCR models.
+///
+/// Searched in order:
+/// - `models_dir`, when given (`--ocr-models`)
+/// - the `AGENT_RDP_OCR_MODELS` environment variable
+/// - `bin/../models` relative to the executable:
+///   - Dev: packages/{platform}/bin/agent-rdp -> packages/{platform}/models/
+///   - npm: node_modules/@agent-rdp/{platform}/bin/agent-rdp -> node_modules/@agent-rdp/{platform}/models/
+pub fn find_models_dir(models_dir: Option<&Path>) -> Result<PathBuf> {
+    let configured = models_dir
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(std::env::var_os(MODELS_DIR_ENV).filter(|dir| !dir.is_empty()).map(PathBuf::from));
+    let mut searched: Vec<PathBuf> = Vec::new();
+    for dir in configured {
+        if has_models(&dir) {
+            debug!("Found models directory at {:?}", dir);
+            return Ok(dir);
+        }
+        warn!(
+            "OCR models directory {:?} doesn't contain {} and {}",
+            dir, DETECTION_MODEL, RECOGNITION_MODEL
+        );
+        searched.push(dir);
+    }
 
-    if detection.exists() && recognition.exists() {
-        debug!("Found models directory at {:?}", models_dir);
-        return Ok(models_dir);
+    // Models ship as a sibling of the bin directory: bin/../models
+    let exe_path = std::env::current_exe().context("Failed to get executable path")?;
+    if let Some(dir) = exe_path.parent().and_then(|bin| bin.parent()).map(|root| root.join("models")) {
+        if has_models(&dir) {
+            debug!("Found models directory at {:?}", dir);
+            return Ok(dir);
+        }
+        searched.push(dir);
     }
 
+    let searched: Vec<String> = searched.iter().map(|dir| dir.display().to_string()).collect();
     anyhow::bail!(
-        "Could not find OCR models at {:?}. Run 'pnpm build' to copy models.",
-        models_dir
+        "Could not find OCR models ({} and {}) in: {}. Point --ocr-models or {} at a \
+         directory containing them, or run 'pnpm build' to copy them next to the binary.",
+        DETECTION_MODEL,
+        RECOGNITION_MODEL,
+        searched.join(", "),
+        MODELS_DIR_ENV
     )
 }
 
+/// Whether a directory contains both models.
+fn has_models(dir: &Path) -> bool {
+    dir.join(DETECTION_MODEL).is_file() && dir.join(RECOGNITION_MODEL).is_file()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,4 +466,44 @@ mod tests {
         assert!(glob_match("h*o", "hello"));
         assert!(glob_match("h?ll*", "helloworld"));
     }
+
+    #[test]
+    fn test_mean_probability() {
+        // Left half of a 4x2 image is text, right half background
+        let prob = |x: usize, _y: usize| if x < 2 { 1.0 } else { 0.0 };
+
+        assert_eq!(mean_probability((4, 2), (0, 0, 2, 2), prob), 1.0);
+        assert_eq!(mean_probability((4, 2), (0, 0, 4, 2), prob), 0.5);
+        // Clipped to the image
+        assert_eq!(mean_probability((4, 2), (-2, -2, 4, 4), prob), 1.0);
+        assert_eq!(mean_probability((4, 2), (10, 10, 2, 2), prob), 0.0);
+    }
+
+    #[test]
+    fn test_find_models_dir_override() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join(DETECTION_MODEL), b"").unwrap();
+        assert!(!has_models(dir.path()));
+
+        std::fs::write(dir.path().join(RECOGNITION_MODEL), b"").unwrap();
+        assert!(has_models(dir.path()));
+        assert_eq!(find_models_dir(Some(dir.path())).unwrap(), dir.path());
+    }
+
+    #[test]
+    fn test_installed_languages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for file in [RECOGNITION_MODEL, "text-recognition-ru.rten", "alphabet-ru.txt"] {
+            std::fs::write(dir.path().join(file), b"").unwrap();
+        }
+        // A model without its alphabet is not usable
+        std::fs::write(dir.path().join("text-recognition-ja.rten"), b"").unwrap();
+        assert_eq!(installed_languages(dir.path()), ["en", "ru"]);
+
+        let err = OcrService::new(dir.path(), "ja").err().unwrap();
+        let missing = err.downcast_ref::<LanguageNotAvailable>().unwrap();
+        assert_eq!(missing.lang, "ja");
+        assert_eq!(missing.installed, ["en", "ru"]);
+        assert!(OcrService::new(dir.path(), "../ru").is_err());
+    }
 }
@@ -4,4 +4,7 @@
 
 mod engine;
 
-pub use engine::{find_models_dir, OcrService};
+pub use engine::{
+    find_models_dir, installed_languages, shared_service, LanguageNotAvailable, OcrService,
+    DEFAULT_LANGUAGE,
+};
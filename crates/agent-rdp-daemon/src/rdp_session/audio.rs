@@ -0,0 +1,200 @@
+//! Audio capture backend for RDPSND.
+//!
+//! Replaces the no-op backend when audio capture is requested: we offer the
+//! server a few PCM formats and write every wave it sends to a WAV file. The WAV
+//! header describes a single format, so only the first format the server uses is
+//! recorded; waves in any other format are dropped.
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ironrdp_rdpsnd::client::RdpsndClientHandler;
+use ironrdp_rdpsnd::pdu::{AudioFormat, PitchPdu, VolumePdu, WaveFormat};
+use parking_lot::Mutex;
+use tracing::{debug, info, warn};
+
+/// Size of the canonical 44-byte WAV header.
+const WAV_HEADER_SIZE: u32 = 44;
+
+/// PCM formats offered to the server, in order of preference.
+const OFFERED_FORMATS: &[(u16, u32)] = &[(2, 44_100), (2, 48_000), (2, 22_050), (1, 22_050)];
+
+/// Bits per sample for all offered formats.
+const BITS_PER_SAMPLE: u16 = 16;
+
+/// Audio capture shared across reconnects, so one recording spans the session.
+pub type SharedAudioCapture = Arc<Mutex<AudioCapture>>;
+
+/// A WAV file receiving PCM from the server.
+#[derive(Debug)]
+pub struct AudioCapture {
+    path: PathBuf,
+    file: File,
+    /// Index (into the offered formats) of the recording's format, fixed by the first wave.
+    format_no: Option<usize>,
+    /// Bytes of sample data written so far.
+    data_len: u32,
+    /// Whether a wave in another format has already been reported.
+    warned_format_change: bool,
+}
+
+impl AudioCapture {
+    /// Create (or truncate) the capture file.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = File::create(path)?;
+        info!("Capturing audio to {:?}", path);
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            format_no: None,
+            data_len: 0,
+            warned_format_change: false,
+        })
+    }
+
+    /// Path of the capture file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append samples, writing the header on the first call.
+    fn write_samples(&mut self, format_no: usize, format: &AudioFormat, data: &[u8]) -> io::Result<()> {
+        match self.format_no {
+            None => {
+                info!(
+                    "Recording audio as {} Hz, {} channel(s), {}-bit PCM",
+                    format.n_samples_per_sec, format.n_channels, format.bits_per_sample
+                );
+                self.format_no = Some(format_no);
+                self.file.write_all(&wav_header(format, 0))?;
+            }
+            Some(recorded) if recorded != format_no => {
+                if !self.warned_format_change {
+                    warn!(
+                        "Server switched audio format to {} Hz, {} channel(s); dropping audio not in the recorded format",
+                        format.n_samples_per_sec, format.n_channels
+                    );
+                    self.warned_format_change = true;
+                }
+                return Ok(());
+            }
+            Some(_) => {}
+        }
+
+        // Stop before the 4 GiB RIFF limit rather than writing a corrupt file
+        let Some(data_len) = self.data_len.checked_add(data.len() as u32) else {
+            return Ok(());
+        };
+        if data_len > u32::MAX - WAV_HEADER_SIZE {
+            return Ok(());
+        }
+
+        self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(data)?;
+        self.data_len = data_len;
+
+        // Keep the header sizes current so the file is playable at any point
+        self.file.seek(SeekFrom::Start(4))?;
+        self.file.write_all(&(WAV_HEADER_SIZE - 8 + data_len).to_le_bytes())?;
+        self.file.seek(SeekFrom::Start(40))?;
+        self.file.write_all(&data_len.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Flush buffered data to disk.
+    fn flush(&mut self) {
+        if let Err(e) = self.file.flush() {
+            warn!("Failed to flush audio capture {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Build a 44-byte PCM WAV header.
+fn wav_header(format: &AudioFormat, data_len: u32) -> [u8; WAV_HEADER_SIZE as usize] {
+    let mut header = [0u8; WAV_HEADER_SIZE as usize];
+    header[0..4].copy_from_slice(b"RIFF");
+    header[4..8].copy_from_slice(&(WAV_HEADER_SIZE - 8 + data_len).to_le_bytes());
+    header[8..12].copy_from_slice(b"WAVE");
+    header[12..16].copy_from_slice(b"fmt ");
+    header[16..20].copy_from_slice(&16u32.to_le_bytes());
+    header[20..22].copy_from_slice(&1u16.to_le_bytes()); // WAVE_FORMAT_PCM
+    header[22..24].copy_from_slice(&format.n_channels.to_le_bytes());
+    header[24..28].copy_from_slice(&format.n_samples_per_sec.to_le_bytes());
+    header[28..32].copy_from_slice(&format.n_avg_bytes_per_sec.to_le_bytes());
+    header[32..34].copy_from_slice(&format.n_block_align.to_le_bytes());
+    header[34..36].copy_from_slice(&format.bits_per_sample.to_le_bytes());
+    header[36..40].copy_from_slice(b"data");
+    header[40..44].copy_from_slice(&data_len.to_le_bytes());
+    header
+}
+
+/// Build a PCM format descriptor.
+fn pcm_format(n_channels: u16, n_samples_per_sec: u32) -> AudioFormat {
+    let n_block_align = n_channels * BITS_PER_SAMPLE / 8;
+    AudioFormat {
+        format: WaveFormat::PCM,
+        n_channels,
+        n_samples_per_sec,
+        n_avg_bytes_per_sec: n_samples_per_sec * u32::from(n_block_align),
+        n_block_align,
+        bits_per_sample: BITS_PER_SAMPLE,
+        data: None,
+    }
+}
+
+/// RDPSND backend that records received audio.
+#[derive(Debug)]
+pub struct AudioCaptureBackend {
+    formats: Vec<AudioFormat>,
+    capture: SharedAudioCapture,
+}
+
+impl AudioCaptureBackend {
+    pub fn new(capture: SharedAudioCapture) -> Self {
+        Self {
+            formats: OFFERED_FORMATS
+                .iter()
+                .map(|&(channels, rate)| pcm_format(channels, rate))
+                .collect(),
+            capture,
+        }
+    }
+}
+
+impl RdpsndClientHandler for AudioCaptureBackend {
+    fn get_formats(&self) -> &[AudioFormat] {
+        &self.formats
+    }
+
+    fn wave(&mut self, format_no: usize, _ts: u32, data: Cow<'_, [u8]>) {
+        let Some(format) = self.formats.get(format_no) else {
+            warn!("RDPSND wave with unknown format index {}", format_no);
+            return;
+        };
+
+        let mut capture = self.capture.lock();
+        if let Err(e) = capture.write_samples(format_no, format, &data) {
+            warn!("Failed to write audio capture {:?}: {}", capture.path(), e);
+        }
+    }
+
+    fn set_volume(&mut self, volume: VolumePdu) {
+        debug!("RDPSND volume: {:?}", volume);
+    }
+
+    fn set_pitch(&mut self, pitch: PitchPdu) {
+        debug!("RDPSND pitch: {:?}", pitch);
+    }
+
+    fn close(&mut self) {
+        debug!("RDPSND channel closed");
+        self.capture.lock().flush();
+    }
+}
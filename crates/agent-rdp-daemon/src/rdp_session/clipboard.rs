@@ -1,8 +1,15 @@
 //! Clipboard backend for CLIPRDR integration.
 //!
 //! This module provides a custom clipboard backend that stores clipboard data
-//! and communicates with the frame processor via channels.
+//! and communicates with the frame processor via channels. File lists are
+//! handled by the `files` submodule, HTML by the `html` submodule and recent
+//! remote copies by the `history` submodule.
 
+pub mod files;
+pub mod history;
+pub mod html;
+
+use std::io::Cursor;
 use std::sync::Arc;
 
 use ironrdp_cliprdr::backend::{CliprdrBackend, ClipboardMessage, ClipboardMessageProxy};
@@ -25,15 +32,53 @@ pub fn cf_unicodetext() -> ClipboardFormatId {
     ClipboardFormatId::new(13)
 }
 
+/// Standard clipboard format ID for device-independent bitmaps (CF_DIB = 8).
+pub fn cf_dib() -> ClipboardFormatId {
+    ClipboardFormatId::new(8)
+}
+
+/// Standard clipboard format ID for version 5 bitmaps (CF_DIBV5 = 17).
+pub fn cf_dibv5() -> ClipboardFormatId {
+    ClipboardFormatId::new(17)
+}
+
+/// Standard text formats, which Windows converts between (CF_UNICODETEXT, CF_TEXT, CF_OEMTEXT).
+const TEXT_FORMATS: [u32; 3] = [13, 1, 7];
+
+/// Standard bitmap formats, which Windows converts between (CF_DIB, CF_DIBV5, CF_BITMAP).
+const BITMAP_FORMATS: [u32; 3] = [8, 17, 2];
+
+/// Kinds of content a clipboard format list offers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClipboardContent {
+    pub text: bool,
+    pub image: bool,
+    pub html: bool,
+    pub files: bool,
+}
+
+/// Size of a BITMAPINFOHEADER.
+const BITMAPINFOHEADER_SIZE: usize = 40;
+
+/// Uncompressed pixels.
+const BI_RGB: u32 = 0;
+
+/// Uncompressed pixels with explicit color masks.
+const BI_BITFIELDS: u32 = 3;
+
 /// Messages from backend to frame processor.
 #[derive(Debug)]
 pub enum BackendMessage {
     /// Backend wants to initiate copy (announce formats).
     InitiateCopy(Vec<ClipboardFormat>),
-    /// Backend has format data ready to send.
+    /// Backend has format data ready to send (text, or DIB for images).
     FormatData(OwnedFormatDataResponse),
     /// Backend wants to request data from remote.
     InitiatePaste(ClipboardFormatId),
+    /// Backend wants a chunk (or the size) of a remote file.
+    FileContentsRequest(FileContentsRequest),
+    /// Backend has a chunk (or the size) of a local file ready to send.
+    FileContents(FileContentsResponse<'static>),
 }
 
 /// Proxy that sends messages to the frame processor.
@@ -68,28 +113,120 @@ impl ClipboardMessageProxy for ChannelProxy {
 pub struct ClipboardState {
     /// Text we want to send to remote (set by clipboard set command).
     pub local_text: Option<String>,
+    /// Image (CF_DIB) we want to send to remote (set by clipboard set-image command).
+    pub local_image: Option<Vec<u8>>,
+    /// HTML (CF_HTML, header included) we want to send to remote (set by clipboard
+    /// set-html command); `local_text` holds its plain text version.
+    pub local_html: Option<String>,
+    /// Files we want to send to remote (set by clipboard set-files command).
+    pub local_files: Option<files::LocalFiles>,
     /// Text received from remote.
     pub remote_text: Option<String>,
+    /// Image (CF_DIB) received from remote.
+    pub remote_image: Option<Vec<u8>>,
+    /// HTML fragment received from remote.
+    pub remote_html: Option<String>,
     /// Formats available on remote clipboard.
     pub remote_formats: Vec<ClipboardFormat>,
+    /// We announced after the remote's last copy, so remote pastes read our
+    /// local content.
+    pub local_owner: bool,
+    /// Format of the outstanding paste request (responses don't identify their format).
+    pub pending_format: Option<ClipboardFormatId>,
     /// Pending text get request response channel.
     pub pending_get: Option<tokio::sync::oneshot::Sender<Result<Option<String>, String>>>,
+    /// Pending image get request response channel.
+    pub pending_get_image: Option<tokio::sync::oneshot::Sender<Result<Option<Vec<u8>>, String>>>,
+    /// Pending HTML get request response channel.
+    pub pending_get_html: Option<tokio::sync::oneshot::Sender<Result<Option<String>, String>>>,
+    /// Notified on the server's next request for our data (a remote paste).
+    pub pending_read: Option<tokio::sync::oneshot::Sender<()>>,
+    /// File transfer from remote in progress.
+    pub incoming_files: Option<files::IncomingFiles>,
     /// Notify when remote clipboard changes (for WebSocket integration).
     pub clipboard_changed_tx: Option<mpsc::UnboundedSender<()>>,
+    /// Text of recent remote copies.
+    pub history: history::ClipboardHistory,
 }
 
 impl Default for ClipboardState {
     fn default() -> Self {
         Self {
             local_text: None,
+            local_image: None,
+            local_html: None,
+            local_files: None,
             remote_text: None,
+            remote_image: None,
+            remote_html: None,
             remote_formats: Vec::new(),
+            local_owner: false,
+            pending_format: None,
             pending_get: None,
+            pending_get_image: None,
+            pending_get_html: None,
+            pending_read: None,
+            incoming_files: None,
             clipboard_changed_tx: None,
+            history: history::ClipboardHistory::default(),
         }
     }
 }
 
+impl ClipboardState {
+    /// Formats to announce for the local clipboard contents.
+    ///
+    /// Images and HTML are also offered as text so paste targets that only accept
+    /// text still work.
+    pub fn local_formats(&self) -> Vec<ClipboardFormat> {
+        if self.local_files.is_some() {
+            vec![files::local_file_list_format()]
+        } else if self.local_image.is_some() {
+            vec![
+                ClipboardFormat::new(cf_dib()),
+                ClipboardFormat::new(cf_unicodetext()),
+            ]
+        } else if self.local_html.is_some() {
+            vec![
+                html::local_html_format(),
+                ClipboardFormat::new(cf_unicodetext()),
+            ]
+        } else if self.local_text.is_some() {
+            vec![ClipboardFormat::new(cf_unicodetext())]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Kinds of content the remote's last copy offers, from its format list.
+    pub fn remote_content(&self) -> ClipboardContent {
+        let offers_any = |ids: &[u32]| {
+            self.remote_formats
+                .iter()
+                .any(|format| ids.iter().any(|&id| format.id() == ClipboardFormatId::new(id)))
+        };
+        ClipboardContent {
+            text: offers_any(&TEXT_FORMATS),
+            image: offers_any(&BITMAP_FORMATS),
+            html: html::find_html_format(&self.remote_formats).is_some(),
+            files: files::find_file_list_format(&self.remote_formats).is_some(),
+        }
+    }
+
+    /// Drop the contents of both sides, as when an empty format list is announced.
+    pub fn clear(&mut self) {
+        self.local_text = None;
+        self.local_image = None;
+        self.local_html = None;
+        self.local_files = None;
+        self.remote_text = None;
+        self.remote_image = None;
+        self.remote_html = None;
+        self.remote_formats.clear();
+        self.local_owner = false;
+    }
+}
+
 /// Custom clipboard backend that stores data in memory.
 #[derive(Debug)]
 pub struct AgentClipboardBackend {
@@ -103,6 +240,25 @@ impl AgentClipboardBackend {
     pub fn new(state: Arc<Mutex<ClipboardState>>, proxy: ChannelProxy) -> Self {
         Self { state, proxy }
     }
+
+    /// Send the next file contents request, or finish the incoming transfer.
+    fn continue_incoming(&self, state: &mut ClipboardState, next: Result<Option<FileContentsRequest>, String>) {
+        match next {
+            Ok(Some(request)) => {
+                let _ = self.proxy.tx.send(BackendMessage::FileContentsRequest(request));
+            }
+            Ok(None) => {
+                if let Some(transfer) = state.incoming_files.take() {
+                    transfer.complete();
+                }
+            }
+            Err(e) => {
+                if let Some(transfer) = state.incoming_files.take() {
+                    transfer.fail(e);
+                }
+            }
+        }
+    }
 }
 
 impl CliprdrBackend for AgentClipboardBackend {
@@ -111,7 +267,11 @@ impl CliprdrBackend for AgentClipboardBackend {
     }
 
     fn client_capabilities(&self) -> ClipboardGeneralCapabilityFlags {
+        // Long names carry "FileGroupDescriptorW"; file contents are streamed, never paths
         ClipboardGeneralCapabilityFlags::USE_LONG_FORMAT_NAMES
+            | ClipboardGeneralCapabilityFlags::STREAM_FILECLIP_ENABLED
+            | ClipboardGeneralCapabilityFlags::FILECLIP_NO_FILE_PATHS
+            | ClipboardGeneralCapabilityFlags::HUGE_FILE_SUPPORT_ENABLED
     }
 
     fn on_ready(&mut self) {
@@ -120,15 +280,10 @@ impl CliprdrBackend for AgentClipboardBackend {
 
     fn on_request_format_list(&mut self) {
         debug!("Backend: on_request_format_list");
-        // During initialization, send our available formats (if any).
-        let state = self.state.lock();
-        if state.local_text.is_some() {
-            let formats = vec![ClipboardFormat::new(cf_unicodetext())];
-            self.proxy.send_clipboard_message(ClipboardMessage::SendInitiateCopy(formats));
-        } else {
-            // Send empty format list to complete initialization.
-            self.proxy.send_clipboard_message(ClipboardMessage::SendInitiateCopy(vec![]));
-        }
+        // During initialization, send our available formats (an empty list completes
+        // initialization when there are none).
+        let formats = self.state.lock().local_formats();
+        self.proxy.send_clipboard_message(ClipboardMessage::SendInitiateCopy(formats));
     }
 
     fn on_process_negotiated_capabilities(&mut self, _capabilities: ClipboardGeneralCapabilityFlags) {
@@ -139,8 +294,12 @@ impl CliprdrBackend for AgentClipboardBackend {
         debug!("Backend: remote copied, formats: {:?}", available_formats);
         let mut state = self.state.lock();
         state.remote_formats = available_formats.to_vec();
+        state.local_owner = false;
         // Clear old remote data since new data is available.
         state.remote_text = None;
+        state.remote_image = None;
+        state.remote_html = None;
+        state.history.on_remote_copy();
 
         // Notify WebSocket clients that clipboard changed (if channel is set up).
         if let Some(ref tx) = state.clipboard_changed_tx {
@@ -150,10 +309,19 @@ impl CliprdrBackend for AgentClipboardBackend {
 
     fn on_format_data_request(&mut self, request: FormatDataRequest) {
         debug!("Backend: format data request for {:?}", request.format);
-        let state = self.state.lock();
+        let mut state = self.state.lock();
+        if let Some(tx) = state.pending_read.take() {
+            let _ = tx.send(());
+        }
 
         let response = if request.format == cf_unicodetext() {
-            if let Some(ref text) = state.local_text {
+            // An image on the clipboard is offered as (empty) text as well
+            let text = match (&state.local_text, &state.local_image) {
+                (Some(text), _) => Some(text.as_str()),
+                (None, Some(_)) => Some(""),
+                (None, None) => None,
+            };
+            if let Some(text) = text {
                 // Convert to UTF-16LE with null terminator.
                 let utf16: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
                 let bytes: Vec<u8> = utf16.iter().flat_map(|&c| c.to_le_bytes()).collect();
@@ -161,6 +329,26 @@ impl CliprdrBackend for AgentClipboardBackend {
             } else {
                 OwnedFormatDataResponse::new_error()
             }
+        } else if request.format == cf_dib() {
+            match state.local_image {
+                Some(ref dib) => OwnedFormatDataResponse::new_data(dib.clone()),
+                None => OwnedFormatDataResponse::new_error(),
+            }
+        } else if request.format == html::local_html_format_id() {
+            match state.local_html {
+                // CF_HTML is null-terminated like the other text formats
+                Some(ref cf_html) => {
+                    let mut bytes = cf_html.as_bytes().to_vec();
+                    bytes.push(0);
+                    OwnedFormatDataResponse::new_data(bytes)
+                }
+                None => OwnedFormatDataResponse::new_error(),
+            }
+        } else if request.format == files::local_file_list_format_id() {
+            match state.local_files {
+                Some(ref files) => OwnedFormatDataResponse::new_data(files.file_list()),
+                None => OwnedFormatDataResponse::new_error(),
+            }
         } else {
             OwnedFormatDataResponse::new_error()
         };
@@ -172,6 +360,47 @@ impl CliprdrBackend for AgentClipboardBackend {
         debug!("Backend: format data response, is_error={}", response.is_error());
 
         let mut state = self.state.lock();
+        let format = state.pending_format.take();
+
+        // File list for a get-files request: start pulling the file contents
+        let started = match state.incoming_files.as_mut() {
+            Some(transfer) if Some(transfer.format_id()) == format => Some(if response.is_error() {
+                Err("Remote clipboard no longer contains files".to_string())
+            } else {
+                transfer.start(response.data())
+            }),
+            _ => None,
+        };
+        if let Some(next) = started {
+            self.continue_incoming(&mut state, next);
+            return;
+        }
+
+        if format.is_some() && format == html::find_html_format(&state.remote_formats) {
+            let fragment = if response.is_error() {
+                None
+            } else {
+                html::decode_cf_html(response.data())
+            };
+            debug!("Received clipboard HTML: {:?} bytes", fragment.as_ref().map(String::len));
+            state.remote_html = fragment.clone();
+            if let Some(tx) = state.pending_get_html.take() {
+                let _ = tx.send(Ok(fragment));
+            }
+            return;
+        }
+
+        if format == Some(cf_dib()) {
+            // Server returns an error when the clipboard has no bitmap - not an error condition.
+            let image = (!response.is_error() && !response.data().is_empty())
+                .then(|| response.data().to_vec());
+            debug!("Received clipboard image: {:?} bytes", image.as_ref().map(Vec::len));
+            state.remote_image = image.clone();
+            if let Some(tx) = state.pending_get_image.take() {
+                let _ = tx.send(Ok(image));
+            }
+            return;
+        }
 
         if response.is_error() {
             // Server returned error - clipboard is empty or doesn't have text format.
@@ -195,6 +424,7 @@ impl CliprdrBackend for AgentClipboardBackend {
                 .to_string();
 
             debug!("Received clipboard text: {} chars", text.len());
+            state.history.record(&text);
             state.remote_text = Some(text.clone());
 
             if let Some(tx) = state.pending_get.take() {
@@ -205,12 +435,27 @@ impl CliprdrBackend for AgentClipboardBackend {
         }
     }
 
-    fn on_file_contents_request(&mut self, _request: FileContentsRequest) {
-        debug!("Backend: file contents request (not supported)");
+    fn on_file_contents_request(&mut self, request: FileContentsRequest) {
+        debug!(
+            "Backend: file contents request for index {}, flags {:?}, position {}",
+            request.index, request.flags, request.position
+        );
+        let response = match self.state.lock().local_files {
+            Some(ref files) => files.contents_response(&request),
+            None => FileContentsResponse::new_error(request.stream_id),
+        };
+        let _ = self.proxy.tx.send(BackendMessage::FileContents(response));
     }
 
-    fn on_file_contents_response(&mut self, _response: FileContentsResponse<'_>) {
-        debug!("Backend: file contents response (not supported)");
+    fn on_file_contents_response(&mut self, response: FileContentsResponse<'_>) {
+        debug!("Backend: file contents response for stream {}", response.stream_id());
+        let mut state = self.state.lock();
+        let Some(transfer) = state.incoming_files.as_mut() else {
+            debug!("Ignoring file contents response with no transfer in progress");
+            return;
+        };
+        let next = transfer.on_contents(&response);
+        self.continue_incoming(&mut state, next);
     }
 
     fn on_lock(&mut self, _data_id: LockDataId) {
@@ -233,3 +478,143 @@ pub fn create_cliprdr(
     let cliprdr = Cliprdr::<Client>::new(backend);
     (cliprdr, proxy_rx)
 }
+
+/// Convert PNG data to a CF_DIB payload (BITMAPINFOHEADER + bottom-up 32bpp BGRA pixels).
+pub fn png_to_dib(png: &[u8]) -> Result<Vec<u8>, String> {
+    let image = image::load_from_memory_with_format(png, image::ImageFormat::Png)
+        .map_err(|e| format!("Invalid PNG data: {}", e))?
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+    let too_large = || format!("Image too large: {}x{}", width, height);
+    let signed_width = i32::try_from(width).map_err(|_| too_large())?;
+    let signed_height = i32::try_from(height).map_err(|_| too_large())?;
+    let pixels_len = u32::try_from(width as u64 * height as u64 * 4).map_err(|_| too_large())?;
+
+    let mut dib = Vec::with_capacity(BITMAPINFOHEADER_SIZE + pixels_len as usize);
+    dib.extend_from_slice(&(BITMAPINFOHEADER_SIZE as u32).to_le_bytes()); // biSize
+    dib.extend_from_slice(&signed_width.to_le_bytes()); // biWidth
+    dib.extend_from_slice(&signed_height.to_le_bytes()); // biHeight (positive = bottom-up)
+    dib.extend_from_slice(&1u16.to_le_bytes()); // biPlanes
+    dib.extend_from_slice(&32u16.to_le_bytes()); // biBitCount
+    dib.extend_from_slice(&BI_RGB.to_le_bytes()); // biCompression
+    dib.extend_from_slice(&pixels_len.to_le_bytes()); // biSizeImage
+    dib.extend_from_slice(&0i32.to_le_bytes()); // biXPelsPerMeter
+    dib.extend_from_slice(&0i32.to_le_bytes()); // biYPelsPerMeter
+    dib.extend_from_slice(&0u32.to_le_bytes()); // biClrUsed
+    dib.extend_from_slice(&0u32.to_le_bytes()); // biClrImportant
+
+    for row in image.rows().rev() {
+        for pixel in row {
+            let [r, g, b, a] = pixel.0;
+            dib.extend_from_slice(&[b, g, r, a]);
+        }
+    }
+
+    Ok(dib)
+}
+
+/// Convert a CF_DIB (or CF_DIBV5) payload to PNG, returning (width, height, png).
+///
+/// Supports uncompressed 24bpp and 32bpp bitmaps, which is what Windows provides
+/// when synthesizing CF_DIB from screenshots and most applications.
+pub fn dib_to_png(dib: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    let read_u32 = |offset: usize| {
+        dib.get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    let header_size = read_u32(0).ok_or("Bitmap data too short")? as usize;
+    if header_size < BITMAPINFOHEADER_SIZE || dib.len() < header_size {
+        return Err(format!("Unsupported bitmap header size: {}", header_size));
+    }
+    let width = read_u32(4).unwrap_or_default() as i32;
+    let height = read_u32(8).unwrap_or_default() as i32;
+    let bit_count = u16::from_le_bytes([dib[14], dib[15]]);
+    let compression = read_u32(16).unwrap_or_default();
+    let colors_used = read_u32(32).unwrap_or_default() as usize;
+
+    match (bit_count, compression) {
+        (24, BI_RGB) | (32, BI_RGB) | (32, BI_BITFIELDS) => {}
+        _ => {
+            return Err(format!(
+                "Unsupported bitmap format: {} bpp, compression {}",
+                bit_count, compression
+            ))
+        }
+    }
+    if width <= 0 || height == 0 {
+        return Err(format!("Invalid bitmap dimensions: {}x{}", width, height));
+    }
+
+    // Pixels follow the header, the color masks (BI_BITFIELDS with a plain
+    // BITMAPINFOHEADER) and the optional color table
+    let mut offset = header_size + colors_used * 4;
+    if compression == BI_BITFIELDS && header_size == BITMAPINFOHEADER_SIZE {
+        offset += 12;
+    }
+
+    let width = width as u32;
+    let top_down = height < 0;
+    let height = height.unsigned_abs();
+    let bytes_per_pixel = bit_count as usize / 8;
+    let stride = (width as usize * bit_count as usize).div_ceil(32) * 4;
+    if dib.len() < offset + stride * height as usize {
+        return Err("Bitmap pixel data truncated".to_string());
+    }
+
+    let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height as usize {
+        let src_row = if top_down { y } else { height as usize - 1 - y };
+        let row = &dib[offset + src_row * stride..][..width as usize * bytes_per_pixel];
+        for pixel in row.chunks_exact(bytes_per_pixel) {
+            let alpha = if bytes_per_pixel == 4 { pixel[3] } else { 255 };
+            rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], alpha]);
+        }
+    }
+
+    // Most 32bpp bitmaps leave the alpha byte unused (zero), which would make the PNG invisible
+    if bytes_per_pixel == 4 && rgba.chunks_exact(4).all(|pixel| pixel[3] == 0) {
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+    }
+
+    let image = image::RgbaImage::from_raw(width, height, rgba)
+        .ok_or("Failed to create image from bitmap data")?;
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+
+    Ok((width, height, png.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_remote_content_and_clear() {
+        let mut state = ClipboardState::default();
+        assert_eq!(state.remote_content(), ClipboardContent::default());
+
+        // Legacy text and bitmap formats count as well
+        state.remote_formats = vec![
+            ClipboardFormat::new(ClipboardFormatId::new(1)),
+            ClipboardFormat::new(ClipboardFormatId::new(2)),
+        ];
+        let content = state.remote_content();
+        assert!(content.text && content.image && !content.html && !content.files);
+
+        state.remote_formats = vec![html::local_html_format(), files::local_file_list_format()];
+        let content = state.remote_content();
+        assert!(!content.text && content.html && content.files);
+
+        state.local_text = Some("local".to_string());
+        state.remote_text = Some("remote".to_string());
+        state.clear();
+        assert!(state.local_formats().is_empty());
+        assert!(state.remote_text.is_none());
+        assert_eq!(state.remote_content(), ClipboardContent::default());
+    }
+}
@@ -0,0 +1,571 @@
+//! Clipboard file transfer (MS-RDPECLIP 2.2.5.2 and 3.1.5.4).
+//!
+//! A file selection is announced as a `FileGroupDescriptorW` list with one
+//! FILEDESCRIPTORW per file or directory; the receiver then pulls each file's
+//! bytes with FileContents range requests. Outgoing files are read from disk as
+//! the server asks for them, and incoming files are written to disk one chunk at
+//! a time, so neither direction holds a whole file in memory.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use ironrdp_cliprdr::pdu::{
+    ClipboardFormat, ClipboardFormatId, ClipboardFormatName, ClipboardPdu, ClipboardPduFlags,
+    FileContentsFlags, FileContentsRequest, FileContentsResponse,
+};
+use ironrdp_cliprdr::CliprdrClient;
+use ironrdp_svc::{ChannelFlags, SvcMessage, SvcProcessorMessages};
+use tokio::sync::oneshot;
+use tracing::{debug, info, warn};
+
+/// Registered clipboard format name for file lists.
+pub const FILE_GROUP_DESCRIPTOR_W: &str = "FileGroupDescriptorW";
+
+/// Format ID we register for our own file lists (registered formats start at 0xC000).
+const LOCAL_FILE_LIST_FORMAT_ID: u32 = 0xC0F0;
+
+/// Default cap on the total size of a file transfer (1 GiB).
+pub const DEFAULT_MAX_TRANSFER_BYTES: u64 = 1 << 30;
+
+/// Bytes requested per FileContents range request.
+const CHUNK_SIZE: u32 = 64 * 1024;
+
+/// Upper bound on entries in one file list, so a huge tree can't stall the session.
+const MAX_FILE_ENTRIES: usize = 10_000;
+
+/// Size of a FILEDESCRIPTORW structure.
+const FILE_DESCRIPTOR_SIZE: usize = 592;
+
+/// Offset of cFileName within FILEDESCRIPTORW.
+const FILE_NAME_OFFSET: usize = 72;
+
+/// Maximum file name length in UTF-16 code units, including the terminator.
+const MAX_NAME_UNITS: usize = 260;
+
+// FILEDESCRIPTORW flags.
+const FD_ATTRIBUTES: u32 = 0x0000_0004;
+const FD_FILESIZE: u32 = 0x0000_0040;
+const FD_SHOWPROGRESSUI: u32 = 0x0000_4000;
+
+// File attributes.
+const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x0000_0010;
+const FILE_ATTRIBUTE_NORMAL: u32 = 0x0000_0080;
+
+/// Response channel for a file get request (`None` when the clipboard has no files).
+pub type FilesResponder = oneshot::Sender<Result<Option<ReceivedFiles>, String>>;
+
+/// Format announced for local file lists.
+pub fn local_file_list_format() -> ClipboardFormat {
+    ClipboardFormat::new(local_file_list_format_id())
+        .with_name(ClipboardFormatName::new(FILE_GROUP_DESCRIPTOR_W))
+}
+
+/// Format ID of local file lists.
+pub fn local_file_list_format_id() -> ClipboardFormatId {
+    ClipboardFormatId::new(LOCAL_FILE_LIST_FORMAT_ID)
+}
+
+/// Find the remote file list format (its ID is chosen by the remote, so match by name).
+pub fn find_file_list_format(formats: &[ClipboardFormat]) -> Option<ClipboardFormatId> {
+    formats
+        .iter()
+        .find(|format| {
+            format
+                .name()
+                .is_some_and(|name| name.value() == FILE_GROUP_DESCRIPTOR_W)
+        })
+        .map(|format| format.id())
+}
+
+/// Wrap a file contents PDU for sending on the CLIPRDR channel.
+pub fn cliprdr_messages(pdu: ClipboardPdu<'static>) -> SvcProcessorMessages<CliprdrClient> {
+    SvcProcessorMessages::new(vec![SvcMessage::from(pdu).with_flags(ChannelFlags::SHOW_PROTOCOL)])
+}
+
+/// One entry of a file list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileEntry {
+    /// Backslash-separated path relative to the copied selection.
+    name: String,
+    /// File size (None for directories, or when the sender omitted it).
+    size: Option<u64>,
+    /// Whether the entry is a directory.
+    is_dir: bool,
+}
+
+/// Encode a `FileGroupDescriptorW` payload (cItems followed by FILEDESCRIPTORW entries).
+fn encode_file_list(entries: &[FileEntry]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(4 + entries.len() * FILE_DESCRIPTOR_SIZE);
+    data.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+
+    for entry in entries {
+        let mut descriptor = [0u8; FILE_DESCRIPTOR_SIZE];
+        let attributes = if entry.is_dir {
+            FILE_ATTRIBUTE_DIRECTORY
+        } else {
+            FILE_ATTRIBUTE_NORMAL
+        };
+        let size = entry.size.unwrap_or(0);
+
+        descriptor[0..4].copy_from_slice(&(FD_ATTRIBUTES | FD_FILESIZE | FD_SHOWPROGRESSUI).to_le_bytes());
+        descriptor[36..40].copy_from_slice(&attributes.to_le_bytes());
+        descriptor[64..68].copy_from_slice(&((size >> 32) as u32).to_le_bytes());
+        descriptor[68..72].copy_from_slice(&(size as u32).to_le_bytes());
+        // Names are validated to fit when the list is built; the rest stays zero
+        for (i, unit) in entry.name.encode_utf16().take(MAX_NAME_UNITS - 1).enumerate() {
+            let offset = FILE_NAME_OFFSET + i * 2;
+            descriptor[offset..offset + 2].copy_from_slice(&unit.to_le_bytes());
+        }
+
+        data.extend_from_slice(&descriptor);
+    }
+
+    data
+}
+
+/// Decode a `FileGroupDescriptorW` payload.
+fn decode_file_list(data: &[u8]) -> Result<Vec<FileEntry>, String> {
+    let read_u32 = |offset: usize| {
+        data.get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    let count = read_u32(0).ok_or("File list too short")? as usize;
+    if count > MAX_FILE_ENTRIES {
+        return Err(format!("File list has too many entries ({})", count));
+    }
+    if data.len() < 4 + count * FILE_DESCRIPTOR_SIZE {
+        return Err("File list truncated".to_string());
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    for i in 0..count {
+        let base = 4 + i * FILE_DESCRIPTOR_SIZE;
+        let flags = read_u32(base).unwrap_or_default();
+        let attributes = read_u32(base + 36).unwrap_or_default();
+        let size_high = read_u32(base + 64).unwrap_or_default();
+        let size_low = read_u32(base + 68).unwrap_or_default();
+
+        let name_bytes = &data[base + FILE_NAME_OFFSET..base + FILE_DESCRIPTOR_SIZE];
+        let units: Vec<u16> = name_bytes
+            .chunks_exact(2)
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+
+        let is_dir = flags & FD_ATTRIBUTES != 0 && attributes & FILE_ATTRIBUTE_DIRECTORY != 0;
+        let size = (!is_dir && flags & FD_FILESIZE != 0)
+            .then(|| (u64::from(size_high) << 32) | u64::from(size_low));
+
+        entries.push(FileEntry {
+            name: String::from_utf16_lossy(&units),
+            size,
+            is_dir,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Map a remote file name to a path below the destination directory.
+///
+/// Names come from the remote machine, so anything that could escape the
+/// destination (absolute paths, drive letters, `..`) is rejected.
+fn relative_path(name: &str) -> Result<PathBuf, String> {
+    let mut path = PathBuf::new();
+    for part in name.split(['\\', '/']) {
+        if part.is_empty() || part == "." || part == ".." || part.contains(':') {
+            return Err(format!("Refusing unsafe file name from remote: {:?}", name));
+        }
+        path.push(part);
+    }
+    if path.as_os_str().is_empty() {
+        return Err("Remote sent an empty file name".to_string());
+    }
+    Ok(path)
+}
+
+/// Files announced to the remote clipboard.
+#[derive(Debug)]
+pub struct LocalFiles {
+    /// Entries in the order they are announced.
+    entries: Vec<FileEntry>,
+    /// Local path of each entry.
+    sources: Vec<PathBuf>,
+    /// Total size of all files.
+    total_bytes: u64,
+}
+
+impl LocalFiles {
+    /// Walk the given files and directories into a file list.
+    ///
+    /// Fails if any path is unreadable or the total size exceeds `max_bytes`.
+    /// Symlinks inside directories are skipped.
+    pub fn collect(paths: &[PathBuf], max_bytes: u64) -> Result<Self, String> {
+        let mut files = Self {
+            entries: Vec::new(),
+            sources: Vec::new(),
+            total_bytes: 0,
+        };
+
+        for path in paths {
+            let meta = fs::metadata(path).map_err(|e| format!("Cannot read {:?}: {}", path, e))?;
+            let name = path
+                .file_name()
+                .ok_or_else(|| format!("Cannot copy {:?}: path has no file name", path))?
+                .to_string_lossy()
+                .to_string();
+            files.add(path, name, &meta, max_bytes)?;
+        }
+
+        Ok(files)
+    }
+
+    /// Add a file, or a directory and everything below it.
+    fn add(&mut self, path: &Path, name: String, meta: &fs::Metadata, max_bytes: u64) -> Result<(), String> {
+        if self.entries.len() >= MAX_FILE_ENTRIES {
+            return Err(format!("Too many files to copy (limit {})", MAX_FILE_ENTRIES));
+        }
+        if name.encode_utf16().count() >= MAX_NAME_UNITS {
+            return Err(format!("Path too long for the remote clipboard: {}", name));
+        }
+
+        if meta.is_dir() {
+            self.push(path, name.clone(), None);
+
+            let mut children: Vec<_> = fs::read_dir(path)
+                .map_err(|e| format!("Cannot read directory {:?}: {}", path, e))?
+                .flatten()
+                .collect();
+            children.sort_by_key(|child| child.file_name());
+
+            for child in children {
+                let Ok(child_meta) = child.metadata() else {
+                    continue;
+                };
+                if child_meta.file_type().is_symlink() {
+                    debug!("Skipping symlink {:?}", child.path());
+                    continue;
+                }
+                let child_name = format!("{}\\{}", name, child.file_name().to_string_lossy());
+                self.add(&child.path(), child_name, &child_meta, max_bytes)?;
+            }
+        } else {
+            self.total_bytes = self.total_bytes.saturating_add(meta.len());
+            if self.total_bytes > max_bytes {
+                return Err(format!(
+                    "Files exceed the transfer limit of {} bytes",
+                    max_bytes
+                ));
+            }
+            self.push(path, name, Some(meta.len()));
+        }
+
+        Ok(())
+    }
+
+    fn push(&mut self, path: &Path, name: String, size: Option<u64>) {
+        self.entries.push(FileEntry {
+            name,
+            is_dir: size.is_none(),
+            size,
+        });
+        self.sources.push(path.to_path_buf());
+    }
+
+    /// Names of the files (not directories) in the list, with `/` separators.
+    pub fn file_names(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.is_dir)
+            .map(|entry| entry.name.replace('\\', "/"))
+            .collect()
+    }
+
+    /// Total size of all files.
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// The `FileGroupDescriptorW` payload for this list.
+    pub fn file_list(&self) -> Vec<u8> {
+        encode_file_list(&self.entries)
+    }
+
+    /// Answer a FileContents request from the remote.
+    pub fn contents_response(&self, request: &FileContentsRequest) -> FileContentsResponse<'static> {
+        let index = request.index as usize;
+        let (Some(entry), Some(source)) = (self.entries.get(index), self.sources.get(index)) else {
+            warn!("File contents request for unknown index {}", request.index);
+            return FileContentsResponse::new_error(request.stream_id);
+        };
+
+        if request.flags.contains(FileContentsFlags::SIZE) {
+            return FileContentsResponse::new_size_response(request.stream_id, entry.size.unwrap_or(0));
+        }
+
+        match read_range(source, request.position, request.requested_size) {
+            Ok(data) => FileContentsResponse::new_data_response(request.stream_id, data),
+            Err(e) => {
+                warn!("Failed to read {:?} for the remote clipboard: {}", source, e);
+                FileContentsResponse::new_error(request.stream_id)
+            }
+        }
+    }
+}
+
+/// Read up to `len` bytes of a file starting at `position`.
+fn read_range(path: &Path, position: u64, len: u32) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(position))?;
+    let mut data = Vec::with_capacity(len as usize);
+    file.take(u64::from(len)).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Whether a FileContents response reports failure (CB_RESPONSE_FAIL).
+///
+/// The response keeps its flags private, so they are read back from the
+/// header (msgFlags, then dataLen) of the encoded PDU.
+fn is_failure(response: &FileContentsResponse<'_>) -> bool {
+    match ironrdp_core::encode_vec(response) {
+        Ok(encoded) if encoded.len() >= 2 => {
+            ClipboardPduFlags::from_bits_truncate(u16::from_le_bytes([encoded[0], encoded[1]]))
+                .contains(ClipboardPduFlags::RESPONSE_FAIL)
+        }
+        _ => true,
+    }
+}
+
+/// Files saved from the remote clipboard.
+#[derive(Debug)]
+pub struct ReceivedFiles {
+    /// Local paths of the saved files.
+    pub files: Vec<PathBuf>,
+    /// Total bytes written.
+    pub total_bytes: u64,
+}
+
+/// File being written.
+#[derive(Debug)]
+struct CurrentFile {
+    index: u32,
+    path: PathBuf,
+    file: File,
+    /// Size, once known (requested from the remote when the descriptor omits it).
+    size: Option<u64>,
+    position: u64,
+    stream_id: u32,
+}
+
+/// An in-progress transfer from the remote clipboard to a local directory.
+#[derive(Debug)]
+pub struct IncomingFiles {
+    /// Remote format ID of the file list.
+    format_id: ClipboardFormatId,
+    dest_dir: PathBuf,
+    max_bytes: u64,
+    entries: Vec<FileEntry>,
+    /// Index of the next entry to start.
+    next_index: usize,
+    current: Option<CurrentFile>,
+    next_stream_id: u32,
+    files: Vec<PathBuf>,
+    total_bytes: u64,
+    response_tx: FilesResponder,
+}
+
+impl IncomingFiles {
+    pub fn new(
+        format_id: ClipboardFormatId,
+        dest_dir: PathBuf,
+        max_bytes: u64,
+        response_tx: FilesResponder,
+    ) -> Self {
+        Self {
+            format_id,
+            dest_dir,
+            max_bytes,
+            entries: Vec::new(),
+            next_index: 0,
+            current: None,
+            next_stream_id: 1,
+            files: Vec::new(),
+            total_bytes: 0,
+            response_tx,
+        }
+    }
+
+    /// Remote format ID of the file list being fetched.
+    pub fn format_id(&self) -> ClipboardFormatId {
+        self.format_id
+    }
+
+    /// Start the transfer from the remote file list, returning the first contents request.
+    pub fn start(&mut self, file_list: &[u8]) -> Result<Option<FileContentsRequest>, String> {
+        let entries = decode_file_list(file_list)?;
+        for entry in &entries {
+            relative_path(&entry.name)?;
+        }
+
+        let declared: u64 = entries.iter().filter_map(|entry| entry.size).sum();
+        if declared > self.max_bytes {
+            return Err(format!(
+                "Remote files ({} bytes) exceed the transfer limit of {} bytes",
+                declared, self.max_bytes
+            ));
+        }
+
+        info!("Receiving {} clipboard entries into {:?}", entries.len(), self.dest_dir);
+        self.entries = entries;
+        self.next_request()
+    }
+
+    /// Handle a FileContents response, returning the next request (None when done).
+    pub fn on_contents(&mut self, response: &FileContentsResponse<'_>) -> Result<Option<FileContentsRequest>, String> {
+        let remaining = self.max_bytes.saturating_sub(self.total_bytes);
+        let Some(current) = self.current.as_mut() else {
+            return Err("Unexpected file contents response".to_string());
+        };
+        if response.stream_id() != current.stream_id {
+            return Err(format!(
+                "File contents response for unknown stream {}",
+                response.stream_id()
+            ));
+        }
+        if is_failure(response) {
+            return Err(format!("Remote failed to send {:?}", current.path));
+        }
+
+        match current.size {
+            None => {
+                let size = response
+                    .data_as_size()
+                    .map_err(|e| format!("Invalid file size from remote: {}", e))?;
+                if size > remaining {
+                    return Err(format!(
+                        "Remote files exceed the transfer limit of {} bytes",
+                        self.max_bytes
+                    ));
+                }
+                current.size = Some(size);
+            }
+            Some(size) => {
+                let data = response.data();
+                if data.is_empty() || data.len() as u64 > size - current.position {
+                    return Err(format!("Remote sent a malformed chunk for {:?}", current.path));
+                }
+                if data.len() as u64 > remaining {
+                    return Err(format!(
+                        "Remote files exceed the transfer limit of {} bytes",
+                        self.max_bytes
+                    ));
+                }
+                current
+                    .file
+                    .write_all(data)
+                    .map_err(|e| format!("Failed to write {:?}: {}", current.path, e))?;
+                current.position += data.len() as u64;
+                self.total_bytes += data.len() as u64;
+            }
+        }
+
+        self.next_request()
+    }
+
+    /// Advance to the next request, creating directories and files along the way.
+    fn next_request(&mut self) -> Result<Option<FileContentsRequest>, String> {
+        loop {
+            if let Some(current) = self.current.as_mut() {
+                let request = match current.size {
+                    None => Some((FileContentsFlags::SIZE, 0, 8)),
+                    Some(size) if current.position < size => {
+                        let len = (size - current.position).min(u64::from(CHUNK_SIZE)) as u32;
+                        Some((FileContentsFlags::DATA, current.position, len))
+                    }
+                    Some(_) => None,
+                };
+
+                if let Some((flags, position, requested_size)) = request {
+                    current.stream_id = self.next_stream_id;
+                    self.next_stream_id = self.next_stream_id.wrapping_add(1);
+                    return Ok(Some(FileContentsRequest {
+                        stream_id: current.stream_id,
+                        index: current.index,
+                        flags,
+                        position,
+                        requested_size,
+                        data_id: None,
+                    }));
+                }
+
+                // Current file complete
+                if let Some(done) = self.current.take() {
+                    debug!("Received {:?} ({} bytes)", done.path, done.position);
+                    self.files.push(done.path);
+                }
+            }
+
+            let Some(entry) = self.entries.get(self.next_index) else {
+                return Ok(None);
+            };
+            let index = self.next_index as u32;
+            self.next_index += 1;
+
+            let path = self.dest_dir.join(relative_path(&entry.name)?);
+            if entry.is_dir {
+                fs::create_dir_all(&path).map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+                continue;
+            }
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+            }
+            let file = File::create(&path).map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+            self.current = Some(CurrentFile {
+                index,
+                path,
+                file,
+                size: entry.size,
+                position: 0,
+                stream_id: 0,
+            });
+        }
+    }
+
+    /// Report the saved files.
+    pub fn complete(self) {
+        info!(
+            "Saved {} clipboard files ({} bytes)",
+            self.files.len(),
+            self.total_bytes
+        );
+        let _ = self.response_tx.send(Ok(Some(ReceivedFiles {
+            files: self.files,
+            total_bytes: self.total_bytes,
+        })));
+    }
+
+    /// Abort the transfer, removing the partially written file.
+    pub fn fail(self, error: String) {
+        warn!("Clipboard file transfer failed: {}", error);
+        if let Some(current) = self.current {
+            drop(current.file);
+            let _ = fs::remove_file(&current.path);
+        }
+        let _ = self.response_tx.send(Err(error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_contents_failure() {
+        assert!(is_failure(&FileContentsResponse::new_error(7)));
+        assert!(!is_failure(&FileContentsResponse::new_size_response(7, 42)));
+        assert!(!is_failure(&FileContentsResponse::new_data_response(7, vec![1u8, 2, 3])));
+    }
+}
@@ -0,0 +1,127 @@
+//! Recent remote clipboard text.
+//!
+//! A remote copy only announces the available formats; the text itself
+//! crosses the channel when it is fetched (`clipboard get`, or a paste through
+//! the viewer). Each copy whose text was fetched is recorded once, so a copy
+//! that was never read leaves no entry.
+
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Entries kept when no depth is configured.
+pub const DEFAULT_HISTORY_DEPTH: usize = 5;
+
+/// Most entries a session can be configured to keep.
+pub const MAX_HISTORY_DEPTH: usize = 100;
+
+/// Text from one remote copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub text: String,
+    /// When the remote copy was announced, in milliseconds since the Unix epoch.
+    pub copied_at_ms: u64,
+}
+
+/// Ring buffer of the most recent remote copies, newest last.
+#[derive(Debug, Clone)]
+pub struct ClipboardHistory {
+    entries: VecDeque<HistoryEntry>,
+    depth: usize,
+    /// Time of the latest remote copy, until its text has been recorded.
+    unrecorded_copy_ms: Option<u64>,
+}
+
+impl Default for ClipboardHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_DEPTH)
+    }
+}
+
+impl ClipboardHistory {
+    /// History keeping up to `depth` entries (0 disables it).
+    pub fn new(depth: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(depth),
+            depth,
+            unrecorded_copy_ms: None,
+        }
+    }
+
+    /// Note a new remote copy; its text is recorded when it's first fetched.
+    pub fn on_remote_copy(&mut self) {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        self.unrecorded_copy_ms = Some(now_ms);
+    }
+
+    /// Record text fetched from the remote clipboard, unless the current copy
+    /// has already been recorded.
+    pub fn record(&mut self, text: &str) {
+        if self.depth == 0 || text.is_empty() {
+            return;
+        }
+        let Some(copied_at_ms) = self.unrecorded_copy_ms.take() else {
+            return;
+        };
+        if self.entries.len() == self.depth {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(HistoryEntry {
+            text: text.to_string(),
+            copied_at_ms,
+        });
+    }
+
+    /// Up to `count` entries (all when unset), newest first.
+    pub fn recent(&self, count: Option<usize>) -> Vec<HistoryEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .take(count.unwrap_or(self.depth))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_history() {
+        let mut history = ClipboardHistory::new(2);
+
+        // Text fetched without a new copy isn't recorded twice
+        history.on_remote_copy();
+        history.record("first");
+        history.record("first");
+        assert_eq!(history.recent(None).len(), 1);
+        assert!(history.recent(None)[0].copied_at_ms > 0);
+
+        // Oldest entries drop out, newest come first
+        for text in ["second", "third"] {
+            history.on_remote_copy();
+            history.record(text);
+        }
+        let texts: Vec<String> = history
+            .recent(None)
+            .into_iter()
+            .map(|entry| entry.text)
+            .collect();
+        assert_eq!(texts, ["third", "second"]);
+        assert_eq!(history.recent(Some(1))[0].text, "third");
+
+        // Copies that were never fetched and empty text leave no entry
+        history.on_remote_copy();
+        history.on_remote_copy();
+        history.record("");
+        assert_eq!(history.recent(None)[0].text, "third");
+
+        let mut disabled = ClipboardHistory::new(0);
+        disabled.on_remote_copy();
+        disabled.record("text");
+        assert!(disabled.recent(None).is_empty());
+    }
+}
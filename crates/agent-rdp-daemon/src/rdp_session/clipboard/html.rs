@@ -0,0 +1,165 @@
+//! Clipboard HTML (the registered "HTML Format", CF_HTML).
+//!
+//! The payload is UTF-8: a header of `Key:Value` lines giving byte offsets into
+//! the payload, followed by an HTML document whose copied part is marked with
+//! `<!--StartFragment-->` and `<!--EndFragment-->` comments.
+
+use ironrdp_cliprdr::pdu::{ClipboardFormat, ClipboardFormatId, ClipboardFormatName};
+
+/// Registered clipboard format name for HTML.
+pub const HTML_FORMAT: &str = "HTML Format";
+
+/// Format ID we register for our own HTML (registered formats start at 0xC000).
+const LOCAL_HTML_FORMAT_ID: u32 = 0xC0F1;
+
+const START_FRAGMENT: &str = "<!--StartFragment-->";
+const END_FRAGMENT: &str = "<!--EndFragment-->";
+
+/// Format announced for local HTML.
+pub fn local_html_format() -> ClipboardFormat {
+    ClipboardFormat::new(local_html_format_id()).with_name(ClipboardFormatName::new(HTML_FORMAT))
+}
+
+/// Format ID of local HTML.
+pub fn local_html_format_id() -> ClipboardFormatId {
+    ClipboardFormatId::new(LOCAL_HTML_FORMAT_ID)
+}
+
+/// Find the remote HTML format (its ID is chosen by the remote, so match by name).
+pub fn find_html_format(formats: &[ClipboardFormat]) -> Option<ClipboardFormatId> {
+    formats
+        .iter()
+        .find(|format| format.name().is_some_and(|name| name.value() == HTML_FORMAT))
+        .map(|format| format.id())
+}
+
+/// Wrap an HTML fragment in a CF_HTML document with its offset header.
+///
+/// Offsets are written as fixed-width numbers, so the header length is known
+/// before they are filled in.
+pub fn encode_cf_html(fragment: &str) -> String {
+    let header = |start_html: usize, end_html: usize, start_fragment: usize, end_fragment: usize| {
+        format!(
+            "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+            start_html, end_html, start_fragment, end_fragment
+        )
+    };
+    let prefix = format!("<html>\r\n<body>\r\n{}", START_FRAGMENT);
+    let suffix = format!("{}\r\n</body>\r\n</html>", END_FRAGMENT);
+
+    let start_html = header(0, 0, 0, 0).len();
+    let start_fragment = start_html + prefix.len();
+    let end_fragment = start_fragment + fragment.len();
+    let end_html = end_fragment + suffix.len();
+
+    format!(
+        "{}{}{}{}",
+        header(start_html, end_html, start_fragment, end_fragment),
+        prefix,
+        fragment,
+        suffix
+    )
+}
+
+/// Extract the copied fragment from a CF_HTML payload.
+///
+/// Uses the header offsets, falling back to the fragment markers when they're
+/// missing or out of range.
+pub fn decode_cf_html(data: &[u8]) -> Option<String> {
+    // Windows includes the null terminator in the data
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    let data = &data[..end];
+    let text = String::from_utf8_lossy(data);
+
+    let offset = |key: &str| -> Option<usize> {
+        text.lines()
+            .take_while(|line| !line.starts_with('<'))
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+            .and_then(|value| value.trim().parse().ok())
+    };
+
+    if let (Some(start), Some(end)) = (offset("StartFragment"), offset("EndFragment")) {
+        if let Some(fragment) = data.get(start..end) {
+            return Some(String::from_utf8_lossy(fragment).into_owned());
+        }
+    }
+
+    let start = text.find(START_FRAGMENT)? + START_FRAGMENT.len();
+    let end = text[start..].find(END_FRAGMENT)? + start;
+    Some(text[start..end].to_string())
+}
+
+/// Plain text for an HTML fragment, offered alongside it for targets that
+/// don't accept HTML.
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(tag_start) = rest.find('<') {
+        text.push_str(&rest[..tag_start]);
+        let Some(tag_end) = rest[tag_start..].find('>') else {
+            rest = &rest[tag_start..];
+            break;
+        };
+        let tag = rest[tag_start + 1..tag_start + tag_end].to_ascii_lowercase();
+        let name = tag.trim_start_matches('/').split([' ', '/']).next().unwrap_or("");
+        if matches!(name, "br" | "p" | "div" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6")
+            && (name == "br" || tag.starts_with('/'))
+        {
+            text.push('\n');
+        }
+        rest = &rest[tag_start + tag_end + 1..];
+    }
+    text.push_str(rest);
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cf_html_offsets() {
+        let fragment = "<b>Grüße</b> &amp; more";
+        let cf_html = encode_cf_html(fragment);
+        let offset = |key: &str| -> usize {
+            let start = cf_html.find(&format!("{}:", key)).unwrap() + key.len() + 1;
+            cf_html[start..start + 10].parse().unwrap()
+        };
+
+        assert!(cf_html.starts_with("Version:0.9\r\n"));
+        let (start_html, end_html) = (offset("StartHTML"), offset("EndHTML"));
+        let (start_fragment, end_fragment) = (offset("StartFragment"), offset("EndFragment"));
+
+        // Offsets count UTF-8 bytes from the start of the header
+        assert!(cf_html[start_html..].starts_with("<html>"));
+        assert_eq!(end_html, cf_html.len());
+        assert_eq!(&cf_html[start_fragment..end_fragment], fragment);
+        assert!(cf_html[..start_fragment].ends_with("<!--StartFragment-->"));
+        assert!(cf_html[end_fragment..].starts_with("<!--EndFragment-->"));
+
+        // Round trip, with the null terminator Windows adds
+        let mut data = cf_html.into_bytes();
+        data.push(0);
+        assert_eq!(decode_cf_html(&data).as_deref(), Some(fragment));
+    }
+
+    #[test]
+    fn test_cf_html_decode_falls_back_to_markers() {
+        let data = b"Version:0.9\r\nStartHTML:-1\r\nEndHTML:-1\r\n<html><body><!--StartFragment--><p>Hi</p><!--EndFragment--></body></html>";
+        assert_eq!(decode_cf_html(data).as_deref(), Some("<p>Hi</p>"));
+        assert_eq!(decode_cf_html(b"<p>no markers</p>"), None);
+
+        assert_eq!(
+            html_to_text("<p>Hello <b>world</b></p><p>a &lt; b &amp; c</p>line<br>break"),
+            "Hello world\na < b & c\nline\nbreak"
+        );
+    }
+}
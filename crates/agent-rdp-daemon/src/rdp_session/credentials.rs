@@ -0,0 +1,68 @@
+//! Credential providers, which supply the RDP username and password at connect time.
+//!
+//! The CLI passes credentials in the connect request and uses
+//! `StaticCredentialProvider`. Programs embedding the daemon can inject their
+//! own provider instead, for example one that reads a secret manager, so the
+//! password never appears in a request or a config file. The provider is asked
+//! again on every connection attempt, including reconnects, so rotated
+//! secrets are picked up.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use super::Password;
+
+/// Username and password for one connection attempt.
+#[derive(Clone, Debug)]
+pub struct Credentials {
+    pub username: String,
+    pub password: Password,
+}
+
+impl Credentials {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: Password::new(password),
+        }
+    }
+}
+
+/// Future returned by `CredentialProvider::credentials`.
+pub type CredentialsFuture<'a> = Pin<Box<dyn Future<Output = Result<Credentials>> + Send + 'a>>;
+
+/// Source of connection credentials.
+///
+/// The future is boxed so providers can be used as trait objects; implement
+/// `credentials` as `Box::pin(async move { ... })`. Debug output must not
+/// include the password.
+pub trait CredentialProvider: std::fmt::Debug + Send + Sync {
+    /// Fetch the credentials to log on with.
+    fn credentials(&self) -> CredentialsFuture<'_>;
+}
+
+/// Credential provider shared by the clones of a connection config.
+pub type SharedCredentialProvider = Arc<dyn CredentialProvider>;
+
+/// Credentials fixed when the provider is created (as given on the command line).
+#[derive(Clone, Debug)]
+pub struct StaticCredentialProvider {
+    credentials: Credentials,
+}
+
+impl StaticCredentialProvider {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            credentials: Credentials::new(username, password),
+        }
+    }
+}
+
+impl CredentialProvider for StaticCredentialProvider {
+    fn credentials(&self) -> CredentialsFuture<'_> {
+        Box::pin(async move { Ok(self.credentials.clone()) })
+    }
+}
@@ -0,0 +1,438 @@
+//! RD Gateway client (MS-TSGU HTTP transport over WebSocket).
+//!
+//! Opens a WebSocket to `https://<gateway>/remoteDesktopGateway/` with HTTP
+//! Basic authentication, runs the handshake/tunnel/channel exchange for the
+//! target host, then relays the RDP byte stream in DATA packets. The RDP
+//! connection (including its own TLS) runs inside the tunnel unchanged.
+//!
+//! The legacy RPC-over-HTTP transport, and NTLM/Kerberos authentication to the
+//! gateway, are not implemented.
+
+use base64::Engine;
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{header, HeaderValue, StatusCode};
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::WebSocketStream;
+use tracing::{debug, info, warn};
+
+use super::{CertCheck, Password, RdpError};
+
+/// Packet types (MS-TSGU 2.2.5.3.3).
+const PKT_TYPE_HANDSHAKE_REQUEST: u16 = 0x1;
+const PKT_TYPE_HANDSHAKE_RESPONSE: u16 = 0x2;
+const PKT_TYPE_TUNNEL_CREATE: u16 = 0x4;
+const PKT_TYPE_TUNNEL_RESPONSE: u16 = 0x5;
+const PKT_TYPE_TUNNEL_AUTH: u16 = 0x6;
+const PKT_TYPE_TUNNEL_AUTH_RESPONSE: u16 = 0x7;
+const PKT_TYPE_CHANNEL_CREATE: u16 = 0x8;
+const PKT_TYPE_CHANNEL_RESPONSE: u16 = 0x9;
+const PKT_TYPE_DATA: u16 = 0xA;
+const PKT_TYPE_KEEPALIVE: u16 = 0xD;
+const PKT_TYPE_CLOSE_CHANNEL: u16 = 0x10;
+const PKT_TYPE_CLOSE_CHANNEL_RESPONSE: u16 = 0x11;
+
+/// Size of the common packet header (type, reserved, length).
+const PACKET_HEADER_SIZE: usize = 8;
+
+/// Capabilities offered in the tunnel request (quarantine SoH, idle timeout).
+const TUNNEL_CAPABILITIES: u32 = 0x1 | 0x2;
+
+/// Channel protocol identifier for RDP.
+const CHANNEL_PROTOCOL_RDP: u16 = 3;
+
+/// Largest payload of a single DATA packet (its length field is 16 bits).
+const MAX_DATA_CHUNK: usize = 0xFFFF;
+
+/// Buffer between the RDP connection and the relay task.
+const TUNNEL_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Client name reported to the gateway.
+const CLIENT_NAME: &str = "agent-rdp";
+
+/// Configuration for connecting through an RD Gateway.
+#[derive(Clone, Debug)]
+pub struct GatewayConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: Password,
+    /// Check of the gateway's certificate, which guards the Basic credentials.
+    pub cert_check: CertCheck,
+}
+
+/// Open a tunnel through the gateway to `target_host:target_port`.
+///
+/// `stream` is a TLS connection to the gateway. Returns a stream carrying the raw
+/// RDP connection; it reports EOF once the gateway closes the channel.
+///
+/// The arguments are owned, so the future borrows nothing from the session
+/// task that reconnects through it.
+pub async fn open_tunnel<S>(
+    stream: S,
+    gateway: GatewayConfig,
+    target_host: String,
+    target_port: u16,
+) -> Result<DuplexStream, RdpError>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let mut ws = websocket_handshake(stream, &gateway).await?;
+    debug!("Gateway WebSocket established with {}", gateway.host);
+
+    // Packets may span or share WebSocket messages, so keep one buffer throughout
+    let mut incoming = PacketBuffer::default();
+
+    send_packet(&mut ws, PKT_TYPE_HANDSHAKE_REQUEST, &handshake_request()).await?;
+    let body = expect_packet(&mut ws, &mut incoming, PKT_TYPE_HANDSHAKE_RESPONSE).await?;
+    check_status(&body, 0, "Handshake")?;
+
+    send_packet(&mut ws, PKT_TYPE_TUNNEL_CREATE, &tunnel_create()).await?;
+    let body = expect_packet(&mut ws, &mut incoming, PKT_TYPE_TUNNEL_RESPONSE).await?;
+    // The status follows the 2-byte server version
+    check_status(&body, 2, "Tunnel creation")?;
+
+    send_packet(&mut ws, PKT_TYPE_TUNNEL_AUTH, &tunnel_auth()).await?;
+    let body = expect_packet(&mut ws, &mut incoming, PKT_TYPE_TUNNEL_AUTH_RESPONSE).await?;
+    check_status(&body, 0, "Tunnel authorization")?;
+
+    send_packet(
+        &mut ws,
+        PKT_TYPE_CHANNEL_CREATE,
+        &channel_create(&target_host, target_port),
+    )
+    .await?;
+    let body = expect_packet(&mut ws, &mut incoming, PKT_TYPE_CHANNEL_RESPONSE).await?;
+    let status = read_u32(&body, 0).unwrap_or(u32::MAX);
+    if status != 0 {
+        return Err(RdpError::GatewayRefused(
+            format!("{}:{}", target_host, target_port),
+            status_description(status),
+        ));
+    }
+
+    info!(
+        "Gateway {} opened a channel to {}:{}",
+        gateway.host, target_host, target_port
+    );
+
+    let (tunnel, relay_end) = tokio::io::duplex(TUNNEL_BUFFER_SIZE);
+    tokio::spawn(relay(ws, incoming, relay_end));
+    Ok(tunnel)
+}
+
+/// Upgrade the gateway connection to a WebSocket, authenticating with Basic auth.
+async fn websocket_handshake<S>(
+    stream: S,
+    gateway: &GatewayConfig,
+) -> Result<WebSocketStream<S>, RdpError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let url = format!(
+        "wss://{}:{}/remoteDesktopGateway/",
+        gateway.host, gateway.port
+    );
+    let mut request = url
+        .into_client_request()
+        .map_err(|e| RdpError::Gateway(format!("Invalid gateway address: {}", e)))?;
+
+    let credentials = base64::engine::general_purpose::STANDARD.encode(format!(
+        "{}:{}",
+        gateway.username,
+        gateway.password.expose()
+    ));
+    let headers = request.headers_mut();
+    headers.insert(
+        header::AUTHORIZATION,
+        HeaderValue::from_str(&format!("Basic {}", credentials)).map_err(|_| {
+            RdpError::Gateway("Gateway credentials contain invalid characters".into())
+        })?,
+    );
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    headers.insert(header::PRAGMA, HeaderValue::from_static("no-cache"));
+    if let Ok(connection_id) = HeaderValue::from_str(&format!("{{{}}}", uuid::Uuid::new_v4())) {
+        headers.insert("rdg-connection-id", connection_id);
+    }
+
+    match tokio_tungstenite::client_async(request, stream).await {
+        Ok((ws, _)) => Ok(ws),
+        Err(tungstenite::Error::Http(response)) => Err(http_error(
+            response.status(),
+            response
+                .headers()
+                .get_all(header::WWW_AUTHENTICATE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .filter_map(|value| value.split_whitespace().next())
+                .collect(),
+        )),
+        Err(e) => Err(RdpError::Gateway(format!(
+            "WebSocket handshake failed: {}",
+            e
+        ))),
+    }
+}
+
+/// Explain an HTTP error returned instead of the WebSocket upgrade.
+fn http_error(status: StatusCode, auth_schemes: Vec<&str>) -> RdpError {
+    if status == StatusCode::UNAUTHORIZED {
+        if !auth_schemes.is_empty() && !auth_schemes.iter().any(|s| s.eq_ignore_ascii_case("basic"))
+        {
+            return RdpError::Gateway(format!(
+                "Gateway requires {} authentication; only Basic is supported",
+                auth_schemes.join("/")
+            ));
+        }
+        return RdpError::Gateway("Gateway rejected the credentials (HTTP 401)".into());
+    }
+    RdpError::Gateway(format!(
+        "Gateway returned HTTP {} (is the WebSocket transport enabled?)",
+        status
+    ))
+}
+
+/// Relay RDP bytes between the tunnel stream and the gateway until either side closes.
+async fn relay<S>(ws: WebSocketStream<S>, mut incoming: PacketBuffer, stream: DuplexStream)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (mut ws_tx, mut ws_rx) = ws.split();
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let mut buf = vec![0u8; MAX_DATA_CHUNK];
+
+    loop {
+        tokio::select! {
+            read = reader.read(&mut buf) => {
+                let n = match read {
+                    Ok(0) | Err(_) => {
+                        let _ = ws_tx.send(status_message(PKT_TYPE_CLOSE_CHANNEL)).await;
+                        break;
+                    }
+                    Ok(n) => n,
+                };
+                if let Err(e) = ws_tx.send(data_message(&buf[..n])).await {
+                    warn!("Gateway send failed: {}", e);
+                    break;
+                }
+            }
+            msg = ws_rx.next() => {
+                let data = match msg {
+                    Some(Ok(Message::Binary(data))) => data,
+                    Some(Ok(Message::Close(_))) | None => {
+                        info!("Gateway closed the connection");
+                        break;
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        warn!("Gateway receive failed: {}", e);
+                        break;
+                    }
+                };
+                incoming.extend(&data);
+                while let Some((packet_type, body)) = incoming.next_packet() {
+                    match packet_type {
+                        PKT_TYPE_DATA => {
+                            let len = read_u16(&body, 0).map(usize::from).unwrap_or(0);
+                            let payload = body.get(2..2 + len).unwrap_or_default();
+                            if writer.write_all(payload).await.is_err() {
+                                return;
+                            }
+                        }
+                        PKT_TYPE_KEEPALIVE => {}
+                        PKT_TYPE_CLOSE_CHANNEL => {
+                            info!("Gateway closed the channel");
+                            let _ = ws_tx.send(status_message(PKT_TYPE_CLOSE_CHANNEL_RESPONSE)).await;
+                            return;
+                        }
+                        other => debug!("Ignoring gateway packet type 0x{:X}", other),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reassembles packets from WebSocket messages.
+#[derive(Default)]
+struct PacketBuffer {
+    buf: Vec<u8>,
+}
+
+impl PacketBuffer {
+    fn extend(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Take the next complete packet as (type, body).
+    fn next_packet(&mut self) -> Option<(u16, Vec<u8>)> {
+        let packet_type = read_u16(&self.buf, 0)?;
+        let length = read_u32(&self.buf, 4)? as usize;
+        if length < PACKET_HEADER_SIZE {
+            // Malformed length; drop the buffer rather than looping forever
+            self.buf.clear();
+            return None;
+        }
+        if self.buf.len() < length {
+            return None;
+        }
+        let body = self.buf[PACKET_HEADER_SIZE..length].to_vec();
+        self.buf.drain(..length);
+        Some((packet_type, body))
+    }
+}
+
+/// Send one packet as a binary WebSocket message.
+async fn send_packet<S>(
+    ws: &mut WebSocketStream<S>,
+    packet_type: u16,
+    body: &[u8],
+) -> Result<(), RdpError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    ws.send(Message::Binary(packet(packet_type, body)))
+        .await
+        .map_err(|e| RdpError::Gateway(format!("Send failed: {}", e)))
+}
+
+/// Wait for a packet of the given type, skipping keepalives and other messages.
+async fn expect_packet<S>(
+    ws: &mut WebSocketStream<S>,
+    incoming: &mut PacketBuffer,
+    packet_type: u16,
+) -> Result<Vec<u8>, RdpError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        while let Some((received_type, body)) = incoming.next_packet() {
+            if received_type == packet_type {
+                return Ok(body);
+            }
+            debug!("Skipping gateway packet type 0x{:X}", received_type);
+        }
+        match ws.next().await {
+            Some(Ok(Message::Binary(data))) => incoming.extend(&data),
+            Some(Ok(Message::Close(_))) | None => {
+                return Err(RdpError::Gateway(
+                    "Gateway closed the connection during setup".into(),
+                ));
+            }
+            Some(Ok(_)) => {}
+            Some(Err(e)) => return Err(RdpError::Gateway(format!("Receive failed: {}", e))),
+        }
+    }
+}
+
+/// Fail with a description if a response's status code (at `offset`) is an error.
+fn check_status(body: &[u8], offset: usize, stage: &str) -> Result<(), RdpError> {
+    match read_u32(body, offset) {
+        Some(0) => Ok(()),
+        Some(status) => Err(RdpError::Gateway(format!(
+            "{} failed: {}",
+            stage,
+            status_description(status)
+        ))),
+        None => Err(RdpError::Gateway(format!(
+            "{} response is truncated",
+            stage
+        ))),
+    }
+}
+
+/// Describe a gateway status code (an HRESULT).
+fn status_description(status: u32) -> String {
+    let name = match status {
+        0x800759D8 => "internal gateway error",
+        0x800759DA => "resource authorization policy denied access to the target",
+        0x800759DB => "network access protection denied access",
+        0x800759DD => "gateway could not connect to the target",
+        0x800759E0 => "gateway reached its connection limit",
+        0x800759E9 => "capability mismatch",
+        0x800759F9 => "unsupported authentication method",
+        _ => return format!("status 0x{:08X}", status),
+    };
+    format!("{} (0x{:08X})", name, status)
+}
+
+/// Build a packet with the common header.
+fn packet(packet_type: u16, body: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(PACKET_HEADER_SIZE + body.len());
+    packet.extend_from_slice(&packet_type.to_le_bytes());
+    packet.extend_from_slice(&0u16.to_le_bytes());
+    packet.extend_from_slice(&((PACKET_HEADER_SIZE + body.len()) as u32).to_le_bytes());
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// DATA packet carrying up to `MAX_DATA_CHUNK` bytes of the RDP stream.
+fn data_message(data: &[u8]) -> Message {
+    let mut body = Vec::with_capacity(data.len() + 2);
+    body.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    body.extend_from_slice(data);
+    Message::Binary(packet(PKT_TYPE_DATA, &body))
+}
+
+/// Packet whose body is a single success status code (close channel and its response).
+fn status_message(packet_type: u16) -> Message {
+    Message::Binary(packet(packet_type, &0u32.to_le_bytes()))
+}
+
+/// Handshake request: version 1.0, no extended authentication.
+fn handshake_request() -> Vec<u8> {
+    let mut body = vec![1, 0];
+    body.extend_from_slice(&0u16.to_le_bytes()); // Client version
+    body.extend_from_slice(&0u16.to_le_bytes()); // Extended auth: none
+    body
+}
+
+/// Tunnel create request with no optional fields (no PAA cookie).
+fn tunnel_create() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&TUNNEL_CAPABILITIES.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes()); // Fields present
+    body.extend_from_slice(&0u16.to_le_bytes()); // Reserved
+    body
+}
+
+/// Tunnel authorization request carrying the client name.
+fn tunnel_auth() -> Vec<u8> {
+    let name = utf16_null_terminated(CLIENT_NAME);
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u16.to_le_bytes()); // Fields present
+    body.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    body.extend_from_slice(&name);
+    body
+}
+
+/// Channel create request for one RDP resource.
+fn channel_create(target_host: &str, target_port: u16) -> Vec<u8> {
+    let name = utf16_null_terminated(target_host);
+    let mut body = vec![1, 0]; // One resource, no alternates
+    body.extend_from_slice(&target_port.to_le_bytes());
+    body.extend_from_slice(&CHANNEL_PROTOCOL_RDP.to_le_bytes());
+    body.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    body.extend_from_slice(&name);
+    body
+}
+
+/// Encode a string as null-terminated UTF-16LE.
+fn utf16_null_terminated(value: &str) -> Vec<u8> {
+    value
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect()
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -0,0 +1,179 @@
+//! Keys and mouse buttons held down on the server.
+//!
+//! Every scancode press we send is recorded until its release, so keys left
+//! down on purpose (`keyboard hold`, raw presses) or by an interrupted
+//! combination can be released later, and always are before disconnecting.
+//! Mouse buttons (`mouse down`) are followed the same way.
+
+use ironrdp::pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
+use ironrdp::pdu::input::mouse::{MousePdu, PointerFlags};
+
+/// Button flags of the mouse events we send.
+const MOUSE_BUTTONS: [PointerFlags; 3] = [
+    PointerFlags::LEFT_BUTTON,
+    PointerFlags::RIGHT_BUTTON,
+    PointerFlags::MIDDLE_BUTTON_OR_WHEEL,
+];
+
+/// Scancodes currently pressed, in press order.
+#[derive(Debug, Default, Clone)]
+pub struct HeldKeys {
+    /// (scancode, extended) pairs.
+    keys: Vec<(u8, bool)>,
+}
+
+impl HeldKeys {
+    /// Follow the keyboard events we send to the server.
+    pub fn track_input(&mut self, events: &[FastPathInputEvent]) {
+        for event in events {
+            if let FastPathInputEvent::KeyboardEvent(flags, scancode) = event {
+                let key = (*scancode, flags.contains(KeyboardFlags::EXTENDED));
+                if flags.contains(KeyboardFlags::RELEASE) {
+                    self.keys.retain(|held| *held != key);
+                } else if !self.keys.contains(&key) {
+                    self.keys.push(key);
+                }
+            }
+        }
+    }
+
+    /// Pressed keys as (scancode, extended) pairs, in press order.
+    pub fn keys(&self) -> &[(u8, bool)] {
+        &self.keys
+    }
+
+    /// Release events for every held key, most recently pressed first.
+    pub fn release_events(&self) -> Vec<FastPathInputEvent> {
+        self.keys
+            .iter()
+            .rev()
+            .map(|&(scancode, extended)| {
+                let mut flags = KeyboardFlags::RELEASE;
+                if extended {
+                    flags |= KeyboardFlags::EXTENDED;
+                }
+                FastPathInputEvent::KeyboardEvent(flags, scancode)
+            })
+            .collect()
+    }
+}
+
+/// Mouse buttons currently pressed, in press order.
+#[derive(Debug, Default, Clone)]
+pub struct HeldButtons {
+    buttons: Vec<PointerFlags>,
+}
+
+impl HeldButtons {
+    /// Follow the mouse events we send to the server.
+    pub fn track_input(&mut self, events: &[FastPathInputEvent]) {
+        for event in events {
+            let FastPathInputEvent::MouseEvent(pdu) = event else {
+                continue;
+            };
+            // The middle button flag doubles as the wheel flag in wheel events
+            if pdu
+                .flags
+                .intersects(PointerFlags::VERTICAL_WHEEL | PointerFlags::HORIZONTAL_WHEEL)
+            {
+                continue;
+            }
+            for button in MOUSE_BUTTONS {
+                if !pdu.flags.contains(button) {
+                    continue;
+                }
+                if pdu.flags.contains(PointerFlags::DOWN) {
+                    if !self.buttons.contains(&button) {
+                        self.buttons.push(button);
+                    }
+                } else {
+                    self.buttons.retain(|held| *held != button);
+                }
+            }
+        }
+    }
+
+    /// Pressed buttons as pointer flags, in press order.
+    pub fn buttons(&self) -> &[PointerFlags] {
+        &self.buttons
+    }
+
+    /// Release events at (`x`, `y`) for every held button, most recently pressed first.
+    pub fn release_events(&self, x: u16, y: u16) -> Vec<FastPathInputEvent> {
+        self.buttons
+            .iter()
+            .rev()
+            .map(|&button| {
+                FastPathInputEvent::MouseEvent(MousePdu {
+                    flags: button,
+                    number_of_wheel_rotation_units: 0,
+                    x_position: x,
+                    y_position: y,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rdp_session::create_key_event;
+
+    #[test]
+    fn test_held_keys_tracking() {
+        let mut held = HeldKeys::default();
+        // Combination left half-done: ctrl and right alt down, c pressed and released
+        held.track_input(&[
+            create_key_event(0x1D, false, false),
+            create_key_event(0x38, true, false),
+            create_key_event(0x2E, false, false),
+            create_key_event(0x2E, false, true),
+        ]);
+        assert_eq!(held.keys(), &[(0x1D, false), (0x38, true)]);
+
+        // Released most recent first; the left alt was never pressed
+        let release = held.release_events();
+        assert_eq!(release.len(), 2);
+        assert!(matches!(
+            release[0],
+            FastPathInputEvent::KeyboardEvent(flags, 0x38)
+                if flags.contains(KeyboardFlags::RELEASE) && flags.contains(KeyboardFlags::EXTENDED)
+        ));
+
+        held.track_input(&release);
+        assert!(held.keys().is_empty());
+        assert!(held.release_events().is_empty());
+    }
+
+    #[test]
+    fn test_held_buttons_tracking() {
+        let mouse = |flags: PointerFlags| {
+            FastPathInputEvent::MouseEvent(MousePdu {
+                flags,
+                number_of_wheel_rotation_units: 0,
+                x_position: 10,
+                y_position: 20,
+            })
+        };
+        let mut held = HeldButtons::default();
+        // A click leaves nothing held; a down and a wheel turn leave only the button
+        held.track_input(&[
+            mouse(PointerFlags::LEFT_BUTTON | PointerFlags::DOWN),
+            mouse(PointerFlags::LEFT_BUTTON),
+            mouse(PointerFlags::RIGHT_BUTTON | PointerFlags::DOWN),
+            mouse(PointerFlags::VERTICAL_WHEEL | PointerFlags::MIDDLE_BUTTON_OR_WHEEL),
+        ]);
+        assert_eq!(held.buttons(), &[PointerFlags::RIGHT_BUTTON]);
+
+        let release = held.release_events(30, 40);
+        assert!(matches!(
+            &release[..],
+            [FastPathInputEvent::MouseEvent(pdu)]
+                if pdu.flags == PointerFlags::RIGHT_BUTTON && (pdu.x_position, pdu.y_position) == (30, 40)
+        ));
+
+        held.track_input(&release);
+        assert!(held.buttons().is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+//! Client identity reported to the server.
+//!
+//! Windows keys per-device state (license tokens, device redirection policy,
+//! reconnect matching) on the client name and hardware ID. Without a hardware
+//! ID every connect looks like a new device, so one is derived from the
+//! session name unless given explicitly.
+
+use sha2::{Digest, Sha256};
+
+/// Client name reported when none is configured.
+pub const DEFAULT_CLIENT_NAME: &str = "agent-rdp";
+
+/// Longest client name the core data block holds (16 UTF-16 units with the terminator).
+const MAX_CLIENT_NAME_LEN: usize = 15;
+
+/// Longest product ID the core data block holds (32 UTF-16 units with the terminator).
+const MAX_DIG_PRODUCT_ID_LEN: usize = 31;
+
+/// Name, build, product ID and hardware ID presented during the connection sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientIdentity {
+    pub client_name: String,
+    pub client_build: u32,
+    pub dig_product_id: String,
+    pub hardware_id: [u32; 4],
+}
+
+impl ClientIdentity {
+    /// Identity for a session, with unset fields falling back to the defaults.
+    ///
+    /// Returns a message naming the offending field when an override doesn't
+    /// fit the connector's limits.
+    pub fn new(
+        session_name: &str,
+        client_name: Option<String>,
+        client_build: Option<u32>,
+        dig_product_id: Option<String>,
+        hardware_id: Option<&str>,
+    ) -> Result<Self, String> {
+        let client_name = client_name.unwrap_or_else(|| DEFAULT_CLIENT_NAME.to_string());
+        let name_len = client_name.encode_utf16().count();
+        if name_len == 0 || name_len > MAX_CLIENT_NAME_LEN {
+            return Err(format!(
+                "Client name must be 1-{} characters, got {}",
+                MAX_CLIENT_NAME_LEN, name_len
+            ));
+        }
+        if client_name.chars().any(char::is_control) {
+            return Err("Client name must not contain control characters".to_string());
+        }
+
+        let dig_product_id = dig_product_id.unwrap_or_default();
+        let product_id_len = dig_product_id.encode_utf16().count();
+        if product_id_len > MAX_DIG_PRODUCT_ID_LEN {
+            return Err(format!(
+                "Product ID must be at most {} characters, got {}",
+                MAX_DIG_PRODUCT_ID_LEN, product_id_len
+            ));
+        }
+
+        let hardware_id = match hardware_id {
+            Some(value) => parse_hardware_id(value)?,
+            None => session_hardware_id(session_name),
+        };
+
+        Ok(Self {
+            client_name,
+            client_build: client_build.unwrap_or(0),
+            dig_product_id,
+            hardware_id,
+        })
+    }
+}
+
+/// Hardware ID derived from the session name, the same on every connect.
+pub fn session_hardware_id(session_name: &str) -> [u32; 4] {
+    let digest = Sha256::new()
+        .chain_update(b"agent-rdp hardware id\0")
+        .chain_update(session_name.as_bytes())
+        .finalize();
+    let mut id = [0u32; 4];
+    for (word, bytes) in id.iter_mut().zip(digest.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    id
+}
+
+/// Parse a hardware ID given as 32 hex digits; dashes and braces are ignored,
+/// so a GUID can be passed as is.
+pub fn parse_hardware_id(value: &str) -> Result<[u32; 4], String> {
+    let hex: String = value
+        .chars()
+        .filter(|c| !matches!(c, '-' | '{' | '}'))
+        .collect();
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "Invalid hardware ID '{}': expected 32 hex digits",
+            value
+        ));
+    }
+
+    let mut id = [0u32; 4];
+    for (i, word) in id.iter_mut().enumerate() {
+        *word = u32::from_str_radix(&hex[i * 8..i * 8 + 8], 16)
+            .map_err(|e| format!("Invalid hardware ID '{}': {}", value, e))?;
+    }
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_identity_defaults() {
+        let identity = ClientIdentity::new("work", None, None, None, None).unwrap();
+        assert_eq!(identity.client_name, DEFAULT_CLIENT_NAME);
+        assert_eq!(identity.client_build, 0);
+        assert_eq!(identity.dig_product_id, "");
+
+        // Stable per session, distinct across sessions
+        assert_eq!(identity.hardware_id, session_hardware_id("work"));
+        assert_eq!(session_hardware_id("work"), session_hardware_id("work"));
+        assert_ne!(session_hardware_id("work"), session_hardware_id("default"));
+    }
+
+    #[test]
+    fn test_client_identity_overrides() {
+        let identity = ClientIdentity::new(
+            "work",
+            Some("BUILD-07".to_string()),
+            Some(19045),
+            Some("00330-80000-00000-AA123".to_string()),
+            Some("{00112233-4455-6677-8899-AABBCCDDEEFF}"),
+        )
+        .unwrap();
+        assert_eq!(identity.client_name, "BUILD-07");
+        assert_eq!(identity.client_build, 19045);
+        assert_eq!(identity.dig_product_id, "00330-80000-00000-AA123");
+        assert_eq!(
+            identity.hardware_id,
+            [0x00112233, 0x44556677, 0x8899aabb, 0xccddeeff]
+        );
+    }
+
+    #[test]
+    fn test_client_identity_validation() {
+        let new = |name: &str, product_id: &str, hardware_id: Option<&str>| {
+            ClientIdentity::new(
+                "default",
+                Some(name.to_string()),
+                None,
+                Some(product_id.to_string()),
+                hardware_id,
+            )
+        };
+
+        assert!(new("", "", None).is_err());
+        assert!(new("ABCDEFGHIJKLMNO", "", None).is_ok());
+        assert!(new("ABCDEFGHIJKLMNOP", "", None).is_err());
+        assert!(new("tab\there", "", None).is_err());
+        assert!(new("host", &"9".repeat(31), None).is_ok());
+        assert!(new("host", &"9".repeat(32), None).is_err());
+        assert!(new("host", "", Some("0011223344556677")).is_err());
+        assert!(new("host", "", Some("00112233445566778899aabbccddeefg")).is_err());
+        assert!(new("host", "", Some("00112233445566778899aabbccddeeff")).is_ok());
+    }
+}
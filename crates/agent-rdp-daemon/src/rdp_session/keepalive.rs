@@ -0,0 +1,126 @@
+//! Keepalive for connections whose network path dies silently.
+//!
+//! When a NAT mapping expires or a VPN drops while the remote desktop is idle,
+//! no frames arrive and nothing fails until the next write, which can then
+//! hang for minutes in TCP retransmission. With a keepalive interval set, the
+//! frame processor sends a heartbeat after each interval without a frame from
+//! the server, and declares the connection lost once the server has been
+//! silent for `MISSED_INTERVALS` intervals.
+//!
+//! The heartbeat is a mouse move to the pointer's current position, so it
+//! changes nothing on the remote desktop. It does count as input, so the
+//! server's own idle timeout won't end the session while keepalive is on.
+
+use std::time::{Duration, Instant};
+
+use ironrdp::pdu::input::fast_path::FastPathInputEvent;
+use ironrdp::pdu::input::mouse::{MousePdu, PointerFlags};
+
+use super::pointer::PointerState;
+
+/// Silent intervals after which the connection is considered dead.
+pub const MISSED_INTERVALS: u32 = 3;
+
+/// What the frame processor should do on a keepalive tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepaliveAction {
+    /// The server sent something within the last interval.
+    None,
+    /// The server has been quiet for an interval; send a heartbeat.
+    Heartbeat,
+    /// The server has been silent for this long; give up on the connection.
+    Dead(Duration),
+}
+
+/// Time since the server was last heard from.
+#[derive(Debug, Clone)]
+pub struct Keepalive {
+    interval: Duration,
+    last_received: Instant,
+}
+
+impl Keepalive {
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Self {
+            interval,
+            last_received: now,
+        }
+    }
+
+    /// How often to check on the connection.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Note a frame from the server.
+    pub fn on_received(&mut self, now: Instant) {
+        self.last_received = now;
+    }
+
+    /// Decide what to do on a tick.
+    pub fn check(&self, now: Instant) -> KeepaliveAction {
+        let silent = now.saturating_duration_since(self.last_received);
+        if silent >= self.interval * MISSED_INTERVALS {
+            KeepaliveAction::Dead(silent)
+        } else if silent >= self.interval {
+            KeepaliveAction::Heartbeat
+        } else {
+            KeepaliveAction::None
+        }
+    }
+}
+
+/// Heartbeat input: a move to where the pointer already is.
+pub fn heartbeat(pointer: &PointerState) -> FastPathInputEvent {
+    FastPathInputEvent::MouseEvent(MousePdu {
+        flags: PointerFlags::MOVE,
+        number_of_wheel_rotation_units: 0,
+        x_position: pointer.x,
+        y_position: pointer.y,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keepalive() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(10);
+        let mut keepalive = Keepalive::new(interval, start);
+        assert_eq!(
+            keepalive.check(start + Duration::from_secs(5)),
+            KeepaliveAction::None
+        );
+        assert_eq!(
+            keepalive.check(start + interval),
+            KeepaliveAction::Heartbeat
+        );
+
+        // A frame from the server resets the silence
+        keepalive.on_received(start + Duration::from_secs(25));
+        assert_eq!(
+            keepalive.check(start + Duration::from_secs(30)),
+            KeepaliveAction::None
+        );
+        assert_eq!(
+            keepalive.check(start + Duration::from_secs(45)),
+            KeepaliveAction::Heartbeat
+        );
+        assert_eq!(
+            keepalive.check(start + Duration::from_secs(55)),
+            KeepaliveAction::Dead(Duration::from_secs(30))
+        );
+
+        let pointer = PointerState {
+            x: 12,
+            y: 34,
+            ..Default::default()
+        };
+        let FastPathInputEvent::MouseEvent(event) = heartbeat(&pointer) else {
+            panic!("heartbeat should be a mouse event");
+        };
+        assert_eq!((event.x_position, event.y_position), (12, 34));
+    }
+}
@@ -0,0 +1,162 @@
+//! Kerberos for CredSSP: the KDC address and the network client that carries
+//! the KDC exchanges.
+//!
+//! Without a KDC, CredSSP authenticates with NTLM, which needs no network
+//! round-trips of its own. With one, the sspi Kerberos generator asks for the
+//! AS and TGS exchanges, which are sent here over TCP or UDP (RFC 4120 section
+//! 7.2). KDC traffic goes straight to the KDC, not through the proxy or gateway.
+
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use ironrdp::connector::credssp::KerberosConfig;
+use ironrdp::connector::sspi::generator::NetworkRequest;
+use ironrdp::connector::sspi::network_client::NetworkProtocol;
+use ironrdp::connector::{self, ConnectorResult};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tracing::debug;
+use url::Url;
+
+/// Standard Kerberos port, used when the KDC address has none.
+pub const DEFAULT_KDC_PORT: u16 = 88;
+
+/// Time allowed for one KDC exchange.
+const KDC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest KDC reply accepted over TCP.
+const MAX_TCP_REPLY: usize = 1024 * 1024;
+
+/// Largest UDP datagram.
+const MAX_UDP_REPLY: usize = 65_535;
+
+/// Parse a KDC address: `host`, `host:port`, `tcp://host[:port]` or `udp://host[:port]`.
+///
+/// A bare address uses TCP, and the port defaults to 88.
+pub fn parse_kdc_url(kdc: &str) -> Result<Url, String> {
+    let with_scheme = if kdc.contains("://") {
+        kdc.to_string()
+    } else {
+        format!("tcp://{}", kdc)
+    };
+    let mut url = Url::parse(&with_scheme)
+        .map_err(|e| format!("Invalid KDC address '{}': {}", kdc, e))?;
+
+    if !matches!(url.scheme(), "tcp" | "udp") {
+        return Err(format!(
+            "Unsupported KDC scheme '{}': expected tcp or udp",
+            url.scheme()
+        ));
+    }
+    if url.host_str().map_or(true, str::is_empty) {
+        return Err(format!("Invalid KDC address '{}': missing host", kdc));
+    }
+    if url.port().is_none() {
+        // Only fails for URLs that can't have a port, which tcp/udp URLs with a host can
+        let _ = url.set_port(Some(DEFAULT_KDC_PORT));
+    }
+    Ok(url)
+}
+
+/// Kerberos settings for CredSSP, with the client's name as the workstation.
+pub fn kerberos_config(kdc_url: &str, client_name: &str) -> Result<KerberosConfig, String> {
+    Ok(KerberosConfig {
+        kdc_proxy_url: Some(parse_kdc_url(kdc_url)?),
+        hostname: Some(client_name.to_string()),
+    })
+}
+
+/// Network client for CredSSP that sends KDC requests over TCP or UDP.
+///
+/// NTLM never issues requests, so the same client serves both.
+pub struct KdcNetworkClient;
+
+impl ironrdp_tokio::NetworkClient for KdcNetworkClient {
+    async fn send(&mut self, network_request: &NetworkRequest) -> ConnectorResult<Vec<u8>> {
+        match tokio::time::timeout(KDC_TIMEOUT, exchange(network_request)).await {
+            Ok(Ok(reply)) => Ok(reply),
+            Ok(Err(e)) => Err(connector::custom_err!("KDC request", e)),
+            Err(_) => Err(connector::general_err!("KDC request timed out")),
+        }
+    }
+}
+
+/// Send one request to the KDC and return its reply in the form sspi expects.
+async fn exchange(request: &NetworkRequest) -> io::Result<Vec<u8>> {
+    let host = request.url.host_str().unwrap_or_default();
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = request.url.port().unwrap_or(DEFAULT_KDC_PORT);
+    let addr = tokio::net::lookup_host((host, port))
+        .await?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("KDC {} not found", host)))?;
+    debug!("KDC request to {} ({} bytes)", addr, request.data.len());
+
+    match request.protocol {
+        NetworkProtocol::Tcp => send_tcp(addr, &request.data).await,
+        NetworkProtocol::Udp => send_udp(addr, &request.data).await,
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "KDC proxies over HTTP(S) are not supported; use a tcp:// or udp:// KDC",
+        )),
+    }
+}
+
+/// Exchange over TCP. The request already carries its 4-byte length prefix, and
+/// the reply is returned with its own.
+async fn send_tcp(addr: SocketAddr, data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(addr).await?;
+    stream.write_all(data).await?;
+
+    let len = stream.read_u32().await? as usize;
+    if len > MAX_TCP_REPLY {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("KDC reply of {} bytes is too large", len),
+        ));
+    }
+    let mut reply = vec![0u8; 4 + len];
+    reply[..4].copy_from_slice(&(len as u32).to_be_bytes());
+    stream.read_exact(&mut reply[4..]).await?;
+    Ok(reply)
+}
+
+/// Exchange over UDP. The reply datagram is returned with a 4-byte length
+/// prefix, the same as a TCP reply.
+async fn send_udp(addr: SocketAddr, data: &[u8]) -> io::Result<Vec<u8>> {
+    let bind_addr: SocketAddr = if addr.is_ipv4() {
+        ([0, 0, 0, 0], 0).into()
+    } else {
+        ([0u16; 8], 0).into()
+    };
+    let socket = UdpSocket::bind(bind_addr).await?;
+    socket.connect(addr).await?;
+    socket.send(data).await?;
+
+    let mut buf = vec![0u8; MAX_UDP_REPLY];
+    let len = socket.recv(&mut buf).await?;
+    let mut reply = Vec::with_capacity(4 + len);
+    reply.extend_from_slice(&(len as u32).to_be_bytes());
+    reply.extend_from_slice(&buf[..len]);
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kdc_url_parsing() {
+        let url = parse_kdc_url("dc01.corp.example").unwrap();
+        assert_eq!(url.as_str(), "tcp://dc01.corp.example:88");
+
+        let url = parse_kdc_url("udp://10.0.0.5:8888").unwrap();
+        assert_eq!((url.scheme(), url.port()), ("udp", Some(8888)));
+        assert_eq!(parse_kdc_url("[::1]").unwrap().port(), Some(88));
+
+        assert!(parse_kdc_url("https://kdcproxy.corp/KdcProxy").is_err());
+        assert!(parse_kdc_url("tcp://").is_err());
+        assert!(parse_kdc_url("dc01:notaport").is_err());
+    }
+}
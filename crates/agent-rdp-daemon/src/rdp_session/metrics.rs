@@ -0,0 +1,124 @@
+//! Connection latency and traffic counters.
+//!
+//! Round trips are estimated from input: the time between sending an input
+//! frame and the next frame from the server. The server doesn't answer every
+//! input, so samples longer than `MAX_RTT_SAMPLE_MS` are taken to be unrelated
+//! updates and dropped.
+
+use std::time::{Duration, Instant};
+
+/// Longest gap between input and the next server frame counted as a round trip.
+pub const MAX_RTT_SAMPLE_MS: u64 = 2000;
+
+/// Weight of each new sample in the rolling average (as in TCP's smoothed RTT).
+const RTT_SMOOTHING: f64 = 0.125;
+
+/// Latency and traffic for a session, kept across reconnects.
+#[derive(Debug, Default, Clone)]
+pub struct SessionMetrics {
+    /// How long the latest connection handshake took.
+    connect_duration: Option<Duration>,
+    /// Rolling average round trip, in milliseconds.
+    avg_rtt_ms: Option<f64>,
+    /// Most recent round trip sample.
+    last_rtt: Option<Duration>,
+    /// When the oldest input not yet followed by a server frame was sent.
+    unanswered_input: Option<Instant>,
+    frames_received: u64,
+    bytes_received: u64,
+}
+
+impl SessionMetrics {
+    /// Record a newly established connection.
+    pub fn on_connected(&mut self, connect_duration: Duration) {
+        self.connect_duration = Some(connect_duration);
+        self.unanswered_input = None;
+    }
+
+    /// Note that input was sent to the server.
+    pub fn on_input_sent(&mut self, now: Instant) {
+        self.unanswered_input.get_or_insert(now);
+    }
+
+    /// Count a frame from the server, completing a round trip if input is waiting.
+    pub fn on_frame_received(&mut self, bytes: usize, now: Instant) {
+        self.frames_received += 1;
+        self.bytes_received += bytes as u64;
+
+        let Some(sent) = self.unanswered_input.take() else {
+            return;
+        };
+        let rtt = now.saturating_duration_since(sent);
+        if rtt > Duration::from_millis(MAX_RTT_SAMPLE_MS) {
+            return;
+        }
+        let sample_ms = rtt.as_secs_f64() * 1000.0;
+        self.avg_rtt_ms = Some(match self.avg_rtt_ms {
+            Some(avg) => avg + RTT_SMOOTHING * (sample_ms - avg),
+            None => sample_ms,
+        });
+        self.last_rtt = Some(rtt);
+    }
+
+    /// How long the latest connection handshake took, in milliseconds.
+    pub fn connect_ms(&self) -> Option<u64> {
+        self.connect_duration.map(|duration| duration.as_millis() as u64)
+    }
+
+    /// Rolling average round trip, in milliseconds.
+    pub fn rtt_ms(&self) -> Option<u32> {
+        self.avg_rtt_ms.map(|avg| avg.round() as u32)
+    }
+
+    /// Most recent round trip, in milliseconds.
+    pub fn last_rtt_ms(&self) -> Option<u32> {
+        self.last_rtt.map(|rtt| rtt.as_millis() as u32)
+    }
+
+    /// Frames received from the server.
+    pub fn frames_received(&self) -> u64 {
+        self.frames_received
+    }
+
+    /// Bytes received from the server.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_metrics_round_trip() {
+        let mut metrics = SessionMetrics::default();
+        let start = Instant::now();
+        metrics.on_connected(Duration::from_millis(900));
+        assert_eq!(metrics.connect_ms(), Some(900));
+
+        // Frames without input waiting are only counted
+        metrics.on_frame_received(100, start);
+        assert_eq!(metrics.rtt_ms(), None);
+
+        // Only the first unanswered input starts the round trip
+        metrics.on_input_sent(start);
+        metrics.on_input_sent(start + Duration::from_millis(10));
+        metrics.on_frame_received(200, start + Duration::from_millis(40));
+        assert_eq!(metrics.rtt_ms(), Some(40));
+        assert_eq!(metrics.last_rtt_ms(), Some(40));
+
+        metrics.on_input_sent(start + Duration::from_millis(100));
+        metrics.on_frame_received(300, start + Duration::from_millis(120));
+        assert_eq!(metrics.last_rtt_ms(), Some(20));
+        assert_eq!(metrics.rtt_ms(), Some(38)); // 40 + (20 - 40) / 8
+
+        // A server frame long after the input isn't a reply to it
+        metrics.on_input_sent(start + Duration::from_secs(1));
+        metrics.on_frame_received(400, start + Duration::from_secs(10));
+        assert_eq!(metrics.last_rtt_ms(), Some(20));
+
+        assert_eq!(metrics.frames_received(), 4);
+        assert_eq!(metrics.bytes_received(), 1000);
+    }
+}
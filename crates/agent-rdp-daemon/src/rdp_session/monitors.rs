@@ -0,0 +1,221 @@
+//! Multi-monitor layouts.
+//!
+//! Monitors are configured relative to the primary monitor, as Windows arranges
+//! them, and stored relative to the top-left corner of their bounding box. That
+//! bounding box is the virtual desktop: the session framebuffer, screenshots and
+//! mouse coordinates all use it.
+//!
+//! The connector doesn't expose the GCC monitor data block, so the layout is sent
+//! over the display control channel once it opens; the server then reactivates
+//! the session with one display per monitor.
+
+use agent_rdp_protocol::MonitorLayout;
+use ironrdp::displaycontrol::pdu::{
+    DisplayControlMonitorLayout, DisplayControlPdu, MonitorLayoutEntry,
+};
+use ironrdp_core::EncodeResult;
+use ironrdp_dvc::encode_dvc_messages;
+use ironrdp_svc::{ChannelFlags, SvcMessage};
+
+/// Most monitors accepted in a layout.
+const MAX_MONITORS: usize = 16;
+
+/// Smallest monitor dimension the display control channel accepts.
+const MIN_MONITOR_SIZE: u32 = 200;
+
+/// Largest monitor dimension the display control channel accepts.
+const MAX_MONITOR_SIZE: u32 = 8192;
+
+/// Validate a layout and move it into virtual desktop coordinates.
+///
+/// The first monitor is primary unless another one is marked.
+pub fn normalize(monitors: &[MonitorLayout]) -> Result<Vec<MonitorLayout>, String> {
+    if monitors.len() > MAX_MONITORS {
+        return Err(format!("At most {} monitors are supported", MAX_MONITORS));
+    }
+    match monitors.iter().filter(|m| m.primary).count() {
+        0 | 1 => {}
+        _ => return Err("Only one monitor can be primary".to_string()),
+    }
+
+    for (index, monitor) in monitors.iter().enumerate() {
+        let size_range = MIN_MONITOR_SIZE..=MAX_MONITOR_SIZE;
+        if !size_range.contains(&monitor.width) || !size_range.contains(&monitor.height) {
+            return Err(format!(
+                "Monitor {} is {}x{}; sizes must be between {} and {}",
+                index, monitor.width, monitor.height, MIN_MONITOR_SIZE, MAX_MONITOR_SIZE
+            ));
+        }
+        if monitor.width % 2 != 0 {
+            return Err(format!("Monitor {} width must be even", index));
+        }
+        for (other_index, other) in monitors.iter().enumerate().skip(index + 1) {
+            if overlaps(monitor, other) {
+                return Err(format!("Monitors {} and {} overlap", index, other_index));
+            }
+        }
+    }
+
+    let Some(left) = monitors.iter().map(|m| m.x).min() else {
+        return Ok(Vec::new());
+    };
+    let top = monitors.iter().map(|m| m.y).min().unwrap_or(0);
+    let has_primary = monitors.iter().any(|m| m.primary);
+
+    let normalized: Vec<MonitorLayout> = monitors
+        .iter()
+        .enumerate()
+        .map(|(index, m)| MonitorLayout {
+            x: m.x - left,
+            y: m.y - top,
+            primary: m.primary || (!has_primary && index == 0),
+            ..*m
+        })
+        .collect();
+
+    let (width, height) = bounding_size(&normalized);
+    if width > u32::from(u16::MAX) || height > u32::from(u16::MAX) {
+        return Err(format!(
+            "Virtual desktop of {}x{} is too large",
+            width, height
+        ));
+    }
+    Ok(normalized)
+}
+
+/// Size of the virtual desktop covering normalized monitors.
+pub fn desktop_size(monitors: &[MonitorLayout]) -> Option<(u16, u16)> {
+    if monitors.is_empty() {
+        return None;
+    }
+    let (width, height) = bounding_size(monitors);
+    Some((width as u16, height as u16))
+}
+
+/// Display control entries for a normalized layout, positioned relative to the
+/// primary monitor as the protocol requires.
+pub fn layout_entries(monitors: &[MonitorLayout]) -> Result<Vec<MonitorLayoutEntry>, String> {
+    let primary = monitors
+        .iter()
+        .find(|m| m.primary)
+        .ok_or_else(|| "Layout has no primary monitor".to_string())?;
+
+    monitors
+        .iter()
+        .map(|m| {
+            let entry = if m.primary {
+                MonitorLayoutEntry::new_primary(m.width, m.height)
+            } else {
+                MonitorLayoutEntry::new_secondary(m.width, m.height)
+            };
+            entry
+                .and_then(|entry| entry.with_position(m.x - primary.x, m.y - primary.y))
+                .map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+/// Monitor layout PDU for a set of display control entries.
+fn layout_pdu(entries: &[MonitorLayoutEntry]) -> EncodeResult<DisplayControlPdu> {
+    Ok(DisplayControlMonitorLayout::new(entries)?.into())
+}
+
+/// Messages sending a monitor layout on the display control channel.
+///
+/// The client only builds single-monitor layouts, so the PDU is wrapped for
+/// the channel here the same way it does.
+pub fn layout_messages(
+    channel_id: u32,
+    entries: &[MonitorLayoutEntry],
+) -> EncodeResult<Vec<SvcMessage>> {
+    let pdu = layout_pdu(entries)?;
+    encode_dvc_messages(channel_id, vec![Box::new(pdu)], ChannelFlags::empty())
+}
+
+/// Right and bottom edges of the monitors' bounding box.
+fn bounding_size(monitors: &[MonitorLayout]) -> (u32, u32) {
+    let width = monitors
+        .iter()
+        .map(|m| m.x as u32 + m.width)
+        .max()
+        .unwrap_or(0);
+    let height = monitors
+        .iter()
+        .map(|m| m.y as u32 + m.height)
+        .max()
+        .unwrap_or(0);
+    (width, height)
+}
+
+/// Whether two monitors share any pixels.
+fn overlaps(a: &MonitorLayout, b: &MonitorLayout) -> bool {
+    let (a_right, a_bottom) = (a.x as i64 + a.width as i64, a.y as i64 + a.height as i64);
+    let (b_right, b_bottom) = (b.x as i64 + b.width as i64, b.y as i64 + b.height as i64);
+    (a.x as i64) < b_right
+        && (b.x as i64) < a_right
+        && (a.y as i64) < b_bottom
+        && (b.y as i64) < a_bottom
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_layout_normalization() {
+        let monitor = |x, y, width, height| MonitorLayout {
+            x,
+            y,
+            width,
+            height,
+            primary: false,
+        };
+
+        // A secondary monitor left of the primary shifts the primary right
+        let layout =
+            normalize(&[monitor(0, 0, 1920, 1080), monitor(-1280, 56, 1280, 1024)]).unwrap();
+        assert_eq!((layout[0].x, layout[0].y), (1280, 0));
+        assert_eq!((layout[1].x, layout[1].y), (0, 56));
+        assert!(layout[0].primary && !layout[1].primary);
+        assert_eq!(desktop_size(&layout), Some((3200, 1080)));
+        assert_eq!(desktop_size(&[]), None);
+
+        assert!(normalize(&[monitor(0, 0, 1920, 1080), monitor(1000, 0, 1920, 1080)]).is_err());
+        assert!(normalize(&[monitor(0, 0, 1921, 1080)]).is_err());
+        assert!(normalize(&[monitor(0, 0, 100, 1080)]).is_err());
+    }
+
+    #[test]
+    fn test_monitor_layout_pdu() {
+        let layout = vec![
+            MonitorLayout {
+                x: 1280,
+                y: 0,
+                width: 1920,
+                height: 1080,
+                primary: true,
+            },
+            MonitorLayout {
+                x: 0,
+                y: 56,
+                width: 1280,
+                height: 1024,
+                primary: false,
+            },
+        ];
+        let entries = layout_entries(&layout).unwrap();
+        assert_eq!(entries[1].position(), Some((-1280, 56)));
+
+        // DISPLAYCONTROL_HEADER (type 2, length), then MonitorLayoutSize and NumMonitors
+        let encoded = ironrdp_core::encode_vec(&layout_pdu(&entries).unwrap()).unwrap();
+        let field =
+            |offset: usize| u32::from_le_bytes(encoded[offset..offset + 4].try_into().unwrap());
+        assert_eq!(field(0), 2);
+        assert_eq!(field(4) as usize, encoded.len());
+        assert_eq!(field(8), 40);
+        assert_eq!(field(12), 2);
+
+        assert_eq!(layout_messages(7, &entries).unwrap().len(), 1);
+        assert!(layout_pdu(&[]).is_err());
+    }
+}
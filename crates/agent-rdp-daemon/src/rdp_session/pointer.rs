@@ -0,0 +1,187 @@
+//! Mouse pointer tracking.
+//!
+//! The server sends the pointer shape (and occasionally moves the pointer) as
+//! pointer updates rather than drawing it into the desktop image, so screenshots
+//! don't show it. The position also follows the mouse input we send. Callers
+//! that want the pointer in an image composite it on with [`PointerState::draw`].
+
+use std::sync::Arc;
+
+use ironrdp::pdu::input::fast_path::FastPathInputEvent;
+use ironrdp::session::ActiveStageOutput;
+use ironrdp_graphics::pointer::DecodedPointer;
+
+/// Latest pointer position and shape.
+#[derive(Debug, Clone)]
+pub struct PointerState {
+    /// Position of the hot spot on the desktop.
+    pub x: u16,
+    pub y: u16,
+    /// Whether the server shows the pointer.
+    pub visible: bool,
+    /// Pointer image (RGBA, straight alpha); `None` until the server sends one,
+    /// or after it switches back to the system default pointer.
+    pub shape: Option<Arc<DecodedPointer>>,
+}
+
+impl Default for PointerState {
+    fn default() -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            visible: true,
+            shape: None,
+        }
+    }
+}
+
+impl PointerState {
+    /// Apply a pointer update from the frame processor (other outputs are ignored).
+    pub fn apply(&mut self, output: &ActiveStageOutput) {
+        match output {
+            ActiveStageOutput::PointerDefault => {
+                self.visible = true;
+                self.shape = None;
+            }
+            ActiveStageOutput::PointerHidden => self.visible = false,
+            ActiveStageOutput::PointerPosition { x, y } => {
+                self.x = *x;
+                self.y = *y;
+            }
+            ActiveStageOutput::PointerBitmap(pointer) => {
+                self.visible = true;
+                self.shape = Some(Arc::clone(pointer));
+            }
+            _ => {}
+        }
+    }
+
+    /// Follow the mouse events we send to the server.
+    pub fn track_input(&mut self, events: &[FastPathInputEvent]) {
+        for event in events {
+            if let FastPathInputEvent::MouseEvent(pdu) = event {
+                self.x = pdu.x_position;
+                self.y = pdu.y_position;
+            }
+        }
+    }
+
+    /// Alpha-blend the pointer onto an RGBA frame of the desktop at its current
+    /// position. Does nothing when it's hidden or has no shape; parts that fall
+    /// outside the frame are clipped.
+    pub fn draw(&self, frame: &mut [u8], frame_width: u16, frame_height: u16) {
+        let Some(ref pointer) = self.shape else {
+            return;
+        };
+        if !self.visible {
+            return;
+        }
+
+        let (frame_width, frame_height) = (frame_width as i64, frame_height as i64);
+        let (width, height) = (pointer.width as i64, pointer.height as i64);
+        let left = self.x as i64 - pointer.hotspot_x as i64;
+        let top = self.y as i64 - pointer.hotspot_y as i64;
+
+        for py in 0..height {
+            let fy = top + py;
+            if fy < 0 || fy >= frame_height {
+                continue;
+            }
+            for px in 0..width {
+                let fx = left + px;
+                if fx < 0 || fx >= frame_width {
+                    continue;
+                }
+                let src = ((py * width + px) * 4) as usize;
+                let Some(src) = pointer.bitmap_data.get(src..src + 4) else {
+                    return;
+                };
+                let alpha = src[3] as u32;
+                if alpha == 0 {
+                    continue;
+                }
+                let dst = ((fy * frame_width + fx) * 4) as usize;
+                let Some(dst) = frame.get_mut(dst..dst + 4) else {
+                    return;
+                };
+                for (d, s) in dst[..3].iter_mut().zip(&src[..3]) {
+                    *d = ((*s as u32 * alpha + *d as u32 * (255 - alpha)) / 255) as u8;
+                }
+                dst[3] = 255;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pointer_tracking() {
+        use ironrdp::pdu::input::mouse::{MousePdu, PointerFlags};
+
+        let mut pointer = PointerState::default();
+        pointer.track_input(&[FastPathInputEvent::MouseEvent(MousePdu {
+            flags: PointerFlags::MOVE,
+            number_of_wheel_rotation_units: 0,
+            x_position: 100,
+            y_position: 50,
+        })]);
+        assert_eq!((pointer.x, pointer.y), (100, 50));
+
+        pointer.apply(&ActiveStageOutput::PointerPosition { x: 10, y: 20 });
+        assert_eq!((pointer.x, pointer.y), (10, 20));
+
+        pointer.apply(&ActiveStageOutput::PointerHidden);
+        assert!(!pointer.visible);
+        pointer.apply(&ActiveStageOutput::PointerDefault);
+        assert!(pointer.visible && pointer.shape.is_none());
+    }
+
+    #[test]
+    fn test_pointer_draw_at_hotspot() {
+        // 2x2 pointer: opaque red, half-transparent green, two transparent pixels
+        let mut bitmap = vec![0u8; 16];
+        bitmap[0..4].copy_from_slice(&[255, 0, 0, 255]);
+        bitmap[4..8].copy_from_slice(&[0, 255, 0, 128]);
+        let pointer = PointerState {
+            x: 2,
+            y: 1,
+            visible: true,
+            shape: Some(Arc::new(DecodedPointer {
+                width: 2,
+                height: 2,
+                hotspot_x: 1,
+                hotspot_y: 0,
+                bitmap_data: bitmap,
+            })),
+        };
+
+        let mut frame = vec![0u8; 4 * 3 * 4];
+        pointer.draw(&mut frame, 4, 3);
+        let pixel = |frame: &[u8], x: usize, y: usize| frame[(y * 4 + x) * 4..][..4].to_vec();
+        // The hot spot (second pixel of the pointer) lands on (2, 1)
+        assert_eq!(pixel(&frame, 1, 1), vec![255, 0, 0, 255]);
+        assert_eq!(pixel(&frame, 2, 1), vec![0, 128, 0, 255]);
+        assert_eq!(pixel(&frame, 1, 2), vec![0, 0, 0, 0]);
+
+        // Clipped at the frame edge, and not drawn while hidden
+        let edge = PointerState {
+            x: 0,
+            y: 0,
+            ..pointer.clone()
+        };
+        let mut frame = vec![0u8; 4 * 3 * 4];
+        edge.draw(&mut frame, 4, 3);
+        assert_eq!(pixel(&frame, 0, 0), vec![0, 128, 0, 255]);
+
+        let hidden = PointerState {
+            visible: false,
+            ..pointer
+        };
+        let mut frame = vec![0u8; 4 * 3 * 4];
+        hidden.draw(&mut frame, 4, 3);
+        assert!(frame.iter().all(|&b| b == 0));
+    }
+}
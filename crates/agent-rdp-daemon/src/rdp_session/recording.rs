@@ -0,0 +1,177 @@
+//! Recording the desktop to an animated GIF.
+//!
+//! A task samples the desktop image at a fixed rate and hands samples that
+//! changed to an encoder thread, which appends them to the file as it goes so
+//! long sessions don't build up in memory. GIF frames carry their own display
+//! time, so each frame is written once the next one arrives, and the last one
+//! when recording stops. Samples taken while the encoder is still busy are
+//! dropped rather than queued. Audio is not recorded.
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::sync::mpsc::{self, TrySendError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, ImageResult, RgbaImage};
+use parking_lot::RwLock;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use super::SharedState;
+
+/// Frames per second sampled when no rate is given.
+pub const DEFAULT_RECORD_FPS: u32 = 2;
+
+/// Highest sampling rate accepted (GIF encoding of full desktops is slow).
+pub const MAX_RECORD_FPS: u32 = 10;
+
+/// Color quantization speed, from 1 (best quality) to 30 (fastest).
+const GIF_ENCODER_SPEED: i32 = 10;
+
+/// Shortest time the final frame is shown.
+const MIN_LAST_FRAME: Duration = Duration::from_millis(500);
+
+/// Create (or truncate) the recording file.
+pub fn create_file(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = File::create(path)?;
+    info!("Recording the desktop to {:?}", path);
+    Ok(file)
+}
+
+/// A running recording. Dropping it stops the recording in the background;
+/// `finish` also waits for the file to be finalized.
+#[derive(Debug)]
+pub struct Recorder {
+    stop_tx: oneshot::Sender<()>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Recorder {
+    /// Start sampling the session's desktop into `file` at `fps` frames per second.
+    pub(super) fn start(file: File, fps: u32, shared: Arc<RwLock<SharedState>>) -> Self {
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let task = tokio::spawn(record(file, fps.clamp(1, MAX_RECORD_FPS), shared, stop_rx));
+        Self { stop_tx, task }
+    }
+
+    /// Stop recording and wait until the file is complete.
+    pub async fn finish(self) {
+        let _ = self.stop_tx.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// A desktop image taken for the recording.
+struct Sample {
+    taken_at: Instant,
+    width: u16,
+    height: u16,
+    data: Vec<u8>,
+}
+
+/// Sample the desktop until stopped, then wait for the encoder to finish.
+async fn record(
+    file: File,
+    fps: u32,
+    shared: Arc<RwLock<SharedState>>,
+    mut stop_rx: oneshot::Receiver<()>,
+) {
+    let (sample_tx, sample_rx) = mpsc::sync_channel::<Sample>(1);
+    let encoder = std::thread::spawn(move || encode(file, sample_rx));
+
+    let mut frames = shared.read().frame_generation.subscribe();
+    let mut timer = tokio::time::interval(Duration::from_millis(1000 / fps as u64));
+    timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    // The first sample is always taken; after that only changed desktops are,
+    // and an unchanged one just extends the previous frame
+    let mut changed = true;
+    loop {
+        tokio::select! {
+            _ = &mut stop_rx => break,
+            _ = timer.tick() => {
+                if frames.has_changed().unwrap_or(false) {
+                    frames.borrow_and_update();
+                    changed = true;
+                }
+                if !changed {
+                    continue;
+                }
+                let sample = {
+                    let state = shared.read();
+                    let (width, height) = (state.image.width(), state.image.height());
+                    let mut data = state.image.data().to_vec();
+                    state.pointer.draw(&mut data, width, height);
+                    Sample { taken_at: Instant::now(), width, height, data }
+                };
+                match sample_tx.try_send(sample) {
+                    Ok(()) => changed = false,
+                    // Still encoding the previous frame; retry on the next tick
+                    Err(TrySendError::Full(_)) => {}
+                    Err(TrySendError::Disconnected(_)) => break,
+                }
+            }
+        }
+    }
+
+    drop(sample_tx);
+    match tokio::task::spawn_blocking(move || encoder.join()).await {
+        Ok(Ok(Ok(frames))) => info!("Recording finished with {} frames", frames),
+        Ok(Ok(Err(e))) => warn!("Recording failed: {}", e),
+        _ => warn!("Recording encoder stopped unexpectedly"),
+    }
+}
+
+/// Append samples to the GIF as they arrive; returns the number of frames written.
+///
+/// The GIF's size is fixed by the first sample. After a resize, frames are
+/// cropped or padded to it.
+fn encode(file: File, samples: mpsc::Receiver<Sample>) -> ImageResult<u32> {
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), GIF_ENCODER_SPEED);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let mut size = None;
+    let mut pending: Option<(Instant, RgbaImage)> = None;
+    let mut written = 0;
+    for sample in samples {
+        let Some(image) =
+            RgbaImage::from_raw(u32::from(sample.width), u32::from(sample.height), sample.data)
+        else {
+            continue;
+        };
+        let (width, height) = *size.get_or_insert(image.dimensions());
+        let image = if image.dimensions() == (width, height) {
+            image
+        } else {
+            let mut canvas = RgbaImage::new(width, height);
+            image::imageops::replace(&mut canvas, &image, 0, 0);
+            canvas
+        };
+
+        if let Some((shown_at, previous)) = pending.replace((sample.taken_at, image)) {
+            write_frame(&mut encoder, previous, sample.taken_at - shown_at)?;
+            written += 1;
+        }
+    }
+
+    if let Some((shown_at, last)) = pending {
+        write_frame(&mut encoder, last, shown_at.elapsed().max(MIN_LAST_FRAME))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+fn write_frame(
+    encoder: &mut GifEncoder<BufWriter<File>>,
+    image: RgbaImage,
+    shown_for: Duration,
+) -> ImageResult<()> {
+    let delay = Delay::from_saturating_duration(shown_for);
+    encoder.encode_frame(Frame::from_parts(image, 0, 0, delay))
+}
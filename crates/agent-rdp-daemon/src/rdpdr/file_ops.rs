@@ -1,7 +1,7 @@
 //! File operations for RDPDR: create, read, write, close.
 
 use std::fs::{self, File};
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 
 use ironrdp::pdu::PduResult;
 use ironrdp_rdpdr::pdu::efs::*;
@@ -9,6 +9,7 @@ use ironrdp_rdpdr::pdu::RdpdrPdu;
 use ironrdp_svc::SvcMessage;
 use tracing::{debug, warn};
 
+use super::notify_ops::cancel_notify;
 use super::MultiDriveBackend;
 
 /// Handle device write request.
@@ -16,6 +17,21 @@ pub fn write_device(
     backend: &mut MultiDriveBackend,
     req_inner: DeviceWriteRequest,
 ) -> PduResult<Vec<SvcMessage>> {
+    if backend.is_read_only(req_inner.device_io_request.device_id) {
+        warn!(
+            "Write rejected on read-only drive: device_id={}",
+            req_inner.device_io_request.device_id
+        );
+        let res = RdpdrPdu::DeviceWriteResponse(DeviceWriteResponse {
+            device_io_reply: DeviceIoResponse::new(
+                req_inner.device_io_request,
+                NtStatus::ACCESS_DENIED,
+            ),
+            length: 0u32,
+        });
+        return Ok(vec![SvcMessage::from(res)]);
+    }
+
     process_dependent_file(
         backend,
         req_inner.device_io_request,
@@ -85,7 +101,7 @@ pub fn read_device(
             Ok(vec![SvcMessage::from(res)])
         },
         |file, request| {
-            match read_inner(file, req_inner.offset, usize::try_from(req_inner.length).unwrap()) {
+            match read_at(file, req_inner.offset, usize::try_from(req_inner.length).unwrap()) {
                 Ok(buf) => {
                     let res = RdpdrPdu::DeviceReadResponse(DeviceReadResponse {
                         device_io_reply: DeviceIoResponse::new(request, NtStatus::SUCCESS),
@@ -106,11 +122,14 @@ pub fn read_device(
     )
 }
 
-fn read_inner(file: &mut File, offset: u64, length: usize) -> std::io::Result<Vec<u8>> {
+/// Read `length` bytes at `offset`, or up to the end of the file if it is shorter.
+///
+/// A single `read` may return fewer bytes mid-file (network filesystems do),
+/// and a short buffer would look like the end of the file to the server.
+fn read_at<R: Read + Seek>(file: &mut R, offset: u64, length: usize) -> io::Result<Vec<u8>> {
     file.seek(SeekFrom::Start(offset))?;
-    let mut buf = vec![0; length];
-    let length = file.read(&mut buf)?;
-    buf.resize(length, 0u8);
+    let mut buf = Vec::with_capacity(length);
+    file.take(length as u64).read_to_end(&mut buf)?;
     Ok(buf)
 }
 
@@ -129,30 +148,10 @@ pub fn close_device(
         }
     }
 
-    // Check if file was marked for deletion
-    let should_delete = backend.delete_on_close.remove(&file_id).unwrap_or(false);
-
-    // Get path before removing from maps (needed for deletion)
-    let file_path = backend.file_path_map.get(&file_id).cloned();
-
-    // Clean up all mappings
-    backend.file_map.remove(&file_id);
-    backend.file_path_map.remove(&file_id);
-    backend.file_device_map.remove(&file_id);
-    backend.file_dir_map.remove(&file_id);
-
-    // Perform actual deletion after closing handle and cleaning up maps
-    if should_delete {
-        if let Some(path) = file_path {
-            debug!("Deleting file on close: {:?}", path);
-            if let Err(e) = fs::remove_file(&path) {
-                // Try removing as directory if file removal fails
-                if let Err(e2) = fs::remove_dir(&path) {
-                    warn!("Failed to delete {:?}: file={:?}, dir={:?}", path, e, e2);
-                }
-            }
-        }
-    }
+    // Clean up all mappings, then perform any pending deletion. This is the
+    // only place files are deleted; set_information just marks them
+    cancel_notify(backend, file_id);
+    backend.close_file(file_id);
 
     let res = RdpdrPdu::DeviceCloseResponse(DeviceCloseResponse {
         device_io_response: DeviceIoResponse::new(req_inner.device_io_request, NtStatus::SUCCESS),
@@ -175,12 +174,13 @@ pub fn create_drive(
         backend.drive_paths_keys()
     );
 
-    // Get base path for this device
-    let base_path = match backend.get_base_path(device_id) {
-        Some(p) => p.clone(),
+    let path = match backend.resolve_path(device_id, &req_inner.path) {
+        Some(p) => p,
         None => {
+            // Unknown device, or a path that climbs out of the drive
             warn!(
-                "No base path for device {}. Registered: {:?}",
+                "Can't resolve {:?} on device {}. Registered: {:?}",
+                req_inner.path,
                 device_id,
                 backend.drive_paths_debug()
             );
@@ -195,15 +195,31 @@ pub fn create_drive(
         }
     };
 
-    // Convert backslashes and strip leading slashes to prevent join from replacing base path
-    let req_path = req_inner.path.replace('\\', "/");
-    let req_path = req_path.trim_start_matches('/');
-    let path = if req_path.is_empty() {
-        base_path.clone()
-    } else {
-        base_path.join(req_path)
-    };
-    debug!("create_drive resolved: base={:?}, full_path={:?}", base_path, path);
+    debug!("create_drive resolved: full_path={:?}", path);
+
+    // Read-only drives may only open existing files and directories
+    let read_only = backend.is_read_only(device_id);
+    if read_only {
+        let opens_existing = match req_inner.create_disposition {
+            CreateDisposition::FILE_OPEN => true,
+            CreateDisposition::FILE_OPEN_IF => path.exists(),
+            _ => false,
+        };
+        if !opens_existing {
+            warn!(
+                "Create rejected on read-only drive: disposition={:?}, path={:?}",
+                req_inner.create_disposition, path
+            );
+            let io_response =
+                DeviceIoResponse::new(req_inner.device_io_request, NtStatus::ACCESS_DENIED);
+            let res = RdpdrPdu::DeviceCreateResponse(DeviceCreateResponse {
+                device_io_reply: io_response,
+                file_id,
+                information: Information::empty(),
+            });
+            return Ok(vec![SvcMessage::from(res)]);
+        }
+    }
 
     // First process directory
     match fs::metadata(&path) {
@@ -291,6 +307,9 @@ pub fn create_drive(
 
     let mut fs_opts = fs::OpenOptions::new();
     match req_inner.create_disposition {
+        _ if read_only => {
+            fs_opts.read(true);
+        }
         CreateDisposition::FILE_OPEN_IF => {
             fs_opts.create(true).write(true).read(true);
         }
@@ -371,3 +390,40 @@ pub fn process_dependent_file(
         _ => error_fx(request), // None or Some(None) for directories
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drive_read_fills_short_reads() {
+        use std::io::{Cursor, Read, Seek, SeekFrom};
+
+        /// Returns at most a few KB per read, like some network filesystems.
+        struct ShortReader(Cursor<Vec<u8>>);
+
+        impl Read for ShortReader {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let len = buf.len().min(3000);
+                self.0.read(&mut buf[..len])
+            }
+        }
+
+        impl Seek for ShortReader {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                self.0.seek(pos)
+            }
+        }
+
+        let contents: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let mut file = ShortReader(Cursor::new(contents.clone()));
+
+        let data = read_at(&mut file, 0, contents.len()).unwrap();
+        assert!(data == contents);
+
+        // Past the end of file, only the remaining bytes come back
+        let data = read_at(&mut file, 100, contents.len()).unwrap();
+        assert!(data[..] == contents[100..]);
+        assert!(read_at(&mut file, 1 << 30, 16).unwrap().is_empty());
+    }
+}
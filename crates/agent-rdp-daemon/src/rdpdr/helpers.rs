@@ -1,10 +1,91 @@
 //! Platform-specific helper functions for RDPDR.
 
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use ironrdp_rdpdr::pdu::efs::FileAttributes;
 
+/// Match a file name against a Windows wildcard pattern (`*` and `?`), ignoring case.
+///
+/// `*.*` matches every name, with or without an extension, as it does on Windows.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    if pattern == "*.*" {
+        return true;
+    }
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+
+    // Greedy match, backtracking to the last `*` on a mismatch
+    let (mut p, mut n) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            last_star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = last_star {
+            // Let the `*` absorb one more character
+            p = star_p + 1;
+            n = star_n + 1;
+            last_star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Path below the drive root that a server request refers to, with `/` separators.
+///
+/// The root can arrive as `\`, an empty path or a trailing separator, and some
+/// requests carry a volume prefix (`X:`) or the whole `\\tsclient\Name` UNC path.
+/// All of these reduce to the same relative path, which is empty for the root.
+///
+/// `..` components are resolved here, so every request sees the same path;
+/// returns None if they would leave the drive.
+pub fn drive_relative_path(req_path: &str) -> Option<String> {
+    let path = req_path.replace('\\', "/");
+    let mut path = path.as_str();
+
+    if let Some(unc) = path.strip_prefix("//") {
+        // `//tsclient/Name/rest`: drop the host and the drive name
+        let mut parts = unc.splitn(3, '/');
+        if parts.next().is_some_and(|host| host.eq_ignore_ascii_case("tsclient")) {
+            parts.next();
+            path = parts.next().unwrap_or("");
+        }
+    }
+    if let [letter, b':', ..] = path.as_bytes() {
+        let rest = &path[2..];
+        if letter.is_ascii_alphabetic() && (rest.is_empty() || rest.starts_with('/')) {
+            path = rest;
+        }
+    }
+
+    let mut parts = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Join a path from `drive_relative_path` onto a drive's base path.
+pub fn join_drive_path(base_path: &Path, relative: &str) -> PathBuf {
+    if relative.is_empty() {
+        base_path.to_path_buf()
+    } else {
+        base_path.join(relative)
+    }
+}
+
 /// Get file attributes from metadata.
 pub fn get_file_attributes(meta: &fs::Metadata, file_name: &str) -> FileAttributes {
     let mut file_attribute = FileAttributes::empty();
@@ -91,6 +172,25 @@ pub fn get_last_write_time(meta: &fs::Metadata) -> i64 {
     unix_to_filetime(meta.mtime())
 }
 
+/// Size of an allocation unit, matching the 4KB units reported for the volume.
+const BYTES_PER_ALLOC_UNIT: u64 = 4096;
+
+/// Space allocated for a file of `len` bytes (rounded up to whole allocation units).
+pub fn get_allocation_size(len: u64) -> i64 {
+    let size = len.div_ceil(BYTES_PER_ALLOC_UNIT) * BYTES_PER_ALLOC_UNIT;
+    i64::try_from(size).unwrap_or(i64::MAX)
+}
+
+/// End of file and allocation size to report for a file, as Windows does:
+/// the real length and whole allocation units, or zero for directories.
+pub fn get_file_sizes(meta: &fs::Metadata) -> (i64, i64) {
+    if meta.is_dir() {
+        return (0, 0);
+    }
+    let end_of_file = i64::try_from(meta.len()).unwrap_or(i64::MAX);
+    (end_of_file, get_allocation_size(meta.len()))
+}
+
 /// Convert Unix timestamp to Windows FILETIME.
 /// Windows FILETIME is 100-nanosecond intervals since January 1, 1601.
 /// Unix timestamp is seconds since January 1, 1970.
@@ -155,3 +255,32 @@ pub fn get_disk_space(path: &Path) -> std::io::Result<(u64, u64)> {
         Err(std::io::Error::last_os_error())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drive_wildcard_match() {
+        // Bare `*` and `*.*` list everything
+        assert!(wildcard_match("*", "notes"));
+        assert!(wildcard_match("*.*", "Makefile"));
+
+        // Extension globs
+        assert!(wildcard_match("*.txt", "readme.txt"));
+        assert!(wildcard_match("*.txt", "archive.tar.txt"));
+        assert!(!wildcard_match("*.txt", "readme.txt.bak"));
+        assert!(!wildcard_match("*.txt", "txt"));
+
+        // `?` is exactly one character
+        assert!(wildcard_match("file?.log", "file1.log"));
+        assert!(!wildcard_match("file?.log", "file.log"));
+        assert!(!wildcard_match("file?.log", "file12.log"));
+        assert!(wildcard_match("ab*", "abc"));
+        assert!(wildcard_match("a*b*c", "aXbYbZc"));
+
+        // Case-insensitive, like NTFS
+        assert!(wildcard_match("*.TXT", "Report.txt"));
+        assert!(wildcard_match("REPORT.*", "report.pdf"));
+    }
+}
@@ -1,36 +1,62 @@
 //! Cross-platform RDPDR backend with multi-drive support.
 //!
 //! This module provides drive redirection for RDP sessions, supporting multiple
-//! drives mapped to different local directories.
+//! drives mapped to different local directories. With the `smartcard` feature,
+//! smart card calls can also be bridged to the local PC/SC service.
 
 mod file_ops;
 mod helpers;
+mod notify_ops;
 mod query_ops;
+#[cfg(feature = "smartcard")]
+mod scard;
 mod set_ops;
 
 use std::collections::HashMap;
-use std::fs::File;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
 
+use agent_rdp_protocol::DriveMapping;
 use ironrdp::pdu::PduResult;
 use ironrdp_rdpdr::pdu::efs::*;
 use ironrdp_rdpdr::pdu::esc::{ScardCall, ScardIoCtlCode};
 use ironrdp_rdpdr::pdu::RdpdrPdu;
 use ironrdp_rdpdr::RdpdrBackend;
 use ironrdp_svc::{impl_as_any, SvcMessage};
-use tracing::{debug, info};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
 
 use file_ops::{close_device, create_drive, read_device, write_device};
+use helpers::{drive_relative_path, join_drive_path};
+pub(crate) use helpers::get_disk_space;
+use notify_ops::{notify_change_directory, NotifyWatch};
 use query_ops::{query_directory, query_information, query_volume_information, DirIterState};
 use set_ops::set_information;
 
+/// Responses to smart card calls, sent to the session as they complete.
+pub type ScardResponseSender = mpsc::UnboundedSender<RdpdrPdu>;
+pub type ScardResponseReceiver = mpsc::UnboundedReceiver<RdpdrPdu>;
+
+/// Disposition flags, as in FILE_DISPOSITION_INFORMATION_EX ([MS-FSCC] 2.4.12).
+/// Plain FileDispositionInformation maps to DELETE or DO_NOT_DELETE.
+pub(crate) const FILE_DISPOSITION_DO_NOT_DELETE: u32 = 0x0000_0000;
+pub(crate) const FILE_DISPOSITION_DELETE: u32 = 0x0000_0001;
+pub(crate) const FILE_DISPOSITION_POSIX_SEMANTICS: u32 = 0x0000_0002;
+
 /// Cross-platform RDPDR backend supporting multiple drives.
 #[derive(Debug, Default)]
 pub struct MultiDriveBackend {
     /// Next file ID to assign.
     file_id: u32,
+    /// Highest device ID handed out so far (IDs of unmounted drives aren't reused).
+    last_device_id: u32,
     /// Mapping from device_id to base path for each drive.
     pub(crate) drive_paths: HashMap<u32, PathBuf>,
+    /// Device IDs of drives mapped read-only.
+    pub(crate) drive_read_only: HashMap<u32, bool>,
+    /// Drive name of each device, as announced to the server.
+    pub(crate) drive_names: HashMap<u32, String>,
     /// File handles - None for directories.
     pub(crate) file_map: HashMap<u32, Option<File>>,
     /// File ID to full path mapping.
@@ -39,8 +65,13 @@ pub struct MultiDriveBackend {
     pub(crate) file_device_map: HashMap<u32, u32>,
     /// Directory iteration state.
     pub(crate) file_dir_map: HashMap<u32, DirIterState>,
-    /// Files marked for deletion on close (set via FileDispositionInformation).
-    pub(crate) delete_on_close: HashMap<u32, bool>,
+    /// Disposition flags of handles marked for deletion on close.
+    pub(crate) delete_on_close: HashMap<u32, u32>,
+    /// Directory change watches (set via NotifyChangeDirectory), keyed by file ID.
+    pub(crate) notify_watches: HashMap<u32, NotifyWatch>,
+    /// Bridge to the local PC/SC service (smart card calls are ignored without one).
+    #[cfg(feature = "smartcard")]
+    smartcard: Option<scard::SmartCardBridge>,
 }
 
 impl MultiDriveBackend {
@@ -52,9 +83,108 @@ impl MultiDriveBackend {
     /// Add a drive mapping.
     ///
     /// The device_id should match the ID used when registering drives with Rdpdr::with_drives().
-    pub fn add_drive(&mut self, device_id: u32, path: PathBuf) {
-        info!("Adding drive mapping: device_id={} -> {:?}", device_id, path);
+    pub fn add_drive(&mut self, device_id: u32, path: PathBuf, read_only: bool) {
+        info!(
+            "Adding drive mapping: device_id={} -> {:?} (read_only={})",
+            device_id, path, read_only
+        );
         self.drive_paths.insert(device_id, path);
+        self.drive_read_only.insert(device_id, read_only);
+        self.last_device_id = self.last_device_id.max(device_id);
+    }
+
+    /// Add each drive under its own device ID, numbered from 1 in order.
+    ///
+    /// Returns the `(device_id, name)` list to register with Rdpdr::with_drives().
+    pub fn add_drives(&mut self, drives: &[DriveMapping]) -> Vec<(u32, String)> {
+        drives
+            .iter()
+            .zip(1u32..)
+            .map(|(drive, device_id)| {
+                self.add_drive(device_id, PathBuf::from(&drive.path), drive.read_only);
+                self.drive_names.insert(device_id, drive.name.clone());
+                (device_id, drive.name.clone())
+            })
+            .collect()
+    }
+
+    /// Take the next unused device ID, for a device other than a drive.
+    pub fn reserve_device_id(&mut self) -> u32 {
+        self.last_device_id += 1;
+        self.last_device_id
+    }
+
+    /// Map a drive while connected, returning the device ID to announce it under.
+    pub fn mount_drive(&mut self, drive: &DriveMapping) -> u32 {
+        let device_id = self.reserve_device_id();
+        self.add_drive(device_id, PathBuf::from(&drive.path), drive.read_only);
+        self.drive_names.insert(device_id, drive.name.clone());
+        device_id
+    }
+
+    /// Remove a drive by name (case-insensitive), returning its device ID.
+    ///
+    /// Open handles on the drive are synced and closed, since the server
+    /// drops them when the device goes away.
+    pub fn unmount_drive(&mut self, name: &str) -> Option<u32> {
+        let device_id = self
+            .drive_names
+            .iter()
+            .find(|(_, drive_name)| drive_name.eq_ignore_ascii_case(name))
+            .map(|(device_id, _)| *device_id)?;
+
+        let file_ids: Vec<u32> = self
+            .file_device_map
+            .iter()
+            .filter(|(_, device)| **device == device_id)
+            .map(|(file_id, _)| *file_id)
+            .collect();
+        for file_id in file_ids {
+            if let Some(Some(file)) = self.file_map.get(&file_id) {
+                if let Err(e) = file.sync_all() {
+                    warn!("Failed to sync file {} on unmount: {:?}", file_id, e);
+                }
+            }
+            self.notify_watches.remove(&file_id);
+            self.close_file(file_id);
+        }
+
+        info!("Removing drive mapping: device_id={} ({})", device_id, name);
+        self.drive_paths.remove(&device_id);
+        self.drive_read_only.remove(&device_id);
+        self.drive_names.remove(&device_id);
+        Some(device_id)
+    }
+
+    /// Bridge smart card calls to the local PC/SC service.
+    ///
+    /// Returns the receiver for call responses, or `None` when there's nothing
+    /// to redirect: no reader is present, or the build lacks the `smartcard`
+    /// feature. Smart card calls are then ignored.
+    pub fn enable_smartcard(&mut self) -> Option<ScardResponseReceiver> {
+        #[cfg(feature = "smartcard")]
+        {
+            let (tx, rx) = mpsc::unbounded_channel();
+            self.smartcard = Some(scard::SmartCardBridge::start(tx)?);
+            Some(rx)
+        }
+        #[cfg(not(feature = "smartcard"))]
+        {
+            tracing::warn!("Smart card redirection requested, but agent-rdp was built without the smartcard feature");
+            None
+        }
+    }
+
+    /// Whether a device is mapped read-only.
+    pub(crate) fn is_read_only(&self, device_id: u32) -> bool {
+        self.drive_read_only.get(&device_id).copied().unwrap_or(false)
+    }
+
+    /// Whether a file belongs to a read-only drive (via file_id -> device_id lookup).
+    pub(crate) fn is_read_only_for_file(&self, file_id: u32) -> bool {
+        self.file_device_map
+            .get(&file_id)
+            .is_some_and(|device_id| self.is_read_only(*device_id))
     }
 
     /// Get the base path for a device.
@@ -62,6 +192,12 @@ impl MultiDriveBackend {
         self.drive_paths.get(&device_id)
     }
 
+    /// Resolve a path requested by the server against its device's directory.
+    pub(crate) fn resolve_path(&self, device_id: u32, req_path: &str) -> Option<PathBuf> {
+        let base_path = self.drive_paths.get(&device_id)?;
+        drive_relative_path(req_path).map(|relative| join_drive_path(base_path, &relative))
+    }
+
     /// Get the base path for a file (via file_id -> device_id lookup).
     pub(crate) fn get_base_path_for_file(&self, file_id: u32) -> Option<&PathBuf> {
         self.file_device_map
@@ -93,6 +229,126 @@ impl MultiDriveBackend {
         self.file_device_map.insert(file_id, device_id);
     }
 
+    /// Complete pending directory change notifications.
+    ///
+    /// Called periodically by the session; returns responses to send to the server.
+    pub fn poll_change_notifications(&mut self) -> Vec<SvcMessage> {
+        if self.notify_watches.is_empty() {
+            return Vec::new();
+        }
+        notify_ops::poll_change_notifications(self)
+    }
+
+    /// Point open handles at their new paths after `from` was renamed to `to`.
+    ///
+    /// Covers the renamed entry itself and, for directories, every open handle
+    /// beneath it.
+    pub(crate) fn rename_paths(&mut self, from: &Path, to: &Path) {
+        for path in self.file_path_map.values_mut() {
+            if let Ok(rest) = path.strip_prefix(from) {
+                *path = if rest.as_os_str().is_empty() {
+                    to.to_path_buf()
+                } else {
+                    to.join(rest)
+                };
+            }
+        }
+    }
+
+    /// Mark or unmark an open handle's file for deletion.
+    ///
+    /// Nothing is removed here; deletion happens in `close_file`. A pending
+    /// delete belongs to the file rather than the handle, so clearing it
+    /// unmarks every handle open on the same path.
+    pub(crate) fn set_disposition(&mut self, file_id: u32, flags: u32) {
+        if flags & FILE_DISPOSITION_DELETE != 0 {
+            self.delete_on_close.insert(file_id, flags);
+            return;
+        }
+        let path = self.file_path_map.get(&file_id);
+        self.delete_on_close.retain(|id, _| {
+            *id != file_id && (path.is_none() || self.file_path_map.get(id) != path)
+        });
+    }
+
+    /// Reserve space for an open file by growing it to `allocation_size`.
+    ///
+    /// Allocations at or below the current end of file leave the file alone,
+    /// as do files already marked for deletion. Directories have no handle to
+    /// grow and report `NotFound`.
+    pub(crate) fn allocate(&self, file_id: u32, allocation_size: u64) -> io::Result<()> {
+        let Some(Some(file)) = self.file_map.get(&file_id) else {
+            return Err(io::ErrorKind::NotFound.into());
+        };
+        if self.delete_on_close.contains_key(&file_id) {
+            return Ok(());
+        }
+        if allocation_size > file.metadata()?.len() {
+            file.set_len(allocation_size)?;
+        }
+        Ok(())
+    }
+
+    /// Forget a closed handle, deleting its file if a delete is pending.
+    ///
+    /// Without POSIX semantics the file stays until the last handle on it
+    /// closes, so the pending delete moves to another open handle instead.
+    pub(crate) fn close_file(&mut self, file_id: u32) {
+        let flags = self.delete_on_close.remove(&file_id);
+        let path = self.file_path_map.remove(&file_id);
+        self.file_map.remove(&file_id);
+        self.file_device_map.remove(&file_id);
+        self.file_dir_map.remove(&file_id);
+
+        let (Some(flags), Some(path)) = (flags, path) else {
+            return;
+        };
+        if flags & FILE_DISPOSITION_POSIX_SEMANTICS == 0 {
+            let other = self
+                .file_path_map
+                .iter()
+                .find(|(_, open)| **open == path)
+                .map(|(id, _)| *id);
+            if let Some(other) = other {
+                debug!("Deferring delete of {:?} until handle {} closes", path, other);
+                self.delete_on_close.entry(other).or_insert(flags);
+                return;
+            }
+        }
+
+        debug!("Deleting on close: {:?}", path);
+        let result = match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => fs::remove_dir(&path),
+            Ok(_) => fs::remove_file(&path),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("Failed to delete {:?} on close: {:?}", path, e);
+        }
+    }
+
+    /// Sync every open file to disk and close all handles, returning how many
+    /// were open.
+    ///
+    /// Called when the connection ends, since the server never gets to close
+    /// its handles then. Pending deletes are carried out as on a normal close.
+    pub fn flush_all(&mut self) -> usize {
+        for (file_id, file) in &self.file_map {
+            if let Some(file) = file {
+                if let Err(e) = file.sync_all() {
+                    warn!("Failed to sync file {} on disconnect: {:?}", file_id, e);
+                }
+            }
+        }
+
+        let file_ids: Vec<u32> = self.file_map.keys().copied().collect();
+        for file_id in &file_ids {
+            self.close_file(*file_id);
+        }
+        self.notify_watches.clear();
+        file_ids.len()
+    }
+
     /// Insert a file entry with handle.
     pub(crate) fn insert_file(&mut self, file_id: u32, device_id: u32, path: PathBuf, file: File) {
         self.file_map.insert(file_id, Some(file));
@@ -113,9 +369,18 @@ impl RdpdrBackend for MultiDriveBackend {
 
     fn handle_scard_call(
         &mut self,
-        _req: DeviceControlRequest<ScardIoCtlCode>,
-        _call: ScardCall,
+        req: DeviceControlRequest<ScardIoCtlCode>,
+        call: ScardCall,
     ) -> PduResult<()> {
+        // Responses are sent by the session once the bridge completes the call
+        #[cfg(feature = "smartcard")]
+        if let Some(bridge) = &self.smartcard {
+            bridge.handle(req, call);
+            return Ok(());
+        }
+        // Without a bridge the call goes unanswered (the server times it out)
+        let _ = call;
+        debug!("Ignoring smart card call {:?}", req.io_control_code);
         Ok(())
     }
 
@@ -128,9 +393,8 @@ impl RdpdrBackend for MultiDriveBackend {
             }
             ServerDriveIoRequest::DeviceReadRequest(req_inner) => read_device(self, req_inner),
             ServerDriveIoRequest::DeviceCloseRequest(req_inner) => close_device(self, req_inner),
-            ServerDriveIoRequest::ServerDriveNotifyChangeDirectoryRequest(_) => {
-                // TODO: implement directory change notifications
-                Ok(Vec::new())
+            ServerDriveIoRequest::ServerDriveNotifyChangeDirectoryRequest(req_inner) => {
+                notify_change_directory(self, req_inner)
             }
             ServerDriveIoRequest::ServerDriveQueryDirectoryRequest(req_inner) => {
                 query_directory(self, req_inner)
@@ -157,3 +421,184 @@ impl RdpdrBackend for MultiDriveBackend {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drive_delete_pending_can_be_cleared() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("keep.txt");
+        std::fs::write(&path, "data").unwrap();
+
+        let mut backend = MultiDriveBackend::new();
+        backend.add_drive(1, dir.path().to_path_buf(), false);
+        backend.insert_file(1, 1, path.clone(), std::fs::File::open(&path).unwrap());
+
+        backend.set_disposition(1, FILE_DISPOSITION_DELETE);
+        assert!(path.exists(), "marking must not delete eagerly");
+        backend.set_disposition(1, FILE_DISPOSITION_DO_NOT_DELETE);
+        backend.close_file(1);
+        assert!(path.exists());
+
+        backend.insert_file(2, 1, path.clone(), std::fs::File::open(&path).unwrap());
+        backend.set_disposition(2, FILE_DISPOSITION_DELETE);
+        backend.close_file(2);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_drive_delete_on_close_waits_for_last_handle() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let subdir = dir.path().join("old");
+        std::fs::create_dir(&subdir).unwrap();
+
+        let mut backend = MultiDriveBackend::new();
+        backend.add_drive(1, dir.path().to_path_buf(), false);
+        backend.insert_directory(1, 1, subdir.clone());
+        backend.insert_directory(2, 1, subdir.clone());
+
+        backend.set_disposition(1, FILE_DISPOSITION_DELETE);
+        backend.close_file(1);
+        assert!(subdir.is_dir(), "another handle is still open");
+        backend.close_file(2);
+        assert!(!subdir.exists());
+
+        // POSIX semantics remove the name when the marking handle closes
+        let path = dir.path().join("posix.txt");
+        std::fs::write(&path, "data").unwrap();
+        backend.insert_file(3, 1, path.clone(), std::fs::File::open(&path).unwrap());
+        backend.insert_file(4, 1, path.clone(), std::fs::File::open(&path).unwrap());
+        backend.set_disposition(3, FILE_DISPOSITION_DELETE | FILE_DISPOSITION_POSIX_SEMANTICS);
+        backend.close_file(3);
+        assert!(!path.exists());
+        backend.close_file(4);
+    }
+
+    #[test]
+    fn test_drives_resolve_to_their_own_directories() {
+        let first = tempfile::TempDir::new().unwrap();
+        let second = tempfile::TempDir::new().unwrap();
+        std::fs::write(first.path().join("notes.txt"), "first").unwrap();
+        std::fs::write(second.path().join("notes.txt"), "second").unwrap();
+
+        let drive = |name: &str, dir: &tempfile::TempDir| DriveMapping {
+            name: name.to_string(),
+            path: dir.path().to_string_lossy().to_string(),
+            read_only: false,
+        };
+        let mut backend = MultiDriveBackend::new();
+        let drive_list = backend.add_drives(&[drive("First", &first), drive("Second", &second)]);
+        assert_eq!(drive_list, vec![(1, "First".to_string()), (2, "Second".to_string())]);
+
+        let read = |device_id| {
+            let path = backend.resolve_path(device_id, "\\notes.txt").unwrap();
+            std::fs::read_to_string(path).unwrap()
+        };
+        assert_eq!(read(1), "first");
+        assert_eq!(read(2), "second");
+        assert_eq!(backend.resolve_path(2, "").unwrap(), second.path());
+        assert!(backend.resolve_path(3, "notes.txt").is_none());
+    }
+
+    #[test]
+    fn test_drive_allocation_grows_without_truncating() {
+        use std::io::{Read, Seek, SeekFrom, Write};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("db.bin");
+        std::fs::write(&path, "header").unwrap();
+        let open = || {
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .unwrap()
+        };
+
+        let mut backend = MultiDriveBackend::new();
+        backend.add_drive(1, dir.path().to_path_buf(), false);
+        backend.insert_file(1, 1, path.clone(), open());
+
+        backend.allocate(1, 8192).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 8192);
+
+        // Smaller allocations keep the current end of file
+        backend.allocate(1, 16).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 8192);
+
+        let mut file = open();
+        file.seek(SeekFrom::Start(4096)).unwrap();
+        file.write_all(b"tail").unwrap();
+        let mut contents = Vec::new();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents.len(), 8192);
+        assert_eq!(&contents[..6], b"header");
+        assert_eq!(&contents[4096..4100], b"tail");
+
+        let subdir = dir.path().join("dir");
+        std::fs::create_dir(&subdir).unwrap();
+        backend.insert_directory(2, 1, subdir);
+        assert!(backend.allocate(2, 4096).is_err());
+    }
+
+    #[test]
+    fn test_drive_flush_all_on_disconnect() {
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("report.docx");
+        let temp = dir.path().join("~report.tmp");
+        std::fs::write(&temp, "scratch").unwrap();
+
+        let mut backend = MultiDriveBackend::new();
+        backend.add_drive(1, dir.path().to_path_buf(), false);
+        backend.insert_file(1, 1, path.clone(), std::fs::File::create(&path).unwrap());
+        backend.insert_file(2, 1, temp.clone(), std::fs::File::open(&temp).unwrap());
+        backend.insert_directory(3, 1, dir.path().to_path_buf());
+        backend.set_disposition(2, FILE_DISPOSITION_DELETE);
+
+        // Written, then the connection drops before the server closes the handle
+        if let Some(Some(file)) = backend.file_map.get_mut(&1) {
+            file.write_all(b"saved").unwrap();
+        }
+        assert_eq!(backend.flush_all(), 3);
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "saved");
+        assert!(!temp.exists(), "pending deletes still happen");
+        assert!(backend.file_map.is_empty());
+        assert!(backend.file_path_map.is_empty());
+        assert!(backend.file_device_map.is_empty());
+        assert!(backend.delete_on_close.is_empty());
+    }
+
+    #[test]
+    fn test_drive_mount_and_unmount() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let drive = |name: &str| DriveMapping {
+            name: name.to_string(),
+            path: dir.path().to_string_lossy().to_string(),
+            read_only: false,
+        };
+        let mut backend = MultiDriveBackend::new();
+        backend.add_drives(&[drive("First")]);
+        // A smart card device takes the ID after the connect-time drives
+        assert_eq!(backend.reserve_device_id(), 2);
+
+        let device_id = backend.mount_drive(&drive("Extra"));
+        assert_eq!(device_id, 3);
+        assert!(backend.resolve_path(device_id, "").is_some());
+
+        let file = std::fs::File::create(dir.path().join("open.txt")).unwrap();
+        backend.insert_file(7, device_id, dir.path().join("open.txt"), file);
+        assert_eq!(backend.unmount_drive("extra"), Some(device_id));
+        assert!(backend.file_map.is_empty());
+        assert!(backend.resolve_path(device_id, "").is_none());
+        assert_eq!(backend.unmount_drive("Extra"), None);
+
+        // IDs of removed drives aren't handed out again
+        assert_eq!(backend.mount_drive(&drive("Extra")), 4);
+    }
+}
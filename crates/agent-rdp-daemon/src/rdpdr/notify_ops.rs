@@ -0,0 +1,337 @@
+//! Directory change notifications for RDPDR.
+//!
+//! The RDPDR backend is synchronous, so change notifications are implemented by
+//! polling: when the server sends a notify request we snapshot the directory, and
+//! the session's frame processor periodically calls `poll_change_notifications`
+//! to diff against the snapshot and complete the pending request.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use ironrdp::pdu::PduResult;
+use ironrdp_core::{ensure_size, Encode, EncodeResult, WriteCursor};
+use ironrdp_rdpdr::pdu::efs::*;
+use ironrdp_svc::{SvcEncode, SvcMessage};
+use tracing::{debug, warn};
+
+use super::MultiDriveBackend;
+
+// CompletionFilter flags (MS-FSCC 2.4.42 / MS-SMB2 2.2.35).
+const FILE_NOTIFY_CHANGE_FILE_NAME: u32 = 0x0000_0001;
+const FILE_NOTIFY_CHANGE_DIR_NAME: u32 = 0x0000_0002;
+const FILE_NOTIFY_CHANGE_ATTRIBUTES: u32 = 0x0000_0004;
+const FILE_NOTIFY_CHANGE_SIZE: u32 = 0x0000_0008;
+const FILE_NOTIFY_CHANGE_LAST_WRITE: u32 = 0x0000_0010;
+
+// FILE_NOTIFY_INFORMATION actions.
+const FILE_ACTION_ADDED: u32 = 0x0000_0001;
+const FILE_ACTION_REMOVED: u32 = 0x0000_0002;
+const FILE_ACTION_MODIFIED: u32 = 0x0000_0003;
+
+/// Upper bound on entries walked per watch, so a huge tree can't stall the session.
+const MAX_SNAPSHOT_ENTRIES: usize = 10_000;
+
+/// Metadata tracked per directory entry for change detection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EntryInfo {
+    is_dir: bool,
+    len: u64,
+    modified: Option<SystemTime>,
+    readonly: bool,
+}
+
+/// A notify request the server is waiting on.
+#[derive(Debug)]
+struct PendingNotify {
+    device_io_request: DeviceIoRequest,
+    completion_filter: u32,
+}
+
+/// Watch state for a directory handle.
+#[derive(Debug)]
+pub struct NotifyWatch {
+    /// Directory being watched.
+    path: PathBuf,
+    /// Whether subdirectories are included.
+    watch_tree: bool,
+    /// Last observed directory contents, keyed by path relative to `path`.
+    snapshot: HashMap<PathBuf, EntryInfo>,
+    /// Outstanding request (None once completed, until the server re-arms it).
+    pending: Option<PendingNotify>,
+}
+
+/// Handle a notify change directory request.
+///
+/// The request stays pending until a matching change is observed by
+/// `poll_change_notifications`, so no response is returned here on success.
+pub fn notify_change_directory(
+    backend: &mut MultiDriveBackend,
+    req_inner: ServerDriveNotifyChangeDirectoryRequest,
+) -> PduResult<Vec<SvcMessage>> {
+    let file_id = req_inner.device_io_request.file_id;
+    let watch_tree = req_inner.watch_tree != 0;
+
+    let path = match (backend.file_map.get(&file_id), backend.file_path_map.get(&file_id)) {
+        // Only directory handles (no file) can be watched
+        (Some(None), Some(path)) => path.clone(),
+        _ => {
+            warn!("notify_change_directory: file_id={} is not an open directory", file_id);
+            let res = ClientDriveNotifyChangeDirectoryResponse::new(
+                req_inner.device_io_request,
+                NtStatus::NOT_A_DIRECTORY,
+                Vec::new(),
+            );
+            return Ok(vec![SvcMessage::from(res)]);
+        }
+    };
+
+    debug!(
+        "notify_change_directory: file_id={}, path={:?}, filter=0x{:x}, watch_tree={}",
+        file_id, path, req_inner.completion_filter, watch_tree
+    );
+
+    let pending = PendingNotify {
+        device_io_request: req_inner.device_io_request,
+        completion_filter: req_inner.completion_filter,
+    };
+
+    // Keep the previous snapshot when re-armed so changes between requests aren't lost
+    match backend.notify_watches.get_mut(&file_id) {
+        Some(watch) if watch.watch_tree == watch_tree => {
+            watch.pending = Some(pending);
+        }
+        _ => {
+            let snapshot = snapshot_directory(&path, watch_tree);
+            backend.notify_watches.insert(
+                file_id,
+                NotifyWatch {
+                    path,
+                    watch_tree,
+                    snapshot,
+                    pending: Some(pending),
+                },
+            );
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+/// Stop watching a directory handle (called when the handle is closed).
+pub fn cancel_notify(backend: &mut MultiDriveBackend, file_id: u32) {
+    if backend.notify_watches.remove(&file_id).is_some() {
+        debug!("notify_change_directory: cancelled watch for file_id={}", file_id);
+    }
+}
+
+/// Check all pending notify requests and complete those with matching changes.
+pub fn poll_change_notifications(backend: &mut MultiDriveBackend) -> Vec<SvcMessage> {
+    let mut messages = Vec::new();
+
+    for (file_id, watch) in backend.notify_watches.iter_mut() {
+        if watch.pending.is_none() {
+            continue;
+        }
+
+        let current = snapshot_directory(&watch.path, watch.watch_tree);
+        let changes = diff_snapshots(&watch.snapshot, &current);
+        watch.snapshot = current;
+
+        let Some(pending) = watch.pending.as_ref() else {
+            continue;
+        };
+        let matching: Vec<(u32, PathBuf)> = changes
+            .into_iter()
+            .filter(|(action, _, is_dir)| matches_filter(pending.completion_filter, *action, *is_dir))
+            .map(|(action, path, _)| (action, path))
+            .collect();
+
+        if matching.is_empty() {
+            continue;
+        }
+
+        debug!(
+            "notify_change_directory: file_id={} completed with {} change(s)",
+            file_id,
+            matching.len()
+        );
+
+        if let Some(pending) = watch.pending.take() {
+            let res = ClientDriveNotifyChangeDirectoryResponse::new(
+                pending.device_io_request,
+                NtStatus::SUCCESS,
+                encode_notify_information(&matching),
+            );
+            messages.push(SvcMessage::from(res));
+        }
+    }
+
+    messages
+}
+
+/// Take a snapshot of a directory (recursively if `watch_tree` is set).
+fn snapshot_directory(root: &Path, watch_tree: bool) -> HashMap<PathBuf, EntryInfo> {
+    let mut snapshot = HashMap::new();
+    let mut stack = vec![PathBuf::new()];
+
+    while let Some(relative) = stack.pop() {
+        let Ok(entries) = fs::read_dir(root.join(&relative)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if snapshot.len() >= MAX_SNAPSHOT_ENTRIES {
+                return snapshot;
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let entry_path = relative.join(entry.file_name());
+            if watch_tree && meta.is_dir() {
+                stack.push(entry_path.clone());
+            }
+            snapshot.insert(
+                entry_path,
+                EntryInfo {
+                    is_dir: meta.is_dir(),
+                    len: meta.len(),
+                    modified: meta.modified().ok(),
+                    readonly: meta.permissions().readonly(),
+                },
+            );
+        }
+    }
+
+    snapshot
+}
+
+/// Compare two snapshots, returning (action, relative path, is_dir) for each change.
+fn diff_snapshots(
+    old: &HashMap<PathBuf, EntryInfo>,
+    new: &HashMap<PathBuf, EntryInfo>,
+) -> Vec<(u32, PathBuf, bool)> {
+    let mut changes = Vec::new();
+
+    for (path, info) in new {
+        match old.get(path) {
+            None => changes.push((FILE_ACTION_ADDED, path.clone(), info.is_dir)),
+            Some(old_info) if old_info != info => {
+                changes.push((FILE_ACTION_MODIFIED, path.clone(), info.is_dir))
+            }
+            Some(_) => {}
+        }
+    }
+    for (path, info) in old {
+        if !new.contains_key(path) {
+            changes.push((FILE_ACTION_REMOVED, path.clone(), info.is_dir));
+        }
+    }
+
+    changes.sort_by(|a, b| a.1.cmp(&b.1));
+    changes
+}
+
+/// Whether a change is covered by the request's CompletionFilter.
+fn matches_filter(filter: u32, action: u32, is_dir: bool) -> bool {
+    match action {
+        FILE_ACTION_ADDED | FILE_ACTION_REMOVED => {
+            let name_flag = if is_dir {
+                FILE_NOTIFY_CHANGE_DIR_NAME
+            } else {
+                FILE_NOTIFY_CHANGE_FILE_NAME
+            };
+            filter & name_flag != 0
+        }
+        _ => {
+            filter
+                & (FILE_NOTIFY_CHANGE_ATTRIBUTES
+                    | FILE_NOTIFY_CHANGE_SIZE
+                    | FILE_NOTIFY_CHANGE_LAST_WRITE)
+                != 0
+        }
+    }
+}
+
+/// Encode changes as a chain of FILE_NOTIFY_INFORMATION structures (MS-FSCC 2.4.42).
+fn encode_notify_information(changes: &[(u32, PathBuf)]) -> Vec<u8> {
+    let mut buffer = Vec::new();
+
+    for (idx, (action, path)) in changes.iter().enumerate() {
+        // Windows expects backslash-separated names relative to the watched directory
+        let name: Vec<u8> = path
+            .to_string_lossy()
+            .replace('/', "\\")
+            .encode_utf16()
+            .flat_map(|c| c.to_le_bytes())
+            .collect();
+
+        // Each entry is 4-byte aligned; NextEntryOffset is 0 for the last one
+        let entry_len = 12 + name.len();
+        let padded_len = (entry_len + 3) & !3;
+        let next_offset = if idx + 1 < changes.len() { padded_len } else { 0 };
+
+        buffer.extend_from_slice(&(next_offset as u32).to_le_bytes());
+        buffer.extend_from_slice(&action.to_le_bytes());
+        buffer.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        buffer.extend_from_slice(&name);
+        if next_offset != 0 {
+            buffer.resize(buffer.len() + (padded_len - entry_len), 0);
+        }
+    }
+
+    buffer
+}
+
+/// DR_DRIVE_NOTIFY_CHANGE_DIRECTORY_RSP (MS-RDPEFS 2.2.3.4.11).
+///
+/// IronRDP doesn't model this PDU, so it is encoded here.
+#[derive(Debug)]
+struct ClientDriveNotifyChangeDirectoryResponse {
+    device_io_request: DeviceIoRequest,
+    io_status: NtStatus,
+    buffer: Vec<u8>,
+}
+
+impl ClientDriveNotifyChangeDirectoryResponse {
+    const NAME: &'static str = "DR_DRIVE_NOTIFY_CHANGE_DIRECTORY_RSP";
+
+    /// RDPDR_CTYP_CORE
+    const COMPONENT: u16 = 0x4472;
+    /// PAKID_CORE_DEVICE_IOCOMPLETION
+    const PACKET_ID: u16 = 0x4943;
+
+    fn new(device_io_request: DeviceIoRequest, io_status: NtStatus, buffer: Vec<u8>) -> Self {
+        Self {
+            device_io_request,
+            io_status,
+            buffer,
+        }
+    }
+}
+
+impl Encode for ClientDriveNotifyChangeDirectoryResponse {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+        // RDPDR_HEADER + DR_DEVICE_IOCOMPLETION header
+        dst.write_u16(Self::COMPONENT);
+        dst.write_u16(Self::PACKET_ID);
+        dst.write_u32(self.device_io_request.device_id);
+        dst.write_u32(self.device_io_request.completion_id);
+        dst.write_u32(u32::from(self.io_status));
+        // Length + Buffer
+        dst.write_u32(self.buffer.len() as u32);
+        dst.write_slice(&self.buffer);
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        4 + 12 + 4 + self.buffer.len()
+    }
+}
+
+impl SvcEncode for ClientDriveNotifyChangeDirectoryResponse {}
@@ -1,7 +1,7 @@
 //! Query operations for RDPDR: query_information, query_volume_information, query_directory.
 
 use std::fs::{self, ReadDir};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use ironrdp::pdu::PduResult;
 use ironrdp_rdpdr::pdu::efs::*;
@@ -10,8 +10,8 @@ use ironrdp_svc::SvcMessage;
 use tracing::{debug, warn};
 
 use super::helpers::{
-    get_creation_time, get_disk_space, get_file_attributes, get_last_access_time,
-    get_last_write_time,
+    drive_relative_path, get_creation_time, get_disk_space, get_file_attributes, get_file_sizes,
+    get_last_access_time, get_last_write_time, join_drive_path, wildcard_match,
 };
 use super::MultiDriveBackend;
 
@@ -21,6 +21,42 @@ pub struct DirIterState {
     pub iter: ReadDir,
     #[allow(dead_code)]
     pub base_path: PathBuf,
+    /// Wildcard pattern the returned entries must match.
+    pub pattern: String,
+}
+
+/// What an initial directory query asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirQuery {
+    /// Entries of a directory whose names match a wildcard pattern.
+    List { dir: PathBuf, pattern: String },
+    /// A single file or directory.
+    Single(PathBuf),
+}
+
+/// Resolve the path of an initial directory query under a drive's base path.
+///
+/// A wildcard in the last component lists its directory (`\*` lists the root);
+/// anything else, the root included, names one entry. None if the path leaves
+/// the drive.
+pub fn resolve_dir_query(base_path: &Path, query_path: &str) -> Option<DirQuery> {
+    let relative = drive_relative_path(query_path)?;
+    let (dir, pattern) = relative.rsplit_once('/').unwrap_or(("", &relative));
+    if pattern.contains(['*', '?']) {
+        Some(DirQuery::List {
+            dir: join_drive_path(base_path, dir),
+            pattern: pattern.to_string(),
+        })
+    } else {
+        Some(DirQuery::Single(join_drive_path(base_path, &relative)))
+    }
+}
+
+/// Advance `iter` to the next entry whose name matches `pattern`.
+fn next_match(iter: &mut ReadDir, pattern: &str) -> Option<PathBuf> {
+    iter.filter_map(Result::ok)
+        .find(|entry| wildcard_match(pattern, &entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
 }
 
 /// Handle query information request.
@@ -48,6 +84,7 @@ pub fn query_information(
                 Ok(meta) => {
                     let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
                     let file_attribute = get_file_attributes(&meta, name);
+                    let (end_of_file, allocation_size) = get_file_sizes(&meta);
 
                     if FileInformationClassLevel::FILE_BASIC_INFORMATION
                         == req_inner.file_info_class_lvl
@@ -78,8 +115,8 @@ pub fn query_information(
                             Boolean::False
                         };
                         let standard_info = FileStandardInformation {
-                            allocation_size: i64::try_from(meta.len()).unwrap_or(0),
-                            end_of_file: i64::try_from(meta.len()).unwrap_or(0),
+                            allocation_size,
+                            end_of_file,
                             number_of_links: 1,
                             delete_pending: Boolean::False,
                             directory: dir,
@@ -94,6 +131,21 @@ pub fn query_information(
                             },
                         );
                         Ok(vec![SvcMessage::from(res)])
+                    } else if FileInformationClassLevel::FILE_END_OF_FILE_INFORMATION
+                        == req_inner.file_info_class_lvl
+                    {
+                        let res = RdpdrPdu::ClientDriveQueryInformationResponse(
+                            ClientDriveQueryInformationResponse {
+                                device_io_response: DeviceIoResponse::new(
+                                    req_inner.device_io_request,
+                                    NtStatus::SUCCESS,
+                                ),
+                                buffer: Some(FileInformationClass::EndOfFile(
+                                    FileEndOfFileInformation { end_of_file },
+                                )),
+                            },
+                        );
+                        Ok(vec![SvcMessage::from(res)])
                     } else if FileInformationClassLevel::FILE_ATTRIBUTE_TAG_INFORMATION
                         == req_inner.file_info_class_lvl
                     {
@@ -225,6 +277,13 @@ pub fn query_volume_information(
             } else if FileSystemInformationClassLevel::FILE_FS_ATTRIBUTE_INFORMATION
                 == req_inner.fs_info_class_lvl
             {
+                let mut file_system_attributes = FileSystemAttributes::FILE_CASE_SENSITIVE_SEARCH
+                    | FileSystemAttributes::FILE_CASE_PRESERVED_NAMES
+                    | FileSystemAttributes::FILE_UNICODE_ON_DISK;
+                if backend.is_read_only_for_file(req_inner.device_io_request.file_id) {
+                    file_system_attributes |= FileSystemAttributes::FILE_READ_ONLY_VOLUME;
+                }
+
                 Ok(vec![SvcMessage::from(
                     RdpdrPdu::ClientDriveQueryVolumeInformationResponse(
                         ClientDriveQueryVolumeInformationResponse {
@@ -234,10 +293,7 @@ pub fn query_volume_information(
                             ),
                             buffer: Some(FileSystemInformationClass::FileFsAttributeInformation(
                                 FileFsAttributeInformation {
-                                    file_system_attributes:
-                                        FileSystemAttributes::FILE_CASE_SENSITIVE_SEARCH
-                                            | FileSystemAttributes::FILE_CASE_PRESERVED_NAMES
-                                            | FileSystemAttributes::FILE_UNICODE_ON_DISK,
+                                    file_system_attributes,
                                     max_component_name_len: 260,
                                     file_system_name: "NTFS".to_owned(),
                                 },
@@ -374,47 +430,24 @@ pub fn query_directory(
             };
 
             if req_inner.initial_query > 0 {
-                if req_inner.path.ends_with('*') {
-                    // Wildcard query - list directory contents
-                    let query_path = req_inner.path.replace('\\', "/");
-                    let len = query_path.len();
-                    // Strip the trailing * and any leading slashes
-                    let dir_path_str = query_path[..len - 1].trim_start_matches('/');
-                    let dir_path = if dir_path_str.is_empty() {
-                        base_path.clone()
-                    } else {
-                        base_path.join(dir_path_str)
-                    };
-
-                    if let Ok(read_dir) = fs::read_dir(&dir_path) {
-                        let mut iter = read_dir;
-                        // Find first non-. and non-.. entry
-                        while let Some(Ok(entry)) = iter.next() {
-                            let name = entry.file_name();
-                            let name_str = name.to_string_lossy();
-                            if name_str != "." && name_str != ".." {
-                                find_file_path = Some(entry.path());
-                                break;
-                            }
+                match resolve_dir_query(&base_path, &req_inner.path) {
+                    Some(DirQuery::List { dir, pattern }) => {
+                        // Wildcard query - list the directory entries matching the last component
+                        if let Ok(mut iter) = fs::read_dir(&dir) {
+                            find_file_path = next_match(&mut iter, &pattern);
+                            backend.file_dir_map.insert(
+                                req_inner.device_io_request.file_id,
+                                DirIterState {
+                                    iter,
+                                    base_path: dir,
+                                    pattern,
+                                },
+                            );
                         }
-                        backend.file_dir_map.insert(
-                            req_inner.device_io_request.file_id,
-                            DirIterState {
-                                iter,
-                                base_path: dir_path,
-                            },
-                        );
                     }
-                } else {
-                    // Specific file query
-                    let query_path = req_inner.path.replace('\\', "/");
-                    let query_path = query_path.trim_start_matches('/');
-                    let full_path = if query_path.is_empty() {
-                        base_path.clone()
-                    } else {
-                        base_path.join(query_path)
-                    };
-                    find_file_path = Some(full_path);
+                    Some(DirQuery::Single(path)) => find_file_path = Some(path),
+                    // Outside the drive: answered as not found
+                    None => {}
                 }
 
                 make_query_dir_resp(
@@ -429,9 +462,7 @@ pub fn query_directory(
                     .file_dir_map
                     .get_mut(&req_inner.device_io_request.file_id)
                 {
-                    if let Some(Ok(entry)) = dir_state.iter.next() {
-                        find_file_path = Some(entry.path());
-                    }
+                    find_file_path = next_match(&mut dir_state.iter, &dir_state.pattern);
                 }
 
                 make_query_dir_resp(
@@ -488,18 +519,7 @@ fn make_query_dir_resp(
 
             match fs::metadata(&file_full_path) {
                 Ok(meta) => {
-                    let file_attribute = get_file_attributes(&meta, file_name);
-                    if file_class == FileInformationClassLevel::FILE_BOTH_DIRECTORY_INFORMATION {
-                        let info = FileBothDirectoryInformation::new(
-                            get_creation_time(&meta),
-                            get_last_write_time(&meta),
-                            get_last_access_time(&meta),
-                            get_last_write_time(&meta),
-                            i64::try_from(meta.len()).unwrap_or(0),
-                            file_attribute,
-                            file_name.to_owned(),
-                        );
-                        let info2 = FileInformationClass::BothDirectory(info);
+                    if let Some(info) = dir_entry_info(&file_class, &meta, file_name) {
                         Ok(vec![SvcMessage::from(
                             RdpdrPdu::ClientDriveQueryDirectoryResponse(
                                 ClientDriveQueryDirectoryResponse {
@@ -507,7 +527,7 @@ fn make_query_dir_resp(
                                         device_io_request,
                                         NtStatus::SUCCESS,
                                     ),
-                                    buffer: Some(info2),
+                                    buffer: Some(info),
                                 },
                             ),
                         )])
@@ -549,3 +569,150 @@ fn make_query_dir_resp(
         }
     }
 }
+
+/// Build a directory entry in the requested information class.
+///
+/// MS-RDPEFS allows the directory, full directory, both directory and names
+/// classes for directory queries; `None` for anything else. The ID variants
+/// (`FileIdBothDirectoryInformation` and friends) can't be expressed in a
+/// drive query directory response, so they stay unsupported. The
+/// constructors report the allocation size as the end of file, so it's
+/// replaced with the size in whole allocation units, as `query_information`
+/// reports it.
+fn dir_entry_info(
+    file_class: &FileInformationClassLevel,
+    meta: &fs::Metadata,
+    file_name: &str,
+) -> Option<FileInformationClass> {
+    let file_attribute = get_file_attributes(meta, file_name);
+    let (end_of_file, allocation_size) = get_file_sizes(meta);
+
+    if *file_class == FileInformationClassLevel::FILE_BOTH_DIRECTORY_INFORMATION {
+        let mut info = FileBothDirectoryInformation::new(
+            get_creation_time(meta),
+            get_last_write_time(meta),
+            get_last_access_time(meta),
+            get_last_write_time(meta),
+            end_of_file,
+            file_attribute,
+            file_name.to_owned(),
+        );
+        info.allocation_size = allocation_size;
+        Some(FileInformationClass::BothDirectory(info))
+    } else if *file_class == FileInformationClassLevel::FILE_FULL_DIRECTORY_INFORMATION {
+        let mut info = FileFullDirectoryInformation::new(
+            get_creation_time(meta),
+            get_last_write_time(meta),
+            get_last_access_time(meta),
+            get_last_write_time(meta),
+            end_of_file,
+            file_attribute,
+            file_name.to_owned(),
+        );
+        info.allocation_size = allocation_size;
+        Some(FileInformationClass::FullDirectory(info))
+    } else if *file_class == FileInformationClassLevel::FILE_DIRECTORY_INFORMATION {
+        let mut info = FileDirectoryInformation::new(
+            get_creation_time(meta),
+            get_last_write_time(meta),
+            get_last_access_time(meta),
+            get_last_write_time(meta),
+            end_of_file,
+            file_attribute,
+            file_name.to_owned(),
+        );
+        info.allocation_size = allocation_size;
+        Some(FileInformationClass::Directory(info))
+    } else if *file_class == FileInformationClassLevel::FILE_NAMES_INFORMATION {
+        Some(FileInformationClass::Names(FileNamesInformation::new(file_name.to_owned())))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_rdp_protocol::DriveMapping;
+
+    #[test]
+    fn test_drive_root_paths_resolve_alike() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("file.txt"), "root file").unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let mut backend = MultiDriveBackend::new();
+        backend.add_drives(&[DriveMapping {
+            name: "Share".to_string(),
+            path: dir.path().to_string_lossy().to_string(),
+            read_only: false,
+        }]);
+
+        // Every spelling of the root is the base directory, which stats as a directory
+        let roots = ["", r"\", "/", r"\.", "C:", r"C:\", r"\\tsclient\Share", r"\\TSCLIENT\Share\"];
+        for root in roots {
+            let path = backend.resolve_path(1, root).unwrap();
+            assert_eq!(path, dir.path(), "root spelled {:?}", root);
+            assert!(std::fs::metadata(&path).unwrap().is_dir());
+            assert_eq!(resolve_dir_query(dir.path(), root), Some(DirQuery::Single(path)));
+        }
+
+        // A file directly at the root, however the root is written
+        for file in [r"\file.txt", "file.txt", r"\\tsclient\Share\file.txt", r"C:\file.txt"] {
+            let path = backend.resolve_path(1, file).unwrap();
+            assert_eq!(path, dir.path().join("file.txt"), "file spelled {:?}", file);
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), "root file");
+            assert_eq!(resolve_dir_query(dir.path(), file), Some(DirQuery::Single(path)));
+        }
+        assert_eq!(backend.resolve_path(1, r"\sub\").unwrap(), dir.path().join("sub"));
+
+        // A root wildcard lists the root's entries
+        let root_listing = Some(DirQuery::List {
+            dir: dir.path().to_path_buf(),
+            pattern: "*".to_string(),
+        });
+        assert_eq!(resolve_dir_query(dir.path(), r"\*"), root_listing);
+        assert_eq!(resolve_dir_query(dir.path(), r"\\tsclient\Share\*"), root_listing);
+        let mut names: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(Result::ok)
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| wildcard_match("*", name))
+            .collect();
+        names.sort();
+        assert_eq!(names, ["file.txt", "sub"]);
+
+        // Wildcards below the root list that directory
+        assert_eq!(
+            resolve_dir_query(dir.path(), r"\sub\*.log"),
+            Some(DirQuery::List {
+                dir: dir.path().join("sub"),
+                pattern: "*.log".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_drive_parent_dirs_stay_on_the_drive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut backend = MultiDriveBackend::new();
+        backend.add_drive(1, dir.path().to_path_buf(), false);
+
+        // `..` below the root resolves in place, for opens and queries alike
+        assert_eq!(drive_relative_path(r"\a\..\b\.\c").as_deref(), Some("b/c"));
+        assert_eq!(backend.resolve_path(1, r"\sub\..\file.txt").unwrap(), dir.path().join("file.txt"));
+        assert_eq!(
+            resolve_dir_query(dir.path(), r"\sub\..\*"),
+            Some(DirQuery::List {
+                dir: dir.path().to_path_buf(),
+                pattern: "*".to_string(),
+            })
+        );
+
+        // Anything that climbs above the root is refused
+        for path in [r"\..", r"\..\etc\passwd", r"\sub\..\..\x", r"C:\..\x", r"\\tsclient\Share\..\Other"] {
+            assert_eq!(drive_relative_path(path), None, "path {:?}", path);
+            assert!(backend.resolve_path(1, path).is_none(), "path {:?}", path);
+            assert!(resolve_dir_query(dir.path(), path).is_none(), "path {:?}", path);
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! Smart card redirection bridged to the local PC/SC service.
+//!
+//! The server forwards the SCard* calls made by applications in the session as
+//! device control requests on the redirected smart card device. Each call is
+//! replayed against the local PC/SC daemon on a worker thread (card I/O can be
+//! slow) and the result is handed to the session to send back. Waits for reader
+//! state changes get a thread of their own, so a pending wait doesn't hold up
+//! transmits.
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::sync::mpsc as std_mpsc;
+use std::time::Duration;
+
+use ironrdp_rdpdr::pdu::efs::{DeviceControlRequest, DeviceControlResponse, DeviceIoResponse, NtStatus};
+use ironrdp_rdpdr::pdu::esc::*;
+use ironrdp_rdpdr::pdu::RdpdrPdu;
+use tracing::{debug, info, warn};
+
+use super::ScardResponseSender;
+
+/// Longest response accepted from a card (extended APDUs).
+const MAX_RECV_BUFFER: usize = pcsc::MAX_BUFFER_SIZE_EXTENDED;
+
+/// Longest wait for a reader state change before reporting a timeout. The
+/// server asks again, so an abandoned wait doesn't keep its thread forever.
+const MAX_STATUS_CHANGE_WAIT: Duration = Duration::from_secs(30);
+
+/// SCARD_READER_TYPE_USB, reported for every reader.
+const READER_TYPE_USB: u32 = 0x20;
+
+/// Low 16 bits of a reader state hold the flags, the high 16 bits an event count.
+const STATE_FLAGS_MASK: u32 = 0xFFFF;
+
+type ScardRequest = (DeviceControlRequest<ScardIoCtlCode>, ScardCall);
+
+/// Handle to the worker thread running smart card calls.
+///
+/// The worker stops once the bridge is dropped with the RDPDR backend.
+#[derive(Debug)]
+pub struct SmartCardBridge {
+    requests: std_mpsc::Sender<ScardRequest>,
+}
+
+impl SmartCardBridge {
+    /// Start the worker, or return `None` when the PC/SC service is unavailable
+    /// or has no reader (the smart card device is then not announced).
+    pub fn start(responses: ScardResponseSender) -> Option<Self> {
+        let context = match pcsc::Context::establish(pcsc::Scope::User) {
+            Ok(context) => context,
+            Err(e) => {
+                warn!("Smart card redirection disabled: PC/SC service unavailable ({})", e);
+                return None;
+            }
+        };
+        match context.list_readers_owned() {
+            Ok(readers) if !readers.is_empty() => {
+                let names: Vec<_> = readers.iter().map(|r| r.to_string_lossy()).collect();
+                info!("Smart card readers: {}", names.join(", "));
+            }
+            Ok(_) | Err(pcsc::Error::NoReadersAvailable) => {
+                warn!("Smart card redirection disabled: no reader present");
+                return None;
+            }
+            Err(e) => {
+                warn!("Smart card redirection disabled: failed to list readers ({})", e);
+                return None;
+            }
+        }
+
+        let (requests, rx) = std_mpsc::channel();
+        let worker = Worker {
+            responses,
+            contexts: HashMap::new(),
+            cards: HashMap::new(),
+            cache: HashMap::new(),
+            next_id: 1,
+        };
+        if let Err(e) = std::thread::Builder::new()
+            .name("agent-rdp-scard".to_string())
+            .spawn(move || worker.run(rx))
+        {
+            warn!("Smart card redirection disabled: failed to start worker ({})", e);
+            return None;
+        }
+
+        Some(Self { requests })
+    }
+
+    /// Queue a call from the server; its response is sent when it completes.
+    pub fn handle(&self, req: DeviceControlRequest<ScardIoCtlCode>, call: ScardCall) {
+        if self.requests.send((req, call)).is_err() {
+            warn!("Smart card worker stopped, dropping call");
+        }
+    }
+}
+
+/// A card connected on behalf of the server.
+struct OpenCard {
+    /// Server context the card was connected in (released with it).
+    context: u32,
+    card: pcsc::Card,
+}
+
+/// Smart card state, owned by the worker thread.
+///
+/// Contexts and cards are known to the server by IDs we hand out.
+struct Worker {
+    responses: ScardResponseSender,
+    contexts: HashMap<u32, pcsc::Context>,
+    cards: HashMap<u32, OpenCard>,
+    /// Smart card cache entries written by the server, by lookup name.
+    cache: HashMap<String, Vec<u8>>,
+    next_id: u32,
+}
+
+impl Worker {
+    fn run(mut self, requests: std_mpsc::Receiver<ScardRequest>) {
+        debug!("Smart card worker started");
+        while let Ok((req, call)) = requests.recv() {
+            self.handle(req, call);
+        }
+        debug!("Smart card worker stopped");
+    }
+
+    fn handle(&mut self, req: DeviceControlRequest<ScardIoCtlCode>, call: ScardCall) {
+        debug!("Smart card call {:?}", req.io_control_code);
+        let output: Box<dyn rpce::Encode> = match call {
+            ScardCall::AccessStartedEventCall(_) => Box::new(LongReturn::new(ReturnCode::Success)),
+            ScardCall::EstablishContextCall(_) => self.establish_context(),
+            ScardCall::ContextCall(call) => self.context_call(req.io_control_code, call),
+            ScardCall::ListReadersCall(call) => self.list_readers(call),
+            ScardCall::GetStatusChangeCall(call) => {
+                // Answered from its own thread once a reader changes
+                self.get_status_change(req, call);
+                return;
+            }
+            ScardCall::ConnectCall(call) => self.connect(call),
+            ScardCall::HCardAndDispositionCall(call) => self.hcard_and_disposition(req.io_control_code, call),
+            ScardCall::TransmitCall(call) => self.transmit(call),
+            ScardCall::StatusCall(call) => self.status(req.io_control_code, call),
+            ScardCall::GetDeviceTypeIdCall(_) => {
+                Box::new(GetDeviceTypeIdReturn::new(ReturnCode::Success, READER_TYPE_USB))
+            }
+            ScardCall::ReadCacheCall(call) => match self.cache.get(&call.lookup_name) {
+                Some(data) => Box::new(ReadCacheReturn::new(ReturnCode::Success, data.clone())),
+                None => Box::new(ReadCacheReturn::new(ReturnCode::CacheItemNotFound, Vec::new())),
+            },
+            ScardCall::WriteCacheCall(call) => {
+                self.cache.insert(call.lookup_name, call.common.data);
+                Box::new(LongReturn::new(ReturnCode::Success))
+            }
+            ScardCall::GetReaderIconCall(_) => {
+                Box::new(GetReaderIconReturn::new(ReturnCode::UnsupportedFeature, Vec::new()))
+            }
+            ScardCall::Unsupported => Box::new(LongReturn::new(ReturnCode::UnsupportedFeature)),
+        };
+        send_response(&self.responses, req, output);
+    }
+
+    fn next_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        id
+    }
+
+    fn establish_context(&mut self) -> Box<dyn rpce::Encode> {
+        match pcsc::Context::establish(pcsc::Scope::User) {
+            Ok(context) => {
+                let id = self.next_id();
+                self.contexts.insert(id, context);
+                Box::new(EstablishContextReturn::new(ReturnCode::Success, ScardContext::new(id)))
+            }
+            Err(e) => Box::new(EstablishContextReturn::new(return_code(e), ScardContext::new(0))),
+        }
+    }
+
+    fn context_call(&mut self, code: ScardIoCtlCode, call: ContextCall) -> Box<dyn rpce::Encode> {
+        let id = call.context.value;
+        let Some(context) = self.contexts.get(&id) else {
+            return Box::new(LongReturn::new(ReturnCode::InvalidHandle));
+        };
+        let result = match code {
+            ScardIoCtlCode::ReleaseContext => {
+                // Cards connected in the context go with it
+                self.cards.retain(|_, open| open.context != id);
+                self.contexts.remove(&id);
+                ReturnCode::Success
+            }
+            ScardIoCtlCode::IsValidContext => ReturnCode::Success,
+            ScardIoCtlCode::Cancel => context.cancel().map_or_else(return_code, |_| ReturnCode::Success),
+            _ => ReturnCode::UnsupportedFeature,
+        };
+        Box::new(LongReturn::new(result))
+    }
+
+    fn list_readers(&self, call: ListReadersCall) -> Box<dyn rpce::Encode> {
+        let Some(context) = self.contexts.get(&call.context.value) else {
+            return Box::new(ListReadersReturn::new(ReturnCode::InvalidHandle, Vec::new()));
+        };
+        match context.list_readers_owned() {
+            Ok(readers) => {
+                let readers = readers
+                    .iter()
+                    .map(|reader| reader.to_string_lossy().into_owned())
+                    .collect();
+                Box::new(ListReadersReturn::new(ReturnCode::Success, readers))
+            }
+            Err(e) => Box::new(ListReadersReturn::new(return_code(e), Vec::new())),
+        }
+    }
+
+    fn get_status_change(&self, req: DeviceControlRequest<ScardIoCtlCode>, call: GetStatusChangeCall) {
+        let Some(context) = self.contexts.get(&call.context.value).cloned() else {
+            let states = call.states.into_iter().map(|state| state.common).collect();
+            let output = Box::new(GetStatusChangeReturn::new(ReturnCode::InvalidHandle, states));
+            send_response(&self.responses, req, output);
+            return;
+        };
+
+        let responses = self.responses.clone();
+        let spawned = std::thread::Builder::new()
+            .name("agent-rdp-scard-wait".to_string())
+            .spawn(move || {
+                let output = wait_status_change(&context, call);
+                send_response(&responses, req, output);
+            });
+        if let Err(e) = spawned {
+            warn!("Failed to start smart card wait: {}", e);
+        }
+    }
+
+    fn connect(&mut self, call: ConnectCall) -> Box<dyn rpce::Encode> {
+        let context = call.common.context.value;
+        let Some(pcsc_context) = self.contexts.get(&context) else {
+            return connect_failed(ReturnCode::InvalidHandle, context);
+        };
+        let Ok(reader) = CString::new(call.reader) else {
+            return connect_failed(ReturnCode::UnknownReader, context);
+        };
+
+        let card = match pcsc_context.connect(
+            &reader,
+            share_mode(call.common.share_mode),
+            pcsc::Protocols::from_bits_truncate(call.common.preferred_protocols.bits()),
+        ) {
+            Ok(card) => card,
+            Err(e) => return connect_failed(return_code(e), context),
+        };
+        let protocol = card
+            .status2_owned()
+            .ok()
+            .and_then(|status| status.protocol2())
+            .map_or(CardProtocol::SCARD_PROTOCOL_UNDEFINED, card_protocol);
+
+        let id = self.next_id();
+        self.cards.insert(id, OpenCard { context, card });
+        Box::new(ConnectReturn::new(
+            ReturnCode::Success,
+            ScardHandle::new(ScardContext::new(context), id),
+            protocol,
+        ))
+    }
+
+    /// Disconnect, or begin or end a transaction.
+    ///
+    /// pcsc ties transactions to a borrow of the card, which can't outlive a
+    /// call, so they're acknowledged without locking the card against other
+    /// local PC/SC clients.
+    fn hcard_and_disposition(&mut self, code: ScardIoCtlCode, call: HCardAndDispositionCall) -> Box<dyn rpce::Encode> {
+        let id = call.handle.value;
+        let result = match code {
+            ScardIoCtlCode::Disconnect => match self.cards.remove(&id) {
+                Some(open) => match open.card.disconnect(disposition(call.disposition)) {
+                    Ok(()) => ReturnCode::Success,
+                    Err((_, e)) => return_code(e),
+                },
+                None => ReturnCode::InvalidHandle,
+            },
+            ScardIoCtlCode::BeginTransaction | ScardIoCtlCode::EndTransaction => {
+                if self.cards.contains_key(&id) {
+                    ReturnCode::Success
+                } else {
+                    ReturnCode::InvalidHandle
+                }
+            }
+            _ => ReturnCode::UnsupportedFeature,
+        };
+        Box::new(LongReturn::new(result))
+    }
+
+    fn transmit(&mut self, call: TransmitCall) -> Box<dyn rpce::Encode> {
+        let Some(open) = self.cards.get(&call.handle.value) else {
+            return Box::new(TransmitReturn::new(ReturnCode::InvalidHandle, None, Vec::new()));
+        };
+        let mut buffer = vec![0u8; MAX_RECV_BUFFER];
+        match open.card.transmit(&call.send_buffer, &mut buffer) {
+            Ok(response) => Box::new(TransmitReturn::new(ReturnCode::Success, None, response.to_vec())),
+            Err(e) => Box::new(TransmitReturn::new(return_code(e), None, Vec::new())),
+        }
+    }
+
+    fn status(&self, code: ScardIoCtlCode, call: StatusCall) -> Box<dyn rpce::Encode> {
+        let encoding = match code {
+            ScardIoCtlCode::StatusA => CharacterSet::Ascii,
+            _ => CharacterSet::Unicode,
+        };
+        let failed = |code| {
+            Box::new(StatusReturn::new(
+                code,
+                Vec::new(),
+                CardState::Unknown,
+                CardProtocol::SCARD_PROTOCOL_UNDEFINED,
+                Vec::new(),
+                encoding,
+            ))
+        };
+
+        let Some(open) = self.cards.get(&call.handle.value) else {
+            return failed(ReturnCode::InvalidHandle);
+        };
+        match open.card.status2_owned() {
+            Ok(status) => Box::new(StatusReturn::new(
+                ReturnCode::Success,
+                status
+                    .reader_names()
+                    .map(|reader| reader.to_string_lossy().into_owned())
+                    .collect(),
+                card_state(status.status()),
+                status
+                    .protocol2()
+                    .map_or(CardProtocol::SCARD_PROTOCOL_UNDEFINED, card_protocol),
+                status.atr().to_vec(),
+                encoding,
+            )),
+            Err(e) => failed(return_code(e)),
+        }
+    }
+}
+
+/// Wait until one of the requested readers leaves the state the server last saw.
+fn wait_status_change(context: &pcsc::Context, call: GetStatusChangeCall) -> Box<dyn rpce::Encode> {
+    let timeout = Duration::from_millis(call.timeout as u64).min(MAX_STATUS_CHANGE_WAIT);
+
+    let readers: Result<Vec<_>, _> = call
+        .states
+        .iter()
+        .map(|state| {
+            // Keep the event count in the high bits so only new events wake the wait
+            let current = pcsc::State::from_bits_retain(state.common.current_state.bits());
+            CString::new(state.reader.as_str()).map(|name| pcsc::ReaderState::new(name, current))
+        })
+        .collect();
+    let Ok(mut readers) = readers else {
+        let states = call.states.into_iter().map(|state| state.common).collect();
+        return Box::new(GetStatusChangeReturn::new(ReturnCode::UnknownReader, states));
+    };
+
+    let result = context
+        .get_status_change(Some(timeout), &mut readers)
+        .map_or_else(return_code, |_| ReturnCode::Success);
+
+    let states = call
+        .states
+        .into_iter()
+        .zip(&readers)
+        .map(|(state, reader)| {
+            let mut common = state.common;
+            let event_state = (reader.event_state().bits() & STATE_FLAGS_MASK) | (reader.event_count() << 16);
+            common.event_state = CardStateFlags::from_bits_retain(event_state);
+            let atr = reader.atr();
+            let len = atr.len().min(common.atr.len());
+            common.atr = [0; 36];
+            common.atr[..len].copy_from_slice(&atr[..len]);
+            common.atr_length = len as u32;
+            common
+        })
+        .collect();
+    Box::new(GetStatusChangeReturn::new(result, states))
+}
+
+/// Send a call's result to the session for writing on the RDPDR channel.
+fn send_response(
+    responses: &ScardResponseSender,
+    req: DeviceControlRequest<ScardIoCtlCode>,
+    output: Box<dyn rpce::Encode>,
+) {
+    let pdu = RdpdrPdu::DeviceControlResponse(DeviceControlResponse {
+        device_io_reply: DeviceIoResponse::new(req.header, NtStatus::SUCCESS),
+        output_buffer: Some(output),
+    });
+    // The session is gone if nobody is listening
+    let _ = responses.send(pdu);
+}
+
+fn connect_failed(code: ReturnCode, context: u32) -> Box<dyn rpce::Encode> {
+    Box::new(ConnectReturn::new(
+        code,
+        ScardHandle::new(ScardContext::new(context), 0),
+        CardProtocol::SCARD_PROTOCOL_UNDEFINED,
+    ))
+}
+
+/// SCARD_SHARE_* value to pcsc share mode.
+fn share_mode(value: u32) -> pcsc::ShareMode {
+    match value {
+        1 => pcsc::ShareMode::Exclusive,
+        3 => pcsc::ShareMode::Direct,
+        _ => pcsc::ShareMode::Shared,
+    }
+}
+
+/// SCARD_*_CARD disposition value to pcsc disposition.
+fn disposition(value: u32) -> pcsc::Disposition {
+    match value {
+        1 => pcsc::Disposition::ResetCard,
+        2 => pcsc::Disposition::UnpowerCard,
+        3 => pcsc::Disposition::EjectCard,
+        _ => pcsc::Disposition::LeaveCard,
+    }
+}
+
+/// Active protocol as the server numbers it (RAW differs between Windows and pcsc-lite).
+fn card_protocol(protocol: pcsc::Protocol) -> CardProtocol {
+    match protocol {
+        pcsc::Protocol::T0 => CardProtocol::SCARD_PROTOCOL_T0,
+        pcsc::Protocol::T1 => CardProtocol::SCARD_PROTOCOL_T1,
+        pcsc::Protocol::RAW => CardProtocol::SCARD_PROTOCOL_RAW,
+    }
+}
+
+/// Card state as the server numbers it (pcsc-lite reports flags, Windows a single state).
+fn card_state(status: pcsc::Status) -> CardState {
+    if status.contains(pcsc::Status::SPECIFIC) {
+        CardState::Specific
+    } else if status.contains(pcsc::Status::NEGOTIABLE) {
+        CardState::Negotiable
+    } else if status.contains(pcsc::Status::POWERED) {
+        CardState::Powered
+    } else if status.contains(pcsc::Status::SWALLOWED) {
+        CardState::Swallowed
+    } else if status.contains(pcsc::Status::PRESENT) {
+        CardState::Present
+    } else if status.contains(pcsc::Status::ABSENT) {
+        CardState::Absent
+    } else {
+        CardState::Unknown
+    }
+}
+
+/// Return code the server expects for a PC/SC error.
+fn return_code(error: pcsc::Error) -> ReturnCode {
+    match error {
+        pcsc::Error::Timeout => ReturnCode::Timeout,
+        pcsc::Error::Cancelled => ReturnCode::Cancelled,
+        pcsc::Error::NoReadersAvailable => ReturnCode::NoReadersAvailable,
+        pcsc::Error::UnknownReader => ReturnCode::UnknownReader,
+        pcsc::Error::NoSmartcard => ReturnCode::NoSmartcard,
+        pcsc::Error::RemovedCard => ReturnCode::RemovedCard,
+        pcsc::Error::ResetCard => ReturnCode::ResetCard,
+        pcsc::Error::UnpoweredCard => ReturnCode::UnpoweredCard,
+        pcsc::Error::UnresponsiveCard => ReturnCode::UnresponsiveCard,
+        pcsc::Error::SharingViolation => ReturnCode::SharingViolation,
+        pcsc::Error::ProtoMismatch => ReturnCode::ProtoMismatch,
+        pcsc::Error::InvalidHandle => ReturnCode::InvalidHandle,
+        pcsc::Error::InvalidParameter => ReturnCode::InvalidParameter,
+        pcsc::Error::InsufficientBuffer => ReturnCode::InsufficientBuffer,
+        pcsc::Error::NotTransacted => ReturnCode::NotTransacted,
+        pcsc::Error::NoService | pcsc::Error::ServiceStopped => ReturnCode::NoService,
+        _ => ReturnCode::InternalError,
+    }
+}
@@ -1,6 +1,7 @@
 //! Set operations for RDPDR: set_information (rename, delete disposition, etc.).
 
 use std::fs;
+use std::path::{Path, PathBuf};
 
 use ironrdp::pdu::{encode_err, PduResult};
 use ironrdp_rdpdr::pdu::efs::*;
@@ -8,7 +9,11 @@ use ironrdp_rdpdr::pdu::RdpdrPdu;
 use ironrdp_svc::SvcMessage;
 use tracing::{debug, warn};
 
-use super::MultiDriveBackend;
+use super::helpers::{drive_relative_path, join_drive_path};
+use super::{MultiDriveBackend, FILE_DISPOSITION_DELETE, FILE_DISPOSITION_DO_NOT_DELETE};
+
+/// STATUS_NOT_SAME_DEVICE, which ironrdp_rdpdr has no constant for.
+const STATUS_NOT_SAME_DEVICE: u32 = 0xC000_00D4;
 
 /// Handle set information request (rename, delete, truncate, etc.).
 pub fn set_information(
@@ -25,6 +30,16 @@ pub fn set_information(
         std::mem::discriminant(&req_inner.set_buffer)
     );
 
+    // Renames, deletes and truncation all modify the drive
+    if backend.is_read_only(device_id) {
+        warn!("set_information rejected on read-only drive: device_id={}", device_id);
+        let res = RdpdrPdu::ClientDriveSetInformationResponse(
+            ClientDriveSetInformationResponse::new(&req_inner, NtStatus::ACCESS_DENIED)
+                .map_err(|e| encode_err!(e))?,
+        );
+        return Ok(vec![SvcMessage::from(res)]);
+    }
+
     match backend.file_path_map.get(&file_id) {
         Some(file_path) => {
             match &req_inner.set_buffer {
@@ -33,66 +48,73 @@ pub fn set_information(
                         "set_information RENAME: file_id={}, from={:?}, to={}",
                         file_id, file_path, info.file_name
                     );
-                    // Get the base path for this device to build the new path
-                    let base_path = match backend.get_base_path(device_id) {
-                        Some(p) => p.clone(),
-                        None => {
-                            warn!("No base path for device {}", device_id);
-                            let res = RdpdrPdu::ClientDriveSetInformationResponse(
-                                ClientDriveSetInformationResponse::new(
-                                    &req_inner,
-                                    NtStatus::UNSUCCESSFUL,
-                                )
-                                .map_err(|e| encode_err!(e))?,
+                    // The target name is relative to the root of the request's drive
+                    let (Some(source_base), Some(target_base)) = (
+                        backend.get_base_path_for_file(file_id),
+                        backend.get_base_path(device_id),
+                    ) else {
+                        warn!("No base path for device {}", device_id);
+                        return set_information_response(&req_inner, NtStatus::UNSUCCESSFUL);
+                    };
+
+                    // Moves between mapped drives (or out of one) can't be a single
+                    // rename; NOT_SAME_DEVICE makes Windows fall back to copy + delete
+                    let same_device =
+                        source_base == target_base && file_path.starts_with(source_base);
+                    let to = match resolve_target(target_base, &info.file_name) {
+                        Some(to) if same_device => to,
+                        _ => {
+                            warn!(
+                                "set_information RENAME across devices: from={:?}, to={} (device {})",
+                                file_path, info.file_name, device_id
                             );
-                            return Ok(vec![SvcMessage::from(res)]);
+                            return set_information_response(&req_inner, NtStatus::from(STATUS_NOT_SAME_DEVICE));
                         }
                     };
 
-                    let new_path = info.file_name.replace('\\', "/");
-                    let new_path = new_path.trim_start_matches('/');
-                    let to = base_path.join(new_path);
-
                     if let Err(error) = fs::rename(file_path, &to) {
                         warn!(
                             "set_information RENAME FAILED: from={:?}, to={:?}, error={:?}",
                             file_path, to, error
                         );
-                        let res = RdpdrPdu::ClientDriveSetInformationResponse(
-                            ClientDriveSetInformationResponse::new(
-                                &req_inner,
-                                NtStatus::UNSUCCESSFUL,
-                            )
-                            .map_err(|e| encode_err!(e))?,
-                        );
-                        return Ok(vec![SvcMessage::from(res)]);
-                    } else {
-                        debug!(
-                            "set_information RENAME SUCCESS: from={:?}, to={:?}",
-                            file_path, to
-                        );
-                        // CRITICAL: Update file_path_map to point to the new path
-                        // Otherwise subsequent queries will fail with "file not found"
-                        backend.file_path_map.insert(file_id, to);
+                        return set_information_response(&req_inner, NtStatus::UNSUCCESSFUL);
                     }
+                    debug!(
+                        "set_information RENAME SUCCESS: from={:?}, to={:?}",
+                        file_path, to
+                    );
+                    // CRITICAL: Update file_path_map to point to the new path, for this
+                    // handle and any open handles inside a renamed directory.
+                    // Otherwise subsequent queries will fail with "file not found"
+                    let from = file_path.clone();
+                    backend.rename_paths(&from, &to);
                 }
-                FileInformationClass::Allocation(_) => {
-                    // nothing to do
+                FileInformationClass::Allocation(info) => {
+                    let size = u64::try_from(info.allocation_size).unwrap_or(0);
+                    debug!("set_information ALLOCATION: file_id={}, size={}", file_id, size);
+                    if let Err(error) = backend.allocate(file_id, size) {
+                        warn!(%error, "Failed to allocate file");
+                        let status = if error.kind() == std::io::ErrorKind::NotFound {
+                            NtStatus::NO_SUCH_FILE
+                        } else {
+                            NtStatus::UNSUCCESSFUL
+                        };
+                        return set_information_response(&req_inner, status);
+                    }
                 }
                 FileInformationClass::Disposition(info) => {
                     // Mark file for deletion on close (per Windows semantics)
                     // Don't delete immediately - that corrupts the state if file is still in use
-                    let should_delete = info.delete_pending != 0;
+                    let flags = if info.delete_pending != 0 {
+                        FILE_DISPOSITION_DELETE
+                    } else {
+                        FILE_DISPOSITION_DO_NOT_DELETE
+                    };
                     debug!(
-                        "set_information DISPOSITION: file_id={}, delete_on_close={}",
-                        file_id, should_delete
+                        "set_information DISPOSITION: file_id={}, flags={:#x}",
+                        file_id, flags
                     );
-                    if should_delete {
-                        backend.delete_on_close.insert(file_id, true);
-                    } else {
-                        // Can unmark if delete_pending is false
-                        backend.delete_on_close.remove(&file_id);
-                    }
+                    backend.set_disposition(file_id, flags);
                 }
                 FileInformationClass::EndOfFile(info) => {
                     if let Some(Some(file)) =
@@ -142,3 +164,21 @@ pub fn set_information(
         ),
     )])
 }
+
+/// Build a set information response with the given status.
+fn set_information_response(
+    req_inner: &ServerDriveSetInformationRequest,
+    status: NtStatus,
+) -> PduResult<Vec<SvcMessage>> {
+    let res = RdpdrPdu::ClientDriveSetInformationResponse(
+        ClientDriveSetInformationResponse::new(req_inner, status).map_err(|e| encode_err!(e))?,
+    );
+    Ok(vec![SvcMessage::from(res)])
+}
+
+/// Resolve a rename target (relative to the drive root) under the drive's base path.
+///
+/// Returns None if `..` components would leave the drive.
+fn resolve_target(base_path: &Path, file_name: &str) -> Option<PathBuf> {
+    drive_relative_path(file_name).map(|relative| join_drive_path(base_path, &relative))
+}
@@ -0,0 +1,293 @@
+//! Delta encoding for WebSocket frames.
+//!
+//! Compares each frame with the last one sent and reports the changed areas as
+//! rectangles of whole tiles, so only those need to be encoded and sent. The
+//! viewer composites them onto its copy of the desktop.
+
+/// Edge length of the square tiles frames are compared in.
+pub const TILE_SIZE: u16 = 64;
+
+/// Send a keyframe instead of a delta when more than this share of the desktop
+/// changed, since one large image compresses better than many tiles.
+const MAX_DIRTY_PERCENT: u64 = 50;
+
+/// A changed area of the desktop, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtyRect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+/// How to send a frame.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FrameUpdate {
+    /// Send the whole desktop.
+    Keyframe,
+    /// Send only these areas.
+    Delta(Vec<DirtyRect>),
+    /// Nothing changed since the last frame; send nothing.
+    Unchanged,
+}
+
+/// Tracks the last frame sent to decide what the next one needs.
+#[derive(Debug)]
+pub struct DeltaEncoder {
+    last: Vec<u8>,
+    width: u16,
+    height: u16,
+    /// Frames sent since the last keyframe.
+    frames_since_keyframe: u32,
+    /// Send a keyframe at least this often, so clients recover from dropped frames.
+    keyframe_interval: u32,
+}
+
+impl DeltaEncoder {
+    /// Create an encoder that sends a keyframe at least every `keyframe_interval` frames.
+    pub fn new(keyframe_interval: u32) -> Self {
+        Self {
+            last: Vec::new(),
+            width: 0,
+            height: 0,
+            frames_since_keyframe: 0,
+            keyframe_interval: keyframe_interval.max(1),
+        }
+    }
+
+    /// Decide how to send a frame and remember it as the last frame sent.
+    ///
+    /// `force_keyframe` is set when a client joined and needs the full desktop.
+    pub fn update(
+        &mut self,
+        width: u16,
+        height: u16,
+        rgba: &[u8],
+        force_keyframe: bool,
+    ) -> FrameUpdate {
+        let resized = width != self.width || height != self.height || self.last.len() != rgba.len();
+        if force_keyframe || resized || self.frames_since_keyframe + 1 >= self.keyframe_interval {
+            return self.keyframe(width, height, rgba);
+        }
+
+        let rects = dirty_rects(&self.last, rgba, width, height);
+        if rects.is_empty() {
+            return FrameUpdate::Unchanged;
+        }
+
+        let dirty_area: u64 = rects.iter().map(|r| r.width as u64 * r.height as u64).sum();
+        let total_area = width as u64 * height as u64;
+        if dirty_area * 100 > total_area * MAX_DIRTY_PERCENT {
+            return self.keyframe(width, height, rgba);
+        }
+
+        self.last.copy_from_slice(rgba);
+        self.frames_since_keyframe += 1;
+        FrameUpdate::Delta(rects)
+    }
+
+    fn keyframe(&mut self, width: u16, height: u16, rgba: &[u8]) -> FrameUpdate {
+        self.last = rgba.to_vec();
+        self.width = width;
+        self.height = height;
+        self.frames_since_keyframe = 0;
+        FrameUpdate::Keyframe
+    }
+}
+
+/// Find the tiles that differ between two frames of the same size.
+///
+/// Adjacent dirty tiles in a row of tiles are merged into one rectangle, which
+/// keeps the number of images per message down for wide changes like text lines.
+pub fn dirty_rects(previous: &[u8], current: &[u8], width: u16, height: u16) -> Vec<DirtyRect> {
+    let (width, height) = (width as usize, height as usize);
+    let tile = TILE_SIZE as usize;
+    let stride = width * 4;
+    let mut rects = Vec::new();
+
+    for tile_y in (0..height).step_by(tile) {
+        let tile_height = tile.min(height - tile_y);
+        let mut run: Option<DirtyRect> = None;
+
+        for tile_x in (0..width).step_by(tile) {
+            let tile_width = tile.min(width - tile_x);
+            let changed = (tile_y..tile_y + tile_height).any(|y| {
+                let start = y * stride + tile_x * 4;
+                let end = start + tile_width * 4;
+                previous[start..end] != current[start..end]
+            });
+
+            match (&mut run, changed) {
+                (Some(rect), true) => rect.width += tile_width as u16,
+                (None, true) => {
+                    run = Some(DirtyRect {
+                        x: tile_x as u16,
+                        y: tile_y as u16,
+                        width: tile_width as u16,
+                        height: tile_height as u16,
+                    })
+                }
+                (Some(_), false) => rects.extend(run.take()),
+                (None, false) => {}
+            }
+        }
+        rects.extend(run);
+    }
+
+    rects
+}
+
+/// Copy the RGBA pixels of a rectangle out of a frame.
+pub fn crop_rgba(rgba: &[u8], width: u16, rect: DirtyRect) -> Vec<u8> {
+    let stride = width as usize * 4;
+    let row_len = rect.width as usize * 4;
+    let mut out = Vec::with_capacity(row_len * rect.height as usize);
+    for y in rect.y as usize..(rect.y + rect.height) as usize {
+        let start = y * stride + rect.x as usize * 4;
+        out.extend_from_slice(&rgba[start..start + row_len]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(width: u16, height: u16) -> Vec<u8> {
+        vec![0u8; width as usize * height as usize * 4]
+    }
+
+    fn set_pixel(rgba: &mut [u8], width: u16, x: usize, y: usize) {
+        let offset = (y * width as usize + x) * 4;
+        rgba[offset..offset + 4].copy_from_slice(&[255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_first_frame_is_keyframe() {
+        let mut encoder = DeltaEncoder::new(100);
+        let rgba = frame(256, 128);
+        assert_eq!(
+            encoder.update(256, 128, &rgba, false),
+            FrameUpdate::Keyframe
+        );
+        assert_eq!(
+            encoder.update(256, 128, &rgba, false),
+            FrameUpdate::Unchanged
+        );
+    }
+
+    #[test]
+    fn test_delta_reports_changed_tiles() {
+        let mut encoder = DeltaEncoder::new(100);
+        let mut rgba = frame(256, 128);
+        encoder.update(256, 128, &rgba, false);
+
+        set_pixel(&mut rgba, 256, 70, 10);
+        set_pixel(&mut rgba, 256, 130, 20);
+        set_pixel(&mut rgba, 256, 10, 100);
+
+        let update = encoder.update(256, 128, &rgba, false);
+        assert_eq!(
+            update,
+            FrameUpdate::Delta(vec![
+                // Adjacent tiles in the first row are merged
+                DirtyRect {
+                    x: 64,
+                    y: 0,
+                    width: 128,
+                    height: 64
+                },
+                DirtyRect {
+                    x: 0,
+                    y: 64,
+                    width: 64,
+                    height: 64
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_edge_tiles_are_clipped() {
+        let previous = frame(100, 70);
+        let mut current = previous.clone();
+        set_pixel(&mut current, 100, 99, 69);
+
+        assert_eq!(
+            dirty_rects(&previous, &current, 100, 70),
+            vec![DirtyRect {
+                x: 64,
+                y: 64,
+                width: 36,
+                height: 6
+            }]
+        );
+    }
+
+    #[test]
+    fn test_keyframe_when_mostly_changed_resized_or_due() {
+        let mut encoder = DeltaEncoder::new(3);
+        let rgba = frame(128, 128);
+        encoder.update(128, 128, &rgba, false);
+
+        // Three of four tiles changed
+        let mut changed = rgba.clone();
+        set_pixel(&mut changed, 128, 0, 0);
+        set_pixel(&mut changed, 128, 100, 0);
+        set_pixel(&mut changed, 128, 0, 100);
+        assert_eq!(
+            encoder.update(128, 128, &changed, false),
+            FrameUpdate::Keyframe
+        );
+
+        // Size change
+        let resized = frame(256, 256);
+        assert_eq!(
+            encoder.update(256, 256, &resized, false),
+            FrameUpdate::Keyframe
+        );
+
+        // Forced by a new client
+        assert_eq!(
+            encoder.update(256, 256, &resized, true),
+            FrameUpdate::Keyframe
+        );
+
+        // Interval reached
+        let mut next = resized.clone();
+        set_pixel(&mut next, 256, 1, 1);
+        assert!(matches!(
+            encoder.update(256, 256, &next, false),
+            FrameUpdate::Delta(_)
+        ));
+        set_pixel(&mut next, 256, 2, 2);
+        assert!(matches!(
+            encoder.update(256, 256, &next, false),
+            FrameUpdate::Delta(_)
+        ));
+        set_pixel(&mut next, 256, 3, 3);
+        assert_eq!(
+            encoder.update(256, 256, &next, false),
+            FrameUpdate::Keyframe
+        );
+    }
+
+    #[test]
+    fn test_crop_rgba() {
+        let mut rgba = frame(4, 4);
+        set_pixel(&mut rgba, 4, 2, 1);
+        let tile = crop_rgba(
+            &rgba,
+            4,
+            DirtyRect {
+                x: 2,
+                y: 1,
+                width: 2,
+                height: 2,
+            },
+        );
+        assert_eq!(tile.len(), 16);
+        assert_eq!(&tile[0..4], &[255, 255, 255, 255]);
+        assert_eq!(&tile[4..8], &[0, 0, 0, 0]);
+    }
+}
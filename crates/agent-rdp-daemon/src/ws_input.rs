@@ -9,6 +9,8 @@ use ironrdp::pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
 use ironrdp::pdu::input::mouse::{MousePdu, PointerFlags};
 use serde::Deserialize;
 
+use crate::rdp_session::unicode_char_events;
+
 /// Mouse input message from WebSocket client.
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type", rename = "input_mouse")]
@@ -183,17 +185,9 @@ pub fn keyboard_to_fastpath(msg: &KeyboardInputPayload) -> Vec<FastPathInputEven
             }
         }
         "char" => {
-            // Send unicode character
+            // Send unicode characters (composed or IME text may hold several)
             if let Some(text) = &msg.text {
-                text.chars()
-                    .flat_map(|ch| {
-                        let code = ch as u16;
-                        vec![
-                            FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::empty(), code),
-                            FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::RELEASE, code),
-                        ]
-                    })
-                    .collect()
+                text.chars().flat_map(unicode_char_events).collect()
             } else {
                 vec![]
             }
@@ -539,6 +533,32 @@ mod tests {
         assert_eq!(events.len(), 6);
     }
 
+    #[test]
+    fn test_keyboard_char_astral_plane() {
+        let msg = KeyboardInputPayload {
+            event_type: "char".to_string(),
+            key: None,
+            code: None,
+            text: Some("𝔸".to_string()),
+        };
+        let events = keyboard_to_fastpath(&msg);
+        // U+1D538 is the surrogate pair D835 DD38, each pressed and released
+        assert_eq!(events.len(), 4);
+        assert!(matches!(
+            events[0],
+            FastPathInputEvent::UnicodeKeyboardEvent(flags, 0xD835) if flags.is_empty()
+        ));
+        assert!(matches!(
+            events[1],
+            FastPathInputEvent::UnicodeKeyboardEvent(flags, 0xD835)
+                if flags.contains(KeyboardFlags::RELEASE)
+        ));
+        assert!(matches!(
+            events[2],
+            FastPathInputEvent::UnicodeKeyboardEvent(flags, 0xDD38) if flags.is_empty()
+        ));
+    }
+
     #[test]
     fn test_code_to_scancode() {
         assert_eq!(code_to_scancode("KeyA"), Some((0x1E, false)));
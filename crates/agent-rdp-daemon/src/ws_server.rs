@@ -4,24 +4,43 @@
 //! debugging and interactive viewing of the remote desktop.
 //!
 //! Also serves the embedded viewer HTML on regular HTTP requests.
+//!
+//! Clients must present the server's token, either as a `token` query parameter
+//! on the WebSocket URL or in an `auth` message sent right after connecting.
 
+use std::borrow::Cow;
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::Arc;
 
+use agent_rdp_protocol::{DisconnectInfo, DisconnectReason};
 use futures_util::{SinkExt, StreamExt};
 use parking_lot::Mutex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tokio::io::AsyncWriteExt;
 use tokio::net::{TcpListener, TcpStream};
+use tokio::time::Duration;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
 use tokio_tungstenite::tungstenite::Message;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::rdp_session::RdpSession;
+use crate::ws_delta::{crop_rgba, DeltaEncoder, DirtyRect, FrameUpdate};
 use crate::ws_input::{keyboard_to_fastpath, mouse_to_fastpath, ClipboardContent, WsInputMessage};
 
 /// Embedded viewer HTML.
 const VIEWER_HTML: &str = include_str!("../../../assets/viewer/viewer.html");
 
+/// Highest frame rate `stream-config` accepts.
+pub const MAX_STREAM_FPS: u32 = 60;
+
+/// In delta mode, send a full keyframe at least this often (in frames).
+const KEYFRAME_INTERVAL: u32 = 100;
+
+/// How long a client without a token in its URL has to send the auth message.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Frame message sent to clients.
 #[derive(Debug, Serialize)]
 struct FrameMessage {
@@ -40,6 +59,25 @@ struct FrameMetadata {
     device_height: u16,
 }
 
+/// Delta frame message: only the tiles that changed since the previous frame.
+#[derive(Debug, Serialize)]
+struct FrameDeltaMessage {
+    #[serde(rename = "type")]
+    msg_type: &'static str,
+    tiles: Vec<FrameTile>,
+    metadata: FrameMetadata,
+}
+
+/// A changed area of the desktop, as a JPEG to draw at (x, y).
+#[derive(Debug, Serialize)]
+struct FrameTile {
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    data: String,
+}
+
 /// Status message sent to clients.
 #[derive(Debug, Serialize)]
 struct StatusMessage {
@@ -60,6 +98,23 @@ struct ClipboardChangedMessage {
     msg_type: &'static str,
 }
 
+/// Session ended notification (server → client).
+#[derive(Debug, Serialize)]
+struct DisconnectedMessage<'a> {
+    #[serde(rename = "type")]
+    msg_type: &'static str,
+    reason: DisconnectReason,
+    message: &'a str,
+}
+
+/// Authentication message (client → server), for clients that can't put the
+/// token in the URL.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename = "auth")]
+struct AuthMessage {
+    token: String,
+}
+
 /// Clipboard data message (server → client).
 #[derive(Debug, Serialize)]
 struct ClipboardDataMessage {
@@ -74,8 +129,12 @@ type ClientId = u64;
 /// WebSocket server for desktop streaming.
 pub struct WsServer {
     port: u16,
+    bind_addr: String,
+    token: String,
     jpeg_quality: u8,
     serve_viewer: bool,
+    delta: bool,
+    max_frame_size: Option<u16>,
     /// Active clients (by ID).
     clients: Arc<Mutex<HashSet<ClientId>>>,
     /// Next client ID.
@@ -85,19 +144,32 @@ pub struct WsServer {
 /// Configuration for the WebSocket server.
 pub struct WsServerConfig {
     pub port: u16,
+    /// Address to listen on (127.0.0.1 keeps the stream local to this machine).
+    pub bind_addr: String,
+    /// Token clients must present (default: a random token per session).
+    pub token: Option<String>,
     pub fps: u32,
     pub jpeg_quality: u8,
     /// Serve the embedded HTML viewer on HTTP requests.
     pub serve_viewer: bool,
+    /// Send only the changed tiles of each frame, with periodic keyframes.
+    pub delta: bool,
+    /// Largest width or height of streamed frames; larger desktops are scaled
+    /// down before encoding (screenshots are unaffected).
+    pub max_frame_size: Option<u16>,
 }
 
 impl Default for WsServerConfig {
     fn default() -> Self {
         Self {
             port: 9224,
+            bind_addr: "127.0.0.1".to_string(),
+            token: None,
             fps: 10,
             jpeg_quality: 80,
             serve_viewer: false,
+            delta: false,
+            max_frame_size: None,
         }
     }
 }
@@ -107,8 +179,14 @@ impl WsServer {
     pub fn new(config: WsServerConfig) -> Self {
         Self {
             port: config.port,
+            bind_addr: config.bind_addr,
+            token: config
+                .token
+                .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string()),
             jpeg_quality: config.jpeg_quality,
             serve_viewer: config.serve_viewer,
+            delta: config.delta,
+            max_frame_size: config.max_frame_size.filter(|size| *size > 0),
             clients: Arc::new(Mutex::new(HashSet::new())),
             next_client_id: Arc::new(Mutex::new(0)),
         }
@@ -121,9 +199,13 @@ impl WsServer {
         &self,
         rdp_session: Arc<tokio::sync::Mutex<Option<RdpSession>>>,
     ) -> anyhow::Result<WsServerHandle> {
-        let addr = format!("0.0.0.0:{}", self.port);
-        let listener = TcpListener::bind(&addr).await?;
-        info!("WebSocket server listening on ws://{}", addr);
+        let listener = TcpListener::bind((self.bind_addr.as_str(), self.port)).await?;
+        // Report the port actually bound, which session info hands to viewers
+        let port = listener.local_addr()?.port();
+        info!(
+            "WebSocket server listening on ws://{}:{}",
+            self.bind_addr, port
+        );
 
         // Create broadcast channel
         let (broadcast_tx, _) = tokio::sync::broadcast::channel::<String>(16);
@@ -132,10 +214,13 @@ impl WsServer {
         // Spawn accept loop
         let clients = Arc::clone(&self.clients);
         let next_client_id = Arc::clone(&self.next_client_id);
-        let jpeg_quality = self.jpeg_quality;
+        // Shared with the handle so quality changes reach new clients too
+        let jpeg_quality = Arc::new(AtomicU8::new(self.jpeg_quality));
+        let accept_quality = Arc::clone(&jpeg_quality);
         let serve_viewer = self.serve_viewer;
+        let max_frame_size = self.max_frame_size;
+        let token = Arc::new(self.token.clone());
 
-        let port = self.port;
         tokio::spawn(async move {
             loop {
                 match listener.accept().await {
@@ -151,8 +236,9 @@ impl WsServer {
                         let clients = Arc::clone(&clients);
                         let rdp_session = Arc::clone(&rdp_session);
                         let broadcast_rx = broadcast_tx.subscribe();
-                        let jpeg_quality = jpeg_quality;
+                        let jpeg_quality = accept_quality.load(Ordering::Relaxed);
                         let serve_viewer = serve_viewer;
+                        let token = Arc::clone(&token);
 
                         tokio::spawn(async move {
                             if let Err(e) = handle_connection(
@@ -162,8 +248,10 @@ impl WsServer {
                                 rdp_session,
                                 broadcast_rx,
                                 jpeg_quality,
+                                max_frame_size,
                                 port,
                                 serve_viewer,
+                                &token,
                             )
                             .await
                             {
@@ -181,7 +269,13 @@ impl WsServer {
         Ok(WsServerHandle {
             broadcast_tx: broadcast_tx_clone,
             clients: Arc::clone(&self.clients),
-            jpeg_quality: self.jpeg_quality,
+            next_client_id: Arc::clone(&self.next_client_id),
+            port,
+            token: self.token.clone(),
+            serve_viewer: self.serve_viewer,
+            jpeg_quality,
+            delta: Mutex::new(self.delta.then(DeltaStream::new)),
+            max_frame_size: self.max_frame_size,
         })
     }
 }
@@ -190,10 +284,76 @@ impl WsServer {
 pub struct WsServerHandle {
     broadcast_tx: tokio::sync::broadcast::Sender<String>,
     clients: Arc<Mutex<HashSet<ClientId>>>,
-    jpeg_quality: u8,
+    next_client_id: Arc<Mutex<ClientId>>,
+    port: u16,
+    token: String,
+    /// Whether the HTML viewer is served alongside WebSocket connections.
+    serve_viewer: bool,
+    /// JPEG quality of broadcast frames, changed live by `stream-config`.
+    jpeg_quality: Arc<AtomicU8>,
+    /// Delta encoding state (None when sending full frames).
+    delta: Mutex<Option<DeltaStream>>,
+    /// Largest width or height of streamed frames.
+    max_frame_size: Option<u16>,
+}
+
+/// Delta encoding state of the broadcast stream.
+struct DeltaStream {
+    encoder: DeltaEncoder,
+    /// Newest client when the last frame was sent; a newer one gets a keyframe.
+    last_client_id: ClientId,
+}
+
+impl DeltaStream {
+    fn new() -> Self {
+        Self {
+            encoder: DeltaEncoder::new(KEYFRAME_INTERVAL),
+            last_client_id: 0,
+        }
+    }
 }
 
 impl WsServerHandle {
+    /// Port the server listens on.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Token clients must present to connect.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Whether the HTML viewer is served on the port.
+    pub fn serves_viewer(&self) -> bool {
+        self.serve_viewer
+    }
+
+    /// JPEG quality of streamed frames (1-100).
+    pub fn jpeg_quality(&self) -> u8 {
+        self.jpeg_quality.load(Ordering::Relaxed)
+    }
+
+    /// Change the JPEG quality, starting with the next frame.
+    pub fn set_jpeg_quality(&self, quality: u8) {
+        self.jpeg_quality.store(quality.clamp(1, 100), Ordering::Relaxed);
+    }
+
+    /// Whether frames are delta-encoded.
+    pub fn delta(&self) -> bool {
+        self.delta.lock().is_some()
+    }
+
+    /// Switch delta encoding on or off.
+    ///
+    /// Turning it on starts a fresh encoder, so the next frame is a keyframe.
+    pub fn set_delta(&self, enabled: bool) {
+        let mut delta = self.delta.lock();
+        if enabled != delta.is_some() {
+            *delta = enabled.then(DeltaStream::new);
+        }
+    }
+
     /// Check if there are any connected clients.
     pub fn has_clients(&self) -> bool {
         !self.clients.lock().is_empty()
@@ -201,39 +361,38 @@ impl WsServerHandle {
 
     /// Broadcast a frame to all connected clients.
     ///
-    /// Takes the raw RGBA image data and converts it to JPEG.
+    /// Takes the raw RGBA image data and converts it to JPEG, scaled down first
+    /// if the desktop is larger than the maximum frame size. In delta mode only
+    /// the changed tiles are sent, unless a keyframe is due.
     pub fn broadcast_frame(&self, width: u16, height: u16, rgba_data: &[u8]) {
         if !self.has_clients() {
             return;
         }
 
-        // Convert RGBA to JPEG
-        let jpeg_data = match encode_jpeg(width, height, rgba_data, self.jpeg_quality) {
-            Ok(data) => data,
-            Err(e) => {
-                error!("Failed to encode JPEG: {}", e);
-                return;
+        let frame = StreamFrame::new(width, height, rgba_data, self.max_frame_size);
+        let update = match &mut *self.delta.lock() {
+            Some(delta) => {
+                let newest_client = *self.next_client_id.lock();
+                let client_joined = newest_client != delta.last_client_id;
+                delta.last_client_id = newest_client;
+                // Tiles are compared and sent at the streamed size
+                delta.encoder.update(frame.width, frame.height, &frame.rgba, client_joined)
             }
+            None => FrameUpdate::Keyframe,
         };
 
-        // Base64 encode
-        let base64_data = base64::Engine::encode(
-            &base64::engine::general_purpose::STANDARD,
-            &jpeg_data,
-        );
-
-        // Create frame message
-        let msg = FrameMessage {
-            msg_type: "frame",
-            data: base64_data,
-            metadata: FrameMetadata {
-                device_width: width,
-                device_height: height,
-            },
+        let jpeg_quality = self.jpeg_quality();
+        let json = match update {
+            FrameUpdate::Keyframe => frame_json(&frame, jpeg_quality),
+            FrameUpdate::Delta(rects) => delta_json(&frame, &rects, jpeg_quality),
+            FrameUpdate::Unchanged => return,
         };
 
-        if let Ok(json) = serde_json::to_string(&msg) {
-            let _ = self.broadcast_tx.send(json);
+        match json {
+            Ok(json) => {
+                let _ = self.broadcast_tx.send(json);
+            }
+            Err(e) => error!("Failed to encode frame: {}", e),
         }
     }
 
@@ -252,6 +411,24 @@ impl WsServerHandle {
             let _ = self.broadcast_tx.send(json);
         }
     }
+
+    /// Tell clients the RDP session ended, and why.
+    pub fn broadcast_disconnected(&self, disconnect: &DisconnectInfo) {
+        if !self.has_clients() {
+            return;
+        }
+
+        let msg = DisconnectedMessage {
+            msg_type: "disconnected",
+            reason: disconnect.reason,
+            message: &disconnect.message,
+        };
+
+        if let Ok(json) = serde_json::to_string(&msg) {
+            debug!("Broadcasting disconnected to clients");
+            let _ = self.broadcast_tx.send(json);
+        }
+    }
 }
 
 /// Handle an incoming connection - either HTTP or WebSocket.
@@ -262,8 +439,10 @@ async fn handle_connection(
     rdp_session: Arc<tokio::sync::Mutex<Option<RdpSession>>>,
     broadcast_rx: tokio::sync::broadcast::Receiver<String>,
     jpeg_quality: u8,
+    max_frame_size: Option<u16>,
     ws_port: u16,
     serve_viewer: bool,
+    token: &str,
 ) -> anyhow::Result<()> {
     // Peek at the request headers without consuming them
     let mut peek_buf = [0u8; 2048];
@@ -272,14 +451,24 @@ async fn handle_connection(
 
     // Check if this is a WebSocket upgrade request
     let is_websocket = request_preview.to_lowercase().contains("upgrade: websocket");
+    let url_authorized = query_token(&request_preview).is_some_and(|t| tokens_match(&t, token));
 
     if is_websocket {
         // Handle as WebSocket
         let ws_stream = tokio_tungstenite::accept_async(stream).await?;
-        handle_websocket_client(ws_stream, client_id, clients, rdp_session, broadcast_rx, jpeg_quality).await
+        let config = ClientConfig {
+            jpeg_quality,
+            max_frame_size,
+            url_authorized,
+            token,
+        };
+        handle_websocket_client(ws_stream, client_id, clients, rdp_session, broadcast_rx, config)
+            .await
     } else if serve_viewer {
-        // Serve the viewer HTML (consume the request first)
-        serve_viewer_html(stream, ws_port).await
+        // Serve the viewer HTML (consume the request first); the token is only
+        // filled in for pages opened with it
+        let page_token = url_authorized.then_some(token);
+        serve_viewer_html(stream, ws_port, page_token).await
     } else {
         // Return 404 - viewer not enabled
         serve_not_found(stream).await
@@ -300,18 +489,51 @@ async fn serve_not_found(mut stream: TcpStream) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Extract the percent-decoded `token` query parameter from the first line of an HTTP request.
+fn query_token(request: &str) -> Option<String> {
+    let target = request.lines().next()?.split_whitespace().nth(1)?;
+    let (_, query) = target.split_once('?')?;
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(name, _)| name == "token")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Compare tokens without stopping at the first mismatch, so response timing
+/// doesn't reveal how much of a guess was right.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
 /// Serve the embedded viewer HTML.
-async fn serve_viewer_html(mut stream: TcpStream, ws_port: u16) -> anyhow::Result<()> {
+async fn serve_viewer_html(
+    mut stream: TcpStream,
+    ws_port: u16,
+    token: Option<&str>,
+) -> anyhow::Result<()> {
     use tokio::io::AsyncReadExt;
 
     // Consume the HTTP request (we already peeked at it)
     let mut buf = [0u8; 4096];
     let _ = stream.read(&mut buf).await;
 
-    // Inject the WebSocket URL into the HTML
+    // Inject the WebSocket URL into the HTML, with the token percent-encoded
+    let ws_url = match token {
+        Some(token) => {
+            let query: String = url::form_urlencoded::Serializer::new(String::new())
+                .append_pair("token", token)
+                .finish();
+            format!("ws://localhost:{}/?{}", ws_port, query)
+        }
+        None => format!("ws://localhost:{}", ws_port),
+    };
     let html = VIEWER_HTML.replace(
         "value=\"ws://localhost:9224\"",
-        &format!("value=\"ws://localhost:{}\"", ws_port),
+        &format!("value=\"{}\"", escape_html_attribute(&ws_url)),
     );
 
     let response = format!(
@@ -324,6 +546,24 @@ async fn serve_viewer_html(mut stream: TcpStream, ws_port: u16) -> anyhow::Resul
     Ok(())
 }
 
+/// Escape a value for use inside a double-quoted HTML attribute.
+fn escape_html_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Stream settings and authorization for one WebSocket client.
+struct ClientConfig<'a> {
+    jpeg_quality: u8,
+    max_frame_size: Option<u16>,
+    /// Whether the upgrade request already carried a valid token in its URL.
+    url_authorized: bool,
+    token: &'a str,
+}
+
 /// Handle a single WebSocket client connection.
 async fn handle_websocket_client<S>(
     ws_stream: S,
@@ -331,7 +571,7 @@ async fn handle_websocket_client<S>(
     clients: Arc<Mutex<HashSet<ClientId>>>,
     rdp_session: Arc<tokio::sync::Mutex<Option<RdpSession>>>,
     mut broadcast_rx: tokio::sync::broadcast::Receiver<String>,
-    jpeg_quality: u8,
+    config: ClientConfig<'_>,
 ) -> anyhow::Result<()>
 where
     S: futures_util::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>>
@@ -340,6 +580,25 @@ where
 {
     let (mut ws_sink, mut ws_stream) = ws_stream.split();
 
+    // Without a valid token in the URL, the first message must carry it
+    if !config.url_authorized {
+        let first = tokio::time::timeout(AUTH_TIMEOUT, ws_stream.next()).await;
+        let authorized = match first {
+            Ok(Some(Ok(Message::Text(text)))) => serde_json::from_str::<AuthMessage>(&text)
+                .is_ok_and(|auth| tokens_match(&auth.token, config.token)),
+            _ => false,
+        };
+        if !authorized {
+            warn!("Client {} rejected: missing or invalid token", client_id);
+            let close = CloseFrame {
+                code: CloseCode::Policy,
+                reason: "Unauthorized".into(),
+            };
+            let _ = ws_sink.send(Message::Close(Some(close))).await;
+            return Ok(());
+        }
+    }
+
     // Register client
     {
         clients.lock().insert(client_id);
@@ -373,22 +632,9 @@ where
         let session = rdp_session.lock().await;
         if let Some(ref rdp) = *session {
             let (width, height, data) = rdp.get_image_data();
-            if let Ok(jpeg_data) = encode_jpeg(width, height, &data, jpeg_quality) {
-                let base64_data = base64::Engine::encode(
-                    &base64::engine::general_purpose::STANDARD,
-                    &jpeg_data,
-                );
-                let msg = FrameMessage {
-                    msg_type: "frame",
-                    data: base64_data,
-                    metadata: FrameMetadata {
-                        device_width: width,
-                        device_height: height,
-                    },
-                };
-                if let Ok(json) = serde_json::to_string(&msg) {
-                    let _ = ws_sink.send(Message::Text(json.into())).await;
-                }
+            let frame = StreamFrame::new(width, height, &data, config.max_frame_size);
+            if let Ok(json) = frame_json(&frame, config.jpeg_quality) {
+                let _ = ws_sink.send(Message::Text(json)).await;
             }
         }
     }
@@ -518,20 +764,132 @@ async fn handle_client_message<S>(
     }
 }
 
+/// A desktop frame as streamed, scaled down if the desktop exceeds the maximum size.
+struct StreamFrame<'a> {
+    /// Desktop size, which clients map input coordinates to.
+    device_width: u16,
+    device_height: u16,
+    /// Size of the streamed image.
+    width: u16,
+    height: u16,
+    rgba: Cow<'a, [u8]>,
+}
+
+impl<'a> StreamFrame<'a> {
+    fn new(width: u16, height: u16, rgba: &'a [u8], max_size: Option<u16>) -> Self {
+        let mut frame = Self {
+            device_width: width,
+            device_height: height,
+            width,
+            height,
+            rgba: Cow::Borrowed(rgba),
+        };
+        if let Some((scaled_width, scaled_height)) =
+            max_size.and_then(|max_size| scaled_size(width, height, max_size))
+        {
+            if let Some(scaled) = downscale_rgba(width, height, rgba, scaled_width, scaled_height) {
+                frame.width = scaled_width;
+                frame.height = scaled_height;
+                frame.rgba = Cow::Owned(scaled);
+            }
+        }
+        frame
+    }
+
+    fn metadata(&self) -> FrameMetadata {
+        FrameMetadata {
+            device_width: self.device_width,
+            device_height: self.device_height,
+        }
+    }
+}
+
+/// Size of a frame scaled to fit within `max_size` on both sides, keeping its
+/// aspect ratio, or None if it already fits.
+fn scaled_size(width: u16, height: u16, max_size: u16) -> Option<(u16, u16)> {
+    let longest = width.max(height);
+    if longest <= max_size {
+        return None;
+    }
+    let scale = |side: u16| {
+        let scaled = (side as u32 * max_size as u32 + longest as u32 / 2) / longest as u32;
+        scaled.max(1) as u16
+    };
+    Some((scale(width), scale(height)))
+}
+
+/// Resize RGBA image data, reading it in place.
+fn downscale_rgba(
+    width: u16,
+    height: u16,
+    rgba_data: &[u8],
+    scaled_width: u16,
+    scaled_height: u16,
+) -> Option<Vec<u8>> {
+    use image::imageops::{self, FilterType};
+    use image::{ImageBuffer, Rgba};
+
+    let img: ImageBuffer<Rgba<u8>, &[u8]> =
+        ImageBuffer::from_raw(width as u32, height as u32, rgba_data)?;
+    let scaled = imageops::resize(
+        &img,
+        scaled_width as u32,
+        scaled_height as u32,
+        FilterType::Triangle,
+    );
+    Some(scaled.into_raw())
+}
+
+/// Build a full frame message.
+fn frame_json(frame: &StreamFrame, quality: u8) -> anyhow::Result<String> {
+    let jpeg_data = encode_jpeg(frame.width, frame.height, &frame.rgba, quality)?;
+    let msg = FrameMessage {
+        msg_type: "frame",
+        data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &jpeg_data),
+        metadata: frame.metadata(),
+    };
+    Ok(serde_json::to_string(&msg)?)
+}
+
+/// Build a delta frame message with one JPEG per changed rectangle.
+///
+/// Rectangles are in streamed-frame pixels, which the viewer's canvas matches.
+fn delta_json(frame: &StreamFrame, rects: &[DirtyRect], quality: u8) -> anyhow::Result<String> {
+    let tiles = rects
+        .iter()
+        .map(|rect| {
+            let tile = crop_rgba(&frame.rgba, frame.width, *rect);
+            let jpeg_data = encode_jpeg(rect.width, rect.height, &tile, quality)?;
+            Ok(FrameTile {
+                x: rect.x,
+                y: rect.y,
+                width: rect.width,
+                height: rect.height,
+                data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &jpeg_data),
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let msg = FrameDeltaMessage {
+        msg_type: "frame_delta",
+        tiles,
+        metadata: frame.metadata(),
+    };
+    Ok(serde_json::to_string(&msg)?)
+}
+
 /// Encode RGBA image data to JPEG.
 fn encode_jpeg(width: u16, height: u16, rgba_data: &[u8], quality: u8) -> anyhow::Result<Vec<u8>> {
-    use image::{ImageBuffer, Rgba};
+    use image::buffer::ConvertBuffer;
+    use image::{ImageBuffer, Rgb, Rgba};
 
-    // Create image buffer from RGBA data
-    let img: ImageBuffer<Rgba<u8>, _> = ImageBuffer::from_raw(
-        width as u32,
-        height as u32,
-        rgba_data.to_vec(),
-    )
-    .ok_or_else(|| anyhow::anyhow!("Failed to create image buffer"))?;
+    // View the RGBA data in place rather than copying it into a buffer
+    let img: ImageBuffer<Rgba<u8>, &[u8]> =
+        ImageBuffer::from_raw(width as u32, height as u32, rgba_data)
+            .ok_or_else(|| anyhow::anyhow!("Failed to create image buffer"))?;
 
     // Convert to RGB (JPEG doesn't support alpha)
-    let rgb_img = image::DynamicImage::ImageRgba8(img).into_rgb8();
+    let rgb_img: ImageBuffer<Rgb<u8>, Vec<u8>> = img.convert();
 
     // Encode to JPEG
     let mut jpeg_data = Vec::new();
@@ -549,6 +907,18 @@ pub fn get_stream_port() -> u16 {
         .unwrap_or(0)
 }
 
+/// Get the stream bind address from environment or default (127.0.0.1).
+pub fn get_stream_bind() -> String {
+    std::env::var("AGENT_RDP_STREAM_BIND").unwrap_or_else(|_| "127.0.0.1".to_string())
+}
+
+/// Get the stream token from environment, if one is set.
+pub fn get_stream_token() -> Option<String> {
+    std::env::var("AGENT_RDP_STREAM_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+}
+
 /// Get the stream FPS from environment or default.
 pub fn get_stream_fps() -> u32 {
     std::env::var("AGENT_RDP_STREAM_FPS")
@@ -557,6 +927,13 @@ pub fn get_stream_fps() -> u32 {
         .unwrap_or(10)
 }
 
+/// Whether delta-encoded streaming is enabled in the environment.
+pub fn get_stream_delta() -> bool {
+    std::env::var("AGENT_RDP_STREAM_DELTA")
+        .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
 /// Get the stream JPEG quality from environment or default.
 pub fn get_stream_quality() -> u8 {
     std::env::var("AGENT_RDP_STREAM_QUALITY")
@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+use crate::request::ImageFormat;
+
 /// Automation request sent from CLI to daemon.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
@@ -26,6 +28,9 @@ pub enum AutomateRequest {
         /// Start from the currently focused element.
         #[serde(default)]
         focused: bool,
+        /// Shape of the result: the full tree, or a YAML or text rendering of it.
+        #[serde(default)]
+        format: SnapshotFormat,
     },
 
     /// Get element properties.
@@ -51,6 +56,48 @@ pub enum AutomateRequest {
         /// Use double-click instead of single click.
         #[serde(default)]
         double_click: bool,
+        /// Mouse button: "left", "right" or "middle" (default: left).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        button: Option<String>,
+    },
+
+    /// Invoke an element (InvokePattern) without moving the mouse - for
+    /// off-screen or zero-size menu items and buttons that can't be clicked.
+    Invoke {
+        /// Element selector.
+        selector: String,
+    },
+
+    /// Drag an element onto another with the mouse - for reordering list items,
+    /// moving tree items. Runs as an RDP mouse drag between the element centers.
+    DragTo {
+        /// Element selector of the element to drag.
+        selector: String,
+        /// Element selector of the drop target.
+        target: String,
+    },
+
+    /// Capture an image of just one element, cropped from the desktop to its
+    /// bounds (clamped to the desktop when it is partly off screen).
+    Screenshot {
+        /// Element selector.
+        selector: String,
+        /// Image format (default: png).
+        #[serde(default)]
+        format: ImageFormat,
+    },
+
+    /// Draw a colored box around an element on the remote desktop for a while,
+    /// so a screenshot shows which element the selector matched. The box
+    /// never takes focus or clicks.
+    Highlight {
+        /// Element selector.
+        selector: String,
+        /// How long the box stays up, in milliseconds (default: 3000, max: 60000).
+        #[serde(default = "default_highlight_duration")]
+        #[ts(type = "number")]
+        duration_ms: u64,
     },
 
     /// Select an element (SelectionItemPattern) - for list items, radio buttons.
@@ -100,6 +147,20 @@ pub enum AutomateRequest {
         text: String,
     },
 
+    /// Type text into an element, appending to what it holds unless `clear` is set.
+    ///
+    /// Uses ValuePattern when the element supports it, otherwise focuses the
+    /// element and sends keystrokes.
+    Type {
+        /// Element selector.
+        selector: String,
+        /// Text to type.
+        text: String,
+        /// Clear the element first.
+        #[serde(default)]
+        clear: bool,
+    },
+
     /// Clear text from an element.
     Clear {
         /// Element selector.
@@ -153,6 +214,19 @@ pub enum AutomateRequest {
         timeout_ms: u64,
     },
 
+    /// Run a multi-line PowerShell script and wait for it, capturing its output.
+    ///
+    /// The script runs in a separate `powershell.exe` in the user's profile
+    /// directory, with the execution policy bypassed for that process only.
+    ExecScript {
+        /// Script source.
+        script: String,
+        /// Timeout in milliseconds; the script is killed when it runs out.
+        #[serde(default = "default_run_timeout")]
+        #[ts(type = "number")]
+        timeout_ms: u64,
+    },
+
     /// Wait for an element to reach a state.
     WaitFor {
         /// Element selector.
@@ -164,6 +238,10 @@ pub enum AutomateRequest {
         /// State to wait for.
         #[serde(default)]
         state: WaitState,
+        /// Time between checks in milliseconds.
+        #[serde(default = "default_wait_poll_interval")]
+        #[ts(type = "number")]
+        poll_interval_ms: u64,
     },
 
     /// Get automation agent status.
@@ -175,13 +253,24 @@ fn default_max_depth() -> u32 {
 }
 
 fn default_wait_timeout() -> u64 {
-    30000
+    10000
+}
+
+fn default_wait_poll_interval() -> u64 {
+    250
 }
 
 fn default_run_timeout() -> u64 {
     10000
 }
 
+fn default_highlight_duration() -> u64 {
+    3000
+}
+
+/// Longest an element highlight can stay up.
+pub const MAX_HIGHLIGHT_DURATION_MS: u64 = 60_000;
+
 /// Scroll direction for automation.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
@@ -200,8 +289,11 @@ pub enum AutomationScrollDirection {
 pub enum WindowAction {
     /// List all windows.
     List,
-    /// Focus a window.
+    /// Restore a window if minimized and make it the foreground window with
+    /// keyboard focus, failing if Windows refuses.
     Focus,
+    /// Bring a window to the top of the z-order without taking keyboard focus.
+    Raise,
     /// Maximize a window.
     Maximize,
     /// Minimize a window.
@@ -224,6 +316,22 @@ pub enum WaitState {
     Enabled,
     /// Element is gone (no longer exists).
     Gone,
+    /// Element exists, whether or not it's on screen.
+    Exists,
+}
+
+/// How a snapshot is returned.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotFormat {
+    /// The full nested tree.
+    #[default]
+    Json,
+    /// A YAML rendering with only the fields each element has.
+    Yaml,
+    /// An indented outline, one `- role "name" [ref=eN, ...]` line per element.
+    Text,
 }
 
 /// Accessibility tree snapshot.
@@ -244,14 +352,197 @@ pub struct AccessibilitySnapshot {
     pub root: AccessibilityElement,
 }
 
-/// An element in the accessibility tree.
+/// Accessibility tree snapshot rendered as YAML or text.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct FormattedSnapshot {
+    /// Unique snapshot ID.
+    pub snapshot_id: String,
+    /// Total number of elements with refs.
+    pub ref_count: u32,
+    /// Whether the tree was truncated due to depth limit.
+    pub truncated: bool,
+    /// Maximum depth used for this snapshot.
+    pub max_depth: u32,
+    /// Format of `text`.
+    pub format: SnapshotFormat,
+    /// The rendered tree.
+    pub text: String,
+}
+
+impl AccessibilitySnapshot {
+    /// Render the tree in the given format, or `None` for `Json`.
+    pub fn format(&self, format: SnapshotFormat) -> Option<FormattedSnapshot> {
+        let text = match format {
+            SnapshotFormat::Json => return None,
+            SnapshotFormat::Yaml => self.root.to_yaml(),
+            SnapshotFormat::Text => self.root.to_outline(),
+        };
+        Some(FormattedSnapshot {
+            snapshot_id: self.snapshot_id.clone(),
+            ref_count: self.ref_count,
+            truncated: self.truncated,
+            max_depth: self.max_depth,
+            format,
+            text,
+        })
+    }
+}
+
+/// Patterns that make an element interactive, along with being focusable.
+const INTERACTIVE_PATTERNS: &[&str] = &[
+    "invoke",
+    "value",
+    "toggle",
+    "selectionitem",
+    "expandcollapse",
+    "rangevalue",
+    "scroll",
+];
+
+impl AccessibilityElement {
+    /// Whether the element can be interacted with but isn't enabled.
+    pub fn is_disabled(&self) -> bool {
+        let has_state = |state: &str| self.states.iter().any(|s| s == state);
+        let is_interactive = has_state("focusable")
+            || self.patterns.iter().any(|p| INTERACTIVE_PATTERNS.contains(&p.as_str()));
+        is_interactive && !has_state("enabled")
+    }
+
+    /// Indented outline of this element and its descendants, one line each:
+    /// `- role "name" [ref=eN, handle=..., id=..., class=..., value="...", disabled]`.
+    pub fn to_outline(&self) -> String {
+        let mut out = String::new();
+        self.write_outline(&mut out, 0);
+        out
+    }
+
+    fn write_outline(&self, out: &mut String, depth: usize) {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str("- ");
+        out.push_str(&self.role);
+        if let Some(name) = self.name.as_deref().filter(|name| !name.is_empty()) {
+            out.push_str(&format!(" \"{}\"", name));
+        }
+
+        let mut attrs = Vec::new();
+        if let Some(r) = self.r#ref {
+            attrs.push(format!("ref=e{}", r));
+        }
+        if let Some(ref stable_id) = self.stable_id {
+            attrs.push(format!("handle={}", stable_id));
+        }
+        if let Some(id) = self.automation_id.as_deref().filter(|id| !id.is_empty()) {
+            attrs.push(format!("id={}", id));
+        }
+        if let Some(class) = self.class_name.as_deref().filter(|class| !class.is_empty()) {
+            attrs.push(format!("class={}", class));
+        }
+        if let Some(value) = self.value.as_deref().filter(|value| !value.is_empty()) {
+            attrs.push(format!("value=\"{}\"", value));
+        }
+        if self.is_disabled() {
+            attrs.push("disabled".to_string());
+        }
+        if !attrs.is_empty() {
+            out.push_str(&format!(" [{}]", attrs.join(", ")));
+        }
+        out.push('\n');
+
+        for child in &self.children {
+            child.write_outline(out, depth + 1);
+        }
+    }
+
+    /// YAML rendering of this element and its descendants as a one-item list,
+    /// leaving out empty fields.
+    pub fn to_yaml(&self) -> String {
+        let mut out = String::new();
+        self.write_yaml(&mut out, 0);
+        out
+    }
+
+    fn write_yaml(&self, out: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth);
+        let mut fields: Vec<(&str, String)> = vec![("role", yaml_scalar(&self.role))];
+        if let Some(name) = self.name.as_deref().filter(|name| !name.is_empty()) {
+            fields.push(("name", yaml_scalar(name)));
+        }
+        if let Some(r) = self.r#ref {
+            fields.push(("ref", format!("e{}", r)));
+        }
+        if let Some(ref stable_id) = self.stable_id {
+            fields.push(("handle", yaml_scalar(stable_id)));
+        }
+        if let Some(id) = self.automation_id.as_deref().filter(|id| !id.is_empty()) {
+            fields.push(("id", yaml_scalar(id)));
+        }
+        if let Some(class) = self.class_name.as_deref().filter(|class| !class.is_empty()) {
+            fields.push(("class", yaml_scalar(class)));
+        }
+        if let Some(value) = self.value.as_deref().filter(|value| !value.is_empty()) {
+            fields.push(("value", yaml_scalar(value)));
+        }
+        if let Some(ref b) = self.bounds {
+            fields.push(("bounds", format!("[{}, {}, {}, {}]", b.x, b.y, b.width, b.height)));
+        }
+        if !self.states.is_empty() {
+            fields.push(("states", yaml_list(&self.states)));
+        }
+        if !self.patterns.is_empty() {
+            fields.push(("patterns", yaml_list(&self.patterns)));
+        }
+
+        for (i, (key, value)) in fields.iter().enumerate() {
+            let lead = if i == 0 { "- " } else { "  " };
+            out.push_str(&format!("{}{}{}: {}\n", indent, lead, key, value));
+        }
+        if !self.children.is_empty() {
+            out.push_str(&format!("{}  children:\n", indent));
+            for child in &self.children {
+                child.write_yaml(out, depth + 1);
+            }
+        }
+    }
+}
+
+/// A string as a YAML scalar: plain when that reads back as the same string,
+/// otherwise double-quoted (JSON string syntax is valid YAML).
+fn yaml_scalar(s: &str) -> String {
+    let is_plain = !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        && !s.starts_with(|c: char| c == '-' || c == '.' || c.is_ascii_digit())
+        && !matches!(
+            s.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "y" | "n"
+        );
+    if is_plain {
+        s.to_string()
+    } else {
+        serde_json::to_string(s).unwrap_or_default()
+    }
+}
+
+/// A list of strings as a YAML flow sequence.
+fn yaml_list(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|item| yaml_scalar(item)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// An element in the accessibility tree.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
 pub struct AccessibilityElement {
     /// Reference number (for @ref selectors).
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub r#ref: Option<u32>,
+    /// Handle that stays valid across snapshots (for @sXXXXXXXXXX selectors),
+    /// derived from the element's role, automation ID, name and path from the desktop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub stable_id: Option<String>,
     /// Element role (control type).
     pub role: String,
     /// Element name.
@@ -295,6 +586,21 @@ pub struct ElementBounds {
     pub height: i32,
 }
 
+/// Image of one element.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct ElementScreenshot {
+    /// Desktop area captured: the element's bounds, clamped to the desktop.
+    pub bounds: ElementBounds,
+    /// Image format.
+    pub format: String,
+    /// Encoded image size in bytes.
+    #[ts(type = "number")]
+    pub size_bytes: u64,
+    /// Base64-encoded image data.
+    pub base64: String,
+}
+
 /// Element value response.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
@@ -359,6 +665,16 @@ pub struct AutomationStatus {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub version: Option<String>,
+    /// Times the agent was restarted after dying or hanging in this session.
+    #[serde(default)]
+    pub restarts: u32,
+    /// Whether input goes to a secure desktop (a UAC prompt, the lock or
+    /// logon screen), which ignores synthesized input.
+    #[serde(default)]
+    pub secure_desktop: bool,
+    /// Whether Scroll Lock is on in the remote session.
+    #[serde(default)]
+    pub scroll_lock: bool,
 }
 
 /// Command run result.
@@ -381,6 +697,9 @@ pub struct RunResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub pid: Option<u32>,
+    /// Whether stdout or stderr was cut off at the agent's output limit.
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 /// Click action result.
@@ -401,6 +720,64 @@ pub struct ClickResult {
     pub y: Option<i32>,
 }
 
+/// Drag action result.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct DragResult {
+    /// Whether the drag was performed.
+    pub dragged: bool,
+    /// X coordinate the drag started at (center of the source element).
+    pub from_x: i32,
+    /// Y coordinate the drag started at.
+    pub from_y: i32,
+    /// X coordinate the element was dropped at (center of the target element).
+    pub to_x: i32,
+    /// Y coordinate the element was dropped at.
+    pub to_y: i32,
+}
+
+/// Highlight action result.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct HighlightResult {
+    /// Screen area the box was drawn around.
+    pub bounds: ElementBounds,
+    /// How long the box stays up, in milliseconds.
+    #[ts(type = "number")]
+    pub duration_ms: u64,
+}
+
+/// Type action result.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct TypeResult {
+    /// Whether the text was entered.
+    pub typed: bool,
+    /// Method used (value_pattern or sendkeys).
+    pub method: String,
+    /// The element's value after typing, if it exposes one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub value: Option<String>,
+}
+
+/// Result of waiting for an element.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct WaitForResult {
+    /// State that was reached.
+    pub state: WaitState,
+    /// Time spent waiting in milliseconds.
+    #[ts(type = "number")]
+    pub elapsed_ms: u64,
+    /// Whether the element exists at the end of the wait.
+    pub exists: bool,
+    /// Whether the element is on screen at the end of the wait.
+    pub visible: bool,
+    /// Whether the element is enabled at the end of the wait.
+    pub enabled: bool,
+}
+
 /// Handshake data from PowerShell agent.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
@@ -474,6 +851,7 @@ mod tests {
             max_depth: 10,
             selector: None,
             focused: false,
+            format: SnapshotFormat::Json,
         };
 
         let json = serde_json::to_string(&req).unwrap();
@@ -489,16 +867,145 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_exec_script_request() {
+        let json = r#"{"op":"exec_script","script":"$x = 1\n$x + 1"}"#;
+        match serde_json::from_str::<AutomateRequest>(json).unwrap() {
+            AutomateRequest::ExecScript { script, timeout_ms } => {
+                assert_eq!(script, "$x = 1\n$x + 1");
+                assert_eq!(timeout_ms, 10000);
+            }
+            _ => panic!("unexpected request type"),
+        }
+
+        // Agents that predate output capping don't send `truncated`
+        let result: RunResult =
+            serde_json::from_str(r#"{"exit_code":0,"stdout":"2","stderr":""}"#).unwrap();
+        assert!(!result.truncated);
+    }
+
     #[test]
     fn test_click_request_serialization() {
         let req = AutomateRequest::Click {
             selector: "@5".to_string(),
             double_click: false,
+            button: None,
         };
 
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"op\":\"click\""));
         assert!(json.contains("\"selector\":\"@5\""));
+        assert!(!json.contains("button"));
+    }
+
+    #[test]
+    fn test_click_button_roundtrip() {
+        let req = AutomateRequest::Click {
+            selector: "@5".to_string(),
+            double_click: false,
+            button: Some("right".to_string()),
+        };
+
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"button\":\"right\""));
+        match serde_json::from_str(&json).unwrap() {
+            AutomateRequest::Click { button, .. } => assert_eq!(button.as_deref(), Some("right")),
+            other => panic!("Expected Click, got {:?}", other),
+        }
+
+        // Requests from clients that predate the field click with the left button
+        let parsed: AutomateRequest =
+            serde_json::from_str(r#"{"op":"click","selector":"@5"}"#).unwrap();
+        assert!(matches!(parsed, AutomateRequest::Click { button: None, .. }));
+    }
+
+    #[test]
+    fn test_invoke_request_serialization() {
+        let req = AutomateRequest::Invoke {
+            selector: "#SaveButton".to_string(),
+        };
+
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"op\":\"invoke\""));
+        assert!(json.contains("\"selector\":\"#SaveButton\""));
+    }
+
+    #[test]
+    fn test_drag_to_request_serialization() {
+        let req = AutomateRequest::DragTo {
+            selector: "@5".to_string(),
+            target: "#Trash".to_string(),
+        };
+
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"op\":\"drag_to\""));
+        assert!(json.contains("\"target\":\"#Trash\""));
+    }
+
+    #[test]
+    fn test_highlight_request_serialization() {
+        let req: AutomateRequest =
+            serde_json::from_str(r#"{"op":"highlight","selector":"@5"}"#).unwrap();
+        match req {
+            AutomateRequest::Highlight { selector, duration_ms } => {
+                assert_eq!(selector, "@5");
+                assert_eq!(duration_ms, 3000);
+            }
+            _ => panic!("Expected Highlight"),
+        }
+
+        let result = HighlightResult {
+            bounds: ElementBounds { x: 10, y: 20, width: 80, height: 30 },
+            duration_ms: 3000,
+        };
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains("\"bounds\":{\"x\":10,\"y\":20,\"width\":80,\"height\":30}"));
+        assert!(json.contains("\"duration_ms\":3000"));
+    }
+
+    #[test]
+    fn test_screenshot_request_serialization() {
+        let req: AutomateRequest =
+            serde_json::from_str(r##"{"op":"screenshot","selector":"#SaveButton"}"##).unwrap();
+        match req {
+            AutomateRequest::Screenshot { selector, format } => {
+                assert_eq!(selector, "#SaveButton");
+                assert_eq!(format, ImageFormat::Png);
+            }
+            _ => panic!("Expected Screenshot"),
+        }
+    }
+
+    #[test]
+    fn test_type_request_serialization() {
+        let req: AutomateRequest =
+            serde_json::from_str(r#"{"op":"type","selector":"@5","text":"abc"}"#).unwrap();
+        match req {
+            AutomateRequest::Type { text, clear, .. } => {
+                assert_eq!(text, "abc");
+                assert!(!clear);
+            }
+            _ => panic!("Expected type request"),
+        }
+    }
+
+    #[test]
+    fn test_wait_for_request_serialization() {
+        let req: AutomateRequest =
+            serde_json::from_str(r#"{"op":"wait_for","selector":"@5","state":"exists"}"#).unwrap();
+        match req {
+            AutomateRequest::WaitFor {
+                timeout_ms,
+                state,
+                poll_interval_ms,
+                ..
+            } => {
+                assert_eq!(timeout_ms, 10000);
+                assert_eq!(state, WaitState::Exists);
+                assert_eq!(poll_interval_ms, 250);
+            }
+            _ => panic!("Expected wait_for request"),
+        }
     }
 
     #[test]
@@ -523,12 +1030,16 @@ mod tests {
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"op\":\"window\""));
         assert!(json.contains("\"maximize\""));
+
+        let raise: WindowAction = serde_json::from_str("\"raise\"").unwrap();
+        assert_eq!(raise, WindowAction::Raise);
     }
 
     #[test]
     fn test_accessibility_element_serialization() {
         let elem = AccessibilityElement {
             r#ref: Some(1),
+            stable_id: Some("s0a1b2c3d4e".to_string()),
             role: "button".to_string(),
             name: Some("OK".to_string()),
             automation_id: Some("btnOK".to_string()),
@@ -548,5 +1059,56 @@ mod tests {
         let json = serde_json::to_string(&elem).unwrap();
         assert!(json.contains("\"role\":\"button\""));
         assert!(json.contains("\"ref\":1"));
+        assert!(json.contains("\"stable_id\":\"s0a1b2c3d4e\""));
+    }
+
+    #[test]
+    fn test_snapshot_formats() {
+        let button = AccessibilityElement {
+            r#ref: Some(2),
+            stable_id: None,
+            role: "Button".to_string(),
+            name: Some("Save: now".to_string()),
+            automation_id: Some("btnSave".to_string()),
+            class_name: None,
+            bounds: Some(ElementBounds { x: 10, y: 20, width: 80, height: 30 }),
+            states: vec!["focusable".to_string()],
+            value: None,
+            patterns: vec!["invoke".to_string()],
+            children: vec![],
+        };
+        let snapshot = AccessibilitySnapshot {
+            snapshot_id: "abc".to_string(),
+            ref_count: 2,
+            truncated: false,
+            max_depth: 10,
+            root: AccessibilityElement {
+                r#ref: Some(1),
+                role: "Window".to_string(),
+                name: Some("Notepad".to_string()),
+                children: vec![button],
+                ..AccessibilityElement::default()
+            },
+        };
+
+        assert!(snapshot.format(SnapshotFormat::Json).is_none());
+
+        let text = snapshot.format(SnapshotFormat::Text).unwrap();
+        assert_eq!(text.ref_count, 2);
+        assert_eq!(
+            text.text,
+            "- Window \"Notepad\" [ref=e1]\n  - Button \"Save: now\" [ref=e2, id=btnSave, disabled]\n"
+        );
+
+        let yaml = snapshot.format(SnapshotFormat::Yaml).unwrap();
+        assert_eq!(
+            yaml.text,
+            "- role: Window\n  name: Notepad\n  ref: e1\n  children:\n  \
+             - role: Button\n    name: \"Save: now\"\n    ref: e2\n    id: btnSave\n    \
+             bounds: [10, 20, 80, 30]\n    states: [focusable]\n    patterns: [invoke]\n"
+        );
+
+        let json = serde_json::to_string(&text).unwrap();
+        assert!(json.contains(r#""format":"text""#));
     }
 }
@@ -0,0 +1,109 @@
+//! Length-prefixed IPC framing.
+//!
+//! Connections start with one JSON message per line. A client that sends
+//! `Request::Hello { framing: LengthPrefixed }` as its first request switches
+//! both directions to frames once the daemon answers: an 8-byte header (the
+//! JSON length, then the binary length, each a little-endian u32), the JSON,
+//! then the binary region. Binary data such as raw framebuffers travels in
+//! that region instead of after a response line.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+
+/// Size of a frame header in bytes.
+pub const FRAME_HEADER_LEN: usize = 8;
+
+/// Largest JSON region accepted in a frame.
+pub const MAX_FRAME_JSON_LEN: usize = 64 * 1024 * 1024;
+
+/// Largest binary region accepted in a frame.
+pub const MAX_FRAME_BINARY_LEN: usize = 512 * 1024 * 1024;
+
+/// How messages are delimited on an IPC connection.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum IpcFraming {
+    /// One JSON message per line, with binary data following the line.
+    #[default]
+    Line,
+    /// Length-prefixed frames with a separate binary region.
+    LengthPrefixed,
+}
+
+/// A frame that can't be encoded or whose header is invalid.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FrameError {
+    #[error("frame JSON of {0} bytes exceeds the {MAX_FRAME_JSON_LEN} byte limit")]
+    JsonTooLarge(usize),
+
+    #[error("frame binary data of {0} bytes exceeds the {MAX_FRAME_BINARY_LEN} byte limit")]
+    BinaryTooLarge(usize),
+}
+
+/// Lengths of the two regions of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub json_len: usize,
+    pub binary_len: usize,
+}
+
+impl FrameHeader {
+    /// Create a header, checking both lengths against their limits.
+    pub fn new(json_len: usize, binary_len: usize) -> Result<Self, FrameError> {
+        if json_len > MAX_FRAME_JSON_LEN {
+            return Err(FrameError::JsonTooLarge(json_len));
+        }
+        if binary_len > MAX_FRAME_BINARY_LEN {
+            return Err(FrameError::BinaryTooLarge(binary_len));
+        }
+        Ok(Self { json_len, binary_len })
+    }
+
+    /// Parse a header read from the stream.
+    pub fn decode(bytes: [u8; FRAME_HEADER_LEN]) -> Result<Self, FrameError> {
+        let json_len = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let binary_len = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        Self::new(json_len as usize, binary_len as usize)
+    }
+
+    /// Header bytes to write before the regions.
+    pub fn encode(&self) -> [u8; FRAME_HEADER_LEN] {
+        // Both lengths fit in a u32 since `new` checked them against the limits
+        let mut bytes = [0u8; FRAME_HEADER_LEN];
+        bytes[..4].copy_from_slice(&(self.json_len as u32).to_le_bytes());
+        bytes[4..].copy_from_slice(&(self.binary_len as u32).to_le_bytes());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_header_roundtrip() {
+        let header = FrameHeader::new(15, 3).unwrap();
+        let bytes = header.encode();
+        assert_eq!(&bytes[..4], &15u32.to_le_bytes());
+        assert_eq!(&bytes[4..], &3u32.to_le_bytes());
+        assert_eq!(FrameHeader::decode(bytes), Ok(header));
+    }
+
+    #[test]
+    fn test_frame_header_limits() {
+        let mut bytes = [0u8; FRAME_HEADER_LEN];
+        bytes[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            FrameHeader::decode(bytes),
+            Err(FrameError::JsonTooLarge(u32::MAX as usize))
+        );
+    }
+
+    #[test]
+    fn test_framing_serialization() {
+        let json = serde_json::to_string(&IpcFraming::LengthPrefixed).unwrap();
+        assert_eq!(json, r#""length_prefixed""#);
+    }
+}
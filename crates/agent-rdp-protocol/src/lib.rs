@@ -4,13 +4,24 @@
 //! communication between the CLI and the daemon over IPC.
 
 mod automation;
+mod framing;
 mod request;
 mod response;
 
 pub use automation::*;
+pub use framing::*;
 pub use request::*;
 pub use response::*;
 
+/// Version of the IPC protocol between the CLI and the daemon.
+///
+/// Bumped whenever requests or responses change in a way an older CLI or
+/// daemon can't read. Daemons report it in `pong`.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Release version of the crates, reported alongside the protocol version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[cfg(test)]
 mod codegen {
     use super::*;
@@ -30,6 +41,7 @@ mod codegen {
         // Export all types - ts-rs generates files via #[ts(export)]
         // We need to call export_all() to trigger generation
         Request::export_all().unwrap();
+        RequestEnvelope::export_all().unwrap();
         Response::export_all().unwrap();
         AutomateRequest::export_all().unwrap();
         // Export IPC types that aren't directly referenced by the main types
@@ -99,6 +111,7 @@ mod codegen {
                     max_depth: 10,
                     selector: None,
                     focused: false,
+                    format: SnapshotFormat::Json,
                 },
             ),
             (
@@ -109,6 +122,7 @@ mod codegen {
                     max_depth: 5,
                     selector: Some("#Window".to_string()),
                     focused: true,
+                    format: SnapshotFormat::Text,
                 },
             ),
             (
@@ -129,6 +143,15 @@ mod codegen {
                 AutomateRequest::Click {
                     selector: "@5".to_string(),
                     double_click: false,
+                    button: None,
+                },
+            ),
+            (
+                "click_right",
+                AutomateRequest::Click {
+                    selector: "@5".to_string(),
+                    double_click: false,
+                    button: Some("right".to_string()),
                 },
             ),
             (
@@ -136,6 +159,13 @@ mod codegen {
                 AutomateRequest::Click {
                     selector: "#Button[name='OK']".to_string(),
                     double_click: true,
+                    button: None,
+                },
+            ),
+            (
+                "invoke",
+                AutomateRequest::Invoke {
+                    selector: "@5".to_string(),
                 },
             ),
             (
@@ -158,6 +188,13 @@ mod codegen {
                     selector: "@7".to_string(),
                 },
             ),
+            (
+                "highlight",
+                AutomateRequest::Highlight {
+                    selector: "@5".to_string(),
+                    duration_ms: 3000,
+                },
+            ),
             (
                 "collapse",
                 AutomateRequest::Collapse {
@@ -177,6 +214,14 @@ mod codegen {
                     text: "testuser".to_string(),
                 },
             ),
+            (
+                "type",
+                AutomateRequest::Type {
+                    selector: "@5".to_string(),
+                    text: " world".to_string(),
+                    clear: false,
+                },
+            ),
             (
                 "clear",
                 AutomateRequest::Clear {
@@ -235,12 +280,20 @@ mod codegen {
                     timeout_ms: 5000,
                 },
             ),
+            (
+                "exec_script",
+                AutomateRequest::ExecScript {
+                    script: "$items = Get-ChildItem $env:TEMP\n$items.Count".to_string(),
+                    timeout_ms: 30000,
+                },
+            ),
             (
                 "wait_for",
                 AutomateRequest::WaitFor {
                     selector: "@5".to_string(),
                     timeout_ms: 30000,
                     state: WaitState::Visible,
+                    poll_interval_ms: 250,
                 },
             ),
             ("status", AutomateRequest::Status),
@@ -330,16 +383,22 @@ mod codegen {
             AutomateRequest::Get { .. } => "get",
             AutomateRequest::Focus { .. } => "focus",
             AutomateRequest::Click { .. } => "click",
+            AutomateRequest::Invoke { .. } => "invoke",
+            AutomateRequest::DragTo { .. } => "drag_to",
+            AutomateRequest::Screenshot { .. } => "screenshot",
+            AutomateRequest::Highlight { .. } => "highlight",
             AutomateRequest::Select { .. } => "select",
             AutomateRequest::Toggle { .. } => "toggle",
             AutomateRequest::Expand { .. } => "expand",
             AutomateRequest::Collapse { .. } => "collapse",
             AutomateRequest::ContextMenu { .. } => "context_menu",
             AutomateRequest::Fill { .. } => "fill",
+            AutomateRequest::Type { .. } => "type",
             AutomateRequest::Clear { .. } => "clear",
             AutomateRequest::Scroll { .. } => "scroll",
             AutomateRequest::Window { .. } => "window",
             AutomateRequest::Run { .. } => "run",
+            AutomateRequest::ExecScript { .. } => "exec_script",
             AutomateRequest::WaitFor { .. } => "wait_for",
             AutomateRequest::Status => "status",
         }
@@ -1,23 +1,83 @@
 //! Request types for CLI to daemon communication.
 
-use crate::automation::AutomateRequest;
+use crate::automation::{AutomateRequest, ElementBounds};
+use crate::framing::IpcFraming;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+/// A request as sent over IPC, optionally tagged with an ID.
+///
+/// Requests with an ID are processed concurrently and their responses carry
+/// the same ID, in completion order. Requests without one are answered one at
+/// a time, in order.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct RequestEnvelope {
+    /// Client-chosen ID echoed in the response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub id: Option<u64>,
+
+    /// The request itself.
+    #[serde(flatten)]
+    pub request: Request,
+}
+
+impl From<Request> for RequestEnvelope {
+    fn from(request: Request) -> Self {
+        Self { id: None, request }
+    }
+}
+
 /// A request from the CLI to the daemon.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Request {
-    /// Connect to an RDP server.
-    Connect(ConnectRequest),
+    /// Connect to an RDP server. Boxed, since the connection settings are far
+    /// larger than any other request.
+    Connect(Box<ConnectRequest>),
 
     /// Disconnect from the RDP server.
-    Disconnect,
+    Disconnect {
+        /// Log the Windows user off first, ending their programs and the
+        /// session, instead of leaving it running for a later reconnect.
+        #[serde(default)]
+        logoff: bool,
+    },
 
     /// Take a screenshot.
     Screenshot(ScreenshotRequest),
 
+    /// Read the current desktop framebuffer.
+    Frame(FrameRequest),
+
+    /// Resize the remote desktop without reconnecting.
+    Resize {
+        /// New desktop width (200-8192, rounded down to an even number).
+        width: u16,
+        /// New desktop height (200-8192).
+        height: u16,
+    },
+
+    /// Change the WebSocket stream settings while it runs.
+    ///
+    /// Omitted settings keep their current value.
+    StreamConfig {
+        /// Frames per second (1-60).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        fps: Option<u32>,
+        /// JPEG quality (1-100).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        quality: Option<u8>,
+        /// Send only the changed tiles of each frame.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        delta: Option<bool>,
+    },
+
     /// Mouse operation.
     Mouse(MouseRequest),
 
@@ -27,6 +87,9 @@ pub enum Request {
     /// Scroll operation.
     Scroll(ScrollRequest),
 
+    /// Sequence of input events executed in order in one round trip.
+    InputBatch(InputBatchRequest),
+
     /// Clipboard operation.
     Clipboard(ClipboardRequest),
 
@@ -34,17 +97,57 @@ pub enum Request {
     Drive(DriveRequest),
 
     /// UI Automation operation.
-    Automate(AutomateRequest),
+    Automate {
+        #[serde(flatten)]
+        request: AutomateRequest,
+
+        /// How long to wait for each answer from the automation agent, in
+        /// milliseconds (default: 10000, longer for `run --wait`).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional, type = "number")]
+        agent_timeout_ms: Option<u64>,
+    },
 
     /// OCR-based text location.
     Locate(LocateRequest),
 
+    /// Find text with OCR and click the center of the match.
+    LocateClick(LocateClickRequest),
+
+    /// Wait until the screen (or a region of it) changes.
+    WaitForChange {
+        /// Longest time to wait in milliseconds (default: 10000, max: 60000).
+        #[serde(default = "default_change_timeout")]
+        #[ts(type = "number")]
+        timeout_ms: u64,
+        /// Only watch this region of the desktop (default: full screen).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        region: Option<ElementBounds>,
+    },
+
+    /// Get the mouse pointer position and shape.
+    CursorInfo {
+        /// Include the pointer image as a PNG.
+        #[serde(default)]
+        include_image: bool,
+    },
+
     /// Get session info.
     SessionInfo,
 
     /// Ping the daemon (for health checks).
     Ping,
 
+    /// Choose how messages are framed for the rest of the connection.
+    ///
+    /// Only accepted as the first request. The answer is still a line; the
+    /// framing applies from the next message on.
+    Hello {
+        /// Framing to switch to.
+        framing: IpcFraming,
+    },
+
     /// Shutdown the daemon gracefully.
     Shutdown,
 }
@@ -57,6 +160,61 @@ pub struct DriveMapping {
     pub path: String,
     /// Name for the mapped drive (shown in Windows).
     pub name: String,
+    /// Expose the drive read-only (remote host cannot create, modify or delete files).
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// A monitor of a multi-monitor desktop.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct MonitorLayout {
+    /// Left edge (relative to the primary monitor when connecting; in desktop
+    /// coordinates in session info).
+    pub x: i32,
+    /// Top edge.
+    pub y: i32,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Whether this is the primary monitor (default: the first monitor).
+    #[serde(default)]
+    pub primary: bool,
+}
+
+/// RD Gateway used to reach the RDP server.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct GatewayRequest {
+    /// Gateway hostname or IP address.
+    pub host: String,
+
+    /// Gateway HTTPS port (default: 443).
+    #[serde(default = "default_gateway_port")]
+    pub port: u16,
+
+    /// Gateway username (default: the RDP username).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub username: Option<String>,
+
+    /// Gateway password (default: the RDP password).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub password: Option<String>,
+
+    /// Expected SHA-256 fingerprint of the gateway certificate (hex, colons
+    /// optional), for gateways without a publicly trusted certificate. By default
+    /// the certificate must chain to a public root and match the host name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub expected_cert_sha256: Option<String>,
+
+    /// Accept any gateway certificate. The gateway credentials then go to
+    /// whoever answers, so this is only for networks you control.
+    #[serde(default)]
+    pub insecure: bool,
 }
 
 /// RDP connection parameters.
@@ -90,6 +248,17 @@ pub struct ConnectRequest {
     #[serde(default)]
     pub drives: Vec<DriveMapping>,
 
+    /// Monitor layout, positioned relative to the primary monitor. The desktop size
+    /// becomes the bounding box of the monitors (default: one monitor of `width` x `height`).
+    #[serde(default)]
+    pub monitors: Vec<MonitorLayout>,
+
+    /// Logical `[width, height]` that mouse and scroll coordinates are given in,
+    /// scaled to the desktop size (default: desktop pixels).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub coordinate_space: Option<(u16, u16)>,
+
     /// Enable Windows UI Automation.
     #[serde(default)]
     pub enable_win_automation: bool,
@@ -110,6 +279,255 @@ pub struct ConnectRequest {
     /// When false, only WebSocket connections are accepted.
     #[serde(default)]
     pub serve_viewer: bool,
+
+    /// Largest width or height of streamed frames. Larger desktops are scaled
+    /// down, keeping their aspect ratio, before JPEG encoding; screenshots are
+    /// not affected. Unset streams frames at the desktop size.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub stream_max_frame_size: Option<u16>,
+
+    /// Keyboard layout identifier (LCID, e.g. 0x409 for US English, 0x40C for French).
+    /// Defaults to US English when not specified.
+    #[serde(default)]
+    #[ts(optional)]
+    pub keyboard_layout: Option<u32>,
+
+    /// Expected SHA-256 fingerprint of the server certificate (hex, colons optional).
+    /// When set, the connection fails if the server presents a different certificate.
+    #[serde(default)]
+    #[ts(optional)]
+    pub expected_cert_sha256: Option<String>,
+
+    /// Re-establish the connection automatically after a network drop (default: false).
+    #[serde(default)]
+    pub auto_reconnect: bool,
+
+    /// Maximum reconnection attempts before giving up (default: 5).
+    #[serde(default = "default_max_reconnect_attempts")]
+    pub max_reconnect_attempts: u32,
+
+    /// Send a keepalive after this many seconds without data from the server,
+    /// and treat the connection as lost once the server has been silent for
+    /// three intervals. 0 or unset disables it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub keepalive_interval_secs: Option<u32>,
+
+    /// After connecting, check that keyboard input reaches the remote desktop
+    /// and report the result in the response (default: false). Needs
+    /// `enable_win_automation` to read the result back.
+    #[serde(default)]
+    pub test_input: bool,
+
+    /// Wait, at most `FIRST_FRAME_TIMEOUT_MS`, for the first graphics update
+    /// before answering, so a screenshot taken right after connect shows the
+    /// desktop instead of a black image (default: false).
+    #[serde(default)]
+    pub wait_first_frame: bool,
+
+    /// Retry the initial connect this many times while the server refuses,
+    /// resets or doesn't answer the connection, e.g. while it is still
+    /// booting (default: 0). Other failures, such as rejected credentials,
+    /// are never retried.
+    #[serde(default)]
+    pub connect_retries: u32,
+
+    /// Delay before the first connect retry, doubling for each one after it
+    /// up to 30 seconds (default: 1000).
+    #[serde(default = "default_retry_delay_ms")]
+    #[ts(type = "number")]
+    pub retry_delay_ms: u64,
+
+    /// Time each connect attempt may take to open the TCP connection and
+    /// complete the TLS and CredSSP handshake (default: 20000).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub connect_timeout_ms: Option<u64>,
+
+    /// Security protocol to negotiate (default: auto).
+    #[serde(default)]
+    pub security: SecurityMode,
+
+    /// Oldest TLS version to accept from the server (default: 1.2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub min_tls: Option<TlsVersion>,
+
+    /// Graphics codec to request from the server (default: bitmap).
+    #[serde(default)]
+    pub codec: GraphicsCodec,
+
+    /// Visual effects to ask the server to turn off (default: lan).
+    #[serde(default)]
+    pub perf_profile: PerformanceProfile,
+
+    /// Exact performance flags to send, replacing those of `perf_profile`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub perf_flags: Option<Vec<PerformanceFlag>>,
+
+    /// RD Gateway to tunnel the connection through.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub gateway: Option<GatewayRequest>,
+
+    /// Proxy URL for the TCP connection: `socks5://[user:pass@]host[:port]` or
+    /// `http://[user:pass@]host[:port]` (HTTP CONNECT).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub proxy: Option<String>,
+
+    /// KDC for Kerberos authentication: `host[:port]`, `tcp://host[:port]` or
+    /// `udp://host[:port]` (port 88 by default). NTLM is used without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub kdc: Option<String>,
+
+    /// Record remote audio to this WAV file (relative paths are resolved against
+    /// the session directory). Only the first audio format negotiated is recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub capture_audio: Option<String>,
+
+    /// Record the desktop to this animated GIF (relative paths are resolved
+    /// against the session directory). The file is finalized on disconnect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub record: Option<String>,
+
+    /// Frames per second sampled for the recording (default: 2, max: 10).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub record_fps: Option<u32>,
+
+    /// Redirect local smart card readers through the PC/SC service. Ignored when
+    /// the daemon was built without smart card support or no reader is present.
+    #[serde(default)]
+    pub redirect_smartcard: bool,
+
+    /// Attach the clipboard channel (default: true). Clipboard requests fail
+    /// when it's off.
+    #[serde(default = "default_true")]
+    pub enable_clipboard: bool,
+
+    /// Attach the audio output channel (default: true). Required for `capture_audio`.
+    #[serde(default = "default_true")]
+    pub enable_audio: bool,
+
+    /// Shut the daemon down after this many seconds without requests or WebSocket
+    /// clients. Pings count as requests. 0 or unset keeps it running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub idle_timeout_secs: Option<u64>,
+
+    /// Client computer name reported to the server (at most 15 characters).
+    /// Defaults to "agent-rdp".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub client_name: Option<String>,
+
+    /// Client build number reported to the server (default: 0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub client_build: Option<u32>,
+
+    /// Product ID reported to the server (at most 31 characters).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub dig_product_id: Option<String>,
+
+    /// Client hardware ID as 32 hex digits (dashes allowed, as in a GUID).
+    /// Defaults to an ID derived from the session name, so reconnects of the
+    /// same session look like the same device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub hardware_id: Option<String>,
+
+    /// Number of remote clipboard texts to remember (default: 5, max: 100,
+    /// 0 disables the history).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub clipboard_history: Option<u32>,
+}
+
+/// Security protocol negotiated with the RDP server.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityMode {
+    /// Offer both NLA and TLS and let the server choose.
+    #[default]
+    Auto,
+    /// Require Network Level Authentication (CredSSP over TLS).
+    Nla,
+    /// TLS without CredSSP, for hosts with NLA disabled.
+    /// Credentials are sent at the RDP layer (in the client info PDU) instead of via CredSSP.
+    TlsOnly,
+}
+
+/// TLS protocol version.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub enum TlsVersion {
+    #[serde(rename = "1.0")]
+    Tls10,
+    #[serde(rename = "1.1")]
+    Tls11,
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+/// Graphics codec requested for desktop updates.
+///
+/// H.264 (AVC420/AVC444) needs the graphics pipeline channel, which isn't
+/// implemented, so it isn't offered.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum GraphicsCodec {
+    /// Classic bitmap updates (interleaved RLE / planar).
+    #[default]
+    Bitmap,
+    /// RemoteFX surface commands, which need far less bandwidth.
+    Remotefx,
+}
+
+/// Visual effects profile, named after the mstsc connection speeds.
+///
+/// Each step down turns off more eye-candy, which cuts bandwidth and keeps
+/// screenshots the same from run to run.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum PerformanceProfile {
+    /// Everything on, including font smoothing and desktop composition.
+    Full,
+    /// No wallpaper, window contents while dragging, menu animations or cursor
+    /// shadow; themes stay on.
+    #[default]
+    Lan,
+    /// As `lan`, with themes also off.
+    Broadband,
+    /// As `broadband`, with the cursor blink also off.
+    Modem,
+}
+
+/// One of the performance flags sent to the server in the client info.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum PerformanceFlag {
+    DisableWallpaper,
+    DisableFullWindowDrag,
+    DisableMenuAnimations,
+    DisableTheming,
+    DisableCursorShadow,
+    DisableCursorSettings,
+    EnableFontSmoothing,
+    EnableDesktopComposition,
 }
 
 fn default_stream_fps() -> u32 {
@@ -120,6 +538,27 @@ fn default_stream_quality() -> u8 {
     80
 }
 
+fn default_max_reconnect_attempts() -> u32 {
+    5
+}
+
+fn default_retry_delay_ms() -> u64 {
+    1000
+}
+
+/// Longest delay between connect retries.
+pub const MAX_CONNECT_RETRY_DELAY_MS: u64 = 30_000;
+
+/// Time a connect attempt gets for its handshake when the request doesn't set one.
+pub const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 20_000;
+
+/// Longest connect waits for the first graphics update with `wait_first_frame`.
+pub const FIRST_FRAME_TIMEOUT_MS: u64 = 10_000;
+
+fn default_gateway_port() -> u16 {
+    443
+}
+
 impl Default for ConnectRequest {
     fn default() -> Self {
         Self {
@@ -131,15 +570,66 @@ impl Default for ConnectRequest {
             width: 1280,
             height: 800,
             drives: Vec::new(),
+            monitors: Vec::new(),
+            coordinate_space: None,
             enable_win_automation: false,
             stream_port: 0,
             stream_fps: default_stream_fps(),
             stream_quality: default_stream_quality(),
             serve_viewer: false,
+            stream_max_frame_size: None,
+            keyboard_layout: None,
+            expected_cert_sha256: None,
+            auto_reconnect: false,
+            max_reconnect_attempts: default_max_reconnect_attempts(),
+            keepalive_interval_secs: None,
+            test_input: false,
+            wait_first_frame: false,
+            connect_retries: 0,
+            retry_delay_ms: default_retry_delay_ms(),
+            connect_timeout_ms: None,
+            security: SecurityMode::Auto,
+            min_tls: None,
+            codec: GraphicsCodec::Bitmap,
+            perf_profile: PerformanceProfile::Lan,
+            perf_flags: None,
+            gateway: None,
+            proxy: None,
+            kdc: None,
+            capture_audio: None,
+            record: None,
+            record_fps: None,
+            redirect_smartcard: false,
+            enable_clipboard: true,
+            enable_audio: true,
+            idle_timeout_secs: None,
+            client_name: None,
+            client_build: None,
+            dig_product_id: None,
+            hardware_id: None,
+            clipboard_history: None,
         }
     }
 }
 
+impl ConnectRequest {
+    /// Delay in milliseconds before connect retry `retry` (1-based).
+    pub fn retry_delay_ms(&self, retry: u32) -> u64 {
+        let doublings = retry.saturating_sub(1).min(16);
+        self.retry_delay_ms
+            .saturating_mul(1 << doublings)
+            .min(MAX_CONNECT_RETRY_DELAY_MS)
+    }
+
+    /// Time in milliseconds each connect attempt gets for its handshake.
+    pub fn connect_timeout_ms(&self) -> u64 {
+        self.connect_timeout_ms.unwrap_or(DEFAULT_CONNECT_TIMEOUT_MS)
+    }
+}
+
+/// Longest a screenshot waits for the desktop to settle when no timeout is given.
+pub const DEFAULT_STABLE_TIMEOUT_MS: u64 = 10_000;
+
 /// Screenshot request parameters.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
@@ -147,6 +637,83 @@ pub struct ScreenshotRequest {
     /// Image format.
     #[serde(default)]
     pub format: ImageFormat,
+
+    /// Left edge of the region to capture (default: 0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub x: Option<u32>,
+
+    /// Top edge of the region to capture (default: 0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub y: Option<u32>,
+
+    /// Width of the region to capture (default: to the right edge of the desktop).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub width: Option<u32>,
+
+    /// Height of the region to capture (default: to the bottom edge of the desktop).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub height: Option<u32>,
+
+    /// Capture only this monitor (index into the session's monitors); the region is
+    /// then relative to the monitor. Default: the whole desktop (all monitors).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub monitor: Option<u32>,
+
+    /// Draw the mouse pointer onto the image at its current position.
+    #[serde(default)]
+    pub draw_cursor: bool,
+
+    /// Before capturing, wait until the desktop hasn't changed for this many
+    /// milliseconds, so animations have finished (default: capture immediately).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub stable_ms: Option<u64>,
+
+    /// Longest to wait for the desktop to settle before capturing anyway
+    /// (default: 10000, max: 60000).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub stable_timeout_ms: Option<u64>,
+
+    /// `etag` of an earlier screenshot. If the capture is identical, nothing is
+    /// encoded and `not_modified` is returned instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub if_changed_since: Option<String>,
+}
+
+/// Framebuffer request parameters.
+///
+/// Raw frames are `width * height * 4` bytes of RGBA (about 4 MB at
+/// 1280x800), meant for local consumers reading frames at a high rate.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct FrameRequest {
+    /// Pixel encoding.
+    #[serde(default)]
+    pub encoding: FrameEncoding,
+
+    /// Send the pixels as `size_bytes` raw bytes right after the response line
+    /// instead of base64 inside it.
+    #[serde(default)]
+    pub binary: bool,
+}
+
+/// Framebuffer encodings.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "lowercase")]
+pub enum FrameEncoding {
+    /// Uncompressed RGBA, 4 bytes per pixel, rows `stride` bytes apart.
+    #[default]
+    Raw,
+    /// PNG image.
+    Png,
 }
 
 /// Supported image formats.
@@ -157,6 +724,8 @@ pub enum ImageFormat {
     #[default]
     Png,
     Jpeg,
+    Webp,
+    Avif,
 }
 
 /// Mouse operation request.
@@ -165,10 +734,23 @@ pub enum ImageFormat {
 #[serde(tag = "action", rename_all = "snake_case")]
 pub enum MouseRequest {
     /// Move the mouse cursor.
-    Move { x: u16, y: u16 },
+    Move {
+        x: u16,
+        y: u16,
+        /// Glide along a curved, eased path from the current position instead of
+        /// jumping there (default: false).
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        human: bool,
+    },
 
     /// Left click.
-    Click { x: u16, y: u16 },
+    Click {
+        x: u16,
+        y: u16,
+        /// Glide to the position like `Move` with `human` before clicking (default: false).
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        human: bool,
+    },
 
     /// Right click.
     RightClick { x: u16, y: u16 },
@@ -185,20 +767,47 @@ pub enum MouseRequest {
         from_y: u16,
         to_x: u16,
         to_y: u16,
+        /// Number of intermediate moves while the button is held (default: 10).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        steps: Option<u32>,
     },
 
-    /// Press and hold a mouse button.
+    /// Press and hold a mouse button at the current pointer position.
     ButtonDown { button: MouseButton },
 
-    /// Release a mouse button.
+    /// Release a mouse button at the current pointer position.
     ButtonUp { button: MouseButton },
+
+    /// Press a mouse button at a position and keep it held, until `Up` or
+    /// `ReleaseAll` (or disconnecting) releases it.
+    Down {
+        x: u16,
+        y: u16,
+        /// Button to press (default: left).
+        #[serde(default)]
+        button: MouseButton,
+    },
+
+    /// Release a mouse button at a position.
+    Up {
+        x: u16,
+        y: u16,
+        /// Button to release (default: left).
+        #[serde(default)]
+        button: MouseButton,
+    },
+
+    /// Release every mouse button still held down.
+    ReleaseAll,
 }
 
 /// Mouse button identifiers.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
 #[serde(rename_all = "snake_case")]
 pub enum MouseButton {
+    #[default]
     Left,
     Right,
     Middle,
@@ -209,8 +818,20 @@ pub enum MouseButton {
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
 #[serde(tag = "action", rename_all = "snake_case")]
 pub enum KeyboardRequest {
-    /// Type a text string (Unicode).
-    Type { text: String },
+    /// Type a text string.
+    Type {
+        text: String,
+
+        /// How characters are sent (default: unicode).
+        #[serde(default)]
+        mode: TypeMode,
+
+        /// Pause after each character in milliseconds (default: 100). 0 sends
+        /// the whole text at once.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional, type = "number")]
+        delay_ms: Option<u64>,
+    },
 
     /// Press a key combination (e.g., "ctrl+c", "alt+tab", or single key like "enter").
     Press { keys: String },
@@ -220,6 +841,52 @@ pub enum KeyboardRequest {
 
     /// Release a held key.
     KeyUp { key: String },
+
+    /// Send one scancode event as is, for keys without a name.
+    Raw {
+        scancode: u8,
+
+        /// Set the extended-key flag (E0 prefix).
+        #[serde(default)]
+        extended: bool,
+
+        /// Send a release instead of a press.
+        #[serde(default)]
+        release: bool,
+    },
+
+    /// Press keys (e.g., "shift" or "ctrl+alt") and keep them down across
+    /// later commands until released.
+    Hold { keys: String },
+
+    /// Release keys pressed with `hold`.
+    Release { keys: String },
+
+    /// Release every key still held down (also done on disconnect).
+    ReleaseAll,
+
+    /// Put text on the remote clipboard and press Ctrl+V, which is much
+    /// faster than typing long text. The focused window must accept Ctrl+V.
+    Paste {
+        text: String,
+
+        /// Put the previous clipboard text back afterward (default: false).
+        #[serde(default)]
+        restore: bool,
+    },
+}
+
+/// How typed text is sent to the server.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum TypeMode {
+    /// Unicode keyboard events, independent of the keyboard layout.
+    #[default]
+    Unicode,
+    /// Scancodes (with shift/AltGr) for the session's keyboard layout, for
+    /// applications that ignore unicode injection.
+    Scancode,
 }
 
 /// Scroll operation request.
@@ -258,27 +925,194 @@ pub enum ScrollDirection {
     Right,
 }
 
+/// A batch of input events.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct InputBatchRequest {
+    /// Events to execute, in order.
+    pub events: Vec<InputEvent>,
+
+    /// Pause after each event and between typed characters, in milliseconds (default: 20).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub delay_ms: Option<u64>,
+}
+
+/// A single event in an input batch.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputEvent {
+    /// Mouse operation.
+    Mouse(MouseRequest),
+
+    /// Keyboard operation.
+    Keyboard(KeyboardRequest),
+
+    /// Scroll operation.
+    Scroll(ScrollRequest),
+
+    /// Pause before the next event.
+    Wait {
+        /// Milliseconds to wait (max 60000).
+        #[ts(type = "number")]
+        ms: u64,
+    },
+}
+
+/// How line endings of clipboard text are converted.
+///
+/// Windows programs expect CRLF, so text is converted to CRLF when set and
+/// back to LF when read unless asked otherwise.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum LineEndings {
+    /// Leave the text as it is.
+    Preserve,
+    /// Windows line endings (`\r\n`).
+    Crlf,
+    /// Unix line endings (`\n`).
+    Lf,
+}
+
+impl LineEndings {
+    /// Convert every line break in `text`, including mixed ones.
+    ///
+    /// A lone `\r` is not treated as a line break and is left alone.
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            LineEndings::Preserve => text.to_string(),
+            LineEndings::Crlf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+            LineEndings::Lf => text.replace("\r\n", "\n"),
+        }
+    }
+}
+
 /// Clipboard operation request.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
 #[serde(tag = "action", rename_all = "snake_case")]
 pub enum ClipboardRequest {
     /// Get clipboard text content.
-    Get,
+    Get {
+        /// Line ending conversion of the returned text (default: lf).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        line_endings: Option<LineEndings>,
+    },
 
     /// Set clipboard text content.
-    Set { text: String },
+    Set {
+        text: String,
+        /// Line ending conversion before the text is copied (default: crlf).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        line_endings: Option<LineEndings>,
+    },
+
+    /// Get clipboard image as PNG.
+    GetImage,
+
+    /// Set clipboard image from PNG data.
+    SetImage {
+        /// Base64-encoded PNG data.
+        base64: String,
+    },
+
+    /// Get the clipboard HTML fragment.
+    GetHtml,
+
+    /// Set clipboard HTML, also offered as plain text for targets without HTML support.
+    SetHtml {
+        /// HTML fragment to copy.
+        html: String,
+        /// Plain text version (default: the HTML with tags removed).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        text: Option<String>,
+    },
+
+    /// Save files copied on the remote machine into a local directory.
+    GetFiles {
+        /// Local directory to save the files into (created if missing).
+        dest_dir: String,
+        /// Maximum total bytes to transfer (default: 1 GiB).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional, type = "number")]
+        max_bytes: Option<u64>,
+    },
+
+    /// Copy local files and directories so they can be pasted on the remote machine.
+    SetFiles {
+        /// Local files or directories to copy.
+        paths: Vec<String>,
+        /// Maximum total bytes to transfer (default: 1 GiB).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional, type = "number")]
+        max_bytes: Option<u64>,
+    },
+
+    /// Text of recent remote copies that were fetched, newest first.
+    History {
+        /// Number of entries to return (default: all that are kept).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        count: Option<u32>,
+    },
+
+    /// Empty the clipboard on both sides.
+    Clear,
+
+    /// Which kinds of content the remote's last copy offers, without fetching any.
+    Status,
 }
 
 /// Drive mapping operation request.
-/// Note: Drives are configured at connect time with --drive flag.
-/// Dynamic mapping/unmapping is not supported by the RDP protocol.
+/// Drives are configured at connect time with the --drive flag, and can be
+/// mounted and unmounted while connected if drive redirection is enabled.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
 #[serde(tag = "action", rename_all = "snake_case")]
 pub enum DriveRequest {
     /// List mapped drives.
     List,
+
+    /// Copy a local file or directory into a mapped drive.
+    Push {
+        /// Local file or directory to copy.
+        local: String,
+        /// Destination: the drive name followed by a path inside it, e.g.
+        /// `Shared/reports/q3.pdf`. An existing directory receives the source
+        /// under its own name.
+        remote: String,
+    },
+
+    /// Copy a file or directory out of a mapped drive.
+    Pull {
+        /// Source: the drive name followed by a path inside it.
+        remote: String,
+        /// Local destination. An existing directory receives the source under
+        /// its own name.
+        local: String,
+    },
+
+    /// Map another local directory as a drive without reconnecting.
+    Mount {
+        /// Local directory to map.
+        path: String,
+        /// Drive name shown on the remote (`\\tsclient\<name>`).
+        name: String,
+        /// Reject writes from the remote.
+        #[serde(default)]
+        read_only: bool,
+    },
+
+    /// Remove a mapped drive without reconnecting.
+    Unmount {
+        /// Name of the drive to remove.
+        name: String,
+    },
 }
 
 /// OCR-based text location request.
@@ -301,11 +1135,81 @@ pub struct LocateRequest {
     /// Return all text on screen (ignores text/pattern/ignore_case).
     #[serde(default)]
     pub all: bool,
-}
 
-fn default_true() -> bool {
-    true
-}
+    /// Only search within this region of the desktop (default: full screen).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub region: Option<ElementBounds>,
+
+    /// Directory holding the OCR models (default: `AGENT_RDP_OCR_MODELS` in the
+    /// daemon's environment, then the models shipped next to the binary).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub models_dir: Option<String>,
+
+    /// Language to recognize, e.g. `en` or `ru` (default: `en`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub lang: Option<String>,
+}
+
+/// Find text on screen with OCR and click it.
+///
+/// Clicks the most confident match, or the `index`th match in reading order
+/// (top to bottom, then left to right) when several lines contain the text.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct LocateClickRequest {
+    /// Text to search for.
+    pub text: String,
+
+    /// Use pattern matching (glob-style: * and ?).
+    #[serde(default)]
+    pub pattern: bool,
+
+    /// Case-insensitive matching (default: true).
+    #[serde(default = "default_true")]
+    pub ignore_case: bool,
+
+    /// Only search within this region of the desktop (default: full screen).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub region: Option<ElementBounds>,
+
+    /// Button to click with (default: left).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub button: Option<MouseButton>,
+
+    /// Click the match at this position (0-based) in reading order instead of
+    /// the most confident one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub index: Option<u32>,
+
+    /// Ignore matches below this confidence, from 0.0 to 1.0 (default: 0.2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub min_confidence: Option<f32>,
+
+    /// Directory holding the OCR models (see `LocateRequest::models_dir`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub models_dir: Option<String>,
+
+    /// Language to recognize (default: `en`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub lang: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_change_timeout() -> u64 {
+    10000
+}
 
 #[cfg(test)]
 mod tests {
@@ -313,7 +1217,7 @@ mod tests {
 
     #[test]
     fn test_request_serialization() {
-        let req = Request::Connect(ConnectRequest {
+        let req = Request::Connect(Box::new(ConnectRequest {
             host: "192.168.1.100".to_string(),
             port: 3389,
             username: "admin".to_string(),
@@ -324,7 +1228,7 @@ mod tests {
             drives: vec![],
             enable_win_automation: false,
             ..Default::default()
-        });
+        }));
 
         let json = serde_json::to_string(&req).unwrap();
         let parsed: Request = serde_json::from_str(&json).unwrap();
@@ -340,7 +1244,7 @@ mod tests {
 
     #[test]
     fn test_connect_with_drives() {
-        let req = Request::Connect(ConnectRequest {
+        let req = Request::Connect(Box::new(ConnectRequest {
             host: "192.168.1.100".to_string(),
             port: 3389,
             username: "admin".to_string(),
@@ -352,15 +1256,17 @@ mod tests {
                 DriveMapping {
                     path: "/home/user/docs".to_string(),
                     name: "Documents".to_string(),
+                    read_only: true,
                 },
                 DriveMapping {
                     path: "/tmp/shared".to_string(),
                     name: "Shared".to_string(),
+                    read_only: false,
                 },
             ],
             enable_win_automation: false,
             ..Default::default()
-        });
+        }));
 
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"drives\""));
@@ -372,20 +1278,853 @@ mod tests {
             Request::Connect(c) => {
                 assert_eq!(c.drives.len(), 2);
                 assert_eq!(c.drives[0].name, "Documents");
+                assert!(c.drives[0].read_only);
                 assert_eq!(c.drives[1].path, "/tmp/shared");
+                assert!(!c.drives[1].read_only);
             }
             _ => panic!("unexpected request type"),
         }
     }
 
+    #[test]
+    fn test_connect_keyboard_layout() {
+        let req = Request::Connect(Box::new(ConnectRequest {
+            host: "192.168.1.100".to_string(),
+            keyboard_layout: Some(0x40C),
+            ..Default::default()
+        }));
+
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"keyboard_layout\":1036"));
+
+        // Omitted field falls back to None (US English in the daemon)
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.keyboard_layout, None);
+    }
+
+    #[test]
+    fn test_connect_auto_reconnect_defaults() {
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#,
+        )
+        .unwrap();
+        assert!(!parsed.auto_reconnect);
+        assert_eq!(parsed.max_reconnect_attempts, 5);
+
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,"auto_reconnect":true,"max_reconnect_attempts":10}"#,
+        )
+        .unwrap();
+        assert!(parsed.auto_reconnect);
+        assert_eq!(parsed.max_reconnect_attempts, 10);
+    }
+
+    #[test]
+    fn test_connect_security_mode() {
+        let req = ConnectRequest {
+            security: SecurityMode::TlsOnly,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"security\":\"tls_only\""));
+
+        // Omitted field defaults to auto
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.security, SecurityMode::Auto);
+    }
+
+    #[test]
+    fn test_connect_retry_delays() {
+        let json = r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#;
+        let parsed: ConnectRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.connect_retries, 0);
+        assert_eq!(parsed.retry_delay_ms, 1000);
+
+        let delays: Vec<u64> = (1..=7).map(|retry| parsed.retry_delay_ms(retry)).collect();
+        assert_eq!(delays, [1000, 2000, 4000, 8000, 16000, 30000, 30000]);
+        assert_eq!(parsed.retry_delay_ms(u32::MAX), MAX_CONNECT_RETRY_DELAY_MS);
+    }
+
+    #[test]
+    fn test_connect_timeout() {
+        let json = r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#;
+        let parsed: ConnectRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.connect_timeout_ms, None);
+        assert_eq!(parsed.connect_timeout_ms(), DEFAULT_CONNECT_TIMEOUT_MS);
+        assert!(!serde_json::to_string(&parsed).unwrap().contains("connect_timeout_ms"));
+
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,"connect_timeout_ms":5000}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.connect_timeout_ms(), 5000);
+    }
+
+    #[test]
+    fn test_connect_wait_first_frame() {
+        let json = r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#;
+        let parsed: ConnectRequest = serde_json::from_str(json).unwrap();
+        assert!(!parsed.wait_first_frame);
+
+        let req = ConnectRequest {
+            wait_first_frame: true,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains(r#""wait_first_frame":true"#));
+    }
+
+    #[test]
+    fn test_connect_min_tls() {
+        let req = ConnectRequest {
+            min_tls: Some(TlsVersion::Tls10),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"min_tls\":\"1.0\""));
+
+        let parsed: ConnectRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.min_tls, Some(TlsVersion::Tls10));
+        assert!(TlsVersion::Tls11 < TlsVersion::Tls12);
+    }
+
+    #[test]
+    fn test_connect_codec() {
+        let req = ConnectRequest {
+            codec: GraphicsCodec::Remotefx,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"codec\":\"remotefx\""));
+
+        // Omitted field defaults to bitmap
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.codec, GraphicsCodec::Bitmap);
+
+        // H.264 isn't accepted until the graphics pipeline exists
+        let result = serde_json::from_str::<ConnectRequest>(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,
+                "codec":"avc420"}"#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_connect_perf_profile() {
+        let req = ConnectRequest {
+            perf_profile: PerformanceProfile::Full,
+            perf_flags: Some(vec![
+                PerformanceFlag::DisableWallpaper,
+                PerformanceFlag::EnableFontSmoothing,
+            ]),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains(r#""perf_profile":"full""#));
+        assert!(json.contains(r#""perf_flags":["disable_wallpaper","enable_font_smoothing"]"#));
+
+        // Omitted fields default to the lan profile
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.perf_profile, PerformanceProfile::Lan);
+        assert!(parsed.perf_flags.is_none());
+    }
+
+    #[test]
+    fn test_connect_gateway() {
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,
+                "gateway":{"host":"gw.example.com"}}"#,
+        )
+        .unwrap();
+        let gateway = parsed.gateway.unwrap();
+        assert_eq!(gateway.host, "gw.example.com");
+        assert_eq!(gateway.port, 443);
+        assert!(gateway.username.is_none());
+        // The certificate is verified unless the request says otherwise
+        assert!(gateway.expected_cert_sha256.is_none());
+        assert!(!gateway.insecure);
+
+        // No gateway is omitted from the JSON
+        let json = serde_json::to_string(&ConnectRequest::default()).unwrap();
+        assert!(!json.contains("gateway"));
+    }
+
+    #[test]
+    fn test_wait_for_change_serialization() {
+        let req = Request::WaitForChange {
+            timeout_ms: 2000,
+            region: Some(ElementBounds {
+                x: 0,
+                y: 0,
+                width: 100,
+                height: 50,
+            }),
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"type\":\"wait_for_change\""));
+        assert!(json.contains("\"timeout_ms\":2000"));
+
+        // Omitted fields use the defaults
+        let parsed: Request = serde_json::from_str(r#"{"type":"wait_for_change"}"#).unwrap();
+        match parsed {
+            Request::WaitForChange { timeout_ms, region } => {
+                assert_eq!(timeout_ms, 10000);
+                assert!(region.is_none());
+            }
+            _ => panic!("Expected wait_for_change request"),
+        }
+    }
+
+    #[test]
+    fn test_cursor_info_serialization() {
+        let req = Request::CursorInfo { include_image: true };
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"cursor_info","include_image":true}"#);
+
+        // The image is left out by default
+        let parsed: Request = serde_json::from_str(r#"{"type":"cursor_info"}"#).unwrap();
+        assert!(matches!(parsed, Request::CursorInfo { include_image: false }));
+    }
+
+    #[test]
+    fn test_disconnect_logoff() {
+        // Existing clients send a bare disconnect, which doesn't log off
+        let parsed: Request = serde_json::from_str(r#"{"type":"disconnect"}"#).unwrap();
+        assert!(matches!(parsed, Request::Disconnect { logoff: false }));
+
+        let json = serde_json::to_string(&Request::Disconnect { logoff: true }).unwrap();
+        assert_eq!(json, r#"{"type":"disconnect","logoff":true}"#);
+    }
+
+    #[test]
+    fn test_keyboard_type_mode() {
+        let req = Request::Keyboard(KeyboardRequest::Type {
+            text: "Hi!".to_string(),
+            mode: TypeMode::Scancode,
+            delay_ms: None,
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"mode\":\"scancode\""));
+        assert!(!json.contains("delay_ms"));
+
+        // Omitted mode defaults to unicode
+        let parsed: Request =
+            serde_json::from_str(r#"{"type":"keyboard","action":"type","text":"Hi!"}"#).unwrap();
+        match parsed {
+            Request::Keyboard(KeyboardRequest::Type { mode, delay_ms, .. }) => {
+                assert_eq!(mode, TypeMode::Unicode);
+                assert_eq!(delay_ms, None);
+            }
+            _ => panic!("Expected keyboard type request"),
+        }
+    }
+
+    #[test]
+    fn test_connect_capture_audio() {
+        let req = ConnectRequest {
+            capture_audio: Some("/tmp/audio.wav".to_string()),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"capture_audio\":\"/tmp/audio.wav\""));
+
+        // Omitted by default
+        let json = serde_json::to_string(&ConnectRequest::default()).unwrap();
+        assert!(!json.contains("capture_audio"));
+    }
+
+    #[test]
+    fn test_connect_channels_default_on() {
+        let req: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#,
+        )
+        .unwrap();
+        assert!(req.enable_clipboard);
+        assert!(req.enable_audio);
+
+        let req: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,"enable_clipboard":false}"#,
+        )
+        .unwrap();
+        assert!(!req.enable_clipboard);
+        assert!(req.enable_audio);
+    }
+
+    #[test]
+    fn test_connect_record() {
+        let req = ConnectRequest {
+            record: Some("/tmp/run.gif".to_string()),
+            record_fps: Some(5),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"record\":\"/tmp/run.gif\""));
+        assert!(json.contains("\"record_fps\":5"));
+
+        let json = serde_json::to_string(&ConnectRequest::default()).unwrap();
+        assert!(!json.contains("record"));
+    }
+
+    #[test]
+    fn test_drive_push_pull_serialization() {
+        let req = Request::Drive(DriveRequest::Push {
+            local: "/tmp/report.pdf".to_string(),
+            remote: "Shared/reports".to_string(),
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"drive","action":"push","local":"/tmp/report.pdf","remote":"Shared/reports"}"#
+        );
+
+        let json = r#"{"type":"drive","action":"pull","remote":"Shared/out.txt","local":"/tmp"}"#;
+        match serde_json::from_str::<Request>(json).unwrap() {
+            Request::Drive(DriveRequest::Pull { remote, local }) => {
+                assert_eq!(remote, "Shared/out.txt");
+                assert_eq!(local, "/tmp");
+            }
+            other => panic!("Expected drive pull request, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_drive_mount_unmount_serialization() {
+        let json = r#"{"type":"drive","action":"mount","path":"/tmp/share","name":"Share"}"#;
+        match serde_json::from_str::<Request>(json).unwrap() {
+            Request::Drive(DriveRequest::Mount { path, name, read_only }) => {
+                assert_eq!(path, "/tmp/share");
+                assert_eq!(name, "Share");
+                assert!(!read_only);
+            }
+            other => panic!("Expected drive mount request, got {:?}", other),
+        }
+
+        let req = Request::Drive(DriveRequest::Unmount { name: "Share".to_string() });
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"drive","action":"unmount","name":"Share"}"#);
+    }
+
+    #[test]
+    fn test_clipboard_history_request_serialization() {
+        let req = Request::Clipboard(ClipboardRequest::History { count: Some(3) });
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"clipboard","action":"history","count":3}"#);
+
+        match serde_json::from_str(r#"{"type":"clipboard","action":"history"}"#).unwrap() {
+            Request::Clipboard(ClipboardRequest::History { count }) => assert_eq!(count, None),
+            other => panic!("Expected clipboard history request, got {:?}", other),
+        }
+
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,"clipboard_history":20}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.clipboard_history, Some(20));
+    }
+
+    #[test]
+    fn test_clipboard_line_endings() {
+        let mixed = "one\r\ntwo\nthree\rfour";
+        assert_eq!(LineEndings::Crlf.apply(mixed), "one\r\ntwo\r\nthree\rfour");
+        assert_eq!(LineEndings::Lf.apply(mixed), "one\ntwo\nthree\rfour");
+        assert_eq!(LineEndings::Preserve.apply(mixed), mixed);
+
+        // A trailing newline is converted, not dropped or doubled
+        assert_eq!(LineEndings::Crlf.apply("last\n"), "last\r\n");
+        assert_eq!(LineEndings::Crlf.apply("last\r\n"), "last\r\n");
+        assert_eq!(LineEndings::Lf.apply("last\r\n"), "last\n");
+        assert_eq!(LineEndings::Lf.apply("\r\n\r\n"), "\n\n");
+
+        let parsed: Request = serde_json::from_str(r#"{"type":"clipboard","action":"get"}"#).unwrap();
+        assert!(matches!(
+            parsed,
+            Request::Clipboard(ClipboardRequest::Get { line_endings: None })
+        ));
+        let req = Request::Clipboard(ClipboardRequest::Set {
+            text: "a".to_string(),
+            line_endings: Some(LineEndings::Preserve),
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"clipboard","action":"set","text":"a","line_endings":"preserve"}"#
+        );
+    }
+
+    #[test]
+    fn test_clipboard_clear_and_status() {
+        let json = serde_json::to_string(&Request::Clipboard(ClipboardRequest::Clear)).unwrap();
+        assert_eq!(json, r#"{"type":"clipboard","action":"clear"}"#);
+
+        let json = r#"{"type":"clipboard","action":"status"}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        assert!(matches!(parsed, Request::Clipboard(ClipboardRequest::Status)));
+    }
+
+    #[test]
+    fn test_connect_redirect_smartcard() {
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,"redirect_smartcard":true}"#,
+        )
+        .unwrap();
+        assert!(parsed.redirect_smartcard);
+
+        // Off unless requested
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#,
+        )
+        .unwrap();
+        assert!(!parsed.redirect_smartcard);
+    }
+
+    #[test]
+    fn test_connect_idle_timeout() {
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,"idle_timeout_secs":600}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.idle_timeout_secs, Some(600));
+
+        // Omitted by default
+        let json = serde_json::to_string(&ConnectRequest::default()).unwrap();
+        assert!(!json.contains("idle_timeout_secs"));
+    }
+
+    #[test]
+    fn test_connect_stream_max_frame_size() {
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,"stream_max_frame_size":1920}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.stream_max_frame_size, Some(1920));
+
+        let json = serde_json::to_string(&ConnectRequest::default()).unwrap();
+        assert!(!json.contains("stream_max_frame_size"));
+    }
+
+    #[test]
+    fn test_connect_keepalive_interval() {
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,"keepalive_interval_secs":30}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.keepalive_interval_secs, Some(30));
+
+        let json = serde_json::to_string(&ConnectRequest::default()).unwrap();
+        assert!(!json.contains("keepalive_interval_secs"));
+    }
+
+    #[test]
+    fn test_connect_client_identity() {
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,"client_name":"BUILD-07","client_build":19045,"hardware_id":"00112233-4455-6677-8899-aabbccddeeff"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.client_name.as_deref(), Some("BUILD-07"));
+        assert_eq!(parsed.client_build, Some(19045));
+        assert_eq!(parsed.dig_product_id, None);
+        assert_eq!(
+            parsed.hardware_id.as_deref(),
+            Some("00112233-4455-6677-8899-aabbccddeeff")
+        );
+
+        // Omitted by default
+        let json = serde_json::to_string(&ConnectRequest::default()).unwrap();
+        assert!(!json.contains("client_name"));
+        assert!(!json.contains("client_build"));
+        assert!(!json.contains("dig_product_id"));
+        assert!(!json.contains("hardware_id"));
+    }
+
+    #[test]
+    fn test_screenshot_region() {
+        let req = Request::Screenshot(ScreenshotRequest {
+            format: ImageFormat::Png,
+            x: Some(10),
+            y: Some(20),
+            width: Some(200),
+            height: Some(100),
+            monitor: None,
+            draw_cursor: true,
+            stable_ms: None,
+            stable_timeout_ms: None,
+            if_changed_since: None,
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"x\":10"));
+        assert!(json.contains("\"draw_cursor\":true"));
+        assert!(!json.contains("monitor"));
+        assert!(!json.contains("stable"));
+        assert!(json.contains("\"height\":100"));
+
+        // Region fields are optional (full frame)
+        let req: Request = serde_json::from_str(r#"{"type":"screenshot","format":"png"}"#).unwrap();
+        match req {
+            Request::Screenshot(s) => {
+                assert!(s.x.is_none() && s.y.is_none() && s.width.is_none() && s.height.is_none());
+                assert!(!s.draw_cursor);
+                assert!(s.stable_ms.is_none());
+                assert!(s.if_changed_since.is_none());
+            }
+            _ => panic!("Expected Screenshot request"),
+        }
+    }
+
+    #[test]
+    fn test_frame_request() {
+        // Raw, base64-encoded is the default
+        let req: Request = serde_json::from_str(r#"{"type":"frame"}"#).unwrap();
+        match req {
+            Request::Frame(f) => {
+                assert_eq!(f.encoding, FrameEncoding::Raw);
+                assert!(!f.binary);
+            }
+            _ => panic!("Expected Frame request"),
+        }
+
+        let req = Request::Frame(FrameRequest { encoding: FrameEncoding::Png, binary: true });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"type\":\"frame\""));
+        assert!(json.contains("\"encoding\":\"png\""));
+        assert!(json.contains("\"binary\":true"));
+    }
+
+    #[test]
+    fn test_connect_monitors() {
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600,
+                "monitors":[{"x":0,"y":0,"width":1920,"height":1080},
+                            {"x":-1280,"y":0,"width":1280,"height":1024,"primary":false}]}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.monitors.len(), 2);
+        assert_eq!(parsed.monitors[1].x, -1280);
+        assert!(!parsed.monitors[0].primary);
+
+        // Single monitor by default
+        let parsed: ConnectRequest = serde_json::from_str(
+            r#"{"host":"h","port":3389,"username":"u","password":"p","width":800,"height":600}"#,
+        )
+        .unwrap();
+        assert!(parsed.monitors.is_empty());
+    }
+
+    #[test]
+    fn test_clipboard_image_request_serialization() {
+        let req = Request::Clipboard(ClipboardRequest::SetImage {
+            base64: "iVBORw0KGgo=".to_string(),
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"type\":\"clipboard\""));
+        assert!(json.contains("\"action\":\"set_image\""));
+        assert!(json.contains("\"base64\":\"iVBORw0KGgo=\""));
+
+        let req: Request =
+            serde_json::from_str(r#"{"type":"clipboard","action":"get_image"}"#).unwrap();
+        assert!(matches!(req, Request::Clipboard(ClipboardRequest::GetImage)));
+    }
+
+    #[test]
+    fn test_clipboard_html_request_serialization() {
+        let req = Request::Clipboard(ClipboardRequest::SetHtml {
+            html: "<b>bold</b>".to_string(),
+            text: None,
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"action\":\"set_html\""));
+        assert!(json.contains("\"html\":\"<b>bold</b>\""));
+        assert!(!json.contains("\"text\""));
+
+        let req: Request = serde_json::from_str(
+            r#"{"type":"clipboard","action":"set_html","html":"<i>x</i>","text":"x"}"#,
+        )
+        .unwrap();
+        match req {
+            Request::Clipboard(ClipboardRequest::SetHtml { html, text }) => {
+                assert_eq!(html, "<i>x</i>");
+                assert_eq!(text.as_deref(), Some("x"));
+            }
+            _ => panic!("Expected SetHtml request"),
+        }
+
+        let req: Request =
+            serde_json::from_str(r#"{"type":"clipboard","action":"get_html"}"#).unwrap();
+        assert!(matches!(req, Request::Clipboard(ClipboardRequest::GetHtml)));
+    }
+
+    #[test]
+    fn test_clipboard_files_request_serialization() {
+        let req = Request::Clipboard(ClipboardRequest::SetFiles {
+            paths: vec!["/tmp/report.pdf".to_string(), "/tmp/logs".to_string()],
+            max_bytes: None,
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"action\":\"set_files\""));
+        assert!(json.contains("\"paths\":[\"/tmp/report.pdf\",\"/tmp/logs\"]"));
+        assert!(!json.contains("max_bytes"));
+
+        let req: Request = serde_json::from_str(
+            r#"{"type":"clipboard","action":"get_files","dest_dir":"/tmp/out","max_bytes":1024}"#,
+        )
+        .unwrap();
+        match req {
+            Request::Clipboard(ClipboardRequest::GetFiles { dest_dir, max_bytes }) => {
+                assert_eq!(dest_dir, "/tmp/out");
+                assert_eq!(max_bytes, Some(1024));
+            }
+            _ => panic!("Expected GetFiles request"),
+        }
+    }
+
+    #[test]
+    fn test_input_batch_serialization() {
+        let req = Request::InputBatch(InputBatchRequest {
+            events: vec![
+                InputEvent::Mouse(MouseRequest::Click { x: 10, y: 20, human: false }),
+                InputEvent::Keyboard(KeyboardRequest::Type {
+                    text: "hello".to_string(),
+                    mode: TypeMode::Unicode,
+                    delay_ms: None,
+                }),
+                InputEvent::Wait { ms: 500 },
+            ],
+            delay_ms: None,
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"type\":\"input_batch\""));
+        assert!(json.contains("{\"type\":\"mouse\",\"action\":\"click\",\"x\":10,\"y\":20}"));
+        assert!(json.contains("{\"type\":\"wait\",\"ms\":500}"));
+        assert!(!json.contains("delay_ms"));
+
+        let req: Request = serde_json::from_str(
+            r#"{"type":"input_batch","delay_ms":5,"events":[{"type":"keyboard","action":"press","keys":"ctrl+s"},{"type":"scroll","direction":"down"}]}"#,
+        )
+        .unwrap();
+        match req {
+            Request::InputBatch(batch) => {
+                assert_eq!(batch.delay_ms, Some(5));
+                assert!(matches!(
+                    batch.events[0],
+                    InputEvent::Keyboard(KeyboardRequest::Press { ref keys }) if keys == "ctrl+s"
+                ));
+                assert!(matches!(
+                    batch.events[1],
+                    InputEvent::Scroll(ScrollRequest { amount: 3, .. })
+                ));
+            }
+            _ => panic!("Expected InputBatch request"),
+        }
+    }
+
+    #[test]
+    fn test_resize_request_serialization() {
+        let req = Request::Resize {
+            width: 1920,
+            height: 1080,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"resize","width":1920,"height":1080}"#);
+    }
+
+    #[test]
+    fn test_stream_config_request_serialization() {
+        let req = Request::StreamConfig {
+            fps: Some(5),
+            quality: Some(50),
+            delta: None,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"stream_config","fps":5,"quality":50}"#);
+
+        let req: Request = serde_json::from_str(r#"{"type":"stream_config","delta":true}"#).unwrap();
+        assert!(matches!(
+            req,
+            Request::StreamConfig { fps: None, quality: None, delta: Some(true) }
+        ));
+    }
+
+    #[test]
+    fn test_hello_request_serialization() {
+        let req = Request::Hello { framing: IpcFraming::LengthPrefixed };
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"hello","framing":"length_prefixed"}"#);
+    }
+
+    #[test]
+    fn test_request_envelope_id() {
+        let json = r#"{"id":7,"type":"automate","op":"focus","selector":"@e1","agent_timeout_ms":500}"#;
+        let envelope: RequestEnvelope = serde_json::from_str(json).unwrap();
+        assert_eq!(envelope.id, Some(7));
+        assert!(matches!(
+            envelope.request,
+            Request::Automate {
+                request: AutomateRequest::Focus { .. },
+                agent_timeout_ms: Some(500),
+            }
+        ));
+
+        // Plain requests are envelopes without an ID
+        let envelope: RequestEnvelope = serde_json::from_str(r#"{"type":"ping"}"#).unwrap();
+        assert_eq!(envelope.id, None);
+        assert!(matches!(envelope.request, Request::Ping));
+        let json = serde_json::to_string(&RequestEnvelope::from(Request::Ping)).unwrap();
+        assert_eq!(json, r#"{"type":"ping"}"#);
+    }
+
+    #[test]
+    fn test_automate_request_agent_timeout() {
+        let json = r##"{"type":"automate","op":"wait_for","selector":"#Save","timeout_ms":30000,"agent_timeout_ms":2000}"##;
+        match serde_json::from_str::<Request>(json).unwrap() {
+            Request::Automate {
+                request: AutomateRequest::WaitFor { timeout_ms, .. },
+                agent_timeout_ms,
+            } => {
+                assert_eq!(timeout_ms, 30000);
+                assert_eq!(agent_timeout_ms, Some(2000));
+            }
+            other => panic!("Expected automate wait_for, got {:?}", other),
+        }
+
+        let req = Request::Automate {
+            request: AutomateRequest::Focus {
+                selector: "@e1".to_string(),
+            },
+            agent_timeout_ms: None,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"automate","op":"focus","selector":"@e1"}"#);
+    }
+
     #[test]
     fn test_mouse_request_serialization() {
-        let req = Request::Mouse(MouseRequest::Click { x: 100, y: 200 });
+        let req = Request::Mouse(MouseRequest::Click { x: 100, y: 200, human: false });
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"action\":\"click\""));
         assert!(json.contains("\"x\":100"));
     }
 
+    #[test]
+    fn test_mouse_move_human() {
+        // Instant moves keep the plain wire format
+        let json = r#"{"type":"mouse","action":"move","x":5,"y":6}"#;
+        match serde_json::from_str::<Request>(json).unwrap() {
+            Request::Mouse(MouseRequest::Move { human, .. }) => assert!(!human),
+            _ => panic!("unexpected request type"),
+        }
+
+        let req = Request::Mouse(MouseRequest::Move { x: 5, y: 6, human: true });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"human\":true"));
+    }
+
+    #[test]
+    fn test_locate_region() {
+        let json = r#"{"type":"locate","text":"OK"}"#;
+        match serde_json::from_str::<Request>(json).unwrap() {
+            Request::Locate(l) => {
+                assert!(l.ignore_case);
+                assert!(l.region.is_none());
+            }
+            _ => panic!("unexpected request type"),
+        }
+
+        let req = Request::Locate(LocateRequest {
+            text: "OK".to_string(),
+            pattern: false,
+            ignore_case: true,
+            all: false,
+            region: Some(ElementBounds {
+                x: 0,
+                y: 1040,
+                width: 1920,
+                height: 40,
+            }),
+            models_dir: None,
+            lang: None,
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"region\":{\"x\":0,\"y\":1040,\"width\":1920,\"height\":40}"));
+    }
+
+    #[test]
+    fn test_locate_click_defaults() {
+        let json = r#"{"type":"locate_click","text":"Save"}"#;
+        match serde_json::from_str::<Request>(json).unwrap() {
+            Request::LocateClick(l) => {
+                assert_eq!(l.text, "Save");
+                assert!(l.ignore_case);
+                assert!(!l.pattern);
+                assert!(l.button.is_none() && l.index.is_none() && l.min_confidence.is_none());
+            }
+            _ => panic!("unexpected request type"),
+        }
+
+        let req = Request::LocateClick(LocateClickRequest {
+            text: "OK".to_string(),
+            pattern: false,
+            ignore_case: true,
+            region: None,
+            button: Some(MouseButton::Right),
+            index: Some(1),
+            min_confidence: None,
+            models_dir: None,
+            lang: None,
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"button\":\"right\""));
+        assert!(json.contains("\"index\":1"));
+        assert!(!json.contains("min_confidence"));
+    }
+
+    #[test]
+    fn test_mouse_down_up() {
+        let json = r#"{"type":"mouse","action":"down","x":1,"y":2}"#;
+        match serde_json::from_str::<Request>(json).unwrap() {
+            Request::Mouse(MouseRequest::Down { x, y, button }) => {
+                assert_eq!((x, y, button), (1, 2, MouseButton::Left))
+            }
+            _ => panic!("unexpected request type"),
+        }
+
+        let req = Request::Mouse(MouseRequest::Up { x: 3, y: 4, button: MouseButton::Right });
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"mouse","action":"up","x":3,"y":4,"button":"right"}"#);
+
+        let json = serde_json::to_string(&Request::Mouse(MouseRequest::ReleaseAll)).unwrap();
+        assert_eq!(json, r#"{"type":"mouse","action":"release_all"}"#);
+    }
+
+    #[test]
+    fn test_mouse_drag_steps() {
+        let json = r#"{"type":"mouse","action":"drag","from_x":1,"from_y":2,"to_x":3,"to_y":4}"#;
+        match serde_json::from_str::<Request>(json).unwrap() {
+            Request::Mouse(MouseRequest::Drag { steps, .. }) => assert_eq!(steps, None),
+            _ => panic!("unexpected request type"),
+        }
+
+        let req = Request::Mouse(MouseRequest::Drag {
+            from_x: 1,
+            from_y: 2,
+            to_x: 3,
+            to_y: 4,
+            steps: Some(20),
+        });
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"steps\":20"));
+    }
+
     #[test]
     fn test_keyboard_request_serialization() {
         let req = Request::Keyboard(KeyboardRequest::Press {
@@ -395,4 +2134,52 @@ mod tests {
         assert!(json.contains("\"action\":\"press\""));
         assert!(json.contains("ctrl+c"));
     }
+
+    #[test]
+    fn test_keyboard_raw_and_hold() {
+        let req: Request =
+            serde_json::from_str(r#"{"type":"keyboard","action":"raw","scancode":29,"extended":true}"#)
+                .unwrap();
+        match req {
+            Request::Keyboard(KeyboardRequest::Raw {
+                scancode,
+                extended,
+                release,
+            }) => {
+                assert_eq!(scancode, 0x1D);
+                assert!(extended);
+                assert!(!release);
+            }
+            _ => panic!("unexpected request type"),
+        }
+
+        let json = serde_json::to_string(&Request::Keyboard(KeyboardRequest::Hold {
+            keys: "shift".to_string(),
+        }))
+        .unwrap();
+        assert!(json.contains("\"action\":\"hold\""));
+
+        let json = serde_json::to_string(&Request::Keyboard(KeyboardRequest::ReleaseAll)).unwrap();
+        assert_eq!(json, r#"{"type":"keyboard","action":"release_all"}"#);
+    }
+
+    #[test]
+    fn test_keyboard_paste_serialization() {
+        let json = r#"{"type":"keyboard","action":"paste","text":"line 1\nline 2"}"#;
+        match serde_json::from_str(json).unwrap() {
+            Request::Keyboard(KeyboardRequest::Paste { text, restore }) => {
+                assert_eq!(text, "line 1\nline 2");
+                assert!(!restore);
+            }
+            _ => panic!("unexpected request type"),
+        }
+
+        let json = serde_json::to_string(&Request::Keyboard(KeyboardRequest::Paste {
+            text: "hello".to_string(),
+            restore: true,
+        }))
+        .unwrap();
+        assert!(json.contains("\"action\":\"paste\""));
+        assert!(json.contains("\"restore\":true"));
+    }
 }
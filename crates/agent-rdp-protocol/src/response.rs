@@ -1,8 +1,12 @@
 //! Response types for daemon to CLI communication.
 
 use crate::automation::{
-    AccessibilitySnapshot, AutomationStatus, ClickResult, ElementValue, RunResult, WindowInfo,
+    AccessibilitySnapshot, AutomationStatus, ClickResult, DragResult, ElementScreenshot,
+    ElementValue, FormattedSnapshot, HighlightResult, RunResult, TypeResult, WaitForResult,
+    WindowInfo,
 };
+use crate::framing::IpcFraming;
+use crate::request::{FrameEncoding, MonitorLayout};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use ts_rs::TS;
@@ -11,6 +15,11 @@ use ts_rs::TS;
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
 pub struct Response {
+    /// ID of the request this answers, if it had one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub id: Option<u64>,
+
     /// Whether the operation succeeded.
     pub success: bool,
 
@@ -29,6 +38,7 @@ impl Response {
     /// Create a successful response with data.
     pub fn success(data: ResponseData) -> Self {
         Self {
+            id: None,
             success: true,
             data: Some(data),
             error: None,
@@ -38,6 +48,7 @@ impl Response {
     /// Create a simple success response with no data.
     pub fn ok() -> Self {
         Self {
+            id: None,
             success: true,
             data: Some(ResponseData::Ok),
             error: None,
@@ -47,6 +58,7 @@ impl Response {
     /// Create an error response.
     pub fn error(code: ErrorCode, message: impl Into<String>) -> Self {
         Self {
+            id: None,
             success: false,
             data: None,
             error: Some(ErrorInfo {
@@ -55,6 +67,30 @@ impl Response {
             }),
         }
     }
+
+    /// Binary data sent outside the JSON, after the line or in the frame's
+    /// binary region.
+    pub fn payload(&self) -> Option<&[u8]> {
+        match &self.data {
+            Some(ResponseData::Frame { binary: true, payload, .. }) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Number of bytes that follow this response's line.
+    pub fn payload_len(&self) -> usize {
+        match &self.data {
+            Some(ResponseData::Frame { binary: true, size_bytes, .. }) => *size_bytes as usize,
+            _ => 0,
+        }
+    }
+
+    /// Attach binary data received outside the JSON, where `payload` finds it.
+    pub fn attach_payload(&mut self, data: Vec<u8>) {
+        if let Some(ResponseData::Frame { binary: true, payload, .. }) = &mut self.data {
+            *payload = data;
+        }
+    }
 }
 
 /// Response data variants.
@@ -73,6 +109,33 @@ pub enum ResponseData {
         width: u16,
         /// Desktop height.
         height: u16,
+        /// Result of the input self-check, when one was requested.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        input_check: Option<InputCheck>,
+        /// Whether the first graphics update arrived, when `wait_first_frame`
+        /// was requested. Until it does, the desktop image is black.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        first_frame_received: Option<bool>,
+    },
+
+    /// Desktop resized.
+    Resized {
+        /// New desktop width.
+        width: u16,
+        /// New desktop height.
+        height: u16,
+    },
+
+    /// WebSocket stream settings now in effect.
+    StreamConfig {
+        /// Frames per second.
+        fps: u32,
+        /// JPEG quality (1-100).
+        quality: u8,
+        /// Whether frames are delta-encoded.
+        delta: bool,
     },
 
     /// Screenshot data.
@@ -83,8 +146,64 @@ pub enum ResponseData {
         height: u32,
         /// Image format.
         format: String,
+        /// Encoded image size in bytes.
+        #[serde(default)]
+        #[ts(type = "number")]
+        size_bytes: u64,
         /// Base64-encoded image data.
         base64: String,
+        /// Milliseconds waited for the desktop to settle (when `stable_ms` was given).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional, type = "number")]
+        stable_wait_ms: Option<u64>,
+        /// Whether the desktop settled before the timeout (when `stable_ms` was given).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        stable: Option<bool>,
+        /// Hash of the captured pixels and format, to pass as `if_changed_since`.
+        #[serde(default)]
+        etag: String,
+        /// Whether the server has sent any graphics yet. When false the image
+        /// is the blank desktop from before the first update, not the screen.
+        #[serde(default = "default_true")]
+        first_frame_received: bool,
+    },
+
+    /// Screenshot identical to the one named by `if_changed_since`; no image
+    /// was encoded.
+    NotModified {
+        /// `etag` of the capture (the same as the one given).
+        etag: String,
+        /// Image width.
+        width: u32,
+        /// Image height.
+        height: u32,
+    },
+
+    /// Desktop framebuffer.
+    Frame {
+        /// Width in pixels.
+        width: u32,
+        /// Height in pixels.
+        height: u32,
+        /// Bytes per row of raw pixels (0 for PNG).
+        stride: u32,
+        /// Pixel encoding.
+        encoding: FrameEncoding,
+        /// Size of the pixel data in bytes.
+        #[ts(type = "number")]
+        size_bytes: u64,
+        /// Base64-encoded pixel data, unless sent as binary.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        data: Option<String>,
+        /// Whether `size_bytes` raw bytes follow the response line.
+        #[serde(default)]
+        binary: bool,
+        /// Pixel data written after the response line when `binary` is set.
+        #[serde(skip)]
+        #[ts(skip)]
+        payload: Vec<u8>,
     },
 
     /// Clipboard text content.
@@ -93,27 +212,112 @@ pub enum ResponseData {
         text: String,
     },
 
+    /// Clipboard image content.
+    ClipboardImage {
+        /// Image width.
+        width: u32,
+        /// Image height.
+        height: u32,
+        /// Base64-encoded PNG data.
+        base64: String,
+    },
+
+    /// Clipboard HTML content.
+    ClipboardHtml {
+        /// HTML fragment that was copied.
+        html: String,
+    },
+
+    /// Files transferred through the clipboard.
+    ClipboardFiles {
+        /// Saved local paths (get-files) or the names offered to the remote (set-files).
+        files: Vec<String>,
+        /// Total bytes of file data.
+        #[ts(type = "number")]
+        total_bytes: u64,
+    },
+
+    /// Recent remote clipboard text.
+    ClipboardHistory {
+        /// Entries, newest first.
+        entries: Vec<ClipboardHistoryEntry>,
+    },
+
+    /// Kinds of content offered by the remote's last copy.
+    ClipboardStatus {
+        /// Text is available (`clipboard get`).
+        text: bool,
+        /// A bitmap is available (`clipboard get-image`).
+        image: bool,
+        /// HTML is available (`clipboard get-html`).
+        html: bool,
+        /// Files are available (`clipboard get-files`).
+        files: bool,
+    },
+
     /// Session information.
     SessionInfo(SessionInfo),
 
+    /// Result of a connect dry run.
+    DryRun(DryRunResult),
+
+    /// Server certificate fingerprint.
+    CertFingerprint {
+        /// SHA-256 fingerprint of the certificate, as hex.
+        sha256: String,
+    },
+
     /// List of mapped drives.
     DriveList {
         /// Mapped drives.
         drives: Vec<MappedDrive>,
     },
 
+    /// Files copied into or out of a mapped drive.
+    DriveCopy {
+        /// Local path the copy was written to.
+        destination: String,
+        /// Number of files copied.
+        files: u32,
+        /// Total bytes copied.
+        #[ts(type = "number")]
+        total_bytes: u64,
+    },
+
     /// List of active sessions.
     SessionList {
         /// Active sessions.
         sessions: Vec<SessionSummary>,
     },
 
+    /// Sessions torn down by `session kill`.
+    SessionKill {
+        /// What happened to each session.
+        sessions: Vec<KilledSession>,
+    },
+
     /// Pong response for ping.
-    Pong,
+    Pong {
+        /// IPC protocol version the daemon speaks (0 for daemons that predate it).
+        #[serde(default)]
+        protocol_version: u32,
+        /// Daemon release version.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        version: Option<String>,
+    },
+
+    /// Framing accepted by `hello`, used from the next message on.
+    Hello {
+        framing: IpcFraming,
+    },
 
     /// Accessibility tree snapshot.
     Snapshot(AccessibilitySnapshot),
 
+    /// Accessibility tree snapshot rendered as YAML or text.
+    FormattedSnapshot(FormattedSnapshot),
+
     /// Element value/properties.
     Element(ElementValue),
 
@@ -132,8 +336,142 @@ pub enum ResponseData {
     /// Click action result.
     ClickResult(ClickResult),
 
+    /// Drag action result.
+    DragResult(DragResult),
+
+    /// Highlight action result.
+    HighlightResult(HighlightResult),
+
+    /// Image of one element.
+    ElementScreenshot(ElementScreenshot),
+
+    /// Type action result.
+    TypeResult(TypeResult),
+
+    /// Element wait result.
+    WaitForResult(WaitForResult),
+
     /// OCR locate result.
     LocateResult(LocateResult),
+
+    /// Text found with OCR and clicked.
+    LocateClicked {
+        /// The line that was clicked.
+        matched: OcrMatch,
+        /// Where the click landed.
+        x: u16,
+        y: u16,
+        /// How many lines matched (at or above the confidence threshold).
+        match_count: u32,
+    },
+
+    /// Result of waiting for the screen to change.
+    ScreenChange {
+        /// Whether the watched pixels changed before the timeout.
+        changed: bool,
+        /// Time spent waiting in milliseconds.
+        #[ts(type = "number")]
+        elapsed_ms: u64,
+    },
+
+    /// Mouse pointer position and shape.
+    CursorInfo(CursorInfo),
+}
+
+/// Text from one remote copy.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct ClipboardHistoryEntry {
+    /// Copied text.
+    pub text: String,
+
+    /// When the text was copied, in milliseconds since the Unix epoch.
+    #[ts(type = "number")]
+    pub copied_at_ms: u64,
+}
+
+/// Mouse pointer position and shape.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct CursorInfo {
+    /// Pointer X position on the desktop.
+    pub x: u16,
+
+    /// Pointer Y position on the desktop.
+    pub y: u16,
+
+    /// Whether the pointer is shown (the server hides it, e.g. while typing).
+    pub visible: bool,
+
+    /// Pointer image width (when the server sent a pointer shape).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub width: Option<u16>,
+
+    /// Pointer image height (when the server sent a pointer shape).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub height: Option<u16>,
+
+    /// X offset of the pointer's hot spot (the pixel at x, y) in the image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub hotspot_x: Option<u16>,
+
+    /// Y offset of the pointer's hot spot in the image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub hotspot_y: Option<u16>,
+
+    /// Base64-encoded PNG of the pointer image (only when requested).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub base64: Option<String>,
+}
+
+/// How far a connect dry run got before disconnecting.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct DryRunResult {
+    /// Server host.
+    pub host: String,
+
+    /// Server port.
+    pub port: u16,
+
+    /// The server (or the gateway tunnel to it) accepted the TCP connection.
+    pub reachable: bool,
+
+    /// TLS was negotiated.
+    pub tls_ok: bool,
+
+    /// The credentials were accepted and the connection sequence completed.
+    pub auth_ok: bool,
+
+    /// Desktop width negotiated by the server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub width: Option<u16>,
+
+    /// Desktop height negotiated by the server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub height: Option<u16>,
+
+    /// SHA-256 fingerprint of the server certificate (hex).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cert_fingerprint: Option<String>,
+
+    /// Why the handshake stopped, if it failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+
+    /// Error code of the failure, as a real connect would report it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error_code: Option<ErrorCode>,
 }
 
 /// Session information.
@@ -161,6 +499,65 @@ pub struct SessionInfo {
     #[ts(optional)]
     pub height: Option<u16>,
 
+    /// Keyboard layout sent to the server (if connected).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub keyboard_layout: Option<u32>,
+
+    /// SHA-256 fingerprint of the server certificate (if connected).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cert_fingerprint: Option<String>,
+
+    /// Monitors of the desktop, in screenshot/mouse coordinates (if connected).
+    #[serde(default)]
+    pub monitors: Vec<MonitorLayout>,
+
+    /// Why the RDP connection last ended (if it has).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub last_disconnect: Option<DisconnectInfo>,
+
+    /// How long the latest connection handshake took, in milliseconds (if connected).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub connect_ms: Option<u64>,
+
+    /// Rolling average round trip from input to the next server frame, in
+    /// milliseconds (once measured).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub rtt_ms: Option<u32>,
+
+    /// Most recent round trip sample, in milliseconds (once measured).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub last_rtt_ms: Option<u32>,
+
+    /// Frames received from the server (if connected).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub frames_received: Option<u64>,
+
+    /// Bytes received from the server (if connected).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub bytes_received: Option<u64>,
+
+    /// WebSocket streaming port (if streaming is enabled).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub stream_port: Option<u16>,
+
+    /// Token viewers must present to the streaming server (if streaming is enabled).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub stream_token: Option<String>,
+
+    /// Whether the streaming port also serves the HTML viewer (not just WebSocket clients).
+    #[serde(default)]
+    pub stream_viewer: bool,
+
     /// Daemon process ID.
     pub pid: u32,
 
@@ -180,10 +577,70 @@ pub enum ConnectionState {
     Connecting,
     /// Connected and active.
     Connected,
+    /// Connection dropped; reconnection in progress.
+    Reconnecting,
     /// Connection failed.
     Failed,
 }
 
+/// Outcome of the input self-check run after connecting.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum InputCheckStatus {
+    /// Keyboard input reached the remote desktop.
+    Working,
+    /// Keyboard input was sent but had no effect.
+    NotReaching,
+    /// Input goes to a secure desktop (a UAC prompt, the lock or logon
+    /// screen), which ignores synthesized input.
+    SecureDesktop,
+    /// The check needs the automation agent, which isn't running.
+    Unverified,
+}
+
+/// Whether input injection works on the remote desktop.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct InputCheck {
+    pub status: InputCheckStatus,
+    /// What was found, for people reading the output.
+    pub message: String,
+}
+
+/// Why an RDP session ended.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum DisconnectReason {
+    /// The user logged off.
+    LoggedOff,
+    /// The server disconnected an idle session.
+    IdleTimeout,
+    /// Nobody logged on before the logon timeout.
+    LogonTimeout,
+    /// An administrator (or the user from inside the session) disconnected it.
+    AdminDisconnected,
+    /// Another connection took over the session.
+    ReplacedByOtherConnection,
+    /// The server ended the session for another reason.
+    ServerTerminated,
+    /// The session failed at the protocol level.
+    ProtocolError,
+    /// The network connection dropped and was not re-established.
+    ConnectionLost,
+}
+
+/// The reason a session ended, with the server's description.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct DisconnectInfo {
+    /// Classified reason.
+    pub reason: DisconnectReason,
+    /// Detail from the server or the transport error.
+    pub message: String,
+}
+
 /// Summary of a session for listing.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
@@ -196,6 +653,51 @@ pub struct SessionSummary {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub host: Option<String>,
+    /// Daemon process ID (if the session has a readable PID file).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub pid: Option<u32>,
+    /// Whether the daemon process is running (false for stale session directories).
+    #[serde(default)]
+    pub alive: bool,
+    /// Time since daemon started (seconds, if it answered the probe).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub uptime_secs: Option<u64>,
+    /// Whether the stale session directory was removed.
+    #[serde(default)]
+    pub pruned: bool,
+}
+
+/// What `session kill` did to a session's daemon.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+#[serde(rename_all = "snake_case")]
+pub enum KillOutcome {
+    /// The daemon was running and has been stopped.
+    Killed,
+    /// No daemon was running; only leftover files were removed.
+    AlreadyDead,
+    /// The daemon is still running.
+    Failed,
+}
+
+/// Result of tearing down one session.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../packages/agent-rdp/src/generated/")]
+pub struct KilledSession {
+    /// Session name.
+    pub name: String,
+    /// Daemon process ID from the PID file, if it had one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub pid: Option<u32>,
+    /// What happened to the daemon.
+    pub outcome: KillOutcome,
+    /// Why the daemon couldn't be stopped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
 }
 
 /// Mapped drive information.
@@ -206,6 +708,23 @@ pub struct MappedDrive {
     pub name: String,
     /// Local path.
     pub path: String,
+    /// Whether the drive is mapped read-only.
+    #[serde(default)]
+    pub read_only: bool,
+    /// RDPDR device ID the drive is announced under (0 if unknown).
+    #[serde(default)]
+    pub device_id: u32,
+    /// Path the remote sees the drive at (`\\TSCLIENT\Name`).
+    #[serde(default)]
+    pub unc_path: String,
+    /// Size of the volume holding the local path, as reported to the remote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub total_bytes: Option<u64>,
+    /// Free space on that volume available to the daemon.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub free_bytes: Option<u64>,
 }
 
 /// OCR locate result.
@@ -236,6 +755,9 @@ pub struct OcrMatch {
     pub center_x: i32,
     /// Center Y coordinate (for clicking).
     pub center_y: i32,
+    /// Detection confidence (0.0 to 1.0).
+    #[serde(default)]
+    pub confidence: f32,
 }
 
 /// Error information.
@@ -269,6 +791,35 @@ pub enum ErrorCode {
     #[error("authentication failed")]
     AuthenticationFailed,
 
+    /// The host actively refused the TCP connection (nothing listening on the port).
+    #[error("connection refused")]
+    ConnectionRefused,
+
+    /// The host name could not be resolved.
+    #[error("host not found")]
+    HostNotFound,
+
+    /// The server reset or closed the connection during the handshake.
+    #[error("connection reset")]
+    ConnectionReset,
+
+    /// TLS handshake failed.
+    #[error("tls failed")]
+    TlsFailed,
+
+    /// The server certificate doesn't match the pinned fingerprint.
+    #[error("certificate mismatch")]
+    CertificateMismatch,
+
+    /// The credentials were accepted but the account may not log on remotely
+    /// (no Remote Desktop rights, disabled account, expired password, ...).
+    #[error("logon denied")]
+    LogonDenied,
+
+    /// The account is locked out after too many failed logons.
+    #[error("account locked")]
+    AccountLocked,
+
     /// Connection timed out.
     #[error("timeout")]
     Timeout,
@@ -317,6 +868,14 @@ pub enum ErrorCode {
     #[error("element not found")]
     ElementNotFound,
 
+    /// No text on screen matched the search.
+    #[error("text not found")]
+    TextNotFound,
+
+    /// The requested OCR language has no model installed.
+    #[error("language not available")]
+    LanguageNotAvailable,
+
     /// Stale element reference.
     #[error("stale reference")]
     StaleRef,
@@ -326,6 +885,10 @@ pub enum ErrorCode {
     CommandFailed,
 }
 
+fn default_true() -> bool {
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -336,11 +899,33 @@ mod tests {
             host: "192.168.1.100".to_string(),
             width: 1920,
             height: 1080,
+            input_check: None,
+            first_frame_received: None,
         });
 
         let json = serde_json::to_string(&resp).unwrap();
         assert!(json.contains("\"success\":true"));
         assert!(json.contains("\"type\":\"connected\""));
+        assert!(!json.contains("input_check"));
+    }
+
+    #[test]
+    fn test_connected_input_check_serialization() {
+        let resp = Response::success(ResponseData::Connected {
+            host: "h".to_string(),
+            width: 800,
+            height: 600,
+            input_check: Some(InputCheck {
+                status: InputCheckStatus::SecureDesktop,
+                message: "UAC prompt".to_string(),
+            }),
+            first_frame_received: Some(true),
+        });
+
+        let json = serde_json::to_string(&resp).unwrap();
+        let expected = r#""input_check":{"status":"secure_desktop","message":"UAC prompt"}"#;
+        assert!(json.contains(expected));
+        assert!(json.contains(r#""first_frame_received":true"#));
     }
 
     #[test]
@@ -352,16 +937,367 @@ mod tests {
         assert!(json.contains("\"code\":\"connection_failed\""));
     }
 
+    #[test]
+    fn test_connect_error_codes_serialization() {
+        let codes = [
+            (ErrorCode::ConnectionRefused, "\"connection_refused\""),
+            (ErrorCode::HostNotFound, "\"host_not_found\""),
+            (ErrorCode::ConnectionReset, "\"connection_reset\""),
+            (ErrorCode::TlsFailed, "\"tls_failed\""),
+            (ErrorCode::CertificateMismatch, "\"certificate_mismatch\""),
+            (ErrorCode::LogonDenied, "\"logon_denied\""),
+            (ErrorCode::AccountLocked, "\"account_locked\""),
+        ];
+        for (code, json) in codes {
+            assert_eq!(serde_json::to_string(&code).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn test_stream_config_response() {
+        let resp = Response::success(ResponseData::StreamConfig {
+            fps: 5,
+            quality: 50,
+            delta: false,
+        });
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""type":"stream_config","fps":5,"quality":50,"delta":false"#));
+    }
+
+    #[test]
+    fn test_pong_version() {
+        let resp = Response::success(ResponseData::Pong {
+            protocol_version: crate::PROTOCOL_VERSION,
+            version: Some("1.2.3".to_string()),
+        });
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""type":"pong","protocol_version":1,"version":"1.2.3""#));
+
+        // Daemons from before the version check answer with a bare pong
+        let old: Response =
+            serde_json::from_str(r#"{"success":true,"data":{"type":"pong"}}"#).unwrap();
+        assert!(matches!(
+            old.data,
+            Some(ResponseData::Pong { protocol_version: 0, version: None })
+        ));
+    }
+
+    #[test]
+    fn test_response_payload() {
+        let mut resp = Response::success(ResponseData::Frame {
+            width: 2,
+            height: 1,
+            stride: 8,
+            encoding: FrameEncoding::Raw,
+            size_bytes: 8,
+            data: None,
+            binary: true,
+            payload: Vec::new(),
+        });
+        assert_eq!(resp.payload_len(), 8);
+        resp.attach_payload(vec![0xff; 8]);
+        assert_eq!(resp.payload(), Some(&[0xff; 8][..]));
+
+        // Only binary frames carry out-of-band data
+        let mut resp = Response::ok();
+        resp.attach_payload(vec![1]);
+        assert_eq!(resp.payload_len(), 0);
+        assert_eq!(resp.payload(), None);
+    }
+
+    #[test]
+    fn test_response_id() {
+        let resp = Response::ok();
+        assert!(!serde_json::to_string(&resp).unwrap().contains("\"id\""));
+
+        let resp = Response {
+            id: Some(42),
+            ..Response::ok()
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.starts_with(r#"{"id":42,"success":true"#));
+        let parsed: Response = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.id, Some(42));
+    }
+
+    #[test]
+    fn test_session_list_response() {
+        let resp = Response::success(ResponseData::SessionList {
+            sessions: vec![
+                SessionSummary {
+                    name: "default".to_string(),
+                    state: ConnectionState::Connected,
+                    host: Some("192.168.1.100".to_string()),
+                    pid: Some(1234),
+                    alive: true,
+                    uptime_secs: Some(60),
+                    pruned: false,
+                },
+                SessionSummary {
+                    name: "crashed".to_string(),
+                    state: ConnectionState::Disconnected,
+                    host: None,
+                    pid: Some(99999),
+                    alive: false,
+                    uptime_secs: None,
+                    pruned: true,
+                },
+            ],
+        });
+
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("\"uptime_secs\":60"));
+        assert!(json.contains("\"alive\":false"));
+        assert!(json.contains("\"pruned\":true"));
+
+        // Older daemons/clients without the probe fields still parse
+        let summary: SessionSummary =
+            serde_json::from_str(r#"{"name":"default","state":"disconnected"}"#).unwrap();
+        assert!(!summary.alive);
+        assert!(summary.pid.is_none());
+    }
+
+    #[test]
+    fn test_dry_run_result_serialization() {
+        let resp = Response::success(ResponseData::DryRun(DryRunResult {
+            host: "192.168.1.100".to_string(),
+            port: 3389,
+            reachable: true,
+            tls_ok: true,
+            auth_ok: false,
+            width: None,
+            height: None,
+            cert_fingerprint: Some("ab12".to_string()),
+            error: Some("Authentication failed".to_string()),
+            error_code: Some(ErrorCode::AuthenticationFailed),
+        }));
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("\"type\":\"dry_run\""));
+        assert!(json.contains("\"reachable\":true"));
+        assert!(json.contains("\"auth_ok\":false"));
+        assert!(json.contains("\"error_code\":\"authentication_failed\""));
+        assert!(json.contains("\"cert_fingerprint\":\"ab12\""));
+        assert!(!json.contains("\"width\""));
+    }
+
+    #[test]
+    fn test_cert_fingerprint_serialization() {
+        let resp = Response::success(ResponseData::CertFingerprint { sha256: "ab12".to_string() });
+        let json = serde_json::to_string(&resp).unwrap();
+        assert_eq!(json, r#"{"success":true,"data":{"type":"cert_fingerprint","sha256":"ab12"}}"#);
+    }
+
+    #[test]
+    fn test_cursor_info_serialization() {
+        let resp = Response::success(ResponseData::CursorInfo(CursorInfo {
+            x: 640,
+            y: 400,
+            visible: true,
+            width: Some(32),
+            height: Some(32),
+            hotspot_x: Some(0),
+            hotspot_y: Some(0),
+            base64: None,
+        }));
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("\"type\":\"cursor_info\""));
+        assert!(json.contains("\"x\":640"));
+        assert!(json.contains("\"hotspot_x\":0"));
+        assert!(!json.contains("base64"));
+    }
+
+    #[test]
+    fn test_clipboard_status_serialization() {
+        let resp = Response::success(ResponseData::ClipboardStatus {
+            text: true,
+            image: false,
+            html: true,
+            files: false,
+        });
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(
+            r#""data":{"type":"clipboard_status","text":true,"image":false,"html":true,"files":false}"#
+        ));
+    }
+
+    #[test]
+    fn test_clipboard_history_serialization() {
+        let resp = Response::success(ResponseData::ClipboardHistory {
+            entries: vec![ClipboardHistoryEntry {
+                text: "copied".to_string(),
+                copied_at_ms: 1_700_000_000_000,
+            }],
+        });
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("\"type\":\"clipboard_history\""));
+        assert!(json.contains("\"copied_at_ms\":1700000000000"));
+    }
+
+    #[test]
+    fn test_session_kill_serialization() {
+        let resp = Response::success(ResponseData::SessionKill {
+            sessions: vec![
+                KilledSession {
+                    name: "default".to_string(),
+                    pid: Some(4242),
+                    outcome: KillOutcome::Killed,
+                    error: None,
+                },
+                KilledSession {
+                    name: "stale".to_string(),
+                    pid: None,
+                    outcome: KillOutcome::AlreadyDead,
+                    error: None,
+                },
+            ],
+        });
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""type":"session_kill""#));
+        assert!(json.contains(r#"{"name":"default","pid":4242,"outcome":"killed"}"#));
+        assert!(json.contains(r#"{"name":"stale","outcome":"already_dead"}"#));
+    }
+
+    #[test]
+    fn test_drive_list_serialization() {
+        let resp = Response::success(ResponseData::DriveList {
+            drives: vec![MappedDrive {
+                name: "Data".to_string(),
+                path: "/srv/data".to_string(),
+                read_only: true,
+                device_id: 2,
+                unc_path: r"\\TSCLIENT\Data".to_string(),
+                total_bytes: Some(1_000_000),
+                free_bytes: Some(250_000),
+            }],
+        });
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""device_id":2"#));
+        assert!(json.contains(r#""unc_path":"\\\\TSCLIENT\\Data""#));
+        assert!(json.contains(r#""free_bytes":250000"#));
+
+        // Responses from older daemons carry only the mapping
+        let old = r#"{"name":"Data","path":"/srv/data"}"#;
+        let drive: MappedDrive = serde_json::from_str(old).unwrap();
+        assert_eq!(drive.device_id, 0);
+        assert!(drive.unc_path.is_empty());
+        assert_eq!(drive.total_bytes, None);
+    }
+
+    #[test]
+    fn test_session_info_last_disconnect() {
+        let info = SessionInfo {
+            name: "default".to_string(),
+            state: ConnectionState::Disconnected,
+            host: None,
+            width: None,
+            height: None,
+            keyboard_layout: None,
+            cert_fingerprint: None,
+            monitors: Vec::new(),
+            last_disconnect: Some(DisconnectInfo {
+                reason: DisconnectReason::IdleTimeout,
+                message: "idle timeout".to_string(),
+            }),
+            connect_ms: None,
+            rtt_ms: None,
+            last_rtt_ms: None,
+            frames_received: None,
+            bytes_received: None,
+            stream_port: None,
+            stream_token: None,
+            stream_viewer: false,
+            pid: 1234,
+            uptime_secs: 60,
+        };
+        let json = serde_json::to_string(&info).unwrap();
+        assert!(json.contains("\"reason\":\"idle_timeout\""));
+        assert!(!json.contains("stream_token"));
+
+        // Responses from older daemons have no disconnect info
+        let info: SessionInfo = serde_json::from_str(
+            r#"{"name":"default","state":"connected","pid":1,"uptime_secs":5}"#,
+        )
+        .unwrap();
+        assert!(info.last_disconnect.is_none());
+        assert!(info.stream_token.is_none());
+        assert!(!info.stream_viewer);
+        assert!(info.rtt_ms.is_none());
+    }
+
+    #[test]
+    fn test_session_info_metrics() {
+        let info: SessionInfo = serde_json::from_str(
+            r#"{"name":"default","state":"connected","pid":1,"uptime_secs":5,
+                "connect_ms":850,"rtt_ms":42,"last_rtt_ms":38,
+                "frames_received":120,"bytes_received":65536}"#,
+        )
+        .unwrap();
+        assert_eq!(info.connect_ms, Some(850));
+        assert_eq!(info.rtt_ms, Some(42));
+        assert_eq!(info.last_rtt_ms, Some(38));
+        assert_eq!(info.frames_received, Some(120));
+        assert_eq!(info.bytes_received, Some(65536));
+    }
+
     #[test]
     fn test_screenshot_response() {
         let resp = Response::success(ResponseData::Screenshot {
             width: 1920,
             height: 1080,
             format: "png".to_string(),
+            size_bytes: 12,
             base64: "iVBORw0KGgo...".to_string(),
+            stable_wait_ms: Some(350),
+            stable: Some(true),
+            etag: "0123456789abcdef".to_string(),
+            first_frame_received: false,
         });
 
         let json = serde_json::to_string(&resp).unwrap();
         assert!(json.contains("\"type\":\"screenshot\""));
+        assert!(json.contains("\"first_frame_received\":false"));
+        assert!(json.contains("\"size_bytes\":12"));
+        assert!(json.contains("\"stable_wait_ms\":350"));
+        assert!(json.contains("\"etag\":\"0123456789abcdef\""));
+
+        let resp = Response::success(ResponseData::NotModified {
+            etag: "0123456789abcdef".to_string(),
+            width: 1920,
+            height: 1080,
+        });
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("\"type\":\"not_modified\""));
+        assert!(!json.contains("base64"));
+    }
+
+    #[test]
+    fn test_binary_frame_response() {
+        // The payload travels after the line, never inside it
+        let resp = Response::success(ResponseData::Frame {
+            width: 2,
+            height: 1,
+            stride: 8,
+            encoding: FrameEncoding::Raw,
+            size_bytes: 8,
+            data: None,
+            binary: true,
+            payload: vec![0xff; 8],
+        });
+
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("\"type\":\"frame\""));
+        assert!(json.contains("\"encoding\":\"raw\""));
+        assert!(json.contains("\"binary\":true"));
+        assert!(!json.contains("payload"));
+        assert!(!json.contains("\"data\":\""));
+
+        let parsed: Response = serde_json::from_str(&json).unwrap();
+        match parsed.data {
+            Some(ResponseData::Frame { stride, payload, .. }) => {
+                assert_eq!(stride, 8);
+                assert!(payload.is_empty());
+            }
+            _ => panic!("Expected Frame response"),
+        }
     }
 }
@@ -32,14 +32,32 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     /// Connect to an RDP server
-    Connect(ConnectArgs),
+    Connect(Box<ConnectArgs>),
 
     /// Disconnect from RDP and close the session
-    Disconnect,
+    Disconnect {
+        /// Log the Windows user off first (needs --enable-win-automation)
+        #[arg(long)]
+        logoff: bool,
+    },
 
     /// Take a screenshot
     Screenshot(ScreenshotArgs),
 
+    /// Save the raw desktop framebuffer
+    Frame(FrameArgs),
+
+    /// Resize the remote desktop without reconnecting
+    Resize {
+        /// New desktop width (200-8192)
+        width: u16,
+        /// New desktop height (200-8192)
+        height: u16,
+    },
+
+    /// Change the WebSocket stream's frame rate, quality or delta encoding while it runs
+    StreamConfig(StreamConfigArgs),
+
     /// Mouse operations
     Mouse(MouseArgs),
 
@@ -49,6 +67,9 @@ pub enum Commands {
     /// Scroll operations
     Scroll(ScrollArgs),
 
+    /// Run a script of input actions in one round trip
+    Input(InputArgs),
+
     /// Clipboard operations
     Clipboard(ClipboardArgs),
 
@@ -61,6 +82,12 @@ pub enum Commands {
     /// OCR-based text location (find text on screen)
     Locate(LocateArgs),
 
+    /// Wait until the screen (or a region of it) changes
+    WaitForChange(WaitForChangeArgs),
+
+    /// Get the mouse pointer position and shape
+    Cursor(CursorArgs),
+
     /// Session management
     Session(SessionArgs),
 
@@ -77,15 +104,15 @@ pub enum Commands {
 /// View command arguments.
 #[derive(Parser)]
 pub struct ViewArgs {
-    /// WebSocket streaming port to connect to
-    #[arg(long, default_value = "9224")]
-    pub port: u16,
+    /// WebSocket streaming port to connect to (default: the session's stream port)
+    #[arg(long)]
+    pub port: Option<u16>,
 }
 
 /// Connect command arguments.
 #[derive(Parser)]
 pub struct ConnectArgs {
-    /// Server hostname or IP (or set AGENT_RDP_HOST)
+    /// Server hostname or IP, optionally with :port (or set AGENT_RDP_HOST)
     #[arg(long, env = "AGENT_RDP_HOST", required = true)]
     pub host: String,
 
@@ -97,7 +124,7 @@ pub struct ConnectArgs {
     #[arg(long, short = 'u', env = "AGENT_RDP_USERNAME", required = true)]
     pub username: String,
 
-    /// Password (or set AGENT_RDP_PASSWORD, or use --password-stdin)
+    /// Password (or set AGENT_RDP_PASSWORD, or use --password-stdin / --password-file)
     #[arg(long, short = 'p', env = "AGENT_RDP_PASSWORD")]
     pub password: Option<String>,
 
@@ -105,6 +132,11 @@ pub struct ConnectArgs {
     #[arg(long)]
     pub password_stdin: bool,
 
+    /// Read password from the first line of a file (e.g. a mounted CI secret).
+    /// Takes precedence over --password and AGENT_RDP_PASSWORD
+    #[arg(long, conflicts_with = "password_stdin")]
+    pub password_file: Option<String>,
+
     /// Domain
     #[arg(long, short = 'd')]
     pub domain: Option<String>,
@@ -117,13 +149,189 @@ pub struct ConnectArgs {
     #[arg(long, default_value = "800")]
     pub height: u16,
 
-    /// Map local directories as drives (format: /path:DriveName, can be specified multiple times)
-    #[arg(long = "drive", value_name = "PATH:NAME")]
+    /// Give mouse and scroll coordinates in this logical size (WxH), scaled to the desktop;
+    /// whole-desktop screenshots are resized to it
+    #[arg(long, env = "AGENT_RDP_LOGICAL_SIZE", value_name = "WxH")]
+    pub logical_size: Option<String>,
+
+    /// Map local directories as drives (format: /path:DriveName[:ro], can be specified multiple times)
+    #[arg(long = "drive", value_name = "PATH:NAME[:ro]")]
     pub drives: Vec<String>,
 
+    /// Add a monitor (format: WxH[+X+Y] relative to the first monitor, can be specified multiple times; overrides --width/--height)
+    #[arg(long = "monitor", value_name = "WxH[+X+Y]")]
+    pub monitors: Vec<String>,
+
+    /// Keyboard layout as hex LCID (e.g., 0x40C) or locale name (e.g., fr-FR). Default: en-US
+    #[arg(long, env = "AGENT_RDP_KEYBOARD_LAYOUT", value_name = "LAYOUT")]
+    pub keyboard_layout: Option<String>,
+
+    /// Require the server certificate to match this SHA-256 fingerprint (hex, colons optional)
+    #[arg(long, env = "AGENT_RDP_PIN_CERT", value_name = "SHA256")]
+    pub pin_cert: Option<String>,
+
+    /// Security protocol: auto, nla, or tls-only (for hosts with NLA disabled)
+    #[arg(long, default_value = "auto", env = "AGENT_RDP_SECURITY", value_name = "MODE")]
+    pub security: String,
+
+    /// Oldest TLS version to accept: 1.0, 1.1, 1.2 or 1.3 (default: 1.2).
+    /// Lowering it is for isolated legacy lab hosts only
+    #[arg(long, env = "AGENT_RDP_MIN_TLS", value_name = "VERSION")]
+    pub min_tls: Option<String>,
+
+    /// Graphics codec: bitmap or remotefx (falls back to bitmap if the server rejects it)
+    #[arg(long, default_value = "bitmap", env = "AGENT_RDP_CODEC", value_name = "CODEC")]
+    pub codec: String,
+
+    /// Visual effects: full, lan, broadband, modem, or comma-separated performance flags
+    /// (e.g. disable-wallpaper,disable-theming). The default lan turns off eye-candy
+    #[arg(long, default_value = "lan", env = "AGENT_RDP_PERF_PROFILE", value_name = "PROFILE")]
+    pub perf_profile: String,
+
+    /// Connect through an RD Gateway (WebSocket transport, Basic auth)
+    #[arg(long, env = "AGENT_RDP_GATEWAY_HOST", value_name = "HOST")]
+    pub gateway_host: Option<String>,
+
+    /// RD Gateway HTTPS port
+    #[arg(long, default_value = "443", env = "AGENT_RDP_GATEWAY_PORT")]
+    pub gateway_port: u16,
+
+    /// RD Gateway username (default: --username)
+    #[arg(long, env = "AGENT_RDP_GATEWAY_USER", requires = "gateway_host")]
+    pub gateway_user: Option<String>,
+
+    /// RD Gateway password (default: the RDP password)
+    #[arg(long, env = "AGENT_RDP_GATEWAY_PASS", requires = "gateway_host")]
+    pub gateway_pass: Option<String>,
+
+    /// Require the RD Gateway certificate to match this SHA-256 fingerprint instead of
+    /// a publicly trusted chain (for self-signed gateways)
+    #[arg(long, env = "AGENT_RDP_GATEWAY_PIN_CERT", value_name = "SHA256", requires = "gateway_host")]
+    pub gateway_pin_cert: Option<String>,
+
+    /// Accept any RD Gateway certificate (sends the gateway credentials unverified)
+    #[arg(long, requires = "gateway_host", conflicts_with = "gateway_pin_cert")]
+    pub gateway_insecure: bool,
+
+    /// Proxy for the TCP connection: socks5://[user:pass@]host[:port] or http://[user:pass@]host[:port]
+    #[arg(long, env = "AGENT_RDP_PROXY", value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// KDC for Kerberos authentication: host[:port], tcp://host[:port] or udp://host[:port]
+    /// (default: NTLM)
+    #[arg(long, env = "AGENT_RDP_KDC", value_name = "URL")]
+    pub kdc: Option<String>,
+
+    /// Print the server certificate fingerprint from a dry-run handshake, without starting a session
+    #[arg(long)]
+    pub print_cert_fingerprint: bool,
+
+    /// Check reachability, TLS and credentials, then disconnect without starting a daemon
+    /// (exit code 2: unreachable, TLS failed or connection lost; 3: credentials rejected)
+    #[arg(long, conflicts_with = "print_cert_fingerprint")]
+    pub dry_run: bool,
+
+    /// Save the passwords with the connection settings for `session restart`
+    #[arg(long)]
+    pub save_password: bool,
+
     /// Enable Windows UI Automation (requires automation agent on remote host)
     #[arg(long)]
     pub enable_win_automation: bool,
+
+    /// After connecting, check that keyboard input reaches the remote desktop
+    /// (needs --enable-win-automation to read the result back)
+    #[arg(long)]
+    pub test_input: bool,
+
+    /// Wait (up to 10s) for the server to draw the desktop before returning, so the first
+    /// screenshot isn't black
+    #[arg(long)]
+    pub wait_first_frame: bool,
+
+    /// Reconnect automatically if the connection drops (or set AGENT_RDP_AUTO_RECONNECT)
+    #[arg(long, env = "AGENT_RDP_AUTO_RECONNECT")]
+    pub auto_reconnect: bool,
+
+    /// Maximum reconnection attempts before giving up
+    #[arg(long, default_value = "5", value_name = "N")]
+    pub max_reconnect_attempts: u32,
+
+    /// Send a keepalive after this many seconds without data from the server, and treat
+    /// the connection as lost after three silent intervals (0 = off)
+    #[arg(long, env = "AGENT_RDP_KEEPALIVE_INTERVAL", value_name = "SECS")]
+    pub keepalive_interval: Option<u32>,
+
+    /// Retry the initial connect while the server refuses or doesn't answer (e.g. still booting)
+    #[arg(long, default_value = "0", value_name = "N")]
+    pub connect_retries: u32,
+
+    /// Milliseconds before the first connect retry, doubling after each (max 30s)
+    #[arg(long, default_value = "1000", value_name = "MS")]
+    pub retry_delay: u64,
+
+    /// Milliseconds each connect attempt gets to reach the server and finish the TLS and
+    /// CredSSP handshake (default: 20000)
+    #[arg(
+        long,
+        env = "AGENT_RDP_CONNECT_TIMEOUT",
+        value_name = "MS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub connect_timeout: Option<u64>,
+
+    /// Scale streamed frames down so neither side exceeds this many pixels (viewer only;
+    /// screenshots stay at full size)
+    #[arg(long, env = "AGENT_RDP_STREAM_MAX_FRAME_SIZE", value_name = "PX")]
+    pub max_frame_size: Option<u16>,
+
+    /// Record remote audio to a WAV file
+    #[arg(long, value_name = "PATH")]
+    pub capture_audio: Option<String>,
+
+    /// Record the desktop to an animated GIF, finalized on disconnect
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<String>,
+
+    /// Frames per second sampled for --record (default: 2, max: 10)
+    #[arg(long, value_name = "FPS", requires = "record")]
+    pub record_fps: Option<u32>,
+
+    /// Redirect local smart card readers via PC/SC (needs a build with the smartcard feature)
+    #[arg(long)]
+    pub redirect_smartcard: bool,
+
+    /// Don't attach the clipboard channel (for hosts where clipboard redirection is forbidden)
+    #[arg(long)]
+    pub no_clipboard: bool,
+
+    /// Don't attach the audio output channel (for hosts where audio redirection is forbidden)
+    #[arg(long, conflicts_with = "capture_audio")]
+    pub no_audio: bool,
+
+    /// Shut the daemon down after this many seconds without commands or viewers (0 = never)
+    #[arg(long, env = "AGENT_RDP_IDLE_TIMEOUT", value_name = "SECS")]
+    pub idle_timeout: Option<u64>,
+
+    /// Client computer name reported to the server (max 15 characters, default: agent-rdp)
+    #[arg(long, env = "AGENT_RDP_CLIENT_NAME", value_name = "NAME")]
+    pub client_name: Option<String>,
+
+    /// Client build number reported to the server
+    #[arg(long, value_name = "N")]
+    pub client_build: Option<u32>,
+
+    /// Product ID reported to the server (max 31 characters)
+    #[arg(long, value_name = "ID")]
+    pub dig_product_id: Option<String>,
+
+    /// Client hardware ID as 32 hex digits or a GUID (default: derived from the session name)
+    #[arg(long, env = "AGENT_RDP_HARDWARE_ID", value_name = "HEX")]
+    pub hardware_id: Option<String>,
+
+    /// Number of remote clipboard texts to remember (default: 5, max: 100, 0 = off)
+    #[arg(long, env = "AGENT_RDP_CLIPBOARD_HISTORY", value_name = "N")]
+    pub clipboard_history: Option<u32>,
 }
 
 /// Screenshot command arguments.
@@ -133,14 +341,66 @@ pub struct ScreenshotArgs {
     #[arg(long, short = 'o', default_value = "./screenshot.png")]
     pub output: String,
 
-    /// Image format
-    #[arg(long, default_value = "png")]
-    pub format: String,
+    /// Image format: png, jpeg, webp or avif (default: inferred from --output extension)
+    #[arg(long)]
+    pub format: Option<String>,
+
+    /// Capture only a region of the desktop
+    #[arg(long, num_args = 4, value_names = ["X", "Y", "W", "H"])]
+    pub region: Option<Vec<u32>>,
+
+    /// Capture only this monitor (index from `session info`); --region is then relative to it
+    #[arg(long, value_name = "N")]
+    pub monitor: Option<u32>,
+
+    /// Draw the mouse pointer onto the screenshot
+    #[arg(long)]
+    pub draw_cursor: bool,
+
+    /// Wait until the screen hasn't changed for this many milliseconds before capturing
+    #[arg(long, value_name = "MS")]
+    pub wait_stable: Option<u64>,
+
+    /// Capture anyway after this many milliseconds of waiting for --wait-stable
+    #[arg(long, value_name = "MS", requires = "wait_stable")]
+    pub stable_timeout: Option<u64>,
+}
+
+/// Frame command arguments.
+#[derive(Parser)]
+pub struct FrameArgs {
+    /// Save to file path
+    #[arg(long, short = 'o', default_value = "./frame.rgba")]
+    pub output: String,
+
+    /// Pixel encoding: raw (RGBA, 4 bytes per pixel) or png
+    #[arg(long, default_value = "raw")]
+    pub encoding: String,
+}
+
+/// Stream-config command arguments (omitted settings are unchanged).
+#[derive(Parser)]
+pub struct StreamConfigArgs {
+    /// Frames per second (1-60)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=60))]
+    pub fps: Option<u32>,
+
+    /// JPEG quality (1-100)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub quality: Option<u8>,
+
+    /// Send only the changed tiles of each frame (true or false)
+    #[arg(long, value_name = "BOOL")]
+    pub delta: Option<bool>,
 }
 
 /// Mouse command arguments.
 #[derive(Parser)]
 pub struct MouseArgs {
+    /// Interpret coordinates relative to this monitor (index from `session info`)
+    #[arg(long, global = true, value_name = "N")]
+    pub monitor: Option<u32>,
+
     #[command(subcommand)]
     pub action: MouseAction,
 }
@@ -153,6 +413,9 @@ pub enum MouseAction {
         x: u16,
         /// Y coordinate
         y: u16,
+        /// Glide to the position along a curved path first, like a person would
+        #[arg(long)]
+        human: bool,
     },
 
     /// Right click at position
@@ -177,6 +440,10 @@ pub enum MouseAction {
         x: u16,
         /// Y coordinate
         y: u16,
+        /// Glide along a curved, eased path instead of jumping (for apps that
+        /// flag instant moves as automated)
+        #[arg(long)]
+        human: bool,
     },
 
     /// Drag from one position to another
@@ -189,7 +456,35 @@ pub enum MouseAction {
         x2: u16,
         /// End Y coordinate
         y2: u16,
+        /// Number of intermediate moves while the button is held
+        #[arg(long, default_value = "10")]
+        steps: u32,
     },
+
+    /// Press a button at position and keep it held (until `up` or `release-all`)
+    Down {
+        /// X coordinate
+        x: u16,
+        /// Y coordinate
+        y: u16,
+        /// Button to press
+        #[arg(long, default_value = "left", value_parser = ["left", "right", "middle"])]
+        button: String,
+    },
+
+    /// Release a held button at position
+    Up {
+        /// X coordinate
+        x: u16,
+        /// Y coordinate
+        y: u16,
+        /// Button to release
+        #[arg(long, default_value = "left", value_parser = ["left", "right", "middle"])]
+        button: String,
+    },
+
+    /// Release every mouse button still held down
+    ReleaseAll,
 }
 
 /// Keyboard command arguments.
@@ -205,6 +500,16 @@ pub enum KeyboardAction {
     Type {
         /// Text to type
         text: String,
+
+        /// How characters are sent: unicode, or scancode (for apps that ignore
+        /// unicode input; uses the session's keyboard layout)
+        #[arg(long, default_value = "unicode", value_name = "MODE")]
+        mode: String,
+
+        /// Pause after each character in milliseconds (default: 100); 0 sends
+        /// the whole text at once
+        #[arg(long, value_name = "MS")]
+        delay: Option<u64>,
     },
 
     /// Press a key combination (e.g., "ctrl+c", "alt+tab") or single key (e.g., "enter")
@@ -212,6 +517,58 @@ pub enum KeyboardAction {
         /// Key combination or single key
         keys: String,
     },
+
+    /// Send a single scancode press (or release) as is
+    Raw {
+        /// Scancode, decimal or hex (e.g., 0x5D)
+        #[arg(value_parser = parse_scancode)]
+        scancode: u8,
+
+        /// Set the extended-key flag (E0 prefix)
+        #[arg(long)]
+        extended: bool,
+
+        /// Send a release instead of a press
+        #[arg(long)]
+        release: bool,
+    },
+
+    /// Press keys and keep them down across later commands (e.g., "shift", "ctrl+alt")
+    Hold {
+        /// Key or key combination
+        keys: String,
+    },
+
+    /// Release keys held with `hold`, or every held key with --all
+    Release {
+        /// Key or key combination
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        keys: Option<String>,
+
+        /// Release every key still held down
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Paste text through the clipboard with Ctrl+V - much faster than `type` for long
+    /// text (the focused window must accept Ctrl+V)
+    Paste {
+        /// Text to paste
+        text: String,
+
+        /// Put the previous clipboard text back afterward
+        #[arg(long)]
+        restore: bool,
+    },
+}
+
+/// Parse a scancode given in decimal or as 0x-prefixed hex.
+fn parse_scancode(value: &str) -> Result<u8, String> {
+    let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| format!("invalid scancode '{}': expected 0-255 or 0x00-0xFF", value))
 }
 
 /// Scroll command arguments.
@@ -274,13 +631,94 @@ pub struct ClipboardArgs {
 #[derive(Subcommand)]
 pub enum ClipboardAction {
     /// Get clipboard text
-    Get,
+    Get {
+        /// Line endings of the printed text: lf, crlf or preserve (default: lf)
+        #[arg(long, value_parser = ["lf", "crlf", "preserve"])]
+        line_endings: Option<String>,
+    },
 
     /// Set clipboard text
     Set {
         /// Text to set
         text: String,
+
+        /// Line endings to copy the text with: crlf, lf or preserve (default: crlf)
+        #[arg(long, value_parser = ["crlf", "lf", "preserve"])]
+        line_endings: Option<String>,
+    },
+
+    /// Save clipboard image to a PNG file
+    GetImage {
+        /// Output file path
+        #[arg(long, short = 'o', default_value = "./clipboard.png")]
+        output: String,
+    },
+
+    /// Set clipboard image from a PNG file
+    SetImage {
+        /// PNG file to copy
+        path: String,
+    },
+
+    /// Get the clipboard HTML fragment
+    GetHtml,
+
+    /// Set clipboard HTML for rich text paste (also offered as plain text)
+    SetHtml {
+        /// HTML fragment to copy
+        html: String,
+
+        /// Plain text version (default: the HTML with tags removed)
+        #[arg(long)]
+        text: Option<String>,
     },
+
+    /// Save files copied on the remote machine into a local directory
+    GetFiles {
+        /// Directory to save the files into (created if missing)
+        #[arg(long, short = 'o', default_value = ".")]
+        output: String,
+
+        /// Maximum total bytes to transfer (default: 1 GiB)
+        #[arg(long)]
+        max_bytes: Option<u64>,
+    },
+
+    /// Copy local files or directories for pasting on the remote machine
+    SetFiles {
+        /// Files or directories to copy
+        #[arg(required = true)]
+        paths: Vec<String>,
+
+        /// Maximum total bytes to transfer (default: 1 GiB)
+        #[arg(long)]
+        max_bytes: Option<u64>,
+    },
+
+    /// Show text from recent remote copies, newest first
+    History {
+        /// Number of entries to show (default: all that are kept)
+        #[arg(long, short = 'n')]
+        count: Option<u32>,
+    },
+
+    /// Empty the clipboard on both sides
+    Clear,
+
+    /// Show what the remote clipboard holds (text, image, html, files) without fetching it
+    Status,
+}
+
+/// Input script arguments.
+#[derive(Parser)]
+pub struct InputArgs {
+    /// Script file with one action per line ("-" reads stdin)
+    #[arg(long)]
+    pub script: String,
+
+    /// Pause after each action and between typed characters, in milliseconds (default: 20)
+    #[arg(long)]
+    pub delay: Option<u64>,
 }
 
 /// Drive command arguments.
@@ -292,8 +730,46 @@ pub struct DriveArgs {
 
 #[derive(Subcommand)]
 pub enum DriveAction {
-    /// List mapped drives (drives are configured at connect time with --drive)
+    /// List mapped drives
     List,
+
+    /// Map a local directory as a drive without reconnecting (needs --drive at connect)
+    Mount {
+        /// Local directory to map
+        path: String,
+
+        /// Drive name shown on Windows (\\tsclient\<name>)
+        name: String,
+
+        /// Prevent the remote host from creating, modifying or deleting files
+        #[arg(long)]
+        read_only: bool,
+    },
+
+    /// Remove a mapped drive without reconnecting
+    Unmount {
+        /// Name of the drive to remove
+        name: String,
+    },
+
+    /// Copy a local file or directory into a mapped drive
+    Push {
+        /// Local file or directory to copy
+        local: String,
+
+        /// Drive name and path inside it, e.g. Shared/reports (an existing directory receives the file)
+        remote: String,
+    },
+
+    /// Copy a file or directory out of a mapped drive
+    Pull {
+        /// Drive name and path inside it, e.g. Shared/output.csv
+        remote: String,
+
+        /// Local destination (an existing directory receives the file)
+        #[arg(default_value = ".")]
+        local: String,
+    },
 }
 
 /// Session command arguments.
@@ -305,12 +781,47 @@ pub struct SessionArgs {
 
 #[derive(Subcommand)]
 pub enum SessionAction {
-    /// List active sessions
-    List,
+    /// List sessions, probing each daemon (stale sessions have a dead daemon)
+    List {
+        /// Remove stale session directories
+        #[arg(long)]
+        prune: bool,
+    },
 
     /// Get current session info
     Info,
 
+    /// Attach to the session's running daemon and show its state (never starts one)
+    Attach,
+
+    /// Restart the daemon and reconnect with the settings of the last successful connect
+    Restart {
+        /// Read the password from stdin (otherwise the saved password or AGENT_RDP_PASSWORD)
+        #[arg(long)]
+        password_stdin: bool,
+    },
+
+    /// Stop a session's daemon by its PID, even if it doesn't answer, and remove its files
+    Kill {
+        /// Session to kill (default: --session)
+        name: Option<String>,
+
+        /// Kill every session, reporting which were killed and which were already dead
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+    },
+
+    /// Show the daemon log (kept after the daemon exits; level set by AGENT_RDP_LOG)
+    Logs {
+        /// Keep printing new log lines as they are written
+        #[arg(long, short = 'f')]
+        follow: bool,
+
+        /// Number of lines to show from the end of the log
+        #[arg(long, short = 'n', default_value = "50")]
+        lines: usize,
+    },
+
     /// Run as background daemon for this session (starts automatically on connect)
     Daemon,
 }
@@ -320,6 +831,10 @@ pub enum SessionAction {
 pub struct AutomateArgs {
     #[command(subcommand)]
     pub action: AutomateAction,
+
+    /// How long to wait for each answer from the automation agent in milliseconds (default: 10000)
+    #[arg(long, global = true, value_name = "MS")]
+    pub agent_timeout: Option<u64>,
 }
 
 #[derive(Subcommand)]
@@ -345,6 +860,10 @@ pub enum AutomateAction {
         /// Start from the currently focused element
         #[arg(short = 'f', long)]
         focused: bool,
+
+        /// Result shape: json (full tree), yaml, or text (indented outline)
+        #[arg(long, default_value = "json", value_parser = ["json", "yaml", "text"])]
+        format: String,
     },
 
     /// Get element properties
@@ -371,6 +890,49 @@ pub enum AutomateAction {
         /// Use double-click instead of single click
         #[arg(long, short = 'd')]
         double_click: bool,
+
+        /// Button to click with
+        #[arg(long, default_value = "left", value_parser = ["left", "right", "middle"])]
+        button: String,
+    },
+
+    /// Invoke an element (InvokePattern) without the mouse - for off-screen or hidden menu items and buttons
+    Invoke {
+        /// Element selector
+        selector: String,
+    },
+
+    /// Drag an element onto another with the mouse - for reordering list items, tree items
+    Drag {
+        /// Selector of the element to drag
+        selector: String,
+
+        /// Selector of the element to drop onto
+        target: String,
+    },
+
+    /// Save an image of just one element, cropped from the desktop to its bounds
+    ScreenshotElement {
+        /// Element selector
+        selector: String,
+
+        /// Save to file path
+        #[arg(long, short = 'o', default_value = "./element.png")]
+        output: String,
+
+        /// Image format: png, jpeg, webp or avif (default: inferred from --output extension)
+        #[arg(long)]
+        format: Option<String>,
+    },
+
+    /// Draw a box around an element on the remote desktop - to see what a selector matched
+    Highlight {
+        /// Element selector
+        selector: String,
+
+        /// How long the box stays up in milliseconds (max 60000)
+        #[arg(long, default_value = "3000", value_name = "MS")]
+        duration: u64,
     },
 
     /// Select an element or item (SelectionItemPattern) - for list items, radio buttons
@@ -420,6 +982,19 @@ pub enum AutomateAction {
         text: String,
     },
 
+    /// Type text into an element, appending to its current value
+    Type {
+        /// Element selector
+        selector: String,
+
+        /// Text to type
+        text: String,
+
+        /// Clear the element before typing
+        #[arg(long)]
+        clear: bool,
+    },
+
     /// Clear text from an element
     Clear {
         /// Element selector
@@ -446,7 +1021,7 @@ pub enum AutomateAction {
 
     /// Window operations
     Window {
-        /// Action: list, focus, maximize, minimize, restore, close
+        /// Action: list, focus, raise, maximize, minimize, restore, close
         action: String,
 
         /// Window selector (optional)
@@ -475,24 +1050,59 @@ pub enum AutomateAction {
         process_timeout: Option<u64>,
     },
 
+    /// Run a multi-line PowerShell script and wait for its output
+    ExecScript {
+        /// Script file to run ("-" reads the script from stdin)
+        #[arg(long)]
+        file: String,
+
+        /// Process timeout in milliseconds (default: 10000)
+        #[arg(long = "process-timeout")]
+        process_timeout: Option<u64>,
+    },
+
     /// Wait for an element to reach a state
     WaitFor {
         /// Element selector
         selector: String,
 
-        /// Timeout in milliseconds
+        /// Timeout in milliseconds (default: 10000)
         #[arg(long)]
         timeout: Option<u64>,
 
-        /// State to wait for (visible, enabled, gone)
+        /// State to wait for (visible, enabled, exists, gone)
         #[arg(long)]
         state: Option<String>,
+
+        /// Time between checks in milliseconds
+        #[arg(long, default_value = "250", value_name = "MS")]
+        poll_interval: u64,
     },
 
     /// Get automation agent status
     Status,
 }
 
+/// Wait-for-change command arguments.
+#[derive(Parser)]
+pub struct WaitForChangeArgs {
+    /// Longest time to wait in milliseconds (max 60000)
+    #[arg(long, default_value = "10000", value_name = "MS")]
+    pub max_wait: u64,
+
+    /// Only watch a region of the desktop
+    #[arg(long, num_args = 4, value_names = ["X", "Y", "W", "H"])]
+    pub region: Option<Vec<u32>>,
+}
+
+/// Cursor command arguments.
+#[derive(Parser)]
+pub struct CursorArgs {
+    /// Save the pointer image to a PNG file
+    #[arg(long, short = 'o')]
+    pub output: Option<String>,
+}
+
 /// Locate command arguments (OCR-based text location).
 #[derive(Parser)]
 pub struct LocateArgs {
@@ -511,4 +1121,32 @@ pub struct LocateArgs {
     /// Return all text lines on screen (ignores search text)
     #[arg(long, short = 'a')]
     pub all: bool,
+
+    /// Only search within a region of the desktop
+    #[arg(long, num_args = 4, value_names = ["X", "Y", "W", "H"])]
+    pub region: Option<Vec<u32>>,
+
+    /// Click the center of the best match instead of listing matches
+    #[arg(long, conflicts_with = "all")]
+    pub click: bool,
+
+    /// Button to click with: left, right or middle (default: left)
+    #[arg(long, requires = "click")]
+    pub button: Option<String>,
+
+    /// Click the Nth match (0-based, top to bottom) instead of the most confident one
+    #[arg(long, value_name = "N", requires = "click")]
+    pub index: Option<u32>,
+
+    /// Ignore matches below this confidence, from 0 to 1 (default: 0.2)
+    #[arg(long, value_name = "C", requires = "click")]
+    pub min_confidence: Option<f32>,
+
+    /// Directory holding the OCR models (text-detection.rten, text-recognition.rten)
+    #[arg(long, env = "AGENT_RDP_OCR_MODELS", value_name = "DIR")]
+    pub ocr_models: Option<String>,
+
+    /// Language to recognize, e.g. en or ru (default: en)
+    #[arg(long, value_name = "LANG")]
+    pub lang: Option<String>,
 }
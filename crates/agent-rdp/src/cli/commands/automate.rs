@@ -1,13 +1,22 @@
 //! Automate command implementation for Windows UI Automation.
 
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
 use agent_rdp_protocol::{
-    AutomateRequest, AutomationScrollDirection, Request, WaitState, WindowAction,
+    AutomateRequest, AutomationScrollDirection, Request, ResponseData, SnapshotFormat, WaitState,
+    WindowAction,
 };
+use base64::Engine;
 
 use crate::cli::{AutomateAction, AutomateArgs};
 use crate::output::Output;
 use crate::session_manager::SessionManager;
 
+/// Headroom for the daemon to answer after an element wait times out.
+const RESPONSE_MARGIN_MS: u64 = 5000;
+
 pub async fn run(
     session: &str,
     args: AutomateArgs,
@@ -23,6 +32,12 @@ pub async fn run(
 
     let mut client = manager.ensure_daemon().await?;
 
+    // Element images are saved to a file rather than printed
+    let image_path = match &args.action {
+        AutomateAction::ScreenshotElement { output: path, .. } => Some(path.clone()),
+        _ => None,
+    };
+
     let automate_request = match args.action {
         AutomateAction::Snapshot {
             interactive,
@@ -30,19 +45,49 @@ pub async fn run(
             depth,
             selector,
             focused,
+            format,
         } => AutomateRequest::Snapshot {
             interactive_only: interactive,
             compact,
             max_depth: depth,
             selector,
             focused,
+            format: match format.as_str() {
+                "yaml" => SnapshotFormat::Yaml,
+                "text" => SnapshotFormat::Text,
+                _ => SnapshotFormat::Json,
+            },
         },
 
         AutomateAction::Get { selector, property } => AutomateRequest::Get { selector, property },
 
         AutomateAction::Focus { selector } => AutomateRequest::Focus { selector },
 
-        AutomateAction::Click { selector, double_click } => AutomateRequest::Click { selector, double_click },
+        AutomateAction::Click { selector, double_click, button } => AutomateRequest::Click {
+            selector,
+            double_click,
+            button: Some(button),
+        },
+
+        AutomateAction::Invoke { selector } => AutomateRequest::Invoke { selector },
+
+        AutomateAction::Drag { selector, target } => AutomateRequest::DragTo { selector, target },
+
+        AutomateAction::Highlight { selector, duration } => AutomateRequest::Highlight {
+            selector,
+            duration_ms: duration,
+        },
+
+        AutomateAction::ScreenshotElement { selector, output: path, format } => {
+            let format = match super::screenshot::image_format(format.as_deref(), &path) {
+                Ok(format) => format,
+                Err(message) => {
+                    output.print_error("invalid_format", &message);
+                    std::process::exit(1);
+                }
+            };
+            AutomateRequest::Screenshot { selector, format }
+        }
 
         AutomateAction::Select { selector, item } => AutomateRequest::Select { selector, item },
 
@@ -59,6 +104,16 @@ pub async fn run(
 
         AutomateAction::Fill { selector, text } => AutomateRequest::Fill { selector, text },
 
+        AutomateAction::Type {
+            selector,
+            text,
+            clear,
+        } => AutomateRequest::Type {
+            selector,
+            text,
+            clear,
+        },
+
         AutomateAction::Clear { selector } => AutomateRequest::Clear { selector },
 
         AutomateAction::Scroll {
@@ -86,6 +141,7 @@ pub async fn run(
             let action = match action.as_str() {
                 "list" => WindowAction::List,
                 "focus" => WindowAction::Focus,
+                "raise" => WindowAction::Raise,
                 "maximize" => WindowAction::Maximize,
                 "minimize" => WindowAction::Minimize,
                 "restore" => WindowAction::Restore,
@@ -115,28 +171,109 @@ pub async fn run(
             timeout_ms: process_timeout.unwrap_or(10000),
         },
 
+        AutomateAction::ExecScript {
+            file,
+            process_timeout,
+        } => {
+            let script = if file == "-" {
+                let mut script = String::new();
+                io::stdin().read_to_string(&mut script).map(|_| script)
+            } else {
+                fs::read_to_string(&file)
+            };
+            let script = match script {
+                Ok(script) => script,
+                Err(e) => {
+                    output.print_error("file_error", &format!("Failed to read '{}': {}", file, e));
+                    std::process::exit(1);
+                }
+            };
+            AutomateRequest::ExecScript {
+                script,
+                timeout_ms: process_timeout.unwrap_or(10000),
+            }
+        }
+
         AutomateAction::WaitFor {
             selector,
             timeout,
             state,
+            poll_interval,
         } => {
             let state = match state.as_deref() {
                 Some("enabled") => WaitState::Enabled,
                 Some("gone") => WaitState::Gone,
+                Some("exists") => WaitState::Exists,
                 _ => WaitState::Visible,
             };
             AutomateRequest::WaitFor {
                 selector,
-                timeout_ms: timeout.unwrap_or(30000),
+                timeout_ms: timeout.unwrap_or(10000),
                 state,
+                poll_interval_ms: poll_interval,
             }
         }
 
         AutomateAction::Status => AutomateRequest::Status,
     };
 
-    let request = Request::Automate(automate_request);
+    // Don't give up on the daemon before an element wait or a waited-for process can finish
+    let timeout_ms = match automate_request {
+        AutomateRequest::WaitFor { timeout_ms: wait_ms, .. } => {
+            timeout_ms.max(wait_ms + RESPONSE_MARGIN_MS)
+        }
+        AutomateRequest::Run {
+            wait: true,
+            timeout_ms: process_ms,
+            ..
+        } => timeout_ms.max(process_ms + 2 * RESPONSE_MARGIN_MS),
+        AutomateRequest::ExecScript {
+            timeout_ms: process_ms,
+            ..
+        } => timeout_ms.max(process_ms + 2 * RESPONSE_MARGIN_MS),
+        _ => timeout_ms,
+    };
+    let timeout_ms = match args.agent_timeout {
+        Some(agent_ms) => timeout_ms.max(agent_ms + RESPONSE_MARGIN_MS),
+        None => timeout_ms,
+    };
+
+    let request = Request::Automate {
+        request: automate_request,
+        agent_timeout_ms: args.agent_timeout,
+    };
     let response = client.send(&request, timeout_ms).await?;
+
+    if let (Some(path), Some(ResponseData::ElementScreenshot(image))) = (image_path, &response.data)
+    {
+        let path = Path::new(&path);
+        fs::write(path, base64::engine::general_purpose::STANDARD.decode(&image.base64)?)?;
+
+        let bounds = &image.bounds;
+        if output.is_json() {
+            let data = serde_json::json!({
+                "type": "element_screenshot",
+                "path": path.display().to_string(),
+                "bounds": bounds,
+                "format": image.format,
+                "size_bytes": image.size_bytes,
+            });
+            println!("{}", serde_json::json!({ "success": true, "data": data }));
+        } else {
+            println!(
+                "Element saved to {} ({}x{} at {}, {}, {}, {} bytes)",
+                path.display(),
+                bounds.width,
+                bounds.height,
+                bounds.x,
+                bounds.y,
+                image.format,
+                image.size_bytes
+            );
+        }
+        return Ok(());
+    }
+
     output.print_response(&response);
 
     if !response.success {
@@ -1,7 +1,13 @@
 //! Clipboard command implementation.
 
-use agent_rdp_protocol::{ClipboardRequest, Request};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
 
+use agent_rdp_protocol::{ClipboardRequest, LineEndings, Request, ResponseData};
+use base64::Engine;
+
+use super::absolute_path;
 use crate::cli::{ClipboardAction, ClipboardArgs};
 use crate::output::Output;
 use crate::session_manager::SessionManager;
@@ -22,17 +28,86 @@ pub async fn run(
     let mut client = manager.ensure_daemon().await?;
 
     let clipboard_request = match &args.action {
-        ClipboardAction::Get => ClipboardRequest::Get,
-        ClipboardAction::Set { text } => ClipboardRequest::Set { text: text.clone() },
+        ClipboardAction::Get { line_endings } => ClipboardRequest::Get {
+            line_endings: line_endings.as_deref().map(parse_line_endings),
+        },
+        ClipboardAction::Set { text, line_endings } => ClipboardRequest::Set {
+            text: text.clone(),
+            line_endings: line_endings.as_deref().map(parse_line_endings),
+        },
+        ClipboardAction::GetImage { .. } => ClipboardRequest::GetImage,
+        ClipboardAction::SetImage { path } => {
+            let png = match fs::read(path) {
+                Ok(png) => png,
+                Err(e) => {
+                    output.print_error("file_error", &format!("Failed to read '{}': {}", path, e));
+                    std::process::exit(1);
+                }
+            };
+            ClipboardRequest::SetImage {
+                base64: base64::engine::general_purpose::STANDARD.encode(png),
+            }
+        }
+        ClipboardAction::GetHtml => ClipboardRequest::GetHtml,
+        ClipboardAction::SetHtml { html, text } => ClipboardRequest::SetHtml {
+            html: html.clone(),
+            text: text.clone(),
+        },
+        // Paths are resolved here, since the daemon runs in another directory
+        ClipboardAction::GetFiles { output: dest_dir, max_bytes } => ClipboardRequest::GetFiles {
+            dest_dir: absolute_path(dest_dir)?,
+            max_bytes: *max_bytes,
+        },
+        ClipboardAction::SetFiles { paths, max_bytes } => ClipboardRequest::SetFiles {
+            paths: paths.iter().map(|path| absolute_path(path)).collect::<anyhow::Result<_>>()?,
+            max_bytes: *max_bytes,
+        },
+        ClipboardAction::History { count } => ClipboardRequest::History { count: *count },
+        ClipboardAction::Clear => ClipboardRequest::Clear,
+        ClipboardAction::Status => ClipboardRequest::Status,
     };
 
     let request = Request::Clipboard(clipboard_request);
     let response = client.send(&request, timeout_ms).await?;
 
-    output.print_response(&response);
     if !response.success {
+        output.print_response(&response);
         std::process::exit(1);
     }
 
+    // Save image data to file instead of printing it
+    if let ClipboardAction::GetImage { output: path } = &args.action {
+        if let Some(ResponseData::ClipboardImage { width, height, base64 }) = response.data {
+            let image_data = base64::engine::general_purpose::STANDARD.decode(&base64)?;
+
+            let path = Path::new(path);
+            let mut file = File::create(path)?;
+            file.write_all(&image_data)?;
+
+            if output.is_json() {
+                println!(
+                    r#"{{"success":true,"data":{{"type":"clipboard_image","path":"{}","width":{},"height":{}}}}}"#,
+                    path.display(),
+                    width,
+                    height
+                );
+            } else {
+                println!("Clipboard image saved to {} ({}x{})", path.display(), width, height);
+            }
+        }
+        return Ok(());
+    }
+
+    output.print_response(&response);
+
     Ok(())
 }
+
+/// Parse a line ending mode (already checked by clap).
+fn parse_line_endings(mode: &str) -> LineEndings {
+    match mode {
+        "crlf" => LineEndings::Crlf,
+        "lf" => LineEndings::Lf,
+        _ => LineEndings::Preserve,
+    }
+}
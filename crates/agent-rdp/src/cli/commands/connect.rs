@@ -3,12 +3,28 @@
 use std::io::{self, BufRead};
 use std::path::Path;
 
-use agent_rdp_protocol::{ConnectRequest, DriveMapping, Request};
+use agent_rdp_protocol::{
+    ConnectRequest, DriveMapping, ErrorCode, GatewayRequest, GraphicsCodec, MonitorLayout,
+    PerformanceFlag, PerformanceProfile, Request, Response, ResponseData, SecurityMode,
+    TlsVersion, FIRST_FRAME_TIMEOUT_MS,
+};
+use tracing::warn;
 
+use super::absolute_path;
 use crate::cli::ConnectArgs;
 use crate::output::Output;
 use crate::session_manager::SessionManager;
 
+/// Dry run exit code when the server can't be reached, TLS fails, or the
+/// connection drops or times out.
+const EXIT_UNREACHABLE: i32 = 2;
+
+/// Dry run exit code when the server rejects the credentials.
+const EXIT_AUTH_FAILED: i32 = 3;
+
+/// Time to wait for the daemon's answer beyond an attempt's connect timeout.
+const CONNECT_RESPONSE_MARGIN_MS: u64 = 5_000;
+
 pub async fn run(
     session: &str,
     args: ConnectArgs,
@@ -16,16 +32,57 @@ pub async fn run(
     timeout_ms: u64,
     stream_port: u16,
 ) -> anyhow::Result<()> {
-    // Get password from args, env, or stdin
+    // Get password from args, env, stdin, or a file
     let password = get_password(&args, output)?;
 
     // Parse drive mappings
     let drives = parse_drive_mappings(&args.drives, output)?;
 
-    let manager = SessionManager::new(session.to_string());
-    let mut client = manager.ensure_daemon().await?;
+    // Parse monitor layout
+    let monitors = parse_monitors(&args.monitors, output);
+
+    // Parse the logical coordinate space
+    let coordinate_space = args
+        .logical_size
+        .as_deref()
+        .map(|size| parse_logical_size(size, output));
+
+    // Parse keyboard layout
+    let keyboard_layout = args
+        .keyboard_layout
+        .as_deref()
+        .map(|layout| parse_keyboard_layout(layout, output));
+
+    // Parse security mode
+    let security = parse_security_mode(&args.security, output);
+    let min_tls = args.min_tls.as_deref().map(|version| parse_tls_version(version, output));
+
+    // Parse graphics codec
+    let codec = parse_codec(&args.codec, output);
+    let (perf_profile, perf_flags) = parse_perf_profile(&args.perf_profile, output);
 
-    let request = Request::Connect(ConnectRequest {
+    // The daemon falls back to the RDP credentials for the gateway
+    let gateway = args.gateway_host.map(|host| GatewayRequest {
+        host,
+        port: args.gateway_port,
+        username: args.gateway_user,
+        password: args.gateway_pass,
+        expected_cert_sha256: args.gateway_pin_cert,
+        insecure: args.gateway_insecure,
+    });
+
+    // Resolve the audio capture path here, since the daemon runs in another directory
+    let capture_audio = match args.capture_audio.as_deref() {
+        Some(path) => {
+            let expanded = shellexpand::tilde(path);
+            let absolute = std::env::current_dir()?.join(expanded.as_ref());
+            Some(absolute.to_string_lossy().to_string())
+        }
+        None => None,
+    };
+    let record = args.record.as_deref().map(absolute_path).transpose()?;
+
+    let connect = ConnectRequest {
         host: args.host,
         port: args.port,
         username: args.username,
@@ -34,14 +91,70 @@ pub async fn run(
         width: args.width,
         height: args.height,
         drives,
+        monitors,
+        coordinate_space,
         enable_win_automation: args.enable_win_automation,
+        test_input: args.test_input,
+        wait_first_frame: args.wait_first_frame,
         stream_port,
         // CLI enables the viewer HTML when streaming is enabled
         serve_viewer: stream_port > 0,
+        stream_max_frame_size: args.max_frame_size,
+        keyboard_layout,
+        expected_cert_sha256: args.pin_cert,
+        auto_reconnect: args.auto_reconnect,
+        max_reconnect_attempts: args.max_reconnect_attempts,
+        keepalive_interval_secs: args.keepalive_interval,
+        connect_retries: args.connect_retries,
+        retry_delay_ms: args.retry_delay,
+        connect_timeout_ms: args.connect_timeout,
+        security,
+        min_tls,
+        codec,
+        perf_profile,
+        perf_flags,
+        gateway,
+        proxy: args.proxy,
+        kdc: args.kdc,
+        capture_audio,
+        record,
+        record_fps: args.record_fps,
+        redirect_smartcard: args.redirect_smartcard,
+        enable_clipboard: !args.no_clipboard,
+        enable_audio: !args.no_audio,
+        idle_timeout_secs: args.idle_timeout,
+        client_name: args.client_name,
+        client_build: args.client_build,
+        dig_product_id: args.dig_product_id,
+        hardware_id: args.hardware_id,
+        clipboard_history: args.clipboard_history,
         ..Default::default()
-    });
+    };
+
+    if args.dry_run {
+        return dry_run(session, connect, output).await;
+    }
+    if args.print_cert_fingerprint {
+        return print_cert_fingerprint(session, connect, output).await;
+    }
+
+    let manager = SessionManager::new(session.to_string());
+    let mut client = manager.ensure_daemon().await?;
+
+    let wait_ms = connect_wait_ms(&connect, timeout_ms);
+    let response = client.send(&Request::Connect(Box::new(connect.clone())), wait_ms).await?;
+
+    if response.success {
+        let saved = if args.save_password {
+            connect
+        } else {
+            without_passwords(connect)
+        };
+        if let Err(e) = manager.save_connect_request(&saved) {
+            warn!("Failed to save connection settings: {}", e);
+        }
+    }
 
-    let response = client.send(&request, timeout_ms).await?;
     output.print_response(&response);
 
     if !response.success {
@@ -51,15 +164,220 @@ pub async fn run(
     Ok(())
 }
 
-/// Parse drive mapping strings (format: /path:DriveName) into DriveMappings.
+/// Time to wait for the daemon to answer a connect request.
+///
+/// Each attempt gets the full timeout, plus the waits between them. An attempt
+/// waits long enough for the daemon to report its own connect timeout, and
+/// the wait for the first frame comes on top.
+pub(crate) fn connect_wait_ms(connect: &ConnectRequest, timeout_ms: u64) -> u64 {
+    let attempt_ms = timeout_ms
+        .max(connect.connect_timeout_ms().saturating_add(CONNECT_RESPONSE_MARGIN_MS));
+    let first_frame_ms = if connect.wait_first_frame { FIRST_FRAME_TIMEOUT_MS } else { 0 };
+    (1..=connect.connect_retries).fold(attempt_ms.saturating_add(first_frame_ms), |total, retry| {
+        total.saturating_add(attempt_ms).saturating_add(connect.retry_delay_ms(retry))
+    })
+}
+
+/// Run the handshake in this process and report how far it got.
+async fn dry_run(session: &str, connect: ConnectRequest, output: &Output) -> anyhow::Result<()> {
+    let response = agent_rdp_daemon::handlers::connect::dry_run(session, connect).await;
+    output.print_response(&response);
+
+    let result = match response.data {
+        Some(ResponseData::DryRun(result)) => result,
+        _ => std::process::exit(1),
+    };
+    if !result.auth_ok {
+        let code = match result.error_code {
+            Some(ErrorCode::AuthenticationFailed | ErrorCode::LogonDenied | ErrorCode::AccountLocked) => {
+                EXIT_AUTH_FAILED
+            }
+            _ if !result.tls_ok => EXIT_UNREACHABLE,
+            Some(ErrorCode::ConnectionReset | ErrorCode::Timeout) => EXIT_UNREACHABLE,
+            // Licensing, protocol and other server-side failures
+            _ => 1,
+        };
+        std::process::exit(code);
+    }
+
+    Ok(())
+}
+
+/// Clear the passwords from connection settings before they're written to disk.
+fn without_passwords(mut connect: ConnectRequest) -> ConnectRequest {
+    connect.password = String::new();
+    if let Some(ref mut gateway) = connect.gateway {
+        gateway.password = None;
+    }
+    connect.proxy = connect.proxy.as_deref().map(strip_proxy_password);
+    connect
+}
+
+/// Drop the password from a proxy URL's user info, keeping the user name.
+fn strip_proxy_password(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let authority_end = rest.find('/').unwrap_or(rest.len());
+    let (authority, path) = rest.split_at(authority_end);
+    match authority.rsplit_once('@') {
+        Some((user_info, host)) => {
+            let user = user_info.split_once(':').map_or(user_info, |(user, _)| user);
+            format!("{}://{}@{}{}", scheme, user, host, path)
+        }
+        None => url.to_string(),
+    }
+}
+
+/// Print the server certificate fingerprint, read from a dry run so no session is kept.
+async fn print_cert_fingerprint(
+    session: &str,
+    connect: ConnectRequest,
+    output: &Output,
+) -> anyhow::Result<()> {
+    let response = agent_rdp_daemon::handlers::connect::dry_run(session, connect).await;
+    let result = match response.data {
+        Some(ResponseData::DryRun(result)) => result,
+        // The request was rejected before reaching the server
+        _ => {
+            output.print_response(&response);
+            std::process::exit(1);
+        }
+    };
+
+    match result.cert_fingerprint {
+        Some(sha256) => {
+            output.print_response(&Response::success(ResponseData::CertFingerprint { sha256 }));
+            Ok(())
+        }
+        None => {
+            let reason = result.error.unwrap_or_else(|| "no TLS handshake".to_string());
+            output.print_error(
+                "no_fingerprint",
+                &format!("Server certificate fingerprint not available: {}", reason),
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parse a security mode name (auto, nla, tls-only).
+fn parse_security_mode(mode: &str, output: &Output) -> SecurityMode {
+    match mode.to_lowercase().as_str() {
+        "auto" => SecurityMode::Auto,
+        "nla" => SecurityMode::Nla,
+        "tls-only" | "tls_only" | "tls" => SecurityMode::TlsOnly,
+        _ => {
+            output.print_error(
+                "invalid_security",
+                &format!("Invalid security mode '{}': expected auto, nla or tls-only", mode),
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parse a TLS version (1.0, 1.1, 1.2, 1.3).
+fn parse_tls_version(version: &str, output: &Output) -> TlsVersion {
+    match version.to_lowercase().trim_start_matches("tls").trim_start_matches('v') {
+        "1.0" | "1" => TlsVersion::Tls10,
+        "1.1" => TlsVersion::Tls11,
+        "1.2" => TlsVersion::Tls12,
+        "1.3" => TlsVersion::Tls13,
+        _ => {
+            output.print_error(
+                "invalid_tls_version",
+                &format!("Invalid TLS version '{}': expected 1.0, 1.1, 1.2 or 1.3", version),
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parse a graphics codec name (bitmap, remotefx).
+fn parse_codec(codec: &str, output: &Output) -> GraphicsCodec {
+    match codec.to_lowercase().as_str() {
+        "bitmap" => GraphicsCodec::Bitmap,
+        "remotefx" | "rfx" => GraphicsCodec::Remotefx,
+        "avc420" | "avc444" => {
+            output.print_error(
+                "invalid_codec",
+                &format!(
+                    "Codec '{}' (H.264) needs the graphics pipeline, which isn't supported yet: use bitmap or remotefx",
+                    codec
+                ),
+            );
+            std::process::exit(1);
+        }
+        _ => {
+            output.print_error(
+                "invalid_codec",
+                &format!("Invalid codec '{}': expected bitmap or remotefx", codec),
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parse a performance profile name, or a comma-separated list of performance flags.
+fn parse_perf_profile(
+    profile: &str,
+    output: &Output,
+) -> (PerformanceProfile, Option<Vec<PerformanceFlag>>) {
+    match profile.to_lowercase().as_str() {
+        "full" => return (PerformanceProfile::Full, None),
+        "lan" => return (PerformanceProfile::Lan, None),
+        "broadband" => return (PerformanceProfile::Broadband, None),
+        "modem" => return (PerformanceProfile::Modem, None),
+        _ => {}
+    }
+
+    let mut flags = Vec::new();
+    for flag in profile.split(',').map(str::trim).filter(|flag| !flag.is_empty()) {
+        let flag = match flag.to_lowercase().replace('_', "-").as_str() {
+            "disable-wallpaper" => PerformanceFlag::DisableWallpaper,
+            "disable-full-window-drag" => PerformanceFlag::DisableFullWindowDrag,
+            "disable-menu-animations" => PerformanceFlag::DisableMenuAnimations,
+            "disable-theming" => PerformanceFlag::DisableTheming,
+            "disable-cursor-shadow" => PerformanceFlag::DisableCursorShadow,
+            "disable-cursor-settings" => PerformanceFlag::DisableCursorSettings,
+            "enable-font-smoothing" => PerformanceFlag::EnableFontSmoothing,
+            "enable-desktop-composition" => PerformanceFlag::EnableDesktopComposition,
+            _ => {
+                output.print_error(
+                    "invalid_perf_profile",
+                    &format!(
+                        "Invalid performance profile or flag '{}': expected full, lan, \
+                         broadband, modem, or flags such as disable-wallpaper",
+                        flag
+                    ),
+                );
+                std::process::exit(1);
+            }
+        };
+        flags.push(flag);
+    }
+    (PerformanceProfile::Lan, Some(flags))
+}
+
+/// Parse drive mapping strings (format: /path:DriveName[:ro]) into DriveMappings.
 fn parse_drive_mappings(drives: &[String], output: &Output) -> anyhow::Result<Vec<DriveMapping>> {
     let mut result = Vec::new();
 
     for drive_spec in drives {
+        // Strip an optional trailing access mode before splitting path from name
+        let (spec, read_only) = if let Some(spec) = drive_spec.strip_suffix(":ro") {
+            (spec, true)
+        } else if let Some(spec) = drive_spec.strip_suffix(":rw") {
+            (spec, false)
+        } else {
+            (drive_spec.as_str(), false)
+        };
+
         // Find the last colon to split path from name
-        if let Some(colon_pos) = drive_spec.rfind(':') {
-            let path = &drive_spec[..colon_pos];
-            let name = &drive_spec[colon_pos + 1..];
+        if let Some(colon_pos) = spec.rfind(':') {
+            let path = &spec[..colon_pos];
+            let name = &spec[colon_pos + 1..];
 
             if path.is_empty() {
                 output.print_error(
@@ -100,12 +418,13 @@ fn parse_drive_mappings(drives: &[String], output: &Output) -> anyhow::Result<Ve
             result.push(DriveMapping {
                 path: expanded_path.into_owned(),
                 name: name.to_string(),
+                read_only,
             });
         } else {
             output.print_error(
                 "invalid_drive",
                 &format!(
-                    "Invalid drive mapping '{}': expected format /path:DriveName",
+                    "Invalid drive mapping '{}': expected format /path:DriveName[:ro]",
                     drive_spec
                 ),
             );
@@ -116,9 +435,158 @@ fn parse_drive_mappings(drives: &[String], output: &Output) -> anyhow::Result<Ve
     Ok(result)
 }
 
-/// Get password from command line, environment, or stdin.
+/// Parse monitor specs (format: WxH[+X+Y], offsets may be negative) into a layout.
+///
+/// The first monitor is primary; the daemon checks sizes and overlaps.
+fn parse_monitors(specs: &[String], output: &Output) -> Vec<MonitorLayout> {
+    specs
+        .iter()
+        .enumerate()
+        .map(|(index, spec)| match parse_monitor(spec) {
+            Some((width, height, x, y)) => MonitorLayout {
+                x,
+                y,
+                width,
+                height,
+                primary: index == 0,
+            },
+            None => {
+                output.print_error(
+                    "invalid_monitor",
+                    &format!(
+                        "Invalid monitor '{}': expected WxH[+X+Y] (e.g., 1920x1080+1920+0)",
+                        spec
+                    ),
+                );
+                std::process::exit(1);
+            }
+        })
+        .collect()
+}
+
+/// Split a single WxH[+X+Y] spec.
+fn parse_monitor(spec: &str) -> Option<(u32, u32, i32, i32)> {
+    let (width, rest) = spec.trim().split_once(['x', 'X'])?;
+    let width = width.parse().ok()?;
+
+    // The offsets start at the first sign after the height
+    let (height, offsets) = match rest.find(['+', '-']) {
+        Some(pos) => rest.split_at(pos),
+        None => (rest, ""),
+    };
+    let height = height.parse().ok()?;
+    if offsets.is_empty() {
+        return Some((width, height, 0, 0));
+    }
+
+    let y_pos = offsets[1..].find(['+', '-'])? + 1;
+    let (x, y) = offsets.split_at(y_pos);
+    Some((width, height, x.parse().ok()?, y.parse().ok()?))
+}
+
+/// Parse a logical size (format: WxH).
+fn parse_logical_size(size: &str, output: &Output) -> (u16, u16) {
+    let parsed = size
+        .trim()
+        .split_once(['x', 'X'])
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+    match parsed {
+        Some((width, height)) if width > 0 && height > 0 => (width, height),
+        _ => {
+            output.print_error(
+                "invalid_logical_size",
+                &format!("Invalid logical size '{}': expected WxH (e.g., 1280x800)", size),
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Well-known keyboard layouts by locale name.
+const KEYBOARD_LAYOUTS: &[(&str, u32)] = &[
+    ("en-us", 0x0409),
+    ("en-gb", 0x0809),
+    ("en-ca", 0x1009),
+    ("en-au", 0x0C09),
+    ("de-de", 0x0407),
+    ("de-ch", 0x0807),
+    ("de-at", 0x0C07),
+    ("fr-fr", 0x040C),
+    ("fr-be", 0x080C),
+    ("fr-ca", 0x0C0C),
+    ("fr-ch", 0x100C),
+    ("es-es", 0x040A),
+    ("es-mx", 0x080A),
+    ("it-it", 0x0410),
+    ("pt-br", 0x0416),
+    ("pt-pt", 0x0816),
+    ("nl-nl", 0x0413),
+    ("nl-be", 0x0813),
+    ("sv-se", 0x041D),
+    ("nb-no", 0x0414),
+    ("da-dk", 0x0406),
+    ("fi-fi", 0x040B),
+    ("pl-pl", 0x0415),
+    ("cs-cz", 0x0405),
+    ("hu-hu", 0x040E),
+    ("ru-ru", 0x0419),
+    ("uk-ua", 0x0422),
+    ("tr-tr", 0x041F),
+    ("el-gr", 0x0408),
+    ("he-il", 0x040D),
+    ("ar-sa", 0x0401),
+    ("ja-jp", 0x0411),
+    ("ko-kr", 0x0412),
+    ("zh-cn", 0x0804),
+    ("zh-tw", 0x0404),
+];
+
+/// Parse a keyboard layout given as a hex LCID (0x40C) or a locale name (fr-FR).
+fn parse_keyboard_layout(layout: &str, output: &Output) -> u32 {
+    let trimmed = layout.trim();
+
+    let hex = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"));
+    if let Some(hex) = hex {
+        if let Ok(lcid) = u32::from_str_radix(hex, 16) {
+            return lcid;
+        }
+    } else {
+        let name = trimmed.to_lowercase().replace('_', "-");
+        if let Some((_, lcid)) = KEYBOARD_LAYOUTS.iter().find(|(n, _)| *n == name) {
+            return *lcid;
+        }
+    }
+
+    output.print_error(
+        "invalid_keyboard_layout",
+        &format!(
+            "Invalid keyboard layout '{}': expected a hex LCID (e.g., 0x40C) or locale name (e.g., fr-FR)",
+            layout
+        ),
+    );
+    std::process::exit(1);
+}
+
+/// Get password from command line, environment, stdin, or a file.
 fn get_password(args: &ConnectArgs, output: &Output) -> anyhow::Result<String> {
-    // Priority: --password-stdin > --password/env
+    // --password-file and --password-stdin (mutually exclusive) beat --password/env,
+    // so a file or pipe still wins when AGENT_RDP_PASSWORD is set in the environment
+    if let Some(ref path) = args.password_file {
+        let path = shellexpand::tilde(path);
+        return match std::fs::read_to_string(path.as_ref()) {
+            Ok(contents) => Ok(contents.lines().next().unwrap_or_default().trim_end().to_string()),
+            Err(e) => {
+                output.print_error(
+                    "file_error",
+                    &format!("Failed to read password file '{}': {}", path, e),
+                );
+                std::process::exit(1);
+            }
+        };
+    }
+
     if args.password_stdin {
         let stdin = io::stdin();
         let mut line = String::new();
@@ -133,7 +601,7 @@ fn get_password(args: &ConnectArgs, output: &Output) -> anyhow::Result<String> {
     // No password provided
     output.print_error(
         "missing_password",
-        "Password required. Use --password, AGENT_RDP_PASSWORD env var, or --password-stdin",
+        "Password required. Use --password, AGENT_RDP_PASSWORD env var, --password-stdin, or --password-file",
     );
     std::process::exit(1);
 }
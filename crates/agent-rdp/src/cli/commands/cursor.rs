@@ -0,0 +1,75 @@
+//! Cursor command implementation.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use agent_rdp_protocol::{Request, ResponseData};
+use base64::Engine;
+
+use crate::cli::CursorArgs;
+use crate::output::Output;
+use crate::session_manager::SessionManager;
+
+pub async fn run(
+    session: &str,
+    args: CursorArgs,
+    output: &Output,
+    timeout_ms: u64,
+) -> anyhow::Result<()> {
+    let manager = SessionManager::new(session.to_string());
+
+    if !manager.is_daemon_alive() {
+        output.print_error("daemon_not_running", "No daemon running for this session");
+        std::process::exit(1);
+    }
+
+    let mut client = manager.ensure_daemon().await?;
+    let request = Request::CursorInfo {
+        include_image: args.output.is_some(),
+    };
+    let response = client.send(&request, timeout_ms).await?;
+
+    if !response.success {
+        output.print_response(&response);
+        std::process::exit(1);
+    }
+
+    // Save the pointer image to a file instead of printing it
+    if let Some(ref path) = args.output {
+        if let Some(ResponseData::CursorInfo(ref info)) = response.data {
+            let Some(ref base64) = info.base64 else {
+                output.print_error("no_cursor_image", "The server hasn't sent a pointer shape");
+                std::process::exit(1);
+            };
+            let image_data = base64::engine::general_purpose::STANDARD.decode(base64)?;
+
+            let path = Path::new(path);
+            let mut file = File::create(path)?;
+            file.write_all(&image_data)?;
+
+            if output.is_json() {
+                println!(
+                    r#"{{"success":true,"data":{{"type":"cursor_info","x":{},"y":{},"visible":{},"path":"{}"}}}}"#,
+                    info.x,
+                    info.y,
+                    info.visible,
+                    path.display()
+                );
+            } else {
+                println!(
+                    "Pointer at ({}, {}){}, image saved to {}",
+                    info.x,
+                    info.y,
+                    if info.visible { "" } else { " (hidden)" },
+                    path.display()
+                );
+            }
+            return Ok(());
+        }
+    }
+
+    output.print_response(&response);
+
+    Ok(())
+}
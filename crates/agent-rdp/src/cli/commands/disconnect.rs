@@ -7,6 +7,7 @@ use crate::session_manager::SessionManager;
 
 pub async fn run(
     session: &str,
+    logoff: bool,
     output: &Output,
     timeout_ms: u64,
 ) -> anyhow::Result<()> {
@@ -17,7 +18,21 @@ pub async fn run(
         std::process::exit(1);
     }
 
-    let mut client = manager.ensure_daemon().await?;
+    // Attach without the version check, so a daemon from another release can still be stopped
+    let Some(mut client) = manager.attach().await else {
+        output.print_error("daemon_not_running", "Could not connect to the session daemon");
+        std::process::exit(1);
+    };
+
+    // Log off while the session is still up; on failure it stays connected
+    if logoff {
+        let response = client.send(&Request::Disconnect { logoff: true }, timeout_ms).await?;
+        if !response.success {
+            output.print_response(&response);
+            std::process::exit(1);
+        }
+    }
+
     // Send Shutdown to disconnect RDP and close the session daemon
     let response = client.send(&Request::Shutdown, timeout_ms).await?;
     output.print_response(&response);
@@ -2,6 +2,7 @@
 
 use agent_rdp_protocol::{DriveRequest, Request};
 
+use super::absolute_path;
 use crate::cli::{DriveAction, DriveArgs};
 use crate::output::Output;
 use crate::session_manager::SessionManager;
@@ -23,6 +24,21 @@ pub async fn run(
 
     let drive_request = match args.action {
         DriveAction::List => DriveRequest::List,
+        DriveAction::Mount { path, name, read_only } => DriveRequest::Mount {
+            path: absolute_path(&path)?,
+            name,
+            read_only,
+        },
+        DriveAction::Unmount { name } => DriveRequest::Unmount { name },
+        // Local paths are resolved here, since the daemon runs in another directory
+        DriveAction::Push { local, remote } => DriveRequest::Push {
+            local: absolute_path(&local)?,
+            remote,
+        },
+        DriveAction::Pull { remote, local } => DriveRequest::Pull {
+            remote,
+            local: absolute_path(&local)?,
+        },
     };
 
     let request = Request::Drive(drive_request);
@@ -0,0 +1,84 @@
+//! Frame command implementation.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use agent_rdp_protocol::{FrameEncoding, FrameRequest, IpcFraming, Request, ResponseData};
+
+use crate::cli::FrameArgs;
+use crate::output::Output;
+use crate::session_manager::SessionManager;
+
+pub async fn run(
+    session: &str,
+    args: FrameArgs,
+    output: &Output,
+    timeout_ms: u64,
+) -> anyhow::Result<()> {
+    let manager = SessionManager::new(session.to_string());
+
+    if !manager.is_daemon_alive() {
+        output.print_error("daemon_not_running", "No daemon running for this session");
+        std::process::exit(1);
+    }
+
+    let encoding = match args.encoding.to_lowercase().as_str() {
+        "raw" => FrameEncoding::Raw,
+        "png" => FrameEncoding::Png,
+        _ => {
+            output.print_error(
+                "invalid_format",
+                &format!("Unsupported encoding '{}': must be raw or png", args.encoding),
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let mut client = manager.ensure_daemon().await?;
+    // Length-prefixed frames carry the pixels with an explicit size
+    client.use_framing(IpcFraming::LengthPrefixed, timeout_ms).await?;
+
+    // Binary skips base64 on both ends
+    let request = Request::Frame(FrameRequest { encoding, binary: true });
+    let response = client.send(&request, timeout_ms).await?;
+
+    if !response.success {
+        output.print_response(&response);
+        std::process::exit(1);
+    }
+
+    if let Some(ResponseData::Frame { width, height, stride, size_bytes, payload, .. }) = response.data {
+        let path = Path::new(&args.output);
+        let mut file = File::create(path)?;
+        file.write_all(&payload)?;
+
+        let encoding = match encoding {
+            FrameEncoding::Raw => "raw",
+            FrameEncoding::Png => "png",
+        };
+        if output.is_json() {
+            println!(
+                r#"{{"success":true,"data":{{"type":"frame","path":"{}","width":{},"height":{},"stride":{},"encoding":"{}","size_bytes":{}}}}}"#,
+                path.display(),
+                width,
+                height,
+                stride,
+                encoding,
+                size_bytes
+            );
+        } else {
+            println!(
+                "Frame saved to {} ({}x{}, {}, stride {}, {} bytes)",
+                path.display(),
+                width,
+                height,
+                encoding,
+                stride,
+                size_bytes
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,231 @@
+//! Input script command implementation.
+//!
+//! Scripts have one action per line; blank lines and lines starting with `#` are
+//! ignored. Supported actions:
+//!
+//! ```text
+//! move X Y
+//! click X Y | right-click X Y | double-click X Y | middle-click X Y
+//! drag X1 Y1 X2 Y2 [STEPS]
+//! mouse-down [left|right|middle] | mouse-up [left|right|middle]
+//! type TEXT
+//! press KEYS
+//! key-down KEY | key-up KEY
+//! scroll up|down|left|right [AMOUNT] [X Y]
+//! wait MS
+//! ```
+//!
+//! A line starting with `{` is parsed as a JSON input event, e.g.
+//! `{"type":"mouse","action":"move","x":10,"y":20}`.
+
+use std::io::Read;
+
+use agent_rdp_protocol::{
+    InputBatchRequest, InputEvent, KeyboardRequest, MouseButton, MouseRequest, Request,
+    ScrollDirection, ScrollRequest, TypeMode,
+};
+
+use crate::cli::InputArgs;
+use crate::output::Output;
+use crate::session_manager::SessionManager;
+
+pub async fn run(
+    session: &str,
+    args: InputArgs,
+    output: &Output,
+    timeout_ms: u64,
+) -> anyhow::Result<()> {
+    let script = if args.script == "-" {
+        let mut script = String::new();
+        std::io::stdin().read_to_string(&mut script)?;
+        script
+    } else {
+        match std::fs::read_to_string(&args.script) {
+            Ok(script) => script,
+            Err(e) => {
+                output.print_error(
+                    "file_error",
+                    &format!("Failed to read '{}': {}", args.script, e),
+                );
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let events = match parse_script(&script) {
+        Ok(events) => events,
+        Err(e) => {
+            output.print_error("invalid_script", &e);
+            std::process::exit(1);
+        }
+    };
+
+    let manager = SessionManager::new(session.to_string());
+
+    if !manager.is_daemon_alive() {
+        output.print_error("daemon_not_running", "No daemon running for this session");
+        std::process::exit(1);
+    }
+
+    let mut client = manager.ensure_daemon().await?;
+    let request = Request::InputBatch(InputBatchRequest {
+        events,
+        delay_ms: args.delay,
+    });
+    let response = client.send(&request, timeout_ms).await?;
+    output.print_response(&response);
+
+    if !response.success {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Parse a script into input events, reporting the line of the first error.
+fn parse_script(script: &str) -> Result<Vec<InputEvent>, String> {
+    let mut events = Vec::new();
+
+    for (index, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let event = parse_line(line).map_err(|e| format!("Line {}: {}", index + 1, e))?;
+        events.push(event);
+    }
+
+    if events.is_empty() {
+        return Err("Script contains no actions".to_string());
+    }
+    Ok(events)
+}
+
+/// Parse a single script line.
+fn parse_line(line: &str) -> Result<InputEvent, String> {
+    if line.starts_with('{') {
+        return serde_json::from_str(line).map_err(|e| format!("Invalid JSON event: {}", e));
+    }
+
+    let (action, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    let args: Vec<&str> = rest.split_whitespace().collect();
+
+    let event = match action.to_lowercase().as_str() {
+        "move" => {
+            let (x, y) = point(&args)?;
+            InputEvent::Mouse(MouseRequest::Move { x, y, human: false })
+        }
+        "click" => {
+            let (x, y) = point(&args)?;
+            InputEvent::Mouse(MouseRequest::Click { x, y, human: false })
+        }
+        "right-click" => {
+            let (x, y) = point(&args)?;
+            InputEvent::Mouse(MouseRequest::RightClick { x, y })
+        }
+        "double-click" => {
+            let (x, y) = point(&args)?;
+            InputEvent::Mouse(MouseRequest::DoubleClick { x, y })
+        }
+        "middle-click" => {
+            let (x, y) = point(&args)?;
+            InputEvent::Mouse(MouseRequest::MiddleClick { x, y })
+        }
+        "drag" => {
+            let steps = match args.len() {
+                4 => None,
+                5 => Some(number(args[4])?),
+                _ => return Err("drag expects X1 Y1 X2 Y2 [STEPS]".to_string()),
+            };
+            let (from_x, from_y) = point(&args[..2])?;
+            let (to_x, to_y) = point(&args[2..4])?;
+            InputEvent::Mouse(MouseRequest::Drag {
+                from_x,
+                from_y,
+                to_x,
+                to_y,
+                steps,
+            })
+        }
+        "mouse-down" => InputEvent::Mouse(MouseRequest::ButtonDown {
+            button: mouse_button(&args)?,
+        }),
+        "mouse-up" => InputEvent::Mouse(MouseRequest::ButtonUp {
+            button: mouse_button(&args)?,
+        }),
+        "type" => InputEvent::Keyboard(KeyboardRequest::Type {
+            text: rest.to_string(),
+            mode: TypeMode::Unicode,
+            delay_ms: None,
+        }),
+        "press" => InputEvent::Keyboard(KeyboardRequest::Press {
+            keys: single_arg(&args, "press expects KEYS")?,
+        }),
+        "key-down" => InputEvent::Keyboard(KeyboardRequest::KeyDown {
+            key: single_arg(&args, "key-down expects KEY")?,
+        }),
+        "key-up" => InputEvent::Keyboard(KeyboardRequest::KeyUp {
+            key: single_arg(&args, "key-up expects KEY")?,
+        }),
+        "scroll" => {
+            let direction = match args.first().map(|d| d.to_lowercase()).as_deref() {
+                Some("up") => ScrollDirection::Up,
+                Some("down") => ScrollDirection::Down,
+                Some("left") => ScrollDirection::Left,
+                Some("right") => ScrollDirection::Right,
+                _ => return Err("scroll expects up, down, left or right".to_string()),
+            };
+            let (amount, x, y) = match &args[1..] {
+                [] => (3, None, None),
+                [amount] => (number(amount)?, None, None),
+                [amount, x, y] => (number(amount)?, Some(number(x)?), Some(number(y)?)),
+                _ => return Err("scroll expects DIRECTION [AMOUNT] [X Y]".to_string()),
+            };
+            InputEvent::Scroll(ScrollRequest {
+                direction,
+                amount,
+                x,
+                y,
+            })
+        }
+        "wait" => {
+            let ms = number(single_arg(&args, "wait expects MS")?.as_str())?;
+            InputEvent::Wait { ms }
+        }
+        other => return Err(format!("Unknown action '{}'", other)),
+    };
+
+    Ok(event)
+}
+
+/// Parse a number argument.
+fn number<T: std::str::FromStr>(arg: &str) -> Result<T, String> {
+    arg.parse().map_err(|_| format!("Invalid number '{}'", arg))
+}
+
+/// Parse an X Y coordinate pair.
+fn point(args: &[&str]) -> Result<(u16, u16), String> {
+    match args {
+        [x, y] => Ok((number(x)?, number(y)?)),
+        _ => Err("Expected X Y coordinates".to_string()),
+    }
+}
+
+/// Parse exactly one argument.
+fn single_arg(args: &[&str], usage: &str) -> Result<String, String> {
+    match args {
+        [arg] => Ok(arg.to_string()),
+        _ => Err(usage.to_string()),
+    }
+}
+
+/// Parse an optional mouse button argument (default: left).
+fn mouse_button(args: &[&str]) -> Result<MouseButton, String> {
+    match args.first().map(|b| b.to_lowercase()).as_deref() {
+        None | Some("left") => Ok(MouseButton::Left),
+        Some("right") => Ok(MouseButton::Right),
+        Some("middle") => Ok(MouseButton::Middle),
+        Some(other) => Err(format!("Unknown mouse button '{}'", other)),
+    }
+}
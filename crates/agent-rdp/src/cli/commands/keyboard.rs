@@ -1,6 +1,6 @@
 //! Keyboard command implementation.
 
-use agent_rdp_protocol::{KeyboardRequest, Request};
+use agent_rdp_protocol::{KeyboardRequest, Request, TypeMode};
 
 use crate::cli::{KeyboardAction, KeyboardArgs};
 use crate::output::Output;
@@ -22,8 +22,25 @@ pub async fn run(
     let mut client = manager.ensure_daemon().await?;
 
     let keyboard_request = match args.action {
-        KeyboardAction::Type { text } => KeyboardRequest::Type { text },
+        KeyboardAction::Type { text, mode, delay } => KeyboardRequest::Type {
+            text,
+            mode: parse_type_mode(&mode, output),
+            delay_ms: delay,
+        },
         KeyboardAction::Press { keys } => KeyboardRequest::Press { keys },
+        KeyboardAction::Raw {
+            scancode,
+            extended,
+            release,
+        } => KeyboardRequest::Raw {
+            scancode,
+            extended,
+            release,
+        },
+        KeyboardAction::Hold { keys } => KeyboardRequest::Hold { keys },
+        KeyboardAction::Release { keys: Some(keys), all: false } => KeyboardRequest::Release { keys },
+        KeyboardAction::Release { .. } => KeyboardRequest::ReleaseAll,
+        KeyboardAction::Paste { text, restore } => KeyboardRequest::Paste { text, restore },
     };
 
     let request = Request::Keyboard(keyboard_request);
@@ -36,3 +53,18 @@ pub async fn run(
 
     Ok(())
 }
+
+/// Parse a typing mode name (unicode, scancode).
+fn parse_type_mode(mode: &str, output: &Output) -> TypeMode {
+    match mode.to_lowercase().as_str() {
+        "unicode" => TypeMode::Unicode,
+        "scancode" => TypeMode::Scancode,
+        _ => {
+            output.print_error(
+                "invalid_type_mode",
+                &format!("Invalid typing mode '{}': expected unicode or scancode", mode),
+            );
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,7 +1,10 @@
 //! Locate command implementation (OCR-based text location).
 
-use agent_rdp_protocol::{LocateRequest, Request, ResponseData};
+use agent_rdp_protocol::{
+    ElementBounds, LocateClickRequest, LocateRequest, MouseButton, Request, ResponseData,
+};
 
+use super::absolute_path;
 use crate::cli::LocateArgs;
 use crate::output::Output;
 use crate::session_manager::SessionManager;
@@ -21,13 +24,61 @@ pub async fn run(
 
     let mut client = manager.ensure_daemon().await?;
 
+    // The daemon may run from another directory
+    let models_dir = args.ocr_models.as_deref().map(absolute_path).transpose()?;
+
     let search_text = args.text.clone().unwrap_or_default();
 
+    let region = match args.region.as_deref() {
+        Some(&[x, y, width, height]) => Some(ElementBounds {
+            x: x as i32,
+            y: y as i32,
+            width: width as i32,
+            height: height as i32,
+        }),
+        _ => None,
+    };
+
+    if args.click {
+        let button = match args.button.as_deref() {
+            None | Some("left") => MouseButton::Left,
+            Some("right") => MouseButton::Right,
+            Some("middle") => MouseButton::Middle,
+            Some(other) => {
+                output.print_error(
+                    "invalid_request",
+                    &format!("Unknown button '{}': must be left, right or middle", other),
+                );
+                std::process::exit(1);
+            }
+        };
+        let request = Request::LocateClick(LocateClickRequest {
+            text: search_text,
+            pattern: args.pattern,
+            ignore_case: !args.case_sensitive,
+            region,
+            button: Some(button),
+            index: args.index,
+            min_confidence: args.min_confidence,
+            models_dir,
+            lang: args.lang,
+        });
+        let response = client.send(&request, timeout_ms).await?;
+        output.print_response(&response);
+        if !response.success {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let request = Request::Locate(LocateRequest {
         text: search_text.clone(),
         pattern: args.pattern,
         ignore_case: !args.case_sensitive,
         all: args.all,
+        region,
+        models_dir,
+        lang: args.lang,
     });
 
     let response = client.send(&request, timeout_ms).await?;
@@ -52,8 +103,8 @@ pub async fn run(
             // Show all lines
             println!("Found {} text lines on screen:", result.matches.len());
             for m in &result.matches {
-                println!("  '{}' at ({}, {}) size {}x{} - center: ({}, {})",
-                    m.text, m.x, m.y, m.width, m.height, m.center_x, m.center_y);
+                println!("  '{}' at ({}, {}) size {}x{} - center: ({}, {}) - confidence: {:.0}%",
+                    m.text, m.x, m.y, m.width, m.height, m.center_x, m.center_y, m.confidence * 100.0);
             }
         } else {
             // Search mode
@@ -63,8 +114,8 @@ pub async fn run(
                 println!("Found {} line(s) containing '{}' ({} lines detected):",
                     result.matches.len(), search_text, result.total_words);
                 for m in &result.matches {
-                    println!("  '{}' at ({}, {}) size {}x{} - center: ({}, {})",
-                        m.text, m.x, m.y, m.width, m.height, m.center_x, m.center_y);
+                    println!("  '{}' at ({}, {}) size {}x{} - center: ({}, {}) - confidence: {:.0}%",
+                        m.text, m.x, m.y, m.width, m.height, m.center_x, m.center_y, m.confidence * 100.0);
                 }
                 // Show a helpful hint for clicking
                 if let Some(first) = result.matches.first() {
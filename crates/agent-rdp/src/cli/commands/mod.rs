@@ -3,13 +3,28 @@
 pub mod automate;
 pub mod clipboard;
 pub mod connect;
+pub mod cursor;
 pub mod disconnect;
 pub mod drive;
+pub mod frame;
+pub mod input;
 pub mod keyboard;
 pub mod locate;
 pub mod mouse;
+pub mod resize;
 pub mod screenshot;
 pub mod scroll;
 pub mod session;
+pub mod stream_config;
 pub mod view;
 pub mod wait;
+pub mod wait_for_change;
+
+use std::path::PathBuf;
+
+/// Expand `~` and make a path absolute relative to the current directory.
+pub(crate) fn absolute_path(path: &str) -> anyhow::Result<String> {
+    let expanded = shellexpand::tilde(path);
+    let absolute: PathBuf = std::env::current_dir()?.join(expanded.as_ref());
+    Ok(absolute.to_string_lossy().to_string())
+}
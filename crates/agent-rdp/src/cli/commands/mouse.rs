@@ -1,8 +1,9 @@
 //! Mouse command implementation.
 
-use agent_rdp_protocol::{MouseRequest, Request};
+use agent_rdp_protocol::{MouseButton, MouseRequest, Request, ResponseData};
 
 use crate::cli::{MouseAction, MouseArgs};
+use crate::ipc_client::IpcClient;
 use crate::output::Output;
 use crate::session_manager::SessionManager;
 
@@ -21,17 +22,50 @@ pub async fn run(
 
     let mut client = manager.ensure_daemon().await?;
 
+    // Translate monitor-relative coordinates to the virtual desktop
+    let (dx, dy) = match args.monitor {
+        Some(index) => monitor_origin(&mut client, index, output, timeout_ms).await?,
+        None => (0, 0),
+    };
+    let at = |x: u16, y: u16| (offset(x, dx, output), offset(y, dy, output));
+
     let mouse_request = match args.action {
-        MouseAction::Click { x, y } => MouseRequest::Click { x, y },
-        MouseAction::RightClick { x, y } => MouseRequest::RightClick { x, y },
-        MouseAction::DoubleClick { x, y } => MouseRequest::DoubleClick { x, y },
-        MouseAction::Move { x, y } => MouseRequest::Move { x, y },
-        MouseAction::Drag { x1, y1, x2, y2 } => MouseRequest::Drag {
-            from_x: x1,
-            from_y: y1,
-            to_x: x2,
-            to_y: y2,
-        },
+        MouseAction::Click { x, y, human } => {
+            let (x, y) = at(x, y);
+            MouseRequest::Click { x, y, human }
+        }
+        MouseAction::RightClick { x, y } => {
+            let (x, y) = at(x, y);
+            MouseRequest::RightClick { x, y }
+        }
+        MouseAction::DoubleClick { x, y } => {
+            let (x, y) = at(x, y);
+            MouseRequest::DoubleClick { x, y }
+        }
+        MouseAction::Move { x, y, human } => {
+            let (x, y) = at(x, y);
+            MouseRequest::Move { x, y, human }
+        }
+        MouseAction::Drag { x1, y1, x2, y2, steps } => {
+            let (from_x, from_y) = at(x1, y1);
+            let (to_x, to_y) = at(x2, y2);
+            MouseRequest::Drag {
+                from_x,
+                from_y,
+                to_x,
+                to_y,
+                steps: Some(steps),
+            }
+        }
+        MouseAction::Down { x, y, button } => {
+            let (x, y) = at(x, y);
+            MouseRequest::Down { x, y, button: mouse_button(&button) }
+        }
+        MouseAction::Up { x, y, button } => {
+            let (x, y) = at(x, y);
+            MouseRequest::Up { x, y, button: mouse_button(&button) }
+        }
+        MouseAction::ReleaseAll => MouseRequest::ReleaseAll,
     };
 
     let request = Request::Mouse(mouse_request);
@@ -44,3 +78,55 @@ pub async fn run(
 
     Ok(())
 }
+
+/// Parse a button name (clap already limits it to left, right or middle).
+fn mouse_button(name: &str) -> MouseButton {
+    match name {
+        "right" => MouseButton::Right,
+        "middle" => MouseButton::Middle,
+        _ => MouseButton::Left,
+    }
+}
+
+/// Look up the top-left corner of a monitor in the virtual desktop.
+async fn monitor_origin(
+    client: &mut IpcClient,
+    index: u32,
+    output: &Output,
+    timeout_ms: u64,
+) -> anyhow::Result<(u32, u32)> {
+    let response = client.send(&Request::SessionInfo, timeout_ms).await?;
+    let monitors = match response.data {
+        Some(ResponseData::SessionInfo(info)) => info.monitors,
+        _ => {
+            output.print_response(&response);
+            std::process::exit(1);
+        }
+    };
+
+    match monitors.get(index as usize) {
+        Some(monitor) => Ok((monitor.x as u32, monitor.y as u32)),
+        None => {
+            output.print_error(
+                "invalid_monitor",
+                &format!(
+                    "Monitor {} does not exist (session has {} monitors)",
+                    index,
+                    monitors.len()
+                ),
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Shift a coordinate by a monitor offset, failing if it leaves the protocol range.
+fn offset(value: u16, origin: u32, output: &Output) -> u16 {
+    match u16::try_from(u32::from(value) + origin) {
+        Ok(value) => value,
+        Err(_) => {
+            output.print_error("invalid_coordinates", "Coordinate out of range for the desktop");
+            std::process::exit(1);
+        }
+    }
+}
@@ -4,9 +4,13 @@ use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
-use agent_rdp_protocol::{ImageFormat, Request, ResponseData, ScreenshotRequest};
+use agent_rdp_protocol::{
+    ImageFormat, Request, ResponseData, ScreenshotRequest, DEFAULT_STABLE_TIMEOUT_MS,
+};
 use base64::Engine;
+use serde::{Deserialize, Serialize};
 
+use super::absolute_path;
 use crate::cli::ScreenshotArgs;
 use crate::output::Output;
 use crate::session_manager::SessionManager;
@@ -26,16 +30,43 @@ pub async fn run(
 
     let mut client = manager.ensure_daemon().await?;
 
-    let format = match args.format.to_lowercase().as_str() {
-        "png" => ImageFormat::Png,
-        "jpeg" | "jpg" => ImageFormat::Jpeg,
-        _ => {
-            output.print_error("invalid_format", "Format must be 'png' or 'jpeg'");
+    let format = match image_format(args.format.as_deref(), &args.output) {
+        Ok(format) => format,
+        Err(message) => {
+            output.print_error("invalid_format", &message);
             std::process::exit(1);
         }
     };
 
-    let request = Request::Screenshot(ScreenshotRequest { format });
+    // Skip the download when the file still holds an identical capture
+    let cache_path = manager.session_dir().join(ETAG_CACHE_FILE);
+    let output_path = absolute_path(&args.output)?;
+    let if_changed_since = LastScreenshot::load(&cache_path)
+        .filter(|last| last.path == output_path && Path::new(&output_path).is_file())
+        .map(|last| last.etag);
+
+    let (x, y, width, height) = match args.region.as_deref() {
+        Some(&[x, y, width, height]) => (Some(x), Some(y), Some(width), Some(height)),
+        _ => (None, None, None, None),
+    };
+
+    let request = Request::Screenshot(ScreenshotRequest {
+        format,
+        x,
+        y,
+        width,
+        height,
+        monitor: args.monitor,
+        draw_cursor: args.draw_cursor,
+        stable_ms: args.wait_stable,
+        stable_timeout_ms: args.stable_timeout,
+        if_changed_since,
+    });
+    // Leave room for the daemon to wait for the screen to settle
+    let timeout_ms = match args.wait_stable {
+        Some(_) => timeout_ms + args.stable_timeout.unwrap_or(DEFAULT_STABLE_TIMEOUT_MS),
+        None => timeout_ms,
+    };
     let response = client.send(&request, timeout_ms).await?;
 
     if !response.success {
@@ -43,25 +74,134 @@ pub async fn run(
         std::process::exit(1);
     }
 
+    if let Some(ResponseData::NotModified { etag, width, height }) = response.data {
+        if output.is_json() {
+            let data = serde_json::json!({
+                "type": "screenshot",
+                "path": args.output,
+                "width": width,
+                "height": height,
+                "unchanged": true,
+                "etag": etag,
+            });
+            println!("{}", serde_json::json!({ "success": true, "data": data }));
+        } else {
+            println!("Screenshot unchanged, {} kept ({}x{})", args.output, width, height);
+        }
+        return Ok(());
+    }
+
     // Handle the screenshot data - save to file
-    if let Some(ResponseData::Screenshot { width, height, base64, .. }) = response.data {
+    if let Some(ResponseData::Screenshot {
+        width,
+        height,
+        format,
+        size_bytes,
+        base64,
+        stable_wait_ms,
+        stable,
+        etag,
+        first_frame_received,
+    }) = response.data
+    {
         let image_data = base64::engine::general_purpose::STANDARD.decode(&base64)?;
 
         let path = Path::new(&args.output);
         let mut file = File::create(path)?;
         file.write_all(&image_data)?;
+        LastScreenshot {
+            path: output_path,
+            etag: etag.clone(),
+        }
+        .save(&cache_path);
 
         if output.is_json() {
+            let mut data = serde_json::json!({
+                "type": "screenshot",
+                "path": path.display().to_string(),
+                "width": width,
+                "height": height,
+                "format": format,
+                "size_bytes": size_bytes,
+                "etag": etag,
+                "first_frame_received": first_frame_received,
+            });
+            if let (Some(waited), Some(stable)) = (stable_wait_ms, stable) {
+                data["stable_wait_ms"] = waited.into();
+                data["stable"] = stable.into();
+            }
+            println!("{}", serde_json::json!({ "success": true, "data": data }));
+        } else {
             println!(
-                r#"{{"success":true,"data":{{"type":"screenshot","path":"{}","width":{},"height":{}}}}}"#,
+                "Screenshot saved to {} ({}x{}, {}, {} bytes)",
                 path.display(),
                 width,
-                height
+                height,
+                format,
+                size_bytes
             );
-        } else {
-            println!("Screenshot saved to {} ({}x{})", path.display(), width, height);
+            match (stable_wait_ms, stable) {
+                (Some(waited), Some(true)) => println!("Screen settled after {} ms", waited),
+                (Some(waited), Some(false)) => {
+                    println!("Screen was still changing after {} ms; captured anyway", waited)
+                }
+                _ => {}
+            }
+            if !first_frame_received {
+                println!(
+                    "The server hasn't drawn the desktop yet, so the image is blank \
+                     (connect with --wait-first-frame)"
+                );
+            }
         }
     }
 
     Ok(())
 }
+
+/// File in the session directory remembering the last screenshot written.
+const ETAG_CACHE_FILE: &str = "screenshot.etag";
+
+/// The last screenshot written for a session: where, and the daemon's `etag` for it.
+#[derive(Serialize, Deserialize)]
+struct LastScreenshot {
+    path: String,
+    etag: String,
+}
+
+impl LastScreenshot {
+    fn load(cache_path: &Path) -> Option<Self> {
+        let json = std::fs::read_to_string(cache_path).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Remember this screenshot; failing to is harmless, so errors are ignored.
+    fn save(&self, cache_path: &Path) {
+        if let Ok(json) = serde_json::to_string(self) {
+            let _ = std::fs::write(cache_path, json);
+        }
+    }
+}
+
+/// Image format to request, inferred from the output extension when not given explicitly.
+pub(crate) fn image_format(format: Option<&str>, output_path: &str) -> Result<ImageFormat, String> {
+    let format_name = match format {
+        Some(format) => format.to_lowercase(),
+        None => Path::new(output_path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_else(|| "png".to_string()),
+    };
+
+    match format_name.as_str() {
+        "png" => Ok(ImageFormat::Png),
+        "jpeg" | "jpg" => Ok(ImageFormat::Jpeg),
+        "webp" => Ok(ImageFormat::Webp),
+        "avif" => Ok(ImageFormat::Avif),
+        _ => Err(format!(
+            "Unsupported format '{}': must be png, jpeg, webp or avif",
+            format_name
+        )),
+    }
+}
@@ -1,8 +1,14 @@
 //! Session management command implementation.
 
-use agent_rdp_protocol::{Request, ResponseData, SessionSummary, ConnectionState};
+use std::fs::File;
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::time::Duration;
 
+use agent_rdp_protocol::{KillOutcome, Request, Response, ResponseData, SessionInfo};
+
+use super::connect::connect_wait_ms;
 use crate::cli::{SessionAction, SessionArgs};
+use crate::ipc_client::IpcClient;
 use crate::output::Output;
 use crate::session_manager::SessionManager;
 
@@ -13,63 +19,111 @@ pub async fn run(
     timeout_ms: u64,
 ) -> anyhow::Result<()> {
     match args.action {
-        SessionAction::List => {
-            list_sessions(output).await
+        SessionAction::List { prune } => {
+            list_sessions(prune, output).await
         }
         SessionAction::Info => {
             session_info(session, output, timeout_ms).await
         }
+        SessionAction::Attach => {
+            let (_, info) = attach(session, output, timeout_ms).await?;
+            output.print_response(&Response::success(ResponseData::SessionInfo(info)));
+            Ok(())
+        }
+        SessionAction::Restart { password_stdin } => {
+            restart_session(session, password_stdin, output, timeout_ms).await
+        }
+        SessionAction::Kill { name, all } => {
+            kill_sessions(name.as_deref().unwrap_or(session), all, output).await
+        }
+        SessionAction::Logs { follow, lines } => {
+            show_logs(session, lines, follow, output).await
+        }
         SessionAction::Daemon => {
             run_daemon(session).await
         }
     }
 }
 
-async fn list_sessions(output: &Output) -> anyhow::Result<()> {
-    let sessions = SessionManager::list_sessions();
-
-    let mut summaries = Vec::new();
-    for session_name in sessions {
-        let manager = SessionManager::new(session_name.clone());
-
-        let state = if manager.is_daemon_alive() {
-            // Try to get session info
-            if let Ok(mut client) = crate::ipc_client::try_connect(
-                &manager.socket_path(),
-                1,
-                100,
-            ).await {
-                if let Ok(response) = client.send(&Request::SessionInfo, 5000).await {
-                    if let Some(ResponseData::SessionInfo(info)) = response.data {
-                        summaries.push(SessionSummary {
-                            name: session_name,
-                            state: info.state,
-                            host: info.host,
-                        });
-                        continue;
-                    }
-                }
-            }
-            ConnectionState::Disconnected
-        } else {
-            ConnectionState::Disconnected
+/// How often `session logs --follow` checks the log for new lines.
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Print the last lines of the daemon log, then with `follow` whatever is appended.
+async fn show_logs(session: &str, lines: usize, follow: bool, output: &Output) -> anyhow::Result<()> {
+    let path = agent_rdp_daemon::get_log_path(session);
+    let Ok(contents) = std::fs::read(&path) else {
+        output.print_error(
+            "no_logs",
+            &format!("No daemon log for session '{}' at {}", session, path.display()),
+        );
+        std::process::exit(1);
+    };
+
+    let text = String::from_utf8_lossy(&contents);
+    let all_lines: Vec<&str> = text.lines().collect();
+    for line in &all_lines[all_lines.len().saturating_sub(lines)..] {
+        println!("{}", line);
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut offset = contents.len() as u64;
+    loop {
+        tokio::time::sleep(LOG_POLL_INTERVAL).await;
+        let Ok(len) = std::fs::metadata(&path).map(|meta| meta.len()) else {
+            continue;
         };
+        // A shorter file means the daemon rotated the log and started a new one
+        if len < offset {
+            offset = 0;
+        }
+        if len == offset {
+            continue;
+        }
 
-        summaries.push(SessionSummary {
-            name: session_name,
-            state,
-            host: None,
-        });
+        let mut file = File::open(&path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut appended = Vec::new();
+        file.read_to_end(&mut appended)?;
+        offset += appended.len() as u64;
+        print!("{}", String::from_utf8_lossy(&appended));
+        io::stdout().flush()?;
     }
+}
 
-    let response = agent_rdp_protocol::Response::success(ResponseData::SessionList {
-        sessions: summaries,
-    });
+async fn list_sessions(prune: bool, output: &Output) -> anyhow::Result<()> {
+    let sessions = SessionManager::list_sessions(prune).await;
+
+    let response = Response::success(ResponseData::SessionList { sessions });
 
     output.print_response(&response);
     Ok(())
 }
 
+/// Kill one session's daemon, or with `all` every session's, and clean up after them.
+///
+/// Exits with an error if any daemon is still running afterwards.
+async fn kill_sessions(session: &str, all: bool, output: &Output) -> anyhow::Result<()> {
+    let names = if all {
+        SessionManager::session_names()
+    } else {
+        vec![session.to_string()]
+    };
+
+    let mut sessions = Vec::new();
+    for name in names {
+        sessions.push(SessionManager::new(name).kill().await);
+    }
+
+    let failed = sessions.iter().any(|killed| killed.outcome == KillOutcome::Failed);
+    output.print_response(&Response::success(ResponseData::SessionKill { sessions }));
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 async fn session_info(session: &str, output: &Output, timeout_ms: u64) -> anyhow::Result<()> {
     let manager = SessionManager::new(session.to_string());
 
@@ -85,7 +139,119 @@ async fn session_info(session: &str, output: &Output, timeout_ms: u64) -> anyhow
     Ok(())
 }
 
+/// Attach to the session's running daemon and get its state.
+///
+/// Unlike other commands this never starts a daemon: without one it exits with
+/// the command that would start the session.
+pub(crate) async fn attach(
+    session: &str,
+    output: &Output,
+    timeout_ms: u64,
+) -> anyhow::Result<(IpcClient, SessionInfo)> {
+    let manager = SessionManager::new(session.to_string());
+
+    let Some(mut client) = manager.attach().await else {
+        output.print_error("daemon_not_running", &start_hint(session, &manager));
+        std::process::exit(1);
+    };
+
+    let response = client.send(&Request::SessionInfo, timeout_ms).await?;
+    match response.data {
+        Some(ResponseData::SessionInfo(info)) => Ok((client, info)),
+        _ => {
+            output.print_response(&response);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Explain that no daemon is running and give the command that starts the session.
+fn start_hint(session: &str, manager: &SessionManager) -> String {
+    let command = if session == "default" {
+        "agent-rdp".to_string()
+    } else {
+        format!("agent-rdp --session {}", session)
+    };
+
+    match manager.load_connect_request() {
+        Ok(Some(saved)) => {
+            let port = if saved.port == 3389 {
+                String::new()
+            } else {
+                format!(" --port {}", saved.port)
+            };
+            format!(
+                "No daemon is running for session '{}'. Reconnect with: {} session restart \
+                 (or {} connect --host {}{} --username {} --password-stdin)",
+                session, command, command, saved.host, port, saved.username
+            )
+        }
+        _ => format!(
+            "No daemon is running for session '{}'. Start it with: \
+             {} connect --host <host> --username <user> --password-stdin",
+            session, command
+        ),
+    }
+}
+
+/// Restart the daemon and reconnect with the saved connection settings.
+async fn restart_session(
+    session: &str,
+    password_stdin: bool,
+    output: &Output,
+    timeout_ms: u64,
+) -> anyhow::Result<()> {
+    let manager = SessionManager::new(session.to_string());
+
+    let Some(mut connect) = manager.load_connect_request()? else {
+        output.print_error(
+            "no_saved_session",
+            "No saved connection settings for this session; connect first",
+        );
+        std::process::exit(1);
+    };
+
+    // Passwords are only saved with --save-password
+    if password_stdin {
+        let mut line = String::new();
+        io::stdin().lock().read_line(&mut line)?;
+        connect.password = line.trim_end().to_string();
+    } else if connect.password.is_empty() {
+        match std::env::var("AGENT_RDP_PASSWORD") {
+            Ok(password) => connect.password = password,
+            Err(_) => {
+                output.print_error(
+                    "missing_password",
+                    "Password not saved. Set AGENT_RDP_PASSWORD or use --password-stdin",
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(ref mut gateway) = connect.gateway {
+        if gateway.password.is_none() {
+            gateway.password = std::env::var("AGENT_RDP_GATEWAY_PASS").ok();
+        }
+    }
+    if let Ok(proxy) = std::env::var("AGENT_RDP_PROXY") {
+        connect.proxy = Some(proxy);
+    }
+
+    manager.stop_daemon(timeout_ms).await?;
+
+    let mut client = manager.ensure_daemon().await?;
+    let wait_ms = connect_wait_ms(&connect, timeout_ms);
+    let response = client.send(&Request::Connect(Box::new(connect)), wait_ms).await?;
+    output.print_response(&response);
+
+    if !response.success {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
 /// Run as the background daemon (called by session manager).
 async fn run_daemon(session: &str) -> anyhow::Result<()> {
-    agent_rdp_daemon::run_server(session).await
+    agent_rdp_daemon::run_server(session, None).await
 }
@@ -0,0 +1,36 @@
+//! Stream-config command implementation.
+
+use agent_rdp_protocol::Request;
+
+use crate::cli::StreamConfigArgs;
+use crate::output::Output;
+use crate::session_manager::SessionManager;
+
+pub async fn run(
+    session: &str,
+    args: StreamConfigArgs,
+    output: &Output,
+    timeout_ms: u64,
+) -> anyhow::Result<()> {
+    let manager = SessionManager::new(session.to_string());
+
+    if !manager.is_daemon_alive() {
+        output.print_error("daemon_not_running", "No daemon running for this session");
+        std::process::exit(1);
+    }
+
+    let request = Request::StreamConfig {
+        fps: args.fps,
+        quality: args.quality,
+        delta: args.delta,
+    };
+    let mut client = manager.ensure_daemon().await?;
+    let response = client.send(&request, timeout_ms).await?;
+    output.print_response(&response);
+
+    if !response.success {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
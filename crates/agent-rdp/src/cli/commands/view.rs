@@ -3,14 +3,42 @@
 use crate::cli::ViewArgs;
 use crate::output::Output;
 
-pub async fn run(args: ViewArgs, output: &Output) -> anyhow::Result<()> {
+pub async fn run(
+    session: &str,
+    args: ViewArgs,
+    output: &Output,
+    timeout_ms: u64,
+) -> anyhow::Result<()> {
+    // The viewer needs the session's stream port and token, so ask its daemon
+    let (_, info) = super::session::attach(session, output, timeout_ms).await?;
+    let Some(token) = info.stream_token else {
+        output.print_error(
+            "stream_not_running",
+            "Streaming is not enabled for this session (connect with --stream-port)",
+        );
+        std::process::exit(1);
+    };
+    if !info.stream_viewer {
+        output.print_error(
+            "viewer_not_served",
+            "This session's stream only accepts WebSocket clients, without the viewer page \
+             (sessions started from the Node.js API don't serve it)",
+        );
+        std::process::exit(1);
+    }
+
     // The daemon serves the viewer HTML on the same port as the WebSocket server
-    let url = format!("http://localhost:{}", args.port);
+    let port = args.port.or(info.stream_port).unwrap_or(9224);
+    let query: String = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("token", &token)
+        .finish();
+    let url = format!("http://localhost:{}/?{}", port, query);
 
     if output.is_json() {
-        println!(r#"{{"url":"{}"}}"#, url);
+        println!("{}", serde_json::json!({ "url": url, "token": token }));
     } else {
         println!("Opening viewer at: {}", url);
+        println!("Stream token: {}", token);
     }
 
     // Open browser
@@ -0,0 +1,51 @@
+//! Wait-for-change command implementation.
+
+use agent_rdp_protocol::{ElementBounds, Request};
+
+use crate::cli::WaitForChangeArgs;
+use crate::output::Output;
+use crate::session_manager::SessionManager;
+
+/// Headroom for the daemon to answer after the wait itself times out.
+const RESPONSE_MARGIN_MS: u64 = 5000;
+
+pub async fn run(
+    session: &str,
+    args: WaitForChangeArgs,
+    output: &Output,
+    timeout_ms: u64,
+) -> anyhow::Result<()> {
+    let manager = SessionManager::new(session.to_string());
+
+    if !manager.is_daemon_alive() {
+        output.print_error("daemon_not_running", "No daemon running for this session");
+        std::process::exit(1);
+    }
+
+    let mut client = manager.ensure_daemon().await?;
+
+    let region = match args.region.as_deref() {
+        Some(&[x, y, width, height]) => Some(ElementBounds {
+            x: x as i32,
+            y: y as i32,
+            width: width as i32,
+            height: height as i32,
+        }),
+        _ => None,
+    };
+
+    let request = Request::WaitForChange {
+        timeout_ms: args.max_wait,
+        region,
+    };
+    // Don't give up on the daemon before the wait can finish
+    let timeout_ms = timeout_ms.max(args.max_wait + RESPONSE_MARGIN_MS);
+    let response = client.send(&request, timeout_ms).await?;
+    output.print_response(&response);
+
+    if !response.success {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
@@ -4,8 +4,9 @@ use std::io;
 use std::path::Path;
 use std::time::Duration;
 
-use agent_rdp_protocol::{Request, Response};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use agent_rdp_daemon::ipc_server::{read_frame, write_frame};
+use agent_rdp_protocol::{IpcFraming, Request, Response, ResponseData};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::time::timeout;
 
 /// Default connect timeout in seconds.
@@ -17,6 +18,8 @@ pub struct IpcClient {
     stream: tokio::net::UnixStream,
     #[cfg(windows)]
     stream: tokio::net::TcpStream,
+    /// How messages are delimited (lines until `use_framing` switches it).
+    framing: IpcFraming,
 }
 
 impl IpcClient {
@@ -32,7 +35,7 @@ impl IpcClient {
                     format!("Connection to daemon timed out after {}s", CONNECT_TIMEOUT_SECS),
                 )
             })??;
-        Ok(Self { stream })
+        Ok(Self { stream, framing: IpcFraming::Line })
     }
 
     #[cfg(windows)]
@@ -54,16 +57,40 @@ impl IpcClient {
                     format!("Connection to daemon timed out after {}s", CONNECT_TIMEOUT_SECS),
                 )
             })??;
-        Ok(Self { stream })
+        Ok(Self { stream, framing: IpcFraming::Line })
+    }
+
+    /// Ask the daemon to switch the connection to the given framing.
+    ///
+    /// Must come before any other request. Daemons too old to know the
+    /// handshake reject it, and the connection stays on lines.
+    pub async fn use_framing(
+        &mut self,
+        framing: IpcFraming,
+        timeout_ms: u64,
+    ) -> anyhow::Result<IpcFraming> {
+        let response = self.send(&Request::Hello { framing }, timeout_ms).await?;
+        if let Some(ResponseData::Hello { framing }) = response.data {
+            self.framing = framing;
+        }
+        Ok(self.framing)
     }
 
     /// Send a request and receive a response.
     pub async fn send(&mut self, request: &Request, timeout_ms: u64) -> anyhow::Result<Response> {
-        let json = serde_json::to_string(request)? + "\n";
+        match self.framing {
+            IpcFraming::Line => {
+                let json = serde_json::to_string(request)? + "\n";
 
-        // Write request and flush to ensure it's sent immediately
-        self.stream.write_all(json.as_bytes()).await?;
-        self.stream.flush().await?;
+                // Write request and flush to ensure it's sent immediately
+                self.stream.write_all(json.as_bytes()).await?;
+                self.stream.flush().await?;
+            }
+            IpcFraming::LengthPrefixed => {
+                let json = serde_json::to_vec(request)?;
+                write_frame(&mut self.stream, &json, &[]).await?;
+            }
+        }
 
         // Read response with timeout
         let response = timeout(
@@ -76,13 +103,26 @@ impl IpcClient {
         Ok(response)
     }
 
-    /// Read a response from the stream.
+    /// Read a response from the stream, along with the binary payload that
+    /// follows a binary frame.
     async fn read_response(&mut self) -> anyhow::Result<Response> {
+        if self.framing == IpcFraming::LengthPrefixed {
+            let (json, payload) = read_frame(&mut self.stream)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Daemon closed the connection"))?;
+            let mut response: Response = serde_json::from_slice(&json)?;
+            response.attach_payload(payload);
+            return Ok(response);
+        }
+
         let mut reader = BufReader::new(&mut self.stream);
         let mut line = String::new();
         reader.read_line(&mut line).await?;
 
-        let response: Response = serde_json::from_str(line.trim())?;
+        let mut response: Response = serde_json::from_str(line.trim())?;
+        let mut payload = vec![0u8; response.payload_len()];
+        reader.read_exact(&mut payload).await?;
+        response.attach_payload(payload);
         Ok(response)
     }
 }
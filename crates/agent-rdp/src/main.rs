@@ -9,18 +9,24 @@ use clap::Parser;
 use tracing::error;
 use tracing_subscriber::EnvFilter;
 
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, SessionAction, SessionArgs};
 
 #[tokio::main]
 async fn main() {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_target(false)
-        .init();
-
     let cli = Cli::parse();
 
+    // The daemon logs to its session's log file instead (its output is discarded)
+    let is_daemon = matches!(
+        &cli.command,
+        Commands::Session(SessionArgs { action: SessionAction::Daemon })
+    );
+    if !is_daemon {
+        tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::from_default_env())
+            .with_target(false)
+            .init();
+    }
+
     if let Err(e) = run(cli).await {
         error!("{}", e);
         std::process::exit(1);
@@ -34,14 +40,23 @@ async fn run(cli: Cli) -> anyhow::Result<()> {
 
     match cli.command {
         Commands::Connect(args) => {
-            cli::commands::connect::run(&cli.session, args, &output, cli.timeout, cli.stream_port).await
+            cli::commands::connect::run(&cli.session, *args, &output, cli.timeout, cli.stream_port).await
         }
-        Commands::Disconnect => {
-            cli::commands::disconnect::run(&cli.session, &output, cli.timeout).await
+        Commands::Disconnect { logoff } => {
+            cli::commands::disconnect::run(&cli.session, logoff, &output, cli.timeout).await
         }
         Commands::Screenshot(args) => {
             cli::commands::screenshot::run(&cli.session, args, &output, cli.timeout).await
         }
+        Commands::Frame(args) => {
+            cli::commands::frame::run(&cli.session, args, &output, cli.timeout).await
+        }
+        Commands::Resize { width, height } => {
+            cli::commands::resize::run(&cli.session, width, height, &output, cli.timeout).await
+        }
+        Commands::StreamConfig(args) => {
+            cli::commands::stream_config::run(&cli.session, args, &output, cli.timeout).await
+        }
         Commands::Mouse(args) => {
             cli::commands::mouse::run(&cli.session, args, &output, cli.timeout).await
         }
@@ -51,6 +66,9 @@ async fn run(cli: Cli) -> anyhow::Result<()> {
         Commands::Scroll(args) => {
             cli::commands::scroll::run(&cli.session, args, &output, cli.timeout).await
         }
+        Commands::Input(args) => {
+            cli::commands::input::run(&cli.session, args, &output, cli.timeout).await
+        }
         Commands::Clipboard(args) => {
             cli::commands::clipboard::run(&cli.session, args, &output, cli.timeout).await
         }
@@ -63,6 +81,12 @@ async fn run(cli: Cli) -> anyhow::Result<()> {
         Commands::Locate(args) => {
             cli::commands::locate::run(&cli.session, args, &output, cli.timeout).await
         }
+        Commands::WaitForChange(args) => {
+            cli::commands::wait_for_change::run(&cli.session, args, &output, cli.timeout).await
+        }
+        Commands::Cursor(args) => {
+            cli::commands::cursor::run(&cli.session, args, &output, cli.timeout).await
+        }
         Commands::Session(args) => {
             cli::commands::session::run(&cli.session, args, &output, cli.timeout).await
         }
@@ -70,7 +94,7 @@ async fn run(cli: Cli) -> anyhow::Result<()> {
             cli::commands::wait::run(ms).await
         }
         Commands::View(args) => {
-            cli::commands::view::run(args, &output).await
+            cli::commands::view::run(&cli.session, args, &output, cli.timeout).await
         }
     }
 }
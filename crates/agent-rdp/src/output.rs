@@ -40,21 +40,110 @@ impl Output {
 
     /// Print response data in human-readable format.
     fn print_data(&self, data: &agent_rdp_protocol::ResponseData) {
-        use agent_rdp_protocol::ResponseData;
+        use agent_rdp_protocol::{FrameEncoding, KillOutcome, ResponseData};
 
         match data {
             ResponseData::Ok => {
                 println!("OK");
             }
-            ResponseData::Connected { host, width, height } => {
+            ResponseData::Connected { host, width, height, input_check, first_frame_received } => {
                 println!("Connected to {} ({}x{})", host, width, height);
+                if let Some(check) = input_check {
+                    println!("Input check: {}", check.message);
+                }
+                if *first_frame_received == Some(false) {
+                    println!("No desktop update from the server yet; screenshots may be black");
+                }
+            }
+            ResponseData::Resized { width, height } => {
+                println!("Resized desktop to {}x{}", width, height);
+            }
+            ResponseData::Hello { framing } => {
+                println!("IPC framing: {:?}", framing);
+            }
+            ResponseData::StreamConfig { fps, quality, delta } => {
+                let delta = if *delta { "on" } else { "off" };
+                println!("Stream: {} fps, quality {}, delta {}", fps, quality, delta);
+            }
+            ResponseData::Screenshot { width, height, format, size_bytes, .. } => {
+                println!("Screenshot: {}x{} ({}, {} bytes)", width, height, format, size_bytes);
             }
-            ResponseData::Screenshot { width, height, format, .. } => {
-                println!("Screenshot: {}x{} ({})", width, height, format);
+            ResponseData::NotModified { width, height, .. } => {
+                println!("Screenshot unchanged ({}x{})", width, height);
+            }
+            ResponseData::Frame { width, height, encoding, size_bytes, .. } => {
+                let encoding = match encoding {
+                    FrameEncoding::Raw => "raw",
+                    FrameEncoding::Png => "png",
+                };
+                println!("Frame: {}x{} ({}, {} bytes)", width, height, encoding, size_bytes);
             }
             ResponseData::Clipboard { text } => {
                 println!("{}", text);
             }
+            ResponseData::ClipboardImage { width, height, .. } => {
+                println!("Clipboard image: {}x{}", width, height);
+            }
+            ResponseData::ClipboardHtml { html } => {
+                println!("{}", html);
+            }
+            ResponseData::ClipboardFiles { files, total_bytes } => {
+                for file in files {
+                    println!("{}", file);
+                }
+                println!("{} file(s), {} bytes", files.len(), total_bytes);
+            }
+            ResponseData::DriveCopy { destination, files, total_bytes } => {
+                println!("Copied {} file(s), {} bytes to {}", files, total_bytes, destination);
+            }
+            ResponseData::ClipboardStatus { text, image, html, files } => {
+                let kinds: Vec<&str> =
+                    [(*text, "text"), (*image, "image"), (*html, "html"), (*files, "files")]
+                        .into_iter()
+                        .filter_map(|(available, kind)| available.then_some(kind))
+                        .collect();
+                if kinds.is_empty() {
+                    println!("Remote clipboard is empty");
+                } else {
+                    println!("Remote clipboard has: {}", kinds.join(", "));
+                }
+            }
+            ResponseData::ClipboardHistory { entries } => {
+                if entries.is_empty() {
+                    println!("No clipboard history");
+                }
+                let now_ms = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_millis() as u64)
+                    .unwrap_or_default();
+                for (i, entry) in entries.iter().enumerate() {
+                    let age_secs = now_ms.saturating_sub(entry.copied_at_ms) / 1000;
+                    println!("[{}] copied {}s ago", i + 1, age_secs);
+                    println!("{}", entry.text);
+                }
+            }
+            ResponseData::CertFingerprint { sha256 } => {
+                println!("{}", sha256);
+            }
+            ResponseData::DryRun(result) => {
+                let check = |ok: bool| if ok { "ok" } else { "failed" };
+                println!("Host: {}:{}", result.host, result.port);
+                println!("Reachable: {}", check(result.reachable));
+                println!("TLS: {}", check(result.tls_ok));
+                println!("Authentication: {}", check(result.auth_ok));
+                if let (Some(w), Some(h)) = (result.width, result.height) {
+                    println!("Resolution: {}x{}", w, h);
+                }
+                if let Some(ref fingerprint) = result.cert_fingerprint {
+                    println!("Certificate SHA-256: {}", fingerprint);
+                }
+                if let Some(ref error) = result.error {
+                    println!("Error: {}", error);
+                }
+                if let Some(ref code) = result.error_code {
+                    println!("Error code: {:?}", code);
+                }
+            }
             ResponseData::SessionInfo(info) => {
                 println!("Session: {}", info.name);
                 println!("State: {:?}", info.state);
@@ -64,6 +153,43 @@ impl Output {
                 if let (Some(w), Some(h)) = (info.width, info.height) {
                     println!("Resolution: {}x{}", w, h);
                 }
+                if info.monitors.len() > 1 {
+                    for (index, monitor) in info.monitors.iter().enumerate() {
+                        let primary = if monitor.primary { " (primary)" } else { "" };
+                        println!(
+                            "Monitor {}: {}x{} at ({}, {}){}",
+                            index, monitor.width, monitor.height, monitor.x, monitor.y, primary
+                        );
+                    }
+                }
+                if let Some(layout) = info.keyboard_layout {
+                    println!("Keyboard layout: 0x{:04X}", layout);
+                }
+                if let Some(ref fingerprint) = info.cert_fingerprint {
+                    println!("Certificate SHA-256: {}", fingerprint);
+                }
+                if let Some(ref disconnect) = info.last_disconnect {
+                    println!("Last disconnect: {:?} ({})", disconnect.reason, disconnect.message);
+                }
+                if let Some(connect_ms) = info.connect_ms {
+                    println!("Connect time: {}ms", connect_ms);
+                }
+                if let Some(rtt) = info.rtt_ms {
+                    match info.last_rtt_ms {
+                        Some(last) => println!("Round trip: {}ms (last {}ms)", rtt, last),
+                        None => println!("Round trip: {}ms", rtt),
+                    }
+                }
+                if let (Some(frames), Some(bytes)) = (info.frames_received, info.bytes_received) {
+                    println!("Received: {} frames, {} bytes", frames, bytes);
+                }
+                if let Some(port) = info.stream_port {
+                    if info.stream_viewer {
+                        println!("Stream port: {} (viewer: agent-rdp view)", port);
+                    } else {
+                        println!("Stream port: {}", port);
+                    }
+                }
                 println!("PID: {}", info.pid);
                 println!("Uptime: {}s", info.uptime_secs);
             }
@@ -72,7 +198,26 @@ impl Output {
                     println!("No drives mapped");
                 } else {
                     for drive in drives {
-                        println!("{}: {}", drive.name, drive.path);
+                        let mut details = Vec::new();
+                        if drive.device_id != 0 {
+                            details.push(format!("device {}", drive.device_id));
+                        }
+                        if let (Some(free), Some(total)) = (drive.free_bytes, drive.total_bytes) {
+                            details.push(format!("{} of {} bytes free", free, total));
+                        }
+                        if drive.read_only {
+                            details.push("read-only".to_string());
+                        }
+                        let remote = if drive.unc_path.is_empty() {
+                            drive.name.clone()
+                        } else {
+                            drive.unc_path.clone()
+                        };
+                        if details.is_empty() {
+                            println!("{} -> {}", remote, drive.path);
+                        } else {
+                            println!("{} -> {} ({})", remote, drive.path, details.join(", "));
+                        }
                     }
                 }
             }
@@ -81,14 +226,56 @@ impl Output {
                     println!("No active sessions");
                 } else {
                     for session in sessions {
+                        let pid = session
+                            .pid
+                            .map(|pid| pid.to_string())
+                            .unwrap_or_else(|| "-".to_string());
+                        if !session.alive {
+                            let pruned = if session.pruned { ", pruned" } else { "" };
+                            println!("{}: stale (pid {} not running{})", session.name, pid, pruned);
+                            continue;
+                        }
                         let host = session.host.as_deref().unwrap_or("-");
-                        println!("{}: {:?} ({})", session.name, session.state, host);
+                        match session.uptime_secs {
+                            Some(uptime) => println!(
+                                "{}: {:?} ({}) pid {}, up {}s",
+                                session.name, session.state, host, pid, uptime
+                            ),
+                            None => println!(
+                                "{}: {:?} ({}) pid {}, not responding",
+                                session.name, session.state, host, pid
+                            ),
+                        }
                     }
                 }
             }
-            ResponseData::Pong => {
-                println!("Pong");
+            ResponseData::SessionKill { sessions } => {
+                if sessions.is_empty() {
+                    println!("No sessions");
+                }
+                for session in sessions {
+                    let pid = session
+                        .pid
+                        .map(|pid| pid.to_string())
+                        .unwrap_or_else(|| "-".to_string());
+                    match session.outcome {
+                        KillOutcome::Killed => println!("{}: killed (pid {})", session.name, pid),
+                        KillOutcome::AlreadyDead => {
+                            println!("{}: already dead, cleaned up", session.name)
+                        }
+                        KillOutcome::Failed => println!(
+                            "{}: still running (pid {}): {}",
+                            session.name,
+                            pid,
+                            session.error.as_deref().unwrap_or("unknown error")
+                        ),
+                    }
+                }
             }
+            ResponseData::Pong { version, .. } => match version {
+                Some(version) => println!("Pong (daemon {})", version),
+                None => println!("Pong"),
+            },
             ResponseData::Snapshot(snapshot) => {
                 // Print full accessibility tree like agent-browser
                 println!("Snapshot ID: {}", snapshot.snapshot_id);
@@ -100,7 +287,19 @@ impl Output {
                     );
                 }
                 println!();
-                self.print_element_tree(&snapshot.root, 0);
+                print!("{}", snapshot.root.to_outline());
+            }
+            ResponseData::FormattedSnapshot(snapshot) => {
+                println!("Snapshot ID: {}", snapshot.snapshot_id);
+                println!("Elements: {}", snapshot.ref_count);
+                if snapshot.truncated {
+                    println!(
+                        "[Truncated at depth {} - use -d to increase or -s to scope to a window]",
+                        snapshot.max_depth
+                    );
+                }
+                println!();
+                print!("{}", snapshot.text);
             }
             ResponseData::Element(element) => {
                 if let Some(ref name) = element.name {
@@ -141,6 +340,12 @@ impl Output {
                 if !status.capabilities.is_empty() {
                     println!("Capabilities: {}", status.capabilities.join(", "));
                 }
+                if status.restarts > 0 {
+                    println!("Restarts: {}", status.restarts);
+                }
+                if status.secure_desktop {
+                    println!("Secure desktop: input is going to a UAC prompt or lock screen");
+                }
             }
             ResponseData::RunResult(result) => {
                 if let Some(code) = result.exit_code {
@@ -156,6 +361,9 @@ impl Output {
                         eprintln!("{}", stderr);
                     }
                 }
+                if result.truncated {
+                    eprintln!("(output truncated)");
+                }
                 if let Some(pid) = result.pid {
                     println!("Process ID: {}", pid);
                 }
@@ -166,93 +374,89 @@ impl Output {
                 } else {
                     println!("Found {} match(es):", result.matches.len());
                     for m in &result.matches {
-                        println!("  '{}' at ({}, {}) size {}x{} - center: ({}, {})",
-                            m.text, m.x, m.y, m.width, m.height, m.center_x, m.center_y);
+                        println!("  '{}' at ({}, {}) size {}x{} - center: ({}, {}) - confidence: {:.0}%",
+                            m.text, m.x, m.y, m.width, m.height, m.center_x, m.center_y, m.confidence * 100.0);
                     }
                 }
             }
-            ResponseData::ClickResult(result) => {
-                if result.method == "double_click" {
-                    println!("Double-clicked at ({}, {})", result.x.unwrap_or(0), result.y.unwrap_or(0));
+            ResponseData::LocateClicked { matched, x, y, match_count } => {
+                println!(
+                    "Clicked '{}' at ({}, {}) - confidence: {:.0}% ({} match(es))",
+                    matched.text, x, y, matched.confidence * 100.0, match_count
+                );
+            }
+            ResponseData::CursorInfo(info) => {
+                let hidden = if info.visible { "" } else { " (hidden)" };
+                println!("Pointer at ({}, {}){}", info.x, info.y, hidden);
+                if let (Some(w), Some(h)) = (info.width, info.height) {
+                    println!(
+                        "Shape: {}x{}, hot spot ({}, {})",
+                        w,
+                        h,
+                        info.hotspot_x.unwrap_or(0),
+                        info.hotspot_y.unwrap_or(0)
+                    );
+                }
+            }
+            ResponseData::ScreenChange { changed, elapsed_ms } => {
+                if *changed {
+                    println!("Screen changed after {} ms", elapsed_ms);
                 } else {
-                    println!("Clicked at ({}, {})", result.x.unwrap_or(0), result.y.unwrap_or(0));
+                    println!("No change within {} ms", elapsed_ms);
                 }
             }
-        }
-    }
-
-    /// Print an element tree in compact Playwright-like aria format.
-    /// Format: - role "name" [ref=eN, id=..., ...]
-    fn print_element_tree(&self, element: &agent_rdp_protocol::AccessibilityElement, depth: usize) {
-        let indent = "  ".repeat(depth);
-
-        // Build the main line: - role "name"
-        let mut line = format!("{}- {}", indent, element.role);
-
-        // Add name if present
-        if let Some(ref name) = element.name {
-            if !name.is_empty() {
-                line.push_str(&format!(" \"{}\"", name));
+            ResponseData::ClickResult(result) => {
+                let (x, y) = (result.x.unwrap_or(0), result.y.unwrap_or(0));
+                match result.method.as_str() {
+                    "double_click" => println!("Double-clicked at ({}, {})", x, y),
+                    "right_click" => println!("Right-clicked at ({}, {})", x, y),
+                    "middle_click" => println!("Middle-clicked at ({}, {})", x, y),
+                    "double_right_click" => println!("Double right-clicked at ({}, {})", x, y),
+                    "double_middle_click" => println!("Double middle-clicked at ({}, {})", x, y),
+                    _ => println!("Clicked at ({}, {})", x, y),
+                }
             }
-        }
-
-        // Build attributes in brackets
-        let mut attrs = Vec::new();
-
-        // Ref is always first attribute (with "e" prefix)
-        if let Some(r) = element.r#ref {
-            attrs.push(format!("ref=e{}", r));
-        }
-
-        if let Some(ref auto_id) = element.automation_id {
-            if !auto_id.is_empty() {
-                attrs.push(format!("id={}", auto_id));
+            ResponseData::ElementScreenshot(image) => {
+                println!(
+                    "Element image: {}x{} at ({}, {}) ({}, {} bytes)",
+                    image.bounds.width,
+                    image.bounds.height,
+                    image.bounds.x,
+                    image.bounds.y,
+                    image.format,
+                    image.size_bytes
+                );
             }
-        }
-
-        if let Some(ref class) = element.class_name {
-            if !class.is_empty() {
-                attrs.push(format!("class={}", class));
+            ResponseData::DragResult(result) => {
+                println!(
+                    "Dragged from ({}, {}) to ({}, {})",
+                    result.from_x, result.from_y, result.to_x, result.to_y
+                );
             }
-        }
-
-        if let Some(ref value) = element.value {
-            if !value.is_empty() {
-                attrs.push(format!("value=\"{}\"", value));
+            ResponseData::HighlightResult(result) => {
+                println!(
+                    "Highlighted {}x{} at ({}, {}) for {} ms",
+                    result.bounds.width,
+                    result.bounds.height,
+                    result.bounds.x,
+                    result.bounds.y,
+                    result.duration_ms
+                );
+            }
+            ResponseData::TypeResult(result) => match &result.value {
+                Some(value) => println!("Typed via {}, value is now {:?}", result.method, value),
+                None => println!("Typed via {}", result.method),
+            },
+            ResponseData::WaitForResult(result) => {
+                use agent_rdp_protocol::WaitState;
+                let state = match result.state {
+                    WaitState::Visible => "visible",
+                    WaitState::Enabled => "enabled",
+                    WaitState::Gone => "gone",
+                    WaitState::Exists => "present",
+                };
+                println!("Element is {} after {} ms", state, result.elapsed_ms);
             }
-        }
-
-        // Add disabled tag if element is interactive but not enabled
-        // Interactive = focusable OR has interactive patterns
-        let interactive_patterns = [
-            "invoke",
-            "value",
-            "toggle",
-            "selectionitem",
-            "expandcollapse",
-            "rangevalue",
-            "scroll",
-        ];
-        let is_focusable = element.states.contains(&"focusable".to_string());
-        let has_interactive_pattern = element
-            .patterns
-            .iter()
-            .any(|p| interactive_patterns.contains(&p.as_str()));
-        let is_interactive = is_focusable || has_interactive_pattern;
-
-        if is_interactive && !element.states.contains(&"enabled".to_string()) {
-            attrs.push("disabled".to_string());
-        }
-
-        if !attrs.is_empty() {
-            line.push_str(&format!(" [{}]", attrs.join(", ")));
-        }
-
-        println!("{}", line);
-
-        // Recurse into children
-        for child in &element.children {
-            self.print_element_tree(child, depth + 1);
         }
     }
 
@@ -260,6 +464,7 @@ impl Output {
     pub fn print_error(&self, code: &str, message: &str) {
         if self.json {
             let response = agent_rdp_protocol::Response {
+                id: None,
                 success: false,
                 data: None,
                 error: Some(agent_rdp_protocol::ErrorInfo {
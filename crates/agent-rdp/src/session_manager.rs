@@ -4,13 +4,25 @@ use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use std::time::Duration;
 
-use agent_rdp_daemon::{cleanup_session, get_pid_path, get_session_dir, get_socket_path};
-use agent_rdp_protocol::Request;
+use agent_rdp_daemon::{
+    cleanup_session, get_connect_config_path, get_pid_path, get_session_dir, get_socket_path,
+    remove_session,
+};
+use agent_rdp_protocol::{
+    ConnectRequest, ConnectionState, KillOutcome, KilledSession, Request, ResponseData,
+    SessionSummary, PROTOCOL_VERSION, VERSION,
+};
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
 
 use crate::ipc_client::IpcClient;
 
+/// How long to wait for each daemon to answer when listing sessions.
+const SESSION_PROBE_TIMEOUT_MS: u64 = 5000;
+
+/// How long `session kill` waits for a daemon to exit after asking it to terminate.
+const KILL_WAIT_MS: u64 = 5000;
+
 /// Session manager handles daemon lifecycle.
 pub struct SessionManager {
     session: String,
@@ -38,24 +50,72 @@ impl SessionManager {
         get_pid_path(&self.session)
     }
 
+    /// Read the daemon PID from the session's PID file.
+    ///
+    /// 0 and values past `i32::MAX` are rejected: `kill` would take them as a
+    /// process group.
+    pub fn read_pid(&self) -> Option<u32> {
+        std::fs::read_to_string(self.pid_path())
+            .ok()
+            .and_then(|content| content.trim().parse().ok())
+            .filter(|&pid| pid != 0 && pid <= i32::MAX as u32)
+    }
+
+    /// Save the settings of a successful connect for `session restart`.
+    ///
+    /// The file is only readable by the current user, since it may hold a saved password.
+    pub fn save_connect_request(&self, request: &ConnectRequest) -> anyhow::Result<()> {
+        use std::io::Write;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(get_connect_config_path(&self.session))?;
+        file.write_all(serde_json::to_string_pretty(request)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Load the settings saved by the last successful connect, if any.
+    pub fn load_connect_request(&self) -> anyhow::Result<Option<ConnectRequest>> {
+        match std::fs::read_to_string(get_connect_config_path(&self.session)) {
+            Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Ask a running daemon to shut down and wait for it to exit.
+    pub async fn stop_daemon(&self, timeout_ms: u64) -> anyhow::Result<()> {
+        if !self.is_daemon_alive() {
+            return Ok(());
+        }
+        if let Ok(mut client) = self.connect_to_daemon().await {
+            let _ = client.send(&Request::Shutdown, timeout_ms).await;
+        }
+
+        for _ in 0..100 {
+            if !self.is_daemon_alive() {
+                return Ok(());
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        anyhow::bail!("Daemon did not shut down within timeout")
+    }
+
     /// Check if the daemon is running.
     pub fn is_daemon_alive(&self) -> bool {
-        let pid_path = self.pid_path();
-
-        if !pid_path.exists() {
+        if !self.pid_path().exists() {
             return false;
         }
 
-        // Read PID from file
-        let pid: u32 = match std::fs::read_to_string(&pid_path) {
-            Ok(content) => match content.trim().parse() {
-                Ok(p) => p,
-                Err(_) => {
-                    self.cleanup_stale_session();
-                    return false;
-                }
-            },
-            Err(_) => {
+        let pid = match self.read_pid() {
+            Some(pid) => pid,
+            None => {
                 self.cleanup_stale_session();
                 return false;
             }
@@ -71,6 +131,151 @@ impl SessionManager {
         alive
     }
 
+    /// Terminate the daemon by its PID and remove the session's files.
+    ///
+    /// Unlike `stop_daemon` this doesn't need the daemon to answer, so it also
+    /// clears out hung daemons. The saved connection settings and logs are kept,
+    /// as when a daemon exits on its own.
+    pub async fn kill(&self) -> KilledSession {
+        let pid = self.read_pid();
+        let mut killed = KilledSession {
+            name: self.session.clone(),
+            pid,
+            outcome: KillOutcome::AlreadyDead,
+            error: None,
+        };
+
+        if let Some(pid) = pid.filter(|pid| Self::process_exists(*pid)) {
+            // Orphaned sessions are where PIDs get reused; only the files go then
+            if !self.is_session_daemon(pid).await {
+                warn!(
+                    "pid {} is not the daemon for session '{}'; removing the stale session files",
+                    pid, self.session
+                );
+                self.cleanup_stale_session();
+                return killed;
+            }
+            info!("Killing daemon for session '{}' (pid {})", self.session, pid);
+            if let Err(e) = Self::terminate(pid).await {
+                warn!("Failed to kill daemon for session '{}': {}", self.session, e);
+                killed.outcome = KillOutcome::Failed;
+                killed.error = Some(e.to_string());
+                return killed;
+            }
+            killed.outcome = KillOutcome::Killed;
+        }
+
+        self.cleanup_stale_session();
+        killed
+    }
+
+    /// Check that `pid` is this session's daemon before signalling it.
+    ///
+    /// A daemon that answers reports its own PID; a hung one is recognized by
+    /// its command line.
+    async fn is_session_daemon(&self, pid: u32) -> bool {
+        if let Ok(mut client) = crate::ipc_client::try_connect(&self.socket_path(), 1, 100).await {
+            if let Ok(response) = client.send(&Request::SessionInfo, SESSION_PROBE_TIMEOUT_MS).await {
+                if let Some(ResponseData::SessionInfo(info)) = response.data {
+                    return info.pid == pid;
+                }
+            }
+        }
+        Self::process_args(pid).is_some_and(|args| is_daemon_command(&args, &self.session))
+    }
+
+    /// Ask a process to terminate and wait for it to exit.
+    async fn terminate(pid: u32) -> anyhow::Result<()> {
+        Self::send_terminate(pid)?;
+        for _ in 0..KILL_WAIT_MS / 100 {
+            if !Self::process_exists(pid) {
+                return Ok(());
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        // A daemon stuck in a blocking call may never act on SIGTERM
+        #[cfg(unix)]
+        {
+            warn!("Daemon (pid {}) ignored SIGTERM; sending SIGKILL", pid);
+            unsafe { libc::kill(pid as i32, libc::SIGKILL) };
+            sleep(Duration::from_millis(100)).await;
+            if !Self::process_exists(pid) {
+                return Ok(());
+            }
+        }
+        anyhow::bail!("Daemon (pid {}) did not exit within {} ms", pid, KILL_WAIT_MS)
+    }
+
+    /// Send SIGTERM to a process.
+    #[cfg(unix)]
+    fn send_terminate(pid: u32) -> anyhow::Result<()> {
+        if unsafe { libc::kill(pid as i32, libc::SIGTERM) } == 0 {
+            return Ok(());
+        }
+        let err = std::io::Error::last_os_error();
+        // Already gone
+        if err.raw_os_error() == Some(libc::ESRCH) {
+            return Ok(());
+        }
+        Err(anyhow::anyhow!("Failed to signal pid {}: {}", pid, err))
+    }
+
+    /// Terminate a process (Windows has no gentler equivalent of SIGTERM).
+    #[cfg(windows)]
+    fn send_terminate(pid: u32) -> anyhow::Result<()> {
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+
+        unsafe {
+            let handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
+            if handle.is_null() {
+                let err = std::io::Error::last_os_error();
+                anyhow::bail!("Failed to open pid {}: {}", pid, err);
+            }
+            let terminated = TerminateProcess(handle, 1);
+            let err = std::io::Error::last_os_error();
+            CloseHandle(handle);
+            if terminated == 0 {
+                anyhow::bail!("Failed to terminate pid {}: {}", pid, err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Arguments a process was started with.
+    #[cfg(target_os = "linux")]
+    fn process_args(pid: u32) -> Option<Vec<String>> {
+        let cmdline = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+        Some(
+            cmdline
+                .split(|&byte| byte == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect(),
+        )
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    fn process_args(pid: u32) -> Option<Vec<String>> {
+        let output = Command::new("ps")
+            .args(["-o", "command=", "-p", &pid.to_string()])
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).split_whitespace().map(str::to_string).collect())
+    }
+
+    /// Windows has no cheap way to read another process's command line, so
+    /// only a daemon that answers on its socket is recognized.
+    #[cfg(windows)]
+    fn process_args(_pid: u32) -> Option<Vec<String>> {
+        None
+    }
+
     /// Check if a process exists.
     #[cfg(unix)]
     fn process_exists(pid: u32) -> bool {
@@ -95,6 +300,14 @@ impl SessionManager {
         }
     }
 
+    /// Connect to the session's daemon if one is running, without starting one.
+    pub async fn attach(&self) -> Option<IpcClient> {
+        if !self.is_daemon_alive() {
+            return None;
+        }
+        self.connect_to_daemon().await.ok()
+    }
+
     /// Clean up stale session directory.
     fn cleanup_stale_session(&self) {
         cleanup_session(&self.session);
@@ -108,7 +321,7 @@ impl SessionManager {
             match self.connect_to_daemon().await {
                 Ok(client) => {
                     // Verify daemon is responsive with a ping
-                    if self.verify_daemon_health(&client).await {
+                    if self.verify_daemon_health(&client).await? {
                         return Ok(client);
                     }
                     warn!("Daemon not responsive, cleaning up and restarting...");
@@ -131,18 +344,47 @@ impl SessionManager {
     }
 
     /// Verify daemon is responsive by sending a ping.
-    async fn verify_daemon_health(&self, _client: &IpcClient) -> bool {
+    ///
+    /// Fails if the daemon speaks another protocol version, as happens when the
+    /// CLI is upgraded while an old daemon keeps running.
+    async fn verify_daemon_health(&self, _client: &IpcClient) -> anyhow::Result<bool> {
         // Create a temporary mutable client for the ping
         let socket_path = self.socket_path();
-        match IpcClient::connect(&socket_path).await {
-            Ok(mut ping_client) => {
-                match ping_client.send(&Request::Ping, 5000).await {
-                    Ok(response) => response.success,
-                    Err(_) => false,
-                }
+        let response = match IpcClient::connect(&socket_path).await {
+            Ok(mut ping_client) => match ping_client.send(&Request::Ping, 5000).await {
+                Ok(response) => response,
+                Err(_) => return Ok(false),
+            },
+            Err(_) => return Ok(false),
+        };
+        if !response.success {
+            return Ok(false);
+        }
+
+        if let Some(ResponseData::Pong { protocol_version, version }) = response.data {
+            if protocol_version != PROTOCOL_VERSION {
+                anyhow::bail!(self.version_mismatch_message(protocol_version, version));
             }
-            Err(_) => false,
         }
+        Ok(true)
+    }
+
+    /// Explain a daemon/CLI version mismatch and how to restart the daemon.
+    fn version_mismatch_message(&self, protocol_version: u32, version: Option<String>) -> String {
+        let daemon = match version {
+            Some(version) => format!("{} (protocol {})", version, protocol_version),
+            None => "from an older release".to_string(),
+        };
+        let disconnect = if self.session == "default" {
+            "agent-rdp disconnect".to_string()
+        } else {
+            format!("agent-rdp --session {} disconnect", self.session)
+        };
+        format!(
+            "Daemon version {} does not match CLI version {} (protocol {}); \
+             run `{}` to restart it",
+            daemon, VERSION, PROTOCOL_VERSION, disconnect
+        )
     }
 
     /// Start the daemon process.
@@ -237,25 +479,77 @@ impl SessionManager {
             .map_err(|e| anyhow::anyhow!("Failed to connect to daemon: {}", e))
     }
 
-    /// List all active sessions.
-    pub fn list_sessions() -> Vec<String> {
-        let base_dir = agent_rdp_daemon::get_base_dir();
+    /// List all session directories, probing each daemon for its real state.
+    ///
+    /// Sessions whose PID file is missing, unreadable or points at a dead process
+    /// are reported as not alive; with `prune` their directories are removed.
+    pub async fn list_sessions(prune: bool) -> Vec<SessionSummary> {
         let mut sessions = Vec::new();
+        for name in Self::session_names() {
+            let manager = SessionManager::new(name.clone());
+            sessions.push(manager.probe(prune).await);
+        }
 
-        if let Ok(entries) = std::fs::read_dir(&base_dir) {
-            for entry in entries.flatten() {
-                if entry.path().is_dir() {
-                    if let Some(name) = entry.file_name().to_str() {
-                        // Verify this session has a PID file (works on both Unix and Windows)
-                        let pid_path = entry.path().join("pid");
-                        if pid_path.exists() {
-                            sessions.push(name.to_string());
-                        }
-                    }
+        sessions
+    }
+
+    /// Names of all session directories, sorted.
+    pub fn session_names() -> Vec<String> {
+        let base_dir = agent_rdp_daemon::get_base_dir();
+        let mut names: Vec<String> = match std::fs::read_dir(&base_dir) {
+            Ok(entries) => entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        names.sort();
+        names
+    }
+
+    /// Report this session's state without starting or cleaning up the daemon.
+    async fn probe(&self, prune: bool) -> SessionSummary {
+        let pid = self.read_pid();
+        let alive = pid.is_some_and(Self::process_exists);
+
+        let mut summary = SessionSummary {
+            name: self.session.clone(),
+            state: ConnectionState::Disconnected,
+            host: None,
+            pid,
+            alive,
+            uptime_secs: None,
+            pruned: false,
+        };
+
+        if !alive {
+            debug!("Session '{}' is stale (pid {:?})", self.session, pid);
+            if prune {
+                remove_session(&self.session);
+                summary.pruned = true;
+            }
+            return summary;
+        }
+
+        // A live process that doesn't answer is still reported as alive
+        if let Ok(mut client) = crate::ipc_client::try_connect(&self.socket_path(), 1, 100).await {
+            if let Ok(response) = client.send(&Request::SessionInfo, SESSION_PROBE_TIMEOUT_MS).await {
+                if let Some(ResponseData::SessionInfo(info)) = response.data {
+                    summary.state = info.state;
+                    summary.host = info.host;
+                    summary.uptime_secs = Some(info.uptime_secs);
                 }
             }
         }
 
-        sessions
+        summary
     }
 }
+
+/// Whether a command line is `agent-rdp --session <session> ... session daemon`,
+/// as `start_daemon` and the Node.js client start it.
+fn is_daemon_command(args: &[String], session: &str) -> bool {
+    args.windows(2).any(|pair| pair[0] == "--session" && pair[1] == session)
+        && args.ends_with(&["session".to_string(), "daemon".to_string()])
+}
@@ -5,11 +5,21 @@
 import type { RdpSession } from './index.js';
 import type {
   AutomationSnapshot,
+  AutomationFormattedSnapshot,
   AutomationElementValue,
   AutomationWindowInfo,
   AutomationStatus,
   AutomationRunResult,
   AutomationClickResult,
+  AutomationDragResult,
+  AutomationHighlightResult,
+  AutomationElementScreenshot,
+  AutomationTypeResult,
+  AutomationWaitForResult,
+  WaitState,
+  ImageFormat,
+  Request,
+  Response,
 } from './types.js';
 
 export interface SnapshotOptions {
@@ -23,6 +33,11 @@ export interface SnapshotOptions {
   selector?: string;
 }
 
+export interface SnapshotTextOptions extends SnapshotOptions {
+  /** 'text' for an indented outline (default), or 'yaml'. */
+  format?: 'text' | 'yaml';
+}
+
 export interface GetOptions {
   /** Property to retrieve: 'name', 'value', 'states', 'bounds', or 'all'. */
   property?: 'name' | 'value' | 'states' | 'bounds' | 'all';
@@ -38,6 +53,11 @@ export interface ToggleOptions {
   state?: boolean;
 }
 
+export interface TypeOptions {
+  /** Clear the element before typing. */
+  clear?: boolean;
+}
+
 export interface ScrollOptions {
   /** Scroll direction. */
   direction?: 'up' | 'down' | 'left' | 'right';
@@ -58,11 +78,18 @@ export interface RunOptions {
   processTimeout?: number;
 }
 
+export interface ExecScriptOptions {
+  /** Process timeout in milliseconds (default: 10000). */
+  processTimeout?: number;
+}
+
 export interface WaitForOptions {
-  /** Timeout in milliseconds. */
+  /** Timeout in milliseconds (default: 10000). */
   timeout?: number;
-  /** State to wait for: 'visible', 'enabled', or 'gone'. */
-  state?: 'visible' | 'enabled' | 'gone';
+  /** State to wait for: 'visible', 'enabled', 'exists', or 'gone'. */
+  state?: WaitState;
+  /** Time between checks in milliseconds (default: 250). */
+  pollInterval?: number;
 }
 
 /**
@@ -103,12 +130,25 @@ export interface WaitForOptions {
  * ```
  */
 export class AutomationController {
-  constructor(private rdp: RdpSession) {}
+  constructor(
+    private rdp: RdpSession,
+    private agentTimeout?: number,
+  ) {}
+
+  /** Send a request, with the session's agent timeout if one is set. */
+  private send(request: Request): Promise<Response> {
+    if (this.agentTimeout === undefined) {
+      return this.rdp._send(request);
+    }
+    return this.rdp._send({ ...request, agent_timeout_ms: this.agentTimeout } as Request);
+  }
 
   /**
    * Take a snapshot of the accessibility tree.
    *
-   * Refs are always included (use @eN format to reference elements).
+   * Refs are always included (use @eN format to reference elements). Each
+   * element also has a `stable_id` (use as `@<stable_id>`) that stays valid
+   * across later snapshots.
    */
   async snapshot(options: SnapshotOptions = {}): Promise<AutomationSnapshot> {
     const request = {
@@ -120,10 +160,28 @@ export class AutomationController {
       selector: options.selector,
       focused: false,
     };
-    const response = await this.rdp._send(request);
+    const response = await this.send(request);
     return response.data as unknown as AutomationSnapshot;
   }
 
+  /**
+   * Take a snapshot rendered by the daemon as an indented text outline or YAML,
+   * which is far smaller than the full tree.
+   */
+  async snapshotText(options: SnapshotTextOptions = {}): Promise<AutomationFormattedSnapshot> {
+    const response = await this.send({
+      type: 'automate' as const,
+      op: 'snapshot' as const,
+      interactive_only: options.interactive ?? false,
+      compact: options.compact ?? false,
+      max_depth: options.depth ?? 10,
+      selector: options.selector,
+      focused: false,
+      format: options.format ?? 'text',
+    });
+    return response.data as unknown as AutomationFormattedSnapshot;
+  }
+
   /**
    * Get element properties.
    */
@@ -134,7 +192,7 @@ export class AutomationController {
       selector,
       property: options.property,
     };
-    const response = await this.rdp._send(request);
+    const response = await this.send(request);
     return response.data as unknown as AutomationElementValue;
   }
 
@@ -142,7 +200,7 @@ export class AutomationController {
    * Set focus to an element.
    */
   async focus(selector: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'focus' as const,
       selector,
@@ -153,25 +211,104 @@ export class AutomationController {
    * Click an element - for buttons, links, menu items.
    *
    * @param selector - Element selector
-   * @param options - Optional settings (e.g., doubleClick for file list items)
+   * @param options - Optional settings (e.g., doubleClick for file list items,
+   *   button: 'right' for context menus)
    * @returns Result with click coordinates and method used
    */
-  async click(selector: string, options: { doubleClick?: boolean } = {}): Promise<AutomationClickResult> {
-    const response = await this.rdp._send({
+  async click(
+    selector: string,
+    options: { doubleClick?: boolean; button?: 'left' | 'right' | 'middle' } = {}
+  ): Promise<AutomationClickResult> {
+    const response = await this.send({
       type: 'automate' as const,
       op: 'click' as const,
       selector,
       double_click: options.doubleClick ?? false,
+      button: options.button,
     });
     return response.data as unknown as AutomationClickResult;
   }
 
+  /**
+   * Invoke an element (InvokePattern) without moving the mouse.
+   * For menu items and buttons that are off-screen or have no clickable point.
+   * Fails if the element doesn't support InvokePattern; fall back to `click`.
+   */
+  async invoke(selector: string): Promise<void> {
+    await this.send({
+      type: 'automate' as const,
+      op: 'invoke' as const,
+      selector,
+    });
+  }
+
+  /**
+   * Drag an element onto another with the mouse - for reordering list items,
+   * moving tree items. Drags from the center of one element to the center of the other.
+   *
+   * @param selector - Element to drag
+   * @param target - Element to drop onto
+   * @returns Result with the drag coordinates
+   */
+  async dragTo(selector: string, target: string): Promise<AutomationDragResult> {
+    const response = await this.send({
+      type: 'automate' as const,
+      op: 'drag_to' as const,
+      selector,
+      target,
+    });
+    return response.data as unknown as AutomationDragResult;
+  }
+
+  /**
+   * Capture an image of just one element, cropped from the desktop to its
+   * bounds. Bounds past the desktop edges are clamped, so an element partly
+   * off screen yields its visible part.
+   *
+   * @param selector - Element to capture
+   * @param options - Image format (default: 'png')
+   * @returns Base64-encoded image and the desktop area captured
+   */
+  async screenshot(
+    selector: string,
+    options: { format?: ImageFormat } = {}
+  ): Promise<AutomationElementScreenshot> {
+    const response = await this.send({
+      type: 'automate' as const,
+      op: 'screenshot' as const,
+      selector,
+      format: options.format ?? 'png',
+    });
+    return response.data as unknown as AutomationElementScreenshot;
+  }
+
+  /**
+   * Draw a colored box around an element on the remote desktop, so a screenshot
+   * shows which element a selector matched. The box never takes focus or clicks.
+   *
+   * @param selector - Element to highlight
+   * @param options - How long the box stays up (default: 3000 ms, max: 60000)
+   * @returns The screen area the box was drawn around
+   */
+  async highlight(
+    selector: string,
+    options: { durationMs?: number } = {}
+  ): Promise<AutomationHighlightResult> {
+    const response = await this.send({
+      type: 'automate' as const,
+      op: 'highlight' as const,
+      selector,
+      duration_ms: options.durationMs ?? 3000,
+    });
+    return response.data as unknown as AutomationHighlightResult;
+  }
+
   /**
    * Select an element or item within container (SelectionItemPattern).
    * For list items, radio buttons, etc.
    */
   async select(selector: string, options: SelectOptions = {}): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'select' as const,
       selector,
@@ -183,7 +320,7 @@ export class AutomationController {
    * Toggle an element (TogglePattern) - for checkboxes.
    */
   async toggle(selector: string, options: ToggleOptions = {}): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'toggle' as const,
       selector,
@@ -195,7 +332,7 @@ export class AutomationController {
    * Expand an element (ExpandCollapsePattern) - for menus, tree items, combo boxes.
    */
   async expand(selector: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'expand' as const,
       selector,
@@ -206,7 +343,7 @@ export class AutomationController {
    * Collapse an element (ExpandCollapsePattern).
    */
   async collapse(selector: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'collapse' as const,
       selector,
@@ -217,7 +354,7 @@ export class AutomationController {
    * Open context menu for an element (Focus + Shift+F10).
    */
   async contextMenu(selector: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'context_menu' as const,
       selector,
@@ -228,7 +365,7 @@ export class AutomationController {
    * Clear and fill text in an element.
    */
   async fill(selector: string, text: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'fill' as const,
       selector,
@@ -236,11 +373,26 @@ export class AutomationController {
     });
   }
 
+  /**
+   * Type text into an element, appending to its value unless `clear` is set.
+   * Returns the element's resulting value so the input can be confirmed.
+   */
+  async type(selector: string, text: string, options: TypeOptions = {}): Promise<AutomationTypeResult> {
+    const response = await this.send({
+      type: 'automate' as const,
+      op: 'type' as const,
+      selector,
+      text,
+      clear: options.clear ?? false,
+    });
+    return response.data as unknown as AutomationTypeResult;
+  }
+
   /**
    * Clear text from an element.
    */
   async clear(selector: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'clear' as const,
       selector,
@@ -251,7 +403,7 @@ export class AutomationController {
    * Scroll an element.
    */
   async scroll(selector: string, options: ScrollOptions = {}): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'scroll' as const,
       selector,
@@ -265,7 +417,7 @@ export class AutomationController {
    * List all windows.
    */
   async listWindows(): Promise<AutomationWindowInfo[]> {
-    const response = await this.rdp._send({
+    const response = await this.send({
       type: 'automate' as const,
       op: 'window' as const,
       action: 'list' as const,
@@ -275,10 +427,11 @@ export class AutomationController {
   }
 
   /**
-   * Focus a window.
+   * Focus a window, restoring it if minimized.
+   * Fails if Windows doesn't let it become the foreground window.
    */
   async focusWindow(selector?: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'window' as const,
       action: 'focus' as const,
@@ -286,11 +439,23 @@ export class AutomationController {
     });
   }
 
+  /**
+   * Bring a window to the top without taking keyboard focus.
+   */
+  async raiseWindow(selector?: string): Promise<void> {
+    await this.send({
+      type: 'automate' as const,
+      op: 'window' as const,
+      action: 'raise' as const,
+      selector,
+    });
+  }
+
   /**
    * Maximize a window.
    */
   async maximizeWindow(selector?: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'window' as const,
       action: 'maximize' as const,
@@ -302,7 +467,7 @@ export class AutomationController {
    * Minimize a window.
    */
   async minimizeWindow(selector?: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'window' as const,
       action: 'minimize' as const,
@@ -314,7 +479,7 @@ export class AutomationController {
    * Restore a window.
    */
   async restoreWindow(selector?: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'window' as const,
       action: 'restore' as const,
@@ -326,7 +491,7 @@ export class AutomationController {
    * Close a window.
    */
   async closeWindow(selector?: string): Promise<void> {
-    await this.rdp._send({
+    await this.send({
       type: 'automate' as const,
       op: 'window' as const,
       action: 'close' as const,
@@ -338,7 +503,7 @@ export class AutomationController {
    * Run a PowerShell command.
    */
   async run(command: string, options: RunOptions = {}): Promise<AutomationRunResult> {
-    const response = await this.rdp._send({
+    const response = await this.send({
       type: 'automate' as const,
       op: 'run' as const,
       command,
@@ -350,24 +515,42 @@ export class AutomationController {
     return response.data as unknown as AutomationRunResult;
   }
 
+  /**
+   * Run a multi-line PowerShell script in its own powershell.exe and wait for it.
+   * The script runs in the user profile directory with the execution policy
+   * bypassed; stdout and stderr are capped, with `truncated` set when cut.
+   */
+  async execScript(script: string, options: ExecScriptOptions = {}): Promise<AutomationRunResult> {
+    const response = await this.send({
+      type: 'automate' as const,
+      op: 'exec_script' as const,
+      script,
+      timeout_ms: options.processTimeout ?? 10000,
+    });
+    return response.data as unknown as AutomationRunResult;
+  }
+
   /**
    * Wait for an element to reach a state.
+   * Rejects with a 'timeout' error if the state isn't reached in time.
    */
-  async waitFor(selector: string, options: WaitForOptions = {}): Promise<void> {
-    await this.rdp._send({
+  async waitFor(selector: string, options: WaitForOptions = {}): Promise<AutomationWaitForResult> {
+    const response = await this.send({
       type: 'automate' as const,
       op: 'wait_for' as const,
       selector,
-      timeout_ms: options.timeout ?? 30000,
+      timeout_ms: options.timeout ?? 10000,
       state: options.state ?? 'visible',
+      poll_interval_ms: options.pollInterval ?? 250,
     });
+    return response.data as unknown as AutomationWaitForResult;
   }
 
   /**
    * Get automation agent status.
    */
   async status(): Promise<AutomationStatus> {
-    const response = await this.rdp._send({
+    const response = await this.send({
       type: 'automate' as const,
       op: 'status' as const,
     });
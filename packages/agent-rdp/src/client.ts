@@ -41,8 +41,8 @@ export function getSessionDir(session: string): string {
  */
 export class IpcClient {
   private socket: net.Socket | null = null;
-  private buffer = '';
-  private pendingResolve: ((response: Response) => void) | null = null;
+  private buffer = Buffer.alloc(0);
+  private pendingResolve: ((response: Response, payload?: Buffer) => void) | null = null;
   private pendingReject: ((error: Error) => void) | null = null;
 
   constructor(private session: string) {}
@@ -69,7 +69,7 @@ export class IpcClient {
       });
 
       socket.on('data', (data) => {
-        this.buffer += data.toString();
+        this.buffer = Buffer.concat([this.buffer, data]);
         this.processBuffer();
       });
 
@@ -85,20 +85,36 @@ export class IpcClient {
   }
 
   /**
-   * Process buffered data looking for newline-delimited JSON.
+   * Process buffered data looking for newline-delimited JSON, each binary
+   * frame followed by its raw pixel bytes.
    */
   private processBuffer(): void {
-    const newlineIndex = this.buffer.indexOf('\n');
+    const newlineIndex = this.buffer.indexOf(0x0a);
     if (newlineIndex === -1) return;
 
-    const line = this.buffer.slice(0, newlineIndex);
-    this.buffer = this.buffer.slice(newlineIndex + 1);
+    const line = this.buffer.subarray(0, newlineIndex).toString();
+    let response: Response | null = null;
+    try {
+      response = JSON.parse(line) as Response;
+    } catch (_err) {
+      response = null;
+    }
+
+    let end = newlineIndex + 1;
+    let payload: Buffer | undefined;
+    const data = response?.data;
+    if (data?.type === 'frame' && data.binary) {
+      // Wait until the whole payload has arrived
+      if (this.buffer.length < end + data.size_bytes) return;
+      payload = Buffer.from(this.buffer.subarray(end, end + data.size_bytes));
+      end += data.size_bytes;
+    }
+    this.buffer = this.buffer.subarray(end);
 
     if (this.pendingResolve) {
-      try {
-        const response = JSON.parse(line) as Response;
-        this.pendingResolve(response);
-      } catch (_err) {
+      if (response) {
+        this.pendingResolve(response, payload);
+      } else {
         this.pendingReject?.(new RdpError('ipc_error', `Invalid JSON response: ${line}`));
       }
       this.pendingResolve = null;
@@ -113,24 +129,32 @@ export class IpcClient {
    * Send a request and wait for the response.
    */
   async send(request: Request, timeout = DEFAULT_TIMEOUT): Promise<Response> {
+    const { response } = await this.sendWithPayload(request, timeout);
+    return response;
+  }
+
+  /**
+   * Send a request and wait for the response and the binary payload that
+   * follows it, if any.
+   */
+  async sendWithPayload(
+    request: Request,
+    timeout = DEFAULT_TIMEOUT,
+  ): Promise<{ response: Response; payload?: Buffer }> {
     if (!this.socket) {
       throw new RdpError('ipc_error', 'Not connected to daemon');
     }
 
     return new Promise((resolve, reject) => {
-      this.pendingResolve = resolve;
-      this.pendingReject = reject;
-
       const timeoutId = setTimeout(() => {
         this.pendingResolve = null;
         this.pendingReject = null;
         reject(new RdpError('timeout', 'Request timed out'));
       }, timeout);
 
-      const originalResolve = resolve;
-      this.pendingResolve = (response) => {
+      this.pendingResolve = (response, payload) => {
         clearTimeout(timeoutId);
-        originalResolve(response);
+        resolve({ response, payload });
       };
 
       const originalReject = reject;
@@ -9,6 +9,11 @@ export type AccessibilityElement = {
  * Reference number (for @ref selectors).
  */
 ref?: number, 
+/**
+ * Handle that stays valid across snapshots (for @sXXXXXXXXXX selectors),
+ * derived from the element's role, automation ID, name and path from the desktop.
+ */
+stable_id?: string, 
 /**
  * Element role (control type).
  */
@@ -1,5 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AutomationScrollDirection } from "./AutomationScrollDirection.js";
+import type { ImageFormat } from "./ImageFormat.js";
+import type { SnapshotFormat } from "./SnapshotFormat.js";
 import type { WaitState } from "./WaitState.js";
 import type { WindowAction } from "./WindowAction.js";
 
@@ -26,7 +28,11 @@ selector?: string,
 /**
  * Start from the currently focused element.
  */
-focused: boolean, } | { "op": "get", 
+focused: boolean, 
+/**
+ * Shape of the result: the full tree, or a YAML or text rendering of it.
+ */
+format: SnapshotFormat, } | { "op": "get", 
 /**
  * Element selector.
  */
@@ -46,7 +52,39 @@ selector: string,
 /**
  * Use double-click instead of single click.
  */
-double_click: boolean, } | { "op": "select", 
+double_click: boolean, 
+/**
+ * Mouse button: "left", "right" or "middle" (default: left).
+ */
+button?: string, } | { "op": "invoke", 
+/**
+ * Element selector.
+ */
+selector: string, } | { "op": "drag_to", 
+/**
+ * Element selector of the element to drag.
+ */
+selector: string, 
+/**
+ * Element selector of the drop target.
+ */
+target: string, } | { "op": "screenshot", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * Image format (default: png).
+ */
+format: ImageFormat, } | { "op": "highlight", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * How long the box stays up, in milliseconds (default: 3000, max: 60000).
+ */
+duration_ms: number, } | { "op": "select", 
 /**
  * Element selector (container or item directly).
  */
@@ -82,7 +120,19 @@ selector: string,
 /**
  * Text to fill.
  */
-text: string, } | { "op": "clear", 
+text: string, } | { "op": "type", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * Text to type.
+ */
+text: string, 
+/**
+ * Clear the element first.
+ */
+clear: boolean, } | { "op": "clear", 
 /**
  * Element selector.
  */
@@ -130,6 +180,14 @@ hidden: boolean,
 /**
  * Timeout in milliseconds when waiting.
  */
+timeout_ms: number, } | { "op": "exec_script", 
+/**
+ * Script source.
+ */
+script: string, 
+/**
+ * Timeout in milliseconds; the script is killed when it runs out.
+ */
 timeout_ms: number, } | { "op": "wait_for", 
 /**
  * Element selector.
@@ -142,4 +200,8 @@ timeout_ms: number,
 /**
  * State to wait for.
  */
-state: WaitState, } | { "op": "status" };
+state: WaitState, 
+/**
+ * Time between checks in milliseconds.
+ */
+poll_interval_ms: number, } | { "op": "status" };
@@ -19,4 +19,17 @@ capabilities: Array<string>,
 /**
  * Agent version.
  */
-version?: string, };
+version?: string, 
+/**
+ * Times the agent was restarted after dying or hanging in this session.
+ */
+restarts: number, 
+/**
+ * Whether input goes to a secure desktop (a UAC prompt, the lock or
+ * logon screen), which ignores synthesized input.
+ */
+secure_desktop: boolean, 
+/**
+ * Whether Scroll Lock is on in the remote session.
+ */
+scroll_lock: boolean, };
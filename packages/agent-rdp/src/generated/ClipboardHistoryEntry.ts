@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Text from one remote copy.
+ */
+export type ClipboardHistoryEntry = { 
+/**
+ * Copied text.
+ */
+text: string, 
+/**
+ * When the text was copied, in milliseconds since the Unix epoch.
+ */
+copied_at_ms: number, };
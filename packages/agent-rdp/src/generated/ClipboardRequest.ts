@@ -1,6 +1,47 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LineEndings } from "./LineEndings.js";
 
 /**
  * Clipboard operation request.
  */
-export type ClipboardRequest = { "action": "get" } | { "action": "set", text: string, };
+export type ClipboardRequest = { "action": "get", 
+/**
+ * Line ending conversion of the returned text (default: lf).
+ */
+line_endings?: LineEndings, } | { "action": "set", text: string, 
+/**
+ * Line ending conversion before the text is copied (default: crlf).
+ */
+line_endings?: LineEndings, } | { "action": "get_image" } | { "action": "set_image", 
+/**
+ * Base64-encoded PNG data.
+ */
+base64: string, } | { "action": "get_html" } | { "action": "set_html", 
+/**
+ * HTML fragment to copy.
+ */
+html: string, 
+/**
+ * Plain text version (default: the HTML with tags removed).
+ */
+text?: string, } | { "action": "get_files", 
+/**
+ * Local directory to save the files into (created if missing).
+ */
+dest_dir: string, 
+/**
+ * Maximum total bytes to transfer (default: 1 GiB).
+ */
+max_bytes?: number, } | { "action": "set_files", 
+/**
+ * Local files or directories to copy.
+ */
+paths: Array<string>, 
+/**
+ * Maximum total bytes to transfer (default: 1 GiB).
+ */
+max_bytes?: number, } | { "action": "history", 
+/**
+ * Number of entries to return (default: all that are kept).
+ */
+count?: number, } | { "action": "clear" } | { "action": "status" };
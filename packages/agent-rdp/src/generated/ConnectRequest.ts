@@ -1,5 +1,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { DriveMapping } from "./DriveMapping.js";
+import type { GatewayRequest } from "./GatewayRequest.js";
+import type { GraphicsCodec } from "./GraphicsCodec.js";
+import type { MonitorLayout } from "./MonitorLayout.js";
+import type { PerformanceFlag } from "./PerformanceFlag.js";
+import type { PerformanceProfile } from "./PerformanceProfile.js";
+import type { SecurityMode } from "./SecurityMode.js";
+import type { TlsVersion } from "./TlsVersion.js";
 
 /**
  * RDP connection parameters.
@@ -37,6 +44,16 @@ height: number,
  * Drives to map at connect time.
  */
 drives: Array<DriveMapping>, 
+/**
+ * Monitor layout, positioned relative to the primary monitor. The desktop size
+ * becomes the bounding box of the monitors (default: one monitor of `width` x `height`).
+ */
+monitors: Array<MonitorLayout>, 
+/**
+ * Logical `[width, height]` that mouse and scroll coordinates are given in,
+ * scaled to the desktop size (default: desktop pixels).
+ */
+coordinate_space?: [number, number], 
 /**
  * Enable Windows UI Automation.
  */
@@ -57,4 +74,154 @@ stream_quality: number,
  * Serve the embedded HTML viewer on the streaming port (default: false).
  * When false, only WebSocket connections are accepted.
  */
-serve_viewer: boolean, };
+serve_viewer: boolean, 
+/**
+ * Largest width or height of streamed frames. Larger desktops are scaled
+ * down, keeping their aspect ratio, before JPEG encoding; screenshots are
+ * not affected. Unset streams frames at the desktop size.
+ */
+stream_max_frame_size?: number, 
+/**
+ * Keyboard layout identifier (LCID, e.g. 0x409 for US English, 0x40C for French).
+ * Defaults to US English when not specified.
+ */
+keyboard_layout?: number, 
+/**
+ * Expected SHA-256 fingerprint of the server certificate (hex, colons optional).
+ * When set, the connection fails if the server presents a different certificate.
+ */
+expected_cert_sha256?: string, 
+/**
+ * Re-establish the connection automatically after a network drop (default: false).
+ */
+auto_reconnect: boolean, 
+/**
+ * Maximum reconnection attempts before giving up (default: 5).
+ */
+max_reconnect_attempts: number, 
+/**
+ * Send a keepalive after this many seconds without data from the server,
+ * and treat the connection as lost once the server has been silent for
+ * three intervals. 0 or unset disables it.
+ */
+keepalive_interval_secs?: number, 
+/**
+ * After connecting, check that keyboard input reaches the remote desktop
+ * and report the result in the response (default: false). Needs
+ * `enable_win_automation` to read the result back.
+ */
+test_input: boolean, 
+/**
+ * Wait, at most `FIRST_FRAME_TIMEOUT_MS`, for the first graphics update
+ * before answering, so a screenshot taken right after connect shows the
+ * desktop instead of a black image (default: false).
+ */
+wait_first_frame: boolean, 
+/**
+ * Retry the initial connect this many times while the server refuses,
+ * resets or doesn't answer the connection, e.g. while it is still
+ * booting (default: 0). Other failures, such as rejected credentials,
+ * are never retried.
+ */
+connect_retries: number, 
+/**
+ * Delay before the first connect retry, doubling for each one after it
+ * up to 30 seconds (default: 1000).
+ */
+retry_delay_ms: number, 
+/**
+ * Time each connect attempt may take to open the TCP connection and
+ * complete the TLS and CredSSP handshake (default: 20000).
+ */
+connect_timeout_ms?: number, 
+/**
+ * Security protocol to negotiate (default: auto).
+ */
+security: SecurityMode, 
+/**
+ * Oldest TLS version to accept from the server (default: 1.2).
+ */
+min_tls?: TlsVersion, 
+/**
+ * Graphics codec to request from the server (default: bitmap).
+ */
+codec: GraphicsCodec, 
+/**
+ * Visual effects to ask the server to turn off (default: lan).
+ */
+perf_profile: PerformanceProfile, 
+/**
+ * Exact performance flags to send, replacing those of `perf_profile`.
+ */
+perf_flags?: Array<PerformanceFlag>, 
+/**
+ * RD Gateway to tunnel the connection through.
+ */
+gateway?: GatewayRequest, 
+/**
+ * Proxy URL for the TCP connection: `socks5://[user:pass@]host[:port]` or
+ * `http://[user:pass@]host[:port]` (HTTP CONNECT).
+ */
+proxy?: string, 
+/**
+ * KDC for Kerberos authentication: `host[:port]`, `tcp://host[:port]` or
+ * `udp://host[:port]` (port 88 by default). NTLM is used without one.
+ */
+kdc?: string, 
+/**
+ * Record remote audio to this WAV file (relative paths are resolved against
+ * the session directory). Only the first audio format negotiated is recorded.
+ */
+capture_audio?: string, 
+/**
+ * Record the desktop to this animated GIF (relative paths are resolved
+ * against the session directory). The file is finalized on disconnect.
+ */
+record?: string, 
+/**
+ * Frames per second sampled for the recording (default: 2, max: 10).
+ */
+record_fps?: number, 
+/**
+ * Redirect local smart card readers through the PC/SC service. Ignored when
+ * the daemon was built without smart card support or no reader is present.
+ */
+redirect_smartcard: boolean, 
+/**
+ * Attach the clipboard channel (default: true). Clipboard requests fail
+ * when it's off.
+ */
+enable_clipboard: boolean, 
+/**
+ * Attach the audio output channel (default: true). Required for `capture_audio`.
+ */
+enable_audio: boolean, 
+/**
+ * Shut the daemon down after this many seconds without requests or WebSocket
+ * clients. Pings count as requests. 0 or unset keeps it running.
+ */
+idle_timeout_secs?: bigint, 
+/**
+ * Client computer name reported to the server (at most 15 characters).
+ * Defaults to "agent-rdp".
+ */
+client_name?: string, 
+/**
+ * Client build number reported to the server (default: 0).
+ */
+client_build?: number, 
+/**
+ * Product ID reported to the server (at most 31 characters).
+ */
+dig_product_id?: string, 
+/**
+ * Client hardware ID as 32 hex digits (dashes allowed, as in a GUID).
+ * Defaults to an ID derived from the session name, so reconnects of the
+ * same session look like the same device.
+ */
+hardware_id?: string, 
+/**
+ * Number of remote clipboard texts to remember (default: 5, max: 100,
+ * 0 disables the history).
+ */
+clipboard_history?: number, };
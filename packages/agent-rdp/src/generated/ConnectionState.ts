@@ -3,4 +3,4 @@
 /**
  * Connection state.
  */
-export type ConnectionState = "disconnected" | "connecting" | "connected" | "failed";
+export type ConnectionState = "disconnected" | "connecting" | "connected" | "reconnecting" | "failed";
@@ -0,0 +1,38 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Mouse pointer position and shape.
+ */
+export type CursorInfo = { 
+/**
+ * Pointer X position on the desktop.
+ */
+x: number, 
+/**
+ * Pointer Y position on the desktop.
+ */
+y: number, 
+/**
+ * Whether the pointer is shown (the server hides it, e.g. while typing).
+ */
+visible: boolean, 
+/**
+ * Pointer image width (when the server sent a pointer shape).
+ */
+width?: number, 
+/**
+ * Pointer image height (when the server sent a pointer shape).
+ */
+height?: number, 
+/**
+ * X offset of the pointer's hot spot (the pixel at x, y) in the image.
+ */
+hotspot_x?: number, 
+/**
+ * Y offset of the pointer's hot spot in the image.
+ */
+hotspot_y?: number, 
+/**
+ * Base64-encoded PNG of the pointer image (only when requested).
+ */
+base64?: string, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DisconnectReason } from "./DisconnectReason.js";
+
+/**
+ * The reason a session ended, with the server's description.
+ */
+export type DisconnectInfo = { 
+/**
+ * Classified reason.
+ */
+reason: DisconnectReason, 
+/**
+ * Detail from the server or the transport error.
+ */
+message: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Why an RDP session ended.
+ */
+export type DisconnectReason = "logged_off" | "idle_timeout" | "logon_timeout" | "admin_disconnected" | "replaced_by_other_connection" | "server_terminated" | "protocol_error" | "connection_lost";
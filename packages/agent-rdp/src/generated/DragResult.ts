@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Drag action result.
+ */
+export type DragResult = { 
+/**
+ * Whether the drag was performed.
+ */
+dragged: boolean, 
+/**
+ * X coordinate the drag started at (center of the source element).
+ */
+from_x: number, 
+/**
+ * Y coordinate the drag started at.
+ */
+from_y: number, 
+/**
+ * X coordinate the element was dropped at (center of the target element).
+ */
+to_x: number, 
+/**
+ * Y coordinate the element was dropped at.
+ */
+to_y: number, };
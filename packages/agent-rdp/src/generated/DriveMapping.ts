@@ -11,4 +11,8 @@ path: string,
 /**
  * Name for the mapped drive (shown in Windows).
  */
-name: string, };
+name: string, 
+/**
+ * Expose the drive read-only (remote host cannot create, modify or delete files).
+ */
+read_only: boolean, };
@@ -2,7 +2,42 @@
 
 /**
  * Drive mapping operation request.
- * Note: Drives are configured at connect time with --drive flag.
- * Dynamic mapping/unmapping is not supported by the RDP protocol.
+ * Drives are configured at connect time with the --drive flag, and can be
+ * mounted and unmounted while connected if drive redirection is enabled.
  */
-export type DriveRequest = { "action": "list" };
+export type DriveRequest = { "action": "list" } | { "action": "push", 
+/**
+ * Local file or directory to copy.
+ */
+local: string, 
+/**
+ * Destination: the drive name followed by a path inside it, e.g.
+ * `Shared/reports/q3.pdf`. An existing directory receives the source
+ * under its own name.
+ */
+remote: string, } | { "action": "pull", 
+/**
+ * Source: the drive name followed by a path inside it.
+ */
+remote: string, 
+/**
+ * Local destination. An existing directory receives the source under
+ * its own name.
+ */
+local: string, } | { "action": "mount", 
+/**
+ * Local directory to map.
+ */
+path: string, 
+/**
+ * Drive name shown on the remote (`\\tsclient\<name>`).
+ */
+name: string, 
+/**
+ * Reject writes from the remote.
+ */
+read_only: boolean, } | { "action": "unmount", 
+/**
+ * Name of the drive to remove.
+ */
+name: string, };
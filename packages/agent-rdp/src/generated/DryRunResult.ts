@@ -0,0 +1,47 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrorCode } from "./ErrorCode.js";
+
+/**
+ * How far a connect dry run got before disconnecting.
+ */
+export type DryRunResult = { 
+/**
+ * Server host.
+ */
+host: string, 
+/**
+ * Server port.
+ */
+port: number, 
+/**
+ * The server (or the gateway tunnel to it) accepted the TCP connection.
+ */
+reachable: boolean, 
+/**
+ * TLS was negotiated.
+ */
+tls_ok: boolean, 
+/**
+ * The credentials were accepted and the connection sequence completed.
+ */
+auth_ok: boolean, 
+/**
+ * Desktop width negotiated by the server.
+ */
+width?: number, 
+/**
+ * Desktop height negotiated by the server.
+ */
+height?: number, 
+/**
+ * SHA-256 fingerprint of the server certificate (hex).
+ */
+cert_fingerprint?: string, 
+/**
+ * Why the handshake stopped, if it failed.
+ */
+error?: string, 
+/**
+ * Error code of the failure, as a real connect would report it.
+ */
+error_code?: ErrorCode, };
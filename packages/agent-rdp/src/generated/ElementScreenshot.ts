@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ElementBounds } from "./ElementBounds.js";
+
+/**
+ * Image of one element.
+ */
+export type ElementScreenshot = { 
+/**
+ * Desktop area captured: the element's bounds, clamped to the desktop.
+ */
+bounds: ElementBounds, 
+/**
+ * Image format.
+ */
+format: string, 
+/**
+ * Encoded image size in bytes.
+ */
+size_bytes: number, 
+/**
+ * Base64-encoded image data.
+ */
+base64: string, };
@@ -3,4 +3,4 @@
 /**
  * Error codes for structured error handling.
  */
-export type ErrorCode = "not_connected" | "already_connected" | "connection_failed" | "authentication_failed" | "timeout" | "invalid_request" | "not_supported" | "internal_error" | "session_not_found" | "ipc_error" | "daemon_not_running" | "clipboard_error" | "drive_error" | "automation_not_enabled" | "automation_error" | "element_not_found" | "stale_ref" | "command_failed";
+export type ErrorCode = "not_connected" | "already_connected" | "connection_failed" | "authentication_failed" | "connection_refused" | "host_not_found" | "connection_reset" | "tls_failed" | "certificate_mismatch" | "logon_denied" | "account_locked" | "timeout" | "invalid_request" | "not_supported" | "internal_error" | "session_not_found" | "ipc_error" | "daemon_not_running" | "clipboard_error" | "drive_error" | "automation_not_enabled" | "automation_error" | "element_not_found" | "text_not_found" | "language_not_available" | "stale_ref" | "command_failed";
@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SnapshotFormat } from "./SnapshotFormat.js";
+
+/**
+ * Accessibility tree snapshot rendered as YAML or text.
+ */
+export type FormattedSnapshot = { 
+/**
+ * Unique snapshot ID.
+ */
+snapshot_id: string, 
+/**
+ * Total number of elements with refs.
+ */
+ref_count: number, 
+/**
+ * Whether the tree was truncated due to depth limit.
+ */
+truncated: boolean, 
+/**
+ * Maximum depth used for this snapshot.
+ */
+max_depth: number, 
+/**
+ * Format of `text`.
+ */
+format: SnapshotFormat, 
+/**
+ * The rendered tree.
+ */
+text: string, };
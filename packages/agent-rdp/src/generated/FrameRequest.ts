@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FrameEncoding } from "./FrameEncoding.js";
+
+/**
+ * Framebuffer request parameters.
+ *
+ * Raw frames are `width * height * 4` bytes of RGBA (about 4 MB at
+ * 1280x800), meant for local consumers reading frames at a high rate.
+ */
+export type FrameRequest = { 
+/**
+ * Pixel encoding.
+ */
+encoding: FrameEncoding, 
+/**
+ * Send the pixels as `size_bytes` raw bytes right after the response line
+ * instead of base64 inside it.
+ */
+binary: boolean, };
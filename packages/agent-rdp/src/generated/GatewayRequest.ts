@@ -0,0 +1,33 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * RD Gateway used to reach the RDP server.
+ */
+export type GatewayRequest = { 
+/**
+ * Gateway hostname or IP address.
+ */
+host: string, 
+/**
+ * Gateway HTTPS port (default: 443).
+ */
+port: number, 
+/**
+ * Gateway username (default: the RDP username).
+ */
+username?: string, 
+/**
+ * Gateway password (default: the RDP password).
+ */
+password?: string, 
+/**
+ * Expected SHA-256 fingerprint of the gateway certificate (hex, colons
+ * optional), for gateways without a publicly trusted certificate. By default
+ * the certificate must chain to a public root and match the host name.
+ */
+expected_cert_sha256?: string, 
+/**
+ * Accept any gateway certificate. The gateway credentials then go to
+ * whoever answers, so this is only for networks you control.
+ */
+insecure: boolean, };
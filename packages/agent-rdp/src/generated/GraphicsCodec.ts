@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Graphics codec requested for desktop updates.
+ *
+ * H.264 (AVC420/AVC444) needs the graphics pipeline channel, which isn't
+ * implemented, so it isn't offered.
+ */
+export type GraphicsCodec = "bitmap" | "remotefx";
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ElementBounds } from "./ElementBounds.js";
+
+/**
+ * Highlight action result.
+ */
+export type HighlightResult = { 
+/**
+ * Screen area the box was drawn around.
+ */
+bounds: ElementBounds, 
+/**
+ * How long the box stays up, in milliseconds.
+ */
+duration_ms: number, };
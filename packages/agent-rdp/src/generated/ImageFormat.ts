@@ -3,4 +3,4 @@
 /**
  * Supported image formats.
  */
-export type ImageFormat = "png" | "jpeg";
+export type ImageFormat = "png" | "jpeg" | "webp" | "avif";
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InputEvent } from "./InputEvent.js";
+
+/**
+ * A batch of input events.
+ */
+export type InputBatchRequest = { 
+/**
+ * Events to execute, in order.
+ */
+events: Array<InputEvent>, 
+/**
+ * Pause after each event and between typed characters, in milliseconds (default: 20).
+ */
+delay_ms?: number, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InputCheckStatus } from "./InputCheckStatus.js";
+
+/**
+ * Whether input injection works on the remote desktop.
+ */
+export type InputCheck = { status: InputCheckStatus, 
+/**
+ * What was found, for people reading the output.
+ */
+message: string, };
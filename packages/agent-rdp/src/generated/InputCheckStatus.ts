@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of the input self-check run after connecting.
+ */
+export type InputCheckStatus = "working" | "not_reaching" | "secure_desktop" | "unverified";
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { KeyboardRequest } from "./KeyboardRequest.js";
+import type { MouseRequest } from "./MouseRequest.js";
+import type { ScrollRequest } from "./ScrollRequest.js";
+
+/**
+ * A single event in an input batch.
+ */
+export type InputEvent = { "type": "mouse" } & MouseRequest | { "type": "keyboard" } & KeyboardRequest | { "type": "scroll" } & ScrollRequest | { "type": "wait", 
+/**
+ * Milliseconds to wait (max 60000).
+ */
+ms: number, };
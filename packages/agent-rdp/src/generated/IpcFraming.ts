@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How messages are delimited on an IPC connection.
+ */
+export type IpcFraming = "line" | "length_prefixed";
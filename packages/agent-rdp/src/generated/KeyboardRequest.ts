@@ -1,6 +1,28 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TypeMode } from "./TypeMode.js";
 
 /**
  * Keyboard operation request.
  */
-export type KeyboardRequest = { "action": "type", text: string, } | { "action": "press", keys: string, } | { "action": "key_down", key: string, } | { "action": "key_up", key: string, };
+export type KeyboardRequest = { "action": "type", text: string, 
+/**
+ * How characters are sent (default: unicode).
+ */
+mode: TypeMode, 
+/**
+ * Pause after each character in milliseconds (default: 100). 0 sends
+ * the whole text at once.
+ */
+delay_ms?: number, } | { "action": "press", keys: string, } | { "action": "key_down", key: string, } | { "action": "key_up", key: string, } | { "action": "raw", scancode: number, 
+/**
+ * Set the extended-key flag (E0 prefix).
+ */
+extended: boolean, 
+/**
+ * Send a release instead of a press.
+ */
+release: boolean, } | { "action": "hold", keys: string, } | { "action": "release", keys: string, } | { "action": "release_all" } | { "action": "paste", text: string, 
+/**
+ * Put the previous clipboard text back afterward (default: false).
+ */
+restore: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What `session kill` did to a session's daemon.
+ */
+export type KillOutcome = "killed" | "already_dead" | "failed";
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { KillOutcome } from "./KillOutcome.js";
+
+/**
+ * Result of tearing down one session.
+ */
+export type KilledSession = { 
+/**
+ * Session name.
+ */
+name: string, 
+/**
+ * Daemon process ID from the PID file, if it had one.
+ */
+pid?: number, 
+/**
+ * What happened to the daemon.
+ */
+outcome: KillOutcome, 
+/**
+ * Why the daemon couldn't be stopped.
+ */
+error?: string, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How line endings of clipboard text are converted.
+ *
+ * Windows programs expect CRLF, so text is converted to CRLF when set and
+ * back to LF when read unless asked otherwise.
+ */
+export type LineEndings = "preserve" | "crlf" | "lf";
@@ -0,0 +1,48 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ElementBounds } from "./ElementBounds.js";
+import type { MouseButton } from "./MouseButton.js";
+
+/**
+ * Find text on screen with OCR and click it.
+ *
+ * Clicks the most confident match, or the `index`th match in reading order
+ * (top to bottom, then left to right) when several lines contain the text.
+ */
+export type LocateClickRequest = { 
+/**
+ * Text to search for.
+ */
+text: string, 
+/**
+ * Use pattern matching (glob-style: * and ?).
+ */
+pattern: boolean, 
+/**
+ * Case-insensitive matching (default: true).
+ */
+ignore_case: boolean, 
+/**
+ * Only search within this region of the desktop (default: full screen).
+ */
+region?: ElementBounds, 
+/**
+ * Button to click with (default: left).
+ */
+button?: MouseButton, 
+/**
+ * Click the match at this position (0-based) in reading order instead of
+ * the most confident one.
+ */
+index?: number, 
+/**
+ * Ignore matches below this confidence, from 0.0 to 1.0 (default: 0.2).
+ */
+min_confidence?: number, 
+/**
+ * Directory holding the OCR models (see `LocateRequest::models_dir`).
+ */
+models_dir?: string, 
+/**
+ * Language to recognize (default: `en`).
+ */
+lang?: string, };
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ElementBounds } from "./ElementBounds.js";
 
 /**
  * OCR-based text location request.
@@ -20,4 +21,17 @@ ignore_case: boolean,
 /**
  * Return all text on screen (ignores text/pattern/ignore_case).
  */
-all: boolean, };
+all: boolean, 
+/**
+ * Only search within this region of the desktop (default: full screen).
+ */
+region?: ElementBounds, 
+/**
+ * Directory holding the OCR models (default: `AGENT_RDP_OCR_MODELS` in the
+ * daemon's environment, then the models shipped next to the binary).
+ */
+models_dir?: string, 
+/**
+ * Language to recognize, e.g. `en` or `ru` (default: `en`).
+ */
+lang?: string, };
@@ -11,4 +11,24 @@ name: string,
 /**
  * Local path.
  */
-path: string, };
+path: string, 
+/**
+ * Whether the drive is mapped read-only.
+ */
+read_only: boolean, 
+/**
+ * RDPDR device ID the drive is announced under (0 if unknown).
+ */
+device_id: number, 
+/**
+ * Path the remote sees the drive at (`\\TSCLIENT\Name`).
+ */
+unc_path: string, 
+/**
+ * Size of the volume holding the local path, as reported to the remote.
+ */
+total_bytes?: number, 
+/**
+ * Free space on that volume available to the daemon.
+ */
+free_bytes?: number, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A monitor of a multi-monitor desktop.
+ */
+export type MonitorLayout = { 
+/**
+ * Left edge (relative to the primary monitor when connecting; in desktop
+ * coordinates in session info).
+ */
+x: number, 
+/**
+ * Top edge.
+ */
+y: number, 
+/**
+ * Width in pixels.
+ */
+width: number, 
+/**
+ * Height in pixels.
+ */
+height: number, 
+/**
+ * Whether this is the primary monitor (default: the first monitor).
+ */
+primary: boolean, };
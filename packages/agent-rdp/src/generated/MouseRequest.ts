@@ -4,4 +4,25 @@ import type { MouseButton } from "./MouseButton.js";
 /**
  * Mouse operation request.
  */
-export type MouseRequest = { "action": "move", x: number, y: number, } | { "action": "click", x: number, y: number, } | { "action": "right_click", x: number, y: number, } | { "action": "double_click", x: number, y: number, } | { "action": "middle_click", x: number, y: number, } | { "action": "drag", from_x: number, from_y: number, to_x: number, to_y: number, } | { "action": "button_down", button: MouseButton, } | { "action": "button_up", button: MouseButton, };
+export type MouseRequest = { "action": "move", x: number, y: number, 
+/**
+ * Glide along a curved, eased path from the current position instead of
+ * jumping there (default: false).
+ */
+human: boolean, } | { "action": "click", x: number, y: number, 
+/**
+ * Glide to the position like `Move` with `human` before clicking (default: false).
+ */
+human: boolean, } | { "action": "right_click", x: number, y: number, } | { "action": "double_click", x: number, y: number, } | { "action": "middle_click", x: number, y: number, } | { "action": "drag", from_x: number, from_y: number, to_x: number, to_y: number, 
+/**
+ * Number of intermediate moves while the button is held (default: 10).
+ */
+steps?: number, } | { "action": "button_down", button: MouseButton, } | { "action": "button_up", button: MouseButton, } | { "action": "down", x: number, y: number, 
+/**
+ * Button to press (default: left).
+ */
+button: MouseButton, } | { "action": "up", x: number, y: number, 
+/**
+ * Button to release (default: left).
+ */
+button: MouseButton, } | { "action": "release_all" };
@@ -31,4 +31,8 @@ center_x: number,
 /**
  * Center Y coordinate (for clicking).
  */
-center_y: number, };
+center_y: number, 
+/**
+ * Detection confidence (0.0 to 1.0).
+ */
+confidence: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One of the performance flags sent to the server in the client info.
+ */
+export type PerformanceFlag = "disable_wallpaper" | "disable_full_window_drag" | "disable_menu_animations" | "disable_theming" | "disable_cursor_shadow" | "disable_cursor_settings" | "enable_font_smoothing" | "enable_desktop_composition";
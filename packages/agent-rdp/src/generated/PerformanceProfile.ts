@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Visual effects profile, named after the mstsc connection speeds.
+ *
+ * Each step down turns off more eye-candy, which cuts bandwidth and keeps
+ * screenshots the same from run to run.
+ */
+export type PerformanceProfile = "full" | "lan" | "broadband" | "modem";
@@ -0,0 +1,274 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AutomationScrollDirection } from "./AutomationScrollDirection.js";
+import type { ClipboardRequest } from "./ClipboardRequest.js";
+import type { ConnectRequest } from "./ConnectRequest.js";
+import type { DriveRequest } from "./DriveRequest.js";
+import type { ElementBounds } from "./ElementBounds.js";
+import type { FrameRequest } from "./FrameRequest.js";
+import type { ImageFormat } from "./ImageFormat.js";
+import type { InputBatchRequest } from "./InputBatchRequest.js";
+import type { IpcFraming } from "./IpcFraming.js";
+import type { KeyboardRequest } from "./KeyboardRequest.js";
+import type { LocateClickRequest } from "./LocateClickRequest.js";
+import type { LocateRequest } from "./LocateRequest.js";
+import type { MouseRequest } from "./MouseRequest.js";
+import type { ScreenshotRequest } from "./ScreenshotRequest.js";
+import type { ScrollRequest } from "./ScrollRequest.js";
+import type { SnapshotFormat } from "./SnapshotFormat.js";
+import type { WaitState } from "./WaitState.js";
+import type { WindowAction } from "./WindowAction.js";
+
+/**
+ * A request as sent over IPC, optionally tagged with an ID.
+ *
+ * Requests with an ID are processed concurrently and their responses carry
+ * the same ID, in completion order. Requests without one are answered one at
+ * a time, in order.
+ */
+export type RequestEnvelope = { 
+/**
+ * Client-chosen ID echoed in the response.
+ */
+id?: number, } & ({ "type": "connect" } & ConnectRequest | { "type": "disconnect", 
+/**
+ * Log the Windows user off first, ending their programs and the
+ * session, instead of leaving it running for a later reconnect.
+ */
+logoff: boolean, } | { "type": "screenshot" } & ScreenshotRequest | { "type": "frame" } & FrameRequest | { "type": "resize", 
+/**
+ * New desktop width (200-8192, rounded down to an even number).
+ */
+width: number, 
+/**
+ * New desktop height (200-8192).
+ */
+height: number, } | { "type": "stream_config", 
+/**
+ * Frames per second (1-60).
+ */
+fps?: number, 
+/**
+ * JPEG quality (1-100).
+ */
+quality?: number, 
+/**
+ * Send only the changed tiles of each frame.
+ */
+delta?: boolean, } | { "type": "mouse" } & MouseRequest | { "type": "keyboard" } & KeyboardRequest | { "type": "scroll" } & ScrollRequest | { "type": "input_batch" } & InputBatchRequest | { "type": "clipboard" } & ClipboardRequest | { "type": "drive" } & DriveRequest | { "type": "automate", 
+/**
+ * How long to wait for each answer from the automation agent, in
+ * milliseconds (default: 10000, longer for `run --wait`).
+ */
+agent_timeout_ms?: number, } & ({ "op": "snapshot", 
+/**
+ * Filter to interactive elements only (buttons, inputs, links).
+ */
+interactive_only: boolean, 
+/**
+ * Compact mode - remove empty structural elements.
+ */
+compact: boolean, 
+/**
+ * Maximum tree depth to traverse.
+ */
+max_depth: number, 
+/**
+ * Scope to a specific element (window, panel, etc.) via selector.
+ */
+selector?: string, 
+/**
+ * Start from the currently focused element.
+ */
+focused: boolean, 
+/**
+ * Shape of the result: the full tree, or a YAML or text rendering of it.
+ */
+format: SnapshotFormat, } | { "op": "get", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * Property to retrieve (name, value, states, bounds, or all).
+ */
+property?: string, } | { "op": "focus", 
+/**
+ * Element selector.
+ */
+selector: string, } | { "op": "click", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * Use double-click instead of single click.
+ */
+double_click: boolean, 
+/**
+ * Mouse button: "left", "right" or "middle" (default: left).
+ */
+button?: string, } | { "op": "invoke", 
+/**
+ * Element selector.
+ */
+selector: string, } | { "op": "drag_to", 
+/**
+ * Element selector of the element to drag.
+ */
+selector: string, 
+/**
+ * Element selector of the drop target.
+ */
+target: string, } | { "op": "screenshot", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * Image format (default: png).
+ */
+format: ImageFormat, } | { "op": "highlight", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * How long the box stays up, in milliseconds (default: 3000, max: 60000).
+ */
+duration_ms: number, } | { "op": "select", 
+/**
+ * Element selector (container or item directly).
+ */
+selector: string, 
+/**
+ * Item name to select within container (optional).
+ */
+item?: string, } | { "op": "toggle", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * Target state: true=on, false=off, None=toggle.
+ */
+state?: boolean, } | { "op": "expand", 
+/**
+ * Element selector.
+ */
+selector: string, } | { "op": "collapse", 
+/**
+ * Element selector.
+ */
+selector: string, } | { "op": "context_menu", 
+/**
+ * Element selector.
+ */
+selector: string, } | { "op": "fill", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * Text to fill.
+ */
+text: string, } | { "op": "type", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * Text to type.
+ */
+text: string, 
+/**
+ * Clear the element first.
+ */
+clear: boolean, } | { "op": "clear", 
+/**
+ * Element selector.
+ */
+selector: string, } | { "op": "scroll", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * Scroll direction.
+ */
+direction?: AutomationScrollDirection, 
+/**
+ * Scroll amount.
+ */
+amount?: number, 
+/**
+ * Child element to scroll into view.
+ */
+to_child?: string, } | { "op": "window", 
+/**
+ * Window action to perform.
+ */
+action: WindowAction, 
+/**
+ * Window selector (optional, uses foreground window if not specified).
+ */
+selector?: string, } | { "op": "run", 
+/**
+ * Command to run.
+ */
+command: string, 
+/**
+ * Command arguments.
+ */
+args: Array<string>, 
+/**
+ * Wait for command to complete.
+ */
+wait: boolean, 
+/**
+ * Run with hidden window.
+ */
+hidden: boolean, 
+/**
+ * Timeout in milliseconds when waiting.
+ */
+timeout_ms: number, } | { "op": "exec_script", 
+/**
+ * Script source.
+ */
+script: string, 
+/**
+ * Timeout in milliseconds; the script is killed when it runs out.
+ */
+timeout_ms: number, } | { "op": "wait_for", 
+/**
+ * Element selector.
+ */
+selector: string, 
+/**
+ * Timeout in milliseconds.
+ */
+timeout_ms: number, 
+/**
+ * State to wait for.
+ */
+state: WaitState, 
+/**
+ * Time between checks in milliseconds.
+ */
+poll_interval_ms: number, } | { "op": "status" }) | { "type": "locate" } & LocateRequest | { "type": "locate_click" } & LocateClickRequest | { "type": "wait_for_change", 
+/**
+ * Longest time to wait in milliseconds (default: 10000, max: 60000).
+ */
+timeout_ms: number, 
+/**
+ * Only watch this region of the desktop (default: full screen).
+ */
+region?: ElementBounds, } | { "type": "cursor_info", 
+/**
+ * Include the pointer image as a PNG.
+ */
+include_image: boolean, } | { "type": "session_info" } | { "type": "ping" } | { "type": "hello", 
+/**
+ * Framing to switch to.
+ */
+framing: IpcFraming, } | { "type": "shutdown" });
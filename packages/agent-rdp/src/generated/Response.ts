@@ -6,6 +6,10 @@ import type { ResponseData } from "./ResponseData.js";
  * A response from the daemon to the CLI.
  */
 export type Response = { 
+/**
+ * ID of the request this answers, if it had one.
+ */
+id?: number, 
 /**
  * Whether the operation succeeded.
  */
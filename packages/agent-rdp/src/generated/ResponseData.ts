@@ -2,12 +2,26 @@
 import type { AccessibilitySnapshot } from "./AccessibilitySnapshot.js";
 import type { AutomationStatus } from "./AutomationStatus.js";
 import type { ClickResult } from "./ClickResult.js";
+import type { ClipboardHistoryEntry } from "./ClipboardHistoryEntry.js";
+import type { CursorInfo } from "./CursorInfo.js";
+import type { DragResult } from "./DragResult.js";
+import type { DryRunResult } from "./DryRunResult.js";
+import type { ElementScreenshot } from "./ElementScreenshot.js";
 import type { ElementValue } from "./ElementValue.js";
+import type { FormattedSnapshot } from "./FormattedSnapshot.js";
+import type { FrameEncoding } from "./FrameEncoding.js";
+import type { HighlightResult } from "./HighlightResult.js";
+import type { InputCheck } from "./InputCheck.js";
+import type { IpcFraming } from "./IpcFraming.js";
+import type { KilledSession } from "./KilledSession.js";
 import type { LocateResult } from "./LocateResult.js";
 import type { MappedDrive } from "./MappedDrive.js";
+import type { OcrMatch } from "./OcrMatch.js";
 import type { RunResult } from "./RunResult.js";
 import type { SessionInfo } from "./SessionInfo.js";
 import type { SessionSummary } from "./SessionSummary.js";
+import type { TypeResult } from "./TypeResult.js";
+import type { WaitForResult } from "./WaitForResult.js";
 import type { WindowInfo } from "./WindowInfo.js";
 
 /**
@@ -25,7 +39,36 @@ width: number,
 /**
  * Desktop height.
  */
-height: number, } | { "type": "screenshot", 
+height: number, 
+/**
+ * Result of the input self-check, when one was requested.
+ */
+input_check?: InputCheck, 
+/**
+ * Whether the first graphics update arrived, when `wait_first_frame`
+ * was requested. Until it does, the desktop image is black.
+ */
+first_frame_received?: boolean, } | { "type": "resized", 
+/**
+ * New desktop width.
+ */
+width: number, 
+/**
+ * New desktop height.
+ */
+height: number, } | { "type": "stream_config", 
+/**
+ * Frames per second.
+ */
+fps: number, 
+/**
+ * JPEG quality (1-100).
+ */
+quality: number, 
+/**
+ * Whether frames are delta-encoded.
+ */
+delta: boolean, } | { "type": "screenshot", 
 /**
  * Image width.
  */
@@ -38,23 +81,176 @@ height: number,
  * Image format.
  */
 format: string, 
+/**
+ * Encoded image size in bytes.
+ */
+size_bytes: number, 
 /**
  * Base64-encoded image data.
  */
-base64: string, } | { "type": "clipboard", 
+base64: string, 
+/**
+ * Milliseconds waited for the desktop to settle (when `stable_ms` was given).
+ */
+stable_wait_ms?: number, 
+/**
+ * Whether the desktop settled before the timeout (when `stable_ms` was given).
+ */
+stable?: boolean, 
+/**
+ * Hash of the captured pixels and format, to pass as `if_changed_since`.
+ */
+etag: string, 
+/**
+ * Whether the server has sent any graphics yet. When false the image
+ * is the blank desktop from before the first update, not the screen.
+ */
+first_frame_received: boolean, } | { "type": "not_modified", 
+/**
+ * `etag` of the capture (the same as the one given).
+ */
+etag: string, 
+/**
+ * Image width.
+ */
+width: number, 
+/**
+ * Image height.
+ */
+height: number, } | { "type": "frame", 
+/**
+ * Width in pixels.
+ */
+width: number, 
+/**
+ * Height in pixels.
+ */
+height: number, 
+/**
+ * Bytes per row of raw pixels (0 for PNG).
+ */
+stride: number, 
+/**
+ * Pixel encoding.
+ */
+encoding: FrameEncoding, 
+/**
+ * Size of the pixel data in bytes.
+ */
+size_bytes: number, 
+/**
+ * Base64-encoded pixel data, unless sent as binary.
+ */
+data?: string, 
+/**
+ * Whether `size_bytes` raw bytes follow the response line.
+ */
+binary: boolean, } | { "type": "clipboard", 
 /**
  * Text content.
  */
-text: string, } | { "type": "session_info" } & SessionInfo | { "type": "drive_list", 
+text: string, } | { "type": "clipboard_image", 
+/**
+ * Image width.
+ */
+width: number, 
+/**
+ * Image height.
+ */
+height: number, 
+/**
+ * Base64-encoded PNG data.
+ */
+base64: string, } | { "type": "clipboard_html", 
+/**
+ * HTML fragment that was copied.
+ */
+html: string, } | { "type": "clipboard_files", 
+/**
+ * Saved local paths (get-files) or the names offered to the remote (set-files).
+ */
+files: Array<string>, 
+/**
+ * Total bytes of file data.
+ */
+total_bytes: number, } | { "type": "clipboard_history", 
+/**
+ * Entries, newest first.
+ */
+entries: Array<ClipboardHistoryEntry>, } | { "type": "clipboard_status", 
+/**
+ * Text is available (`clipboard get`).
+ */
+text: boolean, 
+/**
+ * A bitmap is available (`clipboard get-image`).
+ */
+image: boolean, 
+/**
+ * HTML is available (`clipboard get-html`).
+ */
+html: boolean, 
+/**
+ * Files are available (`clipboard get-files`).
+ */
+files: boolean, } | { "type": "session_info" } & SessionInfo | { "type": "dry_run" } & DryRunResult | { "type": "cert_fingerprint", 
+/**
+ * SHA-256 fingerprint of the certificate, as hex.
+ */
+sha256: string, } | { "type": "drive_list", 
 /**
  * Mapped drives.
  */
-drives: Array<MappedDrive>, } | { "type": "session_list", 
+drives: Array<MappedDrive>, } | { "type": "drive_copy", 
+/**
+ * Local path the copy was written to.
+ */
+destination: string, 
+/**
+ * Number of files copied.
+ */
+files: number, 
+/**
+ * Total bytes copied.
+ */
+total_bytes: number, } | { "type": "session_list", 
 /**
  * Active sessions.
  */
-sessions: Array<SessionSummary>, } | { "type": "pong" } | { "type": "snapshot" } & AccessibilitySnapshot | { "type": "element" } & ElementValue | { "type": "window_list", 
+sessions: Array<SessionSummary>, } | { "type": "session_kill", 
+/**
+ * What happened to each session.
+ */
+sessions: Array<KilledSession>, } | { "type": "pong", 
+/**
+ * IPC protocol version the daemon speaks (0 for daemons that predate it).
+ */
+protocol_version: number, 
+/**
+ * Daemon release version.
+ */
+version?: string, } | { "type": "hello", framing: IpcFraming, } | { "type": "snapshot" } & AccessibilitySnapshot | { "type": "formatted_snapshot" } & FormattedSnapshot | { "type": "element" } & ElementValue | { "type": "window_list", 
 /**
  * List of windows.
  */
-windows: Array<WindowInfo>, } | { "type": "automation_status" } & AutomationStatus | { "type": "run_result" } & RunResult | { "type": "click_result" } & ClickResult | { "type": "locate_result" } & LocateResult;
+windows: Array<WindowInfo>, } | { "type": "automation_status" } & AutomationStatus | { "type": "run_result" } & RunResult | { "type": "click_result" } & ClickResult | { "type": "drag_result" } & DragResult | { "type": "highlight_result" } & HighlightResult | { "type": "element_screenshot" } & ElementScreenshot | { "type": "type_result" } & TypeResult | { "type": "wait_for_result" } & WaitForResult | { "type": "locate_result" } & LocateResult | { "type": "locate_clicked", 
+/**
+ * The line that was clicked.
+ */
+matched: OcrMatch, 
+/**
+ * Where the click landed.
+ */
+x: number, y: number, 
+/**
+ * How many lines matched (at or above the confidence threshold).
+ */
+match_count: number, } | { "type": "screen_change", 
+/**
+ * Whether the watched pixels changed before the timeout.
+ */
+changed: boolean, 
+/**
+ * Time spent waiting in milliseconds.
+ */
+elapsed_ms: number, } | { "type": "cursor_info" } & CursorInfo;
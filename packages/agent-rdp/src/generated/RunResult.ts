@@ -19,4 +19,8 @@ stderr?: string,
 /**
  * Process ID (if not waited).
  */
-pid?: number, };
+pid?: number, 
+/**
+ * Whether stdout or stderr was cut off at the agent's output limit.
+ */
+truncated: boolean, };
@@ -8,4 +8,44 @@ export type ScreenshotRequest = {
 /**
  * Image format.
  */
-format: ImageFormat, };
+format: ImageFormat, 
+/**
+ * Left edge of the region to capture (default: 0).
+ */
+x?: number, 
+/**
+ * Top edge of the region to capture (default: 0).
+ */
+y?: number, 
+/**
+ * Width of the region to capture (default: to the right edge of the desktop).
+ */
+width?: number, 
+/**
+ * Height of the region to capture (default: to the bottom edge of the desktop).
+ */
+height?: number, 
+/**
+ * Capture only this monitor (index into the session's monitors); the region is
+ * then relative to the monitor. Default: the whole desktop (all monitors).
+ */
+monitor?: number, 
+/**
+ * Draw the mouse pointer onto the image at its current position.
+ */
+draw_cursor: boolean, 
+/**
+ * Before capturing, wait until the desktop hasn't changed for this many
+ * milliseconds, so animations have finished (default: capture immediately).
+ */
+stable_ms?: number, 
+/**
+ * Longest to wait for the desktop to settle before capturing anyway
+ * (default: 10000, max: 60000).
+ */
+stable_timeout_ms?: number, 
+/**
+ * `etag` of an earlier screenshot. If the capture is identical, nothing is
+ * encoded and `not_modified` is returned instead.
+ */
+if_changed_since?: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Security protocol negotiated with the RDP server.
+ */
+export type SecurityMode = "auto" | "nla" | "tls_only";
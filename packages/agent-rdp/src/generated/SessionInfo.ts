@@ -1,5 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { ConnectionState } from "./ConnectionState.js";
+import type { DisconnectInfo } from "./DisconnectInfo.js";
+import type { MonitorLayout } from "./MonitorLayout.js";
 
 /**
  * Session information.
@@ -25,6 +27,55 @@ width?: number,
  * Desktop height (if connected).
  */
 height?: number, 
+/**
+ * Keyboard layout sent to the server (if connected).
+ */
+keyboard_layout?: number, 
+/**
+ * SHA-256 fingerprint of the server certificate (if connected).
+ */
+cert_fingerprint?: string, 
+/**
+ * Monitors of the desktop, in screenshot/mouse coordinates (if connected).
+ */
+monitors: Array<MonitorLayout>, 
+/**
+ * Why the RDP connection last ended (if it has).
+ */
+last_disconnect?: DisconnectInfo, 
+/**
+ * How long the latest connection handshake took, in milliseconds (if connected).
+ */
+connect_ms?: number, 
+/**
+ * Rolling average round trip from input to the next server frame, in
+ * milliseconds (once measured).
+ */
+rtt_ms?: number, 
+/**
+ * Most recent round trip sample, in milliseconds (once measured).
+ */
+last_rtt_ms?: number, 
+/**
+ * Frames received from the server (if connected).
+ */
+frames_received?: number, 
+/**
+ * Bytes received from the server (if connected).
+ */
+bytes_received?: number, 
+/**
+ * WebSocket streaming port (if streaming is enabled).
+ */
+stream_port?: number, 
+/**
+ * Token viewers must present to the streaming server (if streaming is enabled).
+ */
+stream_token?: string, 
+/**
+ * Whether the streaming port also serves the HTML viewer (not just WebSocket clients).
+ */
+stream_viewer: boolean, 
 /**
  * Daemon process ID.
  */
@@ -16,4 +16,20 @@ state: ConnectionState,
 /**
  * Connected host (if any).
  */
-host?: string, };
+host?: string, 
+/**
+ * Daemon process ID (if the session has a readable PID file).
+ */
+pid?: number, 
+/**
+ * Whether the daemon process is running (false for stale session directories).
+ */
+alive: boolean, 
+/**
+ * Time since daemon started (seconds, if it answered the probe).
+ */
+uptime_secs?: number, 
+/**
+ * Whether the stale session directory was removed.
+ */
+pruned: boolean, };
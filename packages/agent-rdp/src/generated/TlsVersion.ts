@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * TLS protocol version.
+ */
+export type TlsVersion = "1.0" | "1.1" | "1.2" | "1.3";
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Type action result.
+ */
+export type TypeResult = { 
+/**
+ * Whether the text was entered.
+ */
+typed: boolean, 
+/**
+ * Method used (value_pattern or sendkeys).
+ */
+method: string, 
+/**
+ * The element's value after typing, if it exposes one.
+ */
+value?: string, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WaitState } from "./WaitState.js";
+
+/**
+ * Result of waiting for an element.
+ */
+export type WaitForResult = { 
+/**
+ * State that was reached.
+ */
+state: WaitState, 
+/**
+ * Time spent waiting in milliseconds.
+ */
+elapsed_ms: number, 
+/**
+ * Whether the element exists at the end of the wait.
+ */
+exists: boolean, 
+/**
+ * Whether the element is on screen at the end of the wait.
+ */
+visible: boolean, 
+/**
+ * Whether the element is enabled at the end of the wait.
+ */
+enabled: boolean, };
@@ -3,4 +3,4 @@
 /**
  * State to wait for in WaitFor command.
  */
-export type WaitState = "visible" | "enabled" | "gone";
+export type WaitState = "visible" | "enabled" | "gone" | "exists";
@@ -3,4 +3,4 @@
 /**
  * Window action for automation.
  */
-export type WindowAction = "list" | "focus" | "maximize" | "minimize" | "restore" | "close";
+export type WindowAction = "list" | "focus" | "raise" | "maximize" | "minimize" | "restore" | "close";
@@ -7,35 +7,67 @@ export type { AutomationHandshake } from './AutomationHandshake.js';
 export type { AutomationScrollDirection } from './AutomationScrollDirection.js';
 export type { AutomationStatus } from './AutomationStatus.js';
 export type { ClickResult } from './ClickResult.js';
+export type { ClipboardHistoryEntry } from './ClipboardHistoryEntry.js';
 export type { ClipboardRequest } from './ClipboardRequest.js';
 export type { ConnectRequest } from './ConnectRequest.js';
 export type { ConnectionState } from './ConnectionState.js';
+export type { CursorInfo } from './CursorInfo.js';
+export type { DisconnectInfo } from './DisconnectInfo.js';
+export type { DisconnectReason } from './DisconnectReason.js';
+export type { DragResult } from './DragResult.js';
 export type { DriveMapping } from './DriveMapping.js';
 export type { DriveRequest } from './DriveRequest.js';
+export type { DryRunResult } from './DryRunResult.js';
 export type { ElementBounds } from './ElementBounds.js';
+export type { ElementScreenshot } from './ElementScreenshot.js';
 export type { ElementValue } from './ElementValue.js';
 export type { ErrorCode } from './ErrorCode.js';
 export type { ErrorInfo } from './ErrorInfo.js';
 export type { FileIpcError } from './FileIpcError.js';
 export type { FileIpcRequest } from './FileIpcRequest.js';
 export type { FileIpcResponse } from './FileIpcResponse.js';
+export type { FormattedSnapshot } from './FormattedSnapshot.js';
+export type { FrameEncoding } from './FrameEncoding.js';
+export type { FrameRequest } from './FrameRequest.js';
+export type { GatewayRequest } from './GatewayRequest.js';
+export type { GraphicsCodec } from './GraphicsCodec.js';
+export type { HighlightResult } from './HighlightResult.js';
 export type { ImageFormat } from './ImageFormat.js';
+export type { InputBatchRequest } from './InputBatchRequest.js';
+export type { InputCheck } from './InputCheck.js';
+export type { InputCheckStatus } from './InputCheckStatus.js';
+export type { InputEvent } from './InputEvent.js';
+export type { IpcFraming } from './IpcFraming.js';
 export type { KeyboardRequest } from './KeyboardRequest.js';
+export type { KillOutcome } from './KillOutcome.js';
+export type { KilledSession } from './KilledSession.js';
+export type { LineEndings } from './LineEndings.js';
+export type { LocateClickRequest } from './LocateClickRequest.js';
 export type { LocateRequest } from './LocateRequest.js';
 export type { LocateResult } from './LocateResult.js';
 export type { MappedDrive } from './MappedDrive.js';
+export type { MonitorLayout } from './MonitorLayout.js';
 export type { MouseButton } from './MouseButton.js';
 export type { MouseRequest } from './MouseRequest.js';
 export type { OcrMatch } from './OcrMatch.js';
+export type { PerformanceFlag } from './PerformanceFlag.js';
+export type { PerformanceProfile } from './PerformanceProfile.js';
 export type { Request } from './Request.js';
+export type { RequestEnvelope } from './RequestEnvelope.js';
 export type { Response } from './Response.js';
 export type { ResponseData } from './ResponseData.js';
 export type { RunResult } from './RunResult.js';
 export type { ScreenshotRequest } from './ScreenshotRequest.js';
 export type { ScrollDirection } from './ScrollDirection.js';
 export type { ScrollRequest } from './ScrollRequest.js';
+export type { SecurityMode } from './SecurityMode.js';
 export type { SessionInfo } from './SessionInfo.js';
 export type { SessionSummary } from './SessionSummary.js';
+export type { SnapshotFormat } from './SnapshotFormat.js';
+export type { TlsVersion } from './TlsVersion.js';
+export type { TypeMode } from './TypeMode.js';
+export type { TypeResult } from './TypeResult.js';
+export type { WaitForResult } from './WaitForResult.js';
 export type { WaitState } from './WaitState.js';
 export type { WindowAction } from './WindowAction.js';
 export type { WindowInfo } from './WindowInfo.js';
@@ -28,18 +28,53 @@ import { AutomationController } from './automation.js';
 import {
   ConnectOptions,
   ConnectResult,
+  DisconnectOptions,
   ScreenshotOptions,
   ScreenshotResult,
+  FrameOptions,
+  FrameResult,
+  StreamConfigOptions,
+  StreamConfig,
   SessionInfo,
   MappedDrive,
+  DriveOptions,
+  DrivePushOptions,
+  DrivePullOptions,
+  DriveCopy,
   MouseClickOptions,
+  MouseMoveOptions,
+  MouseButtonOptions,
   MouseDragOptions,
+  Point,
   ScrollOptions,
   KeyboardTypeOptions,
   KeyboardPressOptions,
+  KeyboardRawOptions,
+  KeyboardPasteOptions,
+  ClipboardGetOptions,
   ClipboardSetOptions,
+  ClipboardSetImageOptions,
+  ClipboardSetHtmlOptions,
+  ClipboardImage,
+  ClipboardGetFilesOptions,
+  ClipboardSetFilesOptions,
+  ClipboardFiles,
+  ClipboardHistoryOptions,
+  ClipboardHistoryItem,
+  ClipboardHistoryEntry,
+  ClipboardStatus,
+  InputEvent,
+  InputCheck,
   LocateOptions,
+  LocateClickOptions,
+  LocateClickResult,
   OcrMatch,
+  WaitForChangeOptions,
+  ScreenChange,
+  CursorInfoOptions,
+  CursorState,
+  CursorInfo,
+  FrameEncoding,
   Request,
   Response,
   RdpError,
@@ -56,6 +91,8 @@ export interface RdpSessionOptions {
   timeout?: number;
   /** WebSocket streaming port (0 = disabled). Connect to ws://localhost:<port> for frames. */
   streamPort?: number;
+  /** How long automation requests wait for each answer from the agent in milliseconds (default: 10000) */
+  agentTimeout?: number;
 }
 
 /**
@@ -65,36 +102,73 @@ export class MouseController {
   constructor(private rdp: RdpSession) {}
 
   /** Move cursor to position. */
-  async move(options: MouseClickOptions): Promise<void> {
-    await this.rdp._send({ type: 'mouse', action: 'move', x: options.x, y: options.y });
+  async move(options: MouseMoveOptions): Promise<void> {
+    const { x, y } = await this.toDesktop(options, options.monitor);
+    await this.rdp._send({ type: 'mouse', action: 'move', x, y, human: options.human ?? false });
   }
 
   /** Left click at position. */
-  async click(options: MouseClickOptions): Promise<void> {
-    await this.rdp._send({ type: 'mouse', action: 'click', x: options.x, y: options.y });
+  async click(options: MouseMoveOptions): Promise<void> {
+    const { x, y } = await this.toDesktop(options, options.monitor);
+    await this.rdp._send({ type: 'mouse', action: 'click', x, y, human: options.human ?? false });
   }
 
   /** Right click at position. */
   async rightClick(options: MouseClickOptions): Promise<void> {
-    await this.rdp._send({ type: 'mouse', action: 'right_click', x: options.x, y: options.y });
+    const { x, y } = await this.toDesktop(options, options.monitor);
+    await this.rdp._send({ type: 'mouse', action: 'right_click', x, y });
   }
 
   /** Double click at position. */
   async doubleClick(options: MouseClickOptions): Promise<void> {
-    await this.rdp._send({ type: 'mouse', action: 'double_click', x: options.x, y: options.y });
+    const { x, y } = await this.toDesktop(options, options.monitor);
+    await this.rdp._send({ type: 'mouse', action: 'double_click', x, y });
+  }
+
+  /** Press a button at position and keep it held, until `up` or `releaseAll`. */
+  async down(options: MouseButtonOptions): Promise<void> {
+    const { x, y } = await this.toDesktop(options, options.monitor);
+    await this.rdp._send({ type: 'mouse', action: 'down', x, y, button: options.button ?? 'left' });
+  }
+
+  /** Release a held button at position. */
+  async up(options: MouseButtonOptions): Promise<void> {
+    const { x, y } = await this.toDesktop(options, options.monitor);
+    await this.rdp._send({ type: 'mouse', action: 'up', x, y, button: options.button ?? 'left' });
+  }
+
+  /** Release every mouse button still held down. */
+  async releaseAll(): Promise<void> {
+    await this.rdp._send({ type: 'mouse', action: 'release_all' });
   }
 
   /** Drag from one position to another. */
   async drag(options: MouseDragOptions): Promise<void> {
+    const from = await this.toDesktop(options.from, options.monitor);
+    const to = await this.toDesktop(options.to, options.monitor);
     await this.rdp._send({
       type: 'mouse',
       action: 'drag',
-      from_x: options.from.x,
-      from_y: options.from.y,
-      to_x: options.to.x,
-      to_y: options.to.y,
+      from_x: from.x,
+      from_y: from.y,
+      to_x: to.x,
+      to_y: to.y,
+      steps: options.steps,
     });
   }
+
+  /** Translate monitor-relative coordinates to the virtual desktop. */
+  private async toDesktop(point: Point, monitor?: number): Promise<Point> {
+    if (monitor === undefined) {
+      return { x: point.x, y: point.y };
+    }
+    const { monitors } = await this.rdp.getInfo();
+    const layout = monitors[monitor];
+    if (!layout) {
+      throw new RdpError('invalid_request', `Monitor ${monitor} does not exist (session has ${monitors.length} monitors)`);
+    }
+    return { x: layout.x + point.x, y: layout.y + point.y };
+  }
 }
 
 /**
@@ -103,15 +177,60 @@ export class MouseController {
 export class KeyboardController {
   constructor(private rdp: RdpSession) {}
 
-  /** Type a text string (Unicode). */
+  /** Type a text string (as Unicode, or as scancodes with `mode: 'scancode'`). */
   async type(options: KeyboardTypeOptions): Promise<void> {
-    await this.rdp._send({ type: 'keyboard', action: 'type', text: options.text });
+    await this.rdp._send({
+      type: 'keyboard',
+      action: 'type',
+      text: options.text,
+      mode: options.mode ?? 'unicode',
+      delay_ms: options.delayMs,
+    });
   }
 
   /** Press a key combination (e.g., 'ctrl+c', 'alt+tab') or single key (e.g., 'enter'). */
   async press(options: KeyboardPressOptions): Promise<void> {
     await this.rdp._send({ type: 'keyboard', action: 'press', keys: options.keys });
   }
+
+  /** Send one scancode press (or release with `release: true`) as is. */
+  async raw(options: KeyboardRawOptions): Promise<void> {
+    await this.rdp._send({
+      type: 'keyboard',
+      action: 'raw',
+      scancode: options.scancode,
+      extended: options.extended ?? false,
+      release: options.release ?? false,
+    });
+  }
+
+  /** Press keys (e.g., 'shift', 'ctrl+alt') and keep them down until released. */
+  async hold(options: KeyboardPressOptions): Promise<void> {
+    await this.rdp._send({ type: 'keyboard', action: 'hold', keys: options.keys });
+  }
+
+  /** Release keys pressed with `hold`. */
+  async release(options: KeyboardPressOptions): Promise<void> {
+    await this.rdp._send({ type: 'keyboard', action: 'release', keys: options.keys });
+  }
+
+  /** Release every key still held down (also done on disconnect). */
+  async releaseAll(): Promise<void> {
+    await this.rdp._send({ type: 'keyboard', action: 'release_all' });
+  }
+
+  /**
+   * Paste text through the remote clipboard with Ctrl+V, much faster than `type`
+   * for long text. The focused window must accept Ctrl+V.
+   */
+  async paste(options: KeyboardPasteOptions): Promise<void> {
+    await this.rdp._send({
+      type: 'keyboard',
+      action: 'paste',
+      text: options.text,
+      restore: options.restore ?? false,
+    });
+  }
 }
 
 /**
@@ -148,15 +267,104 @@ export class ClipboardController {
   constructor(private rdp: RdpSession) {}
 
   /** Get clipboard text. */
-  async get(): Promise<string> {
-    const response = await this.rdp._send({ type: 'clipboard', action: 'get' });
+  async get(options: ClipboardGetOptions = {}): Promise<string> {
+    const response = await this.rdp._send({
+      type: 'clipboard',
+      action: 'get',
+      line_endings: options.lineEndings,
+    });
     const data = response.data as { type: 'clipboard'; text: string };
     return data.text;
   }
 
   /** Set clipboard text. */
   async set(options: ClipboardSetOptions): Promise<void> {
-    await this.rdp._send({ type: 'clipboard', action: 'set', text: options.text });
+    await this.rdp._send({
+      type: 'clipboard',
+      action: 'set',
+      text: options.text,
+      line_endings: options.lineEndings,
+    });
+  }
+
+  /** Get clipboard image as PNG. */
+  async getImage(): Promise<ClipboardImage> {
+    const response = await this.rdp._send({ type: 'clipboard', action: 'get_image' });
+    const data = response.data as {
+      type: 'clipboard_image';
+      width: number;
+      height: number;
+      base64: string;
+    };
+    return { base64: data.base64, width: data.width, height: data.height };
+  }
+
+  /** Set clipboard image from PNG data. */
+  async setImage(options: ClipboardSetImageOptions): Promise<void> {
+    await this.rdp._send({ type: 'clipboard', action: 'set_image', base64: options.base64 });
+  }
+
+  /** Get the clipboard HTML fragment. */
+  async getHtml(): Promise<string> {
+    const response = await this.rdp._send({ type: 'clipboard', action: 'get_html' });
+    const data = response.data as { type: 'clipboard_html'; html: string };
+    return data.html;
+  }
+
+  /** Set clipboard HTML for rich text paste (also offered as plain text). */
+  async setHtml(options: ClipboardSetHtmlOptions): Promise<void> {
+    await this.rdp._send({
+      type: 'clipboard',
+      action: 'set_html',
+      html: options.html,
+      text: options.text,
+    });
+  }
+
+  /** Save files copied on the remote machine into a local directory. */
+  async getFiles(options: ClipboardGetFilesOptions): Promise<ClipboardFiles> {
+    const response = await this.rdp._send({
+      type: 'clipboard',
+      action: 'get_files',
+      dest_dir: options.destDir,
+      max_bytes: options.maxBytes,
+    });
+    const data = response.data as { type: 'clipboard_files'; files: string[]; total_bytes: number };
+    return { files: data.files, totalBytes: data.total_bytes };
+  }
+
+  /** Copy local files or directories so they can be pasted on the remote machine. */
+  async setFiles(options: ClipboardSetFilesOptions): Promise<ClipboardFiles> {
+    const response = await this.rdp._send({
+      type: 'clipboard',
+      action: 'set_files',
+      paths: options.paths,
+      max_bytes: options.maxBytes,
+    });
+    const data = response.data as { type: 'clipboard_files'; files: string[]; total_bytes: number };
+    return { files: data.files, totalBytes: data.total_bytes };
+  }
+
+  /**
+   * Text from recent remote copies, newest first. Only copies whose text was
+   * fetched (by get() or a paste) are recorded.
+   */
+  async history(options: ClipboardHistoryOptions = {}): Promise<ClipboardHistoryItem[]> {
+    const response = await this.rdp._send({ type: 'clipboard', action: 'history', count: options.count });
+    const data = response.data as { type: 'clipboard_history'; entries: ClipboardHistoryEntry[] };
+    return data.entries.map((entry) => ({ text: entry.text, copiedAtMs: entry.copied_at_ms }));
+  }
+
+  /** Empty the clipboard on both sides. */
+  async clear(): Promise<void> {
+    await this.rdp._send({ type: 'clipboard', action: 'clear' });
+  }
+
+  /** Check what kinds of content the remote clipboard has, without fetching it. */
+  async status(): Promise<ClipboardStatus> {
+    const response = await this.rdp._send({ type: 'clipboard', action: 'status' });
+    const data = response.data as { type: 'clipboard_status' } & ClipboardStatus;
+    return { text: data.text, image: data.image, html: data.html, files: data.files };
   }
 }
 
@@ -172,6 +380,47 @@ export class DriveController {
     const data = response.data as { type: 'drive_list'; drives: MappedDrive[] };
     return data.drives;
   }
+
+  /**
+   * Map a local directory as a drive without reconnecting. Needs drive
+   * redirection, enabled by mapping at least one drive at connect time.
+   * Returns the drives mapped afterwards.
+   */
+  async mount(drive: DriveOptions): Promise<MappedDrive[]> {
+    const response = await this.rdp._send({
+      type: 'drive',
+      action: 'mount',
+      path: drive.path,
+      name: drive.name,
+      read_only: drive.readOnly ?? false,
+    });
+    const data = response.data as { type: 'drive_list'; drives: MappedDrive[] };
+    return data.drives;
+  }
+
+  /** Remove a mapped drive without reconnecting. Returns the drives mapped afterwards. */
+  async unmount(name: string): Promise<MappedDrive[]> {
+    const response = await this.rdp._send({ type: 'drive', action: 'unmount', name });
+    const data = response.data as { type: 'drive_list'; drives: MappedDrive[] };
+    return data.drives;
+  }
+
+  /** Copy a local file or directory into a mapped drive, where the remote sees it. */
+  async push(options: DrivePushOptions): Promise<DriveCopy> {
+    const response = await this.rdp._send({ type: 'drive', action: 'push', local: options.local, remote: options.remote });
+    return toDriveCopy(response.data);
+  }
+
+  /** Copy a file or directory out of a mapped drive. */
+  async pull(options: DrivePullOptions): Promise<DriveCopy> {
+    const response = await this.rdp._send({ type: 'drive', action: 'pull', remote: options.remote, local: options.local });
+    return toDriveCopy(response.data);
+  }
+}
+
+function toDriveCopy(data: Response['data']): DriveCopy {
+  const copy = data as { type: 'drive_copy'; destination: string; files: number; total_bytes: number };
+  return { destination: copy.destination, files: copy.files, totalBytes: copy.total_bytes };
 }
 
 /**
@@ -208,7 +457,7 @@ export class RdpSession {
     this.scroll = new ScrollController(this);
     this.clipboard = new ClipboardController(this);
     this.drives = new DriveController(this);
-    this.automation = new AutomationController(this);
+    this.automation = new AutomationController(this, options.agentTimeout);
   }
 
   /**
@@ -223,7 +472,40 @@ export class RdpSession {
    * @param options.width Desktop width (default: 1280)
    * @param options.height Desktop height (default: 800)
    * @param options.drives Drives to map
+   * @param options.monitors Monitor layout (overrides width/height)
+   * @param options.logicalSize Logical [width, height] for mouse and scroll coordinates
    * @param options.enableWinAutomation Enable Windows UI Automation
+   * @param options.testInput Check that keyboard input reaches the remote desktop
+   * @param options.waitFirstFrame Wait (up to 10s) for the server to draw the desktop
+   * @param options.keyboardLayout Keyboard layout LCID (default: 0x409, US English)
+   * @param options.pinCert Expected SHA-256 fingerprint of the server certificate
+   * @param options.autoReconnect Reconnect automatically if the connection drops
+   * @param options.maxReconnectAttempts Maximum reconnection attempts (default: 5)
+   * @param options.keepaliveIntervalSecs Seconds of server silence before a keepalive (default: off)
+   * @param options.maxFrameSize Largest width or height of streamed frames (default: desktop size)
+   * @param options.connectRetries Retries of the initial connect while the server isn't accepting connections (default: 0)
+   * @param options.retryDelayMs Delay before the first connect retry, doubling after each (default: 1000)
+   * @param options.connectTimeoutMs Time each connect attempt gets for the handshake (default: 20000)
+   * @param options.security Security protocol: 'auto', 'nla' or 'tls_only' (default: 'auto')
+   * @param options.minTls Oldest TLS version to accept (default: '1.2')
+   * @param options.codec Graphics codec: 'bitmap' or 'remotefx' (default: 'bitmap')
+   * @param options.perfProfile Visual effects: 'full', 'lan', 'broadband' or 'modem' (default: 'lan')
+   * @param options.perfFlags Exact performance flags, replacing those of the profile
+   * @param options.gateway RD Gateway to tunnel the connection through
+   * @param options.proxy SOCKS5 or HTTP proxy URL for the TCP connection
+   * @param options.kdc KDC for Kerberos authentication (default: NTLM)
+   * @param options.captureAudio Record remote audio to this WAV file
+   * @param options.record Record the desktop to this animated GIF
+   * @param options.recordFps Frames per second sampled for the recording (default: 2, max: 10)
+   * @param options.redirectSmartcard Redirect local smart card readers via PC/SC
+   * @param options.enableClipboard Attach the clipboard channel (default: true)
+   * @param options.enableAudio Attach the audio output channel (default: true)
+   * @param options.idleTimeoutSecs Shut the daemon down after this many idle seconds
+   * @param options.clientName Client computer name reported to the server (max 15 characters)
+   * @param options.clientBuild Client build number reported to the server
+   * @param options.digProductId Product ID reported to the server (max 31 characters)
+   * @param options.hardwareId Client hardware ID as 32 hex digits or a GUID
+   * @param options.clipboardHistory Number of remote clipboard texts to remember
    */
   async connect(options: ConnectOptions): Promise<ConnectResult> {
     // Ensure daemon is running and connect
@@ -238,39 +520,149 @@ export class RdpSession {
       domain: options.domain,
       width: options.width ?? 1280,
       height: options.height ?? 800,
-      drives: options.drives ?? [],
+      drives: (options.drives ?? []).map((d) => ({
+        path: d.path,
+        name: d.name,
+        read_only: d.readOnly ?? false,
+      })),
+      monitors: (options.monitors ?? []).map((m) => ({
+        x: m.x ?? 0,
+        y: m.y ?? 0,
+        width: m.width,
+        height: m.height,
+        primary: m.primary ?? false,
+      })),
+      coordinate_space: options.logicalSize,
       enable_win_automation: options.enableWinAutomation ?? false,
+      test_input: options.testInput ?? false,
+      wait_first_frame: options.waitFirstFrame ?? false,
       stream_port: 0,
       stream_fps: 10,
       stream_quality: 80,
       serve_viewer: false,
+      stream_max_frame_size: options.maxFrameSize,
+      keyboard_layout: options.keyboardLayout,
+      expected_cert_sha256: options.pinCert,
+      auto_reconnect: options.autoReconnect ?? false,
+      max_reconnect_attempts: options.maxReconnectAttempts ?? 5,
+      keepalive_interval_secs: options.keepaliveIntervalSecs,
+      connect_retries: options.connectRetries ?? 0,
+      retry_delay_ms: options.retryDelayMs ?? 1000,
+      connect_timeout_ms: options.connectTimeoutMs,
+      security: options.security ?? 'auto',
+      min_tls: options.minTls,
+      codec: options.codec ?? 'bitmap',
+      perf_profile: options.perfProfile ?? 'lan',
+      perf_flags: options.perfFlags,
+      gateway: options.gateway && {
+        host: options.gateway.host,
+        port: options.gateway.port ?? 443,
+        username: options.gateway.username,
+        password: options.gateway.password,
+        expected_cert_sha256: options.gateway.pinCert,
+        insecure: options.gateway.insecure ?? false,
+      },
+      proxy: options.proxy,
+      kdc: options.kdc,
+      capture_audio: options.captureAudio,
+      record: options.record,
+      record_fps: options.recordFps,
+      redirect_smartcard: options.redirectSmartcard ?? false,
+      enable_clipboard: options.enableClipboard ?? true,
+      enable_audio: options.enableAudio ?? true,
+      idle_timeout_secs: options.idleTimeoutSecs,
+      client_name: options.clientName,
+      client_build: options.clientBuild,
+      dig_product_id: options.digProductId,
+      hardware_id: options.hardwareId,
+      clipboard_history: options.clipboardHistory,
     };
 
     const response = await this._send(request);
-    const data = response.data as { type: 'connected'; host: string; width: number; height: number };
+    const data = response.data as {
+      type: 'connected';
+      host: string;
+      width: number;
+      height: number;
+      input_check?: InputCheck;
+      first_frame_received?: boolean;
+    };
 
     return {
       host: data.host,
       width: data.width,
       height: data.height,
+      inputCheck: data.input_check,
+      firstFrameReceived: data.first_frame_received,
     };
   }
 
+  /**
+   * Resize the remote desktop without reconnecting.
+   *
+   * Requires the server to support the display control channel (Windows 8.1 /
+   * Server 2012 R2 and later). Odd widths are rounded down to an even number.
+   *
+   * @returns The desktop size the server applied
+   */
+  async resize(width: number, height: number): Promise<{ width: number; height: number }> {
+    const response = await this._send({ type: 'resize', width, height });
+    const data = response.data as { type: 'resized'; width: number; height: number };
+    return { width: data.width, height: data.height };
+  }
+
+  /**
+   * Run a sequence of input events in order in a single round trip.
+   *
+   * @param options.delayMs - Pause after each event and between typed characters (default: 20)
+   */
+  async input(events: InputEvent[], options: { delayMs?: number } = {}): Promise<void> {
+    await this._send({ type: 'input_batch', events, delay_ms: options.delayMs });
+  }
+
   /**
    * Take a screenshot.
+   *
+   * With `waitStable`, the capture waits until the screen has stopped changing,
+   * e.g. for a window to finish animating open. Pass the previous result's
+   * `etag` as `ifChangedSince` when polling: an unchanged screen then comes back
+   * as `notModified` with no image, skipping the encode.
    */
   async screenshot(options: ScreenshotOptions = {}): Promise<ScreenshotResult> {
     const response = await this._send({
       type: 'screenshot',
       format: options.format ?? 'png',
+      ...options.region,
+      monitor: options.monitor,
+      draw_cursor: options.drawCursor ?? false,
+      stable_ms: options.waitStable,
+      stable_timeout_ms: options.stableTimeoutMs,
+      if_changed_since: options.ifChangedSince,
     });
 
+    if (response.data?.type === 'not_modified') {
+      return {
+        base64: '',
+        width: response.data.width,
+        height: response.data.height,
+        format: options.format ?? 'png',
+        sizeBytes: 0,
+        etag: response.data.etag,
+        notModified: true,
+      };
+    }
+
     const data = response.data as {
       type: 'screenshot';
       width: number;
       height: number;
       format: string;
+      size_bytes: number;
       base64: string;
+      stable_wait_ms?: number;
+      stable?: boolean;
+      etag: string;
+      first_frame_received: boolean;
     };
 
     return {
@@ -278,6 +670,46 @@ export class RdpSession {
       width: data.width,
       height: data.height,
       format: data.format,
+      sizeBytes: data.size_bytes,
+      stableWaitMs: data.stable_wait_ms,
+      stable: data.stable,
+      etag: data.etag,
+      notModified: false,
+      firstFrameReceived: data.first_frame_received,
+    };
+  }
+
+  /**
+   * Read the desktop framebuffer, for local consumers processing frames at a
+   * high rate.
+   *
+   * Raw frames are uncompressed RGBA, `stride * height` bytes (about 4 MB at
+   * 1280x800). The pixels are sent as binary after the response rather than
+   * base64-encoded.
+   */
+  async frame(options: FrameOptions = {}): Promise<FrameResult> {
+    const { response, payload } = await this._sendWithPayload({
+      type: 'frame',
+      encoding: options.encoding ?? 'raw',
+      binary: true,
+    });
+
+    const data = response.data as {
+      type: 'frame';
+      width: number;
+      height: number;
+      stride: number;
+      encoding: FrameEncoding;
+      size_bytes: number;
+    };
+
+    return {
+      data: payload ?? Buffer.alloc(0),
+      width: data.width,
+      height: data.height,
+      stride: data.stride,
+      encoding: data.encoding,
+      sizeBytes: data.size_bytes,
     };
   }
 
@@ -293,6 +725,18 @@ export class RdpSession {
       host?: string;
       width?: number;
       height?: number;
+      keyboard_layout?: number;
+      cert_fingerprint?: string;
+      last_disconnect?: SessionInfo['last_disconnect'];
+      monitors?: SessionInfo['monitors'];
+      connect_ms?: number;
+      rtt_ms?: number;
+      last_rtt_ms?: number;
+      frames_received?: number;
+      bytes_received?: number;
+      stream_port?: number;
+      stream_token?: string;
+      stream_viewer?: boolean;
       pid: number;
       uptime_secs: number;
     };
@@ -303,6 +747,18 @@ export class RdpSession {
       host: data.host,
       width: data.width,
       height: data.height,
+      keyboard_layout: data.keyboard_layout,
+      cert_fingerprint: data.cert_fingerprint,
+      last_disconnect: data.last_disconnect,
+      monitors: data.monitors ?? [],
+      connect_ms: data.connect_ms,
+      rtt_ms: data.rtt_ms,
+      last_rtt_ms: data.last_rtt_ms,
+      frames_received: data.frames_received,
+      bytes_received: data.bytes_received,
+      stream_port: data.stream_port,
+      stream_token: data.stream_token,
+      stream_viewer: data.stream_viewer ?? false,
       pid: data.pid,
       uptime_secs: data.uptime_secs,
     };
@@ -316,6 +772,9 @@ export class RdpSession {
    * @param options.all If true, returns all text on screen
    * @param options.pattern Use glob-style pattern matching (* and ?)
    * @param options.caseSensitive Case-sensitive matching (default: false)
+   * @param options.region Only search within this region (results use desktop coordinates)
+   * @param options.ocrModels Directory holding the OCR models (default: AGENT_RDP_OCR_MODELS, then the bundled models)
+   * @param options.lang Language to recognize (default: 'en'); throws 'language_not_available' if its model isn't installed
    * @returns Array of matching text lines with coordinates
    *
    * @example
@@ -340,17 +799,100 @@ export class RdpSession {
       pattern: options.pattern ?? false,
       ignore_case: !(options.caseSensitive ?? false),
       all: options.all ?? false,
+      region: options.region,
+      models_dir: options.ocrModels,
+      lang: options.lang,
     });
 
     const data = response.data as { matches: OcrMatch[] };
     return data.matches ?? [];
   }
 
+  /**
+   * Find text on screen using OCR and click the center of the match in one call.
+   *
+   * Clicks the most confident match, or the `index`th in reading order.
+   * Throws an RdpError with code 'text_not_found' when nothing matches.
+   *
+   * @example
+   * ```typescript
+   * await rdp.locateClick({ text: 'Save' });
+   * await rdp.locateClick({ text: 'Delete', index: 1, button: 'right' });
+   * ```
+   */
+  async locateClick(options: LocateClickOptions): Promise<LocateClickResult> {
+    const response = await this._send({
+      type: 'locate_click',
+      text: options.text,
+      pattern: options.pattern ?? false,
+      ignore_case: !(options.caseSensitive ?? false),
+      region: options.region,
+      button: options.button,
+      index: options.index,
+      min_confidence: options.minConfidence,
+      models_dir: options.ocrModels,
+      lang: options.lang,
+    });
+
+    const data = response.data as {
+      type: 'locate_clicked';
+      matched: OcrMatch;
+      x: number;
+      y: number;
+      match_count: number;
+    };
+    return { matched: data.matched, x: data.x, y: data.y, matchCount: data.match_count };
+  }
+
+  /**
+   * Wait until the screen (or a region of it) changes, instead of polling screenshots.
+   *
+   * @param options.timeoutMs - Longest time to wait (default: 10000, max: 60000; keep it
+   *   below the session's request timeout)
+   * @param options.region - Only watch this region of the desktop
+   * @returns Whether a change occurred and the time spent waiting
+   */
+  async waitForChange(options: WaitForChangeOptions = {}): Promise<ScreenChange> {
+    const response = await this._send({
+      type: 'wait_for_change',
+      timeout_ms: options.timeoutMs ?? 10000,
+      region: options.region,
+    });
+    const data = response.data as { type: 'screen_change'; changed: boolean; elapsed_ms: number };
+    return { changed: data.changed, elapsedMs: data.elapsed_ms };
+  }
+
+  /**
+   * Get the mouse pointer position and shape.
+   * Screenshots don't include the pointer; use `screenshot({ drawCursor: true })` for that.
+   */
+  async cursorInfo(options: CursorInfoOptions = {}): Promise<CursorState> {
+    const response = await this._send({
+      type: 'cursor_info',
+      include_image: options.includeImage ?? false,
+    });
+    const data = response.data as { type: 'cursor_info' } & CursorInfo;
+    return {
+      x: data.x,
+      y: data.y,
+      visible: data.visible,
+      width: data.width,
+      height: data.height,
+      hotspotX: data.hotspot_x,
+      hotspotY: data.hotspot_y,
+      base64: data.base64,
+    };
+  }
+
   /**
    * Disconnect from the RDP server.
+   *
+   * The Windows session stays logged on unless `logoff` is set, which logs
+   * the user off first through the automation agent (requires
+   * enableWinAutomation).
    */
-  async disconnect(): Promise<void> {
-    await this._send({ type: 'disconnect' });
+  async disconnect(options: DisconnectOptions = {}): Promise<void> {
+    await this._send({ type: 'disconnect', logoff: options.logoff ?? false });
     await this.close();
   }
 
@@ -366,13 +908,34 @@ export class RdpSession {
 
   /**
    * Get the WebSocket streaming URL, if streaming is enabled.
-   * Connect to this URL to receive JPEG frames.
+   * Connect to this URL to receive JPEG frames; it includes the session's
+   * stream token.
    */
-  getStreamUrl(): string | null {
+  async getStreamUrl(): Promise<string | null> {
     if (this.streamPort === 0) {
       return null;
     }
-    return `ws://localhost:${this.streamPort}`;
+    const info = await this.getInfo();
+    if (!info.stream_token) {
+      return null;
+    }
+    const port = info.stream_port ?? this.streamPort;
+    return `ws://localhost:${port}/?token=${encodeURIComponent(info.stream_token)}`;
+  }
+
+  /**
+   * Change the WebSocket stream's frame rate, JPEG quality or delta encoding
+   * while it runs. Returns the settings now in effect.
+   */
+  async configureStream(options: StreamConfigOptions = {}): Promise<StreamConfig> {
+    const response = await this._send({
+      type: 'stream_config',
+      fps: options.fps,
+      quality: options.quality,
+      delta: options.delta,
+    });
+    const data = response.data as { type: 'stream_config' } & StreamConfig;
+    return { fps: data.fps, quality: data.quality, delta: data.delta };
   }
 
   /**
@@ -380,12 +943,21 @@ export class RdpSession {
    * @internal
    */
   async _send(request: Request): Promise<Response> {
+    const { response } = await this._sendWithPayload(request);
+    return response;
+  }
+
+  /**
+   * Internal: Send a request and also return the binary payload after the response.
+   * @internal
+   */
+  async _sendWithPayload(request: Request): Promise<{ response: Response; payload?: Buffer }> {
     if (!this.client) {
       // Auto-connect to daemon if not connected
       this.client = await this.daemon.ensureRunning();
     }
 
-    const response = await this.client.send(request, this.timeout);
+    const { response, payload } = await this.client.sendWithPayload(request, this.timeout);
 
     if (!response.success) {
       throw new RdpError(
@@ -394,6 +966,6 @@ export class RdpSession {
       );
     }
 
-    return response;
+    return { response, payload };
   }
 }
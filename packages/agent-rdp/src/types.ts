@@ -12,14 +12,19 @@
 export type {
   // Request types
   Request,
+  RequestEnvelope,
   ConnectRequest,
   ScreenshotRequest,
+  FrameRequest,
   MouseRequest,
   KeyboardRequest,
   ScrollRequest,
+  InputBatchRequest,
+  InputEvent,
   ClipboardRequest,
   DriveRequest,
   LocateRequest,
+  LocateClickRequest,
   AutomateRequest,
 
   // Response types
@@ -28,14 +33,32 @@ export type {
   ErrorCode,
   ErrorInfo,
   SessionInfo,
+  DisconnectInfo,
+  DisconnectReason,
+  InputCheck,
+  InputCheckStatus,
   SessionSummary,
+  KilledSession,
+  KillOutcome,
   MappedDrive,
   LocateResult,
   OcrMatch,
+  CursorInfo,
+  ClipboardHistoryEntry,
 
   // Supporting types
   DriveMapping,
+  GatewayRequest,
+  MonitorLayout,
+  SecurityMode,
+  TlsVersion,
+  GraphicsCodec,
+  PerformanceProfile,
+  PerformanceFlag,
+  TypeMode,
   ImageFormat,
+  FrameEncoding,
+  LineEndings,
   MouseButton,
   ScrollDirection,
   ConnectionState,
@@ -47,8 +70,12 @@ export type {
   AutomationScrollDirection,
   AutomationHandshake,
   ClickResult,
+  DragResult,
+  HighlightResult,
+  TypeResult,
   RunResult,
   ElementBounds,
+  ElementScreenshot,
   ElementValue,
   WindowInfo,
   WindowAction,
@@ -63,7 +90,63 @@ export type {
 // --- SDK Convenience Types ---
 // These are higher-level types for the SDK API, not IPC.
 
-import type { DriveMapping, ErrorCode } from './generated/index.js';
+import type {
+  ElementBounds,
+  ErrorCode,
+  FrameEncoding,
+  GraphicsCodec,
+  ImageFormat,
+  InputCheck,
+  LineEndings,
+  MouseButton,
+  OcrMatch,
+  PerformanceFlag,
+  PerformanceProfile,
+  SecurityMode,
+  TlsVersion,
+  TypeMode,
+} from './generated/index.js';
+
+/** A local directory to map as a drive on the remote machine. */
+export interface DriveOptions {
+  /** Local directory path. */
+  path: string;
+  /** Drive name shown in Windows. */
+  name: string;
+  /** Prevent the remote host from creating, modifying or deleting files. */
+  readOnly?: boolean;
+}
+
+/** An RD Gateway to tunnel the connection through. */
+export interface GatewayOptions {
+  /** Gateway hostname or IP address. */
+  host: string;
+  /** Gateway HTTPS port. Default: 443. */
+  port?: number;
+  /** Gateway username. Default: the RDP username. */
+  username?: string;
+  /** Gateway password. Default: the RDP password. */
+  password?: string;
+  /**
+   * Expected SHA-256 fingerprint of the gateway certificate, for gateways
+   * without a publicly trusted certificate.
+   */
+  pinCert?: string;
+  /** Accept any gateway certificate. Only for networks you control. */
+  insecure?: boolean;
+}
+
+/** A monitor of a multi-monitor desktop. */
+export interface MonitorOptions {
+  width: number;
+  height: number;
+  /** Left edge relative to the primary monitor (may be negative). Default: 0. */
+  x?: number;
+  /** Top edge relative to the primary monitor (may be negative). Default: 0. */
+  y?: number;
+  /** Make this the primary monitor. Default: the first monitor. */
+  primary?: boolean;
+}
 
 /** Options for connecting to an RDP session. */
 export interface ConnectOptions {
@@ -74,9 +157,143 @@ export interface ConnectOptions {
   domain?: string;
   width?: number;
   height?: number;
-  drives?: DriveMapping[];
+  drives?: DriveOptions[];
+  /**
+   * Monitor layout. The desktop becomes the bounding box of the monitors and
+   * overrides width/height. Default: one monitor of width x height.
+   */
+  monitors?: MonitorOptions[];
+  /**
+   * Logical size [width, height] for mouse and scroll coordinates, scaled to the
+   * desktop. Whole-desktop screenshots are resized to it.
+   */
+  logicalSize?: [number, number];
   /** Enable Windows UI Automation. */
   enableWinAutomation?: boolean;
+  /**
+   * After connecting, check that keyboard input reaches the remote desktop and report
+   * it as `inputCheck` (needs enableWinAutomation to read the result back). Default: false.
+   */
+  testInput?: boolean;
+  /**
+   * Wait (up to 10s) for the server to draw the desktop before returning, so the first
+   * screenshot isn't black. Reported as `firstFrameReceived`. Default: false.
+   */
+  waitFirstFrame?: boolean;
+  /** Keyboard layout LCID (e.g., 0x40C for French). Default: 0x409 (US English). */
+  keyboardLayout?: number;
+  /** Expected SHA-256 fingerprint of the server certificate (hex, colons optional). */
+  pinCert?: string;
+  /** Reconnect automatically if the connection drops. Default: false. */
+  autoReconnect?: boolean;
+  /** Maximum reconnection attempts before giving up. Default: 5. */
+  maxReconnectAttempts?: number;
+  /**
+   * Send a keepalive after this many seconds without data from the server, and treat
+   * the connection as lost after three silent intervals. Default: off.
+   */
+  keepaliveIntervalSecs?: number;
+  /**
+   * Scale streamed frames down so neither side exceeds this many pixels, keeping the
+   * aspect ratio. Only the WebSocket stream is affected, not screenshots. Default: off.
+   */
+  maxFrameSize?: number;
+  /**
+   * Retry the initial connect this many times while the server refuses, resets or
+   * doesn't answer the connection (e.g. still booting). Rejected credentials are
+   * never retried. Raise the client timeout to cover the retries. Default: 0.
+   */
+  connectRetries?: number;
+  /** Milliseconds before the first connect retry, doubling after each up to 30s. Default: 1000. */
+  retryDelayMs?: number;
+  /**
+   * Milliseconds each connect attempt gets to reach the server and finish the TLS and
+   * CredSSP handshake. Keep it below the client timeout. Default: 20000.
+   */
+  connectTimeoutMs?: number;
+  /**
+   * Security protocol: 'auto', 'nla', or 'tls_only' (for hosts with NLA disabled).
+   * 'tls_only' sends credentials at the RDP layer rather than via CredSSP. Default: 'auto'.
+   */
+  security?: SecurityMode;
+  /**
+   * Oldest TLS version to accept (default: '1.2'). '1.0' and '1.1' are
+   * rejected, as this build doesn't implement them.
+   */
+  minTls?: TlsVersion;
+  /**
+   * Graphics codec to request: 'bitmap' or 'remotefx'. RemoteFX cuts bandwidth on
+   * slow links. Falls back to bitmap if the server rejects the codec. Default: 'bitmap'.
+   */
+  codec?: GraphicsCodec;
+  /**
+   * Visual effects to turn off: 'full' keeps them all, 'lan' turns off the
+   * wallpaper, menu animations and similar eye-candy, 'broadband' also themes
+   * and 'modem' also the cursor blink. Default: 'lan'.
+   */
+  perfProfile?: PerformanceProfile;
+  /** Exact performance flags to send, replacing those of `perfProfile`. */
+  perfFlags?: PerformanceFlag[];
+  /**
+   * Connect through an RD Gateway. Only the WebSocket transport with Basic
+   * authentication is supported.
+   */
+  gateway?: GatewayOptions;
+  /**
+   * Proxy for the TCP connection: 'socks5://[user:pass@]host[:port]' or
+   * 'http://[user:pass@]host[:port]' (HTTP CONNECT).
+   */
+  proxy?: string;
+  /**
+   * KDC for Kerberos authentication: 'host[:port]', 'tcp://host[:port]' or
+   * 'udp://host[:port]' (port 88 by default). NTLM is used without one.
+   */
+  kdc?: string;
+  /**
+   * Record remote audio to this WAV file. Relative paths are resolved against the
+   * session directory. Only the first audio format the server picks is recorded.
+   */
+  captureAudio?: string;
+  /**
+   * Record the desktop to this animated GIF until disconnect. Relative paths are
+   * resolved against the session directory. Audio is not included.
+   */
+  record?: string;
+  /** Frames per second sampled for the recording (default: 2, max: 10). */
+  recordFps?: number;
+  /**
+   * Redirect local smart card readers through the PC/SC service. Needs a binary
+   * built with the smartcard feature; ignored when no reader is present.
+   */
+  redirectSmartcard?: boolean;
+  /**
+   * Attach the clipboard channel (default: true). Turn off for hosts where
+   * clipboard redirection is forbidden; clipboard calls then fail.
+   */
+  enableClipboard?: boolean;
+  /**
+   * Attach the audio output channel (default: true). Turn off for hosts where
+   * audio redirection is forbidden; `captureAudio` needs it.
+   */
+  enableAudio?: boolean;
+  /**
+   * Shut the daemon down after this many seconds without requests or viewers.
+   * Pings count as requests. 0 or unset keeps it running.
+   */
+  idleTimeoutSecs?: number;
+  /** Client computer name reported to the server, at most 15 characters (default: 'agent-rdp'). */
+  clientName?: string;
+  /** Client build number reported to the server (default: 0). */
+  clientBuild?: number;
+  /** Product ID reported to the server, at most 31 characters. */
+  digProductId?: string;
+  /**
+   * Client hardware ID as 32 hex digits (dashes allowed, as in a GUID). Defaults to
+   * an ID derived from the session name, so the server sees the same device each time.
+   */
+  hardwareId?: string;
+  /** Number of remote clipboard texts to remember (default: 5, max: 100, 0 disables the history). */
+  clipboardHistory?: number;
 }
 
 /** Result of a successful connection. */
@@ -84,19 +301,93 @@ export interface ConnectResult {
   host: string;
   width: number;
   height: number;
+  /** Result of the input self-check, when `testInput` was set. */
+  inputCheck?: InputCheck;
+  /** Whether the server drew the desktop in time, when `waitFirstFrame` was set. */
+  firstFrameReceived?: boolean;
+}
+
+/** Options for disconnecting. */
+export interface DisconnectOptions {
+  /** Log the Windows user off first, ending the session (requires enableWinAutomation). */
+  logoff?: boolean;
 }
 
 /** Options for taking a screenshot. */
 export interface ScreenshotOptions {
-  format?: 'png' | 'jpeg';
+  format?: ImageFormat;
+  /** Capture only this region of the desktop (default: full frame). */
+  region?: { x: number; y: number; width: number; height: number };
+  /** Capture only this monitor (index into getInfo().monitors); region is then relative to it. */
+  monitor?: number;
+  /** Draw the mouse pointer onto the image at its current position. */
+  drawCursor?: boolean;
+  /** Capture once the screen hasn't changed for this many milliseconds (default: immediately). */
+  waitStable?: number;
+  /** Capture anyway after waiting this long for waitStable (default: 10000, max: 60000). */
+  stableTimeoutMs?: number;
+  /** etag of an earlier screenshot; if nothing changed, no image is returned (notModified). */
+  ifChangedSince?: string;
 }
 
 /** Result of a screenshot operation. */
 export interface ScreenshotResult {
+  /** Encoded image, empty when notModified. */
   base64: string;
   width: number;
   height: number;
   format: string;
+  /** Encoded image size in bytes (0 when notModified). */
+  sizeBytes: number;
+  /** Hash of the capture, to pass as ifChangedSince next time. */
+  etag: string;
+  /** The capture matched ifChangedSince, so no image was encoded. */
+  notModified: boolean;
+  /** Milliseconds waited for the screen to settle (with waitStable). */
+  stableWaitMs?: number;
+  /** Whether the screen settled before the timeout (with waitStable). */
+  stable?: boolean;
+  /**
+   * False while the server hasn't drawn the desktop yet, when the image is blank
+   * (absent when notModified).
+   */
+  firstFrameReceived?: boolean;
+}
+
+/** WebSocket stream settings to change; omitted settings are unchanged. */
+export interface StreamConfigOptions {
+  /** Frames per second (1-60). */
+  fps?: number;
+  /** JPEG quality (1-100). */
+  quality?: number;
+  /** Send only the changed tiles of each frame. */
+  delta?: boolean;
+}
+
+/** WebSocket stream settings in effect. */
+export interface StreamConfig {
+  fps: number;
+  quality: number;
+  delta: boolean;
+}
+
+/** Options for reading the desktop framebuffer. */
+export interface FrameOptions {
+  /** Pixel encoding (default: 'raw'). */
+  encoding?: FrameEncoding;
+}
+
+/** The desktop framebuffer. */
+export interface FrameResult {
+  /** RGBA pixels for 'raw' (4 bytes per pixel, rows `stride` bytes apart), or a PNG. */
+  data: Buffer;
+  width: number;
+  height: number;
+  /** Bytes per row of raw pixels (0 for PNG). */
+  stride: number;
+  encoding: FrameEncoding;
+  /** Size of `data` in bytes. */
+  sizeBytes: number;
 }
 
 /** A point representing x,y coordinates. */
@@ -109,12 +400,33 @@ export interface Point {
 export interface MouseClickOptions {
   x: number;
   y: number;
+  /** Interpret coordinates relative to this monitor (index into getInfo().monitors). */
+  monitor?: number;
+}
+
+/** Options for mouse moves and left clicks. */
+export interface MouseMoveOptions extends MouseClickOptions {
+  /**
+   * Glide along a curved, eased path from the current position instead of
+   * jumping there, for apps that flag instant moves as automated (default: false).
+   */
+  human?: boolean;
+}
+
+/** Options for pressing or releasing a single mouse button. */
+export interface MouseButtonOptions extends MouseClickOptions {
+  /** Button to press or release (default: 'left'). */
+  button?: MouseButton;
 }
 
 /** Options for mouse drag operations. */
 export interface MouseDragOptions {
   from: Point;
   to: Point;
+  /** Number of intermediate moves while the button is held (default: 10). */
+  steps?: number;
+  /** Interpret coordinates relative to this monitor (index into getInfo().monitors). */
+  monitor?: number;
 }
 
 /** Options for scroll operations. */
@@ -131,6 +443,17 @@ export interface ScrollOptions {
 export interface KeyboardTypeOptions {
   /** Text to type. */
   text: string;
+  /**
+   * How characters are sent (default: 'unicode'). 'scancode' types with the
+   * session's keyboard layout, for apps that ignore unicode input; it fails
+   * listing characters the layout can't type.
+   */
+  mode?: TypeMode;
+  /**
+   * Pause after each character in milliseconds (default: 100). 0 sends the
+   * whole text at once.
+   */
+  delayMs?: number;
 }
 
 /** Options for keyboard press operations. */
@@ -139,10 +462,134 @@ export interface KeyboardPressOptions {
   keys: string;
 }
 
+/** Options for pasting text through the clipboard. */
+export interface KeyboardPasteOptions {
+  /** Text to paste. */
+  text: string;
+  /** Put the previous clipboard text back afterward. Default: false. */
+  restore?: boolean;
+}
+
+/** Options for sending a raw scancode. */
+export interface KeyboardRawOptions {
+  /** Scancode (0-255). */
+  scancode: number;
+  /** Set the extended-key flag (E0 prefix). Default: false. */
+  extended?: boolean;
+  /** Send a release instead of a press. Default: false. */
+  release?: boolean;
+}
+
 /** Options for clipboard set operations. */
 export interface ClipboardSetOptions {
   /** Text to set. */
   text: string;
+  /** Line ending conversion. Default: 'crlf' (Windows line endings). */
+  lineEndings?: LineEndings;
+}
+
+/** Options for clipboard get operations. */
+export interface ClipboardGetOptions {
+  /** Line ending conversion. Default: 'lf'. */
+  lineEndings?: LineEndings;
+}
+
+/** Options for clipboard image set operations. */
+export interface ClipboardSetImageOptions {
+  /** Base64-encoded PNG data. */
+  base64: string;
+}
+
+/** Image read from the remote clipboard. */
+export interface ClipboardImage {
+  /** Base64-encoded PNG data. */
+  base64: string;
+  width: number;
+  height: number;
+}
+
+/** Options for clipboard HTML set operations. */
+export interface ClipboardSetHtmlOptions {
+  /** HTML fragment to copy. */
+  html: string;
+  /** Plain text version for targets without HTML support (default: the HTML with tags removed). */
+  text?: string;
+}
+
+/** Options for saving files from the remote clipboard. */
+export interface ClipboardGetFilesOptions {
+  /** Local directory to save the files into (absolute, created if missing). */
+  destDir: string;
+  /** Maximum total bytes to transfer (default: 1 GiB). */
+  maxBytes?: number;
+}
+
+/** Options for copying local files to the remote clipboard. */
+export interface ClipboardSetFilesOptions {
+  /** Local files or directories to copy (absolute paths). */
+  paths: string[];
+  /** Maximum total bytes to transfer (default: 1 GiB). */
+  maxBytes?: number;
+}
+
+/** Files transferred through the clipboard. */
+export interface ClipboardFiles {
+  /** Saved local paths (getFiles) or the names offered to the remote (setFiles). */
+  files: string[];
+  /** Total bytes of file data. */
+  totalBytes: number;
+}
+
+/** Options for copying a local file or directory into a mapped drive. */
+export interface DrivePushOptions {
+  /** Local file or directory to copy (absolute). */
+  local: string;
+  /** Drive name and path inside it, e.g. 'Shared/reports'. An existing directory receives the source. */
+  remote: string;
+}
+
+/** Options for copying a file or directory out of a mapped drive. */
+export interface DrivePullOptions {
+  /** Drive name and path inside it, e.g. 'Shared/output.csv'. */
+  remote: string;
+  /** Local destination (absolute). An existing directory receives the source. */
+  local: string;
+}
+
+/** Files copied into or out of a mapped drive. */
+export interface DriveCopy {
+  /** Local path the copy was written to. */
+  destination: string;
+  /** Number of files copied. */
+  files: number;
+  /** Total bytes copied. */
+  totalBytes: number;
+}
+
+/** Options for reading the clipboard history. */
+export interface ClipboardHistoryOptions {
+  /** Number of entries to return (default: all that are kept). */
+  count?: number;
+}
+
+/** Text from one remote copy. */
+export interface ClipboardHistoryItem {
+  /** Copied text. */
+  text: string;
+  /** When the text was copied, in milliseconds since the Unix epoch. */
+  copiedAtMs: number;
+}
+
+/** Kinds of content on the remote clipboard. */
+export interface ClipboardStatus {
+  /** Text is available. */
+  text: boolean;
+  /** An image is available. */
+  image: boolean;
+  /** HTML is available. */
+  html: boolean;
+  /** Files copied in Explorer are available. */
+  files: boolean;
 }
 
 /** Options for locate (OCR) operations. */
@@ -155,6 +602,82 @@ export interface LocateOptions {
   pattern?: boolean;
   /** Case-sensitive matching (default: false). */
   caseSensitive?: boolean;
+  /** Only search within this region of the desktop (default: full screen). */
+  region?: ElementBounds;
+  /** Directory holding the OCR models (default: AGENT_RDP_OCR_MODELS, then the bundled models). */
+  ocrModels?: string;
+  /** Language to recognize, e.g. 'en' or 'ru' (default: 'en'). */
+  lang?: string;
+}
+
+/** Options for finding text with OCR and clicking it. */
+export interface LocateClickOptions {
+  /** Text to search for. */
+  text: string;
+  /** Use glob-style pattern matching (* and ?). */
+  pattern?: boolean;
+  /** Case-sensitive matching (default: false). */
+  caseSensitive?: boolean;
+  /** Only search within this region of the desktop (default: full screen). */
+  region?: ElementBounds;
+  /** Button to click with (default: 'left'). */
+  button?: MouseButton;
+  /** Click the Nth match (0-based, top to bottom) instead of the most confident one. */
+  index?: number;
+  /** Ignore matches below this confidence, from 0 to 1 (default: 0.2). */
+  minConfidence?: number;
+  /** Directory holding the OCR models (default: AGENT_RDP_OCR_MODELS, then the bundled models). */
+  ocrModels?: string;
+  /** Language to recognize, e.g. 'en' or 'ru' (default: 'en'). */
+  lang?: string;
+}
+
+/** Result of a locate-click. */
+export interface LocateClickResult {
+  /** The line that was clicked. */
+  matched: OcrMatch;
+  /** Where the click landed. */
+  x: number;
+  y: number;
+  /** How many lines matched. */
+  matchCount: number;
+}
+
+/** Options for waiting for a screen change. */
+export interface WaitForChangeOptions {
+  /** Longest time to wait in milliseconds (default: 10000, max: 60000). */
+  timeoutMs?: number;
+  /** Only watch this region of the desktop (default: full screen). */
+  region?: ElementBounds;
+}
+
+/** Result of waiting for a screen change. */
+export interface ScreenChange {
+  /** Whether the watched pixels changed before the timeout. */
+  changed: boolean;
+  /** Time spent waiting in milliseconds. */
+  elapsedMs: number;
+}
+
+/** Options for cursor info. */
+export interface CursorInfoOptions {
+  /** Include the pointer image as a base64 PNG. */
+  includeImage?: boolean;
+}
+
+/** Mouse pointer position and shape. */
+export interface CursorState {
+  x: number;
+  y: number;
+  /** Whether the pointer is shown (the server hides it, e.g. while typing). */
+  visible: boolean;
+  /** Pointer image size and hot spot, once the server has sent a pointer shape. */
+  width?: number;
+  height?: number;
+  hotspotX?: number;
+  hotspotY?: number;
+  /** Base64-encoded PNG of the pointer image (only with includeImage). */
+  base64?: string;
 }
 
 // --- Automation convenience types (aliases for backwards compatibility) ---
@@ -168,6 +691,9 @@ export type { AccessibilityElement as AutomationElement } from './generated/inde
 /** Automation snapshot result. */
 export type { AccessibilitySnapshot as AutomationSnapshot } from './generated/index.js';
 
+/** Automation snapshot rendered as YAML or text. */
+export type { FormattedSnapshot as AutomationFormattedSnapshot } from './generated/index.js';
+
 /** Element value result (alias for ElementValue). */
 export type { ElementValue as AutomationElementValue } from './generated/index.js';
 
@@ -180,6 +706,21 @@ export type { RunResult as AutomationRunResult } from './generated/index.js';
 /** Click result (alias for ClickResult). */
 export type { ClickResult as AutomationClickResult } from './generated/index.js';
 
+/** Drag result (alias for DragResult). */
+export type { DragResult as AutomationDragResult } from './generated/index.js';
+
+/** Highlight result (alias for HighlightResult). */
+export type { HighlightResult as AutomationHighlightResult } from './generated/index.js';
+
+/** Element image (alias for ElementScreenshot). */
+export type { ElementScreenshot as AutomationElementScreenshot } from './generated/index.js';
+
+/** Type result (alias for TypeResult). */
+export type { TypeResult as AutomationTypeResult } from './generated/index.js';
+
+/** Element wait result (alias for WaitForResult). */
+export type { WaitForResult as AutomationWaitForResult } from './generated/index.js';
+
 // --- Error Class ---
 
 /** Error class for RDP operations. */
@@ -130,16 +130,19 @@ function Test-CommandCoverage {
         "get",
         "focus",
         "click",
+        "invoke",
         "select",
         "toggle",
         "expand",
         "collapse",
         "context_menu",
         "fill",
+        "type",
         "clear",
         "scroll",
         "window",
         "run",
+        "exec_script",
         "wait_for",
         "status"
     )